@@ -0,0 +1,13 @@
+-- Per-tool usage rollup across all runs
+CREATE TABLE IF NOT EXISTS tool_stats (
+  name TEXT PRIMARY KEY,
+  invocations INTEGER NOT NULL DEFAULT 0,
+  errors INTEGER NOT NULL DEFAULT 0,
+  total_result_bytes INTEGER NOT NULL DEFAULT 0,
+  updated_at TEXT NOT NULL
+);
+
+-- Loop detection defaults: flag after N identical calls, optionally abort the run
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('tool_loop_threshold', '3', datetime('now')),
+('tool_loop_abort', '0', datetime('now'));
@@ -0,0 +1,7 @@
+-- Draft artifacts hold partial streamed output until the run finalizes them
+ALTER TABLE artifacts ADD COLUMN draft INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_artifacts_draft ON artifacts(task_id, kind, draft);
+
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('stream_responses', '1', datetime('now'));
@@ -0,0 +1,3 @@
+-- Phase detail used to scope per-phase verification
+ALTER TABLE phases ADD COLUMN description TEXT;
+ALTER TABLE phases ADD COLUMN planned_files_json TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,10 @@
+-- Explicit blocking relationships between phases of the same task
+CREATE TABLE IF NOT EXISTS phase_dependencies (
+  phase_id TEXT NOT NULL,
+  depends_on_phase_id TEXT NOT NULL,
+  PRIMARY KEY (phase_id, depends_on_phase_id),
+  FOREIGN KEY(phase_id) REFERENCES phases(id) ON DELETE CASCADE,
+  FOREIGN KEY(depends_on_phase_id) REFERENCES phases(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_phase_deps_depends_on ON phase_dependencies(depends_on_phase_id);
@@ -0,0 +1,6 @@
+-- Free-form task description (typed, pasted, or transcribed)
+ALTER TABLE tasks ADD COLUMN description TEXT;
+
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('transcription_base_url', '', datetime('now')),
+('transcription_model', 'whisper-1', datetime('now'));
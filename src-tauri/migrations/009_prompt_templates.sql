@@ -0,0 +1,5 @@
+-- Custom user-prompt templates per workflow (empty = built-in prompt)
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('prompt_template_plan', '', datetime('now')),
+('prompt_template_verify', '', datetime('now')),
+('prompt_template_strict', '1', datetime('now'));
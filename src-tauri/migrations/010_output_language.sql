@@ -0,0 +1,13 @@
+-- Per-project overrides for global settings (e.g. output_language)
+CREATE TABLE IF NOT EXISTS project_settings (
+  project_id TEXT NOT NULL,
+  key TEXT NOT NULL,
+  value TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (project_id, key),
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Language for generated plans and reports (en, es, ja, fr, de)
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('output_language', 'en', datetime('now'));
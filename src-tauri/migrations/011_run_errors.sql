@@ -0,0 +1,2 @@
+-- Error message for runs that failed after starting (NULL = succeeded or still running)
+ALTER TABLE runs ADD COLUMN error TEXT;
@@ -0,0 +1,4 @@
+-- Release channel for update checks (stable|beta); update_endpoint overrides the channel URL
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('update_channel', 'stable', datetime('now')),
+('update_endpoint', '', datetime('now'));
@@ -0,0 +1,3 @@
+-- Global shortcut that opens the quick capture window; empty disables it
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('quick_capture_shortcut', 'CommandOrControl+Shift+Space', datetime('now'));
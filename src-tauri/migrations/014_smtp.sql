@@ -0,0 +1,8 @@
+-- SMTP delivery of verification reports; smtp_security is starttls|tls|none
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('smtp_host', '', datetime('now')),
+('smtp_port', '587', datetime('now')),
+('smtp_security', 'starttls', datetime('now')),
+('smtp_username', '', datetime('now')),
+('smtp_password', '', datetime('now')),
+('smtp_from', '', datetime('now'));
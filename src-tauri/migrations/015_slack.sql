@@ -0,0 +1,7 @@
+-- Slack report publishing: a bot token enables threads and channel choice;
+-- a webhook URL posts a single summary to its fixed channel
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('slack_bot_token', '', datetime('now')),
+('slack_webhook_url', '', datetime('now')),
+('slack_default_channel', '', datetime('now')),
+('slack_auto_post_regressions', '0', datetime('now'));
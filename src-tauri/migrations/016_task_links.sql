@@ -0,0 +1,13 @@
+-- Issues (Jira, Linear) a task was imported from, for pushing comments back
+CREATE TABLE IF NOT EXISTS task_links (
+  task_id TEXT NOT NULL,
+  provider TEXT NOT NULL,
+  external_id TEXT NOT NULL,
+  external_key TEXT NOT NULL,
+  url TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (task_id, provider),
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_links_external ON task_links(provider, external_id);
@@ -0,0 +1,15 @@
+-- Test results ingested from CI (JUnit XML or GitHub Actions job logs), used by
+-- verification in place of running the suite locally
+CREATE TABLE IF NOT EXISTS ci_results (
+  id TEXT PRIMARY KEY,
+  task_id TEXT NOT NULL,
+  source TEXT NOT NULL,        -- file path or URL
+  format TEXT NOT NULL,        -- junit|github_actions
+  tests INTEGER,               -- NULL when the source has no test count
+  skipped INTEGER NOT NULL DEFAULT 0,
+  failures_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_ci_results_task ON ci_results(task_id, created_at);
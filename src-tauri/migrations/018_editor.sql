@@ -0,0 +1,6 @@
+-- Editor used to open cited files (vscode|cursor|jetbrains|custom); editor_command is the
+-- custom template with {path}, {line}, and {repo} placeholders
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('editor', 'vscode', datetime('now')),
+('editor_command', '', datetime('now')),
+('editor_jetbrains_launcher', 'idea', datetime('now'));
@@ -0,0 +1,13 @@
+-- Local HTTP API for the VS Code companion extension (off by default, loopback only)
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('companion_api_enabled', '0', datetime('now')),
+('companion_api_port', '7357', datetime('now'));
+
+-- Paired extension clients; only a SHA-256 hash of each bearer token is kept
+CREATE TABLE IF NOT EXISTS companion_clients (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  created_at TEXT NOT NULL,
+  last_used_at TEXT
+);
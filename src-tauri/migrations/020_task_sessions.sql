@@ -0,0 +1,16 @@
+-- Time a task was open in a window ('view') or spent in a plan/verify run ('run')
+CREATE TABLE IF NOT EXISTS task_sessions (
+  id TEXT PRIMARY KEY,
+  task_id TEXT NOT NULL,
+  kind TEXT NOT NULL,          -- view|run
+  run_id TEXT,                 -- set for run sessions
+  run_type TEXT,
+  window_label TEXT,           -- set for view sessions
+  started_at TEXT NOT NULL,
+  ended_at TEXT,               -- NULL while open
+  duration_secs INTEGER,       -- filled in when the session ends
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_sessions_task ON task_sessions(task_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_task_sessions_open ON task_sessions(ended_at) WHERE ended_at IS NULL;
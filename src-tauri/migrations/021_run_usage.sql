@@ -0,0 +1,12 @@
+-- Token usage and estimated cost per run, summed across the run's LLM requests
+ALTER TABLE runs ADD COLUMN prompt_tokens INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE runs ADD COLUMN completion_tokens INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE runs ADD COLUMN cost_usd REAL NOT NULL DEFAULT 0;
+
+-- Prices (USD per million tokens) used to estimate cost; 0 = cost not tracked.
+-- Monthly budgets are per project (budget_monthly_tokens, budget_monthly_usd in
+-- project_settings); budget_enforcement is confirm|block once a budget is spent
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('price_input_per_mtok', '0', datetime('now')),
+('price_output_per_mtok', '0', datetime('now')),
+('budget_enforcement', 'confirm', datetime('now'));
@@ -0,0 +1,15 @@
+-- offline_mode = '1' forces offline mode; LLM runs started while offline are
+-- queued here and start automatically once the provider is reachable again
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('offline_mode', '0', datetime('now'));
+
+CREATE TABLE IF NOT EXISTS queued_runs (
+  id TEXT PRIMARY KEY,
+  project_id TEXT NOT NULL,
+  task_id TEXT NOT NULL,
+  run_type TEXT NOT NULL,      -- plan|verify
+  options_json TEXT NOT NULL,  -- VerifyOptions for verify runs
+  queued_at TEXT NOT NULL,
+  UNIQUE(task_id, run_type),
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+-- Hash of a verify run's assembled prompt, so an identical verification can
+-- reuse the earlier report instead of calling the LLM again
+ALTER TABLE runs ADD COLUMN prompt_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_runs_prompt_hash ON runs(task_id, prompt_hash);
+
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('verify_cache', '1', datetime('now'));
@@ -0,0 +1,6 @@
+-- Sampling seed sent with each request ('' = none, an integer, or 'random' for a
+-- fresh seed per run); recorded on the run so it can be replayed
+ALTER TABLE runs ADD COLUMN seed INTEGER;
+
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('seed', '', datetime('now'));
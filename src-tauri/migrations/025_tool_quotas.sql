@@ -0,0 +1,7 @@
+-- Tool call limits enforced by the dispatcher (0 = unlimited); over-budget calls
+-- return a tool_budget_exceeded result to the model instead of running
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('tool_max_calls_per_run', '60', datetime('now')),
+('tool_max_calls_per_minute', '30', datetime('now')),
+('tool_max_commands_per_run', '5', datetime('now')),
+('tool_max_bytes_per_run', '2000000', datetime('now'));
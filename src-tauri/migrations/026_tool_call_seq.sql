@@ -0,0 +1,9 @@
+-- Per-run sequence number for tool calls; created_at collides when tools run in
+-- parallel. Existing rows are numbered in insertion order.
+ALTER TABLE tool_calls ADD COLUMN seq INTEGER NOT NULL DEFAULT 0;
+
+UPDATE tool_calls SET seq = (
+  SELECT COUNT(*) FROM tool_calls t WHERE t.run_id = tool_calls.run_id AND t.rowid <= tool_calls.rowid
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tool_calls_run_seq ON tool_calls(run_id, seq);
@@ -0,0 +1,17 @@
+-- Content-addressed store for large payloads. Rows reference a blob by its
+-- SHA-256 hash and keep an empty inline value; existing rows are moved over
+-- by blobs::backfill after this migration.
+CREATE TABLE IF NOT EXISTS blobs (
+  hash TEXT PRIMARY KEY,
+  content TEXT NOT NULL,
+  size INTEGER NOT NULL,
+  created_at TEXT NOT NULL
+);
+
+ALTER TABLE messages ADD COLUMN content_hash TEXT;
+ALTER TABLE artifacts ADD COLUMN content_hash TEXT;
+ALTER TABLE tool_calls ADD COLUMN result_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_messages_content_hash ON messages(content_hash);
+CREATE INDEX IF NOT EXISTS idx_artifacts_content_hash ON artifacts(content_hash);
+CREATE INDEX IF NOT EXISTS idx_tool_calls_result_hash ON tool_calls(result_hash);
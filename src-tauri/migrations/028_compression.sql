@@ -0,0 +1,5 @@
+-- Large messages.content, artifacts.content, tool_calls.result_json and
+-- blobs.content values are now stored zstd-compressed as BLOBs with a leading
+-- format marker byte. No schema change; compression::backfill converts
+-- existing rows after this migration.
+SELECT 1;
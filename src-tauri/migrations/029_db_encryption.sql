@@ -0,0 +1,4 @@
+-- Opt-in encryption of stored source excerpts (messages, artifacts, tool
+-- results, blobs). Toggled by set_database_encryption, which rewrites the
+-- data; the key lives in the OS keychain.
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('db_encryption', '0', datetime('now'));
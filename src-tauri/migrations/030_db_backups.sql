@@ -0,0 +1,3 @@
+-- Backups taken before migrations and restores; older ones beyond this count
+-- are deleted
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('db_backup_keep', '5', datetime('now'));
@@ -0,0 +1,102 @@
+-- Multi-device sync. Local changes to synced entities are queued in
+-- sync_outbox by triggers (only once a device id exists, i.e. sync was set
+-- up) and pushed as append-only change log segments to the configured remote.
+CREATE TABLE IF NOT EXISTS sync_state (
+  key TEXT PRIMARY KEY,
+  value TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS sync_outbox (
+  seq INTEGER PRIMARY KEY AUTOINCREMENT,
+  entity TEXT NOT NULL, -- project|task|phase|artifact|setting
+  entity_id TEXT NOT NULL, -- artifacts are keyed by task_id:phase_id:kind
+  changed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_outbox_entity ON sync_outbox(entity, entity_id);
+
+-- Version of the last change applied or pushed per entity, for last-writer-wins
+CREATE TABLE IF NOT EXISTS sync_versions (
+  entity TEXT NOT NULL,
+  entity_id TEXT NOT NULL,
+  changed_at TEXT NOT NULL,
+  device_id TEXT NOT NULL,
+  PRIMARY KEY (entity, entity_id)
+);
+
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('sync_remote', '', datetime('now'));
+
+CREATE TRIGGER IF NOT EXISTS sync_projects_insert AFTER INSERT ON projects
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('project', NEW.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_projects_update AFTER UPDATE OF name ON projects
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('project', NEW.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_projects_delete AFTER DELETE ON projects
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('project', OLD.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_tasks_insert AFTER INSERT ON tasks
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('task', NEW.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_tasks_update AFTER UPDATE ON tasks
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('task', NEW.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_tasks_delete AFTER DELETE ON tasks
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('task', OLD.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_phases_insert AFTER INSERT ON phases
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('phase', NEW.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_phases_update AFTER UPDATE ON phases
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('phase', NEW.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_phases_delete AFTER DELETE ON phases
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('phase', OLD.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_artifacts_insert AFTER INSERT ON artifacts
+WHEN NEW.draft = 0 AND EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('artifact', NEW.task_id || ':' || COALESCE(NEW.phase_id, '') || ':' || NEW.kind, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_artifacts_update AFTER UPDATE ON artifacts
+WHEN NEW.draft = 0 AND EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('artifact', NEW.task_id || ':' || COALESCE(NEW.phase_id, '') || ':' || NEW.kind, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_artifacts_delete AFTER DELETE ON artifacts
+WHEN OLD.draft = 0 AND EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('artifact', OLD.task_id || ':' || COALESCE(OLD.phase_id, '') || ':' || OLD.kind, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_settings_insert AFTER INSERT ON settings
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('setting', NEW.key, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_settings_update AFTER UPDATE ON settings
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('setting', NEW.key, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_settings_delete AFTER DELETE ON settings
+WHEN EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('setting', OLD.key, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
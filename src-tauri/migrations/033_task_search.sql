@@ -0,0 +1,13 @@
+-- Free-form labels for filtering tasks, and indexes for search_tasks' filters
+-- and sort orders
+CREATE TABLE IF NOT EXISTS task_labels (
+  task_id TEXT NOT NULL,
+  label TEXT NOT NULL,
+  PRIMARY KEY (task_id, label),
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_labels_label ON task_labels(label);
+CREATE INDEX IF NOT EXISTS idx_tasks_project_updated ON tasks(project_id, updated_at);
+CREATE INDEX IF NOT EXISTS idx_tasks_project_created ON tasks(project_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_tasks_project_status ON tasks(project_id, status);
@@ -0,0 +1,29 @@
+-- Tags shared by tasks and artifacts. Auto taggings are derived from workflow
+-- output (e.g. the verification report) and replaced when it's regenerated.
+CREATE TABLE IF NOT EXISTS tags (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS taggings (
+  tag_id TEXT NOT NULL,
+  target TEXT NOT NULL,  -- task|artifact
+  target_id TEXT NOT NULL,
+  source TEXT NOT NULL DEFAULT 'user', -- user|auto
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (tag_id, target, target_id),
+  FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_taggings_target ON taggings(target, target_id);
+
+CREATE TRIGGER IF NOT EXISTS taggings_task_delete AFTER DELETE ON tasks BEGIN
+  DELETE FROM taggings WHERE target = 'task' AND target_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS taggings_artifact_delete AFTER DELETE ON artifacts BEGIN
+  DELETE FROM taggings WHERE target = 'artifact' AND target_id = OLD.id;
+END;
+
+-- Task labels become tags, and task_labels is dropped, in tags::migrate_task_labels
@@ -0,0 +1,25 @@
+-- Embeddings index of repository chunks for retrieval-augmented planning
+CREATE TABLE IF NOT EXISTS repo_chunks (
+  id TEXT PRIMARY KEY,
+  project_id TEXT NOT NULL,
+  path TEXT NOT NULL,
+  start_line INTEGER NOT NULL,
+  end_line INTEGER NOT NULL,
+  content TEXT NOT NULL,
+  -- sha256 of the whole file, to skip unchanged files when re-indexing
+  file_hash TEXT NOT NULL,
+  model TEXT NOT NULL,
+  -- little-endian f32 vector
+  embedding BLOB NOT NULL,
+  indexed_at TEXT NOT NULL,
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_repo_chunks_project_path ON repo_chunks(project_id, path);
+
+-- planning_strategy: explore (tools only) | retrieval (top-k chunks inline first)
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('planning_strategy', 'explore', datetime('now')),
+('embedding_base_url', '', datetime('now')),
+('embedding_model', 'text-embedding-3-small', datetime('now')),
+('retrieval_top_k', '8', datetime('now'));
@@ -0,0 +1,4 @@
+-- Language server used by the get_diagnostics tool: '' (off), rust-analyzer, tsserver, or pyright
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('lsp_server', '', datetime('now')),
+('lsp_timeout_secs', '60', datetime('now'));
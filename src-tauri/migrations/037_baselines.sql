@@ -0,0 +1,19 @@
+-- Check results on the pre-change state, captured for verify to compare against
+CREATE TABLE IF NOT EXISTS baselines (
+  id TEXT PRIMARY KEY,
+  task_id TEXT NOT NULL,
+  run_id TEXT,
+  base_commit TEXT NOT NULL,
+  method TEXT NOT NULL,       -- worktree|stash
+  checks_json TEXT NOT NULL,  -- [CheckResult]
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+  FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_baselines_task ON baselines(task_id, created_at);
+
+-- baseline_ref: branch or commit whose merge-base with HEAD is checked; '' checks HEAD
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('baseline_method', 'worktree', datetime('now')),
+('baseline_ref', '', datetime('now'));
@@ -0,0 +1,12 @@
+-- Pass/fail history of tests that have failed at least once, for flaky test detection
+CREATE TABLE IF NOT EXISTS test_stats (
+  project_id TEXT NOT NULL,
+  test_name TEXT NOT NULL,
+  runs INTEGER NOT NULL DEFAULT 0,
+  failures INTEGER NOT NULL DEFAULT 0,
+  flaky_runs INTEGER NOT NULL DEFAULT 0,  -- repeated runs where the test both passed and failed
+  last_failed_at TEXT,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY(project_id, test_name),
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
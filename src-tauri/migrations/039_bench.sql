@@ -0,0 +1,20 @@
+-- Benchmark results per run, compared against the previous result of the same benchmark
+CREATE TABLE IF NOT EXISTS bench_results (
+  id TEXT PRIMARY KEY,
+  project_id TEXT NOT NULL,
+  run_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  mean_ns REAL NOT NULL,
+  low_ns REAL,
+  high_ns REAL,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE,
+  FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_bench_results_project_name ON bench_results(project_id, name, created_at);
+
+-- Commands to benchmark with hyperfine, one per line; empty uses the project's bench script
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('bench_commands', '', datetime('now'));
+-- Percent slowdown against the previous result that counts as a regression
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('bench_regression_pct', '10', datetime('now'));
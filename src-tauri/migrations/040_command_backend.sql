@@ -0,0 +1,7 @@
+-- Where run_command executes: host, docker, or podman
+-- container_image: image the project's commands run in (usually set per project)
+-- container_mount: readonly mounts the repo read-only; copy runs on a throwaway copy inside the container
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('command_backend', 'host', datetime('now')),
+('container_image', '', datetime('now')),
+('container_mount', 'copy', datetime('now'));
@@ -0,0 +1,6 @@
+-- Resource limits for commands run by run_command; empty means no limit
+-- command_memory_mb: containers get --memory, unix hosts a data segment limit
+-- command_cpus: containers only
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('command_memory_mb', '', datetime('now')),
+('command_cpus', '', datetime('now'));
@@ -0,0 +1,2 @@
+-- run_command waits for other runs' commands in the same repository; '0' lets them run in parallel
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('command_repo_lock', '1', datetime('now'));
@@ -0,0 +1,15 @@
+-- Passing check runs keyed by the repository state they ran on, so verify can
+-- reuse them instead of running the same checks on unchanged code
+CREATE TABLE IF NOT EXISTS check_fingerprints (
+  project_id TEXT NOT NULL,
+  args_key TEXT NOT NULL,       -- run_command arguments, e.g. {"kind":"tests"}
+  fingerprint TEXT NOT NULL,    -- hash of HEAD, the uncommitted diff, untracked files and lockfiles
+  tool_call_id TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY(project_id, args_key, fingerprint),
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE,
+  FOREIGN KEY(tool_call_id) REFERENCES tool_calls(id) ON DELETE CASCADE
+);
+
+-- '0' always runs verify's checks, even on unchanged code
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('reuse_check_results', '1', datetime('now'));
@@ -0,0 +1,2 @@
+-- Run commands through the toolchain a repo pins (devenv.nix, flake.nix, mise.toml, .tool-versions); '0' uses PATH as is
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('toolchain_detection', '1', datetime('now'));
@@ -0,0 +1,2 @@
+-- Per-project argv overrides for run_command, by kind and OS, as JSON; see repo_tools::custom_commands
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('custom_commands', '', datetime('now'));
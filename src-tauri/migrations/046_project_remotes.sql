@@ -0,0 +1,13 @@
+-- Projects created from a git URL, cloned into the app data directory
+CREATE TABLE IF NOT EXISTS project_remotes (
+  project_id TEXT PRIMARY KEY,
+  git_url TEXT NOT NULL,
+  branch TEXT NOT NULL,          -- branch checked out by the clone
+  shallow INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  last_pulled_at TEXT,
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- '1' lets verify and baseline runs fast-forward a cloned project with git_pull
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('allow_git_pull', '0', datetime('now'));
@@ -0,0 +1,3 @@
+-- Monorepo subtree a task works in, e.g. services/billing; repo tools in its
+-- runs list, search and diff only under it
+ALTER TABLE tasks ADD COLUMN scope_path TEXT;
@@ -0,0 +1,3 @@
+-- Globs kept out of list_files, grep, read_file and git_diff, as a JSON array
+-- (e.g. ["**/*.min.js", "fixtures/**"]); repos can add more in .spectrail.toml
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('context_ignore', '', datetime('now'));
@@ -0,0 +1,10 @@
+-- Files hand-picked in or out of a task's context: pinned files are read into
+-- plan prompts, excluded ones are hidden from every run's repo tools
+CREATE TABLE IF NOT EXISTS context_overrides (
+  task_id TEXT NOT NULL,
+  path TEXT NOT NULL,            -- repo-relative file path
+  action TEXT NOT NULL CHECK (action IN ('pin', 'exclude')),
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (task_id, path),
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
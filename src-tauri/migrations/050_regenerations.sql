@@ -0,0 +1,15 @@
+-- Tool calls an assistant message made and the call a tool message answers,
+-- so a run's conversation can be replayed
+ALTER TABLE messages ADD COLUMN tool_calls_json TEXT;
+ALTER TABLE messages ADD COLUMN tool_call_id TEXT;
+
+-- Runs that continued an earlier run's conversation after one of its messages
+CREATE TABLE IF NOT EXISTS run_regenerations (
+  run_id TEXT PRIMARY KEY,
+  parent_run_id TEXT NOT NULL,
+  message_id TEXT NOT NULL,      -- last kept assistant message of the parent
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE CASCADE,
+  FOREIGN KEY(parent_run_id) REFERENCES runs(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_run_regenerations_parent ON run_regenerations(parent_run_id);
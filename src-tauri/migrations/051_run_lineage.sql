@@ -0,0 +1,9 @@
+-- The run a run reran, retried or continued, so related runs form a tree
+ALTER TABLE runs ADD COLUMN parent_run_id TEXT;
+ALTER TABLE runs ADD COLUMN parent_relation TEXT;  -- rerun|retry|regenerate
+CREATE INDEX IF NOT EXISTS idx_runs_parent ON runs(parent_run_id);
+
+UPDATE runs SET
+  parent_run_id = (SELECT g.parent_run_id FROM run_regenerations g WHERE g.run_id = runs.id),
+  parent_relation = 'regenerate'
+WHERE id IN (SELECT run_id FROM run_regenerations);
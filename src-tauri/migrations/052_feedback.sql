@@ -0,0 +1,17 @@
+-- Thumbs-up/down and comments on plans and verification reports
+CREATE TABLE IF NOT EXISTS feedback (
+  id TEXT PRIMARY KEY,
+  artifact_id TEXT NOT NULL,
+  task_id TEXT NOT NULL,
+  kind TEXT NOT NULL,            -- plan_md|verification_report
+  rating TEXT NOT NULL CHECK (rating IN ('up', 'down')),
+  comment TEXT,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(artifact_id) REFERENCES artifacts(id) ON DELETE CASCADE,
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_feedback_task ON feedback(task_id);
+CREATE INDEX IF NOT EXISTS idx_feedback_artifact ON feedback(artifact_id);
+
+-- '1' passes feedback on the current plan or report to the next run that replaces it
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('feedback_in_prompt', '1', datetime('now'));
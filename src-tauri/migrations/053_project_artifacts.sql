@@ -0,0 +1,19 @@
+-- Artifacts about a whole project rather than one task (artifacts.task_id is
+-- required). Each generation adds a row, newest wins, and its token usage
+-- counts toward the project's budget since there's no task run to carry it.
+CREATE TABLE IF NOT EXISTS project_artifacts (
+  id TEXT PRIMARY KEY,
+  project_id TEXT NOT NULL,
+  kind TEXT NOT NULL,             -- repo_overview
+  content TEXT NOT NULL,
+  focus TEXT,                     -- what the reader asked to go deeper on
+  provider TEXT,
+  model TEXT,
+  prompt_tokens INTEGER NOT NULL DEFAULT 0,
+  completion_tokens INTEGER NOT NULL DEFAULT 0,
+  cost_usd REAL NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_artifacts_project ON project_artifacts(project_id, kind, created_at);
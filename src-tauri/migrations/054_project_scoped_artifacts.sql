@@ -0,0 +1,86 @@
+-- Artifacts belong to either a task or a whole project (repo map, architecture
+-- overview, release notes, conventions). SQLite can't drop NOT NULL in place,
+-- so the table is rebuilt; foreign keys are off meanwhile so feedback rows
+-- pointing at artifacts survive the drop.
+PRAGMA foreign_keys = OFF;
+
+CREATE TABLE artifacts_new (
+  id TEXT PRIMARY KEY,
+  task_id TEXT,                   -- NULL for project artifacts
+  project_id TEXT,                -- set only for project artifacts
+  phase_id TEXT,
+  kind TEXT NOT NULL, -- plan_md|phase_list|verification_report|handoff_prompt|notes|diff_walkthrough; project: repo_map|repo_overview|release_notes|conventions
+  content TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  pinned INTEGER NOT NULL DEFAULT 0, -- project artifacts: included in plan and verify prompts
+  draft INTEGER NOT NULL DEFAULT 0,
+  content_hash TEXT,
+  CHECK ((task_id IS NULL) != (project_id IS NULL)),
+  FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE,
+  FOREIGN KEY(phase_id) REFERENCES phases(id) ON DELETE SET NULL
+);
+
+INSERT INTO artifacts_new (id, task_id, project_id, phase_id, kind, content, created_at, pinned, draft, content_hash)
+  SELECT id, task_id, NULL, phase_id, kind, content, created_at, pinned, draft, content_hash FROM artifacts;
+
+-- Token usage of project workflows, which have no task run to carry it
+CREATE TABLE IF NOT EXISTS project_usage (
+  id TEXT PRIMARY KEY,
+  project_id TEXT NOT NULL,
+  workflow TEXT NOT NULL,         -- explain_repo
+  provider TEXT,
+  model TEXT,
+  prompt_tokens INTEGER NOT NULL DEFAULT 0,
+  completion_tokens INTEGER NOT NULL DEFAULT 0,
+  cost_usd REAL NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_usage_project ON project_usage(project_id, created_at);
+
+-- Overviews from the short-lived project_artifacts table: the newest of each
+-- kind becomes the project's artifact, with the focus it was asked for noted
+INSERT INTO artifacts_new (id, task_id, project_id, phase_id, kind, content, created_at, pinned, draft, content_hash)
+  SELECT id, NULL, project_id, NULL, kind,
+         CASE WHEN focus IS NULL THEN content ELSE '> Focus: ' || focus || char(10) || char(10) || content END,
+         created_at, 0, 0, NULL
+  FROM project_artifacts pa
+  WHERE created_at = (SELECT MAX(created_at) FROM project_artifacts WHERE project_id = pa.project_id AND kind = pa.kind);
+
+INSERT INTO project_usage (id, project_id, workflow, provider, model, prompt_tokens, completion_tokens, cost_usd, created_at)
+  SELECT id, project_id, 'explain_repo', provider, model, prompt_tokens, completion_tokens, cost_usd, created_at
+  FROM project_artifacts;
+
+DROP TABLE project_artifacts;
+DROP TABLE artifacts;
+ALTER TABLE artifacts_new RENAME TO artifacts;
+
+CREATE INDEX IF NOT EXISTS idx_artifacts_task ON artifacts(task_id);
+CREATE INDEX IF NOT EXISTS idx_artifacts_draft ON artifacts(task_id, kind, draft);
+CREATE INDEX IF NOT EXISTS idx_artifacts_content_hash ON artifacts(content_hash);
+CREATE INDEX IF NOT EXISTS idx_artifacts_project ON artifacts(project_id, kind);
+
+-- Dropping the table dropped its triggers. Project artifacts aren't synced:
+-- sync keys artifacts by task.
+CREATE TRIGGER IF NOT EXISTS sync_artifacts_insert AFTER INSERT ON artifacts
+WHEN NEW.draft = 0 AND NEW.task_id IS NOT NULL AND EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('artifact', NEW.task_id || ':' || COALESCE(NEW.phase_id, '') || ':' || NEW.kind, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_artifacts_update AFTER UPDATE ON artifacts
+WHEN NEW.draft = 0 AND NEW.task_id IS NOT NULL AND EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('artifact', NEW.task_id || ':' || COALESCE(NEW.phase_id, '') || ':' || NEW.kind, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_artifacts_delete AFTER DELETE ON artifacts
+WHEN OLD.draft = 0 AND OLD.task_id IS NOT NULL AND EXISTS (SELECT 1 FROM sync_state WHERE key = 'device_id') BEGIN
+  INSERT INTO sync_outbox (entity, entity_id, changed_at) VALUES ('artifact', OLD.task_id || ':' || COALESCE(OLD.phase_id, '') || ':' || OLD.kind, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS taggings_artifact_delete AFTER DELETE ON artifacts BEGIN
+  DELETE FROM taggings WHERE target = 'artifact' AND target_id = OLD.id;
+END;
+
+PRAGMA foreign_keys = ON;
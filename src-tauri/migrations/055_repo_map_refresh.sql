@@ -0,0 +1,3 @@
+-- Hours between automatic repo map refreshes, each recording any structure
+-- drift since the previous map; '0' refreshes only on demand
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('repo_map_refresh_hours', '24', datetime('now'));
@@ -0,0 +1,6 @@
+-- Experimental plan debate: two planners draft competing plans and a judge
+-- merges them. '1' enables it; the model settings are empty for the configured model.
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('plan_debate', '0', datetime('now'));
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('debate_model_a', '', datetime('now'));
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('debate_model_b', '', datetime('now'));
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('debate_judge_model', '', datetime('now'));
@@ -0,0 +1,9 @@
+-- How much output each tool hands back to the model; projects and workspaces can
+-- override any of these, and a tool call can only ask for less
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES
+('tool_output_max_chars', '200000', datetime('now')),
+('tool_diff_max_chars', '200000', datetime('now')),
+('tool_read_max_bytes', '200000', datetime('now')),
+('tool_list_max_files', '2000', datetime('now')),
+('tool_grep_max_results', '200', datetime('now')),
+('tool_grep_max_line_chars', '200', datetime('now'));
@@ -0,0 +1,4 @@
+-- How tool schemas are shaped for the provider: 'auto' picks by provider_name;
+-- 'standard', 'strict' (OpenAI strict mode), 'flat' (no enums or bounds) or
+-- 'compact' (flat with short descriptions) force one
+INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES ('tool_schema_profile', 'auto', datetime('now'));
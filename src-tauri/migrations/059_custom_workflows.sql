@@ -0,0 +1,13 @@
+-- Workflows users define for a project: a prompt template, the tools the model
+-- may call, checks run beforehand, and the artifact kind the answer is saved
+-- as. Repositories can also define them in .spectrail.toml; a saved workflow
+-- takes precedence over the repository's of the same name.
+CREATE TABLE IF NOT EXISTS custom_workflows (
+  id TEXT PRIMARY KEY,
+  project_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  definition TEXT NOT NULL,       -- JSON
+  updated_at TEXT NOT NULL,
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE,
+  UNIQUE(project_id, name)
+);
@@ -0,0 +1,15 @@
+-- Settings are validated against their types now (see settings::TYPED_SETTINGS).
+-- Flags saved as words become the 1/0 they're read as, and stored numbers lose
+-- stray whitespace; out-of-bounds numbers are clamped by settings::clamp_stored_values.
+UPDATE settings SET value = '1'
+WHERE key IN ('stream_responses', 'feedback_in_prompt', 'prompt_template_strict', 'verify_cache',
+              'reuse_check_results', 'plan_debate', 'tool_loop_abort')
+  AND lower(trim(value)) IN ('true', 'yes', 'on');
+UPDATE settings SET value = '0'
+WHERE key IN ('stream_responses', 'feedback_in_prompt', 'prompt_template_strict', 'verify_cache',
+              'reuse_check_results', 'plan_debate', 'tool_loop_abort')
+  AND lower(trim(value)) IN ('false', 'no', 'off');
+UPDATE settings SET value = trim(value)
+WHERE key IN ('temperature', 'max_tokens', 'tool_loop_threshold', 'tool_max_calls_per_run',
+              'tool_max_calls_per_minute', 'tool_max_commands_per_run', 'tool_max_bytes_per_run',
+              'retrieval_top_k', 'lsp_timeout_secs', 'repo_map_refresh_hours');
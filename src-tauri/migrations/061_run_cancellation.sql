@@ -0,0 +1,2 @@
+-- Set when a run was cancelled; its error is "Run cancelled"
+ALTER TABLE runs ADD COLUMN cancelled_at TEXT;
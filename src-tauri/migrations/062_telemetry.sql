@@ -0,0 +1,17 @@
+-- Anonymous usage counters, counted per day only while telemetry is enabled.
+-- Names identify a workflow and outcome (run.plan.finished) or failure code
+-- (failure.verify.OFFLINE); nothing about projects or code is stored.
+CREATE TABLE IF NOT EXISTS telemetry_counters (
+  day TEXT NOT NULL,              -- YYYY-MM-DD, UTC
+  name TEXT NOT NULL,
+  count INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY(day, name)
+);
+
+-- What was sent, exactly as previewed
+CREATE TABLE IF NOT EXISTS telemetry_uploads (
+  id TEXT PRIMARY KEY,
+  uploaded_at TEXT NOT NULL,
+  endpoint TEXT NOT NULL,
+  payload TEXT NOT NULL           -- JSON
+);
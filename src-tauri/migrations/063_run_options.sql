@@ -0,0 +1,3 @@
+-- Options a verify run was started with (checks to run, CI results, phase), so a
+-- rerun with the same seed repeats the same checks
+ALTER TABLE runs ADD COLUMN options_json TEXT;
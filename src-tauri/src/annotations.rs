@@ -0,0 +1,86 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::export::latest_artifact;
+use crate::workflows::findings::{extract_findings, Finding, Severity};
+
+#[derive(Debug, Serialize, Type)]
+pub struct AnnotationExport {
+  /// Workflow commands, one per line, ready to print from a CI step
+  pub content: String,
+  pub count: usize,
+  pub findings: Vec<Finding>,
+  /// Set when the commands were also written to a file
+  pub path: Option<String>,
+}
+
+fn level(severity: Severity) -> &'static str {
+  match severity {
+    Severity::High => "error",
+    Severity::Medium => "warning",
+    Severity::Low => "notice",
+  }
+}
+
+/// Workflow command data escaping; property values also escape `:` and `,`
+fn escape_data(text: &str) -> String {
+  text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+  escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// `::warning file=src/a.rs,line=3,title=...::message`
+pub fn annotation_command(finding: &Finding) -> String {
+  let mut props = format!("file={}", escape_property(&finding.file));
+  if let Some(line) = finding.line {
+    props.push_str(&format!(",line={}", line));
+  }
+  props.push_str(&format!(",title={}", escape_property(&format!("SpecTrail {}: {}", finding.section, finding.title))));
+  format!("::{} {}::{}", level(finding.severity), props, escape_data(&finding.message))
+}
+
+/// Convert the latest verification report's file-specific findings into
+/// GitHub Actions annotations, optionally writing them to `dest`
+pub fn export_github_annotations(app: &AppHandle, task_id: &str, dest: Option<&str>) -> Result<AnnotationExport, String> {
+  let report = latest_artifact(app, task_id, "verification_report")?;
+  let findings = extract_findings(&report.content);
+  let mut content: String = findings.iter().map(|f| annotation_command(f) + "\n").collect();
+  if content.is_empty() {
+    content = "::notice title=SpecTrail::Verification report has no file-specific findings\n".to_string();
+  }
+
+  if let Some(dest) = dest {
+    std::fs::write(dest, &content).map_err(|e| format!("Cannot write {}: {}", dest, e))?;
+  }
+  tracing::info!(task_id, count = findings.len(), "exported GitHub annotations");
+  Ok(AnnotationExport {
+    content,
+    count: findings.len(),
+    findings,
+    path: dest.map(|d| d.to_string()),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_annotation_command_escaping() {
+    let finding = Finding {
+      section: "risk",
+      severity: Severity::High,
+      title: "Injection, raw: query".into(),
+      message: "Line one\nLine 100%".into(),
+      file: "src/db.rs".into(),
+      line: Some(10),
+    };
+    assert_eq!(
+      annotation_command(&finding),
+      "::error file=src/db.rs,line=10,title=SpecTrail risk%3A Injection%2C raw%3A query::Line one%0ALine 100%25"
+    );
+  }
+}
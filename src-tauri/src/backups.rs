@@ -0,0 +1,217 @@
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+
+/// Number of backups to keep; older ones are deleted after each new backup
+pub const KEEP_SETTING: &str = "db_backup_keep";
+const DEFAULT_KEEP: usize = 5;
+const PREFIX: &str = "spectrail-";
+const EXTENSION: &str = "sqlite";
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct Backup {
+  /// File name without extension, e.g. "spectrail-20260301-093000123-v26"
+  pub id: String,
+  /// Schema version the backup was taken at
+  pub schema_version: Option<i64>,
+  pub size_bytes: u64,
+  pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct DatabaseHealth {
+  /// Result of the startup `PRAGMA quick_check`
+  pub ok: bool,
+  pub problems: Vec<String>,
+  pub backups: Vec<Backup>,
+}
+
+/// Startup integrity check result, kept in managed state
+#[derive(Default)]
+pub struct IntegrityState {
+  problems: Mutex<Option<Vec<String>>>,
+}
+
+pub fn backups_dir(db_path: &Path) -> PathBuf {
+  db_path.parent().unwrap_or(Path::new(".")).join("backups")
+}
+
+/// Sortable UTC timestamp with milliseconds, so backup ids order by age
+fn file_stamp(t: time::OffsetDateTime) -> String {
+  format!(
+    "{:04}{:02}{:02}-{:02}{:02}{:02}{:03}",
+    t.year(), t.month() as u8, t.day(), t.hour(), t.minute(), t.second(), t.millisecond()
+  )
+}
+
+/// Problems reported by `PRAGMA quick_check`; empty when the database is sound
+pub fn quick_check(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+  let mut stmt = conn.prepare("PRAGMA quick_check")?;
+  let rows = stmt.query_map([], |r| r.get::<_, String>(0))?;
+  let results = rows.collect::<Result<Vec<_>, _>>()?;
+  Ok(results.into_iter().filter(|r| r != "ok").collect())
+}
+
+/// Run the startup check and remember the result for `health`
+pub fn record_integrity(app: &AppHandle, conn: &Connection) -> Vec<String> {
+  let problems = quick_check(conn).unwrap_or_else(|e| vec![e.to_string()]);
+  if !problems.is_empty() {
+    tracing::error!(problems = ?problems, "database integrity check failed");
+  }
+  if let Some(state) = app.try_state::<IntegrityState>() {
+    *state.problems.lock().unwrap() = Some(problems.clone());
+  }
+  problems
+}
+
+pub fn keep_count(conn: &Connection) -> usize {
+  conn.query_row("SELECT value FROM settings WHERE key = ?1", [KEEP_SETTING], |r| r.get::<_, String>(0))
+    .ok()
+    .and_then(|v| v.parse().ok())
+    .unwrap_or(DEFAULT_KEEP)
+}
+
+fn read_backup(path: &Path) -> Option<Backup> {
+  let id = path.file_stem()?.to_str()?.to_string();
+  if !id.starts_with(PREFIX) || path.extension()? != EXTENSION {
+    return None;
+  }
+  let metadata = std::fs::metadata(path).ok()?;
+  let created_at = metadata.modified().ok()
+    .map(time::OffsetDateTime::from)
+    .and_then(|t| t.format(&time::format_description::well_known::Rfc3339).ok())
+    .unwrap_or_default();
+  let schema_version = id.rsplit_once("-v").and_then(|(_, v)| v.parse().ok());
+  Some(Backup { id, schema_version, size_bytes: metadata.len(), created_at })
+}
+
+/// Backups in `dir`, newest first
+pub fn list_in(dir: &Path) -> Vec<Backup> {
+  let mut backups: Vec<Backup> = std::fs::read_dir(dir)
+    .map(|entries| entries.flatten().filter_map(|e| read_backup(&e.path())).collect())
+    .unwrap_or_default();
+  backups.sort_by(|a, b| b.id.cmp(&a.id));
+  backups
+}
+
+fn rotate(dir: &Path, keep: usize) {
+  for old in list_in(dir).into_iter().skip(keep.max(1)) {
+    let path = dir.join(format!("{}.{}", old.id, EXTENSION));
+    if let Err(e) = std::fs::remove_file(&path) {
+      tracing::warn!("failed to delete old backup {}: {}", path.display(), e);
+    }
+  }
+}
+
+/// Write a consistent copy of the open database to the backups directory.
+/// Older backups are rotated out unless `rotate_old` is false, e.g. when the
+/// database failed its integrity check and older copies may be the good ones.
+pub fn snapshot(conn: &Connection, dir: &Path, schema_version: i64, keep: usize, rotate_old: bool) -> Result<Backup, String> {
+  std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  let (id, path) = loop {
+    let id = format!("{}{}-v{}", PREFIX, file_stamp(time::OffsetDateTime::now_utc()), schema_version);
+    let path = dir.join(format!("{}.{}", id, EXTENSION));
+    if !path.exists() {
+      break (id, path);
+    }
+    std::thread::sleep(std::time::Duration::from_millis(1));
+  };
+  conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+    .map_err(|e| format!("Cannot back up the database: {}", e))?;
+  if rotate_old {
+    rotate(dir, keep);
+  }
+  tracing::info!(backup = %id, "backed up database");
+  read_backup(&path).ok_or_else(|| format!("Backup {} was not written", id))
+}
+
+pub fn list_backups(app: &AppHandle) -> Result<Vec<Backup>, String> {
+  let paths = db::paths(app).map_err(|e| e.to_string())?;
+  Ok(list_in(&backups_dir(&paths.db_path)))
+}
+
+pub fn health(app: &AppHandle) -> Result<DatabaseHealth, String> {
+  let recorded = app.try_state::<IntegrityState>().and_then(|s| s.problems.lock().unwrap().clone());
+  let problems = match recorded {
+    Some(problems) => problems,
+    None => {
+      let conn = db::connect(app).map_err(|e| e.to_string())?;
+      record_integrity(app, &conn)
+    }
+  };
+  Ok(DatabaseHealth { ok: problems.is_empty(), problems, backups: list_backups(app)? })
+}
+
+/// Replace the database with a backup. The current database is backed up
+/// first, and the restored copy is migrated up to the current schema.
+pub fn restore_backup(app: &AppHandle, backup_id: &str) -> Result<Backup, String> {
+  let paths = db::paths(app).map_err(|e| e.to_string())?;
+  let dir = backups_dir(&paths.db_path);
+  let backup = list_in(&dir).into_iter().find(|b| b.id == backup_id)
+    .ok_or_else(|| format!("Backup {} not found", backup_id))?;
+  let backup_path = dir.join(format!("{}.{}", backup.id, EXTENSION));
+
+  let source = Connection::open_with_flags(&backup_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+    .map_err(|e| e.to_string())?;
+  let problems = quick_check(&source).map_err(|e| e.to_string())?;
+  if !problems.is_empty() {
+    return Err(format!("Backup {} is damaged: {}", backup_id, problems.join("; ")));
+  }
+  drop(source);
+
+  // Keep the database being replaced, without rotating out older backups
+  {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0)).map_err(|e| e.to_string())?;
+    if let Err(e) = snapshot(&conn, &dir, version, usize::MAX, false) {
+      tracing::warn!("failed to back up the database before restoring: {}", e);
+    }
+  }
+
+  let staging = paths.db_path.with_extension("restore");
+  std::fs::copy(&backup_path, &staging).map_err(|e| format!("Cannot copy backup: {}", e))?;
+  std::fs::rename(&staging, &paths.db_path).map_err(|e| format!("Cannot replace the database: {}", e))?;
+  for suffix in ["-wal", "-shm", "-journal"] {
+    let mut sidecar = paths.db_path.clone().into_os_string();
+    sidecar.push(suffix);
+    let _ = std::fs::remove_file(sidecar);
+  }
+
+  db::init_db(app).map_err(|e| e.to_string())?;
+  if let Err(e) = crate::encryption::init(app) {
+    tracing::error!("failed to load the database key: {}", e);
+  }
+  crate::settings::invalidate_settings(app);
+  tracing::info!(backup = %backup.id, "restored database from backup");
+  Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_snapshot_and_rotate() {
+    let dir = std::env::temp_dir().join(format!("spectrail-backups-{}", crate::models::new_id()));
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1);").unwrap();
+    assert!(quick_check(&conn).unwrap().is_empty());
+
+    for _ in 0..3 {
+      snapshot(&conn, &dir, 26, 2, true).unwrap();
+    }
+    let backups = list_in(&dir);
+    assert_eq!(backups.len(), 2);
+    assert_eq!(backups[0].schema_version, Some(26));
+
+    let copy = Connection::open(dir.join(format!("{}.{}", backups[0].id, EXTENSION))).unwrap();
+    let x: i64 = copy.query_row("SELECT x FROM t", [], |r| r.get(0)).unwrap();
+    assert_eq!(x, 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
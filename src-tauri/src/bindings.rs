@@ -0,0 +1,219 @@
+use specta_typescript::{BigIntExportBehavior, Typescript};
+use std::path::Path;
+use tauri::Wry;
+use tauri_specta::{collect_commands, Builder, ErrorHandlingMode};
+
+use crate::commands;
+use crate::events;
+
+/// Generated from the commands below and the types they take and return
+const BINDINGS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../src/lib/bindings.ts");
+
+/// Every command the frontend can invoke. The builder both registers them
+/// with Tauri and knows their signatures, so a command added here shows up
+/// in the bindings with its argument and result types.
+pub fn builder() -> Builder<Wry> {
+  Builder::<Wry>::new()
+    // Commands reject with their error message, as with a plain `invoke`
+    .error_handling(ErrorHandlingMode::Throw)
+    .commands(collect_commands![
+      commands::db_health,
+      commands::list_projects,
+      commands::create_project,
+      commands::create_project_from_url,
+      commands::get_project_remote,
+      commands::pull_project,
+      commands::touch_project,
+      commands::get_project,
+      commands::list_tasks,
+      commands::create_task,
+      commands::get_task,
+      commands::get_task_scope_path,
+      commands::set_task_scope_path,
+      commands::search_tasks,
+      commands::bulk_update_tasks,
+      commands::bulk_archive_tasks,
+      commands::list_runs,
+      commands::get_run_lineage,
+      commands::cancel_run,
+      commands::create_run,
+      commands::list_messages,
+      commands::add_message,
+      commands::list_artifacts,
+      commands::list_tags,
+      commands::get_tags,
+      commands::add_tags,
+      commands::remove_tags,
+      commands::delete_tag,
+      commands::upsert_artifact,
+      commands::get_artifact,
+      commands::get_artifact_render_blocks,
+      commands::export_artifact,
+      commands::email_report,
+      commands::post_to_slack,
+      commands::export_github_annotations,
+      commands::export_sarif,
+      commands::open_in_editor,
+      commands::get_companion_status,
+      commands::revoke_companion_client,
+      commands::get_task_time_summary,
+      commands::get_budget_status,
+      commands::confirm_budget_overrun,
+      commands::get_offline_status,
+      commands::cancel_queued_run,
+      commands::get_telemetry_preview,
+      commands::upload_telemetry,
+      commands::clear_telemetry,
+      commands::send_app_feedback,
+      commands::get_database_encryption,
+      commands::set_database_encryption,
+      commands::get_database_health,
+      commands::list_backups,
+      commands::restore_backup,
+      commands::get_sync_status,
+      commands::configure_sync,
+      commands::sync_now,
+      commands::get_phase_board,
+      commands::set_phase_dependencies,
+      commands::list_phases,
+      commands::create_phase,
+      commands::update_phase,
+      commands::delete_phase,
+      commands::generate_phases,
+      commands::review_changes,
+      commands::list_task_attachments,
+      commands::add_task_note,
+      commands::attach_task_file,
+      commands::delete_task_attachment,
+      commands::transcribe_audio,
+      commands::get_settings,
+      commands::get_settings_typed,
+      commands::export_settings,
+      commands::import_settings,
+      commands::get_setting,
+      commands::set_setting,
+      commands::set_settings,
+      commands::get_project_settings,
+      commands::set_project_setting,
+      commands::get_context_ignore,
+      commands::set_context_ignore,
+      commands::approve_script_fallback,
+      commands::index_repository,
+      commands::get_index_status,
+      commands::list_workspaces,
+      commands::create_workspace,
+      commands::rename_workspace,
+      commands::delete_workspace,
+      commands::set_project_workspace,
+      commands::get_workspace_settings,
+      commands::set_workspace_setting,
+      commands::get_workspace_dashboard,
+      commands::get_integration_status,
+      commands::set_integration_config,
+      commands::set_integration_token,
+      commands::import_issue,
+      commands::list_task_links,
+      commands::push_issue_comment,
+      commands::get_recent_logs,
+      commands::export_diagnostics,
+      commands::onboarding_detect_runtimes,
+      commands::onboarding_test_llm,
+      commands::onboarding_create_project,
+      commands::onboarding_smoke_plan,
+      commands::check_for_updates,
+      commands::install_update,
+      commands::seed_demo_data,
+      commands::clear_demo_data,
+      commands::open_task_window,
+      commands::subscribe_window,
+      commands::unsubscribe_window,
+      commands::verify_current_task,
+      commands::open_last_project,
+      commands::quick_capture,
+      commands::list_tool_calls_cmd,
+      commands::get_run_transcript,
+      commands::execute_repo_tool,
+      commands::get_repo_tool_schemas,
+      commands::get_tool_stats,
+      commands::analyze_run_tools,
+      commands::preview_prompt,
+      commands::preview_context,
+      commands::list_context_overrides,
+      commands::set_context_override,
+      commands::explain_diff,
+      commands::explain_repo,
+      commands::extract_glossary,
+      commands::list_custom_workflows,
+      commands::save_custom_workflow,
+      commands::delete_custom_workflow,
+      commands::run_custom_workflow,
+      commands::reload_tool_plugins,
+      commands::list_hook_scripts,
+      commands::list_event_schemas,
+      commands::list_project_artifacts,
+      commands::upsert_project_artifact,
+      commands::refresh_repo_map,
+      commands::set_project_artifact_pinned,
+      commands::delete_project_artifact,
+      commands::submit_feedback,
+      commands::list_feedback,
+      commands::delete_feedback,
+      commands::get_feedback_summary,
+      commands::ingest_ci_results,
+      commands::capture_baseline,
+      commands::get_baseline,
+      commands::list_test_stats,
+      commands::reset_test_stats,
+      commands::kill_all_running_commands,
+      commands::generate_plan_command,
+      commands::verify_task_command,
+      commands::debate_plan,
+      commands::rerun_with_same_seed,
+      commands::regenerate_from_message,
+    ])
+    // Event payloads aren't in any command's signature but listeners need them too
+    .typ::<events::RunEvent>()
+    .typ::<events::TokenEvent>()
+    .typ::<events::CommandQueueEvent>()
+    .typ::<events::PairRequest>()
+}
+
+/// Write src/lib/bindings.ts. Debug builds do this on startup, so the file
+/// follows the commands as they change; `npm run bindings` does it on demand.
+pub fn export(builder: &Builder<Wry>) -> Result<(), String> {
+  export_to(builder, Path::new(BINDINGS_PATH))
+}
+
+fn export_to(builder: &Builder<Wry>, path: &Path) -> Result<(), String> {
+  let typescript = Typescript::default()
+    .header("// Generated by src-tauri/src/bindings.rs; run `npm run bindings` instead of editing")
+    // Ids, counts and token totals are stored as i64/usize but never near 2^53
+    .bigint(BigIntExportBehavior::Number);
+  builder.export(typescript, path).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Fails when a command or one of its types changed without regenerating the file
+  #[test]
+  fn bindings_are_current() {
+    let path = std::env::temp_dir().join(format!("spectrail-bindings-{}.ts", std::process::id()));
+    export_to(&builder(), &path).unwrap();
+    let generated = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let committed = std::fs::read_to_string(BINDINGS_PATH).unwrap_or_default();
+    assert!(
+      generated == committed.replace("\r\n", "\n"),
+      "src/lib/bindings.ts is out of date; run `npm run bindings` and commit the result"
+    );
+  }
+
+  /// Rewrites the committed file; `npm run bindings` runs this
+  #[test]
+  #[ignore]
+  fn export_bindings() {
+    export(&builder()).unwrap();
+  }
+}
@@ -0,0 +1,123 @@
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+
+use crate::compression::{encode, StoredText};
+
+/// Values at least this large are stored once in `blobs` and referenced by hash
+pub const BLOB_MIN_BYTES: usize = 4096;
+
+/// Columns whose large values live in `blobs`: (table, value column, hash column, rows to skip).
+/// Drafts are rewritten in place on every checkpoint, so they stay inline.
+const BLOB_COLUMNS: &[(&str, &str, &str, &str)] = &[
+  ("messages", "content", "content_hash", "0"),
+  ("artifacts", "content", "content_hash", "draft = 1"),
+  ("tool_calls", "result_json", "result_hash", "0"),
+];
+
+fn hash(content: &str) -> String {
+  format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// Prepare a value for writing. Large values are stored in `blobs` and come
+/// back as an empty inline value plus their hash; smaller ones are returned
+/// inline, compressed if they're big enough.
+pub fn put(conn: &Connection, content: &str) -> rusqlite::Result<(Value, Option<String>)> {
+  if content.len() < BLOB_MIN_BYTES {
+    return Ok((encode(content)?, None));
+  }
+  let hash = hash(content);
+  conn.execute(
+    "INSERT OR IGNORE INTO blobs (hash, content, size, created_at) VALUES (?1, ?2, ?3, ?4)",
+    (&hash, encode(content)?, content.len() as i64, now_iso())
+  )?;
+  Ok((Value::Text(String::new()), Some(hash)))
+}
+
+/// Full value of a column written with `put`
+pub fn hydrate(conn: &Connection, inline: StoredText, hash: Option<String>) -> rusqlite::Result<String> {
+  match hash {
+    Some(hash) => conn.query_row("SELECT content FROM blobs WHERE hash = ?1", [&hash], |r| r.get::<_, StoredText>(0)).map(|t| t.0),
+    None => Ok(inline.0),
+  }
+}
+
+/// Move large values written before the blob store into it, deduplicating
+/// identical payloads. Run once by the migration that adds `blobs`, inside
+/// its transaction.
+pub fn backfill(conn: &Connection) -> rusqlite::Result<usize> {
+  let mut moved = 0;
+  for (table, column, hash_column, skip) in BLOB_COLUMNS {
+    let ids: Vec<String> = {
+      let mut stmt = conn.prepare(&format!(
+        "SELECT id FROM {table} WHERE {hash_column} IS NULL AND length(CAST({column} AS BLOB)) >= ?1 AND NOT ({skip})"
+      ))?;
+      let rows = stmt.query_map([BLOB_MIN_BYTES as i64], |r| r.get(0))?;
+      rows.collect::<Result<_, _>>()?
+    };
+    for id in ids {
+      let value: String = conn.query_row(&format!("SELECT {column} FROM {table} WHERE id = ?1"), [&id], |r| r.get(0))?;
+      let (inline, hash) = put(conn, &value)?;
+      conn.execute(
+        &format!("UPDATE {table} SET {column} = ?1, {hash_column} = ?2 WHERE id = ?3"),
+        (&inline, &hash, &id)
+      )?;
+      moved += 1;
+    }
+  }
+  if moved > 0 {
+    tracing::info!(moved, "moved large values into the blob store");
+  }
+  Ok(moved)
+}
+
+/// Delete blobs no row references any more, e.g. after an artifact was
+/// rewritten or its run deleted
+pub fn collect_garbage(conn: &Connection) -> rusqlite::Result<usize> {
+  let referenced: Vec<String> = BLOB_COLUMNS.iter()
+    .map(|(table, _, hash_column, _)| format!("SELECT {hash_column} FROM {table} WHERE {hash_column} IS NOT NULL"))
+    .collect();
+  conn.execute(&format!("DELETE FROM blobs WHERE hash NOT IN ({})", referenced.join(" UNION ")), [])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_blobs_dedupe_and_hydrate() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE messages (id TEXT PRIMARY KEY, content TEXT NOT NULL);
+       CREATE TABLE artifacts (id TEXT PRIMARY KEY, content TEXT NOT NULL, draft INTEGER NOT NULL DEFAULT 0);
+       CREATE TABLE tool_calls (id TEXT PRIMARY KEY, result_json TEXT NOT NULL);"
+    ).unwrap();
+    conn.execute_batch(include_str!("../migrations/027_blobs.sql")).unwrap();
+
+    let large = "x".repeat(BLOB_MIN_BYTES);
+    for id in ["m1", "m2"] {
+      conn.execute("INSERT INTO messages (id, content) VALUES (?1, ?2)", (id, &large)).unwrap();
+    }
+    conn.execute("INSERT INTO messages (id, content) VALUES ('m3', 'short')", []).unwrap();
+    conn.execute("INSERT INTO artifacts (id, content, draft) VALUES ('d1', ?1, 1)", [&large]).unwrap();
+
+    assert_eq!(backfill(&conn).unwrap(), 2);
+    let blobs: i64 = conn.query_row("SELECT COUNT(*) FROM blobs", [], |r| r.get(0)).unwrap();
+    assert_eq!(blobs, 1);
+
+    let (inline, hash): (String, Option<String>) = conn.query_row(
+      "SELECT content, content_hash FROM messages WHERE id = 'm2'", [], |r| Ok((r.get(0)?, r.get(1)?))
+    ).unwrap();
+    assert!(inline.is_empty());
+    assert_eq!(hydrate(&conn, StoredText(inline), hash).unwrap(), large);
+    assert_eq!(put(&conn, "short").unwrap(), (Value::Text("short".into()), None));
+
+    conn.execute("DELETE FROM messages WHERE id IN ('m1', 'm2')", []).unwrap();
+    assert_eq!(collect_garbage(&conn).unwrap(), 1);
+  }
+}
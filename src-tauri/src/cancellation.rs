@@ -0,0 +1,162 @@
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio_util::sync::CancellationToken;
+
+use crate::db;
+use crate::repo_tools::processes;
+
+pub const CANCELLED_MESSAGE: &str = "Run cancelled";
+
+/// Cancellation tokens of the runs in progress, by run id
+pub struct RunCancellations {
+  tokens: Mutex<HashMap<String, CancellationToken>>,
+  /// Unfinished runs started before this are left over from an earlier session
+  session_started: OffsetDateTime,
+}
+
+impl Default for RunCancellations {
+  fn default() -> Self {
+    Self { tokens: Mutex::default(), session_started: OffsetDateTime::now_utc() }
+  }
+}
+
+/// A run's cancellation token; registered until the run finishes
+pub struct RunCancellation {
+  app: AppHandle,
+  run_id: String,
+  token: CancellationToken,
+}
+
+impl RunCancellation {
+  pub fn is_cancelled(&self) -> bool {
+    self.token.is_cancelled()
+  }
+
+  /// Fail once the run is cancelled. Loops call this between model calls and
+  /// tool executions, so a tool already running finishes first.
+  pub fn check(&self) -> Result<(), String> {
+    if self.is_cancelled() {
+      Err(CANCELLED_MESSAGE.into())
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Await `fut` unless the run is cancelled first, in which case it's dropped.
+  /// Only for work that's safe to abandon halfway, like a model call.
+  pub async fn or_cancel<F: Future>(&self, fut: F) -> Result<F::Output, String> {
+    self.token.run_until_cancelled(fut).await.ok_or_else(|| CANCELLED_MESSAGE.to_string())
+  }
+}
+
+impl Drop for RunCancellation {
+  fn drop(&mut self) {
+    if let Some(state) = self.app.try_state::<RunCancellations>() {
+      state.tokens.lock().unwrap().remove(&self.run_id);
+    }
+  }
+}
+
+pub fn register(app: &AppHandle, run_id: &str) -> RunCancellation {
+  register_token(app, run_id, CancellationToken::new())
+}
+
+/// Register a run started on behalf of another, like a debate's candidate
+/// plans, so cancelling the parent cancels it too
+pub fn register_child(app: &AppHandle, run_id: &str, parent_run_id: &str) -> RunCancellation {
+  let parent = app.try_state::<RunCancellations>()
+    .and_then(|state| state.tokens.lock().unwrap().get(parent_run_id).cloned());
+  let token = parent.map(|p| p.child_token()).unwrap_or_default();
+  register_token(app, run_id, token)
+}
+
+fn register_token(app: &AppHandle, run_id: &str, token: CancellationToken) -> RunCancellation {
+  if let Some(state) = app.try_state::<RunCancellations>() {
+    state.tokens.lock().unwrap().insert(run_id.to_string(), token.clone());
+  }
+  RunCancellation { app: app.clone(), run_id: run_id.to_string(), token }
+}
+
+/// Stop a run in progress. The commands it started are killed, and the run
+/// ends at its next model call or tool execution and records the cancellation
+/// itself. A run left unfinished by an earlier session has nothing to stop and
+/// is ended here.
+pub fn cancel_run(app: &AppHandle, run_id: &str) -> Result<(), String> {
+  let state = app.try_state::<RunCancellations>();
+  let token = state.as_ref().and_then(|state| state.tokens.lock().unwrap().get(run_id).cloned());
+  match token {
+    Some(token) => {
+      tracing::info!(run_id, "cancelling run");
+      token.cancel();
+      processes::kill_run(app, run_id);
+      Ok(())
+    }
+    None => {
+      let session_started = state.map(|state| state.session_started).unwrap_or_else(OffsetDateTime::now_utc);
+      let conn = db::connect(app).map_err(|e| e.to_string())?;
+      end_stale_run(&conn, run_id, session_started)
+    }
+  }
+}
+
+fn end_stale_run(conn: &Connection, run_id: &str, session_started: OffsetDateTime) -> Result<(), String> {
+  let run: Option<(String, Option<String>)> = conn.query_row(
+    "SELECT started_at, ended_at FROM runs WHERE id = ?1", [run_id], |r| Ok((r.get(0)?, r.get(1)?))
+  ).optional().map_err(|e| e.to_string())?;
+  match run {
+    None => Err(format!("Run {} not found", run_id)),
+    Some((_, Some(_))) => Err(format!("Run {} has already finished", run_id)),
+    // Something in this session is still running it
+    Some((started_at, None)) if OffsetDateTime::parse(&started_at, &Rfc3339).is_ok_and(|t| t >= session_started) => {
+      Err(format!("Run {} is still running and can't be cancelled", run_id))
+    }
+    Some((_, None)) => {
+      conn.execute(
+        "UPDATE runs SET cancelled_at = ?1, ended_at = ?1, error = ?2 WHERE id = ?3",
+        (now_iso(), CANCELLED_MESSAGE, run_id)
+      ).map_err(|e| e.to_string())?;
+      Ok(())
+    }
+  }
+}
+
+fn now_iso() -> String {
+  OffsetDateTime::now_utc()
+    .format(&Rfc3339)
+    .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_end_stale_run() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE runs (id TEXT PRIMARY KEY, started_at TEXT NOT NULL, ended_at TEXT, error TEXT, cancelled_at TEXT);
+       INSERT INTO runs (id, started_at) VALUES ('stale', '2024-01-01T00:00:00Z');
+       INSERT INTO runs (id, started_at) VALUES ('live', '2024-01-02T00:00:01.5Z');
+       INSERT INTO runs (id, started_at, ended_at) VALUES ('done', '2024-01-01T00:00:00Z', '2024-01-01T00:01:00Z');"
+    ).unwrap();
+    let session_started = OffsetDateTime::parse("2024-01-02T00:00:00Z", &Rfc3339).unwrap();
+
+    end_stale_run(&conn, "stale", session_started).unwrap();
+    let (ended, error, cancelled): (bool, Option<String>, bool) = conn.query_row(
+      "SELECT ended_at IS NOT NULL, error, cancelled_at IS NOT NULL FROM runs WHERE id = 'stale'", [],
+      |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+    ).unwrap();
+    assert_eq!((ended, error.as_deref(), cancelled), (true, Some(CANCELLED_MESSAGE), true));
+
+    assert!(end_stale_run(&conn, "live", session_started).unwrap_err().contains("still running"));
+    let live_ended: bool = conn.query_row("SELECT ended_at IS NOT NULL FROM runs WHERE id = 'live'", [], |r| r.get(0)).unwrap();
+    assert!(!live_ended);
+    assert!(end_stale_run(&conn, "done", session_started).unwrap_err().contains("already finished"));
+    assert!(end_stale_run(&conn, "missing", session_started).unwrap_err().contains("not found"));
+  }
+}
@@ -0,0 +1,187 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use specta::Type;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::models::{new_id, Project};
+use crate::repo_tools::safety::safe_spawn;
+
+const CLONE_TIMEOUT_SECS: u64 = 900;
+const PULL_TIMEOUT_SECS: u64 = 300;
+
+/// Where a project cloned from a URL came from
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ProjectRemote {
+  pub project_id: String,
+  pub git_url: String,
+  pub branch: String,
+  pub shallow: bool,
+  pub created_at: String,
+  pub last_pulled_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct PullResult {
+  pub branch: String,
+  pub before: String,
+  pub after: String,
+  pub updated: bool,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339)
+    .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// Accept only URLs git fetches over the network. Transports like `ext::` run
+/// arbitrary commands, local paths should be added as projects directly, and a
+/// leading `-` would pass as an option.
+fn validate_git_url(url: &str) -> Result<(), String> {
+  if url.is_empty() || url.starts_with('-') || url.contains(char::is_whitespace) || url.contains("::") {
+    return Err(format!("'{}' is not a git URL", url));
+  }
+  if ["https://", "http://", "ssh://", "git://"].iter().any(|scheme| url.starts_with(scheme)) {
+    return Ok(());
+  }
+  // scp-like user@host:path; a single letter before the colon is a Windows drive
+  match url.split_once(':') {
+    Some((host, path)) if !url.contains("://") && host.len() > 1 && !host.contains('/') && !path.is_empty() => Ok(()),
+    _ => Err(format!("Unsupported git URL '{}' (expected https://, ssh://, git:// or user@host:path)", url)),
+  }
+}
+
+/// `https://github.com/acme/billing.git` -> `billing`
+fn default_name(url: &str) -> String {
+  let last = url.trim_end_matches('/').rsplit(['/', ':']).next().unwrap_or(url);
+  let name = last.strip_suffix(".git").unwrap_or(last);
+  if name.is_empty() { url.to_string() } else { name.to_string() }
+}
+
+async fn git(dir: &Path, args: &[&str], timeout_secs: u64) -> Result<String, String> {
+  let (stdout, stderr, code) = safe_spawn("git", args, dir, timeout_secs)
+    .await
+    .map_err(|e| e.to_string())?;
+  if code != 0 {
+    return Err(format!("git {} failed: {}", args.first().unwrap_or(&""), stderr.trim()));
+  }
+  Ok(stdout.trim().to_string())
+}
+
+/// Clone `git_url` into the app data directory and add it as a project. A
+/// shallow clone fetches only the tip of the branch.
+pub async fn create_project_from_url(
+  app: &AppHandle,
+  git_url: &str,
+  name: Option<String>,
+  branch: Option<String>,
+  shallow: bool,
+) -> Result<Project, String> {
+  let git_url = git_url.trim();
+  validate_git_url(git_url)?;
+  let branch = branch.map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+  if branch.as_deref().is_some_and(|b| b.starts_with('-')) {
+    return Err("Branch names can't start with '-'".to_string());
+  }
+
+  let id = new_id();
+  let clones_dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("clones");
+  std::fs::create_dir_all(&clones_dir).map_err(|e| e.to_string())?;
+  let dir = clones_dir.join(&id);
+  let dir_str = dir.to_string_lossy().to_string();
+
+  let mut args = vec!["clone", "--quiet"];
+  if shallow {
+    args.extend(["--depth", "1"]);
+  }
+  if let Some(branch) = &branch {
+    args.extend(["--branch", branch.as_str()]);
+  }
+  args.extend(["--", git_url, dir_str.as_str()]);
+  if let Err(e) = git(&clones_dir, &args, CLONE_TIMEOUT_SECS).await {
+    let _ = std::fs::remove_dir_all(&dir);
+    return Err(e);
+  }
+  let checked_out = git(&dir, &["rev-parse", "--abbrev-ref", "HEAD"], 10).await?;
+
+  let project = Project {
+    id,
+    name: name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).unwrap_or_else(|| default_name(git_url)),
+    repo_path: dir_str,
+    created_at: now_iso(),
+    last_opened_at: None,
+    workspace_id: None,
+  };
+  let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  tx.execute(
+    "INSERT INTO projects (id, name, repo_path, created_at, last_opened_at) VALUES (?1, ?2, ?3, ?4, NULL)",
+    (&project.id, &project.name, &project.repo_path, &project.created_at)
+  ).map_err(|e| e.to_string())?;
+  tx.execute(
+    "INSERT INTO project_remotes (project_id, git_url, branch, shallow, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+    (&project.id, git_url, &checked_out, shallow, &project.created_at)
+  ).map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())?;
+  Ok(project)
+}
+
+pub fn project_remote(conn: &Connection, project_id: &str) -> Result<Option<ProjectRemote>, String> {
+  conn.query_row(
+    "SELECT project_id, git_url, branch, shallow, created_at, last_pulled_at FROM project_remotes WHERE project_id = ?1",
+    [project_id],
+    |r| Ok(ProjectRemote {
+      project_id: r.get(0)?,
+      git_url: r.get(1)?,
+      branch: r.get(2)?,
+      shallow: r.get(3)?,
+      created_at: r.get(4)?,
+      last_pulled_at: r.get(5)?,
+    })
+  ).optional().map_err(|e| e.to_string())
+}
+
+/// Fast-forward a cloned project to its remote branch. Local commits or
+/// history that diverged make the pull fail rather than merge.
+pub async fn pull_project(app: &AppHandle, project_id: &str) -> Result<PullResult, String> {
+  let (remote, repo_path) = {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let remote = project_remote(&conn, project_id)?
+      .ok_or("Only projects created from a git URL can be pulled")?;
+    let repo_path: String = conn.query_row("SELECT repo_path FROM projects WHERE id = ?1", [project_id], |r| r.get(0))
+      .map_err(|e| e.to_string())?;
+    (remote, repo_path)
+  };
+  let dir = Path::new(&repo_path);
+
+  let before = git(dir, &["rev-parse", "HEAD"], 10).await?;
+  git(dir, &["pull", "--quiet", "--ff-only", "origin", &remote.branch], PULL_TIMEOUT_SECS).await?;
+  let after = git(dir, &["rev-parse", "HEAD"], 10).await?;
+
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "UPDATE project_remotes SET last_pulled_at = ?1 WHERE project_id = ?2",
+    (now_iso(), project_id)
+  ).map_err(|e| e.to_string())?;
+  Ok(PullResult { branch: remote.branch, updated: before != after, before, after })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_git_urls() {
+    for url in ["https://github.com/acme/billing.git", "ssh://git@host:2222/acme/billing", "git@github.com:acme/billing.git"] {
+      assert!(validate_git_url(url).is_ok(), "{}", url);
+    }
+    for url in ["ext::sh -c touch% /tmp/pwned", "--upload-pack=touch /tmp/x", "C:\\repos\\billing", "/home/me/billing", "file:///srv/billing"] {
+      assert!(validate_git_url(url).is_err(), "{}", url);
+    }
+    assert_eq!(default_name("https://github.com/acme/billing.git"), "billing");
+    assert_eq!(default_name("git@github.com:acme/billing/"), "billing");
+    assert_eq!(default_name("git@github.com:billing.git"), "billing");
+  }
+}
@@ -1,7 +1,63 @@
+use std::collections::HashMap;
 use tauri::AppHandle;
 
+use crate::blobs;
+use crate::cancellation;
+use crate::compression;
+use crate::lineage::{self, RunNode};
 use crate::db;
 use crate::models::*;
+use crate::workflows::attachments::{list_attachments, attachments_context};
+use crate::diagnostics::DiagnosticsBundle;
+use crate::demo::{self, DemoSeedResult};
+use crate::onboarding::{self, StepResult};
+use crate::updater::{self, UpdateInfo};
+use crate::windows::{self, WindowScope};
+use crate::tray;
+use crate::quick_capture::{self, CaptureResult};
+use crate::render::{render_artifact, RenderBlock};
+use crate::export::{self, ExportResult};
+use crate::editor::{self, EditorLaunch};
+use crate::encryption::{self, EncryptionStatus};
+use crate::events::{self, EventSchema};
+use crate::email::{self, EmailResult};
+use crate::slack::{self, SlackPostResult};
+use crate::annotations::{self, AnnotationExport};
+use crate::backups::{self, Backup, DatabaseHealth};
+use crate::clones::{self, ProjectRemote, PullResult};
+use crate::sync::{self, S3Credentials, SyncRemote, SyncReport, SyncStatus};
+use crate::tags::{self, AppliedTag, Tag, TagTarget};
+use crate::companion::{self, CompanionStatus};
+use crate::offline::{self, OfflineStatus};
+use crate::sarif::{self, SarifExport};
+use crate::sessions::{self, TaskTimeSummary};
+use crate::support::{self, FeedbackKind, FeedbackSubmission};
+use crate::tasks::{self, BulkTaskResult, TaskFilters, TaskPatch, TaskSearchPage};
+use crate::telemetry::{self, TelemetryPreview, TelemetryUpload};
+use crate::transcript::{self, RunTranscript};
+use crate::workspaces::{self, Workspace, WorkspaceDashboard};
+use crate::integrations::{self, secrets, ImportedIssue, IntegrationConfig, IntegrationStatus, Provider, TaskLink};
+use crate::logs::{read_recent_logs, LogEntry};
+use crate::repo_tools::context_ignore::{self, parse_globs, ContextIgnoreConfig};
+use crate::repo_tools::custom_commands::parse_custom_commands;
+use crate::repo_tools::plugins::{self, PluginList};
+use crate::repo_tools::processes::{self, RunningCommand};
+use crate::repo_tools::sandbox::{Backend, MountMode};
+use crate::repo_tools::schemas::SchemaProfile;
+use crate::repo_tools::scope::normalize_scope;
+use crate::repo_tools::script_fallback::parse_script_fallbacks;
+use crate::settings::{self, cached_settings, invalidate_settings, typed_settings, validate_setting, SettingsExport, TypedSettings};
+use crate::workflows::baseline::{self, Baseline, BaselineMethod};
+use crate::workflows::budget::{self, BudgetStatus};
+use crate::workflows::context::{self, ContextOverride, ContextPreview};
+use crate::workflows::flaky::{self, TestHistory};
+use crate::workflows::hooks::{self, parse_lifecycle_hooks, HookScripts};
+use crate::workflows::locale::{language_name, SUPPORTED_LANGUAGES};
+use crate::workflows::phases::{self, PhaseUpdate, PhasesResult, PHASE_STATUSES};
+use crate::workflows::review::{self, ReviewOptions, ReviewResult};
+use crate::workflows::retrieval::{self, IndexReport, IndexStatus, PlanningStrategy};
+use crate::workflows::templates::{template_setting_key, template_vars, render_template, PromptPreview};
+use crate::llm::transcribe::{transcribe_file, TranscriptionConfig};
 
 fn now_iso() -> String {
   // RFC3339-ish without nanos; good enough for sorting/display.
@@ -10,16 +66,18 @@ fn now_iso() -> String {
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn db_health(app: AppHandle) -> Result<serde_json::Value, String> {
   let p = db::paths(&app).map_err(|e| e.to_string())?;
   Ok(serde_json::json!({ "ok": true, "path": p.db_path.to_string_lossy() }))
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn list_projects(app: AppHandle) -> Result<Vec<Project>, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let mut stmt = conn.prepare(
-    "SELECT id, name, repo_path, created_at, last_opened_at FROM projects ORDER BY COALESCE(last_opened_at, created_at) DESC"
+    "SELECT id, name, repo_path, created_at, last_opened_at, workspace_id FROM projects ORDER BY COALESCE(last_opened_at, created_at) DESC"
   ).map_err(|e| e.to_string())?;
   let rows = stmt.query_map([], |r| {
     Ok(Project {
@@ -28,6 +86,7 @@ pub fn list_projects(app: AppHandle) -> Result<Vec<Project>, String> {
       repo_path: r.get(2)?,
       created_at: r.get(3)?,
       last_opened_at: r.get(4)?,
+      workspace_id: r.get(5)?,
     })
   }).map_err(|e| e.to_string())?;
 
@@ -39,6 +98,7 @@ pub fn list_projects(app: AppHandle) -> Result<Vec<Project>, String> {
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn create_project(app: AppHandle, name: String, repo_path: String) -> Result<Project, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let id = new_id();
@@ -48,10 +108,37 @@ pub fn create_project(app: AppHandle, name: String, repo_path: String) -> Result
     (&id, &name, &repo_path, &created_at)
   ).map_err(|e| e.to_string())?;
 
-  Ok(Project { id, name, repo_path, created_at, last_opened_at: None })
+  Ok(Project { id, name, repo_path, created_at, last_opened_at: None, workspace_id: None })
 }
 
+/// Clone `git_url` (shallow when asked) into app data and add it as a project
 #[tauri::command]
+#[specta::specta]
+pub async fn create_project_from_url(
+  app: AppHandle,
+  git_url: String,
+  name: Option<String>,
+  branch: Option<String>,
+  shallow: Option<bool>,
+) -> Result<Project, String> {
+  clones::create_project_from_url(&app, &git_url, name, branch, shallow.unwrap_or(false)).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_project_remote(app: AppHandle, project_id: String) -> Result<Option<ProjectRemote>, String> {
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  clones::project_remote(&conn, &project_id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn pull_project(app: AppHandle, project_id: String) -> Result<PullResult, String> {
+  clones::pull_project(&app, &project_id).await
+}
+
+#[tauri::command]
+#[specta::specta]
 pub fn touch_project(app: AppHandle, project_id: String) -> Result<(), String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let t = now_iso();
@@ -63,10 +150,11 @@ pub fn touch_project(app: AppHandle, project_id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn get_project(app: AppHandle, project_id: String) -> Result<Project, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   conn.query_row(
-    "SELECT id, name, repo_path, created_at, last_opened_at FROM projects WHERE id = ?1",
+    "SELECT id, name, repo_path, created_at, last_opened_at, workspace_id FROM projects WHERE id = ?1",
     [&project_id],
     |r| Ok(Project {
       id: r.get(0)?,
@@ -74,15 +162,17 @@ pub fn get_project(app: AppHandle, project_id: String) -> Result<Project, String
       repo_path: r.get(2)?,
       created_at: r.get(3)?,
       last_opened_at: r.get(4)?,
+      workspace_id: r.get(5)?,
     })
   ).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn list_tasks(app: AppHandle, project_id: String) -> Result<Vec<Task>, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let mut stmt = conn.prepare(
-    "SELECT id, project_id, title, mode, status, created_at, updated_at FROM tasks WHERE project_id = ?1 ORDER BY updated_at DESC"
+    "SELECT id, project_id, title, mode, status, created_at, updated_at, description FROM tasks WHERE project_id = ?1 ORDER BY updated_at DESC"
   ).map_err(|e| e.to_string())?;
   let rows = stmt.query_map([project_id], |r| {
     Ok(Task {
@@ -93,6 +183,7 @@ pub fn list_tasks(app: AppHandle, project_id: String) -> Result<Vec<Task>, Strin
       status: r.get(4)?,
       created_at: r.get(5)?,
       updated_at: r.get(6)?,
+      description: r.get(7)?,
     })
   }).map_err(|e| e.to_string())?;
 
@@ -104,8 +195,14 @@ pub fn list_tasks(app: AppHandle, project_id: String) -> Result<Vec<Task>, Strin
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn create_task(app: AppHandle, project_id: String, title: String, mode: String) -> Result<Task, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  insert_task(&conn, project_id, title, mode)
+}
+
+/// Insert a draft task on `conn`, so callers can create it inside their own transaction
+pub(crate) fn insert_task(conn: &rusqlite::Connection, project_id: String, title: String, mode: String) -> Result<Task, String> {
   let id = new_id();
   let ts = now_iso();
   conn.execute(
@@ -113,14 +210,15 @@ pub fn create_task(app: AppHandle, project_id: String, title: String, mode: Stri
     (&id, &project_id, &title, &mode, &ts, &ts)
   ).map_err(|e| e.to_string())?;
 
-  Ok(Task { id, project_id, title, mode, status: "draft".into(), created_at: ts.clone(), updated_at: ts })
+  Ok(Task { id, project_id, title, description: None, mode, status: "draft".into(), created_at: ts.clone(), updated_at: ts })
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn get_task(app: AppHandle, task_id: String) -> Result<Task, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   conn.query_row(
-    "SELECT id, project_id, title, mode, status, created_at, updated_at FROM tasks WHERE id = ?1",
+    "SELECT id, project_id, title, mode, status, created_at, updated_at, description FROM tasks WHERE id = ?1",
     [&task_id],
     |r| Ok(Task {
       id: r.get(0)?,
@@ -130,37 +228,119 @@ pub fn get_task(app: AppHandle, task_id: String) -> Result<Task, String> {
       status: r.get(4)?,
       created_at: r.get(5)?,
       updated_at: r.get(6)?,
+      description: r.get(7)?,
     })
   ).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn list_runs(app: AppHandle, task_id: String) -> Result<Vec<Run>, String> {
+#[specta::specta]
+pub fn get_task_scope_path(app: AppHandle, task_id: String) -> Result<Option<String>, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
-  let mut stmt = conn.prepare(
-    "SELECT id, task_id, phase_id, run_type, provider, model, started_at, ended_at FROM runs WHERE task_id = ?1 ORDER BY started_at DESC"
-  ).map_err(|e| e.to_string())?;
-  let rows = stmt.query_map([task_id], |r| {
-    Ok(Run {
-      id: r.get(0)?,
-      task_id: r.get(1)?,
-      phase_id: r.get(2)?,
-      run_type: r.get(3)?,
-      provider: r.get(4)?,
-      model: r.get(5)?,
-      started_at: r.get(6)?,
-      ended_at: r.get(7)?,
-    })
-  }).map_err(|e| e.to_string())?;
+  conn.query_row("SELECT scope_path FROM tasks WHERE id = ?1", [&task_id], |r| r.get(0))
+    .map_err(|e| e.to_string())
+}
 
-  let mut out = vec![];
-  for row in rows {
-    out.push(row.map_err(|e| e.to_string())?);
+/// Limit the task's repo tools to a subtree such as services/billing; empty clears it
+#[tauri::command]
+#[specta::specta]
+pub fn set_task_scope_path(app: AppHandle, task_id: String, scope_path: Option<String>) -> Result<Option<String>, String> {
+  let scope_path = match scope_path {
+    Some(path) => normalize_scope(&path)?,
+    None => None,
+  };
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  let updated = conn.execute(
+    "UPDATE tasks SET scope_path = ?1, updated_at = ?2 WHERE id = ?3",
+    (&scope_path, now_iso(), &task_id)
+  ).map_err(|e| e.to_string())?;
+  if updated == 0 {
+    return Err(format!("Task {} not found", task_id));
   }
-  Ok(out)
+  Ok(scope_path)
+}
+
+/// A page of the project's tasks matching a text query and filters
+#[tauri::command]
+#[specta::specta]
+pub fn search_tasks(app: AppHandle, project_id: String, query: Option<String>, filters: Option<TaskFilters>) -> Result<TaskSearchPage, String> {
+  tasks::search_tasks(&app, &project_id, query.as_deref(), &filters.unwrap_or_default())
+}
+
+/// All tags with how many tasks and artifacts use each
+#[tauri::command]
+#[specta::specta]
+pub fn list_tags(app: AppHandle) -> Result<Vec<Tag>, String> {
+  tags::list_tags(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_tags(app: AppHandle, target: TagTarget, target_id: String) -> Result<Vec<AppliedTag>, String> {
+  tags::get_tags(&app, target, &target_id)
+}
+
+/// Tag a task or artifact; unknown tag names are created
+#[tauri::command]
+#[specta::specta]
+pub fn add_tags(app: AppHandle, target: TagTarget, target_id: String, names: Vec<String>) -> Result<Vec<AppliedTag>, String> {
+  tags::add_tags(&app, target, &target_id, &names)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn remove_tags(app: AppHandle, target: TagTarget, target_id: String, names: Vec<String>) -> Result<Vec<AppliedTag>, String> {
+  tags::remove_tags(&app, target, &target_id, &names)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_tag(app: AppHandle, tag_id: String) -> Result<(), String> {
+  tags::delete_tag(&app, &tag_id)
+}
+
+/// Apply the same change to many tasks in one transaction, with a result per task
+#[tauri::command]
+#[specta::specta]
+pub fn bulk_update_tasks(app: AppHandle, task_ids: Vec<ID>, patch: TaskPatch) -> Result<Vec<BulkTaskResult>, String> {
+  tasks::bulk_update_tasks(&app, &task_ids, &patch)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn bulk_archive_tasks(app: AppHandle, task_ids: Vec<ID>) -> Result<Vec<BulkTaskResult>, String> {
+  tasks::bulk_archive_tasks(&app, &task_ids)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_runs(app: AppHandle, task_id: String, grouped: Option<bool>) -> Result<Vec<RunNode>, String> {
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  let runs = lineage::task_runs(&conn, &task_id)?;
+  Ok(if grouped.unwrap_or(false) {
+    lineage::build_forest(runs)
+  } else {
+    runs.into_iter().map(RunNode::leaf).collect()
+  })
+}
+
+/// The tree of reruns, retries and regenerations the run belongs to
+#[tauri::command]
+#[specta::specta]
+pub fn get_run_lineage(app: AppHandle, run_id: String) -> Result<RunNode, String> {
+  lineage::run_lineage(&app, &run_id)
+}
+
+/// Stop a run in progress at its next model call or tool execution, killing
+/// the commands it started; it ends with the "Run cancelled" error
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_run(app: AppHandle, run_id: String) -> Result<(), String> {
+  cancellation::cancel_run(&app, &run_id)
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn create_run(app: AppHandle, task_id: String, run_type: String) -> Result<Run, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let id = new_id();
@@ -169,21 +349,22 @@ pub fn create_run(app: AppHandle, task_id: String, run_type: String) -> Result<R
     "INSERT INTO runs (id, task_id, phase_id, run_type, provider, model, started_at, ended_at) VALUES (?1, ?2, NULL, ?3, NULL, NULL, ?4, NULL)",
     (&id, &task_id, &run_type, &started_at)
   ).map_err(|e| e.to_string())?;
-  Ok(Run { id, task_id, phase_id: None, run_type, provider: None, model: None, started_at, ended_at: None })
+  Ok(Run { id, task_id, phase_id: None, run_type, provider: None, model: None, started_at, ended_at: None, error: None, prompt_tokens: 0, completion_tokens: 0, cost_usd: 0.0, seed: None, parent_run_id: None, parent_relation: None, cancelled_at: None })
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn list_messages(app: AppHandle, run_id: String) -> Result<Vec<Message>, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let mut stmt = conn.prepare(
-    "SELECT id, run_id, role, content, created_at FROM messages WHERE run_id = ?1 ORDER BY created_at ASC"
+    "SELECT id, run_id, role, content, created_at, content_hash FROM messages WHERE run_id = ?1 ORDER BY created_at ASC"
   ).map_err(|e| e.to_string())?;
   let rows = stmt.query_map([run_id], |r| {
     Ok(Message {
       id: r.get(0)?,
       run_id: r.get(1)?,
       role: r.get(2)?,
-      content: r.get(3)?,
+      content: blobs::hydrate(&conn, r.get(3)?, r.get(5)?)?,
       created_at: r.get(4)?,
     })
   }).map_err(|e| e.to_string())?;
@@ -196,32 +377,42 @@ pub fn list_messages(app: AppHandle, run_id: String) -> Result<Vec<Message>, Str
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn add_message(app: AppHandle, run_id: String, role: String, content: String) -> Result<Message, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let id = new_id();
   let created_at = now_iso();
+  let (stored, hash) = blobs::put(&conn, &content).map_err(|e| e.to_string())?;
   conn.execute(
-    "INSERT INTO messages (id, run_id, role, content, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-    (&id, &run_id, &role, &content, &created_at)
+    "INSERT INTO messages (id, run_id, role, content, created_at, content_hash) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    (&id, &run_id, &role, &stored, &created_at, &hash)
   ).map_err(|e| e.to_string())?;
   Ok(Message { id, run_id, role, content, created_at })
 }
 
 #[tauri::command]
-pub fn list_artifacts(app: AppHandle, task_id: String) -> Result<Vec<Artifact>, String> {
+#[specta::specta]
+pub fn list_artifacts(app: AppHandle, task_id: String, tag: Option<String>) -> Result<Vec<Artifact>, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  let tag = tag.as_deref().map(tags::normalize).transpose()?;
   let mut stmt = conn.prepare(
-    "SELECT id, task_id, phase_id, kind, content, created_at, pinned FROM artifacts WHERE task_id = ?1 ORDER BY created_at DESC"
+    "SELECT id, task_id, phase_id, kind, content, created_at, pinned, draft, content_hash, project_id FROM artifacts a
+     WHERE task_id = ?1 AND (?2 IS NULL OR EXISTS (
+       SELECT 1 FROM taggings g JOIN tags t ON t.id = g.tag_id
+       WHERE g.target = 'artifact' AND g.target_id = a.id AND t.name = ?2))
+     ORDER BY created_at DESC"
   ).map_err(|e| e.to_string())?;
-  let rows = stmt.query_map([task_id], |r| {
+  let rows = stmt.query_map((task_id, tag), |r| {
     Ok(Artifact {
       id: r.get(0)?,
       task_id: r.get(1)?,
+      project_id: r.get(9)?,
       phase_id: r.get(2)?,
       kind: r.get(3)?,
-      content: r.get(4)?,
+      content: blobs::hydrate(&conn, r.get(4)?, r.get(8)?)?,
       created_at: r.get(5)?,
       pinned: r.get(6)?,
+      draft: r.get(7)?,
     })
   }).map_err(|e| e.to_string())?;
 
@@ -233,36 +424,366 @@ pub fn list_artifacts(app: AppHandle, task_id: String) -> Result<Vec<Artifact>,
 }
 
 #[tauri::command]
+#[specta::specta]
+pub fn get_artifact(app: AppHandle, artifact_id: String) -> Result<Artifact, String> {
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  conn.query_row(
+    "SELECT id, task_id, phase_id, kind, content, created_at, pinned, draft, content_hash, project_id FROM artifacts WHERE id = ?1",
+    [&artifact_id],
+    |r| Ok(Artifact {
+      id: r.get(0)?,
+      task_id: r.get(1)?,
+      project_id: r.get(9)?,
+      phase_id: r.get(2)?,
+      kind: r.get(3)?,
+      content: blobs::hydrate(&conn, r.get(4)?, r.get(8)?)?,
+      created_at: r.get(5)?,
+      pinned: r.get(6)?,
+      draft: r.get(7)?,
+    })
+  ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
 pub fn upsert_artifact(app: AppHandle, task_id: String, phase_id: Option<String>, kind: String, content: String) -> Result<Artifact, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   // If an artifact of same (task_id, phase_id, kind) exists, update it; else insert.
   let existing: Option<String> = conn.query_row(
-    "SELECT id FROM artifacts WHERE task_id = ?1 AND COALESCE(phase_id,'') = COALESCE(?2,'') AND kind = ?3 LIMIT 1",
+    "SELECT id FROM artifacts WHERE task_id = ?1 AND COALESCE(phase_id,'') = COALESCE(?2,'') AND kind = ?3 AND draft = 0 LIMIT 1",
     (task_id.as_str(), phase_id.as_deref().unwrap_or(""), kind.as_str()),
     |r| r.get(0)
   ).optional().map_err(|e| e.to_string())?;
 
   let created_at = now_iso();
+  let (stored, hash) = blobs::put(&conn, &content).map_err(|e| e.to_string())?;
   let id = if let Some(id) = existing {
     conn.execute(
-      "UPDATE artifacts SET content = ?1, created_at = ?2 WHERE id = ?3",
-      (&content, &created_at, &id)
+      "UPDATE artifacts SET content = ?1, content_hash = ?2, created_at = ?3 WHERE id = ?4",
+      (&stored, &hash, &created_at, &id)
     ).map_err(|e| e.to_string())?;
     id
   } else {
     let id = new_id();
     conn.execute(
-      "INSERT INTO artifacts (id, task_id, phase_id, kind, content, created_at, pinned) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
-      (&id, &task_id, &phase_id, &kind, &content, &created_at)
+      "INSERT INTO artifacts (id, task_id, phase_id, kind, content, content_hash, created_at, pinned) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+      (&id, &task_id, &phase_id, &kind, &stored, &hash, &created_at)
     ).map_err(|e| e.to_string())?;
     id
   };
 
-  Ok(Artifact { id, task_id, phase_id, kind, content, created_at, pinned: 0 })
+  Ok(Artifact { id, task_id: Some(task_id), project_id: None, phase_id, kind, content, created_at, pinned: 0, draft: 0 })
+}
+
+/// Artifact content split into markdown and validated mermaid blocks
+#[tauri::command]
+#[specta::specta]
+pub fn get_artifact_render_blocks(app: AppHandle, artifact_id: String) -> Result<Vec<RenderBlock>, String> {
+  render_artifact(&app, &artifact_id)
+}
+
+/// Write an artifact as a standalone HTML or PDF report to `path`
+#[tauri::command]
+#[specta::specta]
+pub async fn export_artifact(app: AppHandle, artifact_id: String, format: String, path: String) -> Result<ExportResult, String> {
+  export::export_artifact(&app, &artifact_id, &format, &path).await
+}
+
+/// Send the task's latest verification report over the configured SMTP server
+#[tauri::command]
+#[specta::specta]
+pub async fn email_report(app: AppHandle, task_id: String, recipients: Vec<String>) -> Result<EmailResult, String> {
+  email::email_report(&app, &task_id, &recipients).await
+}
+
+/// Post the latest verification report to Slack; `channel` falls back to
+/// slack_default_channel and is ignored for webhooks
+#[tauri::command]
+#[specta::specta]
+pub async fn post_to_slack(app: AppHandle, task_id: String, channel: Option<String>) -> Result<SlackPostResult, String> {
+  slack::post_report(&app, &task_id, channel.as_deref(), None).await
+}
+
+/// File-specific findings of the latest verification report as GitHub Actions
+/// workflow commands, also written to `path` when given
+#[tauri::command]
+#[specta::specta]
+pub fn export_github_annotations(app: AppHandle, task_id: String, path: Option<String>) -> Result<AnnotationExport, String> {
+  annotations::export_github_annotations(&app, &task_id, path.as_deref())
+}
+
+/// Verification findings as a SARIF 2.1.0 log for code-scanning dashboards
+#[tauri::command]
+#[specta::specta]
+pub fn export_sarif(app: AppHandle, task_id: String, path: Option<String>) -> Result<SarifExport, String> {
+  sarif::export_sarif(&app, &task_id, path.as_deref())
+}
+
+/// Open a file cited in a plan or report at `line` in the configured editor;
+/// `path` is relative to the project's repository
+#[tauri::command]
+#[specta::specta]
+pub async fn open_in_editor(app: AppHandle, project_id: String, path: String, line: Option<u32>) -> Result<EditorLaunch, String> {
+  editor::open_in_editor(&app, &project_id, &path, line).await
+}
+
+// Phase board commands
+#[tauri::command]
+#[specta::specta]
+pub fn get_phase_board(app: AppHandle, task_id: String) -> Result<PhaseBoard, String> {
+  let phases = phases::list_phases(&app, &task_id)?;
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+
+  let mut stmt = conn.prepare(
+    "SELECT d.phase_id, d.depends_on_phase_id FROM phase_dependencies d
+     JOIN phases p ON p.id = d.phase_id WHERE p.task_id = ?1"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([&task_id], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+    .map_err(|e| e.to_string())?;
+  let mut deps = vec![];
+  for row in rows {
+    deps.push(row.map_err(|e| e.to_string())?);
+  }
+
+  // Latest phase-scoped verification report per phase
+  let mut stmt = conn.prepare(
+    "SELECT phase_id, content, created_at, content_hash FROM artifacts
+     WHERE task_id = ?1 AND phase_id IS NOT NULL AND kind = 'verification_report' AND draft = 0
+     ORDER BY created_at DESC"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([&task_id], |r| {
+    Ok((r.get::<_, String>(0)?, blobs::hydrate(&conn, r.get(1)?, r.get(3)?)?, r.get::<_, String>(2)?))
+  }).map_err(|e| e.to_string())?;
+  let mut verdicts: HashMap<String, (String, String)> = HashMap::new();
+  for row in rows {
+    let (phase_id, content, created_at) = row.map_err(|e| e.to_string())?;
+    verdicts.entry(phase_id)
+      .or_insert_with(|| (parse_verdict(&content).as_str().to_string(), created_at));
+  }
+
+  let status_of: HashMap<&str, &str> = phases.iter()
+    .map(|p| (p.id.as_str(), p.status.as_str()))
+    .collect();
+  let total = phases.len();
+  let done = phases.iter().filter(|p| p.status == "done").count();
+
+  let mut columns: Vec<PhaseColumn> = PHASE_STATUSES.iter()
+    .map(|s| PhaseColumn { status: s.to_string(), count: 0, phases: vec![] })
+    .collect();
+
+  for phase in phases.iter().cloned() {
+    let blocked_by = deps.iter()
+      .filter(|(p, dep)| *p == phase.id && status_of.get(dep.as_str()).map_or(false, |s| *s != "done"))
+      .map(|(_, dep)| dep.clone())
+      .collect();
+    let blocks = deps.iter()
+      .filter(|(_, dep)| *dep == phase.id)
+      .map(|(p, _)| p.clone())
+      .collect();
+    let (latest_verdict, latest_verified_at) = match verdicts.remove(&phase.id) {
+      Some((v, at)) => (Some(v), Some(at)),
+      None => (None, None),
+    };
+
+    // Unknown statuses still get a column rather than being dropped
+    let idx = match columns.iter().position(|c| c.status == phase.status) {
+      Some(idx) => idx,
+      None => {
+        columns.push(PhaseColumn { status: phase.status.clone(), count: 0, phases: vec![] });
+        columns.len() - 1
+      }
+    };
+    columns[idx].count += 1;
+    columns[idx].phases.push(PhaseCard { phase, blocked_by, blocks, latest_verdict, latest_verified_at });
+  }
+
+  Ok(PhaseBoard { task_id, total, done, columns })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_phases(app: AppHandle, task_id: String) -> Result<Vec<Phase>, String> {
+  phases::list_phases(&app, &task_id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn create_phase(app: AppHandle, task_id: String, title: String, description: Option<String>, planned_files: Vec<String>) -> Result<Phase, String> {
+  get_task(app.clone(), task_id.clone())?;
+  phases::create_phase(&app, &task_id, &title, description, &planned_files)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_phase(app: AppHandle, phase_id: String, update: PhaseUpdate) -> Result<Phase, String> {
+  phases::update_phase(&app, &phase_id, update)
+}
+
+/// Delete a phase along with its phase-scoped artifacts
+#[tauri::command]
+#[specta::specta]
+pub fn delete_phase(app: AppHandle, phase_id: String) -> Result<(), String> {
+  phases::delete_phase(&app, &phase_id)
+}
+
+/// Break the task's saved plan into phases with the LLM, store them, and save
+/// the list as the task's `phase_list` artifact
+#[tauri::command]
+#[specta::specta]
+pub async fn generate_phases(app: AppHandle, task_id: String) -> Result<PhasesResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; generating phases needs the LLM provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  phases::generate_phases(&app, &settings, &api_key, &task_id).await
+}
+
+/// Review a branch (against `base`), the staged changes, or the working tree
+/// with the repo tools; no plan is needed. Saved as the task's `review_md` artifact.
+#[tauri::command]
+#[specta::specta]
+pub async fn review_changes(app: AppHandle, task_id: String, options: ReviewOptions) -> Result<ReviewResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; reviewing changes needs the LLM provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  review::review_changes(&app, &settings, &api_key, &task_id, &options).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_phase_dependencies(app: AppHandle, phase_id: String, depends_on: Vec<String>) -> Result<(), String> {
+  phases::set_dependencies(&app, &phase_id, &depends_on)
+}
+
+// Task attachment commands
+const MAX_ATTACHMENT_BYTES: u64 = 1_000_000;
+
+pub(crate) fn insert_attachment(conn: &rusqlite::Connection, task_id: &str, name: &str, source: &str, source_path: Option<&str>, content: &str) -> Result<TaskAttachment, String> {
+  let id = new_id();
+  let created_at = now_iso();
+  conn.execute(
+    "INSERT INTO task_attachments (id, task_id, name, source, source_path, content, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    (&id, task_id, name, source, source_path, compression::encode(content).map_err(|e| e.to_string())?, &created_at)
+  ).map_err(|e| e.to_string())?;
+  Ok(TaskAttachment {
+    id,
+    task_id: task_id.to_string(),
+    name: name.to_string(),
+    source: source.to_string(),
+    source_path: source_path.map(|s| s.to_string()),
+    content: content.to_string(),
+    created_at,
+  })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_task_attachments(app: AppHandle, task_id: String) -> Result<Vec<TaskAttachment>, String> {
+  list_attachments(&app, &task_id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn add_task_note(app: AppHandle, task_id: String, name: String, content: String) -> Result<TaskAttachment, String> {
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  insert_attachment(&conn, &task_id, &name, "note", None, &content)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn attach_task_file(app: AppHandle, task_id: String, path: String) -> Result<TaskAttachment, String> {
+  // Store a copy so the attachment survives the original moving or changing
+  let file_path = std::path::Path::new(&path);
+  let size = std::fs::metadata(file_path).map_err(|e| format!("Cannot read file: {}", e))?.len();
+  if size > MAX_ATTACHMENT_BYTES {
+    return Err(format!("File is too large to attach ({} bytes, max {})", size, MAX_ATTACHMENT_BYTES));
+  }
+  let bytes = std::fs::read(file_path).map_err(|e| format!("Cannot read file: {}", e))?;
+  let content = String::from_utf8(bytes).map_err(|_| "Only text files can be attached".to_string())?;
+  let name = file_path.file_name()
+    .map(|n| n.to_string_lossy().to_string())
+    .unwrap_or_else(|| path.clone());
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  insert_attachment(&conn, &task_id, &name, "file", Some(&path), &content)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_task_attachment(app: AppHandle, attachment_id: String) -> Result<(), String> {
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  conn.execute("DELETE FROM task_attachments WHERE id = ?1", [&attachment_id])
+    .map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+// Audio intake
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_audio(
+  app: AppHandle,
+  path: String,
+  task_id: Option<String>,
+  project_id: Option<String>,
+) -> Result<TranscriptionResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; transcription needs the provider to be reachable".into());
+  }
+  let base_url = settings.get("transcription_base_url")
+    .filter(|v| !v.is_empty())
+    .or_else(|| settings.get("base_url"))
+    .cloned()
+    .unwrap_or_default();
+  let config = TranscriptionConfig {
+    base_url,
+    model: settings.get("transcription_model").cloned().unwrap_or_else(|| "whisper-1".into()),
+  };
+  let api_key = settings.get("api_key")
+    .filter(|k| !k.is_empty())
+    .cloned()
+    .or_else(|| std::env::var("SPECTRAIL_API_KEY").ok())
+    .unwrap_or_default();
+
+  let audio_path = std::path::Path::new(&path);
+  let transcript = transcribe_file(&config, &api_key, audio_path).await.map_err(|e| e.to_string())?;
+  if transcript.is_empty() {
+    return Err("Transcription returned no text".into());
+  }
+
+  // Append to an existing task, or start a new one titled from the first line
+  let task_id = match (task_id, project_id) {
+    (Some(task_id), _) => task_id,
+    (None, Some(project_id)) => {
+      let first_line = transcript.lines().next().unwrap_or("Voice memo");
+      let title: String = first_line.chars().take(80).collect();
+      create_task(app.clone(), project_id, title, "plan".into())?.id
+    }
+    (None, None) => return Err("Either task_id or project_id is required".into()),
+  };
+
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "UPDATE tasks SET description = CASE WHEN description IS NULL OR description = '' THEN ?1 ELSE description || char(10) || char(10) || ?1 END,
+     updated_at = ?2 WHERE id = ?3",
+    (&transcript, &now_iso(), &task_id)
+  ).map_err(|e| e.to_string())?;
+
+  let name = format!(
+    "Voice memo: {}",
+    audio_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.clone())
+  );
+  let attachment = insert_attachment(&conn, &task_id, &name, "transcript", Some(&path), &transcript)?;
+  let task = get_task(app, task_id)?;
+
+  Ok(TranscriptionResult { task, transcript, attachment })
 }
 
 // Settings commands
 #[tauri::command]
+#[specta::specta]
 pub fn get_settings(app: AppHandle) -> Result<Vec<SettingsKV>, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let mut stmt = conn.prepare(
@@ -283,7 +804,17 @@ pub fn get_settings(app: AppHandle) -> Result<Vec<SettingsKV>, String> {
   Ok(out)
 }
 
+/// The settings with a fixed type, and any stored values that were invalid
+/// and replaced by their default
+#[tauri::command]
+#[specta::specta]
+pub async fn get_settings_typed(app: AppHandle) -> Result<TypedSettings, String> {
+  let settings = cached_settings(&app).await?;
+  Ok(typed_settings(&settings))
+}
+
 #[tauri::command]
+#[specta::specta]
 pub fn get_setting(app: AppHandle, key: String) -> Result<Option<String>, String> {
   let conn = db::connect(&app).map_err(|e| e.to_string())?;
   let result: Option<String> = conn.query_row(
@@ -294,83 +825,1039 @@ pub fn get_setting(app: AppHandle, key: String) -> Result<Option<String>, String
   Ok(result)
 }
 
+/// Write the global settings to a file for another machine, without credentials
 #[tauri::command]
-pub fn set_setting(app: AppHandle, key: String, value: String) -> Result<(), String> {
-  let conn = db::connect(&app).map_err(|e| e.to_string())?;
-  let updated_at = now_iso();
-  conn.execute(
-    "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
-     ON CONFLICT(key) DO UPDATE SET value=excluded.value, updated_at=excluded.updated_at",
-    (&key, &value, &updated_at)
-  ).map_err(|e| e.to_string())?;
-  Ok(())
+#[specta::specta]
+pub fn export_settings(app: AppHandle, path: String) -> Result<SettingsExport, String> {
+  settings::export_settings(&app, &path)
 }
 
+/// Store the settings in a file written by export_settings; nothing is stored
+/// if any value is invalid. Returns how many were imported.
 #[tauri::command]
-pub fn set_settings(app: AppHandle, pairs: Vec<SettingInput>) -> Result<(), String> {
-  let mut conn = db::connect(&app).map_err(|e| e.to_string())?;
+#[specta::specta]
+pub fn import_settings(app: AppHandle, path: String) -> Result<usize, String> {
+  let pairs: Vec<SettingInput> = settings::read_settings_file(&path)?.into_iter()
+    .map(|(key, value)| SettingInput { key, value })
+    .collect();
+  let imported = pairs.len();
+  set_settings(app, pairs)?;
+  Ok(imported)
+}
+
+/// Settings that have their own command because changing them does more than
+/// store a value
+fn check_managed_setting(key: &str) -> Result<(), String> {
+  match key {
+    encryption::SETTING => Err("Use set_database_encryption to change database encryption".into()),
+    sync::REMOTE_SETTING => Err("Use configure_sync to change the sync remote".into()),
+    _ => Ok(()),
+  }
+}
+
+fn save_settings<'a>(conn: &mut rusqlite::Connection, pairs: impl Iterator<Item = (&'a str, &'a str)>) -> Result<(), String> {
   let tx = conn.transaction().map_err(|e| e.to_string())?;
   let updated_at = now_iso();
-  
-  for pair in pairs {
+  for (key, value) in pairs {
     tx.execute(
       "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
        ON CONFLICT(key) DO UPDATE SET value=excluded.value, updated_at=excluded.updated_at",
-      (&pair.key, &pair.value, &updated_at)
+      (key, value, &updated_at)
     ).map_err(|e| e.to_string())?;
   }
-  
-  tx.commit().map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())
+}
+
+/// Put back the saved shortcut after registering a new one that then wasn't saved
+fn restore_shortcut(app: &AppHandle) {
+  if let Err(e) = quick_capture::register_shortcut(app) {
+    tracing::warn!("failed to restore quick capture shortcut: {}", e);
+  }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_setting(app: AppHandle, key: String, value: String) -> Result<(), String> {
+  check_managed_setting(&key)?;
+  validate_setting(&key, &value)?;
+  let mut conn = db::connect(&app).map_err(|e| e.to_string())?;
+  // The shortcut is saved only once it's registered
+  let shortcut_changed = key == quick_capture::SHORTCUT_SETTING;
+  if shortcut_changed {
+    quick_capture::change_shortcut(&app, &value)?;
+  }
+  if let Err(e) = save_settings(&mut conn, std::iter::once((key.as_str(), value.as_str()))) {
+    if shortcut_changed {
+      restore_shortcut(&app);
+    }
+    return Err(e);
+  }
+  invalidate_settings(&app);
+  if key == companion::ENABLED_SETTING || key == companion::PORT_SETTING {
+    restart_companion(&app);
+  }
+  if key == offline::MANUAL_SETTING {
+    offline::on_mode_changed(&app);
+  }
   Ok(())
 }
 
-// Repo tools commands
-use crate::repo_tools::{list_tool_calls, dispatch_repo_tool, repo_tool_schemas};
+#[tauri::command]
+#[specta::specta]
+pub fn set_settings(app: AppHandle, pairs: Vec<SettingInput>) -> Result<(), String> {
+  let mut conn = db::connect(&app).map_err(|e| e.to_string())?;
+  for pair in &pairs {
+    check_managed_setting(&pair.key)?;
+    validate_setting(&pair.key, &pair.value)?;
+  }
+  // The shortcut is saved only once it's registered
+  let shortcut = pairs.iter().rev().find(|p| p.key == quick_capture::SHORTCUT_SETTING);
+  if let Some(shortcut) = shortcut {
+    quick_capture::change_shortcut(&app, &shortcut.value)?;
+  }
+  let saved = save_settings(&mut conn, pairs.iter().map(|p| (p.key.as_str(), p.value.as_str())));
+  if let Err(e) = saved {
+    if shortcut.is_some() {
+      restore_shortcut(&app);
+    }
+    return Err(e);
+  }
+  invalidate_settings(&app);
+  let companion_changed = pairs.iter().any(|p| p.key == companion::ENABLED_SETTING || p.key == companion::PORT_SETTING);
+  let offline_changed = pairs.iter().any(|p| p.key == offline::MANUAL_SETTING);
+  if companion_changed {
+    restart_companion(&app);
+  }
+  if offline_changed {
+    offline::on_mode_changed(&app);
+  }
+  Ok(())
+}
 
 #[tauri::command]
-pub fn list_tool_calls_cmd(app: AppHandle, run_id: String) -> Result<Vec<ToolCallRow>, String> {
-  list_tool_calls(&app, &run_id)
+#[specta::specta]
+pub fn get_database_encryption(app: AppHandle) -> Result<EncryptionStatus, String> {
+  encryption::status(&app)
 }
 
+/// Encrypt or decrypt the stored excerpts in place; can take a while on large databases
 #[tauri::command]
-pub async fn execute_repo_tool(
-  app: AppHandle,
-  run_id: String,
-  project_id: String,
-  name: String,
-  args: serde_json::Value,
-) -> Result<serde_json::Value, String> {
-  // Look up repo_path from DB
-  let project = get_project(app.clone(), project_id)?;
-  let repo_path = std::path::Path::new(&project.repo_path);
-  
-  // Dispatch tool
-  let result = dispatch_repo_tool(&name, &args, repo_path, &app, &run_id).await;
-  
-  result
+#[specta::specta]
+pub async fn set_database_encryption(app: AppHandle, enabled: bool) -> Result<EncryptionStatus, String> {
+  tauri::async_runtime::spawn_blocking(move || encryption::set_enabled(&app, enabled))
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Startup integrity check result and the backups available to restore
 #[tauri::command]
-pub fn get_repo_tool_schemas() -> Vec<serde_json::Value> {
-  repo_tool_schemas()
+#[specta::specta]
+pub fn get_database_health(app: AppHandle) -> Result<DatabaseHealth, String> {
+  backups::health(&app)
 }
 
-// Plan workflow command
-use crate::workflows::plan::{generate_plan, PlanResult};
-use crate::workflows::verify::{verify_task, VerifyOptions, VerifyResult};
+#[tauri::command]
+#[specta::specta]
+pub fn list_backups(app: AppHandle) -> Result<Vec<Backup>, String> {
+  backups::list_backups(&app)
+}
 
+/// Replace the database with a backup; the current one is backed up first
 #[tauri::command]
-pub async fn generate_plan_command(
-  app: AppHandle,
-  project_id: String,
-  task_id: String,
-) -> Result<PlanResult, String> {
-  generate_plan(app, project_id, task_id)
+#[specta::specta]
+pub async fn restore_backup(app: AppHandle, backup_id: String) -> Result<Backup, String> {
+  tauri::async_runtime::spawn_blocking(move || backups::restore_backup(&app, &backup_id))
     .await
-    .map_err(|e| format!("[{}] {}", e.code, e.message))
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_sync_status(app: AppHandle) -> Result<SyncStatus, String> {
+  sync::status(&app)
+}
+
+/// Set up sync with a git or S3 remote, or turn it off when `remote` is null
+#[tauri::command]
+#[specta::specta]
+pub fn configure_sync(app: AppHandle, remote: Option<SyncRemote>, credentials: Option<S3Credentials>) -> Result<SyncStatus, String> {
+  sync::configure(&app, remote, credentials)
+}
+
+/// Pull changes from other devices, then push this one's
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_now(app: AppHandle) -> Result<SyncReport, String> {
+  sync::sync_now(&app).await
+}
+
+/// Apply companion API setting changes in the background; binding the port is async
+fn restart_companion(app: &AppHandle) {
+  let handle = app.clone();
+  tauri::async_runtime::spawn(async move {
+    if let Err(e) = companion::restart(&handle).await {
+      tracing::warn!("failed to restart companion API: {}", e);
+    }
+  });
+}
+
+// Diagnostics
+#[tauri::command]
+#[specta::specta]
+pub fn get_recent_logs(app: AppHandle, level: Option<String>, limit: Option<usize>) -> Result<Vec<LogEntry>, String> {
+  read_recent_logs(&app, level.as_deref().unwrap_or("info"), limit.unwrap_or(200))
+}
+
+/// Bundle logs, system info, redacted settings, and the last failed run into a zip
+#[tauri::command]
+#[specta::specta]
+pub fn export_diagnostics(app: AppHandle, path: Option<String>) -> Result<DiagnosticsBundle, String> {
+  crate::diagnostics::export_diagnostics(&app, path.as_deref())
+}
+
+// Onboarding wizard: each command returns step results for the wizard to render
+#[tauri::command]
+#[specta::specta]
+pub async fn onboarding_detect_runtimes() -> Result<Vec<StepResult>, String> {
+  Ok(onboarding::detect_runtimes().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn onboarding_test_llm(app: AppHandle) -> Result<StepResult, String> {
+  onboarding::test_llm_endpoint(&app).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn onboarding_create_project(app: AppHandle, repo_path: String, name: Option<String>) -> Result<StepResult, String> {
+  onboarding::create_sample_project(&app, &repo_path, name)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn onboarding_smoke_plan(app: AppHandle, project_id: String) -> Result<StepResult, String> {
+  onboarding::run_smoke_plan(&app, &project_id).await
+}
+
+// App updates
+#[tauri::command]
+#[specta::specta]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+  updater::check_for_updates(&app).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+  updater::install_update(&app).await
+}
+
+// Demo mode
+#[tauri::command]
+#[specta::specta]
+pub fn seed_demo_data(app: AppHandle) -> Result<DemoSeedResult, String> {
+  demo::seed_demo_data(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn clear_demo_data(app: AppHandle) -> Result<usize, String> {
+  demo::clear_demo_data(&app)
+}
+
+// Multi-window commands
+// Async so window creation doesn't deadlock the main thread on Windows
+#[tauri::command]
+#[specta::specta]
+pub async fn open_task_window(app: AppHandle, task_id: String) -> Result<String, String> {
+  windows::open_task_window(&app, &task_id)
+}
+
+/// Scope the calling window's run events to a project and/or task
+#[tauri::command]
+#[specta::specta]
+pub fn subscribe_window(
+  app: AppHandle,
+  window: tauri::Window,
+  project_id: Option<String>,
+  task_id: Option<String>,
+) -> Result<(), String> {
+  windows::subscribe(&app, window.label(), WindowScope { project_id, task_id });
+  Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn unsubscribe_window(app: AppHandle, window: tauri::Window) -> Result<(), String> {
+  windows::unsubscribe(&app, window.label());
+  Ok(())
+}
+
+// Tray quick actions, also callable from the UI
+#[tauri::command]
+#[specta::specta]
+pub fn verify_current_task(app: AppHandle) -> Result<String, String> {
+  tray::verify_current_task(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn open_last_project(app: AppHandle) -> Result<String, String> {
+  tray::open_last_project(&app)
+}
+
+// Companion API (VS Code extension)
+#[tauri::command]
+#[specta::specta]
+pub fn get_companion_status(app: AppHandle) -> Result<CompanionStatus, String> {
+  companion::status(&app)
+}
+
+/// Remove a paired extension; its token stops working immediately
+#[tauri::command]
+#[specta::specta]
+pub fn revoke_companion_client(app: AppHandle, client_id: String) -> Result<(), String> {
+  companion::revoke_client(&app, &client_id)
+}
+
+/// Time the task was open and spent in runs, with the per-session activity log
+#[tauri::command]
+#[specta::specta]
+pub fn get_task_time_summary(app: AppHandle, task_id: String) -> Result<TaskTimeSummary, String> {
+  sessions::task_time_summary(&app, &task_id)
+}
+
+// Quick capture (opened by the global shortcut)
+#[tauri::command]
+#[specta::specta]
+pub fn quick_capture(app: AppHandle, title: String, include_clipboard: bool) -> Result<CaptureResult, String> {
+  quick_capture::capture_task(&app, &title, include_clipboard)
+}
+
+// Project settings commands (overrides of global settings)
+#[tauri::command]
+#[specta::specta]
+pub fn get_project_settings(app: AppHandle, project_id: String) -> Result<Vec<SettingsKV>, String> {
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(
+    "SELECT key, value, updated_at FROM project_settings WHERE project_id = ?1 ORDER BY key"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([&project_id], |r| {
+    Ok(SettingsKV {
+      key: r.get(0)?,
+      value: r.get(1)?,
+      updated_at: r.get(2)?,
+    })
+  }).map_err(|e| e.to_string())?;
+
+  let mut out = vec![];
+  for row in rows {
+    out.push(row.map_err(|e| e.to_string())?);
+  }
+  Ok(out)
+}
+
+/// Check a project or workspace override before it's stored
+fn validate_setting_override(key: &str, value: Option<&str>) -> Result<(), String> {
+  if let Some(value) = value {
+    validate_setting(key, value)?;
+  }
+  if let (Some(code), "output_language") = (value, key) {
+    if language_name(code).is_none() {
+      let supported: Vec<&str> = SUPPORTED_LANGUAGES.iter().map(|(c, _)| *c).collect();
+      return Err(format!("Unsupported output language '{}' (supported: {})", code, supported.join(", ")));
+    }
+  }
+  if let (Some(strategy), "planning_strategy") = (value, key) {
+    if PlanningStrategy::parse(strategy).is_none() {
+      return Err(format!("Unknown planning strategy '{}' (expected explore or retrieval)", strategy));
+    }
+  }
+  if let (Some(method), "baseline_method") = (value, key) {
+    if BaselineMethod::parse(method).is_none() {
+      return Err(format!("Unknown baseline method '{}' (expected worktree or stash)", method));
+    }
+  }
+  if let (Some(backend), "command_backend") = (value, key) {
+    if Backend::parse(backend).is_none() {
+      return Err(format!("Unknown command backend '{}' (expected host, docker, or podman)", backend));
+    }
+  }
+  if let (Some(mount), "container_mount") = (value, key) {
+    if MountMode::parse(mount).is_none() {
+      return Err(format!("Unknown container mount '{}' (expected readonly or copy)", mount));
+    }
+  }
+  if let (Some(mb), "command_memory_mb") = (value, key) {
+    if !mb.is_empty() && !mb.parse::<u64>().is_ok_and(|mb| mb > 0) {
+      return Err(format!("Invalid memory limit '{}' (expected megabytes, or empty for none)", mb));
+    }
+  }
+  if let (Some(cpus), "command_cpus") = (value, key) {
+    if !cpus.is_empty() && !cpus.parse::<f64>().is_ok_and(|c| c > 0.0) {
+      return Err(format!("Invalid CPU limit '{}' (expected a number of CPUs, or empty for none)", cpus));
+    }
+  }
+  if let (Some(json), "custom_commands") = (value, key) {
+    if !json.trim().is_empty() {
+      parse_custom_commands(json)?;
+    }
+  }
+  if let (Some(json), "context_ignore") = (value, key) {
+    parse_globs(json)?;
+  }
+  if let (Some(profile), "tool_schema_profile") = (value, key) {
+    if profile != "auto" && SchemaProfile::parse(profile).is_none() {
+      return Err(format!("Unknown tool schema profile '{}' (expected auto, standard, strict, flat, or compact)", profile));
+    }
+  }
+  if let (Some(json), "script_fallbacks") = (value, key) {
+    if !json.trim().is_empty() {
+      parse_script_fallbacks(json)?;
+    }
+  }
+  if let (Some(json), "lifecycle_hooks") = (value, key) {
+    if !json.trim().is_empty() {
+      parse_lifecycle_hooks(json)?;
+    }
+  }
+  if let (Some(pct), "bench_regression_pct") = (value, key) {
+    if !pct.parse::<f64>().is_ok_and(|p| p >= 0.0) {
+      return Err(format!("Invalid benchmark regression threshold '{}' (expected a non-negative percent)", pct));
+    }
+  }
+  Ok(())
+}
+
+/// Context ignore globs for the project, from its settings and the repo's .spectrail.toml
+#[tauri::command]
+#[specta::specta]
+pub async fn get_context_ignore(app: AppHandle, project_id: String) -> Result<ContextIgnoreConfig, String> {
+  let project = get_project(app.clone(), project_id.clone())?;
+  let globs = context_ignore::project_globs(&app, &project_id).await?;
+  Ok(context_ignore::load_config(globs, std::path::Path::new(&project.repo_path)).await)
+}
+
+/// Replace the project's context ignore globs, e.g. `**/*.min.js` or `fixtures/**`
+#[tauri::command]
+#[specta::specta]
+pub fn set_context_ignore(app: AppHandle, project_id: String, globs: Vec<String>) -> Result<(), String> {
+  let globs: Vec<String> = globs.iter().map(|g| g.trim().to_string()).filter(|g| !g.is_empty()).collect();
+  let json = serde_json::to_string(&globs).map_err(|e| e.to_string())?;
+  set_project_setting(app, project_id, "context_ignore".to_string(), Some(json))
+}
+
+/// Run `script` when the project's standard script for `kind` is missing, e.g. one
+/// run_command suggested; `None` stops falling back for that kind
+#[tauri::command]
+#[specta::specta]
+pub fn approve_script_fallback(app: AppHandle, project_id: String, kind: String, script: Option<String>) -> Result<(), String> {
+  let current = db::get_project_setting(&app, &project_id, "script_fallbacks").map_err(|e| e.to_string())?
+    .filter(|json| !json.trim().is_empty());
+  let mut fallbacks = match current {
+    Some(json) => parse_script_fallbacks(&json)?,
+    None => Default::default(),
+  };
+  match script.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()) {
+    Some(script) => fallbacks.insert(kind, script),
+    None => fallbacks.remove(&kind),
+  };
+  let json = serde_json::to_string(&fallbacks).map_err(|e| e.to_string())?;
+  set_project_setting(app, project_id, "script_fallbacks".to_string(), Some(json))
+}
+
+/// Set a project override, or clear it (fall back to the workspace or global setting) with `None`
+#[tauri::command]
+#[specta::specta]
+pub fn set_project_setting(app: AppHandle, project_id: String, key: String, value: Option<String>) -> Result<(), String> {
+  validate_setting_override(&key, value.as_deref())?;
+
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  match value {
+    Some(value) => {
+      conn.execute(
+        "INSERT INTO project_settings (project_id, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(project_id, key) DO UPDATE SET value=excluded.value, updated_at=excluded.updated_at",
+        (&project_id, &key, &value, now_iso())
+      ).map_err(|e| e.to_string())?;
+    }
+    None => {
+      conn.execute(
+        "DELETE FROM project_settings WHERE project_id = ?1 AND key = ?2",
+        (&project_id, &key)
+      ).map_err(|e| e.to_string())?;
+    }
+  }
+  Ok(())
+}
+
+// Embeddings index used by the retrieval planning strategy
+/// Build or refresh the project's embeddings index; unchanged files are skipped
+#[tauri::command]
+#[specta::specta]
+pub async fn index_repository(app: AppHandle, project_id: String) -> Result<IndexReport, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; indexing needs the embeddings provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  retrieval::index_project(&app, &settings, &api_key, &project_id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_index_status(app: AppHandle, project_id: String) -> Result<IndexStatus, String> {
+  retrieval::index_status(&app, &project_id)
+}
+
+// Workspaces (groups of related projects sharing setting defaults)
+#[tauri::command]
+#[specta::specta]
+pub fn list_workspaces(app: AppHandle) -> Result<Vec<Workspace>, String> {
+  workspaces::list_workspaces(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn create_workspace(app: AppHandle, name: String) -> Result<Workspace, String> {
+  workspaces::create_workspace(&app, &name)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn rename_workspace(app: AppHandle, workspace_id: String, name: String) -> Result<Workspace, String> {
+  workspaces::rename_workspace(&app, &workspace_id, &name)
+}
+
+/// Delete a workspace; its projects are kept and no longer grouped
+#[tauri::command]
+#[specta::specta]
+pub fn delete_workspace(app: AppHandle, workspace_id: String) -> Result<(), String> {
+  workspaces::delete_workspace(&app, &workspace_id)
+}
+
+/// Move a project into a workspace, or out of it with `None`
+#[tauri::command]
+#[specta::specta]
+pub fn set_project_workspace(app: AppHandle, project_id: String, workspace_id: Option<String>) -> Result<(), String> {
+  workspaces::set_project_workspace(&app, &project_id, workspace_id.as_deref())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_workspace_settings(app: AppHandle, workspace_id: String) -> Result<Vec<SettingsKV>, String> {
+  workspaces::get_workspace_settings(&app, &workspace_id)
+}
+
+/// Set a default for the workspace's projects, or clear it with `None`
+#[tauri::command]
+#[specta::specta]
+pub fn set_workspace_setting(app: AppHandle, workspace_id: String, key: String, value: Option<String>) -> Result<(), String> {
+  validate_setting_override(&key, value.as_deref())?;
+  workspaces::set_workspace_setting(&app, &workspace_id, &key, value.as_deref())
+}
+
+/// Task counts, recent tasks and recent runs across the workspace's projects
+#[tauri::command]
+#[specta::specta]
+pub fn get_workspace_dashboard(app: AppHandle, workspace_id: String) -> Result<WorkspaceDashboard, String> {
+  workspaces::get_workspace_dashboard(&app, &workspace_id)
+}
+
+// Offline mode (offline_mode setting, or detected when the LLM provider is unreachable)
+#[tauri::command]
+#[specta::specta]
+pub async fn get_offline_status(app: AppHandle) -> Result<OfflineStatus, String> {
+  offline::status(&app).await
+}
+
+/// Drop a run queued while offline
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_queued_run(app: AppHandle, id: String) -> Result<(), String> {
+  offline::cancel_queued_run(&app, &id)
+}
+
+// Usage statistics (telemetry_enabled / telemetry_endpoint settings)
+#[tauri::command]
+#[specta::specta]
+pub async fn get_telemetry_preview(app: AppHandle) -> Result<TelemetryPreview, String> {
+  telemetry::preview(&app).await
+}
+
+/// Send the report the user previewed; `digest` is the preview's
+#[tauri::command]
+#[specta::specta]
+pub async fn upload_telemetry(app: AppHandle, digest: String) -> Result<TelemetryUpload, String> {
+  telemetry::upload(&app, &digest).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn clear_telemetry(app: AppHandle) -> Result<(), String> {
+  telemetry::clear(&app)
+}
+
+// Feedback (feedback_endpoint / feedback_issue_repo settings)
+/// Send feedback or a bug report, with the redacted diagnostics bundle if asked
+#[tauri::command]
+#[specta::specta]
+pub async fn send_app_feedback(
+  app: AppHandle,
+  kind: FeedbackKind,
+  text: String,
+  include_diagnostics: bool,
+) -> Result<FeedbackSubmission, String> {
+  support::submit_feedback(&app, kind, &text, include_diagnostics).await
+}
+
+// Monthly budgets (budget_monthly_tokens / budget_monthly_usd project settings)
+#[tauri::command]
+#[specta::specta]
+pub async fn get_budget_status(app: AppHandle, project_id: String) -> Result<BudgetStatus, String> {
+  let settings = cached_settings(&app).await?;
+  budget::budget_status(&app, &settings, &project_id)
+}
+
+/// Answer BUDGET_CONFIRMATION_REQUIRED: allow runs over budget for the rest of the month
+#[tauri::command]
+#[specta::specta]
+pub fn confirm_budget_overrun(app: AppHandle, project_id: String) -> Result<(), String> {
+  budget::confirm_budget_overrun(&app, &project_id)
+}
+
+// Issue tracker integrations (Jira, Linear)
+#[tauri::command]
+#[specta::specta]
+pub fn get_integration_status(app: AppHandle, project_id: String, provider: String) -> Result<IntegrationStatus, String> {
+  integrations::status(&app, &project_id, Provider::parse(&provider)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_integration_config(app: AppHandle, project_id: String, provider: String, config: IntegrationConfig) -> Result<(), String> {
+  integrations::save_config(&app, &project_id, Provider::parse(&provider)?, &config)
+}
+
+/// Save the API token to the OS keychain; an empty token removes it
+#[tauri::command]
+#[specta::specta]
+pub fn set_integration_token(provider: String, token: String) -> Result<(), String> {
+  let provider = Provider::parse(&provider)?;
+  if token.trim().is_empty() {
+    secrets::delete_token(provider.as_str())
+  } else {
+    secrets::set_token(provider.as_str(), token.trim())
+  }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn import_issue(app: AppHandle, project_id: String, provider: String, key: String) -> Result<ImportedIssue, String> {
+  integrations::import_issue(&app, &project_id, Provider::parse(&provider)?, &key).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_task_links(app: AppHandle, task_id: String) -> Result<Vec<TaskLink>, String> {
+  integrations::task_links(&app, &task_id)
+}
+
+/// Comment the plan summary and verification verdict on the linked issues
+#[tauri::command]
+#[specta::specta]
+pub async fn push_issue_comment(app: AppHandle, task_id: String) -> Result<Vec<TaskLink>, String> {
+  integrations::push_comment(&app, &task_id).await
+}
+
+// Repo tools commands
+use crate::repo_tools::{list_tool_calls, dispatch_repo_tool, repo_tool_schemas, list_tool_stats, analyze_run_tool_usage};
+use crate::repo_tools::stats::{RunToolAnalysis, DEFAULT_LOOP_THRESHOLD};
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_tool_calls_cmd(app: AppHandle, run_id: String) -> Result<Vec<ToolCallRow>, String> {
+  list_tool_calls(&app, &run_id)
+}
+
+/// Messages and tool calls of a run as one ordered timeline
+#[tauri::command]
+#[specta::specta]
+pub fn get_run_transcript(app: AppHandle, run_id: String) -> Result<RunTranscript, String> {
+  transcript::get_run_transcript(&app, &run_id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn execute_repo_tool(
+  app: AppHandle,
+  run_id: String,
+  project_id: String,
+  name: String,
+  args: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+  // Look up repo_path from DB
+  let project = get_project(app.clone(), project_id)?;
+  let repo_path = std::path::Path::new(&project.repo_path);
+  
+  // Dispatch tool
+  let result = dispatch_repo_tool(&name, &args, repo_path, &app, &run_id).await;
+  
+  result
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_repo_tool_schemas() -> Vec<serde_json::Value> {
+  repo_tool_schemas()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_tool_stats(app: AppHandle) -> Result<Vec<ToolStat>, String> {
+  list_tool_stats(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn analyze_run_tools(app: AppHandle, run_id: String, threshold: Option<usize>) -> Result<RunToolAnalysis, String> {
+  let threshold = match threshold {
+    Some(t) => t,
+    None => get_setting(app.clone(), "tool_loop_threshold".into())?
+      .and_then(|v| v.parse().ok())
+      .unwrap_or(DEFAULT_LOOP_THRESHOLD),
+  };
+  analyze_run_tool_usage(&app, &run_id, threshold)
+}
+
+// Prompt template preview
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_prompt(app: AppHandle, workflow: String, task_id: String) -> Result<PromptPreview, String> {
+  let task = get_task(app.clone(), task_id)?;
+  let project = get_project(app.clone(), task.project_id.clone())?;
+  let settings = cached_settings(&app).await?;
+  let key = template_setting_key(&workflow)?;
+  let strict = settings.get("prompt_template_strict").map_or(true, |v| v == "1");
+
+  // Unknown variables are reported rather than failing so the editor can highlight them
+  if let Some(template) = settings.get(key).filter(|t| !t.trim().is_empty()) {
+    let vars = template_vars(&app, &task, &project, template)?;
+    let rendered = render_template(template, &vars);
+    return Ok(PromptPreview {
+      workflow,
+      template: Some(template.clone()),
+      rendered: rendered.text,
+      used_variables: rendered.used_variables,
+      unknown_variables: rendered.unknown_variables,
+      strict,
+    });
+  }
+
+  let rendered = if workflow == "plan" {
+    let attachments = attachments_context(&app, &task.id)?;
+    build_user_prompt(&task, &project, attachments.as_deref())
+  } else {
+    default_header(&task)
+  };
+  Ok(PromptPreview { workflow, template: None, rendered, used_variables: vec![], unknown_variables: vec![], strict })
+}
+
+/// Files and sections a plan or verify run of the task would gather, with the task's overrides
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_context(app: AppHandle, task_id: String, workflow: String) -> Result<ContextPreview, String> {
+  let task = get_task(app.clone(), task_id)?;
+  let project = get_project(app.clone(), task.project_id.clone())?;
+  let settings = cached_settings(&app).await?;
+  context::preview_context(&app, &settings, &task, &project, &workflow).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_context_overrides(app: AppHandle, task_id: String) -> Result<Vec<ContextOverride>, String> {
+  context::list_context_overrides(&app, &task_id)
+}
+
+/// Pin a file into the task's context or exclude it (`pin`|`exclude`); `None` clears it
+#[tauri::command]
+#[specta::specta]
+pub fn set_context_override(app: AppHandle, task_id: String, path: String, action: Option<String>) -> Result<Vec<ContextOverride>, String> {
+  get_task(app.clone(), task_id.clone())?;
+  context::set_context_override(&app, &task_id, &path, action.as_deref())
+}
+
+// Reviewer walkthroughs of a task's diff
+use crate::workflows::explain::{self, OverviewResult, WalkthroughResult};
+use crate::workflows::glossary::{self, GlossaryResult};
+
+/// Explain the task's current diff file by file, in reading order, and save
+/// it as the task's `diff_walkthrough` artifact
+#[tauri::command]
+#[specta::specta]
+pub async fn explain_diff(app: AppHandle, task_id: String) -> Result<WalkthroughResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; explaining a diff needs the LLM provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  explain::explain_diff(&app, &settings, &api_key, &task_id).await
+}
+
+/// Explain the project's architecture for a newcomer, optionally going deeper on
+/// `focus`, and save it as the project's `repo_overview`
+#[tauri::command]
+#[specta::specta]
+pub async fn explain_repo(app: AppHandle, project_id: String, focus: Option<String>) -> Result<OverviewResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; explaining a repository needs the LLM provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  explain::explain_repo(&app, &settings, &api_key, &project_id, focus.as_deref()).await
+}
+
+/// Extract the project's domain terms into its `glossary`, which plan and verify
+/// prompts always include
+#[tauri::command]
+#[specta::specta]
+pub async fn extract_glossary(app: AppHandle, project_id: String) -> Result<GlossaryResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; extracting a glossary needs the LLM provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  glossary::extract_glossary(&app, &settings, &api_key, &project_id).await
+}
+
+// User-defined workflows, saved for a project or in its repo's .spectrail.toml
+use crate::workflows::custom::{self, CustomWorkflow, CustomWorkflowList, CustomWorkflowResult};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_custom_workflows(app: AppHandle, project_id: String) -> Result<CustomWorkflowList, String> {
+  custom::list_workflows(&app, &project_id).await
+}
+
+/// Save a workflow from its JSON or TOML `definition`, replacing the project's
+/// saved workflow of the same name
+#[tauri::command]
+#[specta::specta]
+pub fn save_custom_workflow(app: AppHandle, project_id: String, name: String, definition: String) -> Result<CustomWorkflow, String> {
+  get_project(app.clone(), project_id.clone())?;
+  custom::save_workflow(&app, &project_id, name.trim(), &definition)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_custom_workflow(app: AppHandle, project_id: String, name: String) -> Result<(), String> {
+  custom::delete_workflow(&app, &project_id, &name)
+}
+
+/// Run the workflow `name` from the task's project and save its answer as
+/// the artifact kind it defines
+#[tauri::command]
+#[specta::specta]
+pub async fn run_custom_workflow(app: AppHandle, name: String, task_id: String) -> Result<CustomWorkflowResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; running a workflow needs the LLM provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  custom::run_custom_workflow(&app, &settings, &api_key, &name, &task_id).await
+}
+
+/// Every event the backend emits, with a JSON schema of its payload, so the
+/// frontend's listener types can be generated rather than kept in sync by hand
+#[tauri::command]
+#[specta::specta]
+pub fn list_event_schemas() -> Vec<EventSchema> {
+  events::event_schemas()
+}
+
+/// Scripts in the hooks directory that a project's lifecycle hooks can run
+#[tauri::command]
+#[specta::specta]
+pub fn list_hook_scripts(app: AppHandle) -> Result<HookScripts, String> {
+  hooks::list_hook_scripts(&app)
+}
+
+/// Re-read the tool plugin manifests, e.g. after installing one
+#[tauri::command]
+#[specta::specta]
+pub fn reload_tool_plugins(app: AppHandle) -> Result<PluginList, String> {
+  plugins::load_plugins(&app)
+}
+
+// Project-level artifacts
+use crate::project_artifacts;
+use crate::workflows::drift::{self, DriftResult};
+
+/// The project's artifacts (repo map, architecture overview, release notes,
+/// conventions), newest first, optionally of one kind
+#[tauri::command]
+#[specta::specta]
+pub fn list_project_artifacts(app: AppHandle, project_id: String, kind: Option<String>) -> Result<Vec<Artifact>, String> {
+  project_artifacts::list_project_artifacts(&app, &project_id, kind.as_deref())
+}
+
+/// Regenerate the project's repo map now and summarize how its structure drifted
+/// since the last one
+#[tauri::command]
+#[specta::specta]
+pub async fn refresh_repo_map(app: AppHandle, project_id: String) -> Result<DriftResult, String> {
+  drift::refresh_repo_map(&app, &project_id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn upsert_project_artifact(app: AppHandle, project_id: String, kind: String, content: String) -> Result<Artifact, String> {
+  project_artifacts::upsert_project_artifact(&app, &project_id, &kind, &content)
+}
+
+/// Pinned project artifacts are included in plan and verify prompts; conventions, the glossary and the structure drift summary always are
+#[tauri::command]
+#[specta::specta]
+pub fn set_project_artifact_pinned(app: AppHandle, artifact_id: String, pinned: bool) -> Result<Artifact, String> {
+  project_artifacts::set_project_artifact_pinned(&app, &artifact_id, pinned)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_project_artifact(app: AppHandle, artifact_id: String) -> Result<(), String> {
+  project_artifacts::delete_project_artifact(&app, &artifact_id)
+}
+
+// Feedback on plans and verification reports
+use crate::feedback::{self, Feedback, FeedbackSummary};
+
+/// Rate a plan or verification report artifact `up` or `down`
+#[tauri::command]
+#[specta::specta]
+pub fn submit_feedback(app: AppHandle, artifact_id: String, rating: String, comment: Option<String>) -> Result<Feedback, String> {
+  feedback::submit_feedback(&app, &artifact_id, &rating, comment.as_deref())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_feedback(app: AppHandle, task_id: String) -> Result<Vec<Feedback>, String> {
+  feedback::list_feedback(&app, &task_id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_feedback(app: AppHandle, feedback_id: String) -> Result<(), String> {
+  feedback::delete_feedback(&app, &feedback_id)
+}
+
+/// Approval rates for one project, or across all of them without `project_id`
+#[tauri::command]
+#[specta::specta]
+pub fn get_feedback_summary(app: AppHandle, project_id: Option<String>) -> Result<FeedbackSummary, String> {
+  feedback::feedback_summary(&app, project_id.as_deref())
+}
+
+// CI results ingestion
+use crate::workflows::ci::{self, CiResults};
+
+/// Parse JUnit XML or a GitHub Actions job log (file path or URL) for the
+/// task's next verification with `use_ci_results`
+#[tauri::command]
+#[specta::specta]
+pub async fn ingest_ci_results(app: AppHandle, task_id: String, path_or_url: String) -> Result<CiResults, String> {
+  ci::ingest_ci_results(&app, &task_id, &path_or_url).await
+}
+
+/// Run the task's checks (tests, lint and build by default) on the pre-change
+/// state so verify reports what the change made worse rather than absolute counts
+#[tauri::command]
+#[specta::specta]
+pub async fn capture_baseline(app: AppHandle, task_id: String, kinds: Option<Vec<String>>) -> Result<Baseline, String> {
+  let settings = cached_settings(&app).await?;
+  baseline::capture_baseline(&app, &settings, &task_id, kinds).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_baseline(app: AppHandle, task_id: String) -> Result<Option<Baseline>, String> {
+  baseline::latest_baseline(&app, &task_id)
+}
+
+/// Recorded pass/fail history of the project's failing tests, most flaky first
+#[tauri::command]
+#[specta::specta]
+pub fn list_test_stats(app: AppHandle, project_id: String, flaky_only: Option<bool>) -> Result<Vec<TestHistory>, String> {
+  flaky::list_test_stats(&app, &project_id, flaky_only.unwrap_or(false))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn reset_test_stats(app: AppHandle, project_id: String) -> Result<(), String> {
+  flaky::reset_test_stats(&app, &project_id)
+}
+
+/// Emergency stop: kill every command run_command started, with their child
+/// processes and containers
+#[tauri::command]
+#[specta::specta]
+pub fn kill_all_running_commands(app: AppHandle) -> Vec<RunningCommand> {
+  processes::kill_all(&app)
+}
+
+// Plan workflow command
+use crate::workflows::plan::{generate_plan, build_user_prompt, get_api_key, PlanResult};
+use crate::workflows::debate::{self, DebateResult};
+use crate::workflows::verify::{verify_task, parse_verdict, default_header, VerifyOptions, VerifyResult};
+use crate::workflows::rerun::{self, RegenerateResult, RerunResult};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn generate_plan_command(
+  app: AppHandle,
+  project_id: String,
+  task_id: String,
+) -> Result<PlanResult, String> {
+  generate_plan(app, project_id, task_id)
+    .await
+    .map_err(|e| format!("[{}] {}", e.code, e.message))
+}
+
+/// Experimental: two planners draft competing plans (optionally with different
+/// models) and a judge merges them into the task's plan
+#[tauri::command]
+#[specta::specta]
+pub async fn debate_plan(
+  app: AppHandle,
+  project_id: String,
+  task_id: String,
+  model_a: Option<String>,
+  model_b: Option<String>,
+) -> Result<DebateResult, String> {
+  let settings = cached_settings(&app).await?;
+  if offline::is_offline(&app, &settings) {
+    return Err("SpecTrail is offline; a plan debate needs the LLM provider to be reachable".into());
+  }
+  let api_key = get_api_key(&settings).map_err(|e| e.message)?;
+  debate::debate_plan(&app, &settings, &api_key, &project_id, &task_id, [model_a.as_deref(), model_b.as_deref()]).await
+}
+
+/// Repeat a run with its recorded seed to check whether output differences are sampling noise
+#[tauri::command]
+#[specta::specta]
+pub async fn rerun_with_same_seed(app: AppHandle, run_id: String) -> Result<RerunResult, String> {
+  rerun::rerun_with_same_seed(&app, &run_id).await
+}
+
+/// Continue a plan run in a new run from one of its assistant messages, dropping what came after
+#[tauri::command]
+#[specta::specta]
+pub async fn regenerate_from_message(app: AppHandle, run_id: String, message_id: String) -> Result<RegenerateResult, String> {
+  rerun::regenerate_from_message(&app, &run_id, &message_id).await
 }
 
 #[tauri::command]
+#[specta::specta]
 pub async fn verify_task_command(
   app: AppHandle,
   project_id: String,
@@ -0,0 +1,397 @@
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+
+use crate::commands::{create_task, get_task, list_projects};
+use crate::db;
+use crate::events::{PairRequest, PAIR_REQUEST};
+use crate::export::latest_artifact;
+use crate::models::{new_id, Artifact, Project, Task};
+use crate::settings::cached_settings;
+use crate::workflows::plan::generate_plan;
+use crate::workflows::verify::{verify_task, VerifyOptions};
+
+/// Settings that restart the server when changed
+pub const ENABLED_SETTING: &str = "companion_api_enabled";
+pub const PORT_SETTING: &str = "companion_api_port";
+const DEFAULT_PORT: u16 = 7357;
+const PAIRING_TTL: Duration = Duration::from_secs(300);
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+/// Pairing requests accepted per window, across all clients
+const MAX_PAIR_REQUESTS: usize = 10;
+const PAIR_REQUEST_WINDOW: Duration = Duration::from_secs(600);
+/// How long a stopping server gets to finish in-flight requests
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+struct Pairing {
+  id: String,
+  client_name: String,
+  code: String,
+  created: Instant,
+  attempts: u32,
+}
+
+struct Server {
+  port: u16,
+  stop: oneshot::Sender<()>,
+  task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Running server and the pending pairing, kept in Tauri managed state
+#[derive(Default)]
+pub struct CompanionState {
+  server: Mutex<Option<Server>>,
+  /// Only one pairing can be pending, so codes can't be guessed across many
+  pairing: Mutex<Option<Pairing>>,
+  pair_requests: Mutex<Vec<Instant>>,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct CompanionStatus {
+  pub running: bool,
+  pub port: Option<u16>,
+  pub clients: Vec<CompanionClient>,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct CompanionClient {
+  pub id: String,
+  pub name: String,
+  pub created_at: String,
+  pub last_used_at: Option<String>,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn hash_token(token: &str) -> String {
+  format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Six digits drawn from a random UUID
+fn pairing_code() -> String {
+  let n = uuid::Uuid::new_v4().as_u128() % 1_000_000;
+  format!("{:06}", n)
+}
+
+/// (Re)start the server from settings: stop any running instance and wait for
+/// it to release the port, then bind 127.0.0.1 on the configured port if the
+/// API is enabled. Called at startup and whenever the settings change.
+pub async fn restart(app: &AppHandle) -> Result<(), String> {
+  let state = app.state::<CompanionState>();
+  let running = state.server.lock().unwrap().take();
+  if let Some(server) = running {
+    let _ = server.stop.send(());
+    // The listener closes as soon as the server stops accepting, so only
+    // in-flight requests can outlast the grace period
+    if tokio::time::timeout(SHUTDOWN_GRACE, server.task).await.is_err() {
+      tracing::warn!(port = server.port, "companion API still finishing requests after shutdown");
+    }
+  }
+
+  let settings = cached_settings(app).await?;
+  if settings.get(ENABLED_SETTING).map(|v| v.as_str()) != Some("1") {
+    return Ok(());
+  }
+  let port = match settings.get(PORT_SETTING).map(|p| p.trim()).filter(|p| !p.is_empty()) {
+    Some(port) => port.parse().map_err(|_| format!("Invalid companion API port '{}'", port))?,
+    None => DEFAULT_PORT,
+  };
+
+  // Loopback only; the extension runs on the same machine
+  let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await
+    .map_err(|e| format!("Cannot listen on 127.0.0.1:{}: {}", port, e))?;
+  let (stop, stopped) = oneshot::channel();
+  let router = router(app.clone());
+  let task = tauri::async_runtime::spawn(async move {
+    let server = axum::serve(listener, router).with_graceful_shutdown(async {
+      let _ = stopped.await;
+    });
+    if let Err(e) = server.await {
+      tracing::warn!("companion API stopped: {}", e);
+    }
+  });
+  *state.server.lock().unwrap() = Some(Server { port, stop, task });
+  tracing::info!(port, "companion API listening");
+  Ok(())
+}
+
+fn router(app: AppHandle) -> Router {
+  let authed = Router::new()
+    .route("/v1/projects", get(projects))
+    .route("/v1/projects/:project_id/tasks", post(new_task))
+    .route("/v1/tasks/:task_id", get(task))
+    .route("/v1/tasks/:task_id/plan", post(start_plan))
+    .route("/v1/tasks/:task_id/verify", post(start_verify))
+    .route("/v1/tasks/:task_id/artifacts/latest", get(latest))
+    .route_layer(middleware::from_fn_with_state(app.clone(), require_token));
+
+  Router::new()
+    .route("/v1/health", get(|| async { Json(json!({ "ok": true, "version": env!("CARGO_PKG_VERSION") })) }))
+    .route("/v1/pair", post(pair))
+    .route("/v1/pair/confirm", post(confirm_pair))
+    .merge(authed)
+    .layer(middleware::from_fn(reject_browsers))
+    .with_state(app)
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+  fn into_response(self) -> Response {
+    (self.0, Json(json!({ "error": self.1 }))).into_response()
+  }
+}
+
+impl From<String> for ApiError {
+  fn from(message: String) -> Self {
+    ApiError(StatusCode::BAD_REQUEST, message)
+  }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Web pages can reach loopback ports; extension hosts don't send an Origin
+/// header, so any request that has one came from a browser and is refused
+async fn reject_browsers(request: Request, next: Next) -> Response {
+  if request.headers().contains_key(header::ORIGIN) {
+    return ApiError(StatusCode::FORBIDDEN, "Browser requests are not accepted".into()).into_response();
+  }
+  next.run(request).await
+}
+
+async fn require_token(State(app): State<AppHandle>, request: Request, next: Next) -> Response {
+  let token = request.headers().get(header::AUTHORIZATION)
+    .and_then(|v| v.to_str().ok())
+    .and_then(|v| v.strip_prefix("Bearer "))
+    .map(|t| t.trim().to_string());
+  let Some(token) = token else {
+    return ApiError(StatusCode::UNAUTHORIZED, "Missing bearer token; pair via /v1/pair".into()).into_response();
+  };
+
+  match client_for_token(&app, &token) {
+    Ok(Some(_)) => next.run(request).await,
+    Ok(None) => ApiError(StatusCode::UNAUTHORIZED, "Unknown or revoked token".into()).into_response(),
+    Err(e) => ApiError(StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+  }
+}
+
+/// Client id for a token, stamping its last use
+fn client_for_token(app: &AppHandle, token: &str) -> Result<Option<String>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let result = conn.query_row(
+    "SELECT id FROM companion_clients WHERE token_hash = ?1",
+    [hash_token(token)],
+    |r| r.get::<_, String>(0)
+  );
+  match result {
+    Ok(id) => {
+      conn.execute("UPDATE companion_clients SET last_used_at = ?1 WHERE id = ?2", (now_iso(), &id))
+        .map_err(|e| e.to_string())?;
+      Ok(Some(id))
+    }
+    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+#[derive(Deserialize)]
+struct PairBody {
+  client_name: String,
+}
+
+/// Step 1 of the handshake: the app shows a code, which the user enters in the
+/// extension. Refused while another pairing is pending, and rate limited
+/// overall.
+async fn pair(State(app): State<AppHandle>, Json(body): Json<PairBody>) -> ApiResult<serde_json::Value> {
+  let client_name: String = body.client_name.trim().chars().take(80).collect();
+  if client_name.is_empty() {
+    return Err("client_name is required".to_string().into());
+  }
+  let state = app.state::<CompanionState>();
+  {
+    let mut requests = state.pair_requests.lock().unwrap();
+    requests.retain(|t| t.elapsed() < PAIR_REQUEST_WINDOW);
+    if requests.len() >= MAX_PAIR_REQUESTS {
+      return Err(ApiError(StatusCode::TOO_MANY_REQUESTS, "Too many pairing requests; try again later".into()));
+    }
+    requests.push(Instant::now());
+  }
+  let request = PairRequest { pairing_id: new_id(), client_name, code: pairing_code() };
+  {
+    let mut pairing = state.pairing.lock().unwrap();
+    if pairing.as_ref().is_some_and(|p| p.created.elapsed() < PAIRING_TTL) {
+      return Err(ApiError(StatusCode::CONFLICT, "Another pairing is in progress; finish it or wait for it to expire".into()));
+    }
+    *pairing = Some(Pairing {
+      id: request.pairing_id.clone(),
+      client_name: request.client_name.clone(),
+      code: request.code.clone(),
+      created: Instant::now(),
+      attempts: 0,
+    });
+  }
+  PAIR_REQUEST.emit(&app, request.clone())?;
+  Ok(Json(json!({ "pairing_id": request.pairing_id, "expires_in_secs": PAIRING_TTL.as_secs() })))
+}
+
+#[derive(Deserialize)]
+struct ConfirmBody {
+  pairing_id: String,
+  code: String,
+}
+
+/// Step 2: a matching code exchanges the pairing for a long-lived token.
+/// Only the token's hash is stored.
+async fn confirm_pair(State(app): State<AppHandle>, Json(body): Json<ConfirmBody>) -> ApiResult<serde_json::Value> {
+  let client_name = {
+    let state = app.state::<CompanionState>();
+    let mut slot = state.pairing.lock().unwrap();
+    let pairing = slot.as_mut()
+      .filter(|p| p.id == body.pairing_id && p.created.elapsed() < PAIRING_TTL)
+      .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "Pairing expired or unknown; start again".into()))?;
+    if pairing.code != body.code.trim() {
+      pairing.attempts += 1;
+      if pairing.attempts >= MAX_PAIRING_ATTEMPTS {
+        *slot = None;
+      }
+      return Err(ApiError(StatusCode::UNAUTHORIZED, "Incorrect pairing code".into()));
+    }
+    slot.take().map(|p| p.client_name).unwrap_or_default()
+  };
+
+  let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+  let id = new_id();
+  let conn = db::connect(&app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "INSERT INTO companion_clients (id, name, token_hash, created_at) VALUES (?1, ?2, ?3, ?4)",
+    (&id, &client_name, hash_token(&token), now_iso())
+  ).map_err(|e| e.to_string())?;
+  tracing::info!(client = %client_name, "paired companion client");
+  Ok(Json(json!({ "client_id": id, "token": token })))
+}
+
+async fn projects(State(app): State<AppHandle>) -> ApiResult<Vec<Project>> {
+  Ok(Json(list_projects(app)?))
+}
+
+#[derive(Deserialize)]
+struct NewTaskBody {
+  title: String,
+  #[serde(default)]
+  description: Option<String>,
+  #[serde(default)]
+  mode: Option<String>,
+}
+
+async fn new_task(State(app): State<AppHandle>, Path(project_id): Path<String>, Json(body): Json<NewTaskBody>) -> ApiResult<Task> {
+  if body.title.trim().is_empty() {
+    return Err("title is required".to_string().into());
+  }
+  let task = create_task(app.clone(), project_id, body.title.trim().to_string(), body.mode.unwrap_or_else(|| "plan".into()))?;
+  if let Some(description) = body.description.filter(|d| !d.trim().is_empty()) {
+    let conn = db::connect(&app).map_err(|e| e.to_string())?;
+    conn.execute(
+      "UPDATE tasks SET description = ?1, updated_at = ?2 WHERE id = ?3",
+      (&description, now_iso(), &task.id)
+    ).map_err(|e| e.to_string())?;
+    return Ok(Json(Task { description: Some(description), ..task }));
+  }
+  Ok(Json(task))
+}
+
+async fn task(State(app): State<AppHandle>, Path(task_id): Path<String>) -> ApiResult<Task> {
+  get_task(app, task_id).map(Json).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))
+}
+
+/// Runs take minutes, so they start in the background; the extension polls
+/// the latest artifacts or the task's runs for the outcome
+async fn start_plan(State(app): State<AppHandle>, Path(task_id): Path<String>) -> Result<impl IntoResponse, ApiError> {
+  let task = get_task(app.clone(), task_id).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))?;
+  let started = json!({ "task_id": task.id, "run_type": "plan" });
+  tauri::async_runtime::spawn(async move {
+    if let Err(e) = generate_plan(app, task.project_id, task.id).await {
+      tracing::warn!(code = %e.code, "companion plan failed: {}", e.message);
+    }
+  });
+  Ok((StatusCode::ACCEPTED, Json(started)))
+}
+
+async fn start_verify(
+  State(app): State<AppHandle>,
+  Path(task_id): Path<String>,
+  body: Option<Json<VerifyOptions>>,
+) -> Result<impl IntoResponse, ApiError> {
+  let task = get_task(app.clone(), task_id).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))?;
+  let options = body.map(|Json(o)| o).unwrap_or_default();
+  let started = json!({ "task_id": task.id, "run_type": "verify" });
+  tauri::async_runtime::spawn(async move {
+    if let Err(e) = verify_task(app, task.project_id, task.id, options).await {
+      tracing::warn!(code = %e.code, "companion verification failed: {}", e.message);
+    }
+  });
+  Ok((StatusCode::ACCEPTED, Json(started)))
+}
+
+#[derive(Deserialize)]
+struct LatestQuery {
+  kind: Option<String>,
+}
+
+async fn latest(State(app): State<AppHandle>, Path(task_id): Path<String>, Query(query): Query<LatestQuery>) -> ApiResult<Artifact> {
+  let kind = query.kind.unwrap_or_else(|| "plan_md".into());
+  latest_artifact(&app, &task_id, &kind).map(Json).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))
+}
+
+pub fn status(app: &AppHandle) -> Result<CompanionStatus, String> {
+  let port = app.state::<CompanionState>().server.lock().unwrap().as_ref().map(|server| server.port);
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(
+    "SELECT id, name, created_at, last_used_at FROM companion_clients ORDER BY created_at DESC"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], |r| Ok(CompanionClient {
+    id: r.get(0)?,
+    name: r.get(1)?,
+    created_at: r.get(2)?,
+    last_used_at: r.get(3)?,
+  })).map_err(|e| e.to_string())?;
+  let clients = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+  Ok(CompanionStatus { running: port.is_some(), port, clients })
+}
+
+pub fn revoke_client(app: &AppHandle, client_id: &str) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute("DELETE FROM companion_clients WHERE id = ?1", [client_id])
+    .map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_pairing_code_and_token_hash() {
+    let code = pairing_code();
+    assert_eq!(code.len(), 6);
+    assert!(code.chars().all(|c| c.is_ascii_digit()));
+
+    let hash = hash_token("secret");
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, hash_token("secret"));
+    assert_ne!(hash, hash_token("other"));
+  }
+}
@@ -0,0 +1,177 @@
+use aes_gcm::Aes256Gcm;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, Value, ValueRef};
+use rusqlite::Connection;
+
+use crate::encryption;
+
+/// Text at least this large is zstd-compressed before it's written
+pub const COMPRESS_MIN_BYTES: usize = 1024;
+const ZSTD_LEVEL: i32 = 3;
+/// First byte of a BLOB value, giving its format. Plain only appears inside
+/// encrypted values; outside them plain text is stored as TEXT.
+const PLAIN_MARKER: u8 = 0;
+const ZSTD_MARKER: u8 = 1;
+/// AES-256-GCM over a plain or zstd value; see `encryption`
+const ENCRYPTED_MARKER: u8 = 2;
+
+/// Columns that may hold compressed or encrypted text: (table, column, rows
+/// to skip)
+const COMPRESSED_COLUMNS: &[(&str, &str, &str)] = &[
+  ("messages", "content", "0"),
+  ("artifacts", "content", "0"),
+  ("tool_calls", "result_json", "0"),
+  ("blobs", "content", "0"),
+  ("repo_chunks", "content", "0"),
+  ("task_attachments", "content", "0"),
+];
+
+fn with_marker(marker: u8, bytes: &[u8]) -> Vec<u8> {
+  let mut stored = Vec::with_capacity(bytes.len() + 1);
+  stored.push(marker);
+  stored.extend_from_slice(bytes);
+  stored
+}
+
+fn compress(text: &str) -> Option<Vec<u8>> {
+  if text.len() < COMPRESS_MIN_BYTES {
+    return None;
+  }
+  let compressed = zstd::encode_all(text.as_bytes(), ZSTD_LEVEL).ok()?;
+  (compressed.len() + 1 < text.len()).then(|| with_marker(ZSTD_MARKER, &compressed))
+}
+
+fn encode_with(text: &str, cipher: Option<&Aes256Gcm>) -> rusqlite::Result<Value> {
+  if let Some(cipher) = cipher {
+    let inner = compress(text).unwrap_or_else(|| with_marker(PLAIN_MARKER, text.as_bytes()));
+    let sealed = encryption::encrypt(cipher, &inner).map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+    return Ok(Value::Blob(with_marker(ENCRYPTED_MARKER, &sealed)));
+  }
+  Ok(match compress(text) {
+    Some(compressed) => Value::Blob(compressed),
+    None => Value::Text(text.to_string()),
+  })
+}
+
+/// Value to write for a text column. Large text becomes a BLOB holding the
+/// format marker and the compressed bytes, and with database encryption on
+/// every value is encrypted; anything else is stored as text. Fails rather
+/// than write plaintext when encryption is on but its key isn't loaded.
+pub fn encode(text: &str) -> rusqlite::Result<Value> {
+  if encryption::key_missing() {
+    return Err(rusqlite::Error::ToSqlConversionFailure(encryption::KEY_MISSING_ERROR.into()));
+  }
+  encode_with(text, encryption::cipher().as_ref())
+}
+
+fn decode(bytes: &[u8]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+  match bytes.split_first() {
+    Some((&PLAIN_MARKER, text)) => Ok(String::from_utf8(text.to_vec())?),
+    Some((&ZSTD_MARKER, compressed)) => Ok(String::from_utf8(zstd::decode_all(compressed)?)?),
+    Some((&ENCRYPTED_MARKER, sealed)) => {
+      let cipher = encryption::cipher()
+        .ok_or("value is encrypted but the database key isn't loaded")?;
+      let inner = encryption::decrypt(&cipher, sealed)?;
+      if inner.first() == Some(&ENCRYPTED_MARKER) {
+        return Err("nested encrypted value".into());
+      }
+      decode(&inner)
+    }
+    Some((marker, _)) => Err(format!("unknown storage format marker {}", marker).into()),
+    None => Ok(String::new()),
+  }
+}
+
+/// Text read from a column written with `encode`
+pub struct StoredText(pub String);
+
+impl FromSql for StoredText {
+  fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+    match value {
+      ValueRef::Blob(bytes) => decode(bytes).map(StoredText).map_err(FromSqlError::Other),
+      value => String::column_result(value).map(StoredText),
+    }
+  }
+}
+
+/// Rewrite stored values for the given key, or none, returning how many
+/// changed. `large_text_only` limits it to plain text big enough to compress.
+fn reencode(conn: &Connection, cipher: Option<&Aes256Gcm>, large_text_only: bool) -> rusqlite::Result<usize> {
+  let mut changed = 0;
+  for (table, column, skip) in COMPRESSED_COLUMNS {
+    // The migration that backfills runs before later ones have added their tables
+    let exists: bool = conn.query_row(
+      "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)", [table], |r| r.get(0)
+    )?;
+    if !exists {
+      continue;
+    }
+    let filter = if large_text_only {
+      format!("typeof({column}) = 'text' AND length(CAST({column} AS BLOB)) >= {COMPRESS_MIN_BYTES}")
+    } else {
+      format!("{column} IS NOT NULL")
+    };
+    let rowids: Vec<i64> = {
+      let mut stmt = conn.prepare(&format!("SELECT rowid FROM {table} WHERE {filter} AND NOT ({skip})"))?;
+      let rows = stmt.query_map([], |r| r.get(0))?;
+      rows.collect::<Result<_, _>>()?
+    };
+    for rowid in rowids {
+      let (current, text): (Value, StoredText) = conn.query_row(
+        &format!("SELECT {column}, {column} FROM {table} WHERE rowid = ?1"),
+        [rowid],
+        |r| Ok((r.get(0)?, r.get(1)?))
+      )?;
+      let value = encode_with(&text.0, cipher)?;
+      if value != current {
+        conn.execute(&format!("UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"), (&value, rowid))?;
+        changed += 1;
+      }
+    }
+  }
+  Ok(changed)
+}
+
+/// Re-encode every stored value for a new database key, or none. Used when
+/// encryption is turned on or off.
+pub fn reencode_all(conn: &Connection, cipher: Option<&Aes256Gcm>) -> rusqlite::Result<usize> {
+  reencode(conn, cipher, false)
+}
+
+/// Compress large values written before compression existed. Run once by
+/// the migration that introduces it, inside its transaction; the caller
+/// vacuums afterwards when anything was compressed.
+pub fn backfill(conn: &Connection) -> rusqlite::Result<usize> {
+  let compressed = reencode(conn, None, true)?;
+  if compressed > 0 {
+    tracing::info!(compressed, "compressed large stored values");
+  }
+  Ok(compressed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_compressed_round_trip() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("CREATE TABLE t (content TEXT NOT NULL)").unwrap();
+
+    let diff = "+ let x = 1;\n- let x = 2;\n".repeat(200);
+    let value = encode(&diff).unwrap();
+    match &value {
+      Value::Blob(bytes) => {
+        assert_eq!(bytes[0], ZSTD_MARKER);
+        assert!(bytes.len() * 5 < diff.len());
+      }
+      _ => panic!("expected compressed value"),
+    }
+    assert!(matches!(encode("short"), Ok(Value::Text(_))));
+
+    conn.execute("INSERT INTO t (content) VALUES (?1), (?2)", (&value, &encode("short").unwrap())).unwrap();
+    let mut stmt = conn.prepare("SELECT content FROM t ORDER BY rowid").unwrap();
+    let read: Vec<String> = stmt.query_map([], |r| r.get::<_, StoredText>(0).map(|t| t.0))
+      .unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(read, vec![diff, "short".to_string()]);
+  }
+}
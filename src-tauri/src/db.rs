@@ -36,12 +36,200 @@ pub fn connect(app: &AppHandle) -> Result<Connection, DbError> {
   Ok(conn)
 }
 
+/// Migrations in order, keyed by schema version. The applied version is tracked in
+/// `PRAGMA user_version`; the early migrations are idempotent so existing databases
+/// at version 0 simply re-apply them.
+const MIGRATIONS: &[(i64, &str)] = &[
+  (1, include_str!("../migrations/001_init.sql")),
+  (2, include_str!("../migrations/002_settings.sql")),
+  (3, include_str!("../migrations/003_tool_stats.sql")),
+  (4, include_str!("../migrations/004_artifact_drafts.sql")),
+  (5, include_str!("../migrations/005_phase_scope.sql")),
+  (6, include_str!("../migrations/006_phase_dependencies.sql")),
+  (7, include_str!("../migrations/007_task_attachments.sql")),
+  (8, include_str!("../migrations/008_task_description.sql")),
+  (9, include_str!("../migrations/009_prompt_templates.sql")),
+  (10, include_str!("../migrations/010_output_language.sql")),
+  (11, include_str!("../migrations/011_run_errors.sql")),
+  (12, include_str!("../migrations/012_update_channel.sql")),
+  (13, include_str!("../migrations/013_quick_capture.sql")),
+  (14, include_str!("../migrations/014_smtp.sql")),
+  (15, include_str!("../migrations/015_slack.sql")),
+  (16, include_str!("../migrations/016_task_links.sql")),
+  (17, include_str!("../migrations/017_ci_results.sql")),
+  (18, include_str!("../migrations/018_editor.sql")),
+  (19, include_str!("../migrations/019_companion_api.sql")),
+  (20, include_str!("../migrations/020_task_sessions.sql")),
+  (21, include_str!("../migrations/021_run_usage.sql")),
+  (22, include_str!("../migrations/022_offline.sql")),
+  (23, include_str!("../migrations/023_verify_cache.sql")),
+  (24, include_str!("../migrations/024_run_seed.sql")),
+  (25, include_str!("../migrations/025_tool_quotas.sql")),
+  (26, include_str!("../migrations/026_tool_call_seq.sql")),
+  (27, include_str!("../migrations/027_blobs.sql")),
+  (28, include_str!("../migrations/028_compression.sql")),
+  (29, include_str!("../migrations/029_db_encryption.sql")),
+  (30, include_str!("../migrations/030_db_backups.sql")),
+  (31, include_str!("../migrations/031_sync.sql")),
+  (32, include_str!("../migrations/032_workspaces.sql")),
+  (33, include_str!("../migrations/033_task_search.sql")),
+  (34, include_str!("../migrations/034_tags.sql")),
+  (35, include_str!("../migrations/035_embeddings.sql")),
+  (36, include_str!("../migrations/036_lsp.sql")),
+  (37, include_str!("../migrations/037_baselines.sql")),
+  (38, include_str!("../migrations/038_test_stats.sql")),
+  (39, include_str!("../migrations/039_bench.sql")),
+  (40, include_str!("../migrations/040_command_backend.sql")),
+  (41, include_str!("../migrations/041_command_limits.sql")),
+  (42, include_str!("../migrations/042_command_repo_lock.sql")),
+  (43, include_str!("../migrations/043_check_fingerprints.sql")),
+  (44, include_str!("../migrations/044_toolchain_detection.sql")),
+  (45, include_str!("../migrations/045_custom_commands.sql")),
+  (46, include_str!("../migrations/046_project_remotes.sql")),
+  (47, include_str!("../migrations/047_task_scope.sql")),
+  (48, include_str!("../migrations/048_context_ignore.sql")),
+  (49, include_str!("../migrations/049_context_overrides.sql")),
+  (50, include_str!("../migrations/050_regenerations.sql")),
+  (51, include_str!("../migrations/051_run_lineage.sql")),
+  (52, include_str!("../migrations/052_feedback.sql")),
+  (53, include_str!("../migrations/053_project_artifacts.sql")),
+  (54, include_str!("../migrations/054_project_scoped_artifacts.sql")),
+  (55, include_str!("../migrations/055_repo_map_refresh.sql")),
+  (56, include_str!("../migrations/056_plan_debate.sql")),
+  (57, include_str!("../migrations/057_tool_output_limits.sql")),
+  (58, include_str!("../migrations/058_tool_schema_profile.sql")),
+  (59, include_str!("../migrations/059_custom_workflows.sql")),
+  (60, include_str!("../migrations/060_typed_settings.sql")),
+  (61, include_str!("../migrations/061_run_cancellation.sql")),
+  (62, include_str!("../migrations/062_telemetry.sql")),
+  (63, include_str!("../migrations/063_run_options.sql")),
+];
+
+/// Data changes that need Rust, run after the schema migration of the same
+/// version and in its transaction. Returns whether the freed space should be
+/// given back with a VACUUM once migrating is done.
+fn migrate_data(conn: &Connection, version: i64) -> Result<bool, DbError> {
+  match version {
+    27 => { crate::blobs::backfill(conn)?; }
+    28 => return Ok(crate::compression::backfill(conn)? > 0),
+    34 => { crate::tags::migrate_task_labels(conn)?; }
+    60 => { crate::settings::clamp_stored_values(conn)?; }
+    _ => {}
+  }
+  Ok(false)
+}
+
+/// Apply the migrations newer than `current`. Each runs in one transaction with
+/// its data changes and version bump, so one that fails part-way leaves the
+/// database at the previous version rather than half-migrated.
+fn migrate(conn: &mut Connection, migrations: &[(i64, &str)], current: i64) -> Result<(), DbError> {
+  // Table rebuilds need foreign keys off, and the pragma does nothing inside a transaction
+  conn.pragma_update(None, "foreign_keys", false)?;
+  let mut vacuum = false;
+  for (version, sql) in migrations {
+    if *version <= current {
+      continue;
+    }
+    let tx = conn.transaction()?;
+    tx.execute_batch(sql)?;
+    vacuum |= migrate_data(&tx, *version)?;
+    tx.pragma_update(None, "user_version", version)?;
+    tx.commit()?;
+  }
+  if vacuum {
+    conn.execute_batch("VACUUM")?;
+  }
+  Ok(())
+}
+
+/// Whether the database has any tables yet. Installs from before
+/// `user_version` was tracked are at version 0 too, but have them.
+fn has_tables(conn: &Connection) -> Result<bool, DbError> {
+  Ok(conn.query_row(
+    "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%')",
+    [],
+    |r| r.get(0)
+  )?)
+}
+
 pub fn init_db(app: &AppHandle) -> Result<(), DbError> {
-  let conn = connect(app)?;
-  // Apply migrations in order. Each uses IF NOT EXISTS for idempotency.
-  let init_sql = include_str!("../migrations/001_init.sql");
-  conn.execute_batch(init_sql)?;
-  let settings_sql = include_str!("../migrations/002_settings.sql");
-  conn.execute_batch(settings_sql)?;
+  let mut conn = connect(app)?;
+  let problems = crate::backups::record_integrity(app, &conn);
+  let current: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+  let is_new = !has_tables(&conn)?;
+
+  // Keep a copy to go back to if a migration fails or misbehaves, including
+  // the first upgrade of a database from before versions were tracked. A
+  // database that failed its check doesn't push older, possibly good, backups out.
+  if !is_new && MIGRATIONS.iter().any(|(version, _)| *version > current) {
+    let dir = crate::backups::backups_dir(&paths(app)?.db_path);
+    let keep = crate::backups::keep_count(&conn);
+    if let Err(e) = crate::backups::snapshot(&conn, &dir, current, keep, problems.is_empty()) {
+      tracing::warn!("failed to back up the database before migrating: {}", e);
+    }
+  }
+
+  migrate(&mut conn, MIGRATIONS, current)?;
+  // A database created just now means a first launch on this machine; an
+  // existing one keeps the settings the user saved
+  if is_new {
+    match crate::settings::bootstrap(app, &conn) {
+      Ok(0) => {}
+      Ok(applied) => tracing::info!(applied, "applied bootstrap settings"),
+      Err(e) => tracing::error!("failed to apply bootstrap settings: {}", e),
+    }
+  }
+  // Blobs left behind by rewritten artifacts and deleted runs
+  if let Err(e) = crate::blobs::collect_garbage(&conn) {
+    tracing::warn!("failed to clean up unused blobs: {}", e);
+  }
   Ok(())
 }
+
+/// Per-project override for a setting, falling back to the project's workspace;
+/// None when the project uses the global value
+pub fn get_project_setting(app: &AppHandle, project_id: &str, key: &str) -> Result<Option<String>, DbError> {
+  let conn = connect(app)?;
+  let result = conn.query_row(
+    "SELECT value FROM (
+       SELECT value, 0 AS level FROM project_settings WHERE project_id = ?1 AND key = ?2
+       UNION ALL
+       SELECT ws.value, 1 AS level FROM workspace_settings ws
+       JOIN projects p ON p.workspace_id = ws.workspace_id
+       WHERE p.id = ?1 AND ws.key = ?2
+     ) ORDER BY level LIMIT 1",
+    [project_id, key],
+    |r| r.get(0)
+  );
+  match result {
+    Ok(value) => Ok(Some(value)),
+    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+    Err(e) => Err(e.into()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_failed_migration_rolls_back() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    let migrations: &[(i64, &str)] = &[
+      (1, "CREATE TABLE a (x TEXT)"),
+      (2, "CREATE TABLE b (x TEXT); ALTER TABLE a ADD COLUMN y TEXT; INSERT INTO missing VALUES (1);"),
+    ];
+    assert!(migrate(&mut conn, migrations, 0).is_err());
+
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0)).unwrap();
+    assert_eq!(version, 1);
+    let tables: i64 = conn.query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'b'", [], |r| r.get(0)).unwrap();
+    assert_eq!(tables, 0);
+    assert!(conn.prepare("SELECT y FROM a").is_err());
+
+    // Fixed, it applies from where it stopped
+    let fixed: &[(i64, &str)] = &[migrations[0], (2, "CREATE TABLE b (x TEXT); ALTER TABLE a ADD COLUMN y TEXT;")];
+    migrate(&mut conn, fixed, version).unwrap();
+    assert!(conn.prepare("SELECT y FROM a").is_ok());
+  }
+}
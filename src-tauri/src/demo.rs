@@ -0,0 +1,338 @@
+use rusqlite::Transaction;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::models::*;
+
+const DEMO_FIXTURE: &str = include_str!("../fixtures/demo.json");
+
+/// Project setting that marks a project as seeded demo data
+pub const DEMO_SETTING_KEY: &str = "demo";
+
+pub const DEMO_READ_ONLY_MESSAGE: &str =
+  "This is a read-only demo project. Configure a provider and add your own project to run workflows.";
+
+#[derive(Debug, Deserialize)]
+struct DemoFixture {
+  project: DemoProject,
+  tasks: Vec<DemoTask>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DemoProject {
+  name: String,
+  files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DemoTask {
+  title: String,
+  description: Option<String>,
+  mode: String,
+  status: String,
+  #[serde(default)]
+  phases: Vec<DemoPhase>,
+  #[serde(default)]
+  attachments: Vec<DemoAttachment>,
+  #[serde(default)]
+  runs: Vec<DemoRun>,
+  #[serde(default)]
+  artifacts: Vec<DemoArtifact>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DemoPhase {
+  title: String,
+  status: String,
+  description: Option<String>,
+  #[serde(default)]
+  planned_files: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DemoAttachment {
+  name: String,
+  source: String,
+  content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DemoRun {
+  run_type: String,
+  messages: Vec<DemoMessage>,
+  #[serde(default)]
+  tool_calls: Vec<DemoToolCall>,
+}
+
+/// A message whose content is `@<artifact kind>` is replaced by that artifact's
+/// content, so the transcript and the saved artifact stay identical.
+#[derive(Debug, Deserialize)]
+struct DemoMessage {
+  role: String,
+  content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DemoToolCall {
+  name: String,
+  args: Value,
+  result: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct DemoArtifact {
+  kind: String,
+  content: String,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct DemoSeedResult {
+  pub project: Project,
+  /// False when demo data already existed and was returned as-is
+  pub created: bool,
+  pub task_count: usize,
+  pub run_count: usize,
+}
+
+/// Hands out increasing timestamps so seeded rows sort in fixture order
+struct Clock(time::OffsetDateTime);
+
+impl Clock {
+  fn next(&mut self) -> String {
+    self.0 += time::Duration::seconds(30);
+    self.0.format(&time::format_description::well_known::Rfc3339)
+      .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+  }
+}
+
+pub fn is_demo_project(app: &AppHandle, project_id: &str) -> Result<bool, String> {
+  let value = db::get_project_setting(app, project_id, DEMO_SETTING_KEY).map_err(|e| e.to_string())?;
+  Ok(value.as_deref() == Some("1"))
+}
+
+fn demo_repo_dir(app: &AppHandle) -> Result<PathBuf, String> {
+  Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("demo-repo"))
+}
+
+fn find_demo_project(conn: &rusqlite::Connection) -> Result<Option<Project>, String> {
+  let result = conn.query_row(
+    "SELECT p.id, p.name, p.repo_path, p.created_at, p.last_opened_at, p.workspace_id
+     FROM projects p JOIN project_settings s ON s.project_id = p.id
+     WHERE s.key = ?1 AND s.value = '1' LIMIT 1",
+    [DEMO_SETTING_KEY],
+    |r| Ok(Project {
+      id: r.get(0)?,
+      name: r.get(1)?,
+      repo_path: r.get(2)?,
+      created_at: r.get(3)?,
+      last_opened_at: r.get(4)?,
+      workspace_id: r.get(5)?,
+    })
+  );
+  match result {
+    Ok(project) => Ok(Some(project)),
+    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+/// Create the demo project from the bundled fixture. Idempotent: an existing
+/// demo project is returned instead of seeding a second copy.
+pub fn seed_demo_data(app: &AppHandle) -> Result<DemoSeedResult, String> {
+  let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+  if let Some(project) = find_demo_project(&conn)? {
+    return Ok(DemoSeedResult { project, created: false, task_count: 0, run_count: 0 });
+  }
+
+  let fixture: DemoFixture = serde_json::from_str(DEMO_FIXTURE).map_err(|e| e.to_string())?;
+
+  // A small on-disk repo so file browsing and repo tools have something to show
+  let repo_dir = demo_repo_dir(app)?;
+  for (path, content) in &fixture.project.files {
+    let target = repo_dir.join(path);
+    if let Some(parent) = target.parent() {
+      std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&target, content).map_err(|e| e.to_string())?;
+  }
+
+  let mut clock = Clock(time::OffsetDateTime::now_utc() - time::Duration::hours(2));
+  let project = Project {
+    id: new_id(),
+    name: fixture.project.name.clone(),
+    repo_path: repo_dir.to_string_lossy().to_string(),
+    created_at: clock.next(),
+    last_opened_at: None,
+    workspace_id: None,
+  };
+
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  tx.execute(
+    "INSERT INTO projects (id, name, repo_path, created_at, last_opened_at) VALUES (?1, ?2, ?3, ?4, NULL)",
+    (&project.id, &project.name, &project.repo_path, &project.created_at)
+  ).map_err(|e| e.to_string())?;
+  tx.execute(
+    "INSERT INTO project_settings (project_id, key, value, updated_at) VALUES (?1, ?2, '1', ?3)",
+    (&project.id, DEMO_SETTING_KEY, &project.created_at)
+  ).map_err(|e| e.to_string())?;
+
+  let mut run_count = 0;
+  for task in &fixture.tasks {
+    run_count += seed_task(&tx, &project.id, task, &mut clock).map_err(|e| e.to_string())?;
+  }
+  tx.commit().map_err(|e| e.to_string())?;
+
+  Ok(DemoSeedResult { project, created: true, task_count: fixture.tasks.len(), run_count })
+}
+
+fn seed_task(tx: &Transaction, project_id: &str, task: &DemoTask, clock: &mut Clock) -> rusqlite::Result<usize> {
+  let task_id = new_id();
+  let ts = clock.next();
+  tx.execute(
+    "INSERT INTO tasks (id, project_id, title, mode, status, created_at, updated_at, description)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7)",
+    (&task_id, project_id, &task.title, &task.mode, &task.status, &ts, &task.description)
+  )?;
+
+  for attachment in &task.attachments {
+    tx.execute(
+      "INSERT INTO task_attachments (id, task_id, name, source, source_path, content, created_at)
+       VALUES (?1, ?2, ?3, ?4, NULL, ?5, ?6)",
+      (new_id(), &task_id, &attachment.name, &attachment.source, &attachment.content, clock.next())
+    )?;
+  }
+
+  // Phases run in order, so each depends on the one before it
+  let mut previous_phase: Option<String> = None;
+  for (idx, phase) in task.phases.iter().enumerate() {
+    let phase_id = new_id();
+    let ts = clock.next();
+    let planned_files = serde_json::to_string(&phase.planned_files).unwrap_or_else(|_| "[]".into());
+    tx.execute(
+      "INSERT INTO phases (id, task_id, idx, title, status, created_at, updated_at, description, planned_files_json)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7, ?8)",
+      (&phase_id, &task_id, idx as i64 + 1, &phase.title, &phase.status, &ts, &phase.description, &planned_files)
+    )?;
+    if let Some(previous) = &previous_phase {
+      tx.execute(
+        "INSERT INTO phase_dependencies (phase_id, depends_on_phase_id) VALUES (?1, ?2)",
+        (&phase_id, previous)
+      )?;
+    }
+    previous_phase = Some(phase_id);
+  }
+
+  for run in &task.runs {
+    let run_id = new_id();
+    tx.execute(
+      "INSERT INTO runs (id, task_id, phase_id, run_type, provider, model, started_at, ended_at)
+       VALUES (?1, ?2, NULL, ?3, 'demo', 'demo-fixture', ?4, NULL)",
+      (&run_id, &task_id, &run.run_type, clock.next())
+    )?;
+
+    for (seq, call) in run.tool_calls.iter().enumerate() {
+      tx.execute(
+        "INSERT INTO tool_calls (id, run_id, name, args_json, result_json, created_at, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        (new_id(), &run_id, &call.name, call.args.to_string(), call.result.to_string(), clock.next(), seq as i64 + 1)
+      )?;
+    }
+
+    for message in &run.messages {
+      let content = message.content.strip_prefix('@')
+        .and_then(|kind| task.artifacts.iter().find(|a| a.kind == kind))
+        .map_or(message.content.as_str(), |a| a.content.as_str());
+      tx.execute(
+        "INSERT INTO messages (id, run_id, role, content, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        (new_id(), &run_id, &message.role, content, clock.next())
+      )?;
+    }
+
+    tx.execute("UPDATE runs SET ended_at = ?1 WHERE id = ?2", (clock.next(), &run_id))?;
+  }
+
+  for artifact in &task.artifacts {
+    tx.execute(
+      "INSERT INTO artifacts (id, task_id, phase_id, kind, content, created_at, pinned, draft)
+       VALUES (?1, ?2, NULL, ?3, ?4, ?5, 0, 0)",
+      (new_id(), &task_id, &artifact.kind, &artifact.content, clock.next())
+    )?;
+  }
+
+  Ok(task.runs.len())
+}
+
+/// Delete every demo project and its rows, plus the on-disk demo repo.
+/// Returns the number of projects removed.
+pub fn clear_demo_data(app: &AppHandle) -> Result<usize, String> {
+  let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+  let project_ids: Vec<String> = {
+    let mut stmt = tx.prepare("SELECT project_id FROM project_settings WHERE key = ?1 AND value = '1'")
+      .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([DEMO_SETTING_KEY], |r| r.get(0)).map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+  };
+
+  // Foreign keys aren't enforced on app connections, so delete children explicitly
+  const TASKS: &str = "SELECT id FROM tasks WHERE project_id = ?1";
+  const RUNS: &str = "SELECT id FROM runs WHERE task_id IN (SELECT id FROM tasks WHERE project_id = ?1)";
+  const PHASES: &str = "SELECT id FROM phases WHERE task_id IN (SELECT id FROM tasks WHERE project_id = ?1)";
+  let statements = [
+    format!("DELETE FROM tool_calls WHERE run_id IN ({})", RUNS),
+    format!("DELETE FROM messages WHERE run_id IN ({})", RUNS),
+    format!("DELETE FROM runs WHERE task_id IN ({})", TASKS),
+    format!("DELETE FROM artifacts WHERE task_id IN ({})", TASKS),
+    format!("DELETE FROM task_attachments WHERE task_id IN ({})", TASKS),
+    format!("DELETE FROM task_links WHERE task_id IN ({})", TASKS),
+    format!("DELETE FROM ci_results WHERE task_id IN ({})", TASKS),
+    format!("DELETE FROM task_sessions WHERE task_id IN ({})", TASKS),
+    format!("DELETE FROM phase_dependencies WHERE phase_id IN ({})", PHASES),
+    format!("DELETE FROM phases WHERE task_id IN ({})", TASKS),
+    "DELETE FROM tasks WHERE project_id = ?1".to_string(),
+    "DELETE FROM artifacts WHERE project_id = ?1".to_string(),
+    "DELETE FROM project_usage WHERE project_id = ?1".to_string(),
+    "DELETE FROM project_settings WHERE project_id = ?1".to_string(),
+    "DELETE FROM projects WHERE id = ?1".to_string(),
+  ];
+  for project_id in &project_ids {
+    for sql in &statements {
+      tx.execute(sql, [project_id]).map_err(|e| e.to_string())?;
+    }
+  }
+  tx.commit().map_err(|e| e.to_string())?;
+
+  let repo_dir = demo_repo_dir(app)?;
+  if repo_dir.exists() {
+    std::fs::remove_dir_all(&repo_dir).map_err(|e| e.to_string())?;
+  }
+  Ok(project_ids.len())
+}
+
+fn new_id() -> String {
+  uuid::Uuid::new_v4().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_fixture_parses_and_placeholders_resolve() {
+    let fixture: DemoFixture = serde_json::from_str(DEMO_FIXTURE).unwrap();
+    assert!(!fixture.tasks.is_empty());
+    for task in &fixture.tasks {
+      for run in &task.runs {
+        for message in run.messages.iter().filter(|m| m.content.starts_with('@')) {
+          let kind = &message.content[1..];
+          assert!(task.artifacts.iter().any(|a| a.kind == kind), "no artifact for {}", kind);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,236 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::db;
+use crate::logs::logs_dir;
+
+const MAX_CRASH_REPORTS: usize = 10;
+const REDACTED: &str = "[redacted]";
+const SENSITIVE_KEY_PARTS: &[&str] = &["key", "token", "secret", "password", "auth", "webhook"];
+
+#[derive(Debug, Serialize, Type)]
+pub struct DiagnosticsBundle {
+  pub path: String,
+  /// Files included in the zip
+  pub entries: Vec<String>,
+}
+
+/// Log panics through tracing and write a crash report synchronously, since the
+/// non-blocking log writer may not flush before the process goes down.
+/// The previous hook still runs afterwards.
+pub fn install_panic_hook(app: &AppHandle) {
+  let crash_dir = app.path().app_data_dir().ok().map(|d| d.join("crashes"));
+  let previous = std::panic::take_hook();
+
+  std::panic::set_hook(Box::new(move |info| {
+    let message = info.payload().downcast_ref::<&str>().map(|s| s.to_string())
+      .or_else(|| info.payload().downcast_ref::<String>().cloned())
+      .unwrap_or_else(|| "unknown panic payload".to_string());
+    let location = info.location()
+      .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+      .unwrap_or_default();
+    let backtrace = std::backtrace::Backtrace::force_capture();
+
+    tracing::error!(location = %location, "panic: {}", message);
+    if let Some(dir) = &crash_dir {
+      let _ = write_crash_report(dir, &message, &location, &backtrace.to_string());
+    }
+
+    previous(info);
+  }));
+}
+
+fn write_crash_report(dir: &Path, message: &str, location: &str, backtrace: &str) -> std::io::Result<()> {
+  std::fs::create_dir_all(dir)?;
+  let now = time::OffsetDateTime::now_utc();
+  let path = dir.join(format!("crash-{}.txt", file_stamp(now)));
+  let mut file = File::create(path)?;
+  writeln!(file, "time: {}", now)?;
+  writeln!(file, "version: {}", env!("CARGO_PKG_VERSION"))?;
+  writeln!(file, "location: {}", location)?;
+  writeln!(file, "message: {}\n", message)?;
+  writeln!(file, "{}", backtrace)?;
+  file.sync_all()
+}
+
+/// Zip up logs, crash reports, system info, redacted settings, and the most
+/// recent failed run's metadata. Writes to `dest` or a timestamped file in app data.
+pub fn export_diagnostics(app: &AppHandle, dest: Option<&str>) -> Result<DiagnosticsBundle, String> {
+  let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+  let now = time::OffsetDateTime::now_utc();
+  let path = match dest {
+    Some(dest) => PathBuf::from(dest),
+    None => {
+      let dir = app_data.join("diagnostics");
+      std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+      dir.join(format!("spectrail-diagnostics-{}.zip", file_stamp(now)))
+    }
+  };
+
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let schema_version: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))
+    .map_err(|e| e.to_string())?;
+  let system = json!({
+    "app_version": env!("CARGO_PKG_VERSION"),
+    "os": std::env::consts::OS,
+    "arch": std::env::consts::ARCH,
+    "schema_version": schema_version,
+    "sqlite_version": rusqlite::version(),
+    "exported_at": now.format(&time::format_description::well_known::Rfc3339).unwrap_or_default(),
+  });
+
+  let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")
+    .map_err(|e| e.to_string())?;
+  let settings = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string())?;
+
+  let file = File::create(&path).map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+  let mut zip = ZipWriter::new(file);
+  let mut entries = vec![];
+
+  add_json(&mut zip, &mut entries, "system.json", &system)?;
+  add_json(&mut zip, &mut entries, "settings.json", &json!(redact_settings(settings)))?;
+  add_json(&mut zip, &mut entries, "last_failed_run.json", &last_failed_run(&conn)?)?;
+
+  for log in list_files(&logs_dir(app)?, usize::MAX) {
+    add_file(&mut zip, &mut entries, "logs", &log)?;
+  }
+  for crash in list_files(&app_data.join("crashes"), MAX_CRASH_REPORTS) {
+    add_file(&mut zip, &mut entries, "crashes", &crash)?;
+  }
+
+  zip.finish().map_err(|e| e.to_string())?;
+  Ok(DiagnosticsBundle { path: path.to_string_lossy().to_string(), entries })
+}
+
+/// Whether a setting may hold a credential
+pub fn is_sensitive_setting(key: &str) -> bool {
+  let lower = key.to_lowercase();
+  lower == "extra_headers_json" || SENSITIVE_KEY_PARTS.iter().any(|part| lower.contains(part))
+}
+
+/// Replace values of credential-like settings; empty values stay empty so
+/// support can still tell whether a key was configured.
+fn redact_settings(settings: Vec<(String, String)>) -> BTreeMap<String, String> {
+  settings.into_iter()
+    .map(|(key, value)| {
+      if is_sensitive_setting(&key) && !value.is_empty() {
+        (key, REDACTED.to_string())
+      } else {
+        (key, value)
+      }
+    })
+    .collect()
+}
+
+fn last_failed_run(conn: &rusqlite::Connection) -> Result<Value, String> {
+  let run = conn.query_row(
+    "SELECT id, task_id, phase_id, run_type, provider, model, started_at, ended_at, error
+     FROM runs WHERE error IS NOT NULL ORDER BY started_at DESC LIMIT 1",
+    [],
+    |r| Ok(json!({
+      "id": r.get::<_, String>(0)?,
+      "task_id": r.get::<_, String>(1)?,
+      "phase_id": r.get::<_, Option<String>>(2)?,
+      "run_type": r.get::<_, String>(3)?,
+      "provider": r.get::<_, Option<String>>(4)?,
+      "model": r.get::<_, Option<String>>(5)?,
+      "started_at": r.get::<_, String>(6)?,
+      "ended_at": r.get::<_, Option<String>>(7)?,
+      "error": r.get::<_, Option<String>>(8)?,
+    }))
+  );
+  let mut run = match run {
+    Ok(run) => run,
+    Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(Value::Null),
+    Err(e) => return Err(e.to_string()),
+  };
+
+  // Tool names and timing only; arguments and results may contain source code
+  let run_id = run["id"].as_str().unwrap_or_default().to_string();
+  let mut stmt = conn.prepare("SELECT name, created_at FROM tool_calls WHERE run_id = ?1 ORDER BY seq")
+    .map_err(|e| e.to_string())?;
+  let tool_calls = stmt.query_map([&run_id], |r| {
+    Ok(json!({ "name": r.get::<_, String>(0)?, "created_at": r.get::<_, String>(1)? }))
+  }).map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string())?;
+  let message_count: i64 = conn.query_row(
+    "SELECT COUNT(*) FROM messages WHERE run_id = ?1", [&run_id], |r| r.get(0)
+  ).map_err(|e| e.to_string())?;
+
+  run["tool_calls"] = json!(tool_calls);
+  run["message_count"] = json!(message_count);
+  Ok(run)
+}
+
+/// Newest-first file listing, capped at `max`; missing directories yield nothing
+fn list_files(dir: &Path, max: usize) -> Vec<PathBuf> {
+  let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+    .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect())
+    .unwrap_or_default();
+  files.sort();
+  files.reverse();
+  files.truncate(max);
+  files
+}
+
+fn add_json(zip: &mut ZipWriter<File>, entries: &mut Vec<String>, name: &str, value: &Value) -> Result<(), String> {
+  let content = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+  zip.start_file(name, zip_options()).map_err(|e| e.to_string())?;
+  zip.write_all(&content).map_err(|e| e.to_string())?;
+  entries.push(name.to_string());
+  Ok(())
+}
+
+fn add_file(zip: &mut ZipWriter<File>, entries: &mut Vec<String>, folder: &str, path: &Path) -> Result<(), String> {
+  let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+    return Ok(());
+  };
+  let content = std::fs::read(path).map_err(|e| e.to_string())?;
+  let name = format!("{}/{}", folder, file_name);
+  zip.start_file(name.as_str(), zip_options()).map_err(|e| e.to_string())?;
+  zip.write_all(&content).map_err(|e| e.to_string())?;
+  entries.push(name);
+  Ok(())
+}
+
+fn zip_options() -> SimpleFileOptions {
+  SimpleFileOptions::default().compression_method(CompressionMethod::Deflated)
+}
+
+fn file_stamp(t: time::OffsetDateTime) -> String {
+  format!(
+    "{:04}{:02}{:02}-{:02}{:02}{:02}",
+    t.year(), t.month() as u8, t.day(), t.hour(), t.minute(), t.second()
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_redact_settings() {
+    let redacted = redact_settings(vec![
+      ("api_key".into(), "sk-123".into()),
+      ("slack_token".into(), "".into()),
+      ("extra_headers_json".into(), r#"{"X-Key":"abc"}"#.into()),
+      ("model".into(), "gpt-4o".into()),
+    ]);
+    assert_eq!(redacted["api_key"], REDACTED);
+    assert_eq!(redacted["slack_token"], "");
+    assert_eq!(redacted["extra_headers_json"], REDACTED);
+    assert_eq!(redacted["model"], "gpt-4o");
+  }
+}
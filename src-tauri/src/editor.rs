@@ -0,0 +1,114 @@
+use serde::Serialize;
+use specta::Type;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri::AppHandle;
+
+use crate::commands::get_project;
+use crate::repo_tools::safety::sanitize_path;
+use crate::settings::cached_settings;
+
+#[derive(Debug, Serialize, Type)]
+pub struct EditorLaunch {
+  pub editor: String,
+  /// Program and arguments that were spawned
+  pub command: Vec<String>,
+}
+
+/// Argument list for the configured editor. Custom templates are split on
+/// whitespace before `{path}`, `{line}`, and `{repo}` are substituted, so a
+/// path with spaces stays one argument and never reaches a shell.
+pub fn editor_command(
+  editor: &str,
+  custom_template: &str,
+  jetbrains_launcher: &str,
+  path: &Path,
+  line: u32,
+  repo: &Path,
+) -> Result<Vec<String>, String> {
+  let path = path.to_string_lossy().to_string();
+  let command = match editor {
+    "vscode" => vec!["code".into(), "--goto".into(), format!("{}:{}", path, line)],
+    "cursor" => vec!["cursor".into(), "--goto".into(), format!("{}:{}", path, line)],
+    "jetbrains" => {
+      let launcher = jetbrains_launcher.trim();
+      let launcher = if launcher.is_empty() { "idea" } else { launcher };
+      vec![launcher.into(), "--line".into(), line.to_string(), path]
+    }
+    "custom" => {
+      let repo = repo.to_string_lossy();
+      let parts: Vec<String> = custom_template.split_whitespace()
+        .map(|part| part.replace("{path}", &path).replace("{line}", &line.to_string()).replace("{repo}", &repo))
+        .collect();
+      if parts.is_empty() {
+        return Err("Set editor_command to use a custom editor, e.g. `subl {path}:{line}`".into());
+      }
+      parts
+    }
+    other => return Err(format!("Unknown editor '{}' (expected vscode, cursor, jetbrains, or custom)", other)),
+  };
+  Ok(command)
+}
+
+/// Open a repo-relative path at `line` in the user's editor
+pub async fn open_in_editor(app: &AppHandle, project_id: &str, path: &str, line: Option<u32>) -> Result<EditorLaunch, String> {
+  let project = get_project(app.clone(), project_id.to_string())?;
+  let repo = Path::new(&project.repo_path);
+  let full_path = sanitize_path(repo, path).map_err(|e| e.to_string())?;
+  if !full_path.exists() {
+    return Err(format!("{} does not exist in the repository", path));
+  }
+
+  let settings = cached_settings(app).await?;
+  let editor = settings.get("editor").map(|e| e.trim()).filter(|e| !e.is_empty()).unwrap_or("vscode");
+  let command = editor_command(
+    editor,
+    settings.get("editor_command").map(|s| s.as_str()).unwrap_or_default(),
+    settings.get("editor_jetbrains_launcher").map(|s| s.as_str()).unwrap_or_default(),
+    &full_path,
+    line.unwrap_or(1).max(1),
+    repo,
+  )?;
+
+  // On Windows `code` and `cursor` are .cmd shims that a bare program name won't
+  // find, so look the launcher up on PATH (with PATHEXT) first
+  let program = which::which(&command[0]).map_err(|_| format!("Cannot find {} on PATH", command[0]))?;
+
+  // Editors' CLI launchers return quickly or hand off to a running instance; don't wait on them
+  Command::new(&program)
+    .args(&command[1..])
+    .current_dir(repo)
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| format!("Cannot start {}: {}", command[0], e))?;
+
+  tracing::info!(editor, path, "opened file in editor");
+  Ok(EditorLaunch { editor: editor.to_string(), command })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_editor_command() {
+    let path = Path::new("/repo/my dir/a.rs");
+    let repo = Path::new("/repo");
+    assert_eq!(
+      editor_command("vscode", "", "", path, 12, repo).unwrap(),
+      vec!["code", "--goto", "/repo/my dir/a.rs:12"]
+    );
+    assert_eq!(
+      editor_command("jetbrains", "", "webstorm", path, 3, repo).unwrap(),
+      vec!["webstorm", "--line", "3", "/repo/my dir/a.rs"]
+    );
+    assert_eq!(
+      editor_command("custom", "subl -a {repo} {path}:{line}", "", path, 7, repo).unwrap(),
+      vec!["subl", "-a", "/repo", "/repo/my dir/a.rs:7"]
+    );
+    assert!(editor_command("custom", "  ", "", path, 1, repo).is_err());
+    assert!(editor_command("notepad", "", "", path, 1, repo).is_err());
+  }
+}
@@ -0,0 +1,154 @@
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::commands::get_task;
+use crate::export::{latest_artifact, render_artifact_document};
+use crate::settings::cached_settings;
+use crate::workflows::verify::parse_verdict;
+
+const DEFAULT_SMTP_PORT: u16 = 587;
+
+#[derive(Debug, Serialize, Type)]
+pub struct EmailResult {
+  pub recipients: Vec<String>,
+  pub subject: String,
+}
+
+struct SmtpConfig {
+  host: String,
+  port: u16,
+  security: String,
+  username: String,
+  password: String,
+  from: Mailbox,
+}
+
+fn smtp_config(settings: &HashMap<String, String>) -> Result<SmtpConfig, String> {
+  let get = |key: &str| settings.get(key).map(|v| v.trim().to_string()).unwrap_or_default();
+
+  let host = get("smtp_host");
+  if host.is_empty() {
+    return Err("SMTP is not configured; set smtp_host in Settings".into());
+  }
+  let port = match get("smtp_port").as_str() {
+    "" => DEFAULT_SMTP_PORT,
+    port => port.parse().map_err(|_| format!("Invalid smtp_port '{}'", port))?,
+  };
+  let security = match get("smtp_security").as_str() {
+    "" => "starttls".to_string(),
+    s @ ("starttls" | "tls" | "none") => s.to_string(),
+    other => return Err(format!("Invalid smtp_security '{}' (expected starttls, tls, or none)", other)),
+  };
+  let username = get("smtp_username");
+  let from = match get("smtp_from") {
+    from if !from.is_empty() => from,
+    _ if username.contains('@') => username.clone(),
+    _ => return Err("Set smtp_from to the sender address".into()),
+  };
+  let from = from.parse().map_err(|e| format!("Invalid smtp_from '{}': {}", from, e))?;
+
+  Ok(SmtpConfig {
+    host,
+    port,
+    security,
+    username,
+    // Not trimmed: passwords may legitimately contain surrounding spaces
+    password: settings.get("smtp_password").cloned().unwrap_or_default(),
+    from,
+  })
+}
+
+fn parse_recipients(recipients: &[String]) -> Result<Vec<Mailbox>, String> {
+  let mailboxes = recipients.iter()
+    .map(|r| r.trim())
+    .filter(|r| !r.is_empty())
+    .map(|r| r.parse::<Mailbox>().map_err(|e| format!("Invalid recipient '{}': {}", r, e)))
+    .collect::<Result<Vec<_>, _>>()?;
+  if mailboxes.is_empty() {
+    return Err("At least one recipient is required".into());
+  }
+  Ok(mailboxes)
+}
+
+fn transport(config: &SmtpConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+  let builder = match config.security.as_str() {
+    "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+    "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)),
+    _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host),
+  }.map_err(|e| e.to_string())?;
+
+  let builder = builder.port(config.port);
+  let builder = if config.username.is_empty() {
+    builder
+  } else {
+    builder.credentials(Credentials::new(config.username.clone(), config.password.clone()))
+  };
+  Ok(builder.build())
+}
+
+/// Email the task's latest verification report as HTML, with the markdown
+/// as the plain-text alternative
+pub async fn email_report(app: &AppHandle, task_id: &str, recipients: &[String]) -> Result<EmailResult, String> {
+  let settings = cached_settings(app).await?;
+  let config = smtp_config(&settings)?;
+  let mailboxes = parse_recipients(recipients)?;
+
+  let task = get_task(app.clone(), task_id.to_string())?;
+  let report = latest_artifact(app, task_id, "verification_report")?;
+  let html = render_artifact_document(app, &report)?;
+  let subject = format!(
+    "[SpecTrail] {}: {}",
+    parse_verdict(&report.content).label(),
+    task.title
+  );
+
+  let mut builder = Message::builder().from(config.from.clone()).subject(subject.clone());
+  for mailbox in &mailboxes {
+    builder = builder.to(mailbox.clone());
+  }
+  let message = builder
+    .multipart(MultiPart::alternative_plain_html(report.content.clone(), html))
+    .map_err(|e| e.to_string())?;
+
+  transport(&config)?.send(message).await.map_err(|e| format!("SMTP delivery failed: {}", e))?;
+  tracing::info!(task_id, recipients = mailboxes.len(), "emailed verification report");
+
+  Ok(EmailResult {
+    recipients: mailboxes.iter().map(|m| m.to_string()).collect(),
+    subject,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_smtp_config_defaults() {
+    let settings: HashMap<String, String> = [
+      ("smtp_host", "smtp.example.com"),
+      ("smtp_port", ""),
+      ("smtp_username", "bot@example.com"),
+      ("smtp_from", ""),
+    ].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let config = smtp_config(&settings).unwrap();
+    assert_eq!(config.port, DEFAULT_SMTP_PORT);
+    assert_eq!(config.security, "starttls");
+    assert_eq!(config.from.email.to_string(), "bot@example.com");
+
+    assert!(smtp_config(&HashMap::new()).is_err());
+  }
+
+  #[test]
+  fn test_parse_recipients() {
+    let parsed = parse_recipients(&["a@example.com".into(), " ".into(), "Bob <b@example.com>".into()]).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert!(parse_recipients(&["not an address".into()]).is_err());
+    assert!(parse_recipients(&[]).is_err());
+  }
+}
@@ -0,0 +1,192 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use rusqlite::Connection;
+use serde::Serialize;
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use tauri::AppHandle;
+
+use crate::compression;
+use crate::db;
+use crate::integrations::secrets;
+
+/// '1' when stored source excerpts are encrypted. Changed only through
+/// `set_enabled`, since flipping it requires rewriting the data.
+pub const SETTING: &str = "db_encryption";
+/// Keychain entry holding the hex-encoded key
+const KEY_ENTRY: &str = "database-encryption";
+const NONCE_LEN: usize = 12;
+
+/// Key for the running process. Values are encoded below the layer that has an
+/// AppHandle, so the key lives here rather than in managed state.
+static CIPHER: RwLock<Option<Aes256Gcm>> = RwLock::new(None);
+/// Set when encryption is on but the key couldn't be loaded. Writes of
+/// encrypted columns fail until it's restored instead of storing plaintext.
+static KEY_MISSING: AtomicBool = AtomicBool::new(false);
+
+pub(crate) const KEY_MISSING_ERROR: &str =
+  "Database encryption is on but its key is missing from the keychain; restore the key and restart Spectrail";
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EncryptionStatus {
+  pub enabled: bool,
+  /// False when encryption is on but the keychain entry is gone; encrypted
+  /// data can't be read until it's restored
+  pub key_available: bool,
+}
+
+pub(crate) fn cipher() -> Option<Aes256Gcm> {
+  CIPHER.read().ok().and_then(|c| c.clone())
+}
+
+pub(crate) fn key_missing() -> bool {
+  KEY_MISSING.load(Ordering::SeqCst)
+}
+
+fn install(cipher: Option<Aes256Gcm>) {
+  if let Ok(mut current) = CIPHER.write() {
+    *current = cipher;
+  }
+  KEY_MISSING.store(false, Ordering::SeqCst);
+}
+
+fn install_missing() {
+  install(None);
+  KEY_MISSING.store(true, Ordering::SeqCst);
+}
+
+pub(crate) fn encrypt(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+  let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+  let ciphertext = cipher.encrypt(&nonce, plaintext).map_err(|_| "encryption failed".to_string())?;
+  let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+  out.extend_from_slice(&nonce);
+  out.extend_from_slice(&ciphertext);
+  Ok(out)
+}
+
+pub(crate) fn decrypt(cipher: &Aes256Gcm, data: &[u8]) -> Result<Vec<u8>, String> {
+  if data.len() < NONCE_LEN {
+    return Err("encrypted value is truncated".into());
+  }
+  let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+  cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+    .map_err(|_| "cannot decrypt stored value; the database key doesn't match".to_string())
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn key_to_hex(key: &Key<Aes256Gcm>) -> String {
+  key.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn key_from_hex(hex: &str) -> Result<Aes256Gcm, String> {
+  let bytes = (0..hex.len()).step_by(2)
+    .map(|i| hex.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+    .collect::<Option<Vec<u8>>>()
+    .filter(|b| b.len() == 32)
+    .ok_or("database key in the keychain is malformed")?;
+  Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)))
+}
+
+fn load_key() -> Result<Option<Aes256Gcm>, String> {
+  secrets::get_token(KEY_ENTRY)?.map(|hex| key_from_hex(&hex)).transpose()
+}
+
+fn enabled(conn: &Connection) -> bool {
+  conn.query_row("SELECT value FROM settings WHERE key = ?1", [SETTING], |r| r.get::<_, String>(0))
+    .is_ok_and(|v| v == "1")
+}
+
+/// Load the key at startup, or after a restore, when encryption is on
+pub fn init(app: &AppHandle) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  if !enabled(&conn) {
+    install(None);
+    return Ok(());
+  }
+  match load_key() {
+    Ok(Some(cipher)) => {
+      install(Some(cipher));
+      Ok(())
+    }
+    Ok(None) => {
+      install_missing();
+      Err(KEY_MISSING_ERROR.into())
+    }
+    Err(e) => {
+      install_missing();
+      Err(e)
+    }
+  }
+}
+
+pub fn status(app: &AppHandle) -> Result<EncryptionStatus, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  Ok(EncryptionStatus { enabled: enabled(&conn), key_available: cipher().is_some() })
+}
+
+/// Turn encryption on or off, rewriting every stored excerpt in the new form.
+/// Turning it on creates a key in the OS keychain; turning it off removes it.
+pub fn set_enabled(app: &AppHandle, enable: bool) -> Result<EncryptionStatus, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  if enabled(&conn) == enable {
+    return status(app);
+  }
+
+  let target = if enable {
+    let cipher = match load_key()? {
+      Some(cipher) => cipher,
+      None => {
+        let key = Aes256Gcm::generate_key(OsRng);
+        secrets::set_token(KEY_ENTRY, &key_to_hex(&key))?;
+        Aes256Gcm::new(&key)
+      }
+    };
+    Some(cipher)
+  } else {
+    if cipher().is_none() {
+      return Err("Cannot decrypt the database: its key is missing from the keychain".into());
+    }
+    None
+  };
+
+  let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+  let rewritten = compression::reencode_all(&tx, target.as_ref()).map_err(|e| e.to_string())?;
+  tx.execute(
+    "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+     ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+    (SETTING, if enable { "1" } else { "0" }, now_iso())
+  ).map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())?;
+  install(target);
+  crate::settings::invalidate_settings(app);
+
+  // Old plaintext or ciphertext pages stay in the file until it's rebuilt
+  conn.execute_batch("VACUUM").map_err(|e| e.to_string())?;
+  if !enable {
+    secrets::delete_token(KEY_ENTRY)?;
+  }
+  tracing::info!(enable, rewritten, "changed database encryption");
+  status(app)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encrypt_round_trip() {
+    let key = Aes256Gcm::generate_key(OsRng);
+    let cipher = key_from_hex(&key_to_hex(&key)).unwrap();
+    let sealed = encrypt(&cipher, b"fn secret() {}").unwrap();
+    assert_eq!(decrypt(&cipher, &sealed).unwrap(), b"fn secret() {}");
+
+    let other = Aes256Gcm::new(&Aes256Gcm::generate_key(OsRng));
+    assert!(decrypt(&other, &sealed).is_err());
+    assert!(key_from_hex("abcd").is_err());
+  }
+}
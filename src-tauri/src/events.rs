@@ -0,0 +1,180 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use std::marker::PhantomData;
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::offline::OfflineStatus;
+use crate::workflows::budget::BudgetStatus;
+
+/// An event the backend sends the frontend, with the payload type it carries.
+/// Every event is declared below and listed by `event_schemas`, so listeners
+/// can be generated from the schemas instead of guessing payload shapes.
+pub struct Event<T> {
+  pub name: &'static str,
+  /// Bumped when the payload changes in a way existing listeners can't read
+  pub version: u32,
+  pub description: &'static str,
+  payload: PhantomData<fn(T)>,
+}
+
+impl<T> Clone for Event<T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T> Copy for Event<T> {}
+
+impl<T: Serialize + Clone> Event<T> {
+  const fn new(name: &'static str, version: u32, description: &'static str) -> Self {
+    Self { name, version, description, payload: PhantomData }
+  }
+
+  /// Send to every window (or, given a window, to that one)
+  pub fn emit<R: Runtime, E: Emitter<R>>(&self, target: &E, payload: T) -> Result<(), String> {
+    target.emit(self.name, payload).map_err(|e| e.to_string())
+  }
+
+  /// Send to the window with `label` only
+  pub fn emit_to(&self, app: &AppHandle, label: &str, payload: T) -> Result<(), String> {
+    app.emit_to(label, self.name, payload).map_err(|e| e.to_string())
+  }
+}
+
+impl<T: JsonSchema> Event<T> {
+  fn schema(&self) -> EventSchema {
+    EventSchema {
+      name: self.name,
+      version: self.version,
+      description: self.description,
+      payload: serde_json::to_value(schemars::schema_for!(T)).unwrap_or(Value::Null),
+    }
+  }
+}
+
+pub const RUN: Event<RunEvent> = Event::new(
+  "run-event", 1,
+  "A run started, finished or failed; sent only to windows subscribed to its project or task",
+);
+pub const PLAN_TOKEN: Event<TokenEvent> = Event::new(
+  "plan_token", 1,
+  "A fragment of a plan as the model streams it",
+);
+pub const VERIFY_TOKEN: Event<TokenEvent> = Event::new(
+  "verify_token", 1,
+  "A fragment of a verification report as the model streams it",
+);
+pub const COMMAND_QUEUE: Event<CommandQueueEvent> = Event::new(
+  "command-queue", 1,
+  "A run's command is waiting for another run's command in the same repository, or got to start",
+);
+pub const BUDGET_WARNING: Event<BudgetStatus> = Event::new(
+  "budget-warning", 1,
+  "A run started with its project at or past the monthly budget warning threshold",
+);
+pub const CONNECTIVITY: Event<OfflineStatus> = Event::new(
+  "connectivity-changed", 1,
+  "Offline mode turned on or off",
+);
+pub const PAIR_REQUEST: Event<PairRequest> = Event::new(
+  "companion-pair-request", 1,
+  "An editor extension asked to pair; show the code the user types into it",
+);
+/// Payload is the route to open
+pub const NAVIGATE: Event<String> = Event::new(
+  "navigate", 1,
+  "Open a route in the window, e.g. from the tray menu",
+);
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+  Started,
+  Finished,
+  Failed,
+  Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+pub struct RunEvent {
+  pub run_id: String,
+  pub project_id: String,
+  pub task_id: String,
+  pub run_type: String,
+  pub status: RunStatus,
+  pub error: Option<String>,
+  /// Machine-readable failure code (e.g. `OFFLINE`, `CANCELLED`), when the workflow has one
+  pub code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+pub struct TokenEvent {
+  pub run_id: String,
+  pub task_id: String,
+  /// Counts the model's responses in the run; a new value means the text so
+  /// far was an intermediate response (e.g. before tool calls) and starts over
+  pub generation: u32,
+  pub delta: String,
+}
+
+/// Sent when run_command has to wait for another run's command in the same
+/// repository ("waiting"), and again once it gets to run ("started")
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+pub struct CommandQueueEvent {
+  pub run_id: String,
+  pub repo_path: String,
+  pub kind: String,
+  pub status: &'static str,
+  /// Run whose command was running when this one queued
+  pub holder_run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+pub struct PairRequest {
+  pub pairing_id: String,
+  pub client_name: String,
+  pub code: String,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct EventSchema {
+  pub name: &'static str,
+  pub version: u32,
+  pub description: &'static str,
+  /// JSON schema of the payload
+  pub payload: Value,
+}
+
+/// Every event the backend emits
+pub fn event_schemas() -> Vec<EventSchema> {
+  vec![
+    RUN.schema(),
+    PLAN_TOKEN.schema(),
+    VERIFY_TOKEN.schema(),
+    COMMAND_QUEUE.schema(),
+    BUDGET_WARNING.schema(),
+    CONNECTIVITY.schema(),
+    PAIR_REQUEST.schema(),
+    NAVIGATE.schema(),
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_event_schemas() {
+    let schemas = event_schemas();
+    let mut names: Vec<&str> = schemas.iter().map(|s| s.name).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), schemas.len(), "event names must be unique");
+
+    let run = schemas.iter().find(|s| s.name == "run-event").unwrap();
+    assert!(run.payload["required"].as_array().unwrap().contains(&"status".into()));
+    assert_eq!(run.payload["definitions"]["RunStatus"]["enum"], serde_json::json!(["started", "finished", "failed", "cancelled"]));
+  }
+}
@@ -0,0 +1,242 @@
+use pulldown_cmark::{html, Event, Options, Parser};
+use rusqlite::OptionalExtension;
+use serde::Serialize;
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::commands::{get_artifact, get_project, get_task};
+use crate::db;
+use crate::models::Artifact;
+
+const REPORT_TEMPLATE: &str = include_str!("../templates/report.html");
+const PDF_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Headless browsers that can print HTML to PDF, tried in order
+const PDF_BROWSERS: &[&str] = &[
+  "chromium",
+  "chromium-browser",
+  "google-chrome",
+  "google-chrome-stable",
+  "microsoft-edge",
+  "msedge",
+  "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+  "/Applications/Chromium.app/Contents/MacOS/Chromium",
+  "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+  Html,
+  Pdf,
+}
+
+impl ExportFormat {
+  pub fn parse(format: &str) -> Result<Self, String> {
+    match format {
+      "html" => Ok(Self::Html),
+      "pdf" => Ok(Self::Pdf),
+      other => Err(format!("Unsupported export format '{}' (expected html or pdf)", other)),
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct ExportResult {
+  pub path: String,
+  pub format: String,
+  pub bytes: u64,
+}
+
+/// Newest saved (non-draft) task-level artifact of `kind`
+pub fn latest_artifact(app: &AppHandle, task_id: &str, kind: &str) -> Result<Artifact, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let id: Option<String> = conn.query_row(
+    "SELECT id FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = ?2 AND draft = 0
+     ORDER BY created_at DESC LIMIT 1",
+    (task_id, kind),
+    |r| r.get(0)
+  ).optional().map_err(|e| e.to_string())?;
+  let id = id.ok_or_else(|| format!("Task has no {} yet", kind_label(kind).to_lowercase()))?;
+  get_artifact(app.clone(), id)
+}
+
+/// Render markdown to an HTML fragment with GitHub-style tables, task lists,
+/// and strikethrough. Raw HTML is shown as text: artifacts come from the model
+/// and the repository, and the PDF is printed by a real browser.
+pub fn markdown_to_html(markdown: &str) -> String {
+  let options = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+  let events = Parser::new_ext(markdown, options).map(|event| match event {
+    Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+    event => event,
+  });
+  let mut out = String::new();
+  html::push_html(&mut out, events);
+  out
+}
+
+/// Full standalone HTML document for an artifact, with the task metadata header;
+/// project artifacts get the project's instead
+pub fn render_artifact_document(app: &AppHandle, artifact: &Artifact) -> Result<String, String> {
+  let task = artifact.task_id.clone().map(|id| get_task(app.clone(), id)).transpose()?;
+  let project_id = match (&task, &artifact.project_id) {
+    (Some(task), _) => task.project_id.clone(),
+    (None, Some(project_id)) => project_id.clone(),
+    (None, None) => return Err(format!("Artifact {} has no task or project", artifact.id)),
+  };
+  let project = get_project(app.clone(), project_id)?;
+  let exported_at = time::OffsetDateTime::now_utc()
+    .format(&time::format_description::well_known::Rfc3339)
+    .unwrap_or_default();
+
+  let mut meta = vec![
+    ("Project", project.name.as_str()),
+    ("Repository", project.repo_path.as_str()),
+  ];
+  if let Some(task) = &task {
+    meta.push(("Task status", task.status.as_str()));
+    meta.push(("Mode", task.mode.as_str()));
+  }
+  meta.push(("Generated", artifact.created_at.as_str()));
+  let meta = meta.iter()
+    .map(|(label, value)| format!("<dt>{}</dt><dd>{}</dd>", label, escape_html(value)))
+    .collect::<String>();
+  let title = task.as_ref().map_or(project.name.as_str(), |t| t.title.as_str());
+
+  // Placeholders are filled in one pass so artifact content can't inject more of them
+  let vars = [
+    ("title", escape_html(title)),
+    ("kind", escape_html(&kind_label(&artifact.kind))),
+    ("meta", meta),
+    ("body", markdown_to_html(&artifact.content)),
+    ("exported_at", exported_at),
+  ];
+  Ok(fill_template(REPORT_TEMPLATE, &vars))
+}
+
+pub async fn export_artifact(app: &AppHandle, artifact_id: &str, format: &str, dest: &str) -> Result<ExportResult, String> {
+  let format = ExportFormat::parse(format)?;
+  let artifact = get_artifact(app.clone(), artifact_id.to_string())?;
+  let document = render_artifact_document(app, &artifact)?;
+  let dest = PathBuf::from(dest);
+
+  match format {
+    ExportFormat::Html => {
+      std::fs::write(&dest, document).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+    }
+    ExportFormat::Pdf => {
+      // The browser prints from a file; keep it next to the output so relative links resolve
+      let html_path = dest.with_extension("export.html");
+      std::fs::write(&html_path, document).map_err(|e| format!("Cannot write {}: {}", html_path.display(), e))?;
+      let printed = print_pdf(&html_path, &dest).await;
+      let _ = std::fs::remove_file(&html_path);
+      printed?;
+    }
+  }
+
+  let bytes = std::fs::metadata(&dest).map(|m| m.len()).map_err(|e| e.to_string())?;
+  tracing::info!(artifact_id, path = %dest.display(), bytes, "exported artifact");
+  Ok(ExportResult {
+    path: dest.to_string_lossy().to_string(),
+    format: match format { ExportFormat::Html => "html", ExportFormat::Pdf => "pdf" }.to_string(),
+    bytes,
+  })
+}
+
+async fn print_pdf(html_path: &Path, dest: &Path) -> Result<(), String> {
+  let browser = PDF_BROWSERS.iter()
+    .find_map(|b| which::which(b).ok())
+    .ok_or("PDF export needs Chrome, Chromium, or Edge installed; export as HTML instead")?;
+
+  let output = timeout(
+    PDF_TIMEOUT,
+    Command::new(&browser)
+      .arg("--headless")
+      .arg("--disable-gpu")
+      .arg("--no-pdf-header-footer")
+      .arg("--blink-settings=scriptEnabled=false")
+      .arg(format!("--print-to-pdf={}", dest.display()))
+      .arg(html_path)
+      .stdout(Stdio::null())
+      .stderr(Stdio::piped())
+      .output(),
+  ).await
+    .map_err(|_| "PDF rendering timed out".to_string())?
+    .map_err(|e| format!("Cannot run {}: {}", browser.display(), e))?;
+
+  if !output.status.success() || !dest.exists() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(format!("PDF rendering failed: {}", stderr.lines().last().unwrap_or("unknown error")));
+  }
+  Ok(())
+}
+
+fn fill_template(template: &str, vars: &[(&str, String)]) -> String {
+  let mut out = String::with_capacity(template.len());
+  let mut rest = template;
+  while let Some(start) = rest.find("{{") {
+    out.push_str(&rest[..start]);
+    let after = &rest[start + 2..];
+    match after.find("}}") {
+      Some(end) => {
+        let name = &after[..end];
+        match vars.iter().find(|(key, _)| *key == name) {
+          Some((_, value)) => out.push_str(value),
+          None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+      }
+      None => {
+        out.push_str(&rest[start..]);
+        rest = "";
+      }
+    }
+  }
+  out.push_str(rest);
+  out
+}
+
+fn kind_label(kind: &str) -> String {
+  match kind {
+    "verification_report" => "Verification Report".to_string(),
+    "plan_md" => "Implementation Plan".to_string(),
+    "repo_overview" => "Architecture Overview".to_string(),
+    other => other.replace('_', " "),
+  }
+}
+
+fn escape_html(text: &str) -> String {
+  text.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_markdown_to_html_tables_and_code() {
+    let html = markdown_to_html("| Risk | Severity |\n|---|---|\n| Drift | Low |\n\n```rust\nfn main() {}\n```\n- [x] done");
+    assert!(html.contains("<table>"));
+    assert!(html.contains("<td>Drift</td>"));
+    assert!(html.contains("<code class=\"language-rust\">"));
+    assert!(html.contains("type=\"checkbox\""));
+
+    let html = markdown_to_html("<script>alert(1)</script>\n\nSee <img src=x onerror=alert(1)>");
+    assert!(!html.contains("<script") && !html.contains("<img"));
+    assert!(html.contains("&lt;script&gt;") && html.contains("&lt;img src=x onerror=alert(1)&gt;"));
+  }
+
+  #[test]
+  fn test_fill_template_single_pass() {
+    let vars = [("body", "<p>{{title}}</p>".to_string()), ("title", "T".to_string())];
+    let out = fill_template("<h1>{{title}}</h1>{{body}}{{unknown}}", &vars);
+    assert_eq!(out, "<h1>T</h1><p>{{title}}</p>{{unknown}}");
+  }
+}
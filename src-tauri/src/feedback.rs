@@ -0,0 +1,230 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::models::{new_id, ID};
+
+const MAX_COMMENT_CHARS: usize = 2_000;
+
+/// A reviewer's rating of a plan or verification report
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct Feedback {
+  pub id: ID,
+  pub artifact_id: ID,
+  pub task_id: ID,
+  /// plan_md|verification_report
+  pub kind: String,
+  /// up|down
+  pub rating: String,
+  pub comment: Option<String>,
+  pub created_at: String,
+}
+
+/// Ratings of one artifact kind
+#[derive(Debug, Clone, Serialize, PartialEq, Type)]
+pub struct FeedbackStats {
+  pub kind: String,
+  pub up: i64,
+  pub down: i64,
+  /// Share of thumbs-up, or None before any rating
+  pub approval_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct FeedbackSummary {
+  pub stats: Vec<FeedbackStats>,
+  /// Latest thumbs-down comments, newest first
+  pub recent_rejections: Vec<Feedback>,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn row_to_feedback(r: &rusqlite::Row) -> rusqlite::Result<Feedback> {
+  Ok(Feedback {
+    id: r.get(0)?,
+    artifact_id: r.get(1)?,
+    task_id: r.get(2)?,
+    kind: r.get(3)?,
+    rating: r.get(4)?,
+    comment: r.get(5)?,
+    created_at: r.get(6)?,
+  })
+}
+
+const FEEDBACK_COLUMNS: &str = "f.id, f.artifact_id, f.task_id, f.kind, f.rating, f.comment, f.created_at";
+
+/// Rate a plan or verification report, with an optional comment on why
+pub fn submit_feedback(app: &AppHandle, artifact_id: &str, rating: &str, comment: Option<&str>) -> Result<Feedback, String> {
+  if !matches!(rating, "up" | "down") {
+    return Err(format!("Unknown rating '{}' (expected up or down)", rating));
+  }
+  let comment = comment.map(str::trim).filter(|c| !c.is_empty());
+  if comment.is_some_and(|c| c.chars().count() > MAX_COMMENT_CHARS) {
+    return Err(format!("Feedback comments are limited to {} characters", MAX_COMMENT_CHARS));
+  }
+
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let (task_id, kind): (Option<String>, String) = conn.query_row(
+    "SELECT task_id, kind FROM artifacts WHERE id = ?1",
+    [artifact_id],
+    |r| Ok((r.get(0)?, r.get(1)?))
+  ).optional().map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Artifact {} not found", artifact_id))?;
+  if !matches!(kind.as_str(), "plan_md" | "verification_report") {
+    return Err(format!("Feedback is only collected on plans and verification reports, not {}", kind));
+  }
+  // Plans and reports always belong to a task; only project artifacts lack one
+  let task_id = task_id.ok_or_else(|| format!("Artifact {} is not a task artifact", artifact_id))?;
+
+  let feedback = Feedback {
+    id: new_id(),
+    artifact_id: artifact_id.to_string(),
+    task_id,
+    kind,
+    rating: rating.to_string(),
+    comment: comment.map(str::to_string),
+    created_at: now_iso(),
+  };
+  conn.execute(
+    "INSERT INTO feedback (id, artifact_id, task_id, kind, rating, comment, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    (&feedback.id, &feedback.artifact_id, &feedback.task_id, &feedback.kind, &feedback.rating, &feedback.comment, &feedback.created_at)
+  ).map_err(|e| e.to_string())?;
+  Ok(feedback)
+}
+
+/// The task's feedback, newest first
+pub fn list_feedback(app: &AppHandle, task_id: &str) -> Result<Vec<Feedback>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(&format!(
+    "SELECT {} FROM feedback f WHERE f.task_id = ?1 ORDER BY f.created_at DESC", FEEDBACK_COLUMNS
+  )).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([task_id], row_to_feedback).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+pub fn delete_feedback(app: &AppHandle, feedback_id: &str) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute("DELETE FROM feedback WHERE id = ?1", [feedback_id]).map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+fn summary(conn: &Connection, project_id: Option<&str>, recent: usize) -> Result<FeedbackSummary, String> {
+  let mut stmt = conn.prepare(
+    "SELECT f.kind, SUM(f.rating = 'up'), SUM(f.rating = 'down')
+     FROM feedback f JOIN tasks t ON t.id = f.task_id
+     WHERE ?1 IS NULL OR t.project_id = ?1
+     GROUP BY f.kind ORDER BY f.kind"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([project_id], |r| {
+    let (up, down): (i64, i64) = (r.get(1)?, r.get(2)?);
+    Ok(FeedbackStats {
+      kind: r.get(0)?,
+      up,
+      down,
+      approval_rate: (up + down > 0).then(|| up as f64 / (up + down) as f64),
+    })
+  }).map_err(|e| e.to_string())?;
+  let stats = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+
+  let mut stmt = conn.prepare(&format!(
+    "SELECT {} FROM feedback f JOIN tasks t ON t.id = f.task_id
+     WHERE f.rating = 'down' AND f.comment IS NOT NULL AND (?1 IS NULL OR t.project_id = ?1)
+     ORDER BY f.created_at DESC LIMIT ?2", FEEDBACK_COLUMNS
+  )).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map((project_id, recent as i64), row_to_feedback).map_err(|e| e.to_string())?;
+  let recent_rejections = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+  Ok(FeedbackSummary { stats, recent_rejections })
+}
+
+/// Approval rates per artifact kind and the latest reasons for rejections,
+/// for one project or all of them
+pub fn feedback_summary(app: &AppHandle, project_id: Option<&str>) -> Result<FeedbackSummary, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  summary(&conn, project_id, 10)
+}
+
+fn feedback_on_current(conn: &Connection, task_id: &str, phase_id: Option<&str>, kind: &str) -> Result<Vec<Feedback>, String> {
+  // Regenerating rewrites the artifact in place and bumps created_at, so
+  // only feedback given since then is about the version the next run replaces
+  let mut stmt = conn.prepare(&format!(
+    "SELECT {} FROM feedback f JOIN artifacts a ON a.id = f.artifact_id
+     WHERE a.task_id = ?1 AND COALESCE(a.phase_id, '') = COALESCE(?2, '') AND a.kind = ?3 AND a.draft = 0
+       AND f.created_at >= a.created_at
+     ORDER BY f.created_at ASC", FEEDBACK_COLUMNS
+  )).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map((task_id, phase_id, kind), row_to_feedback).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn format_feedback(kind: &str, feedback: &[Feedback]) -> Option<String> {
+  let noun = if kind == "plan_md" { "plan" } else { "verification report" };
+  let lines: Vec<String> = feedback.iter()
+    .filter_map(|f| match (f.rating.as_str(), &f.comment) {
+      ("down", Some(comment)) => Some(format!("- The previous {} was rejected because: {}", noun, comment)),
+      ("down", None) => Some(format!("- The previous {} was rejected.", noun)),
+      (_, Some(comment)) => Some(format!("- The previous {} was approved, with this note: {}", noun, comment)),
+      _ => None,
+    })
+    .collect();
+  if lines.is_empty() {
+    return None;
+  }
+  Some(format!(
+    "## Reviewer Feedback\n\nA reviewer rated the previous {} for this task. Address this feedback in the new one.\n\n{}\n",
+    noun,
+    lines.join("\n")
+  ))
+}
+
+/// Prompt section with the feedback on the task's current plan or report
+pub fn feedback_context(app: &AppHandle, task_id: &str, phase_id: Option<&str>, kind: &str) -> Result<Option<String>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  Ok(format_feedback(kind, &feedback_on_current(&conn, task_id, phase_id, kind)?))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_feedback_summary_and_context() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at TEXT NOT NULL);
+       CREATE TABLE tasks (id TEXT PRIMARY KEY, project_id TEXT NOT NULL);
+       CREATE TABLE artifacts (id TEXT PRIMARY KEY, task_id TEXT NOT NULL, phase_id TEXT, kind TEXT NOT NULL,
+         created_at TEXT NOT NULL, draft INTEGER NOT NULL DEFAULT 0);
+       INSERT INTO tasks VALUES ('t1', 'p1'), ('t2', 'p2');
+       INSERT INTO artifacts VALUES ('plan', 't1', NULL, 'plan_md', '2026-05-02T00:00:00Z', 0);
+       INSERT INTO artifacts VALUES ('report', 't2', NULL, 'verification_report', '2026-05-01T00:00:00Z', 0);"
+    ).unwrap();
+    conn.execute_batch(include_str!("../migrations/052_feedback.sql")).unwrap();
+    conn.execute_batch(
+      "INSERT INTO feedback VALUES ('f1', 'plan', 't1', 'plan_md', 'down', 'ignores the cache layer', '2026-05-01T12:00:00Z');
+       INSERT INTO feedback VALUES ('f2', 'plan', 't1', 'plan_md', 'down', 'missing the migration', '2026-05-02T12:00:00Z');
+       INSERT INTO feedback VALUES ('f3', 'plan', 't1', 'plan_md', 'up', NULL, '2026-05-02T13:00:00Z');
+       INSERT INTO feedback VALUES ('f4', 'report', 't2', 'verification_report', 'up', NULL, '2026-05-01T12:00:00Z');"
+    ).unwrap();
+
+    let all = summary(&conn, None, 10).unwrap();
+    assert_eq!(all.stats, vec![
+      FeedbackStats { kind: "plan_md".into(), up: 1, down: 2, approval_rate: Some(1.0 / 3.0) },
+      FeedbackStats { kind: "verification_report".into(), up: 1, down: 0, approval_rate: Some(1.0) },
+    ]);
+    assert_eq!(all.recent_rejections[0].id, "f2");
+    assert_eq!(summary(&conn, Some("p2"), 10).unwrap().stats.len(), 1);
+
+    // f1 rated the plan before it was regenerated
+    let current = feedback_on_current(&conn, "t1", None, "plan_md").unwrap();
+    let context = format_feedback("plan_md", &current).unwrap();
+    assert!(context.contains("- The previous plan was rejected because: missing the migration"));
+    assert!(!context.contains("cache layer"));
+    assert_eq!(context.matches("\n- ").count(), 1);
+    assert!(format_feedback("verification_report", &feedback_on_current(&conn, "t2", None, "verification_report").unwrap()).is_none());
+  }
+}
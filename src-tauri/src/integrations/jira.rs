@@ -0,0 +1,88 @@
+use serde_json::{json, Value};
+
+use super::{ExternalIssue, IntegrationConfig};
+
+/// Jira Cloud/Server REST v2, which takes and returns wiki-markup strings
+/// instead of Atlassian Document Format
+fn api_url(config: &IntegrationConfig, path: &str) -> Result<String, String> {
+  let base = config.base_url.as_deref()
+    .map(|u| u.trim().trim_end_matches('/'))
+    .filter(|u| !u.is_empty())
+    .ok_or("Jira base_url is not configured for this project")?;
+  Ok(format!("{}/rest/api/2/{}", base, path))
+}
+
+/// `issue/{key}` plus any trailing segments, with the key percent-encoded as a
+/// single path segment
+fn issue_url(config: &IntegrationConfig, key: &str, rest: &[&str]) -> Result<reqwest::Url, String> {
+  let mut url = reqwest::Url::parse(&api_url(config, "issue")?)
+    .map_err(|e| format!("Invalid Jira base_url: {}", e))?;
+  url.path_segments_mut()
+    .map_err(|_| "Invalid Jira base_url".to_string())?
+    .push(key)
+    .extend(rest);
+  Ok(url)
+}
+
+fn authorize(request: reqwest::RequestBuilder, config: &IntegrationConfig, token: &str) -> reqwest::RequestBuilder {
+  match config.email.as_deref().filter(|e| !e.is_empty()) {
+    // Jira Cloud: account email + API token
+    Some(email) => request.basic_auth(email, Some(token)),
+    // Jira Server/Data Center: personal access token
+    None => request.bearer_auth(token),
+  }
+}
+
+pub async fn fetch_issue(client: &reqwest::Client, config: &IntegrationConfig, token: &str, key: &str) -> Result<ExternalIssue, String> {
+  let url = issue_url(config, key, &[])?;
+  let response = authorize(client.get(url), config, token)
+    .send()
+    .await
+    .map_err(|e| format!("Jira request failed: {}", e))?;
+  if !response.status().is_success() {
+    return Err(format!("Jira returned {} for issue {}", response.status(), key));
+  }
+  let issue: Value = response.json().await.map_err(|e| format!("Unexpected Jira response: {}", e))?;
+
+  let key = issue["key"].as_str().unwrap_or(key).to_string();
+  let browse = config.base_url.as_deref().unwrap_or_default().trim_end_matches('/');
+  Ok(ExternalIssue {
+    id: issue["id"].as_str().unwrap_or_default().to_string(),
+    url: format!("{}/browse/{}", browse, key),
+    key,
+    fields: issue["fields"].clone(),
+  })
+}
+
+pub async fn add_comment(client: &reqwest::Client, config: &IntegrationConfig, token: &str, key: &str, body: &str) -> Result<(), String> {
+  let url = issue_url(config, key, &["comment"])?;
+  let response = authorize(client.post(url), config, token)
+    .json(&json!({ "body": body }))
+    .send()
+    .await
+    .map_err(|e| format!("Jira request failed: {}", e))?;
+  if !response.status().is_success() {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    return Err(format!("Jira returned {} when commenting: {}", status, text));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_issue_url_encodes_key() {
+    let config = IntegrationConfig { base_url: Some("https://acme.atlassian.net/".into()), email: None, field_mapping: None };
+    assert_eq!(
+      issue_url(&config, "ENG-1", &["comment"]).unwrap().as_str(),
+      "https://acme.atlassian.net/rest/api/2/issue/ENG-1/comment"
+    );
+    assert_eq!(
+      issue_url(&config, "../x?y#z", &[]).unwrap().as_str(),
+      "https://acme.atlassian.net/rest/api/2/issue/..%2Fx%3Fy%23z"
+    );
+  }
+}
@@ -0,0 +1,61 @@
+use serde_json::{json, Value};
+
+use super::ExternalIssue;
+
+const API_URL: &str = "https://api.linear.app/graphql";
+
+const ISSUE_QUERY: &str = "query Issue($id: String!) {
+  issue(id: $id) {
+    id identifier title description url priorityLabel estimate dueDate
+    state { name } assignee { name } team { key name } project { name }
+    labels { nodes { name } }
+  }
+}";
+
+const COMMENT_MUTATION: &str = "mutation Comment($input: CommentCreateInput!) {
+  commentCreate(input: $input) { success }
+}";
+
+async fn graphql(client: &reqwest::Client, token: &str, query: &str, variables: Value) -> Result<Value, String> {
+  // Personal API keys are sent bare; OAuth tokens need the Bearer prefix
+  let auth = if token.starts_with("lin_api_") { token.to_string() } else { format!("Bearer {}", token) };
+  let response: Value = client.post(API_URL)
+    .header(reqwest::header::AUTHORIZATION, auth)
+    .json(&json!({ "query": query, "variables": variables }))
+    .send()
+    .await
+    .map_err(|e| format!("Linear request failed: {}", e))?
+    .json()
+    .await
+    .map_err(|e| format!("Unexpected Linear response: {}", e))?;
+
+  if let Some(error) = response["errors"].as_array().and_then(|errors| errors.first()) {
+    return Err(format!("Linear error: {}", error["message"].as_str().unwrap_or("unknown")));
+  }
+  Ok(response["data"].clone())
+}
+
+/// `key` may be the identifier (ENG-123) or the issue UUID
+pub async fn fetch_issue(client: &reqwest::Client, token: &str, key: &str) -> Result<ExternalIssue, String> {
+  let data = graphql(client, token, ISSUE_QUERY, json!({ "id": key })).await?;
+  let issue = &data["issue"];
+  if issue.is_null() {
+    return Err(format!("Linear issue {} not found", key));
+  }
+  Ok(ExternalIssue {
+    id: issue["id"].as_str().unwrap_or_default().to_string(),
+    key: issue["identifier"].as_str().unwrap_or(key).to_string(),
+    url: issue["url"].as_str().unwrap_or_default().to_string(),
+    fields: issue.clone(),
+  })
+}
+
+pub async fn add_comment(client: &reqwest::Client, token: &str, issue_id: &str, body: &str) -> Result<(), String> {
+  let data = graphql(client, token, COMMENT_MUTATION, json!({
+    "input": { "issueId": issue_id, "body": body }
+  })).await?;
+  if data["commentCreate"]["success"].as_bool() != Some(true) {
+    return Err("Linear did not accept the comment".into());
+  }
+  Ok(())
+}
@@ -0,0 +1,378 @@
+pub mod jira;
+pub mod linear;
+pub mod secrets;
+
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::commands::{get_task, insert_attachment, insert_task};
+use crate::db;
+use crate::export::latest_artifact;
+use crate::models::Task;
+use crate::render::numbered_section;
+use crate::workflows::verify::parse_verdict;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provider {
+  Jira,
+  Linear,
+}
+
+impl Provider {
+  pub fn parse(provider: &str) -> Result<Self, String> {
+    match provider {
+      "jira" => Ok(Provider::Jira),
+      "linear" => Ok(Provider::Linear),
+      other => Err(format!("Unknown integration '{}' (expected jira or linear)", other)),
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Provider::Jira => "jira",
+      Provider::Linear => "linear",
+    }
+  }
+
+  fn setting_key(&self) -> String {
+    format!("integration_{}", self.as_str())
+  }
+}
+
+/// An issue field copied into the imported task's "Issue fields" note
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+pub struct ExtraField {
+  pub label: String,
+  /// Dotted path into the issue's fields, e.g. `priority.name` or `customfield_10010`
+  pub path: String,
+}
+
+/// Which issue fields become the task title and description
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+pub struct FieldMapping {
+  pub title: String,
+  pub description: String,
+  #[serde(default)]
+  pub extra: Vec<ExtraField>,
+}
+
+impl FieldMapping {
+  fn default_for(provider: Provider) -> Self {
+    let extra = |fields: &[(&str, &str)]| fields.iter()
+      .map(|(label, path)| ExtraField { label: label.to_string(), path: path.to_string() })
+      .collect();
+    match provider {
+      Provider::Jira => FieldMapping {
+        title: "summary".into(),
+        description: "description".into(),
+        extra: extra(&[("Priority", "priority.name"), ("Status", "status.name"), ("Labels", "labels")]),
+      },
+      Provider::Linear => FieldMapping {
+        title: "title".into(),
+        description: "description".into(),
+        extra: extra(&[("Priority", "priorityLabel"), ("State", "state.name"), ("Labels", "labels.nodes")]),
+      },
+    }
+  }
+}
+
+/// Per-project integration settings, stored as JSON in `project_settings`.
+/// Tokens live in the OS keychain, never here.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct IntegrationConfig {
+  /// Jira site URL, e.g. https://acme.atlassian.net (unused for Linear)
+  #[serde(default)]
+  pub base_url: Option<String>,
+  /// Jira Cloud account email for basic auth; omit to send a bearer PAT
+  #[serde(default)]
+  pub email: Option<String>,
+  #[serde(default)]
+  pub field_mapping: Option<FieldMapping>,
+}
+
+/// Issue as fetched from the provider; `fields` is the raw field object
+/// that mapping paths resolve against
+#[derive(Debug, Clone)]
+pub struct ExternalIssue {
+  pub id: String,
+  pub key: String,
+  pub url: String,
+  pub fields: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TaskLink {
+  pub task_id: String,
+  pub provider: String,
+  pub external_id: String,
+  pub external_key: String,
+  pub url: String,
+  pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct ImportedIssue {
+  pub task: Task,
+  pub link: TaskLink,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct IntegrationStatus {
+  pub provider: String,
+  pub has_token: bool,
+  pub config: Option<IntegrationConfig>,
+  /// Mapping in effect: the configured one or the provider default
+  pub field_mapping: FieldMapping,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn client() -> Result<reqwest::Client, String> {
+  reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().map_err(|e| e.to_string())
+}
+
+fn require_token(provider: Provider) -> Result<String, String> {
+  secrets::get_token(provider.as_str())?
+    .ok_or_else(|| format!("No {} token saved; add one in Settings", provider.as_str()))
+}
+
+pub fn load_config(app: &AppHandle, project_id: &str, provider: Provider) -> Result<Option<IntegrationConfig>, String> {
+  let raw = db::get_project_setting(app, project_id, &provider.setting_key()).map_err(|e| e.to_string())?;
+  raw.map(|json| serde_json::from_str(&json).map_err(|e| format!("Invalid {} configuration: {}", provider.as_str(), e)))
+    .transpose()
+}
+
+pub fn save_config(app: &AppHandle, project_id: &str, provider: Provider, config: &IntegrationConfig) -> Result<(), String> {
+  let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "INSERT INTO project_settings (project_id, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+     ON CONFLICT(project_id, key) DO UPDATE SET value=excluded.value, updated_at=excluded.updated_at",
+    (project_id, provider.setting_key(), json, now_iso())
+  ).map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+pub fn status(app: &AppHandle, project_id: &str, provider: Provider) -> Result<IntegrationStatus, String> {
+  let config = load_config(app, project_id, provider)?;
+  let field_mapping = config.as_ref()
+    .and_then(|c| c.field_mapping.clone())
+    .unwrap_or_else(|| FieldMapping::default_for(provider));
+  Ok(IntegrationStatus {
+    provider: provider.as_str().to_string(),
+    has_token: secrets::get_token(provider.as_str())?.is_some(),
+    config,
+    field_mapping,
+  })
+}
+
+/// Resolve a dotted path and flatten the value to display text. Arrays are
+/// joined; objects show their name-like field.
+fn field_text(fields: &Value, path: &str) -> Option<String> {
+  let value = path.split('.').try_fold(fields, |value, part| value.get(part))?;
+  value_text(value).filter(|text| !text.trim().is_empty())
+}
+
+fn value_text(value: &Value) -> Option<String> {
+  match value {
+    Value::Null => None,
+    Value::String(s) => Some(s.clone()),
+    Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+    Value::Array(items) => {
+      let parts: Vec<String> = items.iter().filter_map(value_text).collect();
+      (!parts.is_empty()).then(|| parts.join(", "))
+    }
+    Value::Object(map) => ["name", "displayName", "value", "key"].iter()
+      .find_map(|k| map.get(*k).and_then(value_text))
+      .or_else(|| Some(value.to_string())),
+  }
+}
+
+/// Create a task from an issue and remember the link for pushing comments back
+pub async fn import_issue(app: &AppHandle, project_id: &str, provider: Provider, key: &str) -> Result<ImportedIssue, String> {
+  let key = key.trim();
+  if key.is_empty() {
+    return Err("Issue key is required".into());
+  }
+  let config = load_config(app, project_id, provider)?;
+  let token = require_token(provider)?;
+  let client = client()?;
+
+  let issue = match provider {
+    Provider::Jira => {
+      let config = config.as_ref().ok_or("Jira is not configured for this project")?;
+      jira::fetch_issue(&client, config, &token, key).await?
+    }
+    Provider::Linear => linear::fetch_issue(&client, &token, key).await?,
+  };
+
+  // One transaction, so a failure partway leaves no task behind to be duplicated on retry
+  let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  let existing: Option<String> = tx.query_row(
+    "SELECT l.task_id FROM task_links l JOIN tasks t ON t.id = l.task_id
+     WHERE t.project_id = ?1 AND l.provider = ?2 AND l.external_id = ?3",
+    (project_id, provider.as_str(), &issue.id),
+    |r| r.get(0)
+  ).optional().map_err(|e| e.to_string())?;
+  if let Some(task_id) = existing {
+    return Err(format!("{} is already imported as task {}", issue.key, task_id));
+  }
+
+  let mapping = config.and_then(|c| c.field_mapping).unwrap_or_else(|| FieldMapping::default_for(provider));
+  let title = field_text(&issue.fields, &mapping.title).unwrap_or_else(|| issue.key.clone());
+  let task = insert_task(&tx, project_id.to_string(), format!("{}: {}", issue.key, title), "plan".into())?;
+
+  let description = field_text(&issue.fields, &mapping.description);
+  if let Some(description) = &description {
+    tx.execute(
+      "UPDATE tasks SET description = ?1, updated_at = ?2 WHERE id = ?3",
+      (description, now_iso(), &task.id)
+    ).map_err(|e| e.to_string())?;
+  }
+
+  let extra: Vec<String> = mapping.extra.iter()
+    .filter_map(|field| field_text(&issue.fields, &field.path).map(|text| format!("- **{}**: {}", field.label, text)))
+    .collect();
+  let mut note = format!("Imported from {} ({})", issue.key, issue.url);
+  if !extra.is_empty() {
+    note.push_str("\n\n");
+    note.push_str(&extra.join("\n"));
+  }
+  insert_attachment(&tx, &task.id, "Issue fields", "note", None, &note)?;
+
+  let link = TaskLink {
+    task_id: task.id.clone(),
+    provider: provider.as_str().to_string(),
+    external_id: issue.id,
+    external_key: issue.key,
+    url: issue.url,
+    created_at: now_iso(),
+  };
+  tx.execute(
+    "INSERT INTO task_links (task_id, provider, external_id, external_key, url, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    (&link.task_id, &link.provider, &link.external_id, &link.external_key, &link.url, &link.created_at)
+  ).map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())?;
+
+  let task = Task { description, ..task };
+  tracing::info!(provider = provider.as_str(), key = %link.external_key, "imported issue");
+  Ok(ImportedIssue { task, link })
+}
+
+pub fn task_links(app: &AppHandle, task_id: &str) -> Result<Vec<TaskLink>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(
+    "SELECT task_id, provider, external_id, external_key, url, created_at FROM task_links WHERE task_id = ?1 ORDER BY created_at"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([task_id], |r| Ok(TaskLink {
+    task_id: r.get(0)?,
+    provider: r.get(1)?,
+    external_id: r.get(2)?,
+    external_key: r.get(3)?,
+    url: r.get(4)?,
+    created_at: r.get(5)?,
+  })).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Comment body with the plan summary and latest verdict, in the provider's
+/// markup (Jira wiki markup or Linear markdown)
+fn comment_body(provider: Provider, task_title: &str, plan_summary: Option<&str>, verdict: Option<&str>) -> String {
+  let heading = |text: &str| match provider {
+    Provider::Jira => format!("h4. {}", text),
+    Provider::Linear => format!("#### {}", text),
+  };
+  let bold = |text: &str| match provider {
+    Provider::Jira => format!("*{}*", text),
+    Provider::Linear => format!("**{}**", text),
+  };
+
+  let mut body = format!("{}\n", heading(&format!("SpecTrail update: {}", task_title)));
+  if let Some(verdict) = verdict {
+    body.push_str(&format!("\n{} {}\n", bold("Verification verdict:"), verdict));
+  }
+  if let Some(summary) = plan_summary {
+    body.push_str(&format!("\n{}\n{}\n", bold("Plan summary:"), summary));
+  }
+  body
+}
+
+/// Push the plan summary and verification verdict back to each linked issue
+pub async fn push_comment(app: &AppHandle, task_id: &str) -> Result<Vec<TaskLink>, String> {
+  let links = task_links(app, task_id)?;
+  if links.is_empty() {
+    return Err("Task is not linked to a Jira or Linear issue".into());
+  }
+  let task = get_task(app.clone(), task_id.to_string())?;
+  let plan_summary = latest_artifact(app, task_id, "plan_md").ok()
+    .and_then(|plan| numbered_section(&plan.content, 1));
+  let verdict = latest_artifact(app, task_id, "verification_report").ok()
+    .map(|report| parse_verdict(&report.content).label());
+  if plan_summary.is_none() && verdict.is_none() {
+    return Err("Nothing to push yet; generate a plan or run a verification first".into());
+  }
+
+  let client = client()?;
+  for link in &links {
+    let provider = Provider::parse(&link.provider)?;
+    let token = require_token(provider)?;
+    let body = comment_body(provider, &task.title, plan_summary.as_deref(), verdict);
+    match provider {
+      Provider::Jira => {
+        let config = load_config(app, &task.project_id, provider)?
+          .ok_or("Jira is not configured for this project")?;
+        jira::add_comment(&client, &config, &token, &link.external_key, &body).await?;
+      }
+      Provider::Linear => linear::add_comment(&client, &token, &link.external_id, &body).await?,
+    }
+    tracing::info!(provider = %link.provider, key = %link.external_key, "pushed issue comment");
+  }
+  Ok(links)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn test_field_text() {
+    let fields = json!({
+      "summary": "Fix login",
+      "priority": { "name": "High", "id": "2" },
+      "labels": ["auth", "web"],
+      "labels_nodes": { "nodes": [{ "name": "bug" }] },
+      "estimate": 3,
+      "empty": null,
+    });
+    assert_eq!(field_text(&fields, "summary").unwrap(), "Fix login");
+    assert_eq!(field_text(&fields, "priority.name").unwrap(), "High");
+    assert_eq!(field_text(&fields, "priority").unwrap(), "High");
+    assert_eq!(field_text(&fields, "labels").unwrap(), "auth, web");
+    assert_eq!(field_text(&fields, "labels_nodes.nodes").unwrap(), "bug");
+    assert_eq!(field_text(&fields, "estimate").unwrap(), "3");
+    assert!(field_text(&fields, "empty").is_none());
+    assert!(field_text(&fields, "missing.path").is_none());
+  }
+
+  #[test]
+  fn test_comment_body_markup() {
+    let jira = comment_body(Provider::Jira, "Fix login", Some("Use OAuth."), Some("Matches"));
+    assert!(jira.starts_with("h4. SpecTrail update: Fix login"));
+    assert!(jira.contains("*Verification verdict:* Matches"));
+
+    let linear = comment_body(Provider::Linear, "Fix login", None, Some("Does not match"));
+    assert!(linear.starts_with("#### SpecTrail update"));
+    assert!(!linear.contains("Plan summary"));
+  }
+}
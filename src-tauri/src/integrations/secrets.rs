@@ -0,0 +1,27 @@
+use keyring::Entry;
+
+const SERVICE: &str = "spectrail";
+
+fn entry(provider: &str) -> Result<Entry, String> {
+  Entry::new(SERVICE, &format!("{}-token", provider)).map_err(|e| e.to_string())
+}
+
+/// Store an API token in the OS keychain rather than the settings table
+pub fn set_token(provider: &str, token: &str) -> Result<(), String> {
+  entry(provider)?.set_password(token).map_err(|e| format!("Cannot save token to keychain: {}", e))
+}
+
+pub fn get_token(provider: &str) -> Result<Option<String>, String> {
+  match entry(provider)?.get_password() {
+    Ok(token) => Ok(Some(token)),
+    Err(keyring::Error::NoEntry) => Ok(None),
+    Err(e) => Err(format!("Cannot read token from keychain: {}", e)),
+  }
+}
+
+pub fn delete_token(provider: &str) -> Result<(), String> {
+  match entry(provider)?.delete_credential() {
+    Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+    Err(e) => Err(format!("Cannot remove token from keychain: {}", e)),
+  }
+}
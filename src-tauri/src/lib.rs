@@ -1,43 +1,124 @@
+mod annotations;
+mod backups;
+mod bindings;
+mod blobs;
+mod cancellation;
+mod clones;
 mod commands;
+mod companion;
+mod compression;
 mod db;
+mod demo;
+mod diagnostics;
+mod editor;
+mod email;
+mod encryption;
+mod events;
+mod export;
+mod feedback;
+mod integrations;
+mod lineage;
 mod llm;
+mod logs;
 mod models;
+mod offline;
+mod onboarding;
+mod project_artifacts;
+mod quick_capture;
+mod render;
 mod repo_tools;
+mod sarif;
+mod sessions;
+mod settings;
+mod slack;
+mod support;
+mod sync;
+mod tags;
+mod tasks;
+mod telemetry;
+mod transcript;
+mod tray;
+mod updater;
+mod windows;
 mod workflows;
+mod workspaces;
+
+use tauri::Manager;
 
 pub fn run() {
+  let bindings = bindings::builder();
+  #[cfg(debug_assertions)]
+  if let Err(e) = bindings::export(&bindings) {
+    eprintln!("failed to export TypeScript bindings: {}", e);
+  }
+
   tauri::Builder::default()
     .plugin(tauri_plugin_dialog::init())
+    .plugin(updater::plugin().build())
+    .plugin(tauri_plugin_clipboard_manager::init())
+    .plugin(quick_capture::plugin())
+    .manage(settings::SettingsCache::default())
+    .manage(windows::WindowSubscriptions::default())
+    .manage(companion::CompanionState::default())
+    .manage(offline::OfflineState::default())
+    .manage(cancellation::RunCancellations::default())
+    .manage(repo_tools::quota::ToolQuotas::default())
+    .manage(repo_tools::processes::RunningCommands::default())
+    .manage(repo_tools::repo_lock::RepoLocks::default())
+    .manage(backups::IntegrityState::default())
+    .manage(sync::SyncRunning::default())
     .setup(|app| {
       let app_handle = app.handle();
+      // Logging is best-effort; the app still runs if the log file can't be opened
+      match logs::init_tracing(&app_handle) {
+        Ok(guard) => { app.manage(guard); }
+        Err(e) => eprintln!("failed to initialize logging: {}", e),
+      }
+      diagnostics::install_panic_hook(&app_handle);
       db::init_db(&app_handle)?;
+      if let Err(e) = encryption::init(&app_handle) {
+        tracing::error!("failed to load the database key: {}", e);
+      }
+      if let Err(e) = sessions::close_stale_sessions(&app_handle) {
+        tracing::warn!("failed to close stale task sessions: {}", e);
+      }
+      if let Err(e) = tray::init_tray(&app_handle) {
+        tracing::warn!("failed to create tray icon: {}", e);
+      }
+      if let Err(e) = quick_capture::register_shortcut(&app_handle) {
+        tracing::warn!("failed to register quick capture shortcut: {}", e);
+      }
+      match repo_tools::plugins::load_plugins(&app_handle) {
+        Ok(list) => for e in &list.errors {
+          tracing::warn!("skipped tool plugin {}", e);
+        },
+        Err(e) => tracing::warn!("failed to load tool plugins: {}", e),
+      }
+      offline::resume_queue(&app_handle);
+      workflows::drift::start_schedule(&app_handle);
+      let companion_handle = app_handle.clone();
+      tauri::async_runtime::spawn(async move {
+        if let Err(e) = companion::restart(&companion_handle).await {
+          tracing::warn!("failed to start companion API: {}", e);
+        }
+      });
       Ok(())
     })
-    .invoke_handler(tauri::generate_handler![
-      commands::db_health,
-      commands::list_projects,
-      commands::create_project,
-      commands::touch_project,
-      commands::get_project,
-      commands::list_tasks,
-      commands::create_task,
-      commands::get_task,
-      commands::list_runs,
-      commands::create_run,
-      commands::list_messages,
-      commands::add_message,
-      commands::list_artifacts,
-      commands::upsert_artifact,
-      commands::get_settings,
-      commands::get_setting,
-      commands::set_setting,
-      commands::set_settings,
-      commands::list_tool_calls_cmd,
-      commands::execute_repo_tool,
-      commands::get_repo_tool_schemas,
-      commands::generate_plan_command,
-      commands::verify_task_command,
-    ])
+    .on_window_event(|window, event| {
+      // Closing the main window hides it so runs can be followed from the tray.
+      // Without a tray icon nothing could bring it back, so it closes as usual.
+      if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+        if window.label() == "main" && window.app_handle().try_state::<tray::TrayState>().is_some() {
+          api.prevent_close();
+          let _ = window.hide();
+          // A hidden window isn't being worked in; stop timing its task
+          if let Err(e) = sessions::close_view(window.app_handle(), "main") {
+            tracing::warn!("failed to close task session: {}", e);
+          }
+        }
+      }
+    })
+    .invoke_handler(bindings.invoke_handler())
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
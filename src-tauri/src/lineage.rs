@@ -0,0 +1,168 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::models::Run;
+
+/// The run a new run reruns, retries or continues
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+pub struct RunParent {
+  pub run_id: String,
+  /// rerun|retry|regenerate|debate
+  pub relation: String,
+}
+
+impl RunParent {
+  pub fn new(run_id: &str, relation: &str) -> Self {
+    RunParent { run_id: run_id.to_string(), relation: relation.to_string() }
+  }
+}
+
+/// A run and the runs descended from it, oldest first
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RunNode {
+  #[serde(flatten)]
+  pub run: Run,
+  pub children: Vec<RunNode>,
+}
+
+impl RunNode {
+  pub fn leaf(run: Run) -> Self {
+    RunNode { run, children: vec![] }
+  }
+}
+
+/// The task's runs, newest first
+pub fn task_runs(conn: &Connection, task_id: &str) -> Result<Vec<Run>, String> {
+  let mut stmt = conn.prepare(
+    "SELECT id, task_id, phase_id, run_type, provider, model, started_at, ended_at, error, prompt_tokens, completion_tokens, cost_usd, seed,
+            parent_run_id, parent_relation, cancelled_at
+     FROM runs WHERE task_id = ?1 ORDER BY started_at DESC"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([task_id], |r| {
+    Ok(Run {
+      id: r.get(0)?,
+      task_id: r.get(1)?,
+      phase_id: r.get(2)?,
+      run_type: r.get(3)?,
+      provider: r.get(4)?,
+      model: r.get(5)?,
+      started_at: r.get(6)?,
+      ended_at: r.get(7)?,
+      error: r.get(8)?,
+      prompt_tokens: r.get(9)?,
+      completion_tokens: r.get(10)?,
+      cost_usd: r.get(11)?,
+      seed: r.get(12)?,
+      parent_run_id: r.get(13)?,
+      parent_relation: r.get(14)?,
+      cancelled_at: r.get(15)?,
+    })
+  }).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn build_node(run: Run, children: &mut HashMap<String, Vec<Run>>) -> RunNode {
+  let mut kids = children.remove(&run.id).unwrap_or_default();
+  kids.reverse();
+  RunNode { children: kids.into_iter().map(|kid| build_node(kid, children)).collect(), run }
+}
+
+/// Group runs (newest first) under the runs they descend from. Runs whose
+/// parent isn't in the list are roots and keep their order.
+pub fn build_forest(runs: Vec<Run>) -> Vec<RunNode> {
+  let ids: HashSet<String> = runs.iter().map(|r| r.id.clone()).collect();
+  let mut roots = vec![];
+  let mut children: HashMap<String, Vec<Run>> = HashMap::new();
+  for run in runs {
+    match run.parent_run_id.clone().filter(|parent| ids.contains(parent) && *parent != run.id) {
+      Some(parent) => children.entry(parent).or_default().push(run),
+      None => roots.push(run),
+    }
+  }
+  let mut forest: Vec<RunNode> = roots.into_iter().map(|run| build_node(run, &mut children)).collect();
+  // Only a cycle in the links leaves runs unclaimed; show them rather than drop them
+  forest.extend(children.into_values().flatten().map(RunNode::leaf));
+  forest
+}
+
+/// The whole tree the run belongs to, from its oldest ancestor down
+pub fn run_lineage(app: &AppHandle, run_id: &str) -> Result<RunNode, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let task_id: String = conn.query_row("SELECT task_id FROM runs WHERE id = ?1", [run_id], |r| r.get(0))
+    .map_err(|e| match e {
+      rusqlite::Error::QueryReturnedNoRows => format!("Run {} not found", run_id),
+      e => e.to_string(),
+    })?;
+  let runs = task_runs(&conn, &task_id)?;
+
+  let parents: HashMap<&str, &str> = runs.iter()
+    .filter_map(|r| r.parent_run_id.as_deref().map(|parent| (r.id.as_str(), parent)))
+    .collect();
+  let mut root = run_id.to_string();
+  let mut seen = HashSet::from([root.clone()]);
+  while let Some(parent) = parents.get(root.as_str()).filter(|p| runs.iter().any(|r| r.id == **p)) {
+    if !seen.insert(parent.to_string()) {
+      break;
+    }
+    root = parent.to_string();
+  }
+
+  build_forest(runs).into_iter()
+    .find(|node| node.run.id == root)
+    .ok_or_else(|| format!("Run {} not found", run_id))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn run(id: &str, started_at: &str, parent: Option<&str>) -> Run {
+    Run {
+      id: id.into(),
+      task_id: "t1".into(),
+      phase_id: None,
+      run_type: "plan".into(),
+      provider: None,
+      model: None,
+      started_at: started_at.into(),
+      ended_at: None,
+      error: None,
+      prompt_tokens: 0,
+      completion_tokens: 0,
+      cost_usd: 0.0,
+      seed: None,
+      parent_run_id: parent.map(str::to_string),
+      parent_relation: parent.map(|_| "retry".to_string()),
+      cancelled_at: None,
+    }
+  }
+
+  #[test]
+  fn test_build_forest() {
+    // Newest first, as list_runs returns them
+    let runs = vec![
+      run("r5", "2026-05-05", Some("r2")),
+      run("r4", "2026-05-04", Some("r1")),
+      run("r3", "2026-05-03", None),
+      run("r2", "2026-05-02", Some("r1")),
+      run("r1", "2026-05-01", None),
+      run("r0", "2026-04-30", Some("deleted")),
+    ];
+    let forest = build_forest(runs);
+    let roots: Vec<&str> = forest.iter().map(|n| n.run.id.as_str()).collect();
+    assert_eq!(roots, ["r3", "r1", "r0"]);
+
+    let r1 = &forest[1];
+    let children: Vec<&str> = r1.children.iter().map(|n| n.run.id.as_str()).collect();
+    assert_eq!(children, ["r2", "r4"]);
+    assert_eq!(r1.children[0].children[0].run.id, "r5");
+
+    let json = serde_json::to_value(&forest[1]).unwrap();
+    assert_eq!(json["id"], "r1");
+    assert_eq!(json["children"][0]["parent_relation"], "retry");
+  }
+}
@@ -0,0 +1,409 @@
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::llm::types::*;
+
+/// Messages API version sent with every request
+pub const API_VERSION: &str = "2023-06-01";
+
+/// Build a Messages API request. System messages before the conversation
+/// become the `system` prompt and later ones (like budget nudges) become user
+/// text; assistant tool calls become `tool_use` blocks and tool messages
+/// `tool_result` blocks. Consecutive messages of the same role are merged,
+/// since the API expects user and assistant turns to alternate.
+pub fn build_request(
+    config: &LlmConfig,
+    messages: Vec<ChatMessage>,
+    tools: Vec<Value>,
+    options: ChatOptions,
+    stream: bool,
+) -> Value {
+    let mut system: Vec<String> = vec![];
+    let mut turns: Vec<(String, Vec<Value>)> = vec![];
+
+    for message in messages {
+        let content = message.content.unwrap_or_default();
+        let (role, blocks) = match message.role.as_str() {
+            "system" if turns.is_empty() => {
+                if !content.is_empty() {
+                    system.push(content);
+                }
+                continue;
+            }
+            "assistant" => {
+                let mut blocks = text_block(content);
+                for call in message.tool_calls.unwrap_or_default() {
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.function.name,
+                        "input": tool_input(&call.function.arguments),
+                    }));
+                }
+                ("assistant", blocks)
+            }
+            "tool" => ("user", vec![json!({
+                "type": "tool_result",
+                "tool_use_id": message.tool_call_id.unwrap_or_default(),
+                "content": content,
+            })]),
+            _ => ("user", text_block(content)),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+        match turns.last_mut() {
+            Some((last_role, last_blocks)) if last_role == role => last_blocks.extend(blocks),
+            _ => turns.push((role.to_string(), blocks)),
+        }
+    }
+
+    let mut request = json!({
+        "model": config.model,
+        "max_tokens": config.max_tokens,
+        // The API accepts 0 to 1, while OpenAI-style settings go up to 2
+        "temperature": config.temperature.clamp(0.0, 1.0),
+        "messages": turns.into_iter()
+            .map(|(role, content)| json!({ "role": role, "content": content }))
+            .collect::<Vec<_>>(),
+        "stream": stream,
+    });
+    if !system.is_empty() {
+        request["system"] = Value::String(system.join("\n\n"));
+    }
+    // Like the OpenAI request, tool controls are only sent along with tools
+    if !tools.is_empty() {
+        request["tools"] = Value::Array(tools.iter().filter_map(tool_definition).collect());
+        let mut choice = Map::new();
+        let choice_type = match options.tool_choice {
+            Some(ToolChoice::Required) => "any",
+            Some(ToolChoice::None) => "none",
+            Some(ToolChoice::Auto) | None => "auto",
+        };
+        choice.insert("type".into(), choice_type.into());
+        if options.parallel_tool_calls == Some(false) && choice_type != "none" {
+            choice.insert("disable_parallel_tool_use".into(), true.into());
+        }
+        if options.tool_choice.is_some() || choice.len() > 1 {
+            request["tool_choice"] = Value::Object(choice);
+        }
+    }
+    request
+}
+
+/// The API rejects empty text blocks
+fn text_block(text: String) -> Vec<Value> {
+    if text.is_empty() {
+        vec![]
+    } else {
+        vec![json!({ "type": "text", "text": text })]
+    }
+}
+
+/// `tool_use` input must be an object; arguments that don't parse as one are
+/// passed through under `arguments`, which the tool then rejects as it would
+/// the original
+fn tool_input(arguments: &str) -> Value {
+    match serde_json::from_str::<Value>(arguments) {
+        Ok(value @ Value::Object(_)) => value,
+        _ if arguments.trim().is_empty() => json!({}),
+        _ => json!({ "arguments": arguments }),
+    }
+}
+
+/// An OpenAI-style `{"type": "function", "function": {...}}` schema as a Messages API tool
+fn tool_definition(schema: &Value) -> Option<Value> {
+    let function = schema.get("function")?;
+    Some(json!({
+        "name": function.get("name")?,
+        "description": function.get("description").cloned().unwrap_or_else(|| "".into()),
+        "input_schema": function.get("parameters").cloned()
+            .unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<MessagesUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text { text: String },
+    ToolUse { id: String, name: String, input: Value },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MessagesUsage {
+    #[serde(default)]
+    input_tokens: Option<i64>,
+    #[serde(default)]
+    output_tokens: Option<i64>,
+}
+
+fn tool_call(id: String, name: String, arguments: String) -> ToolCall {
+    ToolCall { id, call_type: "function".into(), function: ToolFunction { name, arguments } }
+}
+
+pub fn parse_response(body: Value) -> Result<LlmResponse, LlmError> {
+    let response: MessagesResponse = serde_json::from_value(body)
+        .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+
+    let mut content = String::new();
+    let mut tool_calls = vec![];
+    for block in response.content {
+        match block {
+            ContentBlock::Text { text } => content.push_str(&text),
+            ContentBlock::ToolUse { id, name, input } => tool_calls.push(tool_call(id, name, input.to_string())),
+            ContentBlock::Other => {}
+        }
+    }
+    Ok(LlmResponse {
+        content: if content.is_empty() { None } else { Some(content) },
+        tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+        usage: response.usage.map(|u| Usage {
+            prompt_tokens: u.input_tokens.unwrap_or_default(),
+            completion_tokens: u.output_tokens.unwrap_or_default(),
+        }),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart { message: StreamMessage },
+    ContentBlockStart { index: usize, content_block: ContentBlock },
+    ContentBlockDelta { index: usize, delta: BlockDelta },
+    MessageDelta {
+        #[serde(default)]
+        usage: Option<MessagesUsage>,
+    },
+    Error { error: StreamError },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: Option<MessagesUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BlockDelta {
+    TextDelta { text: String },
+    InputJsonDelta { partial_json: String },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamError {
+    #[serde(rename = "type")]
+    error_type: String,
+    message: String,
+}
+
+/// Reassembles Messages API stream events into a complete response
+#[derive(Default)]
+pub struct StreamAccumulator {
+    content: String,
+    /// Tool calls by content block index
+    tool_calls: BTreeMap<usize, ToolCall>,
+    usage: Option<Usage>,
+}
+
+impl StreamAccumulator {
+    /// Feed one SSE line; returns any new content fragment. The `event:` lines
+    /// are skipped, since each `data:` payload names its own type.
+    pub fn feed_line(&mut self, line: &str) -> Result<Option<String>, LlmError> {
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(None);
+        };
+        let data = data.trim();
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        let event: StreamEvent = serde_json::from_str(data)
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        match event {
+            StreamEvent::MessageStart { message } => {
+                if let Some(usage) = message.usage {
+                    let entry = self.usage.get_or_insert_with(Usage::default);
+                    entry.prompt_tokens = usage.input_tokens.unwrap_or_default();
+                    entry.completion_tokens = usage.output_tokens.unwrap_or_default();
+                }
+            }
+            StreamEvent::ContentBlockStart { index, content_block: ContentBlock::ToolUse { id, name, .. } } => {
+                // The block's `input` is a placeholder; the arguments arrive as deltas
+                self.tool_calls.insert(index, tool_call(id, name, String::new()));
+            }
+            StreamEvent::ContentBlockDelta { delta: BlockDelta::TextDelta { text }, .. } if !text.is_empty() => {
+                self.content.push_str(&text);
+                return Ok(Some(text));
+            }
+            StreamEvent::ContentBlockDelta { index, delta: BlockDelta::InputJsonDelta { partial_json } } => {
+                if let Some(call) = self.tool_calls.get_mut(&index) {
+                    call.function.arguments.push_str(&partial_json);
+                }
+            }
+            StreamEvent::MessageDelta { usage: Some(usage) } => {
+                let entry = self.usage.get_or_insert_with(Usage::default);
+                if let Some(tokens) = usage.input_tokens {
+                    entry.prompt_tokens = tokens;
+                }
+                if let Some(tokens) = usage.output_tokens {
+                    entry.completion_tokens = tokens;
+                }
+            }
+            StreamEvent::Error { error } => {
+                let status = if error.error_type == "overloaded_error" { 529 } else { 500 };
+                return Err(LlmError::Api { status, message: error.message });
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    pub fn finish(self) -> LlmResponse {
+        let tool_calls: Vec<ToolCall> = self.tool_calls.into_values()
+            .map(|mut call| {
+                // A tool without parameters streams no input at all
+                if call.function.arguments.is_empty() {
+                    call.function.arguments = "{}".into();
+                }
+                call
+            })
+            .collect();
+        LlmResponse {
+            content: if self.content.is_empty() { None } else { Some(self.content) },
+            tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+            usage: self.usage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: Option<&str>) -> ChatMessage {
+        ChatMessage { role: role.into(), content: content.map(str::to_string), tool_calls: None, tool_call_id: None }
+    }
+
+    fn tool_result(id: &str, content: &str) -> ChatMessage {
+        ChatMessage { tool_call_id: Some(id.into()), ..message("tool", Some(content)) }
+    }
+
+    #[test]
+    fn test_build_request() {
+        let config = LlmConfig {
+            provider_name: "anthropic".into(),
+            base_url: "https://api.anthropic.com/v1".into(),
+            model: "claude-test".into(),
+            temperature: 0.2,
+            max_tokens: 4096,
+            extra_headers: json!({}),
+            seed: None,
+        };
+        let messages = vec![
+            message("system", Some("You plan changes.")),
+            message("user", Some("Add a flag")),
+            ChatMessage {
+                tool_calls: Some(vec![
+                    tool_call("c1".into(), "grep".into(), r#"{"query":"flag"}"#.into()),
+                    tool_call("c2".into(), "git_status".into(), "".into()),
+                ]),
+                ..message("assistant", None)
+            },
+            tool_result("c1", "src/main.rs:3"),
+            tool_result("c2", "clean"),
+            message("system", Some("Two tool calls left.")),
+        ];
+        let tools = vec![json!({
+            "type": "function",
+            "function": { "name": "grep", "description": "Search", "parameters": { "type": "object" } }
+        })];
+        let options = ChatOptions { tool_choice: Some(ToolChoice::Required), parallel_tool_calls: Some(false) };
+
+        let request = build_request(&config, messages, tools, options, false);
+        assert_eq!(request["system"], "You plan changes.");
+        assert_eq!(request["max_tokens"], 4096);
+        assert_eq!(request["tools"], json!([{ "name": "grep", "description": "Search", "input_schema": { "type": "object" } }]));
+        assert_eq!(request["tool_choice"], json!({ "type": "any", "disable_parallel_tool_use": true }));
+
+        let turns = request["messages"].as_array().unwrap();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[1]["role"], "assistant");
+        assert_eq!(turns[1]["content"][0], json!({ "type": "tool_use", "id": "c1", "name": "grep", "input": { "query": "flag" } }));
+        assert_eq!(turns[1]["content"][1]["input"], json!({}));
+        // Both results and the later system message share one user turn
+        assert_eq!(turns[2]["role"], "user");
+        assert_eq!(turns[2]["content"], json!([
+            { "type": "tool_result", "tool_use_id": "c1", "content": "src/main.rs:3" },
+            { "type": "tool_result", "tool_use_id": "c2", "content": "clean" },
+            { "type": "text", "text": "Two tool calls left." },
+        ]));
+
+        let config = LlmConfig { temperature: 1.5, ..config };
+        let options = ChatOptions { tool_choice: None, parallel_tool_calls: None };
+        let request = build_request(&config, vec![message("user", Some("Hi"))], vec![], options, false);
+        assert_eq!(request["temperature"], 1.0);
+        assert!(request.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn test_parse_response_and_stream() {
+        let response = parse_response(json!({
+            "id": "msg_1",
+            "content": [
+                { "type": "text", "text": "Looking." },
+                { "type": "tool_use", "id": "t1", "name": "read_file", "input": { "path": "a.rs" } },
+            ],
+            "usage": { "input_tokens": 50, "output_tokens": 7 },
+        })).unwrap();
+        assert_eq!(response.content.as_deref(), Some("Looking."));
+        assert_eq!(response.tool_calls.unwrap()[0].function.arguments, r#"{"path":"a.rs"}"#);
+        assert_eq!(response.usage, Some(Usage { prompt_tokens: 50, completion_tokens: 7 }));
+
+        let mut acc = StreamAccumulator::default();
+        let lines = [
+            "event: message_start",
+            r#"data: {"type":"message_start","message":{"usage":{"input_tokens":120,"output_tokens":1}}}"#,
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"lo"}}"#,
+            r#"data: {"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"t1","name":"grep","input":{}}}"#,
+            r#"data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"query\""}}"#,
+            r#"data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":":\"x\"}"}}"#,
+            r#"data: {"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"t2","name":"git_status","input":{}}}"#,
+            r#"data: {"type":"content_block_stop","index":2}"#,
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":15}}"#,
+            r#"data: {"type":"message_stop"}"#,
+        ];
+        let deltas: Vec<String> = lines.iter()
+            .filter_map(|l| acc.feed_line(l).unwrap())
+            .collect();
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+
+        let response = acc.finish();
+        assert_eq!(response.content.as_deref(), Some("Hello"));
+        assert_eq!(response.usage, Some(Usage { prompt_tokens: 120, completion_tokens: 15 }));
+        let calls = response.tool_calls.unwrap();
+        assert_eq!((calls[0].id.as_str(), calls[0].function.arguments.as_str()), ("t1", r#"{"query":"x"}"#));
+        assert_eq!(calls[1].function.arguments, "{}");
+
+        let error = StreamAccumulator::default()
+            .feed_line(r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#)
+            .unwrap_err();
+        assert!(matches!(error, LlmError::Api { status: 529, .. }));
+    }
+}
@@ -1,104 +1,131 @@
 use reqwest::{Client, StatusCode};
 use serde_json::Value;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use backoff::{ExponentialBackoff, future::retry, Error as BackoffError};
 
+use crate::llm::anthropic;
+use crate::llm::provider::Provider;
 use crate::llm::types::*;
 
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest wait for the next bytes of a response. There's no overall limit,
+/// so a long stream keeps going as long as tokens keep arriving.
+const READ_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
 pub struct LlmClient {
     http: Client,
     config: LlmConfig,
     api_key: String,
+    provider: Provider,
 }
 
 impl LlmClient {
     pub fn new(config: LlmConfig, api_key: String) -> Self {
         let http = Client::builder()
-            .timeout(Duration::from_secs(120))
+            .connect_timeout(CONNECT_TIMEOUT)
+            .read_timeout(READ_IDLE_TIMEOUT)
             .build()
             .expect("Failed to build HTTP client");
-        
-        Self { http, config, api_key }
+
+        let provider = Provider::for_config(&config);
+        Self { http, config, api_key, provider }
     }
 
     pub async fn chat_with_tools(
         &self,
         messages: Vec<ChatMessage>,
         tools: Vec<Value>,
+    ) -> Result<LlmResponse, LlmError> {
+        self.chat_with_options(messages, tools, ChatOptions::default()).await
+    }
+
+    #[tracing::instrument(skip_all, fields(model = %self.config.model, tools = tools.len()))]
+    pub async fn chat_with_options(
+        &self,
+        messages: Vec<ChatMessage>,
+        tools: Vec<Value>,
+        options: ChatOptions,
     ) -> Result<LlmResponse, LlmError> {
         if self.api_key.is_empty() {
             return Err(LlmError::MissingApiKey);
         }
 
-        let request = OpenAIChatRequest {
-            model: self.config.model.clone(),
-            messages,
-            tools: Some(tools),
-            temperature: Some(self.config.temperature),
-            max_tokens: Some(self.config.max_tokens),
-            stream: false,
-        };
-
-        let url = format!("{}/chat/completions", self.config.base_url.trim_end_matches('/'));
+        let request = self.provider.build_request(&self.config, messages, tools, options, false);
+        let url = self.provider.url(&self.config.base_url);
 
         let operation = || async {
-            let mut headers = reqwest::header::HeaderMap::new();
-            headers.insert(
-                reqwest::header::AUTHORIZATION,
-                format!("Bearer {}", self.api_key).parse().unwrap(),
-            );
-            headers.insert(
-                reqwest::header::CONTENT_TYPE,
-                "application/json".parse().unwrap(),
-            );
-
-            // Add extra headers from config
-            if let Some(obj) = self.config.extra_headers.as_object() {
-                for (key, value) in obj {
-                    if let Some(val_str) = value.as_str() {
-                        if let (Ok(header_name), Ok(header_value)) = (
-                            reqwest::header::HeaderName::from_bytes(key.as_bytes()),
-                            val_str.parse::<reqwest::header::HeaderValue>()
-                        ) {
-                            headers.insert(header_name, header_value);
-                        }
-                    }
-                }
-            }
-
             let response = self.http
                 .post(&url)
-                .headers(headers)
+                .headers(self.provider.headers(&self.api_key, &self.config.extra_headers))
                 .json(&request)
                 .send()
                 .await
-                .map_err(|e| BackoffError::transient(LlmError::Http(e.to_string())))?;
+                .map_err(|e| BackoffError::transient(send_error(e)))?;
 
             let status = response.status();
 
             if status.is_success() {
-                let chat_response: OpenAIChatResponse = response
+                let body: Value = response
                     .json()
                     .await
                     .map_err(|e| BackoffError::permanent(LlmError::InvalidResponse(e.to_string())))?;
-                Ok(chat_response)
+                Ok(body)
             } else {
                 let error_text = response.text().await.unwrap_or_default();
-                match status {
-                    StatusCode::TOO_MANY_REQUESTS => Err(BackoffError::transient(LlmError::RateLimited)),
-                    StatusCode::UNAUTHORIZED => Err(BackoffError::permanent(LlmError::Api {
-                        status: 401,
-                        message: "Invalid API key".to_string(),
-                    })),
-                    _ if status.as_u16() >= 500 => Err(BackoffError::transient(LlmError::Api {
-                        status: status.as_u16(),
-                        message: error_text,
-                    })),
-                    _ => Err(BackoffError::permanent(LlmError::Api {
-                        status: status.as_u16(),
-                        message: error_text,
-                    })),
-                }
+                Err(classify_status(status, error_text))
+            }
+        };
+
+        let backoff = ExponentialBackoff {
+            initial_interval: Duration::from_millis(500),
+            max_interval: Duration::from_secs(4),
+            max_elapsed_time: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+
+        let started = Instant::now();
+        let body = retry(backoff, operation).await
+            .inspect_err(|e| tracing::warn!(error = %e, "llm request failed"))?;
+        tracing::info!(duration_ms = started.elapsed().as_millis() as u64, "llm response");
+
+        self.provider.parse_response(body)
+    }
+
+    /// Stream a completion, calling `on_delta` with each content fragment as it arrives.
+    /// Only the initial connection is retried; a stream that fails midway returns an error.
+    #[tracing::instrument(skip_all, fields(model = %self.config.model, tools = tools.len(), stream = true))]
+    pub async fn chat_stream<F>(
+        &self,
+        messages: Vec<ChatMessage>,
+        tools: Vec<Value>,
+        options: ChatOptions,
+        mut on_delta: F,
+    ) -> Result<LlmResponse, LlmError>
+    where
+        F: FnMut(&str),
+    {
+        if self.api_key.is_empty() {
+            return Err(LlmError::MissingApiKey);
+        }
+
+        let request = self.provider.build_request(&self.config, messages, tools, options, true);
+        let url = self.provider.url(&self.config.base_url);
+
+        let operation = || async {
+            let response = self.http
+                .post(&url)
+                .headers(self.provider.headers(&self.api_key, &self.config.extra_headers))
+                .json(&request)
+                .send()
+                .await
+                .map_err(|e| BackoffError::transient(send_error(e)))?;
+
+            let status = response.status();
+            if status.is_success() {
+                Ok(response)
+            } else {
+                let error_text = response.text().await.unwrap_or_default();
+                Err(classify_status(status, error_text))
             }
         };
 
@@ -109,15 +136,182 @@ impl LlmClient {
             ..Default::default()
         };
 
-        let result: OpenAIChatResponse = retry(backoff, operation).await?;
+        let started = Instant::now();
+        let mut response = retry(backoff, operation).await
+            .inspect_err(|e| tracing::warn!(error = %e, "llm request failed"))?;
+
+        let mut accumulator = match self.provider {
+            Provider::OpenAi => StreamDecoder::OpenAi(StreamAccumulator::default()),
+            Provider::Anthropic => StreamDecoder::Anthropic(anthropic::StreamAccumulator::default()),
+        };
+        // Buffer raw bytes so multi-byte characters split across chunks decode correctly
+        let mut pending: Vec<u8> = vec![];
+        while let Some(chunk) = response.chunk().await.map_err(|e| LlmError::Http(e.to_string()))? {
+            pending.extend_from_slice(&chunk);
+
+            // SSE events are newline-delimited; keep any partial line for the next chunk
+            while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=pos).collect();
+                if let Some(delta) = accumulator.feed_line(String::from_utf8_lossy(&line).trim())? {
+                    on_delta(&delta);
+                }
+            }
+        }
+        if let Some(delta) = accumulator.feed_line(String::from_utf8_lossy(&pending).trim())? {
+            on_delta(&delta);
+        }
+
+        tracing::info!(duration_ms = started.elapsed().as_millis() as u64, "llm stream finished");
+        Ok(accumulator.finish())
+    }
+}
+
+/// Connection failures mean the provider (or the network) is unreachable
+fn send_error(e: reqwest::Error) -> LlmError {
+    if e.is_connect() {
+        LlmError::Offline(e.to_string())
+    } else {
+        LlmError::Http(e.to_string())
+    }
+}
+
+fn classify_status(status: StatusCode, error_text: String) -> BackoffError<LlmError> {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => BackoffError::transient(LlmError::RateLimited),
+        StatusCode::UNAUTHORIZED => BackoffError::permanent(LlmError::Api {
+            status: 401,
+            message: "Invalid API key".to_string(),
+        }),
+        _ if status.as_u16() >= 500 => BackoffError::transient(LlmError::Api {
+            status: status.as_u16(),
+            message: error_text,
+        }),
+        _ => BackoffError::permanent(LlmError::Api {
+            status: status.as_u16(),
+            message: error_text,
+        }),
+    }
+}
+
+/// A stream accumulator for the provider's event format
+enum StreamDecoder {
+    OpenAi(StreamAccumulator),
+    Anthropic(anthropic::StreamAccumulator),
+}
+
+impl StreamDecoder {
+    fn feed_line(&mut self, line: &str) -> Result<Option<String>, LlmError> {
+        match self {
+            StreamDecoder::OpenAi(acc) => acc.feed_line(line),
+            StreamDecoder::Anthropic(acc) => acc.feed_line(line),
+        }
+    }
+
+    fn finish(self) -> LlmResponse {
+        match self {
+            StreamDecoder::OpenAi(acc) => acc.finish(),
+            StreamDecoder::Anthropic(acc) => acc.finish(),
+        }
+    }
+}
+
+/// Reassembles streamed OpenAI deltas into a complete response
+#[derive(Default)]
+struct StreamAccumulator {
+    content: String,
+    tool_calls: Vec<ToolCall>,
+    usage: Option<Usage>,
+}
+
+impl StreamAccumulator {
+    /// Feed one SSE line; returns any new content fragment
+    fn feed_line(&mut self, line: &str) -> Result<Option<String>, LlmError> {
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(None);
+        };
+        let data = data.trim();
+        if data.is_empty() || data == "[DONE]" {
+            return Ok(None);
+        }
+
+        let chunk: OpenAIStreamChunk = serde_json::from_str(data)
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        // Usage arrives in a final chunk with no choices
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+        let Some(choice) = chunk.choices.into_iter().next() else {
+            return Ok(None);
+        };
+
+        for delta in choice.delta.tool_calls.unwrap_or_default() {
+            while self.tool_calls.len() <= delta.index {
+                self.tool_calls.push(ToolCall {
+                    id: String::new(),
+                    call_type: "function".into(),
+                    function: ToolFunction { name: String::new(), arguments: String::new() },
+                });
+            }
+            let call = &mut self.tool_calls[delta.index];
+            if let Some(id) = delta.id {
+                call.id = id;
+            }
+            if let Some(function) = delta.function {
+                if let Some(name) = function.name {
+                    call.function.name.push_str(&name);
+                }
+                if let Some(arguments) = function.arguments {
+                    call.function.arguments.push_str(&arguments);
+                }
+            }
+        }
+
+        match choice.delta.content {
+            Some(content) if !content.is_empty() => {
+                self.content.push_str(&content);
+                Ok(Some(content))
+            }
+            _ => Ok(None),
+        }
+    }
 
-        if let Some(choice) = result.choices.into_iter().next() {
-            Ok(LlmResponse {
-                content: choice.message.content,
-                tool_calls: choice.message.tool_calls,
-            })
-        } else {
-            Err(LlmError::InvalidResponse("No choices in response".to_string()))
+    fn finish(self) -> LlmResponse {
+        LlmResponse {
+            content: if self.content.is_empty() { None } else { Some(self.content) },
+            tool_calls: if self.tool_calls.is_empty() { None } else { Some(self.tool_calls) },
+            usage: self.usage,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_accumulator() {
+        let mut acc = StreamAccumulator::default();
+        let lines = [
+            r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":"lo"}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"c1","function":{"name":"grep","arguments":"{\"query\""}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":":\"x\"}"}}]}}]}"#,
+            ": keep-alive",
+            r#"data: {"choices":[],"usage":{"prompt_tokens":120,"completion_tokens":8,"total_tokens":128}}"#,
+            "data: [DONE]",
+        ];
+
+        let deltas: Vec<String> = lines.iter()
+            .filter_map(|l| acc.feed_line(l).unwrap())
+            .collect();
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+
+        let response = acc.finish();
+        assert_eq!(response.content.as_deref(), Some("Hello"));
+        assert_eq!(response.usage, Some(Usage { prompt_tokens: 120, completion_tokens: 8 }));
+        let calls = response.tool_calls.unwrap();
+        assert_eq!(calls[0].id, "c1");
+        assert_eq!(calls[0].function.name, "grep");
+        assert_eq!(calls[0].function.arguments, r#"{"query":"x"}"#);
+    }
+}
@@ -0,0 +1,70 @@
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::llm::types::{LlmError, Usage};
+
+#[derive(Debug, Clone)]
+pub struct EmbeddingConfig {
+    pub base_url: String,
+    pub model: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+/// Embed `inputs` with an OpenAI-compatible `/embeddings` endpoint; vectors
+/// come back in input order, with the request's usage when the provider reports it
+pub async fn embed(
+    config: &EmbeddingConfig,
+    api_key: &str,
+    inputs: &[String],
+) -> Result<(Vec<Vec<f32>>, Option<Usage>), LlmError> {
+    if api_key.is_empty() {
+        return Err(LlmError::MissingApiKey);
+    }
+    if inputs.is_empty() {
+        return Ok((vec![], None));
+    }
+
+    let http = Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| LlmError::Http(e.to_string()))?;
+    let url = format!("{}/embeddings", config.base_url.trim_end_matches('/'));
+
+    let response = http
+        .post(&url)
+        .bearer_auth(api_key)
+        .json(&json!({ "model": config.model, "input": inputs }))
+        .send()
+        .await
+        .map_err(|e| LlmError::Http(e.to_string()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(LlmError::Api { status: status.as_u16(), message });
+    }
+
+    let mut parsed: EmbeddingResponse = response.json().await
+        .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+    if parsed.data.len() != inputs.len() {
+        return Err(LlmError::InvalidResponse(format!(
+            "Expected {} embeddings, got {}", inputs.len(), parsed.data.len()
+        )));
+    }
+    parsed.data.sort_by_key(|d| d.index);
+    Ok((parsed.data.into_iter().map(|d| d.embedding).collect(), parsed.usage))
+}
@@ -1,4 +1,8 @@
+pub mod anthropic;
 pub mod client;
+pub mod embeddings;
+pub mod provider;
+pub mod transcribe;
 pub mod types;
 
 pub use client::LlmClient;
@@ -0,0 +1,150 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use serde_json::Value;
+
+use crate::llm::anthropic;
+use crate::llm::types::*;
+
+/// The API a provider speaks. Messages and tool schemas are kept in the
+/// OpenAI shape throughout the app and translated here.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provider {
+    /// OpenAI chat completions, which most providers and local servers accept
+    OpenAi,
+    /// The Anthropic Messages API
+    Anthropic,
+}
+
+impl Provider {
+    /// Picked by `provider_name`, or by the base URL for the Anthropic API itself
+    pub fn for_config(config: &LlmConfig) -> Self {
+        let provider = config.provider_name.to_lowercase();
+        if provider.contains("anthropic") || provider.contains("claude") || config.base_url.contains("api.anthropic.com") {
+            Provider::Anthropic
+        } else {
+            Provider::OpenAi
+        }
+    }
+
+    pub fn url(&self, base_url: &str) -> String {
+        let base_url = base_url.trim_end_matches('/');
+        match self {
+            Provider::OpenAi => format!("{}/chat/completions", base_url),
+            Provider::Anthropic => format!("{}/messages", base_url),
+        }
+    }
+
+    /// Authentication and content headers, followed by the configured extra headers
+    pub fn headers(&self, api_key: &str, extra_headers: &Value) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        match self {
+            Provider::OpenAi => {
+                headers.insert(AUTHORIZATION, format!("Bearer {}", api_key).parse().unwrap());
+            }
+            Provider::Anthropic => {
+                if let Ok(value) = api_key.parse() {
+                    headers.insert("x-api-key", value);
+                }
+                headers.insert("anthropic-version", HeaderValue::from_static(anthropic::API_VERSION));
+            }
+        }
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+
+        if let Some(obj) = extra_headers.as_object() {
+            for (key, value) in obj {
+                if let Some(val_str) = value.as_str() {
+                    if let (Ok(header_name), Ok(header_value)) = (
+                        HeaderName::from_bytes(key.as_bytes()),
+                        val_str.parse::<HeaderValue>()
+                    ) {
+                        headers.insert(header_name, header_value);
+                    }
+                }
+            }
+        }
+
+        headers
+    }
+
+    pub fn build_request(
+        &self,
+        config: &LlmConfig,
+        messages: Vec<ChatMessage>,
+        tools: Vec<Value>,
+        options: ChatOptions,
+        stream: bool,
+    ) -> Value {
+        match self {
+            Provider::OpenAi => {
+                // Some providers reject an empty tools array (and tool controls without tools),
+                // so omit them entirely
+                let has_tools = !tools.is_empty();
+                let request = OpenAIChatRequest {
+                    model: config.model.clone(),
+                    messages,
+                    tools: if has_tools { Some(tools) } else { None },
+                    tool_choice: options.tool_choice.filter(|_| has_tools),
+                    parallel_tool_calls: options.parallel_tool_calls.filter(|_| has_tools),
+                    temperature: Some(config.temperature),
+                    max_tokens: Some(config.max_tokens),
+                    seed: config.seed,
+                    stream,
+                    stream_options: stream.then_some(StreamOptions { include_usage: true }),
+                };
+                serde_json::to_value(request).expect("chat request serializes")
+            }
+            Provider::Anthropic => anthropic::build_request(config, messages, tools, options, stream),
+        }
+    }
+
+    pub fn parse_response(&self, body: Value) -> Result<LlmResponse, LlmError> {
+        match self {
+            Provider::OpenAi => {
+                let response: OpenAIChatResponse = serde_json::from_value(body)
+                    .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+                let choice = response.choices.into_iter().next()
+                    .ok_or_else(|| LlmError::InvalidResponse("No choices in response".to_string()))?;
+                Ok(LlmResponse {
+                    content: choice.message.content,
+                    tool_calls: choice.message.tool_calls,
+                    usage: response.usage,
+                })
+            }
+            Provider::Anthropic => anthropic::parse_response(body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_provider_for_config() {
+        let mut config = LlmConfig {
+            provider_name: "router".into(),
+            base_url: "https://api.openai.com/v1/".into(),
+            model: "m".into(),
+            temperature: 0.0,
+            max_tokens: 100,
+            extra_headers: json!({ "x-team": "a" }),
+            seed: None,
+        };
+        let provider = Provider::for_config(&config);
+        assert_eq!(provider, Provider::OpenAi);
+        assert_eq!(provider.url(&config.base_url), "https://api.openai.com/v1/chat/completions");
+
+        config.base_url = "https://api.anthropic.com/v1".into();
+        let provider = Provider::for_config(&config);
+        assert_eq!(provider, Provider::Anthropic);
+        assert_eq!(provider.url(&config.base_url), "https://api.anthropic.com/v1/messages");
+        let headers = provider.headers("sk-test", &config.extra_headers);
+        assert_eq!(headers["x-api-key"], "sk-test");
+        assert_eq!(headers["x-team"], "a");
+        assert!(!headers.contains_key(AUTHORIZATION));
+
+        config.provider_name = "Claude via proxy".into();
+        config.base_url = "https://llm.internal/v1".into();
+        assert_eq!(Provider::for_config(&config), Provider::Anthropic);
+    }
+}
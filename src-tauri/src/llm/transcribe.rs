@@ -0,0 +1,75 @@
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::llm::types::LlmError;
+
+const MAX_AUDIO_BYTES: u64 = 25 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct TranscriptionConfig {
+    pub base_url: String,
+    pub model: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+/// Send an audio file to a Whisper-compatible `/audio/transcriptions` endpoint
+pub async fn transcribe_file(
+    config: &TranscriptionConfig,
+    api_key: &str,
+    path: &Path,
+) -> Result<String, LlmError> {
+    if api_key.is_empty() {
+        return Err(LlmError::MissingApiKey);
+    }
+
+    // Checked before reading, so an oversized file never ends up in memory
+    let size = tokio::fs::metadata(path).await
+        .map_err(|e| LlmError::Http(format!("Cannot read audio file: {}", e)))?
+        .len();
+    if size > MAX_AUDIO_BYTES {
+        return Err(LlmError::InvalidResponse(format!(
+            "Audio file is too large ({} bytes, max {})", size, MAX_AUDIO_BYTES
+        )));
+    }
+    let audio = tokio::fs::read(path).await
+        .map_err(|e| LlmError::Http(format!("Cannot read audio file: {}", e)))?;
+
+    let file_name = path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "audio".to_string());
+    let form = Form::new()
+        .text("model", config.model.clone())
+        .part("file", Part::bytes(audio).file_name(file_name));
+
+    let http = Client::builder()
+        .timeout(Duration::from_secs(300))
+        .build()
+        .map_err(|e| LlmError::Http(e.to_string()))?;
+    let url = format!("{}/audio/transcriptions", config.base_url.trim_end_matches('/'));
+
+    let response = http
+        .post(&url)
+        .bearer_auth(api_key)
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| LlmError::Http(e.to_string()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(LlmError::Api { status: status.as_u16(), message });
+    }
+
+    let parsed: TranscriptionResponse = response.json().await
+        .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+    Ok(parsed.text.trim().to_string())
+}
+
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use specta::Type;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -11,7 +12,7 @@ pub struct ChatMessage {
     pub tool_call_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ToolCall {
     pub id: String,
     #[serde(rename = "type")]
@@ -19,7 +20,7 @@ pub struct ToolCall {
     pub function: ToolFunction,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ToolFunction {
     pub name: String,
     pub arguments: String,
@@ -33,6 +34,24 @@ pub struct LlmConfig {
     pub temperature: f64,
     pub max_tokens: i64,
     pub extra_headers: Value,
+    /// Sampling seed for providers that support deterministic sampling
+    #[serde(default)]
+    pub seed: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChoice {
+    Auto,
+    Required,
+    None,
+}
+
+/// Per-request tool calling controls
+#[derive(Debug, Clone, Default)]
+pub struct ChatOptions {
+    pub tool_choice: Option<ToolChoice>,
+    pub parallel_tool_calls: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -42,10 +61,33 @@ pub struct OpenAIChatRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
     pub stream: bool,
+    /// Ask streaming providers to send token usage in the final chunk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamOptions {
+    pub include_usage: bool,
+}
+
+/// Token counts reported by the provider for one request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: i64,
+    #[serde(default)]
+    pub completion_tokens: i64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -53,6 +95,8 @@ pub struct OpenAIChatResponse {
     pub id: String,
     pub model: String,
     pub choices: Vec<Choice>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -62,10 +106,43 @@ pub struct Choice {
     pub finish_reason: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAIStreamChunk {
+    pub choices: Vec<StreamChoice>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamChoice {
+    pub delta: StreamDelta,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamDelta {
+    pub content: Option<String>,
+    pub tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolCallDelta {
+    pub index: usize,
+    pub id: Option<String>,
+    pub function: Option<ToolFunctionDelta>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolFunctionDelta {
+    pub name: Option<String>,
+    pub arguments: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct LlmResponse {
     pub content: Option<String>,
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// None when the provider doesn't report usage
+    pub usage: Option<Usage>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -82,4 +159,16 @@ pub enum LlmError {
     Timeout,
     #[error("Rate limited")]
     RateLimited,
+    #[error("Cannot reach the LLM provider: {0}")]
+    Offline(String),
+}
+
+impl LlmError {
+    /// Error code reported by workflows
+    pub fn code(&self) -> &'static str {
+        match self {
+            LlmError::Offline(_) => "OFFLINE",
+            _ => "LLM_ERROR",
+        }
+    }
 }
@@ -0,0 +1,152 @@
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+const LOG_FILE_PREFIX: &str = "spectrail";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_FILTER: &str = "spectrail_lib=info";
+
+/// Keeps the background log writer alive; dropping it flushes and stops file logging
+pub struct LogGuard(#[allow(dead_code)] WorkerGuard);
+
+#[derive(Debug, Serialize, Type)]
+pub struct LogEntry {
+  pub timestamp: String,
+  pub level: String,
+  pub target: String,
+  pub message: String,
+  /// Event fields other than the message (run_id, tool, duration_ms, ...)
+  pub fields: Value,
+  /// Innermost span and its fields, if the event was inside one
+  pub span: Option<Value>,
+}
+
+pub fn logs_dir(app: &AppHandle) -> Result<PathBuf, String> {
+  let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("logs");
+  std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  Ok(dir)
+}
+
+/// Install the global subscriber: JSON lines to a daily-rotated file in app data,
+/// plus human-readable output on stderr. `SPECTRAIL_LOG` overrides the filter.
+pub fn init_tracing(app: &AppHandle) -> Result<LogGuard, String> {
+  let appender = RollingFileAppender::builder()
+    .rotation(Rotation::DAILY)
+    .filename_prefix(LOG_FILE_PREFIX)
+    .filename_suffix(LOG_FILE_SUFFIX)
+    .max_log_files(MAX_LOG_FILES)
+    .build(logs_dir(app)?)
+    .map_err(|e| e.to_string())?;
+  let (writer, guard) = tracing_appender::non_blocking(appender);
+
+  let filter = EnvFilter::try_from_env("SPECTRAIL_LOG")
+    .unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+  tracing_subscriber::registry()
+    .with(filter)
+    .with(fmt::layer().json().with_current_span(true).with_span_list(false).with_writer(writer))
+    .with(fmt::layer().with_writer(std::io::stderr))
+    .try_init()
+    .map_err(|e| e.to_string())?;
+
+  Ok(LogGuard(guard))
+}
+
+/// Most recent log entries at or above `min_level`, oldest first
+pub fn read_recent_logs(app: &AppHandle, min_level: &str, limit: usize) -> Result<Vec<LogEntry>, String> {
+  let min_rank = level_rank(min_level)
+    .ok_or_else(|| format!("Unknown log level '{}' (expected trace, debug, info, warn, error)", min_level))?;
+
+  let mut files: Vec<PathBuf> = std::fs::read_dir(logs_dir(app)?)
+    .map_err(|e| e.to_string())?
+    .flatten()
+    .map(|e| e.path())
+    .filter(|p| {
+      p.file_name()
+        .and_then(|n| n.to_str())
+        .map_or(false, |n| n.starts_with(LOG_FILE_PREFIX) && n.ends_with(LOG_FILE_SUFFIX))
+    })
+    .collect();
+  // Daily file names embed the date, so name order is chronological
+  files.sort();
+
+  let mut entries = vec![];
+  for path in files.iter().rev() {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    for line in content.lines().rev() {
+      let Some(entry) = parse_log_line(line) else { continue };
+      if level_rank(&entry.level).map_or(false, |rank| rank >= min_rank) {
+        entries.push(entry);
+        if entries.len() >= limit {
+          entries.reverse();
+          return Ok(entries);
+        }
+      }
+    }
+  }
+
+  entries.reverse();
+  Ok(entries)
+}
+
+fn level_rank(level: &str) -> Option<u8> {
+  match level.to_ascii_lowercase().as_str() {
+    "trace" => Some(0),
+    "debug" => Some(1),
+    "info" => Some(2),
+    "warn" => Some(3),
+    "error" => Some(4),
+    _ => None,
+  }
+}
+
+fn parse_log_line(line: &str) -> Option<LogEntry> {
+  let mut value: Value = serde_json::from_str(line).ok()?;
+  let obj = value.as_object_mut()?;
+
+  let mut fields = obj.remove("fields").unwrap_or_else(|| Value::Object(Default::default()));
+  let message = fields.as_object_mut()
+    .and_then(|f| f.remove("message"))
+    .and_then(|m| m.as_str().map(String::from))
+    .unwrap_or_default();
+
+  Some(LogEntry {
+    timestamp: obj.get("timestamp")?.as_str()?.to_string(),
+    level: obj.get("level")?.as_str()?.to_string(),
+    target: obj.get("target").and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+    message,
+    fields,
+    span: obj.remove("span"),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_log_line() {
+    let line = r#"{"timestamp":"2024-05-01T10:00:00Z","level":"WARN","fields":{"message":"tool failed","tool":"grep","duration_ms":12},"target":"spectrail_lib::repo_tools","span":{"run_id":"r1","name":"generate_plan"}}"#;
+    let entry = parse_log_line(line).unwrap();
+    assert_eq!(entry.level, "WARN");
+    assert_eq!(entry.message, "tool failed");
+    assert_eq!(entry.fields["tool"], "grep");
+    assert!(entry.fields.get("message").is_none());
+    assert_eq!(entry.span.unwrap()["run_id"], "r1");
+
+    assert!(parse_log_line("not json").is_none());
+  }
+
+  #[test]
+  fn test_level_rank() {
+    assert!(level_rank("WARN") > level_rank("info"));
+    assert_eq!(level_rank("verbose"), None);
+  }
+}
@@ -1,29 +1,69 @@
 use serde::{Deserialize, Serialize};
+use specta::Type;
 use uuid::Uuid;
 
 pub type ID = String;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct Project {
   pub id: ID,
   pub name: String,
   pub repo_path: String,
   pub created_at: String,
   pub last_opened_at: Option<String>,
+  pub workspace_id: Option<ID>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct Task {
   pub id: ID,
   pub project_id: ID,
   pub title: String,
+  pub description: Option<String>,
   pub mode: String,   // plan|phases|review
   pub status: String, // draft|active|done|archived
   pub created_at: String,
   pub updated_at: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct Phase {
+  pub id: ID,
+  pub task_id: ID,
+  pub idx: i64,
+  pub title: String,
+  pub description: Option<String>,
+  pub status: String, // todo|active|done
+  pub planned_files_json: String,
+  pub created_at: String,
+  pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct PhaseCard {
+  pub phase: Phase,
+  pub blocked_by: Vec<ID>, // unfinished phases this one depends on
+  pub blocks: Vec<ID>,
+  pub latest_verdict: Option<String>, // matches|partially_matches|does_not_match|unknown
+  pub latest_verified_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct PhaseColumn {
+  pub status: String,
+  pub count: usize,
+  pub phases: Vec<PhaseCard>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct PhaseBoard {
+  pub task_id: ID,
+  pub total: usize,
+  pub done: usize,
+  pub columns: Vec<PhaseColumn>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct Run {
   pub id: ID,
   pub task_id: ID,
@@ -33,9 +73,23 @@ pub struct Run {
   pub model: Option<String>,
   pub started_at: String,
   pub ended_at: Option<String>,
+  /// Set when the run failed after starting
+  pub error: Option<String>,
+  pub prompt_tokens: i64,
+  pub completion_tokens: i64,
+  /// Estimated from the configured token prices; 0 when prices aren't set
+  pub cost_usd: f64,
+  /// Sampling seed sent to the provider, if any
+  pub seed: Option<i64>,
+  /// The run this one reran, retried or continued
+  pub parent_run_id: Option<ID>,
+  /// rerun|retry|regenerate
+  pub parent_relation: Option<String>,
+  /// Set when the run was cancelled
+  pub cancelled_at: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct Message {
   pub id: ID,
   pub run_id: ID,
@@ -44,31 +98,51 @@ pub struct Message {
   pub created_at: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct Artifact {
   pub id: ID,
-  pub task_id: ID,
+  pub task_id: Option<ID>,    // None for project artifacts
+  pub project_id: Option<ID>, // set only for project artifacts
   pub phase_id: Option<ID>,
-  pub kind: String, // plan_md|phase_list|verification_report|handoff_prompt|notes
+  pub kind: String, // plan_md|phase_list|verification_report|handoff_prompt|notes; project: see PROJECT_ARTIFACT_KINDS
   pub content: String,
   pub created_at: String,
   pub pinned: i64,
+  pub draft: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct TaskAttachment {
+  pub id: ID,
+  pub task_id: ID,
+  pub name: String,
+  pub source: String, // note|file|transcript
+  pub source_path: Option<String>,
+  pub content: String,
+  pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct TranscriptionResult {
+  pub task: Task,
+  pub transcript: String,
+  pub attachment: TaskAttachment,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct SettingsKV {
   pub key: String,
   pub value: String,
   pub updated_at: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Type)]
 pub struct SettingInput {
   pub key: String,
   pub value: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct ToolCallRow {
   pub id: ID,
   pub run_id: ID,
@@ -76,6 +150,19 @@ pub struct ToolCallRow {
   pub args_json: String,
   pub result_json: String,
   pub created_at: String,
+  /// Position within the run, starting at 1
+  pub seq: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct ToolStat {
+  pub name: String,
+  pub invocations: i64,
+  pub errors: i64,
+  pub total_result_bytes: i64,
+  pub avg_result_bytes: i64,
+  pub error_rate: f64,
+  pub updated_at: String,
 }
 
 pub fn new_id() -> ID {
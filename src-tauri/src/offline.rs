@@ -0,0 +1,253 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::events::CONNECTIVITY;
+use crate::models::new_id;
+use crate::settings::{cached_settings, SettingsMap};
+use crate::lineage::RunParent;
+use crate::workflows::plan::{generate_plan_linked, generate_plan_with_seed};
+use crate::workflows::verify::{verify_task, VerifyOptions};
+
+/// Setting that forces offline mode regardless of connectivity
+pub const MANUAL_SETTING: &str = "offline_mode";
+pub const OFFLINE_MESSAGE: &str = "SpecTrail is offline. The run was queued and will start when the connection returns.";
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connectivity as detected from failed LLM requests. Repo tools never need
+/// the network, so only LLM-dependent work checks this.
+#[derive(Default)]
+pub struct OfflineState {
+  detected: AtomicBool,
+  probing: AtomicBool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+pub struct QueuedRun {
+  pub id: String,
+  pub project_id: String,
+  pub task_id: String,
+  pub run_type: String, // plan|verify
+  pub options: Value,
+  pub queued_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+pub struct OfflineStatus {
+  pub offline: bool,
+  /// Offline because the user turned on offline mode
+  pub manual: bool,
+  /// Offline because LLM requests couldn't reach the provider
+  pub detected: bool,
+  pub queued: Vec<QueuedRun>,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn detected(app: &AppHandle) -> bool {
+  app.try_state::<OfflineState>().is_some_and(|s| s.detected.load(Ordering::SeqCst))
+}
+
+fn manual(settings: &SettingsMap) -> bool {
+  settings.get(MANUAL_SETTING).map(|v| v.as_str()) == Some("1")
+}
+
+pub fn is_offline(app: &AppHandle, settings: &SettingsMap) -> bool {
+  manual(settings) || detected(app)
+}
+
+/// Remember a run to start once back online. One entry per task and run type;
+/// queueing again replaces the options.
+pub fn enqueue_run(app: &AppHandle, project_id: &str, task_id: &str, run_type: &str, options: Value) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "INSERT INTO queued_runs (id, project_id, task_id, run_type, options_json, queued_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+     ON CONFLICT(task_id, run_type) DO UPDATE SET options_json=excluded.options_json, queued_at=excluded.queued_at",
+    (new_id(), project_id, task_id, run_type, options.to_string(), now_iso())
+  ).map_err(|e| e.to_string())?;
+  tracing::info!(task_id, run_type, "queued run until connectivity returns");
+  Ok(())
+}
+
+pub fn list_queued_runs(app: &AppHandle) -> Result<Vec<QueuedRun>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(
+    "SELECT id, project_id, task_id, run_type, options_json, queued_at FROM queued_runs ORDER BY queued_at ASC"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], |r| Ok(QueuedRun {
+    id: r.get(0)?,
+    project_id: r.get(1)?,
+    task_id: r.get(2)?,
+    run_type: r.get(3)?,
+    options: serde_json::from_str(&r.get::<_, String>(4)?).unwrap_or(Value::Null),
+    queued_at: r.get(5)?,
+  })).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+pub fn cancel_queued_run(app: &AppHandle, id: &str) -> Result<(), String> {
+  claim_queued_run(app, id).map(|_| ())
+}
+
+/// Remove a queued run, returning whether this call removed it. Only the caller
+/// that claims a run may start it, so overlapping queue drains can't run it twice.
+fn claim_queued_run(app: &AppHandle, id: &str) -> Result<bool, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let removed = conn.execute("DELETE FROM queued_runs WHERE id = ?1", [id]).map_err(|e| e.to_string())?;
+  Ok(removed == 1)
+}
+
+pub async fn status(app: &AppHandle) -> Result<OfflineStatus, String> {
+  let settings = cached_settings(app).await?;
+  let (manual, detected) = (manual(&settings), detected(app));
+  Ok(OfflineStatus { offline: manual || detected, manual, detected, queued: list_queued_runs(app)? })
+}
+
+async fn emit_status(app: &AppHandle) {
+  match status(app).await {
+    Ok(status) => { let _ = CONNECTIVITY.emit(app, status); }
+    Err(e) => tracing::warn!("failed to read offline status: {}", e),
+  }
+}
+
+/// Switch to offline mode after a request couldn't reach the provider, and
+/// poll until it can be reached again
+pub fn mark_offline(app: &AppHandle) {
+  let Some(state) = app.try_state::<OfflineState>() else {
+    return;
+  };
+  if !state.detected.swap(true, Ordering::SeqCst) {
+    tracing::warn!("LLM provider unreachable; switching to offline mode");
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move { emit_status(&handle).await });
+  }
+  if !state.probing.swap(true, Ordering::SeqCst) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move { probe_until_online(handle).await });
+  }
+}
+
+/// Any HTTP response, even an error status, means the provider is reachable.
+/// Without a base URL nothing can be reached.
+async fn provider_reachable(base_url: &str) -> bool {
+  if base_url.is_empty() {
+    return false;
+  }
+  let Ok(client) = reqwest::Client::builder().timeout(PROBE_TIMEOUT).build() else {
+    return true;
+  };
+  match client.get(base_url).send().await {
+    Ok(_) => true,
+    Err(e) => !(e.is_connect() || e.is_timeout()),
+  }
+}
+
+async fn probe_until_online(app: AppHandle) {
+  loop {
+    tokio::time::sleep(PROBE_INTERVAL).await;
+    let base_url = match cached_settings(&app).await {
+      Ok(settings) => settings.get("base_url").cloned().unwrap_or_default(),
+      Err(_) => continue,
+    };
+    if provider_reachable(&base_url).await {
+      break;
+    }
+  }
+
+  if let Some(state) = app.try_state::<OfflineState>() {
+    state.detected.store(false, Ordering::SeqCst);
+    state.probing.store(false, Ordering::SeqCst);
+  }
+  tracing::info!("LLM provider reachable again; leaving offline mode");
+  emit_status(&app).await;
+  run_queued(&app).await;
+}
+
+/// Start queued runs one at a time, stopping if we go offline again.
+/// A run that fails offline re-queues itself.
+pub async fn run_queued(app: &AppHandle) {
+  loop {
+    match cached_settings(app).await {
+      Ok(settings) if !is_offline(app, &settings) => {}
+      _ => return,
+    }
+    let next = match list_queued_runs(app) {
+      Ok(runs) => runs.into_iter().next(),
+      Err(e) => {
+        tracing::warn!("failed to read queued runs: {}", e);
+        return;
+      }
+    };
+    let Some(run) = next else {
+      return;
+    };
+    match claim_queued_run(app, &run.id) {
+      Ok(true) => {}
+      // Cancelled, or started by another drain of the queue
+      Ok(false) => continue,
+      Err(e) => {
+        tracing::warn!("failed to dequeue run: {}", e);
+        return;
+      }
+    }
+
+    tracing::info!(task_id = %run.task_id, run_type = %run.run_type, "starting queued run");
+    let error = match run.run_type.as_str() {
+      "plan" => {
+        // Runs cut off by a dropped connection are retried as children of the failed run,
+        // and reruns keep the seed they were asked to replay
+        let seed = run.options["seed"].as_i64();
+        let result = match serde_json::from_value::<RunParent>(run.options["parent"].clone()) {
+          Ok(parent) => generate_plan_linked(app.clone(), run.project_id, run.task_id, seed, parent).await,
+          Err(_) => generate_plan_with_seed(app.clone(), run.project_id, run.task_id, seed).await,
+        };
+        result.err().map(|e| e.message)
+      }
+      "verify" => {
+        let options: VerifyOptions = serde_json::from_value(run.options).unwrap_or_default();
+        verify_task(app.clone(), run.project_id, run.task_id, options).await.err().map(|e| e.message)
+      }
+      other => Some(format!("unknown run type '{}'", other)),
+    };
+    if let Some(e) = error {
+      tracing::warn!("queued run failed: {}", e);
+    }
+  }
+}
+
+/// Called after the offline_mode setting changes
+pub fn on_mode_changed(app: &AppHandle) {
+  let handle = app.clone();
+  tauri::async_runtime::spawn(async move {
+    emit_status(&handle).await;
+    run_queued(&handle).await;
+  });
+}
+
+/// Runs queued before the app last quit: start them now, or wait for connectivity
+pub fn resume_queue(app: &AppHandle) {
+  if list_queued_runs(app).map_or(true, |runs| runs.is_empty()) {
+    return;
+  }
+  let handle = app.clone();
+  tauri::async_runtime::spawn(async move {
+    let base_url = match cached_settings(&handle).await {
+      Ok(settings) => settings.get("base_url").cloned().unwrap_or_default(),
+      Err(_) => return,
+    };
+    if provider_reachable(&base_url).await {
+      run_queued(&handle).await;
+    } else {
+      mark_offline(&handle);
+    }
+  });
+}
@@ -0,0 +1,188 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use specta::Type;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::commands::{create_project, create_task};
+use crate::llm::{ChatMessage, LlmClient};
+use crate::settings::cached_settings;
+use crate::workflows::plan::{build_llm_config, generate_plan, get_api_key};
+
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+const SMOKE_TASK_TITLE: &str = "Onboarding smoke test: outline the repository structure";
+
+/// Runtimes the wizard checks for, as (name, candidate binaries, required)
+const RUNTIMES: &[(&str, &[&str], bool)] = &[
+  ("git", &["git"], true),
+  ("rg", &["rg"], false),
+  ("node", &["node"], false),
+  ("cargo", &["cargo"], false),
+  ("python", &["python3", "python"], false),
+];
+
+#[derive(Debug, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+  Ok,
+  Warning,
+  Error,
+}
+
+/// Outcome of one wizard step. Failures are reported here rather than as
+/// command errors so the wizard can render every step uniformly.
+#[derive(Debug, Serialize, Type)]
+pub struct StepResult {
+  pub step: String,
+  pub status: StepStatus,
+  pub message: String,
+  pub details: Value,
+}
+
+impl StepResult {
+  fn new(step: &str, status: StepStatus, message: impl Into<String>, details: Value) -> Self {
+    Self { step: step.to_string(), status, message: message.into(), details }
+  }
+}
+
+pub async fn detect_runtimes() -> Vec<StepResult> {
+  let mut results = vec![];
+  for (name, binaries, required) in RUNTIMES {
+    let step = format!("runtime:{}", name);
+    let found = binaries.iter().find_map(|b| which::which(b).ok().map(|path| (*b, path)));
+
+    let result = match found {
+      Some((binary, path)) => {
+        let version = binary_version(binary).await;
+        StepResult::new(
+          &step,
+          StepStatus::Ok,
+          version.clone().unwrap_or_else(|| format!("{} found", name)),
+          json!({ "path": path.to_string_lossy(), "version": version }),
+        )
+      }
+      None => StepResult::new(
+        &step,
+        if *required { StepStatus::Error } else { StepStatus::Warning },
+        if *required {
+          format!("{} is required but was not found on PATH", name)
+        } else {
+          format!("{} not found; related tools will be unavailable", name)
+        },
+        json!({ "path": null, "version": null }),
+      ),
+    };
+    results.push(result);
+  }
+  results
+}
+
+/// First line of `<binary> --version`, if it runs within the timeout
+async fn binary_version(binary: &str) -> Option<String> {
+  let output = timeout(
+    VERSION_TIMEOUT,
+    Command::new(binary).arg("--version").stdout(Stdio::piped()).stderr(Stdio::piped()).output(),
+  ).await.ok()?.ok()?;
+
+  // Some tools (older python) print their version to stderr
+  let text = if output.stdout.is_empty() { output.stderr } else { output.stdout };
+  String::from_utf8_lossy(&text).lines().next().map(|l| l.trim().to_string())
+}
+
+/// Send a minimal chat request with the configured provider settings
+pub async fn test_llm_endpoint(app: &AppHandle) -> Result<StepResult, String> {
+  const STEP: &str = "llm_endpoint";
+  let settings = cached_settings(app).await?;
+  let mut config = build_llm_config(&settings)?;
+  config.max_tokens = 16;
+  let details = json!({ "base_url": config.base_url, "model": config.model });
+
+  if config.base_url.is_empty() || config.model.is_empty() {
+    return Ok(StepResult::new(STEP, StepStatus::Error, "Base URL and model must be set", details));
+  }
+  let api_key = match get_api_key(&settings) {
+    Ok(key) => key,
+    Err(e) => return Ok(StepResult::new(STEP, StepStatus::Error, e.message, details)),
+  };
+
+  let client = LlmClient::new(config, api_key);
+  let started = Instant::now();
+  let response = client.chat_with_tools(vec![ChatMessage {
+    role: "user".into(),
+    content: Some("Reply with the single word OK.".into()),
+    tool_call_id: None,
+    tool_calls: None,
+  }], vec![]).await;
+  let latency_ms = started.elapsed().as_millis() as u64;
+
+  let mut details = details;
+  details["latency_ms"] = json!(latency_ms);
+  Ok(match response {
+    Ok(response) => {
+      details["reply"] = json!(response.content);
+      StepResult::new(STEP, StepStatus::Ok, format!("Endpoint responded in {} ms", latency_ms), details)
+    }
+    Err(e) => StepResult::new(STEP, StepStatus::Error, e.to_string(), details),
+  })
+}
+
+/// Register a project for the chosen repository. A folder that isn't a git
+/// repository is still accepted, with a warning, since diff tools won't work.
+pub fn create_sample_project(app: &AppHandle, repo_path: &str, name: Option<String>) -> Result<StepResult, String> {
+  const STEP: &str = "sample_project";
+  let path = Path::new(repo_path);
+  if !path.is_dir() {
+    return Ok(StepResult::new(
+      STEP,
+      StepStatus::Error,
+      format!("{} is not a directory", repo_path),
+      json!({ "repo_path": repo_path }),
+    ));
+  }
+
+  let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "Sample project".into())
+  });
+  let project = create_project(app.clone(), name, repo_path.to_string())?;
+  let details = json!({ "project": project });
+
+  Ok(if path.join(".git").exists() {
+    StepResult::new(STEP, StepStatus::Ok, "Project created", details)
+  } else {
+    StepResult::new(STEP, StepStatus::Warning, "Project created, but the folder is not a git repository", details)
+  })
+}
+
+/// Create a small task and run the full plan workflow on it end to end
+pub async fn run_smoke_plan(app: &AppHandle, project_id: &str) -> Result<StepResult, String> {
+  const STEP: &str = "smoke_plan";
+  let task = create_task(app.clone(), project_id.to_string(), SMOKE_TASK_TITLE.into(), "plan".into())?;
+
+  let started = Instant::now();
+  let result = generate_plan(app.clone(), project_id.to_string(), task.id.clone()).await;
+  let duration_ms = started.elapsed().as_millis() as u64;
+
+  Ok(match result {
+    Ok(plan) => StepResult::new(
+      STEP,
+      if plan.truncated { StepStatus::Warning } else { StepStatus::Ok },
+      format!("Plan generated with {} tool call(s) in {} ms", plan.tool_calls_count, duration_ms),
+      json!({
+        "task_id": task.id,
+        "run_id": plan.run_id,
+        "tool_calls_count": plan.tool_calls_count,
+        "duration_ms": duration_ms,
+      }),
+    ),
+    Err(e) => StepResult::new(
+      STEP,
+      StepStatus::Error,
+      e.message,
+      json!({ "task_id": task.id, "code": e.code, "duration_ms": duration_ms }),
+    ),
+  })
+}
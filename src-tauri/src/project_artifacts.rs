@@ -0,0 +1,206 @@
+use rusqlite::{Connection, OptionalExtension};
+use tauri::AppHandle;
+
+use crate::blobs;
+use crate::compression::StoredText;
+use crate::db;
+use crate::models::{new_id, Artifact};
+use crate::workflows::sanitize::wrap_untrusted;
+
+/// Artifacts about a whole project rather than one task
+pub const PROJECT_ARTIFACT_KINDS: &[&str] = &["repo_map", "structure_drift", "repo_overview", "release_notes", "conventions", "glossary"];
+
+/// Kinds that go into plan and verify prompts without being pinned; the rest
+/// are only included when pinned
+const ALWAYS_INCLUDED_KINDS: &[&str] = &["conventions", "glossary", "structure_drift"];
+const MAX_PROJECT_CONTEXT_CHARS: usize = 16_000;
+
+const COLUMNS: &str = "id, task_id, project_id, phase_id, kind, content, created_at, pinned, draft, content_hash";
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn row_to_artifact(conn: &Connection, r: &rusqlite::Row) -> rusqlite::Result<Artifact> {
+  Ok(Artifact {
+    id: r.get(0)?,
+    task_id: r.get(1)?,
+    project_id: r.get(2)?,
+    phase_id: r.get(3)?,
+    kind: r.get(4)?,
+    content: blobs::hydrate(conn, r.get::<_, StoredText>(5)?, r.get(9)?)?,
+    created_at: r.get(6)?,
+    pinned: r.get(7)?,
+    draft: r.get(8)?,
+  })
+}
+
+fn get(conn: &Connection, artifact_id: &str) -> Result<Option<Artifact>, String> {
+  conn.query_row(
+    &format!("SELECT {} FROM artifacts WHERE id = ?1 AND project_id IS NOT NULL", COLUMNS),
+    [artifact_id],
+    |r| row_to_artifact(conn, r)
+  ).optional().map_err(|e| e.to_string())
+}
+
+/// One artifact per (project, kind): replaces the content of an existing one,
+/// keeping whether it's pinned
+fn upsert(conn: &Connection, project_id: &str, kind: &str, content: &str) -> Result<Artifact, String> {
+  let existing: Option<String> = conn.query_row(
+    "SELECT id FROM artifacts WHERE project_id = ?1 AND kind = ?2 AND draft = 0 LIMIT 1",
+    (project_id, kind),
+    |r| r.get(0)
+  ).optional().map_err(|e| e.to_string())?;
+
+  let created_at = now_iso();
+  let (stored, hash) = blobs::put(conn, content).map_err(|e| e.to_string())?;
+  let id = match existing {
+    Some(id) => {
+      conn.execute(
+        "UPDATE artifacts SET content = ?1, content_hash = ?2, created_at = ?3 WHERE id = ?4",
+        (&stored, &hash, &created_at, &id)
+      ).map_err(|e| e.to_string())?;
+      id
+    }
+    None => {
+      let id = new_id();
+      conn.execute(
+        "INSERT INTO artifacts (id, task_id, project_id, phase_id, kind, content, content_hash, created_at, pinned, draft)
+         VALUES (?1, NULL, ?2, NULL, ?3, ?4, ?5, ?6, 0, 0)",
+        (&id, project_id, kind, &stored, &hash, &created_at)
+      ).map_err(|e| e.to_string())?;
+      id
+    }
+  };
+  get(conn, &id)?.ok_or_else(|| format!("Artifact {} not found", id))
+}
+
+fn list(conn: &Connection, project_id: &str, kind: Option<&str>) -> Result<Vec<Artifact>, String> {
+  let mut stmt = conn.prepare(&format!(
+    "SELECT {} FROM artifacts WHERE project_id = ?1 AND (?2 IS NULL OR kind = ?2) AND draft = 0
+     ORDER BY created_at DESC",
+    COLUMNS
+  )).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map((project_id, kind), |r| row_to_artifact(conn, r)).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Which project artifacts go into a workflow's prompt: always-included kinds
+/// first, then pinned ones in `PROJECT_ARTIFACT_KINDS` order, while they fit in `budget`
+fn select_for_context(artifacts: &[Artifact], budget: usize) -> Vec<&Artifact> {
+  let mut candidates: Vec<&Artifact> = artifacts.iter()
+    .filter(|a| a.draft == 0 && !a.content.trim().is_empty())
+    .filter(|a| a.pinned != 0 || ALWAYS_INCLUDED_KINDS.contains(&a.kind.as_str()))
+    .collect();
+  candidates.sort_by_key(|a| (
+    !ALWAYS_INCLUDED_KINDS.contains(&a.kind.as_str()),
+    PROJECT_ARTIFACT_KINDS.iter().position(|k| *k == a.kind).unwrap_or(usize::MAX),
+  ));
+
+  let mut used = 0;
+  candidates.into_iter()
+    .filter(|a| {
+      let fits = used + a.content.len() <= budget;
+      if fits {
+        used += a.content.len();
+      }
+      fits
+    })
+    .collect()
+}
+
+fn kind_title(kind: &str) -> &str {
+  match kind {
+    "repo_map" => "Repository Map",
+    "structure_drift" => "Structure Drift",
+    "repo_overview" => "Architecture Overview",
+    "release_notes" => "Release Notes",
+    "conventions" => "Conventions",
+    "glossary" => "Glossary",
+    other => other,
+  }
+}
+
+/// Save `content` as the project's artifact of `kind`
+pub fn upsert_project_artifact(app: &AppHandle, project_id: &str, kind: &str, content: &str) -> Result<Artifact, String> {
+  if !PROJECT_ARTIFACT_KINDS.contains(&kind) {
+    return Err(format!("Unknown project artifact kind '{}' (expected one of {})", kind, PROJECT_ARTIFACT_KINDS.join(", ")));
+  }
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let exists: bool = conn.query_row("SELECT EXISTS (SELECT 1 FROM projects WHERE id = ?1)", [project_id], |r| r.get(0))
+    .map_err(|e| e.to_string())?;
+  if !exists {
+    return Err(format!("Project {} not found", project_id));
+  }
+  upsert(&conn, project_id, kind, content)
+}
+
+/// The project's artifacts, newest first, optionally of one kind
+pub fn list_project_artifacts(app: &AppHandle, project_id: &str, kind: Option<&str>) -> Result<Vec<Artifact>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  list(&conn, project_id, kind)
+}
+
+/// Pin or unpin a project artifact; pinned ones are included in plan and verify prompts
+pub fn set_project_artifact_pinned(app: &AppHandle, artifact_id: &str, pinned: bool) -> Result<Artifact, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "UPDATE artifacts SET pinned = ?1 WHERE id = ?2 AND project_id IS NOT NULL",
+    (pinned as i64, artifact_id)
+  ).map_err(|e| e.to_string())?;
+  get(&conn, artifact_id)?.ok_or_else(|| format!("Project artifact {} not found", artifact_id))
+}
+
+pub fn delete_project_artifact(app: &AppHandle, artifact_id: &str) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute("DELETE FROM artifacts WHERE id = ?1 AND project_id IS NOT NULL", [artifact_id]).map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+/// Prompt section with the project artifacts a workflow should see, or None
+/// when there are none to include
+pub fn project_context(app: &AppHandle, project_id: &str) -> Result<Option<String>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let artifacts = list(&conn, project_id, None)?;
+  let sections: Vec<String> = select_for_context(&artifacts, MAX_PROJECT_CONTEXT_CHARS).into_iter()
+    .map(|a| format!("### {}\n{}", kind_title(&a.kind), wrap_untrusted("project_artifact", a.content.trim())))
+    .collect();
+  if sections.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(format!("## Project Context\n\n{}", sections.join("\n\n"))))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn artifact(kind: &str, content: &str, pinned: i64) -> Artifact {
+    Artifact {
+      id: kind.into(),
+      task_id: None,
+      project_id: Some("p1".into()),
+      phase_id: None,
+      kind: kind.into(),
+      content: content.into(),
+      created_at: "2026-05-01T00:00:00Z".into(),
+      pinned,
+      draft: 0,
+    }
+  }
+
+  #[test]
+  fn test_select_for_context() {
+    let artifacts = vec![
+      artifact("release_notes", "notes", 0),
+      artifact("repo_overview", "overview", 1),
+      artifact("conventions", "use tabs", 0),
+      artifact("repo_map", &"x".repeat(100), 1),
+    ];
+    let kinds = |budget| select_for_context(&artifacts, budget).iter().map(|a| a.kind.clone()).collect::<Vec<_>>();
+    assert_eq!(kinds(1_000), ["conventions", "repo_map", "repo_overview"]);
+    // Whatever doesn't fit is skipped, not truncated
+    assert_eq!(kinds(20), ["conventions", "repo_overview"]);
+  }
+}
@@ -0,0 +1,126 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::commands::{add_task_note, create_task, list_projects};
+use crate::db;
+use crate::models::{Project, Task, TaskAttachment};
+
+/// Setting holding the accelerator (e.g. `CommandOrControl+Shift+Space`); empty disables it
+pub const SHORTCUT_SETTING: &str = "quick_capture_shortcut";
+const CAPTURE_WINDOW: &str = "quick-capture";
+/// Clipboard text beyond this is cut off so a stray copy can't bloat the task
+const MAX_CLIPBOARD_CHARS: usize = 20_000;
+
+#[derive(Debug, Serialize, Type)]
+pub struct CaptureResult {
+  pub project: Project,
+  pub task: Task,
+  /// Clipboard contents saved as a note, when requested and non-empty
+  pub attachment: Option<TaskAttachment>,
+}
+
+/// Global shortcut plugin; the only registered shortcut opens the capture window
+pub fn plugin() -> tauri::plugin::TauriPlugin<Wry> {
+  tauri_plugin_global_shortcut::Builder::new()
+    .with_handler(|app, _shortcut, event| {
+      if event.state() == ShortcutState::Pressed {
+        if let Err(e) = open_capture_window(app) {
+          tracing::warn!("failed to open quick capture: {}", e);
+        }
+      }
+    })
+    .build()
+}
+
+fn parse_shortcut(accelerator: &str) -> Result<Option<Shortcut>, String> {
+  let accelerator = accelerator.trim();
+  if accelerator.is_empty() {
+    return Ok(None);
+  }
+  accelerator.parse()
+    .map(Some)
+    .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))
+}
+
+fn saved_accelerator(app: &AppHandle) -> Result<String, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  Ok(conn.query_row(
+    "SELECT value FROM settings WHERE key = ?1", [SHORTCUT_SETTING], |r| r.get(0)
+  ).unwrap_or_default())
+}
+
+/// Register the shortcut from settings. Called at startup.
+pub fn register_shortcut(app: &AppHandle) -> Result<(), String> {
+  change_shortcut(app, &saved_accelerator(app)?)
+}
+
+/// Switch to `accelerator`, or disable the shortcut when it's empty. Called
+/// before the setting is saved: an invalid or unavailable shortcut is an
+/// error and the current one stays registered.
+pub fn change_shortcut(app: &AppHandle, accelerator: &str) -> Result<(), String> {
+  let shortcut = parse_shortcut(accelerator)?;
+  let previous = saved_accelerator(app).ok().and_then(|saved| parse_shortcut(&saved).ok().flatten());
+
+  let shortcuts = app.global_shortcut();
+  shortcuts.unregister_all().map_err(|e| e.to_string())?;
+  let Some(shortcut) = shortcut else {
+    return Ok(());
+  };
+  shortcuts.register(shortcut).map_err(|e| {
+    if let Some(previous) = previous {
+      if let Err(e) = shortcuts.register(previous) {
+        tracing::warn!("failed to restore the previous quick capture shortcut: {}", e);
+      }
+    }
+    format!("Cannot register shortcut '{}': {}", accelerator.trim(), e)
+  })
+}
+
+/// Show the small always-on-top capture window, creating it on first use
+pub fn open_capture_window(app: &AppHandle) -> Result<(), String> {
+  if let Some(window) = app.get_webview_window(CAPTURE_WINDOW) {
+    window.show().map_err(|e| e.to_string())?;
+    return window.set_focus().map_err(|e| e.to_string());
+  }
+
+  let window = WebviewWindowBuilder::new(app, CAPTURE_WINDOW, WebviewUrl::App("capture".into()))
+    .title("Quick capture")
+    .inner_size(520.0, 200.0)
+    .resizable(false)
+    .always_on_top(true)
+    .center()
+    .build()
+    .map_err(|e| e.to_string())?;
+  window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Create a task in the most recently opened project from a one-line title,
+/// optionally attaching the clipboard text as a note.
+pub fn capture_task(app: &AppHandle, title: &str, include_clipboard: bool) -> Result<CaptureResult, String> {
+  let title = title.lines().next().unwrap_or_default().trim();
+  if title.is_empty() {
+    return Err("Title is required".into());
+  }
+  let project = list_projects(app.clone())?.into_iter().next()
+    .ok_or("Create a project before using quick capture")?;
+  let task = create_task(app.clone(), project.id.clone(), title.to_string(), "plan".into())?;
+
+  let clipboard = if include_clipboard {
+    // An empty or non-text clipboard isn't an error; the task is still created
+    app.clipboard().read_text().ok().filter(|text| !text.trim().is_empty())
+  } else {
+    None
+  };
+  let attachment = match clipboard {
+    Some(text) => {
+      let content: String = text.chars().take(MAX_CLIPBOARD_CHARS).collect();
+      Some(add_task_note(app.clone(), task.id.clone(), "Clipboard".into(), content)?)
+    }
+    None => None,
+  };
+
+  Ok(CaptureResult { project, task, attachment })
+}
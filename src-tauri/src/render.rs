@@ -0,0 +1,297 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::commands::upsert_artifact;
+use crate::blobs;
+use crate::db;
+
+/// Artifact kind holding the diagrams extracted from a task's plan
+pub const DIAGRAM_ARTIFACT_KIND: &str = "plan_diagram";
+
+/// Mermaid diagram types we accept, matched against the first statement
+const DIAGRAM_TYPES: &[&str] = &[
+  "flowchart",
+  "graph",
+  "sequenceDiagram",
+  "classDiagram",
+  "stateDiagram",
+  "stateDiagram-v2",
+  "erDiagram",
+  "gantt",
+  "pie",
+  "journey",
+  "gitGraph",
+  "mindmap",
+  "timeline",
+];
+const FLOWCHART_DIRECTIONS: &[&str] = &["TB", "TD", "BT", "RL", "LR"];
+/// Keywords that open a block closed by `end`, per diagram type
+const FLOWCHART_BLOCKS: &[&str] = &["subgraph"];
+const SEQUENCE_BLOCKS: &[&str] = &["loop", "alt", "opt", "par", "critical", "break", "rect", "box"];
+const SEQUENCE_ARROWS: &[&str] = &["-->>", "->>", "-->", "->", "--x", "-x", "--)", "-)"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MermaidBlock {
+  pub source: String,
+  /// 1-based line of the opening fence in the markdown
+  pub line: usize,
+}
+
+/// A markdown document split into pieces the frontend can render directly.
+/// Invalid diagrams fall back to their source plus the validation error.
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RenderBlock {
+  Markdown { content: String },
+  Mermaid { source: String, diagram_type: String },
+  MermaidError { source: String, error: String },
+}
+
+/// Mermaid fences in document order
+pub fn extract_mermaid_blocks(markdown: &str) -> Vec<MermaidBlock> {
+  split_blocks(markdown).into_iter()
+    .filter_map(|segment| match segment {
+      Segment::Mermaid { source, line } => Some(MermaidBlock { source, line }),
+      Segment::Markdown(_) => None,
+    })
+    .collect()
+}
+
+/// Structural check of a mermaid diagram: a known header, balanced node
+/// brackets and quotes, matched `end`s, and labeled sequence messages.
+/// Returns the diagram type. This can't catch everything mermaid rejects, but
+/// it catches the truncated and half-formed output LLMs tend to produce.
+pub fn validate_mermaid(source: &str) -> Result<String, String> {
+  let mut statements = source.lines()
+    .enumerate()
+    .map(|(i, line)| (i + 1, line.trim()))
+    .filter(|(_, line)| !line.is_empty() && !line.starts_with("%%"));
+
+  let (_, header) = statements.next().ok_or("Diagram is empty")?;
+  let mut header_parts = header.split_whitespace();
+  let diagram_type = header_parts.next().unwrap_or_default();
+  if !DIAGRAM_TYPES.contains(&diagram_type) {
+    return Err(format!("Unknown diagram type '{}'", diagram_type));
+  }
+  let is_flowchart = matches!(diagram_type, "flowchart" | "graph");
+  if is_flowchart {
+    if let Some(direction) = header_parts.next() {
+      if !FLOWCHART_DIRECTIONS.contains(&direction) {
+        return Err(format!("Line 1: unknown flowchart direction '{}'", direction));
+      }
+    }
+  }
+
+  let block_keywords = match diagram_type {
+    "flowchart" | "graph" => FLOWCHART_BLOCKS,
+    "sequenceDiagram" => SEQUENCE_BLOCKS,
+    _ => &[],
+  };
+  let mut open_blocks: Vec<(usize, &str)> = vec![];
+
+  for (line_no, line) in statements {
+    // Only flowchart node shapes use brackets structurally; elsewhere they're label text
+    if is_flowchart {
+      check_balanced(line).map_err(|e| format!("Line {}: {}", line_no, e))?;
+    }
+
+    let first_word = line.split_whitespace().next().unwrap_or_default();
+    if block_keywords.contains(&first_word) {
+      open_blocks.push((line_no, first_word));
+    } else if first_word == "end" && !block_keywords.is_empty() {
+      open_blocks.pop().ok_or_else(|| format!("Line {}: 'end' without an open block", line_no))?;
+    } else if diagram_type == "sequenceDiagram" {
+      let has_arrow = SEQUENCE_ARROWS.iter().any(|arrow| line.contains(arrow));
+      if has_arrow && !line.contains(':') {
+        return Err(format!("Line {}: sequence message is missing a ': label'", line_no));
+      }
+    }
+  }
+
+  if let Some((line_no, keyword)) = open_blocks.last() {
+    return Err(format!("Line {}: '{}' is never closed with 'end'", line_no, keyword));
+  }
+  Ok(diagram_type.to_string())
+}
+
+fn check_balanced(line: &str) -> Result<(), String> {
+  let mut stack = vec![];
+  let mut in_quotes = false;
+  let mut prev = ' ';
+  for c in line.chars() {
+    let before = std::mem::replace(&mut prev, c);
+    if c == '"' {
+      in_quotes = !in_quotes;
+      continue;
+    }
+    if in_quotes {
+      continue;
+    }
+    match c {
+      '(' | '[' | '{' => stack.push(c),
+      // Asymmetric node shape `id>label]`; arrows have `-` or `=` before `>`
+      '>' if before.is_alphanumeric() || before == '_' => stack.push(c),
+      ')' | ']' | '}' => {
+        let expected = match c { ')' => vec!['('], ']' => vec!['[', '>'], _ => vec!['{'] };
+        if !stack.pop().is_some_and(|open| expected.contains(&open)) {
+          return Err(format!("unmatched '{}'", c));
+        }
+      }
+      _ => {}
+    }
+  }
+  if in_quotes {
+    return Err("unterminated string".into());
+  }
+  match stack.last() {
+    Some(open) => Err(format!("unclosed '{}'", open)),
+    None => Ok(()),
+  }
+}
+
+/// Split markdown into prose and validated diagrams
+pub fn render_blocks(markdown: &str) -> Vec<RenderBlock> {
+  split_blocks(markdown).into_iter()
+    .map(|segment| match segment {
+      Segment::Markdown(content) => RenderBlock::Markdown { content },
+      Segment::Mermaid { source, .. } => match validate_mermaid(&source) {
+        Ok(diagram_type) => RenderBlock::Mermaid { source, diagram_type },
+        Err(error) => RenderBlock::MermaidError { source, error },
+      },
+    })
+    .collect()
+}
+
+/// Store the plan's diagrams as their own artifact so they can be rendered
+/// or exported without re-parsing the plan. Returns validation errors for
+/// diagrams that were kept out.
+pub fn store_plan_diagrams(app: &AppHandle, task_id: &str, plan_md: &str) -> Result<Vec<String>, String> {
+  let mut valid = vec![];
+  let mut errors = vec![];
+  for block in extract_mermaid_blocks(plan_md) {
+    match validate_mermaid(&block.source) {
+      Ok(_) => valid.push(format!("```mermaid\n{}\n```", block.source)),
+      Err(e) => errors.push(format!("Diagram at line {}: {}", block.line, e)),
+    }
+  }
+
+  if valid.is_empty() {
+    // Drop diagrams from an earlier plan so they don't outlive it
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.execute(
+      "DELETE FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = ?2 AND draft = 0",
+      (task_id, DIAGRAM_ARTIFACT_KIND),
+    ).map_err(|e| e.to_string())?;
+  } else {
+    upsert_artifact(app.clone(), task_id.to_string(), None, DIAGRAM_ARTIFACT_KIND.into(), valid.join("\n\n"))?;
+  }
+  Ok(errors)
+}
+
+/// Body of the plan/report section whose heading starts with `## {number}.`
+pub fn numbered_section(markdown: &str, number: u8) -> Option<String> {
+  let marker = format!("## {}.", number);
+  let rest = markdown.split(&marker).nth(1)?;
+  let body = rest.split_once('\n').map(|(_, body)| body).unwrap_or_default();
+  let body = body.split("\n## ").next().unwrap_or_default().trim();
+  (!body.is_empty()).then(|| body.to_string())
+}
+
+pub fn render_artifact(app: &AppHandle, artifact_id: &str) -> Result<Vec<RenderBlock>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let content = conn.query_row(
+    "SELECT content, content_hash FROM artifacts WHERE id = ?1", [artifact_id],
+    |r| blobs::hydrate(&conn, r.get(0)?, r.get(1)?)
+  ).map_err(|e| match e {
+    rusqlite::Error::QueryReturnedNoRows => format!("Artifact {} not found", artifact_id),
+    e => e.to_string(),
+  })?;
+  Ok(render_blocks(&content))
+}
+
+enum Segment {
+  Markdown(String),
+  Mermaid { source: String, line: usize },
+}
+
+/// Walk the document once, keeping non-mermaid fences inside markdown
+/// segments. An unterminated mermaid fence runs to the end of the document.
+fn split_blocks(markdown: &str) -> Vec<Segment> {
+  let mut segments = vec![];
+  let mut prose: Vec<&str> = vec![];
+  let mut diagram: Option<(usize, Vec<&str>)> = None;
+  let mut in_other_fence = false;
+
+  for (i, line) in markdown.lines().enumerate() {
+    let trimmed = line.trim();
+    if let Some((start, body)) = diagram.as_mut() {
+      if trimmed.starts_with("```") {
+        segments.push(Segment::Mermaid { source: body.join("\n"), line: *start });
+        diagram = None;
+      } else {
+        body.push(line);
+      }
+      continue;
+    }
+
+    if !in_other_fence && trimmed.starts_with("```mermaid") {
+      if !prose.is_empty() {
+        segments.push(Segment::Markdown(prose.join("\n")));
+        prose.clear();
+      }
+      diagram = Some((i + 1, vec![]));
+      continue;
+    }
+    if trimmed.starts_with("```") {
+      in_other_fence = !in_other_fence;
+    }
+    prose.push(line);
+  }
+
+  if let Some((start, body)) = diagram {
+    segments.push(Segment::Mermaid { source: body.join("\n"), line: start });
+  }
+  if !prose.is_empty() {
+    segments.push(Segment::Markdown(prose.join("\n")));
+  }
+  segments
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_extract_skips_other_fences() {
+    let md = "# Plan\n\n```rust\n// ```mermaid inside code\n```\n\n```mermaid\ngraph TD\n  A --> B\n```\n";
+    let blocks = extract_mermaid_blocks(md);
+    assert_eq!(blocks, vec![MermaidBlock { source: "graph TD\n  A --> B".into(), line: 7 }]);
+  }
+
+  #[test]
+  fn test_validate_mermaid() {
+    assert_eq!(validate_mermaid("flowchart LR\n  A[Start] --> B{Ok?}\n  B -->|yes| C>Done]\n").unwrap(), "flowchart");
+    assert_eq!(
+      validate_mermaid("sequenceDiagram\n  UI->>API: save (draft\n  API-)UI: queued\n  loop retry\n    API-->>DB: write\n  end").unwrap(),
+      "sequenceDiagram"
+    );
+
+    assert!(validate_mermaid("").is_err());
+    assert!(validate_mermaid("architecture\n A --> B").unwrap_err().contains("Unknown diagram type"));
+    assert!(validate_mermaid("graph XY\n A --> B").unwrap_err().contains("direction"));
+    assert!(validate_mermaid("graph TD\n  A[Start --> B").unwrap_err().starts_with("Line 2"));
+    assert!(validate_mermaid("graph TD\n  subgraph api\n  A --> B").unwrap_err().contains("never closed"));
+    assert!(validate_mermaid("sequenceDiagram\n  UI->>API").unwrap_err().contains("label"));
+  }
+
+  #[test]
+  fn test_render_blocks_falls_back_on_errors() {
+    let md = "Intro\n```mermaid\ngraph TD\n A --> B\n```\nMiddle\n```mermaid\ngraph TD\n A[oops --> B\n```";
+    let blocks = render_blocks(md);
+    assert_eq!(blocks.len(), 4);
+    assert_eq!(blocks[0], RenderBlock::Markdown { content: "Intro".into() });
+    assert!(matches!(&blocks[1], RenderBlock::Mermaid { diagram_type, .. } if diagram_type == "graph"));
+    assert!(matches!(&blocks[3], RenderBlock::MermaidError { error, .. } if error.starts_with("Line 2")));
+  }
+}
@@ -0,0 +1,235 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::models::new_id;
+use crate::repo_tools::run_settings::{run_project_id, run_setting};
+
+const DEFAULT_REGRESSION_PCT: f64 = 10.0;
+
+/// One benchmark's measured time per iteration
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BenchResult {
+    pub name: String,
+    pub mean_ns: f64,
+    /// Lower/upper bound of the estimate, when the tool reports one
+    pub low_ns: Option<f64>,
+    pub high_ns: Option<f64>,
+}
+
+/// A benchmark result next to the project's previous result for it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+pub struct BenchComparison {
+    pub name: String,
+    pub mean_ns: f64,
+    pub previous_mean_ns: Option<f64>,
+    /// Positive when slower than before
+    pub change_pct: Option<f64>,
+    /// Slower than before by more than the bench_regression_pct setting
+    pub regression: bool,
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn to_ns(value: &str, unit: &str) -> Option<f64> {
+    let value: f64 = value.replace(',', "").parse().ok()?;
+    let scale = match unit {
+        "ps" => 0.001,
+        "ns" => 1.0,
+        "µs" | "us" | "μs" => 1_000.0,
+        "ms" => 1_000_000.0,
+        "s" => 1_000_000_000.0,
+        _ => return None,
+    };
+    Some(value * scale)
+}
+
+/// `name  time:   [1.20 µs 1.25 µs 1.31 µs]` from criterion; long names are
+/// printed on the line before
+fn parse_criterion_line(line: &str, previous: Option<&str>) -> Option<BenchResult> {
+    let (name, rest) = line.split_once("time:")?;
+    let values: Vec<&str> = rest.trim().strip_prefix('[')?.strip_suffix(']')?.split_whitespace().collect();
+    let [low, low_unit, mean, mean_unit, high, high_unit] = values[..] else { return None };
+    let name = Some(name.trim()).filter(|n| !n.is_empty()).or(previous)?;
+    Some(BenchResult {
+        name: name.to_string(),
+        mean_ns: to_ns(mean, mean_unit)?,
+        low_ns: to_ns(low, low_unit),
+        high_ns: to_ns(high, high_unit),
+    })
+}
+
+/// `test name ... bench:   1,234 ns/iter (+/- 56)` from libtest's `#[bench]`
+fn parse_libtest_line(line: &str) -> Option<BenchResult> {
+    let (name, rest) = line.strip_prefix("test ")?.split_once(" ... bench:")?;
+    let (value, rest) = rest.trim().split_once(" ns/iter")?;
+    let mean_ns = to_ns(value, "ns")?;
+    let spread = rest.trim().strip_prefix("(+/-").and_then(|s| s.trim().strip_suffix(')')).and_then(|s| to_ns(s.trim(), "ns"));
+    Some(BenchResult {
+        name: name.trim().to_string(),
+        mean_ns,
+        low_ns: spread.map(|s| mean_ns - s),
+        high_ns: spread.map(|s| mean_ns + s),
+    })
+}
+
+/// Results in criterion or libtest bench output
+pub fn parse_bench_output(output: &str) -> Vec<BenchResult> {
+    let mut results: Vec<BenchResult> = vec![];
+    let mut previous: Option<&str> = None;
+    for line in output.lines().map(str::trim_end) {
+        if let Some(result) = parse_libtest_line(line.trim_start()).or_else(|| parse_criterion_line(line, previous)) {
+            results.retain(|r| r.name != result.name);
+            results.push(result);
+        }
+        if !line.trim().is_empty() {
+            previous = Some(line.trim());
+        }
+    }
+    results
+}
+
+/// Results from `hyperfine --export-json`, in seconds per command
+pub fn parse_hyperfine_json(json: &str) -> Result<Vec<BenchResult>, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid hyperfine export: {}", e))?;
+    let results = value.get("results").and_then(|r| r.as_array()).ok_or("hyperfine export has no results")?;
+    Ok(results.iter().filter_map(|r| {
+        let seconds = |key: &str| r.get(key).and_then(|v| v.as_f64()).map(|s| s * 1_000_000_000.0);
+        Some(BenchResult {
+            name: r.get("command")?.as_str()?.to_string(),
+            mean_ns: seconds("mean")?,
+            low_ns: seconds("min"),
+            high_ns: seconds("max"),
+        })
+    }).collect())
+}
+
+/// Compare results with the project's most recent earlier result for each
+/// benchmark, then store them for the next comparison
+fn record_and_compare(conn: &Connection, project_id: &str, run_id: &str, results: &[BenchResult], threshold_pct: f64) -> Result<Vec<BenchComparison>, String> {
+    let now = now_iso();
+    let mut comparisons = vec![];
+    for result in results {
+        let previous: Option<f64> = conn.query_row(
+            "SELECT mean_ns FROM bench_results WHERE project_id = ?1 AND name = ?2
+             ORDER BY created_at DESC, rowid DESC LIMIT 1",
+            (project_id, &result.name),
+            |r| r.get(0)
+        ).optional().map_err(|e| e.to_string())?;
+        let change_pct = previous.filter(|p| *p > 0.0).map(|p| (result.mean_ns - p) / p * 100.0);
+        comparisons.push(BenchComparison {
+            name: result.name.clone(),
+            mean_ns: result.mean_ns,
+            previous_mean_ns: previous,
+            change_pct,
+            regression: change_pct.is_some_and(|c| c > threshold_pct),
+        });
+        conn.execute(
+            "INSERT INTO bench_results (id, project_id, run_id, name, mean_ns, low_ns, high_ns, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (new_id(), project_id, run_id, &result.name, result.mean_ns, result.low_ns, result.high_ns, &now)
+        ).map_err(|e| e.to_string())?;
+    }
+    Ok(comparisons)
+}
+
+/// Commands the project benchmarks with hyperfine (the bench_commands
+/// setting, one per line); empty when benchmarks run through the project's runner
+pub async fn hyperfine_commands(app: &AppHandle, run_id: &str) -> Vec<String> {
+    run_setting(app, run_id, "bench_commands").await.ok().flatten()
+        .map(|v| v.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Store a run's results against its project and compare them with the
+/// previous ones. Runs outside a task aren't stored or compared.
+pub async fn compare_with_previous(app: &AppHandle, run_id: &str, results: &[BenchResult]) -> Result<Vec<BenchComparison>, String> {
+    let Some(project_id) = run_project_id(app, run_id)? else {
+        return Ok(results.iter().map(|r| BenchComparison {
+            name: r.name.clone(),
+            mean_ns: r.mean_ns,
+            previous_mean_ns: None,
+            change_pct: None,
+            regression: false,
+        }).collect());
+    };
+    let threshold_pct = run_setting(app, run_id, "bench_regression_pct").await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REGRESSION_PCT);
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    record_and_compare(&conn, &project_id, run_id, results, threshold_pct)
+}
+
+fn format_ns(ns: f64) -> String {
+    match ns {
+        n if n >= 1_000_000_000.0 => format!("{:.2} s", n / 1_000_000_000.0),
+        n if n >= 1_000_000.0 => format!("{:.2} ms", n / 1_000_000.0),
+        n if n >= 1_000.0 => format!("{:.2} µs", n / 1_000.0),
+        n => format!("{:.1} ns", n),
+    }
+}
+
+/// Prompt lines for benchmark comparisons, regressions first
+pub fn format_benchmarks(comparisons: &[BenchComparison]) -> String {
+    let mut sorted: Vec<&BenchComparison> = comparisons.iter().collect();
+    sorted.sort_by_key(|c| !c.regression);
+    sorted.iter()
+        .map(|c| match (c.previous_mean_ns, c.change_pct) {
+            (Some(previous), Some(change)) => format!(
+                "- {}{}: {} (was {}, {:+.1}%)",
+                if c.regression { "[regression] " } else { "" },
+                c.name, format_ns(c.mean_ns), format_ns(previous), change
+            ),
+            _ => format!("- {}: {} (no previous result)", c.name, format_ns(c.mean_ns)),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_compare_benchmarks() {
+        let output = "Benchmarking parse/small: Analyzing\n\
+                      parse/small             time:   [1.2000 µs 1.2500 µs 1.3000 µs]\n\
+                      \x20                       change: [-1.0% +0.5% +2.0%] (p = 0.40 > 0.05)\n\
+                      a_very_long_benchmark_group/with_a_long_name\n\
+                      \x20                       time:   [2.0000 ms 2.1000 ms 2.2000 ms]\n\
+                      test bench_insert ... bench:       1,234 ns/iter (+/- 56)\n";
+        let results = parse_bench_output(output);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], BenchResult { name: "parse/small".into(), mean_ns: 1250.0, low_ns: Some(1200.0), high_ns: Some(1300.0) });
+        assert_eq!((results[1].name.as_str(), results[1].mean_ns), ("a_very_long_benchmark_group/with_a_long_name", 2_100_000.0));
+        assert_eq!((results[2].mean_ns, results[2].low_ns), (1234.0, Some(1178.0)));
+
+        let hyperfine = r#"{"results":[{"command":"./app --help","mean":0.012,"stddev":0.001,"min":0.010,"max":0.015}]}"#;
+        assert_eq!(parse_hyperfine_json(hyperfine).unwrap()[0].mean_ns, 12_000_000.0);
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE projects (id TEXT PRIMARY KEY); CREATE TABLE runs (id TEXT PRIMARY KEY);
+                            CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT, updated_at TEXT);
+                            INSERT INTO projects VALUES ('p1'); INSERT INTO runs VALUES ('r1'), ('r2');").unwrap();
+        conn.execute_batch(include_str!("../../migrations/039_bench.sql")).unwrap();
+        let first = record_and_compare(&conn, "p1", "r1", &results, 10.0).unwrap();
+        assert!(first.iter().all(|c| c.previous_mean_ns.is_none() && !c.regression));
+
+        let slower = vec![
+            BenchResult { mean_ns: 1500.0, ..results[0].clone() },
+            BenchResult { mean_ns: 2_000_000.0, ..results[1].clone() },
+        ];
+        let second = record_and_compare(&conn, "p1", "r2", &slower, 10.0).unwrap();
+        assert_eq!(second[0].change_pct, Some(20.0));
+        assert!(second[0].regression);
+        assert!(!second[1].regression);
+        assert!(format_benchmarks(&second).starts_with("- [regression] parse/small: 1.50 µs (was 1.25 µs, +20.0%)"));
+    }
+}
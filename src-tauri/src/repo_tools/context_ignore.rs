@@ -0,0 +1,202 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::run_settings::run_setting;
+use crate::settings::cached_settings;
+
+/// Repository-level config, committed alongside the code
+pub const REPO_CONFIG_FILE: &str = ".spectrail.toml";
+
+#[derive(Debug, Default, Deserialize)]
+struct RepoConfig {
+    #[serde(default)]
+    context: ContextConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ContextConfig {
+    #[serde(default)]
+    ignore: Vec<String>,
+}
+
+/// Where a project's context ignore globs come from
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ContextIgnoreConfig {
+    /// The `context_ignore` setting
+    pub globs: Vec<String>,
+    /// `[context] ignore` in the repo's .spectrail.toml
+    pub repo_globs: Vec<String>,
+    pub repo_error: Option<String>,
+}
+
+/// The `context_ignore` setting: a JSON array of globs
+pub fn parse_globs(json: &str) -> Result<Vec<String>, String> {
+    if json.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let globs: Vec<String> = serde_json::from_str(json)
+        .map_err(|e| format!("context_ignore must be a JSON array of globs: {}", e))?;
+    // Fail on a bad glob when it's saved, not on every tool call
+    ContextIgnore::new(&globs)?;
+    Ok(globs)
+}
+
+/// `[context] ignore` from a .spectrail.toml
+pub fn parse_repo_config(toml: &str) -> Result<Vec<String>, String> {
+    let config: RepoConfig = toml::from_str(toml).map_err(|e| format!("Invalid {}: {}", REPO_CONFIG_FILE, e))?;
+    Ok(config.context.ignore)
+}
+
+/// Paths kept out of the model's context: listings, search results, file
+/// reads and diffs. Globs follow .gitignore rules, so `*.min.js` matches at
+/// any depth and `fixtures/**` only at the root.
+pub struct ContextIgnore {
+    matcher: Gitignore,
+}
+
+impl ContextIgnore {
+    pub fn new(globs: &[String]) -> Result<Self, String> {
+        let mut builder = GitignoreBuilder::new("");
+        for glob in globs.iter().map(|g| g.trim()).filter(|g| !g.is_empty()) {
+            builder.add_line(None, glob).map_err(|e| format!("Invalid ignore glob '{}': {}", glob, e))?;
+        }
+        Ok(ContextIgnore { matcher: builder.build().map_err(|e| e.to_string())? })
+    }
+
+    /// Whether a repo-relative file, or a directory it's in, is ignored
+    pub fn is_ignored(&self, rel_path: &str) -> bool {
+        let rel_path = rel_path.trim_start_matches("./");
+        !rel_path.is_empty() && self.matcher.matched_path_or_any_parents(rel_path, false).is_ignore()
+    }
+
+    /// The diff without the sections of ignored files, and the files dropped
+    pub fn filter_diff(&self, diff: &str) -> (String, Vec<String>) {
+        let mut kept = String::with_capacity(diff.len());
+        let mut dropped = vec![];
+        let mut skipping = false;
+        for line in diff.split_inclusive('\n') {
+            if let Some(header) = line.strip_prefix("diff --git ") {
+                let path = header.trim_end()
+                    .rsplit_once(" b/")
+                    .map_or(header.trim_end(), |(_, path)| path);
+                skipping = self.is_ignored(path);
+                if skipping {
+                    dropped.push(path.to_string());
+                }
+            }
+            if !skipping {
+                kept.push_str(line);
+            }
+        }
+        (kept, dropped)
+    }
+}
+
+/// `globs` from the settings along with the repository's .spectrail.toml
+pub async fn load_config(globs: Vec<String>, repo_path: &Path) -> ContextIgnoreConfig {
+    let file = match remote_repo(repo_path) {
+        Some(remote) => remote.read(REPO_CONFIG_FILE).await.ok(),
+        None => std::fs::read(repo_path.join(REPO_CONFIG_FILE)).ok(),
+    };
+    let (repo_globs, repo_error) = match file.map(|bytes| parse_repo_config(&String::from_utf8_lossy(&bytes))) {
+        Some(Ok(globs)) => (globs, None),
+        Some(Err(e)) => (vec![], Some(e)),
+        None => (vec![], None),
+    };
+    ContextIgnoreConfig { globs, repo_globs, repo_error }
+}
+
+/// The project's `context_ignore` globs, falling back to the global setting
+pub async fn project_globs(app: &AppHandle, project_id: &str) -> Result<Vec<String>, String> {
+    let globs = match db::get_project_setting(app, project_id, "context_ignore").map_err(|e| e.to_string())? {
+        Some(globs) => globs,
+        None => cached_settings(app).await?.get("context_ignore").cloned().unwrap_or_default(),
+    };
+    parse_globs(&globs)
+}
+
+/// A glob matching exactly one repo-relative file
+fn literal_glob(path: &str) -> String {
+    let mut glob = String::from("/");
+    for c in path.trim_start_matches("./").chars() {
+        if matches!(c, '\\' | '*' | '?' | '[' | ']') {
+            glob.push('\\');
+        }
+        glob.push(c);
+    }
+    glob
+}
+
+/// Matcher for the configured globs plus files excluded by hand. A broken
+/// .spectrail.toml is logged and skipped rather than failing every tool.
+pub fn matcher(config: &ContextIgnoreConfig, excluded: &[String]) -> Result<ContextIgnore, String> {
+    if let Some(e) = &config.repo_error {
+        tracing::warn!(error = %e, "ignoring invalid repository config");
+    }
+    let excluded: Vec<String> = excluded.iter().map(|path| literal_glob(path)).collect();
+    let combined: Vec<String> = config.globs.iter().chain(&config.repo_globs).chain(&excluded).cloned().collect();
+    ContextIgnore::new(&combined).or_else(|e| {
+        tracing::warn!(error = %e, "ignoring invalid repository context globs");
+        let own: Vec<String> = config.globs.iter().chain(&excluded).cloned().collect();
+        ContextIgnore::new(&own)
+    })
+}
+
+/// Files excluded from the context of the task the run belongs to
+fn run_excluded_files(app: &AppHandle, run_id: &str) -> Result<Vec<String>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT co.path FROM context_overrides co JOIN runs r ON r.task_id = co.task_id
+         WHERE r.id = ?1 AND co.action = 'exclude'"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([run_id], |r| r.get(0)).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<String>, _>>().map_err(|e| e.to_string())
+}
+
+/// The matcher for a run's tool calls
+pub async fn context_ignore(app: &AppHandle, run_id: &str, repo_path: &Path) -> Result<ContextIgnore, String> {
+    let globs = parse_globs(&run_setting(app, run_id, "context_ignore").await?.unwrap_or_default())?;
+    let config = load_config(globs, repo_path).await;
+    matcher(&config, &run_excluded_files(app, run_id)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_ignore() {
+        let repo_globs = parse_repo_config("[context]\nignore = [\"fixtures/**\", \"vendor/\"]\n").unwrap();
+        let mut globs = parse_globs(r#"["**/*.min.js", "*.generated.ts"]"#).unwrap();
+        globs.extend(repo_globs);
+        let ignore = ContextIgnore::new(&globs).unwrap();
+
+        assert!(ignore.is_ignored("static/js/app.min.js"));
+        assert!(ignore.is_ignored("src/api/client.generated.ts"));
+        assert!(ignore.is_ignored("fixtures/large/users.json"));
+        assert!(ignore.is_ignored("./vendor/lib/a.go"));
+        assert!(!ignore.is_ignored("src/fixtures/users.json"));
+        assert!(!ignore.is_ignored("src/app.js"));
+
+        let diff = "diff --git a/src/app.js b/src/app.js\n+ok\ndiff --git a/dist/app.min.js b/dist/app.min.js\n+minified\n";
+        let (kept, dropped) = ignore.filter_diff(diff);
+        assert_eq!(kept, "diff --git a/src/app.js b/src/app.js\n+ok\n");
+        assert_eq!(dropped, ["dist/app.min.js"]);
+
+        let config = ContextIgnoreConfig { globs: vec![], repo_globs: vec![], repo_error: None };
+        let excluded = matcher(&config, &["src/[id]/page.tsx".to_string(), "./notes.md".to_string()]).unwrap();
+        assert!(excluded.is_ignored("src/[id]/page.tsx"));
+        assert!(!excluded.is_ignored("src/i/page.tsx"));
+        assert!(excluded.is_ignored("notes.md"));
+        assert!(!excluded.is_ignored("docs/notes.md"));
+
+        assert!(parse_globs("\"*.js\"").is_err());
+        assert!(parse_repo_config("[context]\nignore = \"*.js\"").is_err());
+        assert!(parse_repo_config("[other]\nkey = 1").unwrap().is_empty());
+    }
+}
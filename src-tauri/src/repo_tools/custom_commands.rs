@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::repo_tools::run_settings::run_setting;
+
+const KINDS: &[&str] = &["tests", "lint", "build", "typecheck", "bench"];
+/// `default` applies on any OS without its own entry
+const OSES: &[&str] = &["windows", "macos", "linux", "default"];
+
+/// The `custom_commands` setting: exact argv per command kind and OS, e.g.
+/// `{"tests": {"windows": ["npm.cmd", "test"], "default": ["npm", "test"]}}`.
+/// Arguments are passed to the program as is, never through a shell.
+pub type CustomCommands = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+pub fn parse_custom_commands(json: &str) -> Result<CustomCommands, String> {
+    let commands: CustomCommands = serde_json::from_str(json)
+        .map_err(|e| format!("custom_commands must map kinds to OS names to argv arrays: {}", e))?;
+    for (kind, by_os) in &commands {
+        if !KINDS.contains(&kind.as_str()) {
+            return Err(format!("Unknown command kind '{}' (expected {})", kind, KINDS.join(", ")));
+        }
+        for (os, argv) in by_os {
+            if !OSES.contains(&os.as_str()) {
+                return Err(format!("Unknown OS '{}' for {} (expected {})", os, kind, OSES.join(", ")));
+            }
+            match argv.first() {
+                None => return Err(format!("The {} command for {} is empty", kind, os)),
+                Some(program) if program.trim().is_empty() => {
+                    return Err(format!("The {} command for {} has no program", kind, os));
+                }
+                _ => {}
+            }
+            if argv.iter().any(|arg| arg.contains('\0')) {
+                return Err(format!("The {} command for {} contains a NUL byte", kind, os));
+            }
+        }
+    }
+    Ok(commands)
+}
+
+/// The argv for `kind` on `os`, falling back to the kind's default
+pub fn command_for(commands: &CustomCommands, kind: &str, os: &str) -> Option<Vec<String>> {
+    let by_os = commands.get(kind)?;
+    by_os.get(os).or_else(|| by_os.get("default")).cloned()
+}
+
+/// The run's project's custom command for `kind` on this OS, if it defines one
+pub async fn custom_command(app: &AppHandle, run_id: &str, kind: &str) -> Result<Option<Vec<String>>, String> {
+    let Some(json) = run_setting(app, run_id, "custom_commands").await?.filter(|v| !v.trim().is_empty()) else {
+        return Ok(None);
+    };
+    Ok(command_for(&parse_custom_commands(&json)?, kind, std::env::consts::OS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_commands() {
+        let commands = parse_custom_commands(
+            r#"{"tests": {"windows": ["npm.cmd", "test"], "default": ["npm", "test", "--", "--ci"]}, "lint": {"linux": ["wsl.exe", "-e", "make", "lint"]}}"#
+        ).unwrap();
+        assert_eq!(command_for(&commands, "tests", "windows").unwrap(), ["npm.cmd", "test"]);
+        assert_eq!(command_for(&commands, "tests", "macos").unwrap(), ["npm", "test", "--", "--ci"]);
+        assert_eq!(command_for(&commands, "lint", "windows"), None);
+        assert_eq!(command_for(&commands, "build", "linux"), None);
+
+        assert!(parse_custom_commands(r#"{"deploy": {"default": ["make", "deploy"]}}"#).unwrap_err().contains("Unknown command kind"));
+        assert!(parse_custom_commands(r#"{"tests": {"freebsd": ["make", "test"]}}"#).unwrap_err().contains("Unknown OS"));
+        assert!(parse_custom_commands(r#"{"tests": {"default": []}}"#).unwrap_err().contains("empty"));
+        assert!(parse_custom_commands(r#"{"tests": {"default": "npm test"}}"#).is_err());
+    }
+}
@@ -1,14 +1,34 @@
 use serde_json::Value;
 use std::path::Path;
+use std::time::Instant;
 use tauri::AppHandle;
 
 use crate::repo_tools::fs::{list_files, read_file};
 use crate::repo_tools::search::grep;
-use crate::repo_tools::git::{git_status, git_diff, git_log_short};
+use crate::repo_tools::git::{git_status, git_diff, git_log_short, git_pull};
+use crate::repo_tools::limits::{run_limits, OutputLimits};
+use crate::repo_tools::lsp::get_diagnostics;
+use crate::repo_tools::permissions::check_tool_permission;
+use crate::repo_tools::plugins::{registered_plugin, run_plugin};
+use crate::repo_tools::quota;
 use crate::repo_tools::runner::run_command;
+use crate::repo_tools::scope::{apply_scope, run_scope};
+use crate::repo_tools::stats::record_tool_stat;
+use crate::settings::cached_settings;
 
 pub use crate::repo_tools::schemas::repo_tool_schemas;
 
+/// Drop a `project_id` argument: models trained on the old schemas may still
+/// send one, and calls logged before it was removed carry it
+pub(crate) fn strip_project_id(args: &Value) -> Value {
+    let mut args = args.clone();
+    if let Some(obj) = args.as_object_mut() {
+        obj.remove("project_id");
+    }
+    args
+}
+
+#[tracing::instrument(skip(args, repo_path, app), fields(tool = name))]
 pub async fn dispatch_repo_tool(
     name: &str,
     args: &Value,
@@ -16,14 +36,75 @@ pub async fn dispatch_repo_tool(
     app: &AppHandle,
     run_id: &str,
 ) -> Result<Value, String> {
-    match name {
-        "list_files" => list_files(repo_path, args, app, run_id).await,
-        "read_file" => read_file(repo_path, args, app, run_id).await,
-        "grep" => grep(repo_path, args, app, run_id).await,
+    let started = Instant::now();
+
+    // Tools act on the run's own project; an id the model supplies is ignored
+    let args = &strip_project_id(args);
+
+    // The run's tier is enforced here, whatever tools the model was offered or asked for
+    if let Err(e) = check_tool_permission(app, run_id, name, args) {
+        tracing::warn!(error = %e, "tool call denied");
+        let _ = record_tool_stat(app, name, e.len(), true);
+        return Err(e);
+    }
+
+    // Over-budget calls don't run; the model gets a structured result telling it to wrap up
+    if let Ok(settings) = cached_settings(app).await {
+        if let Some(exceeded) = quota::acquire(app, &settings, run_id, name) {
+            tracing::warn!(limit = %exceeded["limit"], "tool budget exceeded");
+            let _ = record_tool_stat(app, name, 0, true);
+            return Ok(exceeded);
+        }
+    }
+
+    // A scoped task's runs list, search and diff only under its subtree
+    let scoped = run_scope(app, run_id)
+        .and_then(|scope| scope.map(|scope| apply_scope(name, args, &scope)).transpose());
+    let scoped = match scoped {
+        Ok(scoped) => scoped,
+        Err(e) => {
+            tracing::warn!(error = %e, "tool call outside the task's scope");
+            let _ = record_tool_stat(app, name, e.len(), true);
+            return Err(e);
+        }
+    };
+    let args = scoped.as_ref().unwrap_or(args);
+
+    // Output caps come from the run's project settings; each result records the ones it ran under
+    let limits = run_limits(app, run_id).await.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "failed to load tool output limits, using defaults");
+        OutputLimits::default()
+    });
+
+    let result = match name {
+        "list_files" => list_files(repo_path, args, app, run_id, &limits).await,
+        "read_file" => read_file(repo_path, args, app, run_id, &limits).await,
+        "grep" => grep(repo_path, args, app, run_id, &limits).await,
         "git_status" => git_status(repo_path, args, app, run_id).await,
-        "git_diff" => git_diff(repo_path, args, app, run_id).await,
+        "git_diff" => git_diff(repo_path, args, app, run_id, &limits).await,
         "git_log_short" => git_log_short(repo_path, args, app, run_id).await,
-        "run_command" => run_command(repo_path, args, app, run_id).await,
-        _ => Err(format!("Unknown tool: {}", name)),
+        "git_pull" => git_pull(repo_path, args, app, run_id).await,
+        "run_command" => run_command(repo_path, args, app, run_id, &limits).await,
+        "get_diagnostics" => get_diagnostics(repo_path, args, app, run_id).await,
+        _ => match registered_plugin(name) {
+            Some(plugin) => run_plugin(&plugin, repo_path, args, app, run_id, &limits).await,
+            None => Err(format!("Unknown tool: {}", name)),
+        },
+    };
+
+    // Stats are best-effort; never fail a tool call because the rollup failed
+    let (result_bytes, is_error) = match &result {
+        Ok(val) => (val.to_string().len(), false),
+        Err(e) => (e.len(), true),
+    };
+    let _ = record_tool_stat(app, name, result_bytes, is_error);
+    quota::record_bytes(app, run_id, result_bytes);
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    match &result {
+        Ok(_) => tracing::info!(duration_ms, result_bytes, "tool call"),
+        Err(e) => tracing::warn!(duration_ms, error = %e, "tool call failed"),
     }
+
+    result
 }
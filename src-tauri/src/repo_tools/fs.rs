@@ -1,49 +1,102 @@
 use ignore::WalkBuilder;
 use serde_json::{json, Value};
 use std::path::Path;
-use crate::repo_tools::safety::{sanitize_path, truncate_string};
+use crate::repo_tools::safety::{safe_spawn, sanitize_path, truncate_middle};
+use crate::repo_tools::context_ignore::{context_ignore, ContextIgnore};
+use crate::repo_tools::limits::{requested, OutputLimits};
 use crate::repo_tools::logging::log_tool_call;
+use crate::repo_tools::remote::remote_repo;
 use tauri::AppHandle;
 
-const MAX_FILES_DEFAULT: usize = 2000;
-const MAX_BYTES_DEFAULT: usize = 200_000;
+/// Common non-code directories
+fn is_excluded_dir(name: &str) -> bool {
+    matches!(name, ".git" | "node_modules" | "target" | "dist" | "build" | ".next" | "__pycache__" | ".venv" | "venv" | ".pytest_cache" | ".mypy_cache")
+}
+
+/// Files in a remote repository, from git where it's one, honoring .gitignore
+/// like the local walk does
+async fn list_remote_files(repo_path: &Path, subdir: &str, ignore: &ContextIgnore, max_files: usize) -> Result<Vec<String>, String> {
+    let (stdout, _, code) = safe_spawn("git", &["ls-files", "--cached", "--others", "--exclude-standard", "-z", "--", subdir], repo_path, 60)
+        .await
+        .map_err(|e| e.to_string())?;
+    let stdout = if code == 0 {
+        stdout
+    } else {
+        let args = find_args(subdir);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let (stdout, stderr, code) = safe_spawn("find", &args, repo_path, 60)
+            .await
+            .map_err(|e| e.to_string())?;
+        if code != 0 {
+            return Err(format!("Cannot list remote files: {}", stderr.trim()));
+        }
+        stdout
+    };
+    Ok(stdout.split('\0')
+        .map(|path| path.trim_start_matches("./"))
+        .filter(|path| !path.is_empty() && !path.split('/').any(is_excluded_dir) && !ignore.is_ignored(path))
+        .take(max_files)
+        .map(str::to_string)
+        .collect())
+}
+
+/// `find` arguments listing the files under `subdir`. The path is anchored at
+/// `./` so one like `-delete` can't be read as part of the expression.
+fn find_args(subdir: &str) -> Vec<String> {
+    let path = if subdir == "." || subdir.starts_with("./") {
+        subdir.to_string()
+    } else {
+        format!("./{}", subdir)
+    };
+    vec![path, "-type".into(), "f".into(), "-print0".into()]
+}
 
 pub async fn list_files(
     repo_path: &Path,
     args: &Value,
     app: &AppHandle,
     run_id: &str,
+    limits: &OutputLimits,
 ) -> Result<Value, String> {
-    let max_files = args.get("max_files")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(MAX_FILES_DEFAULT as u64) as usize;
+    let max_files = requested(args, "max_files", limits.list_files);
+    
+    // Optional subdirectory to list; paths stay relative to the repo root
+    let subdir = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+    sanitize_path(repo_path, subdir).map_err(|e| e.to_string())?;
+    
+    let ignore = context_ignore(app, run_id, repo_path).await?;
     
     let mut files = vec![];
-    let walker = WalkBuilder::new(repo_path)
-        .hidden(false)
-        .git_ignore(true)
-        .filter_entry(|e| {
-            let name = e.file_name()
-                .to_str()
-                .unwrap_or("");
-            // Exclude common non-code directories
-            !matches!(name, ".git" | "node_modules" | "target" | "dist" | "build" | ".next" | "__pycache__" | ".venv" | "venv" | ".pytest_cache" | ".mypy_cache")
-        })
-        .build();
+    if remote_repo(repo_path).is_some() {
+        files = list_remote_files(repo_path, subdir, &ignore, max_files).await?;
+    } else {
+        let walker = WalkBuilder::new(repo_path.join(subdir))
+            .hidden(false)
+            .git_ignore(true)
+            .filter_entry(|e| {
+                let name = e.file_name()
+                    .to_str()
+                    .unwrap_or("");
+                !is_excluded_dir(name)
+            })
+            .build();
     
-    for entry in walker {
-        if files.len() >= max_files {
-            break;
-        }
+        for entry in walker {
+            if files.len() >= max_files {
+                break;
+            }
         
-        if let Ok(entry) = entry {
-            if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                let rel_path = entry.path()
-                    .strip_prefix(repo_path)
-                    .unwrap_or(entry.path())
-                    .to_string_lossy()
-                    .replace('\\', "/");
-                files.push(rel_path);
+            if let Ok(entry) = entry {
+                if entry.file_type().map_or(false, |ft| ft.is_file()) {
+                    let rel_path = entry.path()
+                        .strip_prefix(repo_path)
+                        .unwrap_or(entry.path())
+                        .to_string_lossy()
+                        .replace('\\', "/");
+                    if !ignore.is_ignored(&rel_path) {
+                        files.push(rel_path);
+                    }
+                }
             }
         }
     }
@@ -53,6 +106,7 @@ pub async fn list_files(
         "files": files,
         "count": files.len(),
         "truncated": truncated,
+        "limits": { "max_files": max_files },
     });
     
     log_tool_call(app, run_id, "list_files", args, &result)?;
@@ -64,21 +118,26 @@ pub async fn read_file(
     args: &Value,
     app: &AppHandle,
     run_id: &str,
+    limits: &OutputLimits,
 ) -> Result<Value, String> {
     let rel_path = args.get("path")
         .and_then(|v| v.as_str())
         .ok_or("path is required")?;
     
-    let max_bytes = args.get("max_bytes")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(MAX_BYTES_DEFAULT as u64) as usize;
+    let max_bytes = requested(args, "max_bytes", limits.read_bytes);
     
     let full_path = sanitize_path(repo_path, rel_path)
         .map_err(|e| e.to_string())?;
+    if context_ignore(app, run_id, repo_path).await?.is_ignored(rel_path) {
+        return Err(format!("{} is excluded from context by the project's ignore globs", rel_path));
+    }
     
-    // Read file
-    let content = tokio::fs::read(&full_path).await
-        .map_err(|e| format!("Cannot read file: {}", e))?;
+    // Read file; remote hosts check the path again where it's resolved
+    let content = match remote_repo(repo_path) {
+        Some(remote) => remote.read(rel_path).await?,
+        None => tokio::fs::read(&full_path).await
+            .map_err(|e| format!("Cannot read file: {}", e))?,
+    };
     
     // Check if binary
     let is_binary = content.iter().any(|&b| b == 0 || (b < 32 && b != 9 && b != 10 && b != 13));
@@ -98,15 +157,45 @@ pub async fn read_file(
     let text = String::from_utf8(content)
         .map_err(|_| "File is not valid UTF-8")?;
     
-    let (content_truncated, truncated) = truncate_string(&text, max_bytes);
+    let (content_truncated, omitted) = truncate_middle(&text, max_bytes);
     
     let result = json!({
         "path": rel_path,
         "content": content_truncated,
         "bytes": text.len(),
-        "truncated": truncated,
+        "truncated": omitted > 0,
+        "omitted_bytes": omitted,
+        "limits": { "max_bytes": max_bytes },
     });
     
     log_tool_call(app, run_id, "read_file", args, &result)?;
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::new_id;
+
+    #[test]
+    fn test_find_args_keep_paths_out_of_the_expression() {
+        assert_eq!(find_args(".")[0], ".");
+        assert_eq!(find_args("src")[0], "./src");
+        assert_eq!(find_args("./src")[0], "./src");
+
+        let dir = std::env::temp_dir().join(format!("spectrail-find-{}", new_id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let status = std::process::Command::new("find")
+            .args(find_args("-delete"))
+            .current_dir(&dir)
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success());
+        assert!(dir.join("src/main.rs").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
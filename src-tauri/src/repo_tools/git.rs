@@ -1,11 +1,12 @@
 use serde_json::{json, Value};
 use std::path::Path;
-use crate::repo_tools::safety::{safe_spawn, truncate_string};
+use crate::repo_tools::safety::{safe_spawn, sanitize_path, truncate_middle};
+use crate::repo_tools::context_ignore::context_ignore;
+use crate::repo_tools::limits::OutputLimits;
 use crate::repo_tools::logging::log_tool_call;
+use crate::repo_tools::run_settings::{run_project_id, run_setting};
 use tauri::AppHandle;
 
-const MAX_DIFF_CHARS: usize = 200_000;
-
 pub async fn git_status(
     repo_path: &Path,
     args: &Value,
@@ -34,13 +35,36 @@ pub async fn git_diff(
     args: &Value,
     app: &AppHandle,
     run_id: &str,
+    limits: &OutputLimits,
 ) -> Result<Value, String> {
     let staged = args.get("staged").and_then(|v| v.as_bool()).unwrap_or(false);
+    let base = args.get("base").and_then(|v| v.as_str()).map(str::trim).filter(|b| !b.is_empty());
+    if let Some(base) = base {
+        check_ref(base)?;
+    }
+    
+    let paths: Vec<&str> = args.get("paths")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|p| p.as_str()).collect())
+        .unwrap_or_default();
     
+    // Reject paths that escape the repo before handing them to git
+    for path in &paths {
+        sanitize_path(repo_path, path).map_err(|e| e.to_string())?;
+    }
+    
+    // Against a base, the branch's own changes since it forked from it
+    let range = base.map(|base| format!("{}...HEAD", base));
     let mut cmd_args = vec!["diff"];
-    if staged {
+    if let Some(range) = &range {
+        cmd_args.push(range);
+    } else if staged {
         cmd_args.push("--staged");
     }
+    if !paths.is_empty() {
+        cmd_args.push("--");
+        cmd_args.extend(paths.iter().copied());
+    }
     
     let (stdout, stderr, code) = safe_spawn(
         "git",
@@ -49,19 +73,39 @@ pub async fn git_diff(
         10
     ).await.map_err(|e| e.to_string())?;
     
-    let (diff_truncated, truncated) = truncate_string(&stdout, MAX_DIFF_CHARS);
+    // Generated and vendored files the project ignores don't crowd out its own changes
+    let (diff, ignored_files) = context_ignore(app, run_id, repo_path).await?.filter_diff(&stdout);
+    let (diff_truncated, omitted) = truncate_middle(&diff, limits.diff_chars);
     
-    let result = json!({
+    let mut result = json!({
         "diff": diff_truncated,
         "stderr": stderr,
         "code": code,
-        "truncated": truncated,
+        "truncated": omitted > 0,
+        "omitted_bytes": omitted,
+        "limits": { "max_chars": limits.diff_chars },
     });
+    if !ignored_files.is_empty() {
+        result["ignored_files"] = json!(ignored_files);
+    }
     
     log_tool_call(app, run_id, "git_diff", args, &result)?;
     Ok(result)
 }
 
+/// A branch, tag or commit name safe to hand git as a revision: no options,
+/// ranges or characters git doesn't allow in ref names
+pub fn check_ref(name: &str) -> Result<(), String> {
+    let valid = !name.starts_with('-')
+        && !name.contains("..")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || "/._-~^@".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid git ref '{}'", name))
+    }
+}
+
 pub async fn git_log_short(
     repo_path: &Path,
     args: &Value,
@@ -107,3 +151,36 @@ pub async fn git_log_short(
     log_tool_call(app, run_id, "git_log_short", args, &result)?;
     Ok(result)
 }
+
+/// Fast-forward a project cloned from a URL, when the project allows it
+pub async fn git_pull(
+    _repo_path: &Path,
+    args: &Value,
+    app: &AppHandle,
+    run_id: &str,
+) -> Result<Value, String> {
+    if run_setting(app, run_id, "allow_git_pull").await?.as_deref() != Some("1") {
+        return Err("git_pull is disabled for this project (allow_git_pull setting)".to_string());
+    }
+    let project_id = run_project_id(app, run_id)?.ok_or("git_pull needs a run that belongs to a project")?;
+    let pulled = crate::clones::pull_project(app, &project_id).await?;
+    
+    let result = json!(pulled);
+    log_tool_call(app, run_id, "git_pull", args, &result)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_ref() {
+        assert!(check_ref("main").is_ok());
+        assert!(check_ref("origin/feature/login-2").is_ok());
+        assert!(check_ref("HEAD~3").is_ok());
+        assert!(check_ref("--output=/tmp/x").is_err());
+        assert!(check_ref("main..evil").is_err());
+        assert!(check_ref("main; rm -rf /").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::repo_tools::run_settings::run_project_id;
+use crate::settings::cached_settings;
+
+/// Settings behind each limit, with the value used when one isn't set
+const SETTINGS: &[(&str, usize)] = &[
+    ("tool_output_max_chars", 200_000),
+    ("tool_diff_max_chars", 200_000),
+    ("tool_read_max_bytes", 200_000),
+    ("tool_list_max_files", 2000),
+    ("tool_grep_max_results", 200),
+    ("tool_grep_max_line_chars", 200),
+];
+
+/// How much output each tool may hand back to the model, from the `tool_*_max_*`
+/// settings. A limit a tool call asks for can only lower these.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OutputLimits {
+    /// stdout and stderr of run_command, each
+    pub output_chars: usize,
+    pub diff_chars: usize,
+    pub read_bytes: usize,
+    pub list_files: usize,
+    pub grep_results: usize,
+    /// Longest matching line grep returns
+    pub grep_line_chars: usize,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        OutputLimits::from_settings(&HashMap::new())
+    }
+}
+
+impl OutputLimits {
+    /// Limits from settings; a missing, zero or malformed value falls back to the default
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let get = |key: &str| {
+            let default = SETTINGS.iter().find(|(k, _)| *k == key).map_or(0, |(_, d)| *d);
+            settings.get(key).and_then(|v| v.trim().parse().ok()).filter(|v| *v > 0).unwrap_or(default)
+        };
+        OutputLimits {
+            output_chars: get("tool_output_max_chars"),
+            diff_chars: get("tool_diff_max_chars"),
+            read_bytes: get("tool_read_max_bytes"),
+            list_files: get("tool_list_max_files"),
+            grep_results: get("tool_grep_max_results"),
+            grep_line_chars: get("tool_grep_max_line_chars"),
+        }
+    }
+}
+
+/// A count a tool call asked for in `args[key]`, capped at `limit`
+pub fn requested(args: &serde_json::Value, key: &str, limit: usize) -> usize {
+    args.get(key)
+        .and_then(|v| v.as_u64())
+        .map_or(limit, |v| (v as usize).min(limit))
+}
+
+/// Limits for a run's tools: the project's settings, falling back to its
+/// workspace's and then the global ones
+pub async fn run_limits(app: &AppHandle, run_id: &str) -> Result<OutputLimits, String> {
+    let mut settings: HashMap<String, String> = (*cached_settings(app).await?).clone();
+    if let Some(project_id) = run_project_id(app, run_id)? {
+        for (key, _) in SETTINGS {
+            if let Some(value) = db::get_project_setting(app, &project_id, key).map_err(|e| e.to_string())? {
+                settings.insert(key.to_string(), value);
+            }
+        }
+    }
+    Ok(OutputLimits::from_settings(&settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_limits_from_settings() {
+        let settings = HashMap::from([
+            ("tool_diff_max_chars".to_string(), "5000".to_string()),
+            ("tool_read_max_bytes".to_string(), "0".to_string()),
+            ("tool_grep_max_results".to_string(), "lots".to_string()),
+        ]);
+        let limits = OutputLimits::from_settings(&settings);
+        assert_eq!(limits.diff_chars, 5000);
+        assert_eq!(limits.read_bytes, 200_000);
+        assert_eq!(limits.grep_results, 200);
+        assert_eq!(limits, OutputLimits { diff_chars: 5000, ..OutputLimits::default() });
+
+        assert_eq!(requested(&json!({ "max_files": 10 }), "max_files", 2000), 10);
+        assert_eq!(requested(&json!({ "max_files": 1_000_000 }), "max_files", 2000), 2000);
+        assert_eq!(requested(&json!({}), "max_files", 2000), 2000);
+    }
+}
@@ -1,5 +1,6 @@
 use serde_json::Value;
 use tauri::AppHandle;
+use crate::blobs;
 use crate::db;
 use crate::models::*;
 
@@ -42,15 +43,19 @@ pub fn log_tool_call(
         result_str
     };
     
+    let (stored, hash) = blobs::put(&conn, &final_result).map_err(|e| e.to_string())?;
+    
+    // seq is assigned in the same statement so parallel calls can't share one
     conn.execute(
-        "INSERT INTO tool_calls (id, run_id, name, args_json, result_json, created_at) 
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO tool_calls (id, run_id, name, args_json, result_json, created_at, seq, result_hash) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, (SELECT COALESCE(MAX(seq), 0) + 1 FROM tool_calls WHERE run_id = ?2), ?7)",
         (&id, 
          run_id, 
          name, 
          &args.to_string(), 
-         &final_result, 
-         &created_at)
+         &stored, 
+         &created_at,
+         &hash)
     ).map_err(|e| e.to_string())?;
     
     Ok(())
@@ -59,8 +64,8 @@ pub fn log_tool_call(
 pub fn list_tool_calls(app: &AppHandle, run_id: &str) -> Result<Vec<ToolCallRow>, String> {
     let conn = db::connect(app).map_err(|e| e.to_string())?;
     let mut stmt = conn.prepare(
-        "SELECT id, run_id, name, args_json, result_json, created_at 
-         FROM tool_calls WHERE run_id = ?1 ORDER BY created_at ASC"
+        "SELECT id, run_id, name, args_json, result_json, created_at, seq, result_hash 
+         FROM tool_calls WHERE run_id = ?1 ORDER BY seq ASC"
     ).map_err(|e| e.to_string())?;
     
     let rows = stmt.query_map([run_id], |r| {
@@ -69,8 +74,9 @@ pub fn list_tool_calls(app: &AppHandle, run_id: &str) -> Result<Vec<ToolCallRow>
             run_id: r.get(1)?,
             name: r.get(2)?,
             args_json: r.get(3)?,
-            result_json: r.get(4)?,
+            result_json: blobs::hydrate(&conn, r.get(4)?, r.get(7)?)?,
             created_at: r.get(5)?,
+            seq: r.get(6)?,
         })
     }).map_err(|e| e.to_string())?;
     
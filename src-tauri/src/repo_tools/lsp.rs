@@ -0,0 +1,404 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::time::{timeout, Instant};
+
+use crate::repo_tools::logging::log_tool_call;
+use crate::repo_tools::run_settings::run_setting;
+use crate::repo_tools::safety::sanitize_path;
+use crate::settings::{cached_settings, AppSettings};
+
+const MAX_PATHS: usize = 20;
+const MAX_DIAGNOSTICS: usize = 200;
+/// Diagnostics count as settled once every file has been published and no
+/// update has arrived for this long
+const QUIET_PERIOD: Duration = Duration::from_secs(2);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Command and arguments for a supported `lsp_server` setting value
+fn server_command(name: &str) -> Option<(&'static str, &'static [&'static str])> {
+    match name {
+        "rust-analyzer" => Some(("rust-analyzer", &[])),
+        "tsserver" => Some(("typescript-language-server", &["--stdio"])),
+        "pyright" => Some(("pyright-langserver", &["--stdio"])),
+        _ => None,
+    }
+}
+
+/// LSP language id for a file, or None when no supported server handles it
+fn language_id(path: &str) -> Option<&'static str> {
+    match Path::new(path).extension()?.to_str()? {
+        "rs" => Some("rust"),
+        "ts" | "mts" | "cts" => Some("typescript"),
+        "tsx" => Some("typescriptreact"),
+        "js" | "mjs" | "cjs" => Some("javascript"),
+        "jsx" => Some("javascriptreact"),
+        "py" | "pyi" => Some("python"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LspDiagnostic {
+    pub path: String,
+    /// 1-based
+    pub line: u64,
+    /// 1-based
+    pub column: u64,
+    /// error|warning|info|hint
+    pub severity: &'static str,
+    pub message: String,
+    pub source: Option<String>,
+    pub code: Option<String>,
+}
+
+fn encode_message(message: &Value) -> Vec<u8> {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+}
+
+/// First complete message in `buf` and the number of bytes it used
+fn decode_message(buf: &[u8]) -> Result<Option<(Value, usize)>, String> {
+    let Some(header_end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return Ok(None);
+    };
+    let headers = String::from_utf8_lossy(&buf[..header_end]);
+    let length: usize = headers.lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("content-length").then(|| value.trim().parse().ok())?
+        })
+        .ok_or("Language server sent a message without Content-Length")?;
+    let start = header_end + 4;
+    if buf.len() < start + length {
+        return Ok(None);
+    }
+    let message = serde_json::from_slice(&buf[start..start + length])
+        .map_err(|e| format!("Malformed message from language server: {}", e))?;
+    Ok(Some((message, start + length)))
+}
+
+fn severity_name(severity: Option<u64>) -> &'static str {
+    match severity {
+        Some(2) => "warning",
+        Some(3) => "info",
+        Some(4) => "hint",
+        // Servers omitting severity are reporting errors in practice
+        _ => "error",
+    }
+}
+
+/// Diagnostics of a `textDocument/publishDiagnostics` notification
+fn parse_publish(params: &Value, path: &str) -> Vec<LspDiagnostic> {
+    params["diagnostics"].as_array().into_iter().flatten()
+        .map(|d| LspDiagnostic {
+            path: path.to_string(),
+            line: d["range"]["start"]["line"].as_u64().unwrap_or(0) + 1,
+            column: d["range"]["start"]["character"].as_u64().unwrap_or(0) + 1,
+            severity: severity_name(d["severity"].as_u64()),
+            message: d["message"].as_str().unwrap_or_default().to_string(),
+            source: d["source"].as_str().map(str::to_string),
+            code: match &d["code"] {
+                Value::String(code) => Some(code.clone()),
+                Value::Number(code) => Some(code.to_string()),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+/// Keep a `textDocument/publishDiagnostics` for one of the opened files,
+/// replacing what the server published for it before
+fn record_publish(message: &Value, uris: &HashMap<String, String>, published: &mut HashMap<String, Vec<LspDiagnostic>>) {
+    if message["method"] != "textDocument/publishDiagnostics" {
+        return;
+    }
+    if let Some(path) = message["params"]["uri"].as_str().and_then(|uri| uris.get(uri)) {
+        published.insert(path.clone(), parse_publish(&message["params"], path));
+    }
+}
+
+fn file_uri(path: &Path) -> Result<String, String> {
+    reqwest::Url::from_file_path(path)
+        .map(|u| u.to_string())
+        .map_err(|_| format!("Cannot build a file URI for {}", path.display()))
+}
+
+/// A running language server speaking JSON-RPC over stdio
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+    buf: Vec<u8>,
+    next_id: i64,
+}
+
+impl Session {
+    fn start(command: &str, args: &[&str], root: &Path) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Cannot start {}: {}", command, e))?;
+        let stdin = child.stdin.take().ok_or("Language server has no stdin")?;
+        let stdout = child.stdout.take().ok_or("Language server has no stdout")?;
+        Ok(Session { child, stdin, stdout, buf: vec![], next_id: 1 })
+    }
+
+    async fn send(&mut self, message: Value) -> Result<(), String> {
+        self.stdin.write_all(&encode_message(&message)).await
+            .map_err(|e| format!("Language server closed its input: {}", e))
+    }
+
+    async fn notify(&mut self, method: &str, params: Value) -> Result<(), String> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params })).await
+    }
+
+    async fn next_message(&mut self) -> Result<Value, String> {
+        loop {
+            if let Some((message, used)) = decode_message(&self.buf)? {
+                self.buf.drain(..used);
+                // Servers ask for configuration, progress tokens, etc.; answer with defaults
+                if let (Some(id), Some(method)) = (message.get("id"), message["method"].as_str()) {
+                    let result = match method {
+                        "workspace/configuration" => {
+                            let items = message["params"]["items"].as_array().map_or(0, |i| i.len());
+                            Value::Array(vec![Value::Null; items])
+                        }
+                        _ => Value::Null,
+                    };
+                    self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result })).await?;
+                    continue;
+                }
+                return Ok(message);
+            }
+            let mut chunk = [0u8; 8192];
+            let read = self.stdout.read(&mut chunk).await.map_err(|e| e.to_string())?;
+            if read == 0 {
+                return Err("Language server exited".into());
+            }
+            self.buf.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    /// Send a request and wait for its response. Notifications arriving in
+    /// the meantime are handed to `on_notification`.
+    async fn request(&mut self, method: &str, params: Value, on_notification: &mut impl FnMut(&Value)) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })).await?;
+        loop {
+            let message = self.next_message().await?;
+            if message.get("id").and_then(|v| v.as_i64()) != Some(id) {
+                on_notification(&message);
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(format!("{} failed: {}", method, error["message"].as_str().unwrap_or("unknown error")));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    async fn shutdown(mut self) {
+        let _ = timeout(SHUTDOWN_TIMEOUT, self.request("shutdown", Value::Null, &mut |_| {})).await;
+        let _ = self.notify("exit", Value::Null).await;
+        let _ = timeout(SHUTDOWN_TIMEOUT, self.child.wait()).await;
+    }
+}
+
+/// Open `paths` in the language server and collect what it publishes for
+/// them. Returns the diagnostics and whether the deadline cut collection short.
+async fn collect_diagnostics(
+    command: &str,
+    args: &[&str],
+    repo_path: &Path,
+    files: &[(String, PathBuf)],
+    deadline: Instant,
+) -> Result<(Vec<LspDiagnostic>, bool), String> {
+    let root = repo_path.canonicalize().map_err(|e| e.to_string())?;
+    let mut session = Session::start(command, args, &root)?;
+
+    // uri -> repo-relative path, and the diagnostics published so far
+    let mut uris: HashMap<String, String> = HashMap::new();
+    for (path, full_path) in files {
+        uris.insert(file_uri(full_path)?, path.clone());
+    }
+    let mut published: HashMap<String, Vec<LspDiagnostic>> = HashMap::new();
+
+    let root_uri = file_uri(&root)?;
+    let params = json!({
+        "processId": std::process::id(),
+        "rootUri": root_uri,
+        "workspaceFolders": [{ "uri": root_uri, "name": "repo" }],
+        "capabilities": {
+            "textDocument": { "publishDiagnostics": { "relatedInformation": false } },
+            "workspace": { "configuration": true, "workspaceFolders": true },
+        },
+    });
+    let mut on_message = |m: &Value| record_publish(m, &uris, &mut published);
+    let initialize = session.request("initialize", params, &mut on_message);
+    let result = match timeout(deadline.saturating_duration_since(Instant::now()), initialize).await {
+        Ok(result) => result,
+        Err(_) => {
+            session.shutdown().await;
+            return Ok((vec![], true));
+        }
+    };
+    if let Err(e) = result {
+        session.shutdown().await;
+        return Err(e);
+    }
+    session.notify("initialized", json!({})).await?;
+
+    for (path, full_path) in files {
+        let text = std::fs::read_to_string(full_path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        session.notify("textDocument/didOpen", json!({
+            "textDocument": {
+                "uri": file_uri(full_path)?,
+                "languageId": language_id(path).unwrap_or("plaintext"),
+                "version": 1,
+                "text": text,
+            }
+        })).await?;
+    }
+
+    let mut timed_out = false;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            timed_out = true;
+            break;
+        }
+        let settled = published.len() == files.len();
+        let wait = if settled { QUIET_PERIOD.min(deadline - now) } else { deadline - now };
+        match timeout(wait, session.next_message()).await {
+            Ok(Ok(message)) => record_publish(&message, &uris, &mut published),
+            Ok(Err(e)) => {
+                session.shutdown().await;
+                return Err(e);
+            }
+            Err(_) if settled => break,
+            Err(_) => {
+                timed_out = true;
+                break;
+            }
+        }
+    }
+    session.shutdown().await;
+
+    let mut diagnostics: Vec<LspDiagnostic> = published.into_values().flatten().collect();
+    diagnostics.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+    Ok((diagnostics, timed_out))
+}
+
+/// `get_diagnostics` tool: type errors, unused symbols and the like reported
+/// by the project's configured language server for `paths`
+pub async fn get_diagnostics(
+    repo_path: &Path,
+    args: &Value,
+    app: &AppHandle,
+    run_id: &str,
+) -> Result<Value, String> {
+    let settings = cached_settings(app).await?;
+    let server = run_setting(app, run_id, "lsp_server").await?.unwrap_or_default();
+    let server = server.trim();
+    if server.is_empty() {
+        return Err("No language server configured; set lsp_server to rust-analyzer, tsserver, or pyright".into());
+    }
+    let (command, command_args) = server_command(server)
+        .ok_or_else(|| format!("Unknown language server '{}' (expected rust-analyzer, tsserver, or pyright)", server))?;
+
+    let paths: Vec<&str> = args.get("paths")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|p| p.as_str()).collect())
+        .unwrap_or_default();
+    if paths.is_empty() {
+        return Err("paths is required".into());
+    }
+    let mut files = vec![];
+    let mut skipped = vec![];
+    for path in paths.iter().take(MAX_PATHS) {
+        let full_path = sanitize_path(repo_path, path).map_err(|e| e.to_string())?;
+        if language_id(path).is_some() && full_path.is_file() {
+            files.push((path.to_string(), full_path));
+        } else {
+            skipped.push(path.to_string());
+        }
+    }
+    skipped.extend(paths.iter().skip(MAX_PATHS).map(|p| p.to_string()));
+
+    let timeout_secs = AppSettings::from_map(&settings).lsp_timeout_secs;
+    let (mut diagnostics, timed_out) = match files.is_empty() {
+        true => (vec![], false),
+        false => {
+            let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+            collect_diagnostics(command, command_args, repo_path, &files, deadline).await?
+        }
+    };
+    let truncated = diagnostics.len() > MAX_DIAGNOSTICS;
+    diagnostics.truncate(MAX_DIAGNOSTICS);
+
+    let result = json!({
+        "server": server,
+        "files": files.len(),
+        "skipped": skipped,
+        "diagnostics": diagnostics,
+        "timed_out": timed_out,
+        "truncated": truncated,
+    });
+    log_tool_call(app, run_id, "get_diagnostics", args, &result)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing_and_publish_parsing() {
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {
+                "uri": "file:///repo/src/lib.rs",
+                "diagnostics": [
+                    { "range": { "start": { "line": 4, "character": 8 } }, "severity": 2, "message": "unused variable: `x`", "source": "rustc", "code": "unused_variables" },
+                    { "range": { "start": { "line": 0, "character": 0 } }, "message": "mismatched types", "code": 308 },
+                ],
+            },
+        });
+        let mut buf = encode_message(&message);
+        buf.extend_from_slice(b"Content-Length: 10\r\n\r\n{\"id\"");
+        let (decoded, used) = decode_message(&buf).unwrap().unwrap();
+        assert_eq!(decoded, message);
+        assert!(decode_message(&buf[used..]).unwrap().is_none());
+        assert!(decode_message(b"X-Other: 1\r\n\r\n{}").is_err());
+
+        let diagnostics = parse_publish(&decoded["params"], "src/lib.rs");
+        assert_eq!(diagnostics[0], LspDiagnostic {
+            path: "src/lib.rs".into(),
+            line: 5,
+            column: 9,
+            severity: "warning",
+            message: "unused variable: `x`".into(),
+            source: Some("rustc".into()),
+            code: Some("unused_variables".into()),
+        });
+        assert_eq!((diagnostics[1].severity, diagnostics[1].code.as_deref()), ("error", Some("308")));
+
+        assert_eq!(language_id("web/App.tsx"), Some("typescriptreact"));
+        assert_eq!(language_id("README.md"), None);
+        assert!(server_command("pyright").is_some());
+    }
+}
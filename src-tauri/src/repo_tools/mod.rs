@@ -1,11 +1,33 @@
+pub mod bench;
+pub mod context_ignore;
+pub mod custom_commands;
 pub mod dispatcher;
 pub mod fs;
 pub mod git;
+pub mod limits;
 pub mod logging;
+pub mod lsp;
+pub mod normalize;
+pub mod outcome;
+pub mod permissions;
+pub mod plugins;
+pub mod processes;
+pub mod quota;
+pub mod remote;
+pub mod repo_lock;
+pub mod run_settings;
 pub mod runner;
 pub mod safety;
+pub mod sandbox;
 pub mod schemas;
+pub mod scope;
+pub mod script_fallback;
 pub mod search;
+pub mod stats;
+pub mod test_results;
+pub mod toolchain;
+pub mod wsl;
 
 pub use dispatcher::{dispatch_repo_tool, repo_tool_schemas};
 pub use logging::list_tool_calls;
+pub use stats::{list_tool_stats, analyze_run_tool_usage, ToolLoopGuard};
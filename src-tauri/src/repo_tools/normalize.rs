@@ -0,0 +1,96 @@
+/// Remove ANSI escape sequences (colors, cursor movement, terminal titles) and
+/// other control characters except tabs and newlines
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: up to BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Two-character sequences
+                _ => {}
+            },
+            '\n' | '\t' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// What a terminal would show for a line redrawn with carriage returns: the
+/// last non-empty redraw
+fn collapse_progress(line: &str) -> &str {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    line.rsplit('\r').find(|part| !part.is_empty()).unwrap_or("")
+}
+
+fn push_line(lines: &mut Vec<String>, line: &str, repeats: usize) {
+    if repeats > 1 && !line.trim().is_empty() {
+        lines.push(format!("{} [repeated {} times]", line, repeats));
+    } else {
+        lines.push(line.to_string());
+    }
+}
+
+/// Clean up command output before it's truncated, logged or shown to the model:
+/// strips ANSI codes, keeps only the final state of `\r` progress lines, and
+/// collapses runs of a repeated line into one line with a count
+pub fn normalize_output(s: &str) -> String {
+    let stripped = strip_ansi(s);
+    let mut lines: Vec<String> = vec![];
+    let mut previous: Option<&str> = None;
+    let mut repeats = 0;
+    for line in stripped.split('\n').map(collapse_progress) {
+        if previous == Some(line) {
+            repeats += 1;
+            continue;
+        }
+        if let Some(prev) = previous {
+            push_line(&mut lines, prev, repeats);
+        }
+        previous = Some(line);
+        repeats = 1;
+    }
+    if let Some(prev) = previous {
+        push_line(&mut lines, prev, repeats);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_output() {
+        let raw = "\u{1b}[1m\u{1b}[32m   Compiling\u{1b}[0m app v0.1.0\n\
+                   \u{1b}]0;title\u{7}Downloading 10%\rDownloading 50%\rDownloading 100%\r\n\
+                   warning: unused\nwarning: unused\nwarning: unused\n\n\n\
+                   error[E0308]: mismatched types\n";
+        assert_eq!(
+            normalize_output(raw),
+            "   Compiling app v0.1.0\nDownloading 100%\nwarning: unused [repeated 3 times]\n\nerror[E0308]: mismatched types\n"
+        );
+        assert_eq!(normalize_output("plain\ttext"), "plain\ttext");
+    }
+}
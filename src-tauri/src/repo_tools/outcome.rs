@@ -0,0 +1,95 @@
+use serde::Serialize;
+
+/// What a check's exit status means, beyond zero and non-zero
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandOutcome {
+    Passed,
+    Failed,
+    /// The runner or a tool it calls isn't installed
+    ToolMissing,
+    /// The project doesn't define the package script the check runs
+    ScriptUndefined,
+}
+
+/// Output of a shell or OS that couldn't find the program
+const TOOL_MISSING_PATTERNS: &[&str] = &[
+    "command not found",
+    "is not recognized as an internal or external command",
+    "no such file or directory (os error 2)",
+    "no such command:",
+    "no such subcommand",
+    "no module named",
+    "could not determine executable to run",
+    "err_pnpm_recursive_exec_first_fail",
+];
+
+/// Output of npm, pnpm and yarn (classic and berry) when a package has no such script
+const SCRIPT_MISSING_PATTERNS: &[&str] = &[
+    "missing script:",
+    "err_pnpm_no_script",
+    "couldn't find a script named",
+    // The placeholder `npm init` writes for the test script
+    "error: no test specified",
+];
+
+/// Package script `cmd` runs, for npm/pnpm/yarn commands that run one
+pub fn package_script(cmd: &[String]) -> Option<&str> {
+    match cmd {
+        [npm, script] if npm == "npm" && script == "test" => Some(script),
+        [npm, run, script] if npm == "npm" && run == "run" => Some(script),
+        [manager, script] if manager == "pnpm" || manager == "yarn" => Some(script),
+        _ => None,
+    }
+}
+
+/// Classify a finished command by its exit code and output. `script` is the
+/// package script it ran, if any, so a missing script isn't mistaken for a
+/// missing tool (yarn reports both as `Command "x" not found`).
+pub fn classify(script: Option<&str>, code: i32, stdout: &str, stderr: &str) -> CommandOutcome {
+    if code == 0 {
+        return CommandOutcome::Passed;
+    }
+    let output = format!("{}\n{}", stdout, stderr).to_lowercase();
+    if let Some(script) = script {
+        let yarn_missing = format!("command \"{}\" not found", script.to_lowercase());
+        let pnpm_missing = format!("has a \"{}\" script", script.to_lowercase());
+        if SCRIPT_MISSING_PATTERNS.iter().any(|p| output.contains(p))
+            || output.contains(&yarn_missing)
+            || output.contains(&pnpm_missing)
+        {
+            return CommandOutcome::ScriptUndefined;
+        }
+    }
+    let yarn_bin_missing = output.contains("command \"") && output.contains("\" not found");
+    if code == 127 || yarn_bin_missing || TOOL_MISSING_PATTERNS.iter().any(|p| output.contains(p)) {
+        return CommandOutcome::ToolMissing;
+    }
+    CommandOutcome::Failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        let npm_lint = cmd(&["npm", "run", "lint"]);
+        assert_eq!(package_script(&npm_lint), Some("lint"));
+        assert_eq!(package_script(&cmd(&["pnpm", "exec", "tsc", "--noEmit"])), None);
+        assert_eq!(package_script(&cmd(&["cargo", "test"])), None);
+
+        assert_eq!(classify(Some("lint"), 0, "ok", ""), CommandOutcome::Passed);
+        assert_eq!(classify(Some("lint"), 1, "", "npm error Missing script: \"lint\""), CommandOutcome::ScriptUndefined);
+        assert_eq!(classify(Some("lint"), 1, "", "error Command \"lint\" not found."), CommandOutcome::ScriptUndefined);
+        assert_eq!(classify(Some("lint"), 1, "src/a.ts\n  3:1  error  'x' is unused", ""), CommandOutcome::Failed);
+        // The same yarn message about a binary is a missing tool
+        assert_eq!(classify(None, 1, "", "error Command \"tsc\" not found."), CommandOutcome::ToolMissing);
+        assert_eq!(classify(None, 127, "", "sh: ruff: not found"), CommandOutcome::ToolMissing);
+        assert_eq!(classify(None, 101, "", "error: no such command: `clippy`"), CommandOutcome::ToolMissing);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use tauri::AppHandle;
+use crate::db;
+use crate::repo_tools::plugins::plugin_tier;
+
+/// What a run's tools may do. Tiers are ordered: each includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolTier {
+    ReadOnly,
+    ReadRun,
+    ReadWrite,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RunPermissions {
+    pub tier: ToolTier,
+    /// `run_command` kinds allowed when the tier includes running commands
+    pub command_kinds: &'static [&'static str],
+}
+
+/// Tier assigned to each run type. Plans only explore; verify and baseline
+/// captures run the project's own checks, as do custom workflows (which offer
+/// the model only the tools their definition lists); nothing writes to the
+/// repository yet, apart from git_pull fast-forwarding clones the app manages.
+pub fn permissions_for(run_type: &str) -> RunPermissions {
+    match run_type {
+        "verify" | "baseline" | "custom" => RunPermissions { tier: ToolTier::ReadRun, command_kinds: &["tests", "lint", "build", "typecheck", "bench"] },
+        _ => RunPermissions { tier: ToolTier::ReadOnly, command_kinds: &[] },
+    }
+}
+
+/// Tier a tool needs; a plugin's comes from its manifest. Tools not listed
+/// here are assumed to write, so a new tool stays out of read-only runs until
+/// it's classified.
+fn required_tier(tool: &str) -> ToolTier {
+    match tool {
+        "list_files" | "read_file" | "grep" | "git_status" | "git_diff" | "git_log_short" => ToolTier::ReadOnly,
+        // Also gated by the project's allow_git_pull setting
+        "run_command" | "get_diagnostics" | "git_pull" => ToolTier::ReadRun,
+        _ => plugin_tier(tool).unwrap_or(ToolTier::ReadWrite),
+    }
+}
+
+pub fn check_permission(permissions: &RunPermissions, run_type: &str, tool: &str, args: &Value) -> Result<(), String> {
+    let required = required_tier(tool);
+    if required > permissions.tier {
+        return Err(format!(
+            "Permission denied: {} is not available to {} runs ({:?} tier)",
+            tool, run_type, permissions.tier
+        ));
+    }
+    if tool == "run_command" {
+        let kind = args.get("kind").and_then(|v| v.as_str()).unwrap_or_default();
+        if !permissions.command_kinds.contains(&kind) {
+            return Err(format!(
+                "Permission denied: run_command kind '{}' is not approved for {} runs (allowed: {})",
+                kind, run_type, permissions.command_kinds.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check a tool call against its run's tier. Calls outside a known run get
+/// the read-only tier.
+pub fn check_tool_permission(app: &AppHandle, run_id: &str, tool: &str, args: &Value) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let run_type: String = conn.query_row("SELECT run_type FROM runs WHERE id = ?1", [run_id], |r| r.get(0))
+        .unwrap_or_else(|_| "unknown".to_string());
+    check_permission(&permissions_for(&run_type), &run_type, tool, args)
+}
+
+/// Tool schemas a run type may use, so the model isn't offered tools it can't call
+pub fn tool_schemas_for(run_type: &str, schemas: Vec<Value>) -> Vec<Value> {
+    let permissions = permissions_for(run_type);
+    schemas.into_iter()
+        .filter(|schema| {
+            let name = schema["function"]["name"].as_str().unwrap_or_default();
+            required_tier(name) <= permissions.tier
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_permission() {
+        let plan = permissions_for("plan");
+        assert!(check_permission(&plan, "plan", "read_file", &json!({})).is_ok());
+        assert!(check_permission(&plan, "plan", "run_command", &json!({ "kind": "tests" })).is_err());
+        assert!(check_permission(&plan, "plan", "write_file", &json!({})).is_err());
+
+        let verify = permissions_for("verify");
+        assert!(check_permission(&verify, "verify", "run_command", &json!({ "kind": "lint" })).is_ok());
+        assert!(check_permission(&verify, "verify", "run_command", &json!({ "kind": "deploy" })).is_err());
+        assert!(check_permission(&verify, "verify", "write_file", &json!({})).is_err());
+
+        let schemas = vec![
+            json!({ "function": { "name": "grep" } }),
+            json!({ "function": { "name": "run_command" } }),
+        ];
+        assert_eq!(tool_schemas_for("plan", schemas.clone()).len(), 1);
+        assert_eq!(tool_schemas_for("verify", schemas).len(), 2);
+    }
+}
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+
+use crate::repo_tools::limits::OutputLimits;
+use crate::repo_tools::logging::log_tool_call;
+use crate::repo_tools::normalize::normalize_output;
+use crate::repo_tools::permissions::ToolTier;
+use crate::repo_tools::processes::resolve_limits;
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::repo_lock;
+use crate::repo_tools::run_settings::run_setting;
+use crate::repo_tools::runner::Executor;
+use crate::repo_tools::sandbox::resolve_sandbox;
+use crate::repo_tools::safety::truncate_middle;
+use crate::repo_tools::schemas::builtin_tool_schemas;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+const MAX_TIMEOUT_SECS: u64 = 600;
+
+/// Tool plugins loaded from the plugins directory
+static PLUGINS: RwLock<Vec<PluginManifest>> = RwLock::new(Vec::new());
+
+/// A third-party repo tool: an executable run in the repository that reads
+/// `{"tool", "args", "repo_path"}` as JSON on stdin and prints its result,
+/// as JSON or text, on stdout. One manifest per `.json` file in the plugins
+/// directory, e.g.
+///
+/// ```json
+/// {
+///   "name": "terraform_plan",
+///   "description": "Show the changes terraform would make in a module",
+///   "parameters": {"type": "object", "properties": {"module": {"type": "string"}}, "required": ["module"]},
+///   "command": ["./terraform-plan-tool"],
+///   "tier": "read_run"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(deny_unknown_fields)]
+pub struct PluginManifest {
+    pub name: String,
+    pub description: String,
+    /// JSON schema of the tool's arguments
+    #[serde(default = "empty_parameters")]
+    pub parameters: Value,
+    /// Program and arguments; a relative program path is resolved against the plugins directory
+    pub command: Vec<String>,
+    /// What the tool does to the repository, which decides the runs it's offered to
+    #[serde(default = "default_tier")]
+    pub tier: ToolTier,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+fn empty_parameters() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+fn default_tier() -> ToolTier {
+    ToolTier::ReadOnly
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct PluginList {
+    pub dir: String,
+    pub plugins: Vec<PluginManifest>,
+    /// Manifests that were skipped, and why
+    pub errors: Vec<String>,
+}
+
+pub fn validate_manifest(manifest: &PluginManifest) -> Result<(), String> {
+    let name = &manifest.name;
+    let valid_name = name.len() <= 64
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid_name {
+        return Err(format!("Invalid plugin tool name '{}' (use lowercase letters, digits and _)", name));
+    }
+    let builtin = builtin_tool_schemas().iter().any(|schema| schema["function"]["name"] == name.as_str());
+    if builtin {
+        return Err(format!("Plugin tool '{}' has the name of a built-in tool", name));
+    }
+    if manifest.description.trim().is_empty() {
+        return Err(format!("Plugin tool '{}' needs a description", name));
+    }
+    if manifest.parameters.get("type").and_then(Value::as_str) != Some("object") {
+        return Err(format!("The parameters of plugin tool '{}' must be a JSON schema of type object", name));
+    }
+    if manifest.command.first().is_none_or(|program| program.trim().is_empty()) {
+        return Err(format!("Plugin tool '{}' has no command", name));
+    }
+    if manifest.tier == ToolTier::ReadWrite {
+        return Err(format!("Plugin tool '{}' can't be read_write; no run may write to the repository yet", name));
+    }
+    if manifest.timeout_secs.is_some_and(|secs| secs == 0 || secs > MAX_TIMEOUT_SECS) {
+        return Err(format!("The timeout of plugin tool '{}' must be between 1 and {} seconds", name, MAX_TIMEOUT_SECS));
+    }
+    Ok(())
+}
+
+/// A manifest file's plugin, with a relative program path resolved against `dir`
+fn parse_manifest(text: &str, dir: &Path) -> Result<PluginManifest, String> {
+    let mut manifest: PluginManifest = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    validate_manifest(&manifest)?;
+    let program = &manifest.command[0];
+    if Path::new(program).is_relative() && (program.contains('/') || program.contains('\\')) {
+        manifest.command[0] = dir.join(program).to_string_lossy().to_string();
+    }
+    Ok(manifest)
+}
+
+pub fn plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("plugins"))
+}
+
+/// Read the plugins directory and replace the loaded plugins with its manifests
+pub fn load_plugins(app: &AppHandle) -> Result<PluginList, String> {
+    let dir = plugins_dir(app)?;
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    paths.sort();
+
+    let mut plugins: Vec<PluginManifest> = vec![];
+    let mut errors = vec![];
+    for path in paths {
+        let file = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let manifest = std::fs::read_to_string(&path).map_err(|e| e.to_string())
+            .and_then(|text| parse_manifest(&text, &dir));
+        match manifest {
+            Ok(manifest) if plugins.iter().any(|p| p.name == manifest.name) => {
+                errors.push(format!("{}: another plugin already defines '{}'", file, manifest.name));
+            }
+            Ok(manifest) => plugins.push(manifest),
+            Err(e) => errors.push(format!("{}: {}", file, e)),
+        }
+    }
+
+    *PLUGINS.write().map_err(|e| e.to_string())? = plugins.clone();
+    Ok(PluginList { dir: dir.to_string_lossy().to_string(), plugins, errors })
+}
+
+pub fn registered_plugin(name: &str) -> Option<PluginManifest> {
+    PLUGINS.read().ok()?.iter().find(|p| p.name == name).cloned()
+}
+
+pub fn plugin_tier(name: &str) -> Option<ToolTier> {
+    registered_plugin(name).map(|p| p.tier)
+}
+
+fn plugin_schema(plugin: &PluginManifest) -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": plugin.name,
+            "description": plugin.description,
+            "parameters": plugin.parameters,
+        }
+    })
+}
+
+pub fn plugin_schemas() -> Vec<Value> {
+    PLUGINS.read().map(|plugins| plugins.iter().map(plugin_schema).collect()).unwrap_or_default()
+}
+
+/// Arguments must be an object with the properties the schema requires
+fn check_args(plugin: &PluginManifest, args: &Value) -> Result<(), String> {
+    if !args.is_object() {
+        return Err(format!("{} takes an object of arguments", plugin.name));
+    }
+    let required = plugin.parameters.get("required").and_then(Value::as_array);
+    for key in required.into_iter().flatten().filter_map(Value::as_str) {
+        if args.get(key).is_none_or(Value::is_null) {
+            return Err(format!("{} requires '{}'", plugin.name, key));
+        }
+    }
+    Ok(())
+}
+
+/// Run a plugin tool in the repository with the model's arguments on stdin
+pub async fn run_plugin(
+    plugin: &PluginManifest,
+    repo_path: &Path,
+    args: &Value,
+    app: &AppHandle,
+    run_id: &str,
+    limits: &OutputLimits,
+) -> Result<Value, String> {
+    // Plugins are installed on this machine, so they can't reach a repository on another host
+    if remote_repo(repo_path).is_some() {
+        return Err(format!("Plugin tool {} can't run against a remote repository", plugin.name));
+    }
+    check_args(plugin, args)?;
+
+    // Plugins run like project scripts: in the project's container when it has
+    // one, under the same limits, tracked, and queued behind other commands
+    let sandbox = resolve_sandbox(app, run_id).await?;
+    let executor = Executor {
+        app,
+        run_id,
+        repo_path,
+        sandbox: sandbox.as_ref(),
+        toolchain: None,
+        limits: resolve_limits(app, run_id).await?,
+        timeout_secs: plugin.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS),
+        // The plugin's executable lives outside the repository
+        readonly: vec![plugins_dir(app)?],
+        env: vec![("SPECTRAIL_RUN_ID".to_string(), run_id.to_string())],
+    };
+    let _repo_guard = match run_setting(app, run_id, "command_repo_lock").await?.as_deref() {
+        Some("0") => None,
+        _ => repo_lock::acquire(app, repo_path, run_id, &plugin.name).await,
+    };
+
+    let input = json!({ "tool": plugin.name, "args": args, "repo_path": repo_path.to_string_lossy() }).to_string();
+    let (stdout, stderr, code) = executor.exec_with_input(&plugin.command, input.as_bytes()).await
+        .map_err(|e| format!("Plugin tool {} failed: {}", plugin.name, e))?;
+
+    // JSON output reaches the model as structured data when it fits. It's parsed
+    // as written: normalizing would collapse repeated lines of pretty-printed JSON
+    let (output_value, stdout_omitted) = match serde_json::from_str::<Value>(stdout.trim()) {
+        Ok(value) if stdout.len() <= limits.output_chars => (value, 0),
+        _ => {
+            let (text, omitted) = truncate_middle(&normalize_output(&stdout), limits.output_chars);
+            (Value::String(text), omitted)
+        }
+    };
+    let (stderr, stderr_omitted) = truncate_middle(&normalize_output(&stderr), limits.output_chars);
+
+    let result = json!({
+        "plugin": plugin.name,
+        "output": output_value,
+        "stderr": stderr,
+        "code": code,
+        "truncated": stdout_omitted > 0 || stderr_omitted > 0,
+        "omitted_bytes": { "stdout": stdout_omitted, "stderr": stderr_omitted },
+        "limits": { "max_chars": limits.output_chars },
+    });
+    log_tool_call(app, run_id, &plugin.name, args, &result)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let dir = Path::new("/plugins");
+        let manifest = parse_manifest(
+            r#"{"name": "terraform_plan", "description": "Plan a module",
+                "parameters": {"type": "object", "properties": {"module": {"type": "string"}}, "required": ["module"]},
+                "command": ["./bin/tf-plan", "--json"], "tier": "read_run"}"#,
+            dir,
+        ).unwrap();
+        assert_eq!(manifest.tier, ToolTier::ReadRun);
+        assert_eq!(Path::new(&manifest.command[0]), dir.join("./bin/tf-plan"));
+        assert_eq!(plugin_schema(&manifest)["function"]["name"], "terraform_plan");
+
+        assert!(check_args(&manifest, &json!({ "module": "infra/vpc" })).is_ok());
+        assert!(check_args(&manifest, &json!({})).unwrap_err().contains("'module'"));
+
+        // Programs on the PATH are left alone, and the schema and tier have defaults
+        let manifest = parse_manifest(r#"{"name": "sqlfluff", "description": "Lint SQL", "command": ["sqlfluff"]}"#, dir).unwrap();
+        assert_eq!(manifest.command[0], "sqlfluff");
+        assert_eq!(manifest.tier, ToolTier::ReadOnly);
+
+        let invalid = [
+            r#"{"name": "grep", "description": "x", "command": ["grep"]}"#,
+            r#"{"name": "Bad-Name", "description": "x", "command": ["x"]}"#,
+            r#"{"name": "x", "description": "x", "command": []}"#,
+            r#"{"name": "x", "description": "x", "command": ["x"], "tier": "read_write"}"#,
+            r#"{"name": "x", "description": "x", "command": ["x"], "parameters": {"type": "string"}}"#,
+            r#"{"name": "x", "description": "x", "command": ["x"], "shell": true}"#,
+        ];
+        for manifest in invalid {
+            assert!(parse_manifest(manifest, dir).is_err(), "{}", manifest);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::repo_tools::run_settings::run_setting;
+
+/// Memory and CPU caps for spawned commands, from the `command_memory_mb` and
+/// `command_cpus` settings
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub memory_mb: Option<u64>,
+    /// Containers only; the host has no portable way to cap cores
+    pub cpus: Option<f64>,
+}
+
+impl ResourceLimits {
+    /// Arguments for `docker run`/`podman run`
+    pub fn container_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(mb) = self.memory_mb {
+            args.extend(["--memory".to_string(), format!("{}m", mb)]);
+        }
+        if let Some(cpus) = self.cpus {
+            args.extend(["--cpus".to_string(), cpus.to_string()]);
+        }
+        args
+    }
+
+    /// `cmd_parts` with the memory limit applied on the host. The data segment
+    /// limit counts allocations but not the address space runtimes like V8
+    /// reserve up front, which a virtual memory limit would.
+    pub fn wrap_host(&self, cmd_parts: &[String]) -> Vec<String> {
+        match self.memory_mb {
+            Some(mb) if cfg!(unix) => {
+                let mut cmd: Vec<String> = vec![
+                    "sh".into(),
+                    "-c".into(),
+                    format!("ulimit -d {} && exec \"$@\"", mb * 1024),
+                    "sh".into(),
+                ];
+                cmd.extend(cmd_parts.iter().cloned());
+                cmd
+            }
+            _ => cmd_parts.to_vec(),
+        }
+    }
+}
+
+pub async fn resolve_limits(app: &AppHandle, run_id: &str) -> Result<ResourceLimits, String> {
+    let non_empty = |v: Option<String>| v.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    Ok(ResourceLimits {
+        memory_mb: non_empty(run_setting(app, run_id, "command_memory_mb").await?)
+            .and_then(|v| v.parse().ok())
+            .filter(|mb| *mb > 0),
+        cpus: non_empty(run_setting(app, run_id, "command_cpus").await?)
+            .and_then(|v| v.parse().ok())
+            .filter(|cpus: &f64| *cpus > 0.0),
+    })
+}
+
+/// A spawned command that hasn't finished yet
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RunningCommand {
+    pub pid: u32,
+    pub run_id: String,
+    pub command: String,
+    /// Backend and name of the container the command runs in
+    pub container: Option<(String, String)>,
+}
+
+/// Commands started by run_command, so they can all be stopped at once
+#[derive(Default)]
+pub struct RunningCommands {
+    commands: Mutex<HashMap<u32, RunningCommand>>,
+}
+
+/// Keeps a command registered while it runs
+pub struct Tracked {
+    app: AppHandle,
+    command: RunningCommand,
+}
+
+impl Tracked {
+    /// Kill the command and everything it started, e.g. on timeout
+    pub fn kill(&self) {
+        stop(&self.command);
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        if let Some(running) = self.app.try_state::<RunningCommands>() {
+            running.commands.lock().unwrap().remove(&self.command.pid);
+        }
+    }
+}
+
+pub fn track(app: &AppHandle, command: RunningCommand) -> Tracked {
+    if let Some(running) = app.try_state::<RunningCommands>() {
+        running.commands.lock().unwrap().insert(command.pid, command.clone());
+    }
+    Tracked { app: app.clone(), command }
+}
+
+/// Kill a command along with every process it started. Commands are spawned
+/// as process group leaders on unix, so the group id is the pid.
+fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    let _ = std::process::Command::new("kill").args(["-KILL", "--", &format!("-{}", pid)]).output();
+    #[cfg(windows)]
+    let _ = std::process::Command::new("taskkill").args(["/T", "/F", "/PID", &pid.to_string()]).output();
+}
+
+fn stop(command: &RunningCommand) {
+    kill_tree(command.pid);
+    // Killing the client leaves the container running
+    if let Some((backend, name)) = &command.container {
+        let _ = std::process::Command::new(backend).args(["rm", "-f", name]).output();
+    }
+}
+
+/// Stop every running command, returning what was stopped
+pub fn kill_all(app: &AppHandle) -> Vec<RunningCommand> {
+    kill_where(app, |_| true)
+}
+
+/// Stop the commands a run started, e.g. when it's cancelled
+pub fn kill_run(app: &AppHandle, run_id: &str) -> Vec<RunningCommand> {
+    kill_where(app, |command| command.run_id == run_id)
+}
+
+fn kill_where(app: &AppHandle, matches: impl Fn(&RunningCommand) -> bool) -> Vec<RunningCommand> {
+    let Some(running) = app.try_state::<RunningCommands>() else { return vec![] };
+    let commands: Vec<RunningCommand> = {
+        let mut running = running.commands.lock().unwrap();
+        let pids: Vec<u32> = running.values().filter(|c| matches(c)).map(|c| c.pid).collect();
+        pids.iter().filter_map(|pid| running.remove(pid)).collect()
+    };
+    for command in &commands {
+        tracing::warn!(pid = command.pid, run_id = %command.run_id, command = %command.command, "killing running command");
+        stop(command);
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_limits() {
+        let cmd = vec!["npm".to_string(), "test".to_string()];
+        assert_eq!(ResourceLimits::default().wrap_host(&cmd), cmd);
+        assert!(ResourceLimits::default().container_args().is_empty());
+
+        let limits = ResourceLimits { memory_mb: Some(2048), cpus: Some(1.5) };
+        assert_eq!(limits.container_args(), ["--memory", "2048m", "--cpus", "1.5"]);
+        if cfg!(unix) {
+            assert_eq!(limits.wrap_host(&cmd), ["sh", "-c", "ulimit -d 2097152 && exec \"$@\"", "sh", "npm", "test"]);
+        }
+    }
+}
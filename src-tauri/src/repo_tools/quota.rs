@@ -0,0 +1,135 @@
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use crate::settings::{AppSettings, SettingsMap};
+
+const WINDOW: Duration = Duration::from_secs(60);
+/// Usage of runs idle this long is dropped
+const IDLE_EXPIRY: Duration = Duration::from_secs(3600);
+
+/// Tool limits, from the `tool_max_*` settings. 0 disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolLimits {
+    pub calls_per_run: usize,
+    pub calls_per_minute: usize,
+    pub commands_per_run: usize,
+    pub bytes_per_run: usize,
+}
+
+impl ToolLimits {
+    pub fn from_settings(settings: &SettingsMap) -> Self {
+        let typed = AppSettings::from_map(settings);
+        ToolLimits {
+            calls_per_run: typed.tool_max_calls_per_run,
+            calls_per_minute: typed.tool_max_calls_per_minute,
+            commands_per_run: typed.tool_max_commands_per_run,
+            bytes_per_run: typed.tool_max_bytes_per_run,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RunUsage {
+    calls: usize,
+    commands: usize,
+    bytes: usize,
+    recent: VecDeque<Instant>,
+    last_used: Option<Instant>,
+}
+
+/// Per-run tool usage, kept in memory for the life of the run
+#[derive(Default)]
+pub struct ToolQuotas {
+    runs: Mutex<HashMap<String, RunUsage>>,
+}
+
+/// Result handed to the model instead of running the tool
+fn exceeded(limit: &str, used: usize, max: usize, message: &str) -> Value {
+    json!({
+        "error": "tool_budget_exceeded",
+        "limit": limit,
+        "used": used,
+        "max": max,
+        "message": message,
+    })
+}
+
+fn check(usage: &mut RunUsage, limits: &ToolLimits, tool: &str, now: Instant) -> Option<Value> {
+    while usage.recent.front().is_some_and(|t| now.duration_since(*t) >= WINDOW) {
+        usage.recent.pop_front();
+    }
+    if limits.calls_per_run > 0 && usage.calls >= limits.calls_per_run {
+        return Some(exceeded("calls_per_run", usage.calls, limits.calls_per_run,
+            "This run has used its tool call budget. Finish with the context you already have."));
+    }
+    if limits.bytes_per_run > 0 && usage.bytes >= limits.bytes_per_run {
+        return Some(exceeded("bytes_per_run", usage.bytes, limits.bytes_per_run,
+            "Tool results for this run have reached their size budget. Finish with the context you already have."));
+    }
+    if tool == "run_command" && limits.commands_per_run > 0 && usage.commands >= limits.commands_per_run {
+        return Some(exceeded("commands_per_run", usage.commands, limits.commands_per_run,
+            "This run may not start more commands. Use the command output you already have."));
+    }
+    if limits.calls_per_minute > 0 && usage.recent.len() >= limits.calls_per_minute {
+        return Some(exceeded("calls_per_minute", usage.recent.len(), limits.calls_per_minute,
+            "Too many tool calls in the last minute. Slow down and use the results you already have."));
+    }
+
+    usage.calls += 1;
+    if tool == "run_command" {
+        usage.commands += 1;
+    }
+    usage.recent.push_back(now);
+    usage.last_used = Some(now);
+    None
+}
+
+/// Count a tool call against its run's limits. Returns the structured
+/// "budget exceeded" result when the call must not run.
+pub fn acquire(app: &AppHandle, settings: &SettingsMap, run_id: &str, tool: &str) -> Option<Value> {
+    let quotas = app.try_state::<ToolQuotas>()?;
+    let mut runs = quotas.runs.lock().unwrap();
+    let now = Instant::now();
+    runs.retain(|_, usage| usage.last_used.is_none_or(|t| now.duration_since(t) < IDLE_EXPIRY));
+    let usage = runs.entry(run_id.to_string()).or_default();
+    check(usage, &ToolLimits::from_settings(settings), tool, now)
+}
+
+/// Add a finished call's result size to the run's byte budget
+pub fn record_bytes(app: &AppHandle, run_id: &str, bytes: usize) {
+    if let Some(quotas) = app.try_state::<ToolQuotas>() {
+        if let Some(usage) = quotas.runs.lock().unwrap().get_mut(run_id) {
+            usage.bytes += bytes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_limits() {
+        let limits = ToolLimits { calls_per_run: 4, calls_per_minute: 3, commands_per_run: 1, bytes_per_run: 100 };
+        let mut usage = RunUsage::default();
+        let start = Instant::now();
+
+        assert!(check(&mut usage, &limits, "run_command", start).is_none());
+        let denied = check(&mut usage, &limits, "run_command", start).unwrap();
+        assert_eq!(denied["limit"], "commands_per_run");
+
+        assert!(check(&mut usage, &limits, "read_file", start).is_none());
+        assert!(check(&mut usage, &limits, "grep", start).is_none());
+        assert_eq!(check(&mut usage, &limits, "grep", start).unwrap()["limit"], "calls_per_minute");
+
+        // The minute window slides; the per-run cap doesn't
+        let later = start + WINDOW;
+        assert!(check(&mut usage, &limits, "grep", later).is_none());
+        assert_eq!(check(&mut usage, &limits, "grep", later).unwrap()["limit"], "calls_per_run");
+
+        let mut usage = RunUsage { bytes: 100, ..Default::default() };
+        assert_eq!(check(&mut usage, &limits, "read_file", start).unwrap()["limit"], "bytes_per_run");
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+const SCHEME: &str = "ssh://";
+
+/// Resolves the file inside the repository on the remote host itself, so
+/// symlinks are followed where they actually point
+const READ_SCRIPT: &str = r#"root=$(pwd -P) && path=$(realpath -- "$1") || exit 2
+case "$path" in "$root"/*) exec cat -- "$path" ;; esac
+echo "Path traversal attempt blocked" >&2
+exit 3"#;
+
+/// A repository on a dev server, configured as the project's repo path in the
+/// form `ssh://[user@]host[:port]/absolute/path`. Hosts from ~/.ssh/config work
+/// as is, keys and jump hosts included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteRepo {
+    /// `user@host` or a host alias
+    pub destination: String,
+    pub port: Option<u16>,
+    pub path: String,
+}
+
+pub fn parse_remote(repo_path: &str) -> Option<RemoteRepo> {
+    let rest = repo_path.strip_prefix(SCHEME)?;
+    let (authority, path) = rest.split_once('/')?;
+    let (destination, port) = match authority.rsplit_once(':') {
+        Some((destination, port)) => (destination, Some(port.parse().ok()?)),
+        None => (authority, None),
+    };
+    // Never let a host pass as an ssh option
+    if destination.is_empty() || destination.starts_with('-') || destination.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(RemoteRepo {
+        destination: destination.to_string(),
+        port,
+        path: format!("/{}", path.trim_end_matches('/')),
+    })
+}
+
+/// The remote repository `cwd` refers to, if it's an ssh:// path
+pub fn remote_repo(cwd: &Path) -> Option<RemoteRepo> {
+    parse_remote(&cwd.to_string_lossy())
+}
+
+/// Quote for the remote POSIX shell, which ssh hands the command line to
+fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+impl RemoteRepo {
+    /// ssh argv running `script` in the remote login shell. BatchMode fails
+    /// instead of prompting for a password nobody can type.
+    fn ssh(&self, script: &str) -> Vec<String> {
+        let mut parts: Vec<String> = vec![
+            "ssh".into(),
+            "-o".into(),
+            "BatchMode=yes".into(),
+            "-o".into(),
+            "ConnectTimeout=15".into(),
+        ];
+        if let Some(port) = self.port {
+            parts.extend(["-p".to_string(), port.to_string()]);
+        }
+        parts.extend(["--".to_string(), self.destination.clone(), script.to_string()]);
+        parts
+    }
+
+    /// ssh argv running `cmd args` in the repository's directory on the host.
+    /// Every argument is quoted, so none is parsed by the remote shell.
+    pub fn wrap(&self, cmd: &str, args: &[&str]) -> Vec<String> {
+        let mut script = format!("cd {} && exec", quote(&self.path));
+        for arg in std::iter::once(cmd).chain(args.iter().copied()) {
+            script.push(' ');
+            script.push_str(&quote(arg));
+        }
+        self.ssh(&script)
+    }
+
+    async fn output(&self, parts: Vec<String>, timeout_secs: u64) -> Result<(Vec<u8>, String, i32), String> {
+        let output = timeout(
+            Duration::from_secs(timeout_secs),
+            Command::new(&parts[0])
+                .args(&parts[1..])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .output()
+        ).await
+            .map_err(|_| format!("Timeout after {}s talking to {}", timeout_secs, self.destination))?
+            .map_err(|e| format!("Cannot run ssh: {}", e))?;
+        Ok((output.stdout, String::from_utf8_lossy(&output.stderr).to_string(), output.status.code().unwrap_or(-1)))
+    }
+
+    /// Contents of a repo-relative file, which must resolve inside the
+    /// repository on the remote side
+    pub async fn read(&self, rel_path: &str) -> Result<Vec<u8>, String> {
+        let script = format!("cd {} && sh -c {} sh {}", quote(&self.path), quote(READ_SCRIPT), quote(rel_path));
+        let (content, stderr, code) = self.output(self.ssh(&script), 30).await?;
+        match code {
+            0 => Ok(content),
+            3 => Err("Path traversal attempt blocked".to_string()),
+            // ssh itself exits 255 when it can't connect
+            255 => Err(format!("Cannot reach {}: {}", self.destination, stderr.trim())),
+            _ => Err(format!("Cannot read file: {}", stderr.trim())),
+        }
+    }
+
+    /// Whether `program` is on the host's PATH
+    pub async fn has_program(&self, program: &str) -> Result<bool, String> {
+        let parts = self.ssh(&format!("command -v {} >/dev/null", quote(program)));
+        let (_, stderr, code) = self.output(parts, 15).await?;
+        match code {
+            0 => Ok(true),
+            255 => Err(format!("Cannot reach {}: {}", self.destination, stderr.trim())),
+            _ => Ok(false),
+        }
+    }
+
+    /// Names in the repository's top-level directory
+    pub async fn top_level_entries(&self) -> Result<Vec<String>, String> {
+        let (stdout, stderr, code) = self.output(self.wrap("ls", &["-A"]), 30).await?;
+        if code != 0 {
+            return Err(format!("Cannot list {} on {}: {}", self.path, self.destination, stderr.trim()));
+        }
+        Ok(String::from_utf8_lossy(&stdout).lines().map(str::to_string).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_repos() {
+        let repo = parse_remote("ssh://dev@devbox:2222/home/dev/app/").unwrap();
+        assert_eq!(repo, RemoteRepo { destination: "dev@devbox".into(), port: Some(2222), path: "/home/dev/app".into() });
+        assert_eq!(parse_remote("ssh://devbox/srv/app").unwrap().port, None);
+        assert_eq!(parse_remote("ssh://devbox:http/srv/app"), None);
+        assert_eq!(parse_remote("ssh://-oProxyCommand=sh/srv/app"), None);
+        assert_eq!(parse_remote("/home/dev/app"), None);
+
+        assert_eq!(
+            repo.wrap("git", &["log", "--pretty=format:%h %s", "it's"]),
+            ["ssh", "-o", "BatchMode=yes", "-o", "ConnectTimeout=15", "-p", "2222", "--", "dev@devbox",
+             r#"cd '/home/dev/app' && exec 'git' 'log' '--pretty=format:%h %s' 'it'\''s'"#]
+        );
+    }
+}
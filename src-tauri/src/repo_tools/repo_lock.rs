@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::events::{CommandQueueEvent, COMMAND_QUEUE};
+use crate::windows::emit_for_run;
+
+#[derive(Default)]
+struct RepoLock {
+    lock: Arc<AsyncMutex<()>>,
+    holder: Option<String>,
+}
+
+/// One command at a time per repository, so parallel runs don't fight over
+/// build directories like target/ or node_modules/.cache
+#[derive(Default)]
+pub struct RepoLocks {
+    repos: Mutex<HashMap<PathBuf, RepoLock>>,
+}
+
+impl RepoLocks {
+    /// The repository's lock and the run currently holding it
+    fn entry(&self, repo: &Path) -> (Arc<AsyncMutex<()>>, Option<String>) {
+        let mut repos = self.repos.lock().unwrap();
+        let entry = repos.entry(repo.to_path_buf()).or_default();
+        (entry.lock.clone(), entry.holder.clone())
+    }
+
+    fn set_holder(&self, repo: &Path, holder: Option<&str>) {
+        if let Some(entry) = self.repos.lock().unwrap().get_mut(repo) {
+            entry.holder = holder.map(str::to_string);
+        }
+    }
+}
+
+/// Held while a command runs in the repository
+pub struct RepoGuard {
+    app: AppHandle,
+    repo: PathBuf,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl Drop for RepoGuard {
+    fn drop(&mut self) {
+        if let Some(locks) = self.app.try_state::<RepoLocks>() {
+            locks.set_holder(&self.repo, None);
+        }
+    }
+}
+
+/// Wait for the repository to be free of other runs' commands, then hold it
+pub async fn acquire(app: &AppHandle, repo_path: &Path, run_id: &str, kind: &str) -> Option<RepoGuard> {
+    let repo = repo_path.canonicalize().unwrap_or_else(|_| repo_path.to_path_buf());
+    let (lock, holder) = app.try_state::<RepoLocks>()?.entry(&repo);
+    let guard = match lock.clone().try_lock_owned() {
+        Ok(guard) => guard,
+        Err(_) => {
+            let event = |status, holder_run_id| CommandQueueEvent {
+                run_id: run_id.to_string(),
+                repo_path: repo.to_string_lossy().to_string(),
+                kind: kind.to_string(),
+                status,
+                holder_run_id,
+            };
+            tracing::info!(holder = ?holder, "command queued behind another run in the same repository");
+            emit_for_run(app, COMMAND_QUEUE, run_id, event("waiting", holder));
+            let guard = lock.lock_owned().await;
+            emit_for_run(app, COMMAND_QUEUE, run_id, event("started", None));
+            guard
+        }
+    };
+    app.try_state::<RepoLocks>()?.set_holder(&repo, Some(run_id));
+    Some(RepoGuard { app: app.clone(), repo, _guard: guard })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_command_per_repo() {
+        let locks = RepoLocks::default();
+        let (lock, holder) = locks.entry(Path::new("/repos/a"));
+        assert!(holder.is_none());
+        let _running = lock.try_lock_owned().unwrap();
+        locks.set_holder(Path::new("/repos/a"), Some("run-1"));
+
+        let (same, holder) = locks.entry(Path::new("/repos/a"));
+        assert_eq!(holder.as_deref(), Some("run-1"));
+        assert!(same.try_lock_owned().is_err());
+
+        let (other, _) = locks.entry(Path::new("/repos/b"));
+        assert!(other.try_lock_owned().is_ok());
+    }
+}
@@ -0,0 +1,25 @@
+use rusqlite::OptionalExtension;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::settings::cached_settings;
+
+/// Project of the task a run belongs to; None for runs outside a task
+pub fn run_project_id(app: &AppHandle, run_id: &str) -> Result<Option<String>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT t.project_id FROM runs r JOIN tasks t ON t.id = r.task_id WHERE r.id = ?1",
+        [run_id],
+        |r| r.get(0)
+    ).optional().map_err(|e| e.to_string())
+}
+
+/// Setting for the run's project, falling back to the global one
+pub async fn run_setting(app: &AppHandle, run_id: &str, key: &str) -> Result<Option<String>, String> {
+    if let Some(project_id) = run_project_id(app, run_id)? {
+        if let Some(value) = db::get_project_setting(app, &project_id, key).map_err(|e| e.to_string())? {
+            return Ok(Some(value));
+        }
+    }
+    Ok(cached_settings(app).await?.get(key).cloned())
+}
@@ -1,21 +1,42 @@
 use serde_json::{json, Value};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
-use crate::repo_tools::safety::truncate_string;
+use crate::repo_tools::safety::{command_in, truncate_middle};
+use crate::repo_tools::limits::OutputLimits;
 use crate::repo_tools::logging::log_tool_call;
+use crate::repo_tools::normalize::normalize_output;
+use crate::repo_tools::outcome::{classify, package_script, CommandOutcome};
+use crate::repo_tools::script_fallback::{configured_fallback, discover_scripts, script_command, suggest_scripts};
+use crate::repo_tools::test_results::{aggregate, parse_test_outcomes, TestStat};
+use crate::repo_tools::custom_commands::custom_command;
+use crate::repo_tools::processes::{resolve_limits, track, ResourceLimits, RunningCommand};
+use crate::repo_tools::repo_lock;
+use crate::repo_tools::run_settings::run_setting;
+use crate::repo_tools::sandbox::{resolve_sandbox, Sandbox};
+use crate::repo_tools::toolchain::{detect_toolchain, Toolchain};
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::wsl::wsl_repo;
+use crate::repo_tools::bench::{compare_with_previous, hyperfine_commands, parse_bench_output, parse_hyperfine_json};
 use tauri::AppHandle;
-use tokio::process::Command;
 use std::process::Stdio;
 use std::time::Duration;
+use tokio::io::AsyncWriteExt;
 use tokio::time::timeout;
 
-const MAX_OUTPUT_CHARS: usize = 200_000;
+const COMMAND_TIMEOUT_SECS: u64 = 300;
+/// Benchmarks run many iterations, so they get longer than other commands
+const BENCH_TIMEOUT_SECS: u64 = 1800;
+const MAX_TEST_REPEAT: u64 = 5;
+/// Reruns of failed tests when `rerun_failures` is set without `repeat`
+const DEFAULT_FAILURE_RERUNS: u64 = 2;
 
 #[derive(Debug, Clone, Copy)]
 enum CommandKind {
     Tests,
     Lint,
     Build,
+    Typecheck,
+    Bench,
 }
 
 impl CommandKind {
@@ -24,6 +45,8 @@ impl CommandKind {
             "tests" => Some(CommandKind::Tests),
             "lint" => Some(CommandKind::Lint),
             "build" => Some(CommandKind::Build),
+            "typecheck" => Some(CommandKind::Typecheck),
+            "bench" => Some(CommandKind::Bench),
             _ => None,
         }
     }
@@ -34,79 +57,428 @@ pub async fn run_command(
     args: &Value,
     app: &AppHandle,
     run_id: &str,
+    output_limits: &OutputLimits,
 ) -> Result<Value, String> {
     let kind_str = args.get("kind")
         .and_then(|v| v.as_str())
-        .ok_or("kind is required (tests, lint, or build)")?;
+        .ok_or("kind is required (tests, lint, build, typecheck, or bench)")?;
     
     let kind = CommandKind::from_str(kind_str)
-        .ok_or("invalid kind, must be: tests, lint, or build")?;
+        .ok_or("invalid kind, must be: tests, lint, build, typecheck, or bench")?;
     
-    // Auto-detect runner
-    let runner = detect_runner(repo_path, args.get("runner").and_then(|v| v.as_str()))?;
+    let repeat = args.get("repeat").and_then(|v| v.as_u64()).unwrap_or(1);
+    let rerun_failures = args.get("rerun_failures").and_then(|v| v.as_bool()).unwrap_or(false);
+    if (repeat > 1 || rerun_failures) && !matches!(kind, CommandKind::Tests) {
+        return Err("repeat and rerun_failures only apply to tests".to_string());
+    }
+    if !(1..=MAX_TEST_REPEAT).contains(&repeat) {
+        return Err(format!("repeat must be between 1 and {}", MAX_TEST_REPEAT));
+    }
+    
+    // A project's own argv for this kind and OS replaces the built-in table
+    let custom = custom_command(app, run_id, kind_str).await?;
+    
+    let remote = remote_repo(repo_path);
+    let runner = resolve_runner(repo_path, args, custom.is_some()).await?;
+    
+    // Project scripts run in a container when the project configures one
+    let sandbox = resolve_sandbox(app, run_id).await?;
+    
+    // Benchmarks go through hyperfine when the project configures commands for it
+    let hyperfine = match kind {
+        CommandKind::Bench if custom.is_none() => hyperfine_commands(app, run_id).await,
+        _ => vec![],
+    };
+    if remote.is_some() && !hyperfine.is_empty() {
+        return Err("hyperfine benchmarks need a local repository; use the project's bench script instead".to_string());
+    }
+    // hyperfine writes its results to a file, in a directory containers can mount
+    let export_dir = std::env::temp_dir().join(format!("spectrail-bench-{}", crate::models::new_id()));
+    let export_path = export_dir.join("results.json");
+    let writable = if hyperfine.is_empty() {
+        vec![]
+    } else {
+        std::fs::create_dir_all(&export_dir).map_err(|e| e.to_string())?;
+        vec![export_dir.clone()]
+    };
     
     // Build allowlisted command
-    let cmd_parts = build_command(&runner, kind)?;
+    let mut cmd_parts = if let Some(custom) = &custom {
+        custom.clone()
+    } else if !hyperfine.is_empty() {
+        let mut cmd = vec!["hyperfine".to_string(), "--style".to_string(), "basic".to_string(), "--export-json".to_string()];
+        cmd.push(export_path.to_string_lossy().to_string());
+        cmd.push("--".to_string());
+        cmd.extend(hyperfine.iter().cloned());
+        cmd
+    } else {
+        build_command(runner.as_deref().unwrap_or_default(), kind)?
+    };
+    let timeout_secs = match kind {
+        CommandKind::Bench => BENCH_TIMEOUT_SECS,
+        _ => COMMAND_TIMEOUT_SECS,
+    };
     
-    let start = Instant::now();
+    let toolchain = resolve_toolchain(app, run_id, repo_path, sandbox.is_some()).await?;
+    let executor = Executor {
+        app,
+        run_id,
+        repo_path,
+        sandbox: sandbox.as_ref(),
+        toolchain,
+        limits: resolve_limits(app, run_id).await?,
+        timeout_secs,
+        readonly: vec![],
+        env: vec![],
+    };
     
-    // Spawn directly since safe_spawn expects &[&str]
-    let output = timeout(
-        Duration::from_secs(300),
-        Command::new(&cmd_parts[0])
-            .args(&cmd_parts[1..])
-            .current_dir(repo_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    ).await
-        .map_err(|_| "Timeout".to_string())?
-        .map_err(|e| format!("Command failed: {}", e))?;
+    // Queue behind other runs' commands in this repo unless the project opts out
+    let queued = Instant::now();
+    let _repo_guard = match run_setting(app, run_id, "command_repo_lock").await?.as_deref() {
+        Some("0") => None,
+        _ => repo_lock::acquire(app, repo_path, run_id, kind_str).await,
+    };
+    let waited_ms = queued.elapsed().as_millis() as u64;
     
-    let duration_ms = start.elapsed().as_millis() as u64;
+    let start = Instant::now();
+    let (mut stdout, mut stderr, mut code) = executor.exec(&cmd_parts, &writable).await?;
+    let mut outcome = classify(package_script(&cmd_parts), code, &stdout, &stderr);
     
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let code = output.status.code().unwrap_or(-1);
+    // A missing standard script: run the project's configured stand-in, or suggest some
+    let mut fallback_script = None;
+    let mut suggested_scripts = vec![];
+    let fallback_applies = custom.is_none() && match outcome {
+        CommandOutcome::ScriptUndefined => true,
+        // A cargo subcommand that isn't installed may have an alias standing in for it
+        CommandOutcome::ToolMissing => runner.as_deref() == Some("cargo"),
+        _ => false,
+    };
+    if let (true, Some(runner)) = (fallback_applies, runner.as_deref()) {
+        let missing = package_script(&cmd_parts)
+            .or_else(|| cmd_parts.get(1).map(String::as_str))
+            .unwrap_or_default()
+            .to_string();
+        let available = discover_scripts(repo_path, runner).await;
+        let configured = configured_fallback(app, run_id, kind_str).await?.filter(|s| available.contains(s));
+        match configured.as_deref().and_then(|script| script_command(runner, script)) {
+            Some(fallback) => {
+                (stdout, stderr, code) = executor.exec(&fallback, &writable).await?;
+                outcome = classify(package_script(&fallback), code, &stdout, &stderr);
+                cmd_parts = fallback;
+                fallback_script = configured;
+            }
+            None => suggested_scripts = suggest_scripts(&missing, kind_str, &available),
+        }
+    }
+    let duration_ms = start.elapsed().as_millis() as u64;
     
-    let (stdout_trunc, out_trunc) = truncate_string(&stdout, MAX_OUTPUT_CHARS);
-    let (stderr_trunc, err_trunc) = truncate_string(&stderr, MAX_OUTPUT_CHARS);
+    // Failures are usually at the end, so long output loses its middle
+    let (stdout_trunc, stdout_omitted) = truncate_middle(&stdout, output_limits.output_chars);
+    let (stderr_trunc, stderr_omitted) = truncate_middle(&stderr, output_limits.output_chars);
     
-    let result = json!({
+    let mut result = json!({
         "stdout": stdout_trunc,
         "stderr": stderr_trunc,
         "code": code,
+        "outcome": outcome,
         "duration_ms": duration_ms,
-        "truncated": out_trunc || err_trunc,
+        "waited_ms": waited_ms,
+        "truncated": stdout_omitted > 0 || stderr_omitted > 0,
+        "omitted_bytes": { "stdout": stdout_omitted, "stderr": stderr_omitted },
+        "limits": { "max_chars": output_limits.output_chars },
+        "backend": sandbox.as_ref().map_or("host", |s| s.backend.as_str()),
+        "toolchain": toolchain.map(|t| t.as_str()),
+        "custom_command": custom.is_some(),
     });
+    if let Some(sandbox) = &sandbox {
+        result["image"] = json!(sandbox.image);
+    }
+    if let Some(script) = &fallback_script {
+        result["fallback_script"] = json!(script);
+    }
+    if !suggested_scripts.is_empty() {
+        result["suggested_scripts"] = json!(suggested_scripts);
+        result["hint"] = json!(format!(
+            "The standard {} command isn't available in this project. It may use one of the \
+             suggested scripts instead; the user can approve one as the project's {} fallback.",
+            kind_str, kind_str
+        ));
+    }
+    
+    if repeat > 1 || rerun_failures {
+        let first = parse_test_outcomes(&format!("{}\n{}", stdout, stderr));
+        let failed: Vec<String> = first.iter().filter(|o| !o.passed).map(|o| o.name.clone()).collect();
+        let mut runs = vec![first];
+        // Only the first run's failures are rerun; runners without a filter rerun the suite
+        let (attempts, rerun_cmd) = if rerun_failures {
+            let attempts = if repeat > 1 { repeat - 1 } else { DEFAULT_FAILURE_RERUNS };
+            let attempts = if failed.is_empty() { 0 } else { attempts };
+            let filtered = runner.as_deref().and_then(|runner| rerun_command(runner, &failed));
+            (attempts, filtered.unwrap_or_else(|| cmd_parts.clone()))
+        } else {
+            (repeat - 1, cmd_parts.clone())
+        };
+        for _ in 0..attempts {
+            let (stdout, stderr, _) = executor.exec(&rerun_cmd, &[]).await?;
+            runs.push(parse_test_outcomes(&format!("{}\n{}", stdout, stderr)));
+        }
+        
+        let stats: Vec<TestStat> = aggregate(&runs).into_iter().filter(|s| s.failures > 0).collect();
+        let flaky: Vec<&str> = stats.iter().filter(|s| s.is_flaky()).map(|s| s.name.as_str()).collect();
+        result["attempts"] = json!(runs.len());
+        result["flaky"] = json!(flaky);
+        result["test_stats"] = json!(stats);
+    }
+    
+    if matches!(kind, CommandKind::Bench) {
+        let results = if hyperfine.is_empty() {
+            Ok(parse_bench_output(&format!("{}\n{}", stdout, stderr)))
+        } else {
+            let export = std::fs::read_to_string(&export_path).map_err(|e| format!("Failed to read hyperfine export: {}", e));
+            let _ = std::fs::remove_dir_all(&export_dir);
+            export.and_then(|json| parse_hyperfine_json(&json))
+        };
+        match results {
+            Ok(results) => {
+                let comparisons = compare_with_previous(app, run_id, &results).await?;
+                result["regressions"] = json!(comparisons.iter().filter(|c| c.regression).count());
+                result["benchmarks"] = json!(comparisons);
+            }
+            // A failed run leaves no export; the output says why
+            Err(e) if code != 0 => result["bench_error"] = json!(e),
+            Err(e) => return Err(e),
+        }
+    }
     
     log_tool_call(app, run_id, "run_command", args, &result)?;
     Ok(result)
 }
 
-fn detect_runner(repo_path: &Path, explicit: Option<&str>) -> Result<String, String> {
+/// The runner for the repository, auto-detected unless `args` names one. None
+/// when a custom command replaces the built-in table.
+async fn resolve_runner(repo_path: &Path, args: &Value, custom: bool) -> Result<Option<String>, String> {
+    if custom {
+        return Ok(None);
+    }
+    let explicit = args.get("runner").and_then(|v| v.as_str());
+    match remote_repo(repo_path) {
+        Some(remote) if explicit.is_none() => {
+            let entries = remote.top_level_entries().await?;
+            detect_runner(|file| entries.iter().any(|e| e == file), None).map(Some)
+        }
+        _ => detect_runner(|file| repo_path.join(file).exists(), explicit).map(Some),
+    }
+}
+
+/// Pinned toolchains apply on the host; a container image brings its own
+async fn resolve_toolchain(app: &AppHandle, run_id: &str, repo_path: &Path, sandboxed: bool) -> Result<Option<Toolchain>, String> {
+    Ok(match run_setting(app, run_id, "toolchain_detection").await?.as_deref() {
+        Some("0") => None,
+        _ if sandboxed => None,
+        _ => detect_toolchain(repo_path),
+    })
+}
+
+/// What a run_command call would execute, without running it: the command,
+/// where it runs, the toolchain wrapping it and the script standing in if it's
+/// missing. A passing result is only reused while all of these are unchanged.
+pub async fn command_identity(repo_path: &Path, args: &Value, app: &AppHandle, run_id: &str) -> Result<Value, String> {
+    let kind_str = args.get("kind").and_then(|v| v.as_str()).unwrap_or_default();
+    let kind = CommandKind::from_str(kind_str)
+        .ok_or("invalid kind, must be: tests, lint, build, typecheck, or bench")?;
+    let custom = custom_command(app, run_id, kind_str).await?;
+    let runner = resolve_runner(repo_path, args, custom.is_some()).await?;
+    let sandbox = resolve_sandbox(app, run_id).await?;
+    let hyperfine = match kind {
+        CommandKind::Bench if custom.is_none() => hyperfine_commands(app, run_id).await,
+        _ => vec![],
+    };
+    let argv = match (&custom, &runner) {
+        (Some(custom), _) => custom.clone(),
+        (None, _) if !hyperfine.is_empty() => hyperfine,
+        (None, runner) => build_command(runner.as_deref().unwrap_or_default(), kind)?,
+    };
+    let toolchain = resolve_toolchain(app, run_id, repo_path, sandbox.is_some()).await?;
+    let fallback = match custom {
+        Some(_) => None,
+        None => configured_fallback(app, run_id, kind_str).await?,
+    };
+    Ok(json!({
+        "argv": argv,
+        "backend": sandbox.as_ref().map_or("host", |s| s.backend.as_str()),
+        "image": sandbox.as_ref().map(|s| s.image.as_str()),
+        "mount": sandbox.as_ref().map(|s| format!("{:?}", s.mount)),
+        "toolchain": toolchain.map(|t| t.as_str()),
+        "fallback_script": fallback,
+    }))
+}
+
+/// Where and under what limits the processes of run_command and plugin tools run
+pub(crate) struct Executor<'a> {
+    pub(crate) app: &'a AppHandle,
+    pub(crate) run_id: &'a str,
+    pub(crate) repo_path: &'a Path,
+    pub(crate) sandbox: Option<&'a Sandbox>,
+    pub(crate) toolchain: Option<Toolchain>,
+    pub(crate) limits: ResourceLimits,
+    pub(crate) timeout_secs: u64,
+    /// Host paths a container may read besides the repository
+    pub(crate) readonly: Vec<PathBuf>,
+    /// Variables set for the command itself, wherever it runs
+    pub(crate) env: Vec<(String, String)>,
+}
+
+impl Executor<'_> {
+    /// Run a command on the host, or in the project's container when one is configured.
+    /// Its output comes back normalized, before anything truncates, parses or logs it.
+    async fn exec(&self, cmd_parts: &[String], writable: &[PathBuf]) -> Result<(String, String, i32), String> {
+        let (stdout, stderr, code) = self.exec_raw(cmd_parts, writable, None).await?;
+        Ok((normalize_output(&stdout), normalize_output(&stderr), code))
+    }
+
+    /// Like `exec`, with `input` written to the command's stdin. The output is
+    /// left as the command wrote it, for callers that parse it before normalizing.
+    pub(crate) async fn exec_with_input(&self, cmd_parts: &[String], input: &[u8]) -> Result<(String, String, i32), String> {
+        self.exec_raw(cmd_parts, &[], Some(input)).await
+    }
+
+    async fn exec_raw(&self, cmd_parts: &[String], writable: &[PathBuf], input: Option<&[u8]>) -> Result<(String, String, i32), String> {
+        match self.sandbox {
+            Some(sandbox) => {
+                let name = format!("spectrail-{}", crate::models::new_id());
+                // In a WSL distro or on a remote host the container engine runs there too,
+                // and mounts the repository's path as that side sees it
+                let repo = remote_repo(self.repo_path).map(|remote| PathBuf::from(remote.path))
+                    .or_else(|| wsl_repo(self.repo_path).map(|wsl| PathBuf::from(wsl.linux_path)));
+                let repo = repo.as_deref().unwrap_or(self.repo_path);
+                let mut wrapped = sandbox.wrap(&name, &self.with_env(cmd_parts), repo, writable, &self.readonly, &self.limits);
+                if input.is_some() {
+                    // Containers only get stdin attached when asked
+                    wrapped.insert(2, "-i".to_string());
+                }
+                self.spawn(&wrapped, Some((sandbox.backend.as_str().to_string(), name)), input).await
+            }
+            None => {
+                let cmd_parts = match self.toolchain {
+                    Some(toolchain) => toolchain.wrap(cmd_parts),
+                    None => cmd_parts.to_vec(),
+                };
+                let cmd_parts = match (remote_repo(self.repo_path), wsl_repo(self.repo_path)) {
+                    (None, None) => cmd_parts,
+                    _ => self.with_env(&cmd_parts),
+                };
+                self.spawn(&self.limits.wrap_host(&cmd_parts), None, input).await
+            }
+        }
+    }
+
+    /// `cmd_parts` run through `env`, for commands started somewhere our own
+    /// environment doesn't reach: a container, WSL or a remote host
+    fn with_env(&self, cmd_parts: &[String]) -> Vec<String> {
+        if self.env.is_empty() {
+            return cmd_parts.to_vec();
+        }
+        let mut cmd = vec!["env".to_string()];
+        cmd.extend(self.env.iter().map(|(key, value)| format!("{}={}", key, value)));
+        cmd.extend(cmd_parts.iter().cloned());
+        cmd
+    }
+
+    /// Run an allowlisted command to completion, returning stdout, stderr and the exit code.
+    /// On timeout the command is killed along with every process it started.
+    async fn spawn(
+        &self,
+        cmd_parts: &[String],
+        container: Option<(String, String)>,
+        input: Option<&[u8]>,
+    ) -> Result<(String, String, i32), String> {
+        // Spawn directly since safe_spawn expects &[&str]
+        let args: Vec<&str> = cmd_parts[1..].iter().map(String::as_str).collect();
+        let mut command = command_in(&cmd_parts[0], &args, self.repo_path);
+        command
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if input.is_some() {
+            command.stdin(Stdio::piped());
+        }
+        // Lead a new process group so the whole tree can be killed together
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            // Reported the way a shell would, so the result can say the tool is missing
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok((String::new(), format!("{}: command not found", cmd_parts[0]), 127));
+            }
+            Err(e) => return Err(format!("Command failed: {}", e)),
+        };
+        let tracked = child.id().map(|pid| track(self.app, RunningCommand {
+            pid,
+            run_id: self.run_id.to_string(),
+            command: cmd_parts.join(" "),
+            container,
+        }));
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            // A command that doesn't read its input may already have closed stdin
+            let _ = stdin.write_all(input).await;
+        }
+        
+        let output = match timeout(Duration::from_secs(self.timeout_secs), child.wait_with_output()).await {
+            Ok(output) => output.map_err(|e| format!("Command failed: {}", e))?,
+            Err(_) => {
+                if let Some(tracked) = &tracked {
+                    tracked.kill();
+                }
+                return Err(format!("Timeout after {}s; the command and its child processes were killed", self.timeout_secs));
+            }
+        };
+        
+        Ok((
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+            output.status.code().unwrap_or(-1),
+        ))
+    }
+}
+
+/// Command running just the named tests, for runners that can filter by exact name
+fn rerun_command(runner: &str, tests: &[String]) -> Option<Vec<String>> {
+    let mut cmd: Vec<String> = match runner {
+        "cargo" => vec!["cargo", "test", "--", "--exact"],
+        "python" | "pytest" => vec!["pytest"],
+        _ => return None,
+    }.into_iter().map(str::to_string).collect();
+    // Names come from the project's own output; never let one pass as a flag
+    cmd.extend(tests.iter().filter(|t| !t.starts_with('-')).cloned());
+    Some(cmd)
+}
+
+/// Runner for the repository, given whether each top-level file exists
+fn detect_runner(has: impl Fn(&str) -> bool, explicit: Option<&str>) -> Result<String, String> {
     if let Some(runner) = explicit {
         return Ok(runner.to_string());
     }
     
     // Check for JS package managers
-    if repo_path.join("pnpm-lock.yaml").exists() {
+    if has("pnpm-lock.yaml") {
         return Ok("pnpm".to_string());
     }
-    if repo_path.join("yarn.lock").exists() {
+    if has("yarn.lock") {
         return Ok("yarn".to_string());
     }
-    if repo_path.join("package-lock.json").exists() {
+    if has("package-lock.json") {
         return Ok("npm".to_string());
     }
     
     // Check for Rust
-    if repo_path.join("Cargo.toml").exists() {
+    if has("Cargo.toml") {
         return Ok("cargo".to_string());
     }
     
     // Check for Python
-    if repo_path.join("pyproject.toml").exists() || repo_path.join("requirements.txt").exists() {
+    if has("pyproject.toml") || has("requirements.txt") {
         return Ok("python".to_string());
     }
     
@@ -125,16 +497,26 @@ fn build_command(runner: &str, kind: CommandKind) -> Result<Vec<String>, String>
         ("yarn", CommandKind::Tests) => vec!["yarn", "test"],
         ("yarn", CommandKind::Lint) => vec!["yarn", "lint"],
         ("yarn", CommandKind::Build) => vec!["yarn", "build"],
+        ("pnpm", CommandKind::Typecheck) => vec!["pnpm", "exec", "tsc", "--noEmit", "--pretty", "false"],
+        ("npm", CommandKind::Typecheck) => vec!["npx", "--no-install", "tsc", "--noEmit", "--pretty", "false"],
+        ("yarn", CommandKind::Typecheck) => vec!["yarn", "tsc", "--noEmit", "--pretty", "false"],
+        ("pnpm", CommandKind::Bench) => vec!["pnpm", "bench"],
+        ("npm", CommandKind::Bench) => vec!["npm", "run", "bench"],
+        ("yarn", CommandKind::Bench) => vec!["yarn", "bench"],
         
         // Rust
         ("cargo", CommandKind::Tests) => vec!["cargo", "test"],
         ("cargo", CommandKind::Lint) => vec!["cargo", "clippy", "--", "-D", "warnings"],
         ("cargo", CommandKind::Build) => vec!["cargo", "build"],
+        ("cargo", CommandKind::Typecheck) => vec!["cargo", "check", "--message-format=json"],
+        ("cargo", CommandKind::Bench) => vec!["cargo", "bench"],
         
         // Python
         ("python" | "pytest", CommandKind::Tests) => vec!["pytest"],
         ("python", CommandKind::Lint) => vec!["ruff", "check", "."],
         ("python", CommandKind::Build) => return Err("Python doesn't have a build step".to_string()),
+        ("python", CommandKind::Typecheck) => vec!["mypy", ".", "--show-column-numbers", "--no-error-summary"],
+        ("python", CommandKind::Bench) => return Err("Set bench_commands to benchmark Python projects with hyperfine".to_string()),
         
         _ => return Err(format!("Unsupported runner '{}' for kind '{:?}'", runner, kind)),
     };
@@ -1,9 +1,14 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 use tokio::process::Command;
 use tokio::time::timeout;
 
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::wsl::{self, wsl_repo};
+
 #[derive(Debug, thiserror::Error)]
 pub enum SafetyError {
     #[error("Path traversal attempt blocked")]
@@ -43,6 +48,11 @@ pub fn sanitize_path(repo_root: &Path, rel_path: &str) -> Result<PathBuf, Safety
         }
     }
     
+    // A remote repository's files are resolved on the host when they're read
+    if remote_repo(repo_root).is_some() {
+        return Ok(repo_root.join(&clean_path));
+    }
+    
     let full_path = repo_root.join(&clean_path);
     
     // Canonicalize and verify it's within repo
@@ -90,6 +100,48 @@ pub fn truncate_string(s: &str, max_chars: usize) -> (String, bool) {
     }
 }
 
+/// Share of a middle-truncated string's budget kept from its start; the rest
+/// goes to the end, where errors and test failures usually are
+const HEAD_SHARE: usize = 2;
+const HEAD_SHARE_OF: usize = 5;
+
+/// Truncate the middle of `s` to fit `max_bytes`, keeping its head and tail
+/// around an elision marker. Returns the text and how many bytes were omitted.
+pub fn truncate_middle(s: &str, max_bytes: usize) -> (String, usize) {
+    if s.len() <= max_bytes {
+        return (s.to_string(), 0);
+    }
+    let mut head = max_bytes * HEAD_SHARE / HEAD_SHARE_OF;
+    while !s.is_char_boundary(head) {
+        head -= 1;
+    }
+    let mut tail = s.len() - (max_bytes - head);
+    while !s.is_char_boundary(tail) {
+        tail += 1;
+    }
+    let omitted = tail - head;
+    (format!("{}\n\n[... {} bytes omitted ...]\n\n{}", &s[..head], omitted, &s[tail..]), omitted)
+}
+
+/// A command running `cmd args` in `cwd`: directly, over ssh when `cwd` is a
+/// remote repository, or through wsl.exe when it's inside a WSL distro, where
+/// Windows builds of git and friends would be slow over the 9P share and see
+/// the wrong line endings and permissions
+pub fn command_in(cmd: &str, args: &[&str], cwd: &Path) -> Command {
+    let parts = match (remote_repo(cwd), wsl_repo(cwd)) {
+        (Some(remote), _) => remote.wrap(cmd, args),
+        (None, Some(distro)) => wsl::wrap(&distro, cmd, args),
+        (None, None) => {
+            let mut command = Command::new(cmd);
+            command.args(args).current_dir(cwd);
+            return command;
+        }
+    };
+    let mut command = Command::new(&parts[0]);
+    command.args(&parts[1..]);
+    command
+}
+
 /// Safe command spawn with timeout
 pub async fn safe_spawn(
     cmd: &str,
@@ -99,9 +151,7 @@ pub async fn safe_spawn(
 ) -> Result<(String, String, i32), SafetyError> {
     let output = timeout(
         Duration::from_secs(timeout_secs),
-        Command::new(cmd)
-            .args(args)
-            .current_dir(cwd)
+        command_in(cmd, args, cwd)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
@@ -116,9 +166,34 @@ pub async fn safe_spawn(
     Ok((stdout, stderr, code))
 }
 
-/// Check if ripgrep is available
-pub fn has_ripgrep() -> bool {
-    which::which("rg").is_ok()
+/// What `has_ripgrep` found per repository, so remote and WSL repos aren't
+/// probed again on every search
+static RIPGREP: OnceLock<Mutex<HashMap<(String, String), bool>>> = OnceLock::new();
+
+/// Check if ripgrep is available where searches in `repo_path` run. A probe
+/// that can't reach the host or distro counts as no ripgrep and is retried on
+/// the next search.
+pub async fn has_ripgrep(repo_path: &Path) -> bool {
+    let cache = RIPGREP.get_or_init(Default::default);
+    let key = (repo_path.to_string_lossy().to_string(), "rg".to_string());
+    if let Some(found) = cache.lock().unwrap().get(&key) {
+        return *found;
+    }
+    let probed = match (remote_repo(repo_path), wsl_repo(repo_path)) {
+        (Some(remote), _) => remote.has_program("rg").await,
+        (None, Some(distro)) => wsl::has_program(&distro, "rg").await,
+        (None, None) => return which::which("rg").is_ok(),
+    };
+    match probed {
+        Ok(found) => {
+            cache.lock().unwrap().insert(key, found);
+            found
+        }
+        Err(e) => {
+            tracing::warn!(repo = %repo_path.display(), error = %e, "cannot check for ripgrep");
+            false
+        }
+    }
 }
 
 #[cfg(test)]
@@ -138,6 +213,20 @@ mod tests {
         assert_eq!(result, "hello");
         assert!(truncated);
     }
+
+    #[test]
+    fn test_truncate_middle() {
+        assert_eq!(truncate_middle("short", 10), ("short".to_string(), 0));
+
+        let (result, omitted) = truncate_middle("headBBBBBBBBBBtail:E", 10);
+        assert_eq!(omitted, 10);
+        assert_eq!(result, "head\n\n[... 10 bytes omitted ...]\n\ntail:E");
+
+        // Cuts stay on character boundaries
+        let (result, omitted) = truncate_middle("ééééé", 5);
+        assert_eq!(result, "é\n\n[... 6 bytes omitted ...]\n\né");
+        assert_eq!(omitted, 6);
+    }
     
     #[test]
     fn test_sanitize_path_valid() {
@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::repo_tools::processes::ResourceLimits;
+use crate::repo_tools::run_settings::run_setting;
+
+/// Where run_command executes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Host,
+    Docker,
+    Podman,
+}
+
+impl Backend {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "host" => Some(Backend::Host),
+            "docker" => Some(Backend::Docker),
+            "podman" => Some(Backend::Podman),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::Host => "host",
+            Backend::Docker => "docker",
+            Backend::Podman => "podman",
+        }
+    }
+}
+
+/// How the repository is made available inside the container
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MountMode {
+    /// Mounted read-only; commands that write into the repo fail
+    ReadOnly,
+    /// Copied into the container's own filesystem, so writes are thrown away with it
+    Copy,
+}
+
+impl MountMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "readonly" => Some(MountMode::ReadOnly),
+            "copy" => Some(MountMode::Copy),
+            _ => None,
+        }
+    }
+}
+
+/// A container backend configured for a run's project
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    pub backend: Backend,
+    pub image: String,
+    pub mount: MountMode,
+}
+
+const WORKDIR: &str = "/workspace";
+const SOURCE_DIR: &str = "/src";
+
+impl Sandbox {
+    /// The container command running `cmd_parts` against `repo_path`.
+    /// `writable` host paths are mounted at the same location, for output files,
+    /// and `readonly` ones likewise but read-only, e.g. a plugin's executable.
+    pub fn wrap(
+        &self,
+        name: &str,
+        cmd_parts: &[String],
+        repo_path: &Path,
+        writable: &[PathBuf],
+        readonly: &[PathBuf],
+        limits: &ResourceLimits,
+    ) -> Vec<String> {
+        let repo = repo_path.to_string_lossy();
+        let mut cmd: Vec<String> = vec![
+            self.backend.as_str().to_string(),
+            "run".into(),
+            "--rm".into(),
+            "--name".into(),
+            name.to_string(),
+            // Project scripts are untrusted; they get no network
+            "--network".into(),
+            "none".into(),
+        ];
+        cmd.extend(limits.container_args());
+        for path in writable {
+            let path = path.to_string_lossy();
+            cmd.extend(["-v".to_string(), format!("{}:{}", path, path)]);
+        }
+        for path in readonly {
+            let path = path.to_string_lossy();
+            cmd.extend(["-v".to_string(), format!("{}:{}:ro", path, path)]);
+        }
+        match self.mount {
+            MountMode::ReadOnly => {
+                cmd.extend(["-v".to_string(), format!("{}:{}:ro", repo, WORKDIR), "-w".to_string(), WORKDIR.to_string()]);
+                cmd.push(self.image.clone());
+            }
+            MountMode::Copy => {
+                cmd.extend(["-v".to_string(), format!("{}:{}:ro", repo, SOURCE_DIR)]);
+                cmd.push(self.image.clone());
+                // The command's arguments follow as "$@", so nothing from them is parsed by the shell
+                cmd.extend([
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    format!("mkdir -p {w} && cp -a {s}/. {w}/ && cd {w} && exec \"$@\"", w = WORKDIR, s = SOURCE_DIR),
+                    "sh".to_string(),
+                ]);
+            }
+        }
+        cmd.extend(cmd_parts.iter().cloned());
+        cmd
+    }
+}
+
+/// The container run_command should use for this run, or None to run on the host
+pub async fn resolve_sandbox(app: &AppHandle, run_id: &str) -> Result<Option<Sandbox>, String> {
+    let backend = run_setting(app, run_id, "command_backend").await?.unwrap_or_default();
+    let backend = match backend.trim() {
+        "" => Backend::Host,
+        b => Backend::parse(b).ok_or_else(|| format!("Unknown command backend '{}' (expected host, docker, or podman)", b))?,
+    };
+    if backend == Backend::Host {
+        return Ok(None);
+    }
+    let image = run_setting(app, run_id, "container_image").await?.unwrap_or_default();
+    if image.trim().is_empty() {
+        // Never fall back to the host when a container was asked for
+        return Err(format!("Set container_image to run commands with {}", backend.as_str()));
+    }
+    let mount = run_setting(app, run_id, "container_mount").await?.unwrap_or_default();
+    let mount = MountMode::parse(mount.trim()).unwrap_or(MountMode::Copy);
+    Ok(Some(Sandbox { backend, image: image.trim().to_string(), mount }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_command() {
+        let npm_test = vec!["npm".to_string(), "test".to_string()];
+        let sandbox = Sandbox { backend: Backend::Podman, image: "node:20".into(), mount: MountMode::ReadOnly };
+        assert_eq!(
+            sandbox.wrap("c1", &npm_test, Path::new("/repo"), &[], &[], &ResourceLimits::default()).join(" "),
+            "podman run --rm --name c1 --network none -v /repo:/workspace:ro -w /workspace node:20 npm test"
+        );
+
+        let sandbox = Sandbox { backend: Backend::Docker, mount: MountMode::Copy, ..sandbox };
+        let limits = ResourceLimits { memory_mb: Some(512), cpus: None };
+        let cmd = sandbox.wrap("c2", &npm_test, Path::new("/repo"), &[PathBuf::from("/tmp/out")], &[PathBuf::from("/plugins")], &limits);
+        assert_eq!(&cmd[..9], ["docker", "run", "--rm", "--name", "c2", "--network", "none", "--memory", "512m"]);
+        assert_eq!(&cmd[9..16], ["-v", "/tmp/out:/tmp/out", "-v", "/plugins:/plugins:ro", "-v", "/repo:/src:ro", "node:20"]);
+        assert_eq!(&cmd[cmd.len() - 3..], ["sh", "npm", "test"]);
+        assert_eq!(Backend::parse("vm"), None);
+    }
+}
@@ -1,6 +1,159 @@
 use serde_json::{json, Value};
+use std::collections::HashMap;
 
+use crate::repo_tools::plugins::plugin_schemas;
+
+/// Description length the compact profile cuts to
+const COMPACT_DESCRIPTION_CHARS: usize = 160;
+
+/// How tool schemas are shaped for a provider, from the `tool_schema_profile`
+/// setting; `auto` picks one by `provider_name`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaProfile {
+    /// The schemas as written
+    Standard,
+    /// OpenAI strict function calling: every property required, optional ones
+    /// nullable, no additional properties, no numeric bounds
+    Strict,
+    /// No `enum` or numeric bounds; the allowed values move into the description
+    Flat,
+    /// Flat, with descriptions cut short for providers with small tool budgets
+    Compact,
+}
+
+impl SchemaProfile {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "standard" => Some(SchemaProfile::Standard),
+            "strict" => Some(SchemaProfile::Strict),
+            "flat" => Some(SchemaProfile::Flat),
+            "compact" => Some(SchemaProfile::Compact),
+            _ => None,
+        }
+    }
+
+    /// The profile for a provider whose schemas are known to need one
+    fn for_provider(provider_name: &str) -> Self {
+        let provider = provider_name.to_lowercase();
+        if provider.contains("openai") || provider.contains("azure") {
+            SchemaProfile::Strict
+        } else if provider.contains("gemini") || provider.contains("google") {
+            SchemaProfile::Flat
+        } else if provider.contains("ollama") || provider.contains("llama.cpp") || provider.contains("lmstudio") {
+            SchemaProfile::Compact
+        } else {
+            SchemaProfile::Standard
+        }
+    }
+
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        match settings.get("tool_schema_profile").map(|v| v.trim()).unwrap_or("auto") {
+            "auto" | "" => SchemaProfile::for_provider(settings.get("provider_name").map_or("", |p| p.as_str())),
+            other => SchemaProfile::parse(other).unwrap_or(SchemaProfile::Standard),
+        }
+    }
+}
+
+/// Note for a description listing what `enum` (unless it's kept) and numeric
+/// bounds would have enforced
+fn constraint_note(property: &Value, keep_enum: bool) -> Option<String> {
+    let mut notes = vec![];
+    if let Some(values) = property.get("enum").and_then(|e| e.as_array()).filter(|_| !keep_enum) {
+        let values: Vec<&str> = values.iter().filter_map(|v| v.as_str()).collect();
+        notes.push(format!("one of: {}", values.join(", ")));
+    }
+    match (property.get("minimum"), property.get("maximum")) {
+        (Some(min), Some(max)) => notes.push(format!("{} to {}", min, max)),
+        (Some(min), None) => notes.push(format!("at least {}", min)),
+        (None, Some(max)) => notes.push(format!("at most {}", max)),
+        (None, None) => {}
+    }
+    (!notes.is_empty()).then(|| notes.join("; "))
+}
+
+/// Move a property's bounds, and its `enum` unless `keep_enum`, into its description
+fn flatten_property(property: &mut Value, keep_enum: bool) {
+    let Some(note) = constraint_note(property, keep_enum) else {
+        return;
+    };
+    let description = property["description"].as_str().unwrap_or_default().trim_end_matches('.').to_string();
+    property["description"] = json!(format!("{} ({})", description, note).trim_start().to_string());
+    if let Some(obj) = property.as_object_mut() {
+        if !keep_enum {
+            obj.remove("enum");
+        }
+        obj.remove("minimum");
+        obj.remove("maximum");
+    }
+}
+
+fn cut_description(value: &mut Value, max_chars: usize) {
+    if let Some(description) = value.get("description").and_then(|d| d.as_str()) {
+        if description.chars().count() > max_chars {
+            let cut: String = description.chars().take(max_chars.saturating_sub(3)).collect();
+            value["description"] = json!(format!("{}...", cut.trim_end()));
+        }
+    }
+}
+
+/// One tool schema shaped for `profile`
+fn adapt_schema(mut schema: Value, profile: SchemaProfile) -> Value {
+    if profile == SchemaProfile::Standard {
+        return schema;
+    }
+    let function = &mut schema["function"];
+    let parameters = &mut function["parameters"];
+    let required: Vec<String> = parameters["required"].as_array()
+        .map(|r| r.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    let mut names = vec![];
+    if let Some(properties) = parameters["properties"].as_object_mut() {
+        for (name, property) in properties.iter_mut() {
+            names.push(name.clone());
+            match profile {
+                SchemaProfile::Strict => {
+                    // Bounds aren't supported in strict mode; enums are
+                    flatten_property(property, true);
+                    if !required.contains(name) {
+                        let kind = property["type"].clone();
+                        property["type"] = json!([kind, "null"]);
+                        if let Some(values) = property.get_mut("enum").and_then(|e| e.as_array_mut()) {
+                            values.push(Value::Null);
+                        }
+                    }
+                }
+                SchemaProfile::Flat | SchemaProfile::Compact => flatten_property(property, false),
+                SchemaProfile::Standard => {}
+            }
+            if profile == SchemaProfile::Compact {
+                cut_description(property, COMPACT_DESCRIPTION_CHARS);
+            }
+        }
+    }
+    if profile == SchemaProfile::Strict {
+        parameters["required"] = json!(names);
+        parameters["additionalProperties"] = json!(false);
+        function["strict"] = json!(true);
+    }
+    if profile == SchemaProfile::Compact {
+        cut_description(function, COMPACT_DESCRIPTION_CHARS);
+    }
+    schema
+}
+
+/// Shape tool schemas for the provider `profile` is for
+pub fn adapt_schemas(schemas: Vec<Value>, profile: SchemaProfile) -> Vec<Value> {
+    schemas.into_iter().map(|schema| adapt_schema(schema, profile)).collect()
+}
+
+/// The built-in tools' schemas, followed by those of the loaded tool plugins
 pub fn repo_tool_schemas() -> Vec<Value> {
+    let mut schemas = builtin_tool_schemas();
+    schemas.extend(plugin_schemas());
+    schemas
+}
+
+pub fn builtin_tool_schemas() -> Vec<Value> {
     vec![
         list_files_schema(),
         read_file_schema(),
@@ -8,7 +161,9 @@ pub fn repo_tool_schemas() -> Vec<Value> {
         git_status_schema(),
         git_diff_schema(),
         git_log_short_schema(),
+        git_pull_schema(),
         run_command_schema(),
+        get_diagnostics_schema(),
     ]
 }
 
@@ -21,9 +176,9 @@ fn list_files_schema() -> Value {
             "parameters": {
                 "type": "object",
                 "properties": {
-                    "project_id": {
+                    "path": {
                         "type": "string",
-                        "description": "Project ID to operate on"
+                        "description": "Optional subdirectory to list, relative to repo root"
                     },
                     "globs": {
                         "type": "array",
@@ -32,10 +187,10 @@ fn list_files_schema() -> Value {
                     },
                     "max_files": {
                         "type": "integer",
-                        "description": "Maximum files to return (default 2000)"
+                        "description": "Maximum files to return (defaults to, and capped at, the configured limit)"
                     }
                 },
-                "required": ["project_id"]
+                "required": []
             }
         }
     })
@@ -46,24 +201,20 @@ fn read_file_schema() -> Value {
         "type": "function",
         "function": {
             "name": "read_file",
-            "description": "Read contents of a file within the repository. Large files are truncated.",
+            "description": "Read contents of a file within the repository. Large files lose their middle; the start and end are kept.",
             "parameters": {
                 "type": "object",
                 "properties": {
-                    "project_id": {
-                        "type": "string",
-                        "description": "Project ID"
-                    },
                     "path": {
                         "type": "string",
                         "description": "Relative path to file within repo"
                     },
                     "max_bytes": {
                         "type": "integer",
-                        "description": "Max bytes to read (default 200000)"
+                        "description": "Max bytes to read (defaults to, and capped at, the configured limit)"
                     }
                 },
-                "required": ["project_id", "path"]
+                "required": ["path"]
             }
         }
     })
@@ -78,10 +229,6 @@ fn grep_schema() -> Value {
             "parameters": {
                 "type": "object",
                 "properties": {
-                    "project_id": {
-                        "type": "string",
-                        "description": "Project ID"
-                    },
                     "query": {
                         "type": "string",
                         "description": "Search pattern"
@@ -92,10 +239,10 @@ fn grep_schema() -> Value {
                     },
                     "max_results": {
                         "type": "integer",
-                        "description": "Max matches to return (default 200)"
+                        "description": "Max matches to return (defaults to, and capped at, the configured limit)"
                     }
                 },
-                "required": ["project_id", "query"]
+                "required": ["query"]
             }
         }
     })
@@ -109,13 +256,8 @@ fn git_status_schema() -> Value {
             "description": "Get git status of the repository including branch info.",
             "parameters": {
                 "type": "object",
-                "properties": {
-                    "project_id": {
-                        "type": "string",
-                        "description": "Project ID"
-                    }
-                },
-                "required": ["project_id"]
+                "properties": {},
+                "required": []
             }
         }
     })
@@ -126,20 +268,25 @@ fn git_diff_schema() -> Value {
         "type": "function",
         "function": {
             "name": "git_diff",
-            "description": "Get git diff of unstaged or staged changes.",
+            "description": "Get git diff of unstaged or staged changes, or of the current branch against a base.",
             "parameters": {
                 "type": "object",
                 "properties": {
-                    "project_id": {
-                        "type": "string",
-                        "description": "Project ID"
-                    },
                     "staged": {
                         "type": "boolean",
                         "description": "Show staged changes instead of unstaged"
+                    },
+                    "base": {
+                        "type": "string",
+                        "description": "Branch, tag or commit to diff the current branch against (its changes since they diverged); overrides staged"
+                    },
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Optional relative paths to limit the diff to"
                     }
                 },
-                "required": ["project_id"]
+                "required": []
             }
         }
     })
@@ -154,16 +301,27 @@ fn git_log_short_schema() -> Value {
             "parameters": {
                 "type": "object",
                 "properties": {
-                    "project_id": {
-                        "type": "string",
-                        "description": "Project ID"
-                    },
                     "max_commits": {
                         "type": "integer",
                         "description": "Number of commits to retrieve (default 10)"
                     }
                 },
-                "required": ["project_id"]
+                "required": []
+            }
+        }
+    })
+}
+
+fn git_pull_schema() -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": "git_pull",
+            "description": "Fast-forward a project cloned from a git URL to the latest commit on its branch. Only available when the project allows it.",
+            "parameters": {
+                "type": "object",
+                "properties": {},
+                "required": []
             }
         }
     })
@@ -174,27 +332,91 @@ fn run_command_schema() -> Value {
         "type": "function",
         "function": {
             "name": "run_command",
-            "description": "Run allowlisted test, lint, or build commands. Auto-detects package manager.",
+            "description": "Run allowlisted test, lint, build, typecheck, or bench commands. Auto-detects package manager unless the project defines its own command for the kind.",
             "parameters": {
                 "type": "object",
                 "properties": {
-                    "project_id": {
-                        "type": "string",
-                        "description": "Project ID"
-                    },
                     "kind": {
                         "type": "string",
-                        "enum": ["tests", "lint", "build"],
+                        "enum": ["tests", "lint", "build", "typecheck", "bench"],
                         "description": "Type of command to run"
                     },
                     "runner": {
                         "type": "string",
                         "enum": ["pnpm", "npm", "yarn", "cargo", "pytest"],
                         "description": "Optional explicit runner (auto-detected if not provided)"
+                    },
+                    "repeat": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 5,
+                        "description": "Tests only: run the suite this many times and report tests that both passed and failed as flaky"
+                    },
+                    "rerun_failures": {
+                        "type": "boolean",
+                        "description": "Tests only: rerun just the failed tests (twice, or repeat - 1 times) to tell flaky failures from real ones"
                     }
                 },
-                "required": ["project_id", "kind"]
+                "required": ["kind"]
             }
         }
     })
 }
+
+fn get_diagnostics_schema() -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": "get_diagnostics",
+            "description": "Get type errors, warnings, and unused symbols the project's configured language server reports for the given files, without running a full build.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Files to check, relative to repo root (at most 20)"
+                    }
+                },
+                "required": ["paths"]
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_command(profile: SchemaProfile) -> Value {
+        adapt_schemas(vec![run_command_schema()], profile).remove(0)
+    }
+
+    #[test]
+    fn test_adapt_schemas() {
+        assert_eq!(run_command(SchemaProfile::Standard), run_command_schema());
+
+        let flat = run_command(SchemaProfile::Flat);
+        let kind = &flat["function"]["parameters"]["properties"]["kind"];
+        assert!(kind.get("enum").is_none());
+        assert_eq!(kind["description"], "Type of command to run (one of: tests, lint, build, typecheck, bench)");
+        assert!(flat["function"]["parameters"]["properties"]["repeat"].get("maximum").is_none());
+
+        let strict = run_command(SchemaProfile::Strict);
+        let parameters = &strict["function"]["parameters"];
+        assert_eq!(strict["function"]["strict"], true);
+        assert_eq!(parameters["additionalProperties"], false);
+        assert_eq!(parameters["required"].as_array().unwrap().len(), parameters["properties"].as_object().unwrap().len());
+        assert_eq!(parameters["properties"]["kind"]["type"], "string");
+        assert_eq!(parameters["properties"]["runner"]["type"], json!(["string", "null"]));
+        assert_eq!(parameters["properties"]["runner"]["enum"].as_array().unwrap().last(), Some(&Value::Null));
+
+        let compact = run_command(SchemaProfile::Compact);
+        assert!(compact["function"]["description"].as_str().unwrap().chars().count() <= COMPACT_DESCRIPTION_CHARS);
+
+        let settings = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+        assert_eq!(SchemaProfile::from_settings(&settings(&[("provider_name", "OpenAI")])), SchemaProfile::Strict);
+        assert_eq!(SchemaProfile::from_settings(&settings(&[("provider_name", "OpenAI"), ("tool_schema_profile", "flat")])), SchemaProfile::Flat);
+        assert_eq!(SchemaProfile::from_settings(&settings(&[("provider_name", "OpenRouter")])), SchemaProfile::Standard);
+    }
+}
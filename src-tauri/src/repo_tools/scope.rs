@@ -0,0 +1,89 @@
+use rusqlite::OptionalExtension;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::db;
+
+/// A task's scope_path as a clean repo-relative directory, or None for the
+/// whole repository
+pub fn normalize_scope(scope_path: &str) -> Result<Option<String>, String> {
+    let normalized = scope_path.trim().replace('\\', "/");
+    if normalized.starts_with('/') || normalized.contains(':') {
+        return Err(format!("scope_path '{}' must be relative to the repository root", scope_path));
+    }
+    let parts: Vec<&str> = normalized.split('/').filter(|p| !p.is_empty() && *p != ".").collect();
+    if parts.contains(&"..") {
+        return Err(format!("scope_path '{}' can't leave the repository", scope_path));
+    }
+    Ok(if parts.is_empty() { None } else { Some(parts.join("/")) })
+}
+
+/// Scope of the task the run belongs to
+pub fn run_scope(app: &AppHandle, run_id: &str) -> Result<Option<String>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let scope: Option<String> = conn.query_row(
+        "SELECT t.scope_path FROM runs r JOIN tasks t ON t.id = r.task_id WHERE r.id = ?1",
+        [run_id],
+        |r| r.get(0)
+    ).optional().map_err(|e| e.to_string())?.flatten();
+    match scope {
+        Some(scope) => normalize_scope(&scope),
+        None => Ok(None),
+    }
+}
+
+fn within(scope: &str, path: &str) -> bool {
+    match normalize_scope(path) {
+        Ok(Some(path)) => path == scope || path.starts_with(&format!("{}/", scope)),
+        _ => false,
+    }
+}
+
+/// `args` for `tool` rooted at the scope: list_files and grep default to the
+/// subtree, git_diff to its paths, and paths outside it are refused
+pub fn apply_scope(tool: &str, args: &Value, scope: &str) -> Result<Value, String> {
+    let outside = |path: &str| format!("'{}' is outside this task's scope ({})", path, scope);
+    let mut args = args.clone();
+    match tool {
+        "list_files" | "grep" => match args.get("path").and_then(|v| v.as_str()) {
+            Some(path) if !within(scope, path) => return Err(outside(path)),
+            Some(_) => {}
+            None => args["path"] = json!(scope),
+        },
+        "git_diff" => {
+            let paths: Vec<&str> = args.get("paths")
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|p| p.as_str()).collect())
+                .unwrap_or_default();
+            if let Some(path) = paths.iter().find(|p| !within(scope, p)) {
+                return Err(outside(path));
+            }
+            if paths.is_empty() {
+                args["paths"] = json!([scope]);
+            }
+        }
+        _ => {}
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_scope() {
+        assert_eq!(normalize_scope("./services/billing/").unwrap().as_deref(), Some("services/billing"));
+        assert_eq!(normalize_scope("  ").unwrap(), None);
+        assert!(normalize_scope("../other").is_err());
+        assert!(normalize_scope("/etc").is_err());
+
+        let scope = "services/billing";
+        assert_eq!(apply_scope("list_files", &json!({}), scope).unwrap()["path"], "services/billing");
+        assert_eq!(apply_scope("grep", &json!({ "query": "x", "path": "services/billing/src" }), scope).unwrap()["path"], "services/billing/src");
+        assert!(apply_scope("grep", &json!({ "query": "x", "path": "services/billing-v2" }), scope).is_err());
+        assert_eq!(apply_scope("git_diff", &json!({ "staged": true }), scope).unwrap()["paths"], json!(["services/billing"]));
+        assert!(apply_scope("git_diff", &json!({ "paths": ["services/billing/a.rs", "web/b.ts"] }), scope).is_err());
+        assert_eq!(apply_scope("read_file", &json!({ "path": "Cargo.toml" }), scope).unwrap()["path"], "Cargo.toml");
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::run_settings::run_setting;
+use crate::repo_tools::safety::sanitize_path;
+
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Tools a project's script for each command kind is often named after
+fn related_names(kind: &str) -> &'static [&'static str] {
+    match kind {
+        "tests" => &["test", "tests", "spec", "jest", "vitest", "mocha", "unit"],
+        "lint" => &["lint", "eslint", "biome", "stylelint", "check"],
+        "build" => &["build", "compile", "bundle", "dist"],
+        "typecheck" => &["typecheck", "tsc", "types", "check"],
+        "bench" => &["bench", "benchmark", "perf"],
+        _ => &[],
+    }
+}
+
+/// The `script_fallbacks` setting: the script to run instead when a kind's
+/// standard one is missing, e.g. `{"lint": "lint:all"}`
+pub type ScriptFallbacks = BTreeMap<String, String>;
+
+pub fn parse_script_fallbacks(json: &str) -> Result<ScriptFallbacks, String> {
+    let fallbacks: ScriptFallbacks = serde_json::from_str(json)
+        .map_err(|e| format!("script_fallbacks must map command kinds to script names: {}", e))?;
+    for (kind, script) in &fallbacks {
+        if related_names(kind).is_empty() {
+            return Err(format!("Unknown command kind '{}' (expected tests, lint, build, typecheck, or bench)", kind));
+        }
+        if script.trim().is_empty() || script.starts_with('-') {
+            return Err(format!("Invalid script name '{}' for {}", script, kind));
+        }
+    }
+    Ok(fallbacks)
+}
+
+async fn read_repo_file(repo_path: &Path, rel_path: &str) -> Option<String> {
+    let content = match remote_repo(repo_path) {
+        Some(remote) => remote.read(rel_path).await.ok()?,
+        None => tokio::fs::read(sanitize_path(repo_path, rel_path).ok()?).await.ok()?,
+    };
+    String::from_utf8(content).ok()
+}
+
+/// Script names in package.json, or cargo aliases in .cargo/config.toml
+fn parse_scripts(runner: &str, manifest: &str) -> Vec<String> {
+    let mut names: Vec<String> = if runner == "cargo" {
+        manifest.parse::<toml::Table>().ok()
+            .and_then(|config| config.get("alias").and_then(|a| a.as_table()).map(|a| a.keys().cloned().collect()))
+            .unwrap_or_default()
+    } else {
+        serde_json::from_str::<serde_json::Value>(manifest).ok()
+            .and_then(|pkg| pkg.get("scripts").and_then(|s| s.as_object()).map(|s| s.keys().cloned().collect()))
+            .unwrap_or_default()
+    };
+    names.sort();
+    names
+}
+
+/// The scripts (or cargo aliases) the repository defines for `runner`
+pub async fn discover_scripts(repo_path: &Path, runner: &str) -> Vec<String> {
+    let manifest = match runner {
+        "npm" | "pnpm" | "yarn" => "package.json",
+        "cargo" => ".cargo/config.toml",
+        _ => return vec![],
+    };
+    match read_repo_file(repo_path, manifest).await {
+        Some(text) => parse_scripts(runner, &text),
+        None => vec![],
+    }
+}
+
+/// Scripts that look like stand-ins for the missing one, best first:
+/// variants of its name (`lint:all`), then ones named after a related tool (`eslint`)
+pub fn suggest_scripts(missing: &str, kind: &str, available: &[String]) -> Vec<String> {
+    let related = related_names(kind);
+    let mut ranked: Vec<(u8, &String)> = available.iter()
+        .filter(|name| name.as_str() != missing)
+        .filter_map(|name| {
+            let lower = name.to_lowercase();
+            let base = lower.split([':', '-', '_', '.']).next().unwrap_or_default();
+            let rank = if base == missing {
+                0
+            } else if lower.contains(missing) {
+                1
+            } else if related.contains(&base) {
+                2
+            } else if related.iter().any(|r| lower.contains(r)) {
+                3
+            } else {
+                return None;
+            };
+            Some((rank, name))
+        })
+        .collect();
+    ranked.sort();
+    ranked.into_iter().take(MAX_SUGGESTIONS).map(|(_, name)| name.clone()).collect()
+}
+
+/// Command running `script` with `runner`
+pub fn script_command(runner: &str, script: &str) -> Option<Vec<String>> {
+    let cmd = match runner {
+        "npm" | "pnpm" | "yarn" => vec![runner, "run", script],
+        "cargo" => vec!["cargo", script],
+        _ => return None,
+    };
+    Some(cmd.into_iter().map(str::to_string).collect())
+}
+
+/// The project's configured stand-in for `kind`, if it has one
+pub async fn configured_fallback(app: &AppHandle, run_id: &str, kind: &str) -> Result<Option<String>, String> {
+    let Some(json) = run_setting(app, run_id, "script_fallbacks").await?.filter(|v| !v.trim().is_empty()) else {
+        return Ok(None);
+    };
+    Ok(parse_script_fallbacks(&json)?.remove(kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_scripts() {
+        let scripts = parse_scripts("npm", r#"{"scripts": {"dev": "vite", "lint:all": "eslint .", "eslint-fix": "eslint --fix .", "test:unit": "vitest", "format": "prettier -w ."}}"#);
+        assert_eq!(scripts, ["dev", "eslint-fix", "format", "lint:all", "test:unit"]);
+        assert_eq!(suggest_scripts("lint", "lint", &scripts), ["lint:all", "eslint-fix"]);
+        assert_eq!(suggest_scripts("test", "tests", &scripts), ["test:unit"]);
+        assert!(suggest_scripts("build", "build", &scripts).is_empty());
+
+        let aliases = parse_scripts("cargo", "[alias]\nlint = \"clippy --all-targets\"\nxtask = \"run -p xtask --\"\n");
+        assert_eq!(aliases, ["lint", "xtask"]);
+        assert_eq!(script_command("pnpm", "lint:all").unwrap(), ["pnpm", "run", "lint:all"]);
+        assert_eq!(script_command("cargo", "lint").unwrap(), ["cargo", "lint"]);
+
+        assert_eq!(parse_script_fallbacks(r#"{"lint": "lint:all"}"#).unwrap()["lint"], "lint:all");
+        assert!(parse_script_fallbacks(r#"{"deploy": "ship"}"#).unwrap_err().contains("Unknown command kind"));
+        assert!(parse_script_fallbacks(r#"{"lint": "--fix"}"#).is_err());
+    }
+}
@@ -1,37 +1,43 @@
 use serde_json::{json, Value};
 use std::path::Path;
-use crate::repo_tools::safety::{safe_spawn, has_ripgrep};
+use crate::repo_tools::safety::{safe_spawn, sanitize_path, has_ripgrep};
+use crate::repo_tools::context_ignore::context_ignore;
+use crate::repo_tools::limits::{requested, OutputLimits};
 use crate::repo_tools::logging::log_tool_call;
+use crate::repo_tools::remote::remote_repo;
 use tauri::AppHandle;
 
-const MAX_RESULTS_DEFAULT: usize = 200;
-
 pub async fn grep(
     repo_path: &Path,
     args: &Value,
     app: &AppHandle,
     run_id: &str,
+    limits: &OutputLimits,
 ) -> Result<Value, String> {
     let query = args.get("query")
         .and_then(|v| v.as_str())
         .ok_or("query is required")?;
     
     let path_filter = args.get("path").and_then(|v| v.as_str());
-    let max_results = args.get("max_results")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(MAX_RESULTS_DEFAULT as u64) as usize;
+    let max_results = requested(args, "max_results", limits.grep_results);
+    let max_line_chars = limits.grep_line_chars;
     
-    let matches = if has_ripgrep() {
-        grep_ripgrep(repo_path, query, path_filter, max_results).await?
+    let mut matches = if has_ripgrep(repo_path).await {
+        grep_ripgrep(repo_path, query, path_filter, max_results, max_line_chars).await?
+    } else if remote_repo(repo_path).is_some() {
+        grep_remote(repo_path, query, path_filter, max_results, max_line_chars).await?
     } else {
-        grep_fallback(repo_path, query, path_filter, max_results).await?
+        grep_fallback(repo_path, query, path_filter, max_results, max_line_chars).await?
     };
+    let ignore = context_ignore(app, run_id, repo_path).await?;
+    matches.retain(|m| !ignore.is_ignored(m["path"].as_str().unwrap_or_default()));
     
     let truncated = matches.len() >= max_results;
     let result = json!({
         "matches": matches,
         "truncated": truncated,
         "count": matches.len(),
+        "limits": { "max_results": max_results, "max_line_chars": max_line_chars },
     });
     
     log_tool_call(app, run_id, "grep", args, &result)?;
@@ -43,14 +49,16 @@ async fn grep_ripgrep(
     query: &str,
     path_filter: Option<&str>,
     max_results: usize,
+    max_line_chars: usize,
 ) -> Result<Vec<Value>, String> {
     let max_results_str = max_results.to_string();
+    let max_columns_str = max_line_chars.to_string();
     let mut args: Vec<&str> = vec![
         "-n",
         "--max-count",
         &max_results_str,
         "--max-columns",
-        "200",
+        &max_columns_str,
         "-g",
         "!.git",
         "-g",
@@ -63,12 +71,15 @@ async fn grep_ripgrep(
         "!build",
     ];
     
+    // Reject filters that escape the repo before handing them to rg
     if let Some(path) = path_filter {
-        args.push(path);
+        sanitize_path(repo_path, path).map_err(|e| e.to_string())?;
     }
     
+    args.push("-e");
     args.push(query);
-    args.push(".");
+    args.push("--");
+    args.push(path_filter.unwrap_or("."));
     
     let (stdout, _, code) = safe_spawn("rg", &args, repo_path, 30)
         .await
@@ -96,11 +107,56 @@ async fn grep_ripgrep(
     Ok(matches)
 }
 
+/// Case-insensitive literal search with the remote host's grep, like the local fallback
+async fn grep_remote(
+    repo_path: &Path,
+    query: &str,
+    path_filter: Option<&str>,
+    max_results: usize,
+    max_line_chars: usize,
+) -> Result<Vec<Value>, String> {
+    let search_root = match path_filter {
+        Some(subdir) => {
+            sanitize_path(repo_path, subdir).map_err(|e| e.to_string())?;
+            subdir
+        }
+        None => ".",
+    };
+    let max_results_str = max_results.to_string();
+    let args = [
+        "-rnIiF", "-m", &max_results_str,
+        "--exclude-dir=.git", "--exclude-dir=node_modules", "--exclude-dir=target", "--exclude-dir=dist",
+        "--exclude-dir=build", "--exclude-dir=__pycache__", "--exclude-dir=.venv", "--exclude-dir=venv",
+        "-e", query, "--", search_root,
+    ];
+    let (stdout, _, _) = safe_spawn("grep", &args, repo_path, 60)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    let mut matches = vec![];
+    for line in stdout.lines().take(max_results) {
+        // Parse: path:line:text
+        let mut parts = line.splitn(3, ':');
+        if let (Some(path), Some(line_num), Some(text)) = (parts.next(), parts.next(), parts.next()) {
+            if let Ok(num) = line_num.parse::<u32>() {
+                matches.push(json!({
+                    "path": path.trim_start_matches("./"),
+                    "line": num,
+                    "text": text.chars().take(max_line_chars).collect::<String>(),
+                }));
+            }
+        }
+    }
+    
+    Ok(matches)
+}
+
 async fn grep_fallback(
     repo_path: &Path,
     query: &str,
     path_filter: Option<&str>,
     max_results: usize,
+    max_line_chars: usize,
 ) -> Result<Vec<Value>, String> {
     use walkdir::WalkDir;
     
@@ -137,7 +193,7 @@ async fn grep_fallback(
                         matches.push(json!({
                             "path": rel_path,
                             "line": line_num + 1,
-                            "text": line.chars().take(200).collect::<String>(),
+                            "text": line.chars().take(max_line_chars).collect::<String>(),
                         }));
                         
                         if matches.len() >= max_results {
@@ -0,0 +1,229 @@
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use std::collections::HashMap;
+use tauri::AppHandle;
+use crate::db;
+use crate::repo_tools::dispatcher::strip_project_id;
+use crate::models::*;
+
+pub const DEFAULT_LOOP_THRESHOLD: usize = 3;
+
+/// A tool+args combination that was repeated more often than allowed
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ToolLoopFlag {
+    pub name: String,
+    pub args_json: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct RunToolAnalysis {
+    pub run_id: String,
+    pub total_calls: usize,
+    pub calls_by_tool: HashMap<String, usize>,
+    pub loop_flags: Vec<ToolLoopFlag>,
+    pub stuck: bool,
+}
+
+/// Tracks identical tool calls during a live run
+pub struct ToolLoopGuard {
+    threshold: usize,
+    seen: HashMap<(String, String), usize>,
+    history: Vec<(String, String)>,
+}
+
+impl ToolLoopGuard {
+    pub fn new(threshold: usize) -> Self {
+        Self { threshold, seen: HashMap::new(), history: vec![] }
+    }
+
+    /// True if the last observed call repeats an earlier one exactly,
+    /// or the calls oscillate A -> B -> A with identical args
+    pub fn is_looping(&self) -> bool {
+        let Some(last) = self.history.last() else {
+            return false;
+        };
+        if self.seen.get(last).copied().unwrap_or(0) > 1 {
+            return true;
+        }
+        let n = self.history.len();
+        n >= 3 && self.history[n - 3] == *last && self.history[n - 2] != *last
+    }
+
+    /// Record a call; returns a flag once the same call exceeds the threshold
+    pub fn observe(&mut self, name: &str, args: &Value) -> Option<ToolLoopFlag> {
+        let args_json = strip_project_id(args).to_string();
+        let key = (name.to_string(), args_json.clone());
+        self.history.push(key.clone());
+        let count = self.seen.entry(key).or_insert(0);
+        *count += 1;
+
+        if *count > self.threshold {
+            Some(ToolLoopFlag { name: name.to_string(), args_json, count: *count })
+        } else {
+            None
+        }
+    }
+}
+
+/// Find tool+args combinations repeated more than `threshold` times
+pub fn detect_repeated_calls(calls: &[(String, String)], threshold: usize) -> Vec<ToolLoopFlag> {
+    let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut order = vec![];
+    for (name, args_json) in calls {
+        let key = (name.as_str(), args_json.as_str());
+        let count = counts.entry(key).or_insert(0);
+        if *count == 0 {
+            order.push(key);
+        }
+        *count += 1;
+    }
+
+    order.into_iter()
+        .filter_map(|key| {
+            let count = counts[&key];
+            (count > threshold).then(|| ToolLoopFlag {
+                name: key.0.to_string(),
+                args_json: key.1.to_string(),
+                count,
+            })
+        })
+        .collect()
+}
+
+/// Update the per-tool rollup after a dispatch
+pub fn record_tool_stat(
+    app: &AppHandle,
+    name: &str,
+    result_bytes: usize,
+    is_error: bool,
+) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let updated_at = now_iso();
+
+    conn.execute(
+        "INSERT INTO tool_stats (name, invocations, errors, total_result_bytes, updated_at)
+         VALUES (?1, 1, ?2, ?3, ?4)
+         ON CONFLICT(name) DO UPDATE SET
+           invocations = invocations + 1,
+           errors = errors + excluded.errors,
+           total_result_bytes = total_result_bytes + excluded.total_result_bytes,
+           updated_at = excluded.updated_at",
+        (name, is_error as i64, result_bytes as i64, &updated_at)
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+pub fn list_tool_stats(app: &AppHandle) -> Result<Vec<ToolStat>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT name, invocations, errors, total_result_bytes, updated_at
+         FROM tool_stats ORDER BY invocations DESC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([], |r| {
+        let invocations: i64 = r.get(1)?;
+        let errors: i64 = r.get(2)?;
+        let total_result_bytes: i64 = r.get(3)?;
+        Ok(ToolStat {
+            name: r.get(0)?,
+            invocations,
+            errors,
+            total_result_bytes,
+            avg_result_bytes: if invocations > 0 { total_result_bytes / invocations } else { 0 },
+            error_rate: if invocations > 0 { errors as f64 / invocations as f64 } else { 0.0 },
+            updated_at: r.get(4)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut out = vec![];
+    for row in rows {
+        out.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
+
+/// Post-run analytics over the logged tool calls of a run
+pub fn analyze_run_tool_usage(
+    app: &AppHandle,
+    run_id: &str,
+    threshold: usize,
+) -> Result<RunToolAnalysis, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT name, args_json FROM tool_calls WHERE run_id = ?1 ORDER BY seq ASC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([run_id], |r| {
+        Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
+    }).map_err(|e| e.to_string())?;
+
+    let mut calls = vec![];
+    for row in rows {
+        let (name, args_json) = row.map_err(|e| e.to_string())?;
+        let args_json = serde_json::from_str::<Value>(&args_json)
+            .map(|v| strip_project_id(&v).to_string())
+            .unwrap_or(args_json);
+        calls.push((name, args_json));
+    }
+
+    let mut calls_by_tool = HashMap::new();
+    for (name, _) in &calls {
+        *calls_by_tool.entry(name.clone()).or_insert(0) += 1;
+    }
+
+    let loop_flags = detect_repeated_calls(&calls, threshold);
+    Ok(RunToolAnalysis {
+        run_id: run_id.to_string(),
+        total_calls: calls.len(),
+        calls_by_tool,
+        stuck: !loop_flags.is_empty(),
+        loop_flags,
+    })
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_repeated_calls() {
+        let read = ("read_file".to_string(), r#"{"path":"a.rs"}"#.to_string());
+        let other = ("read_file".to_string(), r#"{"path":"b.rs"}"#.to_string());
+        let calls = vec![read.clone(), other, read.clone(), read.clone(), read];
+
+        let flags = detect_repeated_calls(&calls, 3);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].count, 4);
+
+        assert!(detect_repeated_calls(&calls, 4).is_empty());
+    }
+
+    #[test]
+    fn test_loop_guard_ignores_project_id() {
+        let mut guard = ToolLoopGuard::new(1);
+        assert!(guard.observe("grep", &json!({ "query": "x", "project_id": "p1" })).is_none());
+        let flag = guard.observe("grep", &json!({ "query": "x" }));
+        assert_eq!(flag.map(|f| f.count), Some(2));
+        assert!(guard.is_looping());
+    }
+
+    #[test]
+    fn test_loop_guard_oscillation() {
+        let mut guard = ToolLoopGuard::new(DEFAULT_LOOP_THRESHOLD);
+        guard.observe("read_file", &json!({ "path": "a.rs" }));
+        guard.observe("grep", &json!({ "query": "foo" }));
+        assert!(!guard.is_looping());
+        guard.observe("read_file", &json!({ "path": "a.rs" }));
+        assert!(guard.is_looping());
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Pass/fail of one test in one run of the suite
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestOutcome {
+    pub name: String,
+    pub passed: bool,
+}
+
+/// How a test fared across repeated runs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TestStat {
+    pub name: String,
+    pub runs: u32,
+    pub failures: u32,
+}
+
+impl TestStat {
+    /// Both passed and failed on the same code
+    pub fn is_flaky(&self) -> bool {
+        self.failures > 0 && self.failures < self.runs
+    }
+}
+
+/// `tests/test_api.py::test_login PASSED  [ 50%]` from `pytest -v`
+fn parse_pytest_verbose(line: &str) -> Option<(&str, bool)> {
+    let mut parts = line.split_whitespace();
+    let (name, status) = (parts.next()?, parts.next()?);
+    if !name.contains("::") {
+        return None;
+    }
+    match status {
+        "PASSED" => Some((name, true)),
+        "FAILED" | "ERROR" => Some((name, false)),
+        _ => None,
+    }
+}
+
+/// Per-test outcomes in cargo, pytest (`-v` lines and the failure summary)
+/// and jest/vitest output. Passing tests are only listed when the runner
+/// prints them.
+pub fn parse_test_outcomes(output: &str) -> Vec<TestOutcome> {
+    let mut outcomes: Vec<TestOutcome> = vec![];
+    for line in output.lines().map(str::trim) {
+        let parsed = if let Some(rest) = line.strip_prefix("test ") {
+            match rest.rsplit_once(" ... ") {
+                Some((name, "ok")) => Some((name, true)),
+                Some((name, "FAILED")) => Some((name, false)),
+                _ => None,
+            }
+        } else if let Some(rest) = line.strip_prefix("FAILED ") {
+            Some((rest.split(" - ").next().unwrap_or(rest), false))
+        } else if let Some(parsed) = parse_pytest_verbose(line) {
+            Some(parsed)
+        } else if let Some(name) = line.strip_prefix("✓ ").or_else(|| line.strip_prefix("√ ")) {
+            Some((name.rsplit_once(" (").map_or(name, |(name, _)| name), true))
+        } else {
+            line.strip_prefix("✕ ").or_else(|| line.strip_prefix("× "))
+                .map(|name| (name.rsplit_once(" (").map_or(name, |(name, _)| name), false))
+        };
+        let Some((name, passed)) = parsed else { continue };
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        // pytest -v reports a failure inline and again in the summary
+        match outcomes.iter_mut().find(|o| o.name == name) {
+            Some(existing) => existing.passed &= passed,
+            None => outcomes.push(TestOutcome { name: name.to_string(), passed }),
+        }
+    }
+    outcomes
+}
+
+/// Aggregate outcomes from several runs of the suite
+pub fn aggregate(runs: &[Vec<TestOutcome>]) -> Vec<TestStat> {
+    let mut stats: BTreeMap<&str, TestStat> = BTreeMap::new();
+    for outcome in runs.iter().flatten() {
+        let stat = stats.entry(outcome.name.as_str()).or_insert_with(|| TestStat {
+            name: outcome.name.clone(),
+            runs: 0,
+            failures: 0,
+        });
+        stat.runs += 1;
+        if !outcome.passed {
+            stat.failures += 1;
+        }
+    }
+    stats.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_aggregate_outcomes() {
+        let cargo = "running 3 tests\ntest db::tests::migrate ... ok\ntest api::tests::login ... FAILED\ntest slow ... ignored\n\
+                     test result: FAILED. 1 passed; 1 failed";
+        let first = parse_test_outcomes(cargo);
+        assert_eq!(first, vec![
+            TestOutcome { name: "db::tests::migrate".into(), passed: true },
+            TestOutcome { name: "api::tests::login".into(), passed: false },
+        ]);
+        let second = parse_test_outcomes("test api::tests::login ... ok\n");
+
+        let stats = aggregate(&[first, second]);
+        assert_eq!(stats[0], TestStat { name: "api::tests::login".into(), runs: 2, failures: 1 });
+        assert!(stats[0].is_flaky());
+        assert!(!stats[1].is_flaky());
+
+        let pytest = "tests/test_api.py::test_login FAILED  [ 50%]\ntests/test_api.py::test_logout PASSED [100%]\n\
+                      FAILED tests/test_api.py::test_login - assert 1 == 2\n";
+        let outcomes = parse_test_outcomes(pytest);
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0], TestOutcome { name: "tests/test_api.py::test_login".into(), passed: false });
+
+        let jest = "  ✓ renders header (12 ms)\n  ✕ submits form (40 ms)\n";
+        assert_eq!(parse_test_outcomes(jest)[1], TestOutcome { name: "submits form".into(), passed: false });
+    }
+}
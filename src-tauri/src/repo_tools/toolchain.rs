@@ -0,0 +1,83 @@
+use std::path::Path;
+
+/// Environment manager that pins a project's toolchain. Commands run through
+/// it get the project's compilers and tools instead of whatever is on PATH.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Toolchain {
+    Devenv,
+    Nix,
+    Mise,
+    Asdf,
+}
+
+impl Toolchain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Toolchain::Devenv => "devenv",
+            Toolchain::Nix => "nix",
+            Toolchain::Mise => "mise",
+            Toolchain::Asdf => "asdf",
+        }
+    }
+
+    /// `cmd_parts` run inside the toolchain's environment
+    pub fn wrap(&self, cmd_parts: &[String]) -> Vec<String> {
+        let prefix: &[&str] = match self {
+            Toolchain::Devenv => &["devenv", "shell"],
+            Toolchain::Nix => &["nix", "develop", "--command"],
+            Toolchain::Mise => &["mise", "exec", "--"],
+            Toolchain::Asdf => &["asdf", "exec"],
+        };
+        prefix.iter().map(|s| s.to_string()).chain(cmd_parts.iter().cloned()).collect()
+    }
+}
+
+/// The toolchain the repo's files ask for, among those `installed`. mise also
+/// reads asdf's `.tool-versions`, and is preferred for it when both are installed.
+fn from_files(repo_path: &Path, installed: impl Fn(&str) -> bool) -> Option<Toolchain> {
+    let has = |file: &str| repo_path.join(file).exists();
+    if has("devenv.nix") && installed("devenv") {
+        Some(Toolchain::Devenv)
+    } else if has("flake.nix") && installed("nix") {
+        Some(Toolchain::Nix)
+    } else if (has("mise.toml") || has(".mise.toml") || has(".tool-versions")) && installed("mise") {
+        Some(Toolchain::Mise)
+    } else if has(".tool-versions") && installed("asdf") {
+        Some(Toolchain::Asdf)
+    } else {
+        None
+    }
+}
+
+/// Toolchain to run the repo's commands through, if it pins one and the
+/// manager is installed
+pub fn detect_toolchain(repo_path: &Path) -> Option<Toolchain> {
+    from_files(repo_path, |program| which::which(program).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_wrap_toolchain() {
+        let dir = std::env::temp_dir().join(format!("spectrail-toolchain-{}", crate::models::new_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let all = |_: &str| true;
+        assert_eq!(from_files(&dir, all), None);
+
+        std::fs::write(dir.join(".tool-versions"), "nodejs 20.11.0\n").unwrap();
+        assert_eq!(from_files(&dir, all), Some(Toolchain::Mise));
+        assert_eq!(from_files(&dir, |p| p == "asdf"), Some(Toolchain::Asdf));
+
+        std::fs::write(dir.join("flake.nix"), "{}").unwrap();
+        assert_eq!(from_files(&dir, all), Some(Toolchain::Nix));
+        // Pinned but not installed: run as before rather than fail
+        assert_eq!(from_files(&dir, |p| p == "devenv"), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let cmd = vec!["cargo".to_string(), "test".to_string()];
+        assert_eq!(Toolchain::Nix.wrap(&cmd), ["nix", "develop", "--command", "cargo", "test"]);
+        assert_eq!(Toolchain::Mise.wrap(&cmd), ["mise", "exec", "--", "cargo", "test"]);
+    }
+}
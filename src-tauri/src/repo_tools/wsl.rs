@@ -0,0 +1,119 @@
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// UNC hosts Windows serves WSL distros' filesystems under
+const WSL_HOSTS: &[&str] = &["wsl$", "wsl.localhost"];
+
+/// A repository inside a WSL distro, opened from Windows through `\\wsl$` or
+/// `\\wsl.localhost`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WslPath {
+    pub distro: String,
+    /// The same directory as seen from inside the distro
+    pub linux_path: String,
+}
+
+/// `\\wsl$\Ubuntu\home\me\repo` (either slash direction, optionally with the
+/// `\\?\UNC\` prefix canonicalize adds) as distro `Ubuntu`, path `/home/me/repo`
+pub fn parse_wsl_path(path: &str) -> Option<WslPath> {
+    let normalized = path.replace('\\', "/");
+    let rest = normalized.strip_prefix("//?/UNC/")
+        .or_else(|| normalized.strip_prefix("//"))?;
+    let (host, rest) = rest.split_once('/')?;
+    if !WSL_HOSTS.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+        return None;
+    }
+    let (distro, linux_path) = rest.split_once('/').unwrap_or((rest, ""));
+    if distro.is_empty() {
+        return None;
+    }
+    let linux_path = linux_path.trim_end_matches('/');
+    Some(WslPath {
+        distro: distro.to_string(),
+        linux_path: format!("/{}", linux_path),
+    })
+}
+
+/// The WSL distro `cwd` lives in, when running on Windows
+pub fn wsl_repo(cwd: &Path) -> Option<WslPath> {
+    if !cfg!(windows) {
+        return None;
+    }
+    parse_wsl_path(&cwd.to_string_lossy())
+}
+
+/// An argument as the distro sees it: paths into the same distro become Linux
+/// paths and Windows drive paths go through /mnt. Anything else is passed as is.
+fn translate_arg(wsl: &WslPath, arg: &str) -> String {
+    if let Some(path) = parse_wsl_path(arg).filter(|p| p.distro.eq_ignore_ascii_case(&wsl.distro)) {
+        return path.linux_path;
+    }
+    let bytes = arg.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && (bytes[2] == b'\\' || bytes[2] == b'/') {
+        let rest = arg[3..].replace('\\', "/");
+        let drive = (bytes[0] as char).to_ascii_lowercase();
+        return format!("/mnt/{}/{}", drive, rest).trim_end_matches('/').to_string();
+    }
+    arg.to_string()
+}
+
+/// `wsl.exe` argv running `cmd args` in the distro, in the repository's
+/// directory, without going through a shell
+pub fn wrap(wsl: &WslPath, cmd: &str, args: &[&str]) -> Vec<String> {
+    let mut parts: Vec<String> = vec![
+        "wsl.exe".into(),
+        "-d".into(),
+        wsl.distro.clone(),
+        "--cd".into(),
+        wsl.linux_path.clone(),
+        "--exec".into(),
+        cmd.to_string(),
+    ];
+    parts.extend(args.iter().map(|arg| translate_arg(wsl, arg)));
+    parts
+}
+
+/// Whether `program` is on the distro's PATH
+pub async fn has_program(wsl: &WslPath, program: &str) -> Result<bool, String> {
+    let output = timeout(
+        Duration::from_secs(15),
+        Command::new("wsl.exe")
+            .args(["-d", &wsl.distro, "--exec", "sh", "-c", "command -v \"$1\" >/dev/null", "sh", program])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output()
+    ).await
+        .map_err(|_| format!("Timeout after 15s starting WSL distro {}", wsl.distro))?
+        .map_err(|e| format!("Cannot run wsl.exe: {}", e))?;
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => Err(format!("Cannot run commands in WSL distro {}: {}", wsl.distro, String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wsl_paths() {
+        let repo = parse_wsl_path(r"\\wsl$\Ubuntu-22.04\home\me\repo\").unwrap();
+        assert_eq!(repo, WslPath { distro: "Ubuntu-22.04".into(), linux_path: "/home/me/repo".into() });
+        assert_eq!(parse_wsl_path("//wsl.localhost/Debian/srv/app").unwrap().linux_path, "/srv/app");
+        assert_eq!(parse_wsl_path(r"\\?\UNC\wsl.localhost\Debian").unwrap().linux_path, "/");
+        assert_eq!(parse_wsl_path(r"\\fileserver\share\repo"), None);
+        assert_eq!(parse_wsl_path(r"C:\Users\me\repo"), None);
+        assert_eq!(parse_wsl_path("/home/me/repo"), None);
+
+        assert_eq!(
+            wrap(&repo, "git", &["diff", r"\\wsl$\Ubuntu-22.04\home\me\repo\src", r"C:\Temp\out.json", "src/main.rs"]),
+            ["wsl.exe", "-d", "Ubuntu-22.04", "--cd", "/home/me/repo", "--exec", "git",
+             "diff", "/home/me/repo/src", "/mnt/c/Temp/out.json", "src/main.rs"]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::export::latest_artifact;
+use crate::workflows::findings::{extract_findings, Finding, Severity};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const TOOL_URI: &str = "https://github.com/ThomasRogersF/spectrail";
+
+#[derive(Debug, Serialize, Type)]
+pub struct SarifExport {
+  pub path: Option<String>,
+  pub results: usize,
+  /// The SARIF 2.1.0 log
+  pub sarif: Value,
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+  match severity {
+    Severity::High => "error",
+    Severity::Medium => "warning",
+    Severity::Low => "note",
+  }
+}
+
+/// One rule per report section, so dashboards can group and filter findings
+fn rule_id(finding: &Finding) -> String {
+  format!("spectrail/{}", finding.section)
+}
+
+fn rule(section: &str) -> Value {
+  let description = match section {
+    "compliance" => "Change diverges from or is missing part of the implementation plan",
+    "risk" => "Risk identified during verification review",
+    "action" => "Recommended follow-up action from verification",
+    _ => "Suggested improvement from verification",
+  };
+  json!({
+    "id": format!("spectrail/{}", section),
+    "name": section,
+    "shortDescription": { "text": description },
+  })
+}
+
+/// SARIF log for `findings`; `artifact_id` and `created_at` identify the report
+pub fn sarif_log(findings: &[Finding], version: &str, artifact_id: &str, created_at: &str) -> Value {
+  let mut rules: Vec<&str> = vec![];
+  for finding in findings {
+    if !rules.contains(&finding.section) {
+      rules.push(finding.section);
+    }
+  }
+
+  let results: Vec<Value> = findings.iter().map(|finding| {
+    let mut location = json!({
+      "physicalLocation": {
+        "artifactLocation": { "uri": finding.file, "uriBaseId": "%SRCROOT%" },
+      }
+    });
+    if let Some(line) = finding.line {
+      location["physicalLocation"]["region"] = json!({ "startLine": line.max(1) });
+    }
+    json!({
+      "ruleId": rule_id(finding),
+      "ruleIndex": rules.iter().position(|r| *r == finding.section).unwrap_or(0),
+      "level": sarif_level(finding.severity),
+      "message": { "text": finding.message },
+      "locations": [location],
+      "properties": {
+        "title": finding.title,
+        "severity": finding.severity,
+      },
+    })
+  }).collect();
+
+  json!({
+    "$schema": SARIF_SCHEMA,
+    "version": "2.1.0",
+    "runs": [{
+      "tool": {
+        "driver": {
+          "name": "SpecTrail",
+          "version": version,
+          "informationUri": TOOL_URI,
+          "rules": rules.iter().map(|s| rule(s)).collect::<Vec<_>>(),
+        }
+      },
+      "automationDetails": { "id": format!("spectrail/verification/{}", artifact_id) },
+      "invocations": [{ "executionSuccessful": true, "endTimeUtc": created_at }],
+      "results": results,
+    }]
+  })
+}
+
+/// Write the latest verification report's findings as a SARIF 2.1.0 document
+pub fn export_sarif(app: &AppHandle, task_id: &str, dest: Option<&str>) -> Result<SarifExport, String> {
+  let report = latest_artifact(app, task_id, "verification_report")?;
+  let findings = extract_findings(&report.content);
+  let sarif = sarif_log(&findings, env!("CARGO_PKG_VERSION"), &report.id, &report.created_at);
+
+  if let Some(dest) = dest {
+    let json = serde_json::to_string_pretty(&sarif).map_err(|e| e.to_string())?;
+    std::fs::write(dest, json).map_err(|e| format!("Cannot write {}: {}", dest, e))?;
+  }
+  tracing::info!(task_id, results = findings.len(), "exported SARIF");
+  Ok(SarifExport { path: dest.map(|d| d.to_string()), results: findings.len(), sarif })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sarif_log_shape() {
+    let findings = vec![
+      Finding {
+        section: "risk",
+        severity: Severity::High,
+        title: "SQL injection".into(),
+        message: "Raw query".into(),
+        file: "src/db.rs".into(),
+        line: Some(10),
+      },
+      Finding {
+        section: "action",
+        severity: Severity::Low,
+        title: "Add tests".into(),
+        message: "Add tests".into(),
+        file: "tests/a.rs".into(),
+        line: None,
+      },
+    ];
+    let log = sarif_log(&findings, "0.1.0", "art-1", "2026-01-01T00:00:00Z");
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+
+    let first = &run["results"][0];
+    assert_eq!(first["ruleId"], "spectrail/risk");
+    assert_eq!(first["level"], "error");
+    assert_eq!(first["locations"][0]["physicalLocation"]["region"]["startLine"], 10);
+
+    let second = &run["results"][1];
+    assert_eq!(second["ruleIndex"], 1);
+    assert_eq!(second["level"], "note");
+    assert!(second["locations"][0]["physicalLocation"].get("region").is_none());
+  }
+}
@@ -0,0 +1,187 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::models::new_id;
+use crate::events::{RunEvent, RunStatus};
+
+/// Duration in whole seconds between two RFC 3339 timestamps, computed by SQLite
+const DURATION_SQL: &str = "CAST(ROUND((julianday(?1) - julianday(started_at)) * 86400) AS INTEGER)";
+
+#[derive(Debug, Serialize, Type)]
+pub struct TaskSession {
+  pub id: String,
+  pub kind: String, // view|run
+  pub run_id: Option<String>,
+  pub run_type: Option<String>,
+  pub started_at: String,
+  /// None while the task is still open or the run still going
+  pub ended_at: Option<String>,
+  pub duration_secs: i64,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct DayTime {
+  pub date: String, // YYYY-MM-DD (UTC)
+  pub view_secs: i64,
+  pub run_secs: i64,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct TaskTimeSummary {
+  pub task_id: String,
+  /// Time the task was open in a window
+  pub view_secs: i64,
+  /// Time spent in plan/verify runs
+  pub run_secs: i64,
+  pub runs: usize,
+  pub days: Vec<DayTime>,
+  /// Activity log, oldest first
+  pub sessions: Vec<TaskSession>,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn close_where(app: &AppHandle, filter: &str, param: &str) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute(
+    &format!("UPDATE task_sessions SET ended_at = ?1, duration_secs = {} WHERE ended_at IS NULL AND {}", DURATION_SQL, filter),
+    (now_iso(), param)
+  ).map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+/// Start timing a task shown in `window`, ending whatever that window showed before.
+/// Re-subscribing to the same task keeps the running session.
+pub fn open_view(app: &AppHandle, window: &str, task_id: Option<&str>) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let current: Option<String> = conn.query_row(
+    "SELECT task_id FROM task_sessions WHERE kind = 'view' AND window_label = ?1 AND ended_at IS NULL",
+    [window],
+    |r| r.get(0)
+  ).ok();
+  if current.is_some() && current.as_deref() == task_id {
+    return Ok(());
+  }
+  close_view(app, window)?;
+
+  if let Some(task_id) = task_id {
+    conn.execute(
+      "INSERT INTO task_sessions (id, task_id, kind, window_label, started_at) VALUES (?1, ?2, 'view', ?3, ?4)",
+      (new_id(), task_id, window, now_iso())
+    ).map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+pub fn close_view(app: &AppHandle, window: &str) -> Result<(), String> {
+  close_where(app, "kind = 'view' AND window_label = ?2", window)
+}
+
+/// Time runs from their lifecycle events. Best-effort like the other run event listeners.
+pub fn on_run_event(app: &AppHandle, event: &RunEvent) {
+  let result = match event.status {
+    RunStatus::Started => db::connect(app).map_err(|e| e.to_string()).and_then(|conn| {
+      conn.execute(
+        "INSERT INTO task_sessions (id, task_id, kind, run_id, run_type, started_at) VALUES (?1, ?2, 'run', ?3, ?4, ?5)",
+        (new_id(), &event.task_id, &event.run_id, &event.run_type, now_iso())
+      ).map(|_| ()).map_err(|e| e.to_string())
+    }),
+    RunStatus::Finished | RunStatus::Failed | RunStatus::Cancelled => close_where(app, "kind = 'run' AND run_id = ?2", &event.run_id),
+  };
+  if let Err(e) = result {
+    tracing::warn!(run_id = %event.run_id, "failed to record run session: {}", e);
+  }
+}
+
+/// Sessions left open by a crash or forced quit have no reliable end, so
+/// they're closed with zero duration rather than counting the time the app was gone
+pub fn close_stale_sessions(app: &AppHandle) -> Result<usize, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "UPDATE task_sessions SET ended_at = started_at, duration_secs = 0 WHERE ended_at IS NULL",
+    []
+  ).map_err(|e| e.to_string())
+}
+
+pub fn task_time_summary(app: &AppHandle, task_id: &str) -> Result<TaskTimeSummary, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  // Open sessions count up to now
+  let mut stmt = conn.prepare(&format!(
+    "SELECT id, kind, run_id, run_type, started_at, ended_at, COALESCE(duration_secs, {})
+     FROM task_sessions WHERE task_id = ?2 ORDER BY started_at ASC",
+    DURATION_SQL
+  )).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map((now_iso(), task_id), |r| Ok(TaskSession {
+    id: r.get(0)?,
+    kind: r.get(1)?,
+    run_id: r.get(2)?,
+    run_type: r.get(3)?,
+    started_at: r.get(4)?,
+    ended_at: r.get(5)?,
+    duration_secs: r.get::<_, Option<i64>>(6)?.unwrap_or(0).max(0),
+  })).map_err(|e| e.to_string())?;
+  let sessions = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+  Ok(summarize(task_id, sessions))
+}
+
+fn summarize(task_id: &str, sessions: Vec<TaskSession>) -> TaskTimeSummary {
+  let mut days: Vec<DayTime> = vec![];
+  let (mut view_secs, mut run_secs, mut runs) = (0, 0, 0);
+  for session in &sessions {
+    let date: String = session.started_at.chars().take(10).collect();
+    let day = match days.iter().position(|d| d.date == date) {
+      Some(i) => &mut days[i],
+      None => {
+        days.push(DayTime { date, view_secs: 0, run_secs: 0 });
+        days.last_mut().unwrap()
+      }
+    };
+    if session.kind == "run" {
+      runs += 1;
+      run_secs += session.duration_secs;
+      day.run_secs += session.duration_secs;
+    } else {
+      view_secs += session.duration_secs;
+      day.view_secs += session.duration_secs;
+    }
+  }
+  TaskTimeSummary { task_id: task_id.to_string(), view_secs, run_secs, runs, days, sessions }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn session(kind: &str, started_at: &str, duration_secs: i64) -> TaskSession {
+    TaskSession {
+      id: new_id(),
+      kind: kind.into(),
+      run_id: None,
+      run_type: None,
+      started_at: started_at.into(),
+      ended_at: None,
+      duration_secs,
+    }
+  }
+
+  #[test]
+  fn test_summarize_by_day() {
+    let summary = summarize("t1", vec![
+      session("view", "2026-03-01T09:00:00Z", 600),
+      session("run", "2026-03-01T09:05:00Z", 45),
+      session("view", "2026-03-02T10:00:00Z", 120),
+    ]);
+    assert_eq!(summary.view_secs, 720);
+    assert_eq!(summary.run_secs, 45);
+    assert_eq!(summary.runs, 1);
+    assert_eq!(summary.days.len(), 2);
+    assert_eq!(summary.days[0].date, "2026-03-01");
+    assert_eq!(summary.days[0].run_secs, 45);
+    assert_eq!(summary.days[1].view_secs, 120);
+  }
+}
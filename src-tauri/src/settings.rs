@@ -0,0 +1,464 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::diagnostics::is_sensitive_setting;
+use crate::encryption;
+use crate::repo_tools::stats::DEFAULT_LOOP_THRESHOLD;
+use crate::sync;
+
+pub type SettingsMap = Arc<HashMap<String, String>>;
+
+/// Cached copy of the settings table, kept in Tauri managed state.
+///
+/// Readers share one snapshot until a write invalidates it. The generation
+/// counter stops a load that raced with an invalidation from caching stale values.
+#[derive(Default)]
+pub struct SettingsCache {
+  snapshot: RwLock<Option<SettingsMap>>,
+  generation: AtomicU64,
+}
+
+impl SettingsCache {
+  pub async fn get(&self, app: &AppHandle) -> Result<SettingsMap, String> {
+    if let Some(cached) = self.snapshot.read().map_err(|e| e.to_string())?.as_ref() {
+      return Ok(cached.clone());
+    }
+
+    let generation = self.generation.load(Ordering::Acquire);
+    let handle = app.clone();
+    let loaded = Arc::new(
+      tauri::async_runtime::spawn_blocking(move || load_settings(&handle))
+        .await
+        .map_err(|e| e.to_string())??
+    );
+
+    let mut snapshot = self.snapshot.write().map_err(|e| e.to_string())?;
+    if self.generation.load(Ordering::Acquire) == generation {
+      *snapshot = Some(loaded.clone());
+    }
+    Ok(loaded)
+  }
+
+  pub fn invalidate(&self) {
+    self.generation.fetch_add(1, Ordering::AcqRel);
+    if let Ok(mut snapshot) = self.snapshot.write() {
+      *snapshot = None;
+    }
+  }
+}
+
+/// Settings snapshot from the managed cache
+pub async fn cached_settings(app: &AppHandle) -> Result<SettingsMap, String> {
+  app.state::<SettingsCache>().get(app).await
+}
+
+pub fn invalidate_settings(app: &AppHandle) {
+  app.state::<SettingsCache>().invalidate();
+}
+
+fn load_settings(app: &AppHandle) -> Result<HashMap<String, String>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare("SELECT key, value FROM settings")
+    .map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], |r| {
+    Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
+  }).map_err(|e| e.to_string())?;
+
+  let mut settings = HashMap::new();
+  for row in rows {
+    let (k, v) = row.map_err(|e| e.to_string())?;
+    settings.insert(k, v);
+  }
+  Ok(settings)
+}
+
+/// How a typed setting's stored string is read, with its default and bounds
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+  Text(&'static str),
+  /// Empty, or an http(s) URL
+  Url(&'static str),
+  /// Stored as "1" or "0"
+  Flag(bool),
+  Int { default: i64, min: i64, max: i64 },
+  Float { default: f64, min: f64, max: f64 },
+}
+
+/// Settings with a fixed type, by key; each is a field of `AppSettings`
+const TYPED_SETTINGS: &[(&str, Kind)] = &[
+  ("provider_name", Kind::Text("")),
+  ("base_url", Kind::Url("")),
+  ("model", Kind::Text("")),
+  ("temperature", Kind::Float { default: 0.2, min: 0.0, max: 2.0 }),
+  ("max_tokens", Kind::Int { default: 4000, min: 1, max: 1_000_000 }),
+  ("stream_responses", Kind::Flag(true)),
+  ("feedback_in_prompt", Kind::Flag(true)),
+  ("prompt_template_strict", Kind::Flag(true)),
+  ("verify_cache", Kind::Flag(true)),
+  ("reuse_check_results", Kind::Flag(true)),
+  ("plan_debate", Kind::Flag(false)),
+  ("tool_loop_threshold", Kind::Int { default: DEFAULT_LOOP_THRESHOLD as i64, min: 2, max: 50 }),
+  ("tool_loop_abort", Kind::Flag(false)),
+  // 0 turns a tool limit off
+  ("tool_max_calls_per_run", Kind::Int { default: 60, min: 0, max: 1000 }),
+  ("tool_max_calls_per_minute", Kind::Int { default: 30, min: 0, max: 600 }),
+  ("tool_max_commands_per_run", Kind::Int { default: 5, min: 0, max: 100 }),
+  ("tool_max_bytes_per_run", Kind::Int { default: 2_000_000, min: 0, max: 100_000_000 }),
+  ("retrieval_top_k", Kind::Int { default: 8, min: 1, max: 32 }),
+  ("lsp_timeout_secs", Kind::Int { default: 60, min: 1, max: 600 }),
+  // 0 turns scheduled refreshes off
+  ("repo_map_refresh_hours", Kind::Int { default: 0, min: 0, max: 720 }),
+  ("telemetry_enabled", Kind::Flag(false)),
+  ("telemetry_endpoint", Kind::Url("")),
+  // Where in-app feedback goes; without an endpoint it opens an issue on the repo
+  ("feedback_endpoint", Kind::Url("")),
+  ("feedback_issue_repo", Kind::Text("ThomasRogersF/spectrail")),
+];
+
+/// The settings with a fixed type, read from the settings table's strings.
+/// Missing and invalid values take the default; `TypedSettings` says which were invalid.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AppSettings {
+  pub provider_name: String,
+  pub base_url: String,
+  pub model: String,
+  pub temperature: f64,
+  pub max_tokens: i64,
+  pub stream_responses: bool,
+  pub feedback_in_prompt: bool,
+  pub prompt_template_strict: bool,
+  pub verify_cache: bool,
+  pub reuse_check_results: bool,
+  pub plan_debate: bool,
+  pub tool_loop_threshold: usize,
+  pub tool_loop_abort: bool,
+  pub tool_max_calls_per_run: usize,
+  pub tool_max_calls_per_minute: usize,
+  pub tool_max_commands_per_run: usize,
+  pub tool_max_bytes_per_run: usize,
+  pub retrieval_top_k: usize,
+  pub lsp_timeout_secs: u64,
+  pub repo_map_refresh_hours: u64,
+  pub telemetry_enabled: bool,
+  pub telemetry_endpoint: String,
+  pub feedback_endpoint: String,
+  pub feedback_issue_repo: String,
+}
+
+impl AppSettings {
+  pub fn from_map(settings: &HashMap<String, String>) -> Self {
+    typed_settings(settings).settings
+  }
+}
+
+impl Default for AppSettings {
+  fn default() -> Self {
+    Self::from_map(&HashMap::new())
+  }
+}
+
+/// A stored value the typed settings couldn't use
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SettingIssue {
+  pub key: String,
+  pub value: String,
+  pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TypedSettings {
+  pub settings: AppSettings,
+  pub issues: Vec<SettingIssue>,
+}
+
+fn default_value(kind: Kind) -> Value {
+  match kind {
+    Kind::Text(default) | Kind::Url(default) => default.into(),
+    Kind::Flag(default) => default.into(),
+    Kind::Int { default, .. } => default.into(),
+    Kind::Float { default, .. } => default.into(),
+  }
+}
+
+fn parse_value(key: &str, kind: Kind, raw: &str) -> Result<Value, String> {
+  let value = raw.trim();
+  match kind {
+    Kind::Text(_) => Ok(value.into()),
+    Kind::Url(_) => match value.is_empty() || value.starts_with("http://") || value.starts_with("https://") {
+      true => Ok(value.into()),
+      false => Err(format!("{} must be an http(s) URL, got '{}'", key, value)),
+    },
+    Kind::Flag(_) => match value {
+      "1" => Ok(true.into()),
+      "0" => Ok(false.into()),
+      _ => Err(format!("{} must be 1 or 0, got '{}'", key, value)),
+    },
+    Kind::Int { min, max, .. } => {
+      let n: i64 = value.parse().map_err(|_| format!("{} must be a whole number, got '{}'", key, value))?;
+      match (min..=max).contains(&n) {
+        true => Ok(n.into()),
+        false => Err(format!("{} must be between {} and {}, got {}", key, min, max, n)),
+      }
+    }
+    Kind::Float { min, max, .. } => {
+      let n: f64 = value.parse().map_err(|_| format!("{} must be a number, got '{}'", key, value))?;
+      match (min..=max).contains(&n) {
+        true => Ok(n.into()),
+        false => Err(format!("{} must be between {} and {}, got {}", key, min, max, n)),
+      }
+    }
+  }
+}
+
+/// Check a value before it's stored; keys without a fixed type accept anything
+pub fn validate_setting(key: &str, value: &str) -> Result<(), String> {
+  match TYPED_SETTINGS.iter().find(|(k, _)| *k == key) {
+    Some((key, kind)) => parse_value(key, *kind, value).map(|_| ()),
+    None => Ok(()),
+  }
+}
+
+pub fn typed_settings(settings: &HashMap<String, String>) -> TypedSettings {
+  let mut fields = Map::new();
+  let mut issues = vec![];
+  for (key, kind) in TYPED_SETTINGS {
+    let value = match settings.get(*key) {
+      Some(raw) => parse_value(key, *kind, raw).unwrap_or_else(|error| {
+        issues.push(SettingIssue { key: key.to_string(), value: raw.clone(), error });
+        default_value(*kind)
+      }),
+      None => default_value(*kind),
+    };
+    fields.insert(key.to_string(), value);
+  }
+  let settings = serde_json::from_value(Value::Object(fields))
+    .expect("TYPED_SETTINGS lists every AppSettings field with a value of its type");
+  TypedSettings { settings, issues }
+}
+
+/// Bring stored numbers that are out of bounds back within them, so a value
+/// saved before settings were validated keeps its intent instead of turning
+/// into the default. Values that don't parse at all are left for the user to
+/// fix; `get_settings_typed` reports them.
+pub fn clamp_stored_values(conn: &Connection) -> rusqlite::Result<usize> {
+  let mut clamped = 0;
+  for (key, kind) in TYPED_SETTINGS {
+    let stored: Option<String> = match conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |r| r.get(0)) {
+      Ok(value) => Some(value),
+      Err(rusqlite::Error::QueryReturnedNoRows) => None,
+      Err(e) => return Err(e),
+    };
+    let Some(value) = stored.and_then(|v| clamp_value(*kind, v.trim())) else {
+      continue;
+    };
+    tracing::info!(key = *key, value = %value, "clamped setting into its bounds");
+    conn.execute("UPDATE settings SET value = ?1 WHERE key = ?2", (&value, key))?;
+    clamped += 1;
+  }
+  Ok(clamped)
+}
+
+/// The in-bounds value for an out-of-bounds number; None when it's in bounds or not a number
+fn clamp_value(kind: Kind, value: &str) -> Option<String> {
+  match kind {
+    Kind::Int { min, max, .. } => value.parse::<i64>().ok()
+      .filter(|n| !(min..=max).contains(n))
+      .map(|n| n.clamp(min, max).to_string()),
+    Kind::Float { min, max, .. } => value.parse::<f64>().ok()
+      .filter(|n| n.is_finite() && !(min..=max).contains(n))
+      .map(|n| n.clamp(min, max).to_string()),
+    _ => None,
+  }
+}
+
+/// Version of the settings file written by `export_settings`
+const SETTINGS_FILE_VERSION: u32 = 1;
+/// Prefix of environment variables that set a setting on first launch,
+/// e.g. `SPECTRAIL_SETTING_MODEL=gpt-4o`
+const ENV_PREFIX: &str = "SPECTRAIL_SETTING_";
+
+/// Settings as a file, for moving them between machines
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsFile {
+  version: u32,
+  #[serde(default)]
+  exported_at: Option<String>,
+  settings: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct SettingsExport {
+  pub path: String,
+  pub exported: usize,
+  /// Credentials and settings changed through their own command, left out
+  pub excluded: Vec<String>,
+}
+
+/// Settings changed through their own command rather than by storing a value
+fn is_managed(key: &str) -> bool {
+  key == encryption::SETTING || key == sync::REMOTE_SETTING
+}
+
+/// Write the global settings to `path`. Credentials are left out; they're
+/// entered again on the other machine.
+pub fn export_settings(app: &AppHandle, path: &str) -> Result<SettingsExport, String> {
+  let mut settings = load_settings(app)?;
+  let mut excluded: Vec<String> = settings.keys()
+    .filter(|key| is_sensitive_setting(key) || is_managed(key))
+    .cloned()
+    .collect();
+  excluded.sort();
+  settings.retain(|key, _| !excluded.contains(key));
+
+  let file = SettingsFile { version: SETTINGS_FILE_VERSION, exported_at: Some(now_iso()), settings };
+  let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+  std::fs::write(path, json).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+  Ok(SettingsExport { path: path.to_string(), exported: file.settings.len(), excluded })
+}
+
+/// Settings from a file written by `export_settings`, sorted by key. Managed
+/// settings are refused; the caller validates and stores the rest.
+pub fn read_settings_file(path: &str) -> Result<Vec<(String, String)>, String> {
+  let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+  let file: SettingsFile = serde_json::from_str(&json).map_err(|e| format!("{} is not a settings file: {}", path, e))?;
+  if file.version > SETTINGS_FILE_VERSION {
+    return Err(format!("{} is from a newer version of the app (settings file version {})", path, file.version));
+  }
+  if let Some(key) = file.settings.keys().find(|key| is_managed(key)) {
+    return Err(format!("{} can't be imported; use its own setting command", key));
+  }
+  let mut pairs: Vec<(String, String)> = file.settings.into_iter().collect();
+  pairs.sort();
+  Ok(pairs)
+}
+
+/// Settings to apply on first launch, from the settings file in app data (or
+/// the one `SPECTRAIL_SETTINGS_FILE` names) overlaid with `SPECTRAIL_SETTING_*`
+/// environment variables, so CI and new machines can be set up without the UI
+fn bootstrap_values(file: Option<&str>, env: impl Iterator<Item = (String, String)>) -> Result<Vec<(String, String)>, String> {
+  let mut values: HashMap<String, String> = match file {
+    Some(path) => read_settings_file(path)?.into_iter().collect(),
+    None => HashMap::new(),
+  };
+  for (name, value) in env {
+    if let Some(key) = name.strip_prefix(ENV_PREFIX).filter(|key| !key.is_empty()) {
+      values.insert(key.to_lowercase(), value);
+    }
+  }
+  let mut pairs: Vec<(String, String)> = values.into_iter().collect();
+  pairs.sort();
+  for (key, value) in &pairs {
+    if is_managed(key) {
+      return Err(format!("{} can't be set on first launch; use its own setting command", key));
+    }
+    validate_setting(key, value)?;
+  }
+  Ok(pairs)
+}
+
+/// Apply the bootstrap settings to a database that was just created
+pub fn bootstrap(app: &AppHandle, conn: &Connection) -> Result<usize, String> {
+  let file = match std::env::var("SPECTRAIL_SETTINGS_FILE") {
+    Ok(path) => Some(path),
+    Err(_) => {
+      let path = app.path().app_data_dir().map_err(|e| e.to_string())?.join("settings.json");
+      path.is_file().then(|| path.to_string_lossy().to_string())
+    }
+  };
+  let pairs = bootstrap_values(file.as_deref(), std::env::vars())?;
+  let updated_at = now_iso();
+  for (key, value) in &pairs {
+    conn.execute(
+      "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+       ON CONFLICT(key) DO UPDATE SET value=excluded.value, updated_at=excluded.updated_at",
+      (key, value, &updated_at)
+    ).map_err(|e| e.to_string())?;
+  }
+  Ok(pairs.len())
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339)
+    .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_typed_settings() {
+    let defaults = AppSettings::default();
+    assert_eq!(defaults.temperature, 0.2);
+    assert_eq!(defaults.tool_loop_threshold, DEFAULT_LOOP_THRESHOLD);
+    assert!(defaults.stream_responses);
+
+    let stored: HashMap<String, String> = [
+      ("temperature", "0.7 "),
+      ("max_tokens", "lots"),
+      ("stream_responses", "0"),
+      ("plan_debate", "yes"),
+      ("retrieval_top_k", "100"),
+      ("unrelated", "anything"),
+    ].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let typed = typed_settings(&stored);
+    assert_eq!(typed.settings.temperature, 0.7);
+    assert_eq!(typed.settings.max_tokens, 4000);
+    assert!(!typed.settings.stream_responses);
+    assert!(!typed.settings.plan_debate);
+    assert_eq!(typed.settings.retrieval_top_k, 8);
+    let invalid: Vec<&str> = typed.issues.iter().map(|i| i.key.as_str()).collect();
+    assert_eq!(invalid, vec!["max_tokens", "plan_debate", "retrieval_top_k"]);
+
+    assert!(validate_setting("temperature", "3").unwrap_err().contains("between 0 and 2"));
+    assert!(validate_setting("base_url", "api.example.com").is_err());
+    assert!(validate_setting("unrelated", "anything").is_ok());
+    assert_eq!(clamp_value(Kind::Float { default: 0.2, min: 0.0, max: 2.0 }, "3.5"), Some("2".to_string()));
+    assert_eq!(clamp_value(Kind::Int { default: 8, min: 1, max: 32 }, "16"), None);
+  }
+
+  #[test]
+  fn test_clamp_stored_values() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at TEXT NOT NULL);
+       INSERT INTO settings VALUES ('retrieval_top_k', '100', ''), ('max_tokens', 'lots', ''), ('temperature', '0.5', '');"
+    ).unwrap();
+    assert_eq!(clamp_stored_values(&conn).unwrap(), 1);
+    let top_k: String = conn.query_row("SELECT value FROM settings WHERE key = 'retrieval_top_k'", [], |r| r.get(0)).unwrap();
+    assert_eq!(top_k, "32");
+  }
+
+  #[test]
+  fn test_bootstrap_values() {
+    let path = std::env::temp_dir().join(format!("spectrail-settings-{}.json", crate::models::new_id()));
+    std::fs::write(&path, r#"{"version": 1, "settings": {"model": "from-file", "temperature": "0.5"}}"#).unwrap();
+    let file = path.to_string_lossy().to_string();
+
+    let env = [
+      ("SPECTRAIL_SETTING_MODEL", "from-env"),
+      ("SPECTRAIL_SETTING_API_KEY", "sk-ci"),
+      ("HOME", "/root"),
+    ].into_iter().map(|(k, v)| (k.to_string(), v.to_string()));
+    let pairs = bootstrap_values(Some(&file), env).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(pairs, vec![
+      ("api_key".to_string(), "sk-ci".to_string()),
+      ("model".to_string(), "from-env".to_string()),
+      ("temperature".to_string(), "0.5".to_string()),
+    ]);
+
+    let invalid = [("SPECTRAIL_SETTING_TEMPERATURE".to_string(), "9".to_string())].into_iter();
+    assert!(bootstrap_values(None, invalid).is_err());
+    let managed = [("SPECTRAIL_SETTING_DB_ENCRYPTION".to_string(), "1".to_string())].into_iter();
+    assert!(bootstrap_values(None, managed).is_err());
+  }
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::commands::{get_project, get_task};
+use crate::export::latest_artifact;
+use crate::render::numbered_section;
+use crate::settings::cached_settings;
+use crate::workflows::verify::{parse_verdict, Verdict};
+
+const POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Slack truncates long messages; stay well under its 4000 character guidance
+const MAX_MESSAGE_CHARS: usize = 3500;
+const MAX_RISK_CHARS: usize = 1500;
+
+#[derive(Debug, Serialize, Type)]
+pub struct SlackPostResult {
+  /// "bot" (threaded, via chat.postMessage) or "webhook" (single message)
+  pub via: String,
+  pub channel: Option<String>,
+  /// Timestamp of the parent message, which identifies the thread
+  pub ts: Option<String>,
+  pub thread_messages: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostMessageResponse {
+  ok: bool,
+  ts: Option<String>,
+  channel: Option<String>,
+  error: Option<String>,
+}
+
+/// Rank for regression checks; unknown verdicts never count as a regression
+fn verdict_rank(verdict: Verdict) -> Option<u8> {
+  match verdict {
+    Verdict::Matches => Some(3),
+    Verdict::PartiallyMatches => Some(2),
+    Verdict::DoesNotMatch => Some(1),
+    Verdict::Unknown => None,
+  }
+}
+
+pub fn is_regression(previous: Verdict, current: Verdict) -> bool {
+  matches!((verdict_rank(previous), verdict_rank(current)), (Some(p), Some(c)) if c < p)
+}
+
+fn verdict_emoji(verdict: Verdict) -> &'static str {
+  match verdict {
+    Verdict::Matches => ":white_check_mark:",
+    Verdict::PartiallyMatches => ":warning:",
+    Verdict::DoesNotMatch => ":x:",
+    Verdict::Unknown => ":grey_question:",
+  }
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+  if text.chars().count() <= max {
+    return text.to_string();
+  }
+  let mut out: String = text.chars().take(max).collect();
+  out.push_str("\n…");
+  out
+}
+
+/// Split on line boundaries into messages of at most `max` characters
+fn chunk_lines(text: &str, max: usize) -> Vec<String> {
+  let mut chunks = vec![];
+  let mut current = String::new();
+  for line in text.lines() {
+    if !current.is_empty() && current.chars().count() + line.chars().count() + 1 > max {
+      chunks.push(std::mem::take(&mut current));
+    }
+    // A single overlong line is cut rather than dropped
+    current.push_str(&truncate_chars(line, max));
+    current.push('\n');
+  }
+  if !current.trim().is_empty() {
+    chunks.push(current);
+  }
+  chunks
+}
+
+fn summary_text(task_title: &str, project_name: &str, report_md: &str, note: Option<&str>) -> String {
+  let verdict = parse_verdict(report_md);
+  let mut text = format!(
+    "*Verification: {}*\n{} *{}* · {}",
+    task_title, verdict_emoji(verdict), verdict.label(), project_name
+  );
+  if let Some(note) = note {
+    text.push_str(&format!("\n_{}_", note));
+  }
+  // Risk tables don't render in Slack, so keep the section monospaced
+  if let Some(risks) = numbered_section(report_md, 4) {
+    text.push_str(&format!("\n\n*Risks*\n```{}```", truncate_chars(&risks, MAX_RISK_CHARS)));
+  }
+  text
+}
+
+/// Post the task's latest verification report. With a bot token the summary
+/// goes to `channel` and the full report follows in its thread; with only a
+/// webhook, a single summary message goes to the webhook's channel.
+pub async fn post_report(app: &AppHandle, task_id: &str, channel: Option<&str>, note: Option<&str>) -> Result<SlackPostResult, String> {
+  let settings = cached_settings(app).await?;
+  let get = |key: &str| settings.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+  let task = get_task(app.clone(), task_id.to_string())?;
+  let project = get_project(app.clone(), task.project_id.clone())?;
+  let report = latest_artifact(app, task_id, "verification_report")?;
+  let summary = summary_text(&task.title, &project.name, &report.content, note);
+
+  let client = reqwest::Client::builder()
+    .timeout(REQUEST_TIMEOUT)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+  if let Some(token) = get("slack_bot_token") {
+    let channel = channel.map(|c| c.trim().to_string()).filter(|c| !c.is_empty())
+      .or_else(|| get("slack_default_channel"))
+      .ok_or("No Slack channel given and slack_default_channel is not set")?;
+
+    let parent = post_message(&client, &token, json!({ "channel": channel, "text": summary })).await?;
+    let ts = parent.ts.ok_or("Slack did not return a message timestamp")?;
+    let thread_channel = parent.channel.unwrap_or(channel);
+
+    let chunks = chunk_lines(&report.content, MAX_MESSAGE_CHARS);
+    for chunk in &chunks {
+      post_message(&client, &token, json!({
+        "channel": thread_channel,
+        "thread_ts": ts,
+        "text": chunk,
+      })).await?;
+    }
+
+    tracing::info!(task_id, channel = %thread_channel, "posted report to slack");
+    return Ok(SlackPostResult {
+      via: "bot".into(),
+      channel: Some(thread_channel),
+      ts: Some(ts),
+      thread_messages: chunks.len(),
+    });
+  }
+
+  let webhook = get("slack_webhook_url").ok_or("Slack is not configured; set a bot token or webhook URL in Settings")?;
+  let response = client.post(&webhook)
+    .json(&json!({ "text": summary }))
+    .send()
+    .await
+    .map_err(|e| format!("Slack webhook request failed: {}", e))?;
+  if !response.status().is_success() {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    return Err(format!("Slack webhook returned {}: {}", status, body));
+  }
+
+  tracing::info!(task_id, "posted report to slack webhook");
+  Ok(SlackPostResult { via: "webhook".into(), channel: None, ts: None, thread_messages: 0 })
+}
+
+async fn post_message(client: &reqwest::Client, token: &str, body: serde_json::Value) -> Result<PostMessageResponse, String> {
+  let response: PostMessageResponse = client.post(POST_MESSAGE_URL)
+    .bearer_auth(token)
+    .json(&body)
+    .send()
+    .await
+    .map_err(|e| format!("Slack request failed: {}", e))?
+    .json()
+    .await
+    .map_err(|e| format!("Unexpected Slack response: {}", e))?;
+  if !response.ok {
+    return Err(format!("Slack error: {}", response.error.as_deref().unwrap_or("unknown")));
+  }
+  Ok(response)
+}
+
+/// Called after a verification; posts in the background when the verdict got
+/// worse and `slack_auto_post_regressions` is enabled
+pub async fn notify_regression(app: &AppHandle, task_id: &str, previous: Verdict, current: Verdict) {
+  if !is_regression(previous, current) {
+    return;
+  }
+  let enabled = match cached_settings(app).await {
+    Ok(settings) => settings.get("slack_auto_post_regressions").is_some_and(|v| v == "1"),
+    Err(_) => false,
+  };
+  if !enabled {
+    return;
+  }
+
+  let app = app.clone();
+  let task_id = task_id.to_string();
+  let note = format!("Regressed from {}", previous.label());
+  tauri::async_runtime::spawn(async move {
+    if let Err(e) = post_report(&app, &task_id, None, Some(&note)).await {
+      tracing::warn!(task_id = %task_id, "automatic slack post failed: {}", e);
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_regression() {
+    assert!(is_regression(Verdict::Matches, Verdict::PartiallyMatches));
+    assert!(is_regression(Verdict::PartiallyMatches, Verdict::DoesNotMatch));
+    assert!(!is_regression(Verdict::DoesNotMatch, Verdict::Matches));
+    assert!(!is_regression(Verdict::Matches, Verdict::Matches));
+    assert!(!is_regression(Verdict::Matches, Verdict::Unknown));
+  }
+
+  #[test]
+  fn test_risk_section_and_chunks() {
+    let md = "## 1. Verdict\nMatches\n\n## 4. Risk Review\n| Risk | Severity |\n| a | Low |\n\n## 5. Tests\nok";
+    assert_eq!(numbered_section(md, 4).unwrap(), "| Risk | Severity |\n| a | Low |");
+    assert!(numbered_section(md, 7).is_none());
+
+    let chunks = chunk_lines("aaaa\nbbbb\ncccc", 10);
+    assert_eq!(chunks, vec!["aaaa\nbbbb\n", "cccc\n"]);
+  }
+}
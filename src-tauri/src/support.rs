@@ -0,0 +1,224 @@
+use reqwest::multipart::{Form, Part};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::diagnostics::export_diagnostics;
+use crate::settings::{cached_settings, AppSettings};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_FEEDBACK_CHARS: usize = 20_000;
+/// Kept well under the URL length GitHub accepts for a pre-filled issue
+const MAX_ISSUE_BODY_CHARS: usize = 4_000;
+const MAX_TITLE_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackKind {
+  Bug,
+  Idea,
+  Question,
+}
+
+impl FeedbackKind {
+  fn as_str(self) -> &'static str {
+    match self {
+      FeedbackKind::Bug => "bug",
+      FeedbackKind::Idea => "idea",
+      FeedbackKind::Question => "question",
+    }
+  }
+
+  /// GitHub's default label for the kind
+  fn issue_label(self) -> &'static str {
+    match self {
+      FeedbackKind::Bug => "bug",
+      FeedbackKind::Idea => "enhancement",
+      FeedbackKind::Question => "question",
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct FeedbackSubmission {
+  /// Whether the report was posted to `feedback_endpoint`
+  pub sent: bool,
+  /// The pre-filled issue opened in the browser when no endpoint is set
+  pub issue_url: Option<String>,
+  /// The diagnostics bundle written for the report; an issue can't carry it,
+  /// so the user attaches it by hand
+  pub diagnostics_path: Option<String>,
+}
+
+/// Send feedback to `feedback_endpoint` as a multipart form (`report` JSON plus
+/// the `diagnostics` zip), or, without an endpoint, open a pre-filled issue on
+/// `feedback_issue_repo`. Diagnostics are the same redacted bundle as
+/// `export_diagnostics`.
+pub async fn submit_feedback(
+  app: &AppHandle,
+  kind: FeedbackKind,
+  text: &str,
+  include_diagnostics: bool,
+) -> Result<FeedbackSubmission, String> {
+  let text = text.trim();
+  if text.is_empty() {
+    return Err("Describe the problem or idea before sending".into());
+  }
+  if text.chars().count() > MAX_FEEDBACK_CHARS {
+    return Err(format!("Feedback is limited to {} characters", MAX_FEEDBACK_CHARS));
+  }
+
+  let settings = AppSettings::from_map(&*cached_settings(app).await?);
+  let version = app.package_info().version.to_string();
+  let bundle = match include_diagnostics {
+    true => Some(export_diagnostics(app, None)?),
+    false => None,
+  };
+  let bundle_path = bundle.as_ref().map(|b| Path::new(&b.path));
+
+  let endpoint = settings.feedback_endpoint.trim();
+  if !endpoint.is_empty() {
+    post_feedback(endpoint, kind, text, &version, bundle_path).await?;
+    tracing::info!(kind = kind.as_str(), diagnostics = include_diagnostics, "sent feedback");
+    return Ok(FeedbackSubmission { sent: true, issue_url: None, diagnostics_path: bundle.map(|b| b.path) });
+  }
+
+  let attachment = bundle_path.and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string());
+  let url = issue_url(&settings.feedback_issue_repo, kind, text, &version, attachment.as_deref())?;
+  open_in_browser(&url)?;
+  tracing::info!(kind = kind.as_str(), diagnostics = include_diagnostics, "opened feedback issue");
+  Ok(FeedbackSubmission { sent: false, issue_url: Some(url), diagnostics_path: bundle.map(|b| b.path) })
+}
+
+async fn post_feedback(
+  endpoint: &str,
+  kind: FeedbackKind,
+  text: &str,
+  version: &str,
+  diagnostics: Option<&Path>,
+) -> Result<(), String> {
+  let report = json!({
+    "kind": kind.as_str(),
+    "text": text,
+    "app_version": version,
+    "os": std::env::consts::OS,
+    "arch": std::env::consts::ARCH,
+  });
+  let mut form = Form::new().text("report", report.to_string());
+  if let Some(path) = diagnostics {
+    let content = std::fs::read(path).map_err(|e| e.to_string())?;
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let part = Part::bytes(content).file_name(file_name).mime_str("application/zip")
+      .map_err(|e| e.to_string())?;
+    form = form.part("diagnostics", part);
+  }
+
+  let client = reqwest::Client::builder()
+    .timeout(REQUEST_TIMEOUT)
+    .build()
+    .map_err(|e| e.to_string())?;
+  let response = client.post(endpoint)
+    .multipart(form)
+    .send()
+    .await
+    .map_err(|e| format!("Sending feedback failed: {}", e))?;
+  if !response.status().is_success() {
+    return Err(format!("Feedback endpoint returned {}", response.status()));
+  }
+  Ok(())
+}
+
+/// A new-issue link with the title, body and label filled in. Long feedback
+/// is cut to fit in the URL.
+fn issue_url(repo: &str, kind: FeedbackKind, text: &str, version: &str, attachment: Option<&str>) -> Result<String, String> {
+  let repo = repo.trim().trim_matches('/');
+  let valid = repo.split('/').count() == 2 && repo.split('/').all(|part| {
+    !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+  });
+  if !valid {
+    return Err(format!("feedback_issue_repo must look like owner/name, got '{}'", repo));
+  }
+
+  let first_line = text.lines().next().unwrap_or_default().trim();
+  let mut title: String = first_line.chars().take(MAX_TITLE_CHARS).collect();
+  if first_line.chars().count() > MAX_TITLE_CHARS {
+    title.push('…');
+  }
+
+  let mut body: String = text.chars().take(MAX_ISSUE_BODY_CHARS).collect();
+  if text.chars().count() > MAX_ISSUE_BODY_CHARS {
+    body.push_str("\n\n_(Cut short to fit in the link.)_");
+  }
+  body.push_str(&format!(
+    "\n\n---\nSpecTrail {} on {} ({})",
+    version, std::env::consts::OS, std::env::consts::ARCH
+  ));
+  if let Some(file_name) = attachment {
+    body.push_str(&format!("\n\nDiagnostics (settings redacted) were saved as `{}`; attach it here.", file_name));
+  }
+
+  let url = Url::parse_with_params(
+    &format!("https://github.com/{}/issues/new", repo),
+    &[("title", title.as_str()), ("body", body.as_str()), ("labels", kind.issue_label())],
+  ).map_err(|e| e.to_string())?;
+  Ok(url.to_string())
+}
+
+fn open_in_browser(url: &str) -> Result<(), String> {
+  let (program, args): (&str, Vec<&str>) = if cfg!(target_os = "macos") {
+    ("open", vec![url])
+  } else if cfg!(target_os = "windows") {
+    // `start` would need shell quoting for the query string's `&`s
+    ("rundll32", vec!["url.dll,FileProtocolHandler", url])
+  } else {
+    ("xdg-open", vec![url])
+  };
+  Command::new(program)
+    .args(args)
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| format!("Cannot open the browser ({}); the issue link is {}", e, url))?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_issue_url() {
+    let url = issue_url(
+      "ThomasRogersF/spectrail",
+      FeedbackKind::Bug,
+      "Plan run hangs & never ends\nSteps: start a plan",
+      "1.2.3",
+      Some("spectrail-diagnostics-20240501-120000.zip"),
+    ).unwrap();
+    let parsed = Url::parse(&url).unwrap();
+    assert_eq!(parsed.path(), "/ThomasRogersF/spectrail/issues/new");
+    let params: std::collections::HashMap<String, String> = parsed.query_pairs().into_owned().collect();
+    assert_eq!(params["title"], "Plan run hangs & never ends");
+    assert_eq!(params["labels"], "bug");
+    assert!(params["body"].starts_with("Plan run hangs & never ends\nSteps: start a plan\n\n---\nSpecTrail 1.2.3"));
+    assert!(params["body"].contains("`spectrail-diagnostics-20240501-120000.zip`"));
+
+    let long = "x".repeat(MAX_ISSUE_BODY_CHARS + 10);
+    let url = issue_url("acme/app", FeedbackKind::Idea, &long, "1.0.0", None).unwrap();
+    let params: std::collections::HashMap<String, String> = Url::parse(&url).unwrap().query_pairs().into_owned().collect();
+    assert_eq!(params["title"].chars().count(), MAX_TITLE_CHARS + 1);
+    assert!(params["body"].contains("Cut short"));
+    assert!(!params["body"].contains("Diagnostics"));
+    assert_eq!(params["labels"], "enhancement");
+
+    for repo in ["", "acme", "acme/app/extra", "acme/ap p", "https://github.com/acme/app"] {
+      assert!(issue_url(repo, FeedbackKind::Bug, "x", "1.0.0", None).is_err(), "{}", repo);
+    }
+  }
+}
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use crate::repo_tools::safety::safe_spawn;
+
+const GIT_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_BRANCH: &str = "main";
+
+/// A local clone of the sync repository under the app data directory
+pub struct GitRemote {
+    dir: PathBuf,
+    branch: String,
+}
+
+async fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let (stdout, stderr, code) = safe_spawn("git", args, dir, GIT_TIMEOUT_SECS)
+        .await
+        .map_err(|e| e.to_string())?;
+    if code != 0 {
+        return Err(format!("git {} failed: {}", args.first().unwrap_or(&""), stderr.trim()));
+    }
+    Ok(stdout)
+}
+
+impl GitRemote {
+    /// Bring the clone in `dir` up to date with `url`, starting a fresh one
+    /// if it doesn't exist yet or points at a different repository
+    pub async fn open(dir: &Path, url: &str, branch: Option<&str>) -> Result<Self, String> {
+        let branch = branch.map(str::trim).filter(|b| !b.is_empty()).unwrap_or(DEFAULT_BRANCH).to_string();
+        let current_url = match dir.join(".git").exists() {
+            true => git(dir, &["remote", "get-url", "origin"]).await.ok(),
+            false => None,
+        };
+        if current_url.as_deref().map(str::trim) != Some(url) {
+            if dir.exists() {
+                std::fs::remove_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            let head = format!("refs/heads/{}", branch);
+            for args in [
+                vec!["init", "--quiet"],
+                vec!["symbolic-ref", "HEAD", head.as_str()],
+                vec!["remote", "add", "origin", url],
+                vec!["config", "user.name", "SpecTrail"],
+                vec!["config", "user.email", "sync@spectrail.invalid"],
+                vec!["config", "commit.gpgsign", "false"],
+            ] {
+                git(dir, &args).await?;
+            }
+        }
+        let remote = GitRemote { dir: dir.to_path_buf(), branch };
+        remote.pull().await?;
+        Ok(remote)
+    }
+
+    async fn pull(&self) -> Result<(), String> {
+        // A new, empty repository has no branch until the first push
+        let heads = git(&self.dir, &["ls-remote", "--heads", "origin", &self.branch]).await?;
+        if heads.trim().is_empty() {
+            return Ok(());
+        }
+        git(&self.dir, &["pull", "--quiet", "--rebase", "origin", &self.branch]).await?;
+        Ok(())
+    }
+
+    /// Keys of the files under `prefix`, relative to the repository root
+    pub fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let mut keys = Vec::new();
+        let mut pending = vec![self.dir.join(prefix)];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&self.dir) {
+                    let parts: Vec<String> = relative.components()
+                        .map(|c| c.as_os_str().to_string_lossy().to_string())
+                        .collect();
+                    keys.push(parts.join("/"));
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    pub fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        std::fs::read(self.dir.join(key)).map_err(|e| format!("Cannot read {}: {}", key, e))
+    }
+
+    pub fn put(&self, key: &str, body: &[u8]) -> Result<(), String> {
+        let path = self.dir.join(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&path, body).map_err(|e| format!("Cannot write {}: {}", key, e))
+    }
+
+    /// Commit written segments and push them. Each device only adds its own
+    /// files, so a rejected push is resolved by rebasing onto the remote.
+    pub async fn publish(&self, message: &str) -> Result<(), String> {
+        git(&self.dir, &["add", "--all"]).await?;
+        // Unchanged when retrying a segment whose push failed last time
+        if !git(&self.dir, &["status", "--porcelain"]).await?.trim().is_empty() {
+            git(&self.dir, &["commit", "--quiet", "-m", message]).await?;
+        }
+        let target = format!("HEAD:{}", self.branch);
+        if git(&self.dir, &["push", "--quiet", "origin", &target]).await.is_ok() {
+            return Ok(());
+        }
+        self.pull().await?;
+        git(&self.dir, &["push", "--quiet", "origin", &target]).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,905 @@
+pub mod git;
+pub mod s3;
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager};
+
+use crate::blobs;
+use crate::compression::StoredText;
+use crate::db;
+use crate::diagnostics::is_sensitive_setting;
+use crate::integrations::secrets;
+use crate::models::new_id;
+use crate::offline;
+use crate::settings::{cached_settings, invalidate_settings, validate_setting};
+
+/// JSON-encoded `SyncRemote`, or empty when sync is off. Changed only through
+/// `configure`, since turning sync on snapshots the database into the outbox.
+pub const REMOTE_SETTING: &str = "sync_remote";
+/// Keychain entry holding the JSON-encoded `S3Credentials`
+const S3_CREDENTIALS_ENTRY: &str = "sync-s3";
+/// Remote directory holding one append-only change log per device
+const CHANGES_DIR: &str = "changes";
+
+/// Settings that follow the user between devices: preferences about how the
+/// model is asked and answers. Anything else may run commands or send
+/// credentials somewhere on this machine, so it stays local.
+const SYNCED_SETTINGS: &[&str] = &[
+    "model",
+    "temperature",
+    "max_tokens",
+    "stream_responses",
+    "parallel_tool_calls",
+    "tool_schema_profile",
+    "feedback_in_prompt",
+    "prompt_template_strict",
+    "prompt_template_plan",
+    "prompt_template_verify",
+    "output_language",
+    "planning_strategy",
+    "retrieval_top_k",
+    "plan_debate",
+    "verify_cache",
+    "reuse_check_results",
+    "tool_loop_threshold",
+    "tool_loop_abort",
+    "tool_max_calls_per_run",
+    "tool_max_calls_per_minute",
+    "tool_max_commands_per_run",
+    "tool_max_bytes_per_run",
+    "bench_regression_pct",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncRemote {
+    Git {
+        url: String,
+        /// Defaults to "main"
+        #[serde(default)]
+        branch: Option<String>,
+    },
+    S3 {
+        bucket: String,
+        region: String,
+        /// For S3-compatible services; buckets are then addressed path-style
+        #[serde(default)]
+        endpoint: Option<String>,
+        /// Key prefix, so one bucket can hold other data too
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+}
+
+impl SyncRemote {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            SyncRemote::Git { url, .. } if url.trim().is_empty() => Err("Git sync needs a repository URL".into()),
+            SyncRemote::S3 { bucket, region, .. } if bucket.trim().is_empty() || region.trim().is_empty() => {
+                Err("S3 sync needs a bucket and region".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+/// Synced entity kinds, in the order changes are applied so parents exist
+/// before their children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Entity {
+    Project,
+    Task,
+    Phase,
+    Artifact,
+    Setting,
+}
+
+impl Entity {
+    fn parse(entity: &str) -> Option<Self> {
+        match entity {
+            "project" => Some(Entity::Project),
+            "task" => Some(Entity::Task),
+            "phase" => Some(Entity::Phase),
+            "artifact" => Some(Entity::Artifact),
+            "setting" => Some(Entity::Setting),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Entity::Project => "project",
+            Entity::Task => "task",
+            Entity::Phase => "phase",
+            Entity::Artifact => "artifact",
+            Entity::Setting => "setting",
+        }
+    }
+}
+
+/// One line of a change log segment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Change {
+    pub entity: Entity,
+    /// Row id; artifacts use their `task_id:phase_id:kind` slot and settings their key
+    pub id: String,
+    /// UTC timestamp with milliseconds; the later change wins
+    pub changed_at: String,
+    pub device_id: String,
+    /// Full record, or none when it was deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct ProjectRecord {
+    id: String,
+    name: String,
+    repo_path: String,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct TaskRecord {
+    id: String,
+    project_id: String,
+    title: String,
+    mode: String,
+    status: String,
+    description: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct PhaseRecord {
+    id: String,
+    task_id: String,
+    idx: i64,
+    title: String,
+    status: String,
+    description: Option<String>,
+    planned_files_json: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct ArtifactRecord {
+    id: String,
+    task_id: String,
+    phase_id: Option<String>,
+    kind: String,
+    content: String,
+    pinned: bool,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct SettingRecord {
+    key: String,
+    value: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SyncStatus {
+    pub remote: Option<SyncRemote>,
+    pub device_id: Option<String>,
+    /// Entities changed locally since the last push
+    pub pending: usize,
+    pub last_sync_at: Option<String>,
+    pub last_error: Option<String>,
+    pub running: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Type)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    /// Pulled changes that were newer than the local copy
+    pub applied: usize,
+}
+
+/// Set while a sync is in progress, kept in managed state
+#[derive(Default)]
+pub struct SyncRunning(AtomicBool);
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn syncs_setting(key: &str) -> bool {
+    SYNCED_SETTINGS.contains(&key) && !is_sensitive_setting(key)
+}
+
+fn is_synced(entity: Entity, id: &str) -> bool {
+    entity != Entity::Setting || syncs_setting(id)
+}
+
+fn state_get(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM sync_state WHERE key = ?1", [key], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn state_set(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO sync_state (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        (key, value),
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn cursor_key(device_id: &str) -> String {
+    format!("cursor:{}", device_id)
+}
+
+fn segment_key(device_id: &str, seq: u64) -> String {
+    format!("{}/{}/{:010}.jsonl", CHANGES_DIR, device_id, seq)
+}
+
+/// Device and sequence number of a change log segment key
+fn parse_segment_key(key: &str) -> Option<(String, u64)> {
+    let rest = key.strip_prefix(CHANGES_DIR)?.strip_prefix('/')?;
+    let (device_id, file) = rest.split_once('/')?;
+    let seq = file.strip_suffix(".jsonl")?.parse().ok()?;
+    Some((device_id.to_string(), seq))
+}
+
+fn split_slot(slot: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = slot.splitn(3, ':');
+    Some((parts.next()?, parts.next()?, parts.next()?))
+}
+
+/// Give this database a device id and queue everything already in it, so
+/// the first push carries the full history. Triggers only record changes
+/// once the device id exists.
+fn start(conn: &Connection, device_id: &str) -> Result<(), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    state_set(&tx, "device_id", device_id)?;
+    tx.execute_batch(
+        "INSERT INTO sync_outbox (entity, entity_id, changed_at)
+           SELECT 'project', id, strftime('%Y-%m-%dT%H:%M:%fZ', created_at) FROM projects;
+         INSERT INTO sync_outbox (entity, entity_id, changed_at)
+           SELECT 'task', id, strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) FROM tasks;
+         INSERT INTO sync_outbox (entity, entity_id, changed_at)
+           SELECT 'phase', id, strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) FROM phases;
+         INSERT INTO sync_outbox (entity, entity_id, changed_at)
+           SELECT 'artifact', task_id || ':' || COALESCE(phase_id, '') || ':' || kind, strftime('%Y-%m-%dT%H:%M:%fZ', created_at)
+           FROM artifacts WHERE draft = 0 AND task_id IS NOT NULL;
+         INSERT INTO sync_outbox (entity, entity_id, changed_at)
+           SELECT 'setting', key, strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) FROM settings;"
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Forget the device id, queued changes and cursors
+fn stop(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("DELETE FROM sync_state; DELETE FROM sync_outbox; DELETE FROM sync_versions;")
+        .map_err(|e| e.to_string())
+}
+
+/// Current record for an entity, or none if it no longer exists
+fn load(conn: &Connection, entity: Entity, id: &str) -> Result<Option<Value>, String> {
+    let record = match entity {
+        Entity::Project => conn.query_row(
+            "SELECT id, name, repo_path, created_at FROM projects WHERE id = ?1",
+            [id],
+            |r| Ok(ProjectRecord { id: r.get(0)?, name: r.get(1)?, repo_path: r.get(2)?, created_at: r.get(3)? }),
+        ).optional().map_err(|e| e.to_string())?.map(serde_json::to_value),
+        Entity::Task => conn.query_row(
+            "SELECT id, project_id, title, mode, status, description, created_at, updated_at FROM tasks WHERE id = ?1",
+            [id],
+            |r| Ok(TaskRecord {
+                id: r.get(0)?,
+                project_id: r.get(1)?,
+                title: r.get(2)?,
+                mode: r.get(3)?,
+                status: r.get(4)?,
+                description: r.get(5)?,
+                created_at: r.get(6)?,
+                updated_at: r.get(7)?,
+            }),
+        ).optional().map_err(|e| e.to_string())?.map(serde_json::to_value),
+        Entity::Phase => conn.query_row(
+            "SELECT id, task_id, idx, title, status, description, planned_files_json, created_at, updated_at
+             FROM phases WHERE id = ?1",
+            [id],
+            |r| Ok(PhaseRecord {
+                id: r.get(0)?,
+                task_id: r.get(1)?,
+                idx: r.get(2)?,
+                title: r.get(3)?,
+                status: r.get(4)?,
+                description: r.get(5)?,
+                planned_files_json: r.get(6)?,
+                created_at: r.get(7)?,
+                updated_at: r.get(8)?,
+            }),
+        ).optional().map_err(|e| e.to_string())?.map(serde_json::to_value),
+        Entity::Artifact => {
+            let Some((task_id, phase_id, kind)) = split_slot(id) else {
+                return Ok(None);
+            };
+            let row = conn.query_row(
+                "SELECT id, task_id, phase_id, kind, content, content_hash, pinned, created_at FROM artifacts
+                 WHERE task_id = ?1 AND COALESCE(phase_id, '') = ?2 AND kind = ?3 AND draft = 0
+                 ORDER BY created_at DESC LIMIT 1",
+                (task_id, phase_id, kind),
+                |r| Ok((
+                    ArtifactRecord {
+                        id: r.get(0)?,
+                        task_id: r.get(1)?,
+                        phase_id: r.get(2)?,
+                        kind: r.get(3)?,
+                        content: String::new(),
+                        pinned: r.get::<_, i64>(6)? != 0,
+                        created_at: r.get(7)?,
+                    },
+                    r.get::<_, StoredText>(4)?,
+                    r.get::<_, Option<String>>(5)?,
+                )),
+            ).optional().map_err(|e| e.to_string())?;
+            match row {
+                Some((mut record, inline, hash)) => {
+                    record.content = blobs::hydrate(conn, inline, hash).map_err(|e| e.to_string())?;
+                    Some(serde_json::to_value(record))
+                }
+                None => None,
+            }
+        }
+        Entity::Setting => conn.query_row(
+            "SELECT key, value, updated_at FROM settings WHERE key = ?1",
+            [id],
+            |r| Ok(SettingRecord { key: r.get(0)?, value: r.get(1)?, updated_at: r.get(2)? }),
+        ).optional().map_err(|e| e.to_string())?.map(serde_json::to_value),
+    };
+    record.transpose().map_err(|e| e.to_string())
+}
+
+fn parse_record<T: serde::de::DeserializeOwned>(change: &Change, data: &Value) -> Result<T, String> {
+    serde_json::from_value(data.clone())
+        .map_err(|e| format!("Malformed {} {} in change log: {}", change.entity.as_str(), change.id, e))
+}
+
+/// Write a remote change into the local tables
+fn write(conn: &Connection, change: &Change) -> Result<(), String> {
+    let id = change.id.as_str();
+    let Some(data) = &change.data else {
+        let deleted = match change.entity {
+            Entity::Project => conn.execute("DELETE FROM projects WHERE id = ?1", [id]),
+            Entity::Task => conn.execute("DELETE FROM tasks WHERE id = ?1", [id]),
+            Entity::Phase => conn.execute("DELETE FROM phases WHERE id = ?1", [id]),
+            Entity::Artifact => match split_slot(id) {
+                Some((task_id, phase_id, kind)) => conn.execute(
+                    "DELETE FROM artifacts WHERE task_id = ?1 AND COALESCE(phase_id, '') = ?2 AND kind = ?3 AND draft = 0",
+                    (task_id, phase_id, kind),
+                ),
+                None => Ok(0),
+            },
+            Entity::Setting => conn.execute("DELETE FROM settings WHERE key = ?1", [id]),
+        };
+        return deleted.map(|_| ()).map_err(|e| e.to_string());
+    };
+
+    let written = match change.entity {
+        Entity::Project => {
+            // Checkouts live at different paths on each machine, so only the
+            // name follows later changes
+            let p: ProjectRecord = parse_record(change, data)?;
+            conn.execute(
+                "INSERT INTO projects (id, name, repo_path, created_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(id) DO UPDATE SET name = excluded.name",
+                (&p.id, &p.name, &p.repo_path, &p.created_at),
+            )
+        }
+        Entity::Task => {
+            let t: TaskRecord = parse_record(change, data)?;
+            conn.execute(
+                "INSERT INTO tasks (id, project_id, title, mode, status, description, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT(id) DO UPDATE SET project_id = excluded.project_id, title = excluded.title,
+                   mode = excluded.mode, status = excluded.status, description = excluded.description,
+                   updated_at = excluded.updated_at",
+                (&t.id, &t.project_id, &t.title, &t.mode, &t.status, &t.description, &t.created_at, &t.updated_at),
+            )
+        }
+        Entity::Phase => {
+            let p: PhaseRecord = parse_record(change, data)?;
+            conn.execute(
+                "INSERT INTO phases (id, task_id, idx, title, status, description, planned_files_json, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT(id) DO UPDATE SET task_id = excluded.task_id, idx = excluded.idx, title = excluded.title,
+                   status = excluded.status, description = excluded.description,
+                   planned_files_json = excluded.planned_files_json, updated_at = excluded.updated_at",
+                (&p.id, &p.task_id, p.idx, &p.title, &p.status, &p.description, &p.planned_files_json, &p.created_at, &p.updated_at),
+            )
+        }
+        Entity::Artifact => {
+            let a: ArtifactRecord = parse_record(change, data)?;
+            let (stored, hash) = blobs::put(conn, &a.content).map_err(|e| e.to_string())?;
+            let existing: Option<String> = conn.query_row(
+                "SELECT id FROM artifacts WHERE task_id = ?1 AND COALESCE(phase_id, '') = COALESCE(?2, '') AND kind = ?3 AND draft = 0 LIMIT 1",
+                (&a.task_id, &a.phase_id, &a.kind),
+                |r| r.get(0),
+            ).optional().map_err(|e| e.to_string())?;
+            match existing {
+                Some(existing) => conn.execute(
+                    "UPDATE artifacts SET content = ?1, content_hash = ?2, pinned = ?3, created_at = ?4 WHERE id = ?5",
+                    (&stored, &hash, a.pinned as i64, &a.created_at, &existing),
+                ),
+                None => {
+                    let taken = conn.query_row("SELECT 1 FROM artifacts WHERE id = ?1", [&a.id], |_| Ok(()))
+                        .optional().map_err(|e| e.to_string())?.is_some();
+                    let id = if taken { new_id() } else { a.id.clone() };
+                    conn.execute(
+                        "INSERT INTO artifacts (id, task_id, phase_id, kind, content, content_hash, created_at, pinned)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        (&id, &a.task_id, &a.phase_id, &a.kind, &stored, &hash, &a.created_at, a.pinned as i64),
+                    )
+                }
+            }
+        }
+        Entity::Setting => {
+            let s: SettingRecord = parse_record(change, data)?;
+            // Another device may run a version with other bounds; its value is left out
+            if let Err(e) = validate_setting(&s.key, &s.value) {
+                tracing::warn!(key = %s.key, error = %e, "skipped synced setting");
+                return Ok(());
+            }
+            conn.execute(
+                "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                (&s.key, &s.value, &s.updated_at),
+            )
+        }
+    };
+    written.map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Latest local version of an entity: the last change pushed or applied, or
+/// a newer local edit still waiting in the outbox
+fn local_version(conn: &Connection, entity: Entity, id: &str, device_id: &str, max_seq: i64) -> Result<Option<(String, String)>, String> {
+    let synced: Option<(String, String)> = conn.query_row(
+        "SELECT changed_at, device_id FROM sync_versions WHERE entity = ?1 AND entity_id = ?2",
+        (entity.as_str(), id),
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    ).optional().map_err(|e| e.to_string())?;
+    let pending: Option<String> = conn.query_row(
+        "SELECT MAX(changed_at) FROM sync_outbox WHERE entity = ?1 AND entity_id = ?2 AND seq <= ?3",
+        (entity.as_str(), id, max_seq),
+        |r| r.get(0),
+    ).map_err(|e| e.to_string())?;
+    let pending = pending.map(|changed_at| (changed_at, device_id.to_string()));
+    Ok(synced.into_iter().chain(pending).max())
+}
+
+fn record_version(conn: &Connection, entity: Entity, id: &str, changed_at: &str, device_id: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO sync_versions (entity, entity_id, changed_at, device_id) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(entity, entity_id) DO UPDATE SET changed_at = excluded.changed_at, device_id = excluded.device_id",
+        (entity.as_str(), id, changed_at, device_id),
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Apply one pulled segment and advance that device's cursor, returning how
+/// many changes won. Each entity is last-writer-wins on (changed_at, device
+/// id), and settings merge key by key, so a change only replaces the local
+/// copy when it's newer than both the last synced version and any local edit
+/// still waiting to be pushed.
+fn apply_segment(conn: &Connection, device_id: &str, from: &str, seq: u64, changes: &[Change]) -> Result<usize, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let max_seq: i64 = tx.query_row("SELECT COALESCE(MAX(seq), 0) FROM sync_outbox", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut ordered: Vec<&Change> = changes.iter()
+        .filter(|c| c.device_id != device_id && is_synced(c.entity, &c.id))
+        .collect();
+    ordered.sort_by_key(|c| c.entity);
+
+    let mut applied = 0;
+    for change in ordered {
+        let remote = (change.changed_at.clone(), change.device_id.clone());
+        if local_version(&tx, change.entity, &change.id, device_id, max_seq)?.is_some_and(|local| local >= remote) {
+            continue;
+        }
+        write(&tx, change)?;
+        tx.execute(
+            "DELETE FROM sync_outbox WHERE entity = ?1 AND entity_id = ?2",
+            (change.entity.as_str(), &change.id),
+        ).map_err(|e| e.to_string())?;
+        record_version(&tx, change.entity, &change.id, &change.changed_at, &change.device_id)?;
+        applied += 1;
+    }
+
+    // Writing pulled changes fired the outbox triggers; they aren't local edits
+    tx.execute("DELETE FROM sync_outbox WHERE seq > ?1", [max_seq]).map_err(|e| e.to_string())?;
+    state_set(&tx, &cursor_key(from), &seq.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(applied)
+}
+
+/// Queued local changes, one per entity with its current record, and the
+/// last outbox sequence number they cover
+fn pending_changes(conn: &Connection, device_id: &str) -> Result<(Vec<Change>, i64), String> {
+    let max_seq: i64 = conn.query_row("SELECT COALESCE(MAX(seq), 0) FROM sync_outbox", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    let queued: Vec<(String, String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT entity, entity_id, MAX(changed_at) FROM sync_outbox WHERE seq <= ?1
+             GROUP BY entity, entity_id ORDER BY MIN(seq)"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([max_seq], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+
+    let mut changes = Vec::new();
+    for (entity, id, changed_at) in queued {
+        let Some(entity) = Entity::parse(&entity) else { continue };
+        if !is_synced(entity, &id) {
+            continue;
+        }
+        changes.push(Change {
+            entity,
+            data: load(conn, entity, &id)?,
+            id,
+            changed_at,
+            device_id: device_id.to_string(),
+        });
+    }
+    Ok((changes, max_seq))
+}
+
+/// Record a pushed segment: its changes become the synced versions and the
+/// outbox rows it covered are dropped
+fn mark_pushed(conn: &Connection, changes: &[Change], max_seq: i64, segment: Option<u64>) -> Result<(), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    for change in changes {
+        record_version(&tx, change.entity, &change.id, &change.changed_at, &change.device_id)?;
+    }
+    tx.execute("DELETE FROM sync_outbox WHERE seq <= ?1", [max_seq]).map_err(|e| e.to_string())?;
+    if let Some(seq) = segment {
+        state_set(&tx, "push_seq", &seq.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn encode_segment(changes: &[Change]) -> Result<Vec<u8>, String> {
+    let mut body = String::new();
+    for change in changes {
+        body.push_str(&serde_json::to_string(change).map_err(|e| e.to_string())?);
+        body.push('\n');
+    }
+    Ok(body.into_bytes())
+}
+
+fn decode_segment(key: &str, body: &[u8]) -> Result<Vec<Change>, String> {
+    String::from_utf8_lossy(body)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| format!("Malformed change in {}: {}", key, e)))
+        .collect()
+}
+
+/// Where change log segments are read and written
+enum Transport {
+    Git(git::GitRemote),
+    S3(s3::S3Remote),
+}
+
+impl Transport {
+    async fn open(app: &AppHandle, remote: &SyncRemote) -> Result<Self, String> {
+        match remote {
+            SyncRemote::Git { url, branch } => {
+                let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("sync").join("git");
+                Ok(Transport::Git(git::GitRemote::open(&dir, url, branch.as_deref()).await?))
+            }
+            SyncRemote::S3 { bucket, region, endpoint, prefix } => {
+                let credentials = load_s3_credentials()?
+                    .ok_or("S3 sync needs an access key; set it up again in Settings")?;
+                Ok(Transport::S3(s3::S3Remote::new(bucket, region, endpoint.as_deref(), prefix.as_deref(), credentials)?))
+            }
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>, String> {
+        match self {
+            Transport::Git(remote) => remote.list(CHANGES_DIR),
+            Transport::S3(remote) => remote.list(CHANGES_DIR).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Transport::Git(remote) => remote.get(key),
+            Transport::S3(remote) => remote.get(key).await,
+        }
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        match self {
+            Transport::Git(remote) => remote.put(key, &body),
+            Transport::S3(remote) => remote.put(key, body).await,
+        }
+    }
+
+    /// Make written segments visible to other devices
+    async fn publish(&self, message: &str) -> Result<(), String> {
+        match self {
+            Transport::Git(remote) => remote.publish(message).await,
+            Transport::S3(_) => Ok(()),
+        }
+    }
+}
+
+fn load_s3_credentials() -> Result<Option<S3Credentials>, String> {
+    secrets::get_token(S3_CREDENTIALS_ENTRY)?
+        .map(|json| serde_json::from_str(&json).map_err(|e| format!("S3 credentials in the keychain are malformed: {}", e)))
+        .transpose()
+}
+
+fn parse_remote(value: Option<&str>) -> Result<Option<SyncRemote>, String> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(json) => serde_json::from_str(json).map(Some).map_err(|e| format!("Sync remote setting is malformed: {}", e)),
+        None => Ok(None),
+    }
+}
+
+pub fn status(app: &AppHandle) -> Result<SyncStatus, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let remote: Option<String> = conn.query_row("SELECT value FROM settings WHERE key = ?1", [REMOTE_SETTING], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let queued: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT DISTINCT entity, entity_id FROM sync_outbox").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?))).map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    let pending = queued.iter()
+        .filter(|(entity, id)| Entity::parse(entity).is_some_and(|e| is_synced(e, id)))
+        .count();
+    Ok(SyncStatus {
+        remote: parse_remote(remote.as_deref())?,
+        device_id: state_get(&conn, "device_id")?,
+        pending,
+        last_sync_at: state_get(&conn, "last_sync_at")?,
+        last_error: state_get(&conn, "last_error")?,
+        running: app.state::<SyncRunning>().0.load(Ordering::SeqCst),
+    })
+}
+
+/// Set up sync with a remote, or turn it off with none. S3 credentials go to
+/// the keychain; they can be left out to keep the saved ones.
+pub fn configure(app: &AppHandle, remote: Option<SyncRemote>, credentials: Option<S3Credentials>) -> Result<SyncStatus, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let value = match &remote {
+        Some(remote) => {
+            remote.validate()?;
+            if let SyncRemote::S3 { .. } = remote {
+                match &credentials {
+                    Some(credentials) => {
+                        secrets::set_token(S3_CREDENTIALS_ENTRY, &serde_json::to_string(credentials).map_err(|e| e.to_string())?)?;
+                    }
+                    None if load_s3_credentials()?.is_none() => return Err("S3 sync needs an access key".into()),
+                    None => {}
+                }
+            }
+            serde_json::to_string(remote).map_err(|e| e.to_string())?
+        }
+        None => String::new(),
+    };
+
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        (REMOTE_SETTING, &value, now_iso()),
+    ).map_err(|e| e.to_string())?;
+    match remote {
+        Some(_) if state_get(&conn, "device_id")?.is_none() => start(&conn, &new_id())?,
+        Some(_) => {}
+        None => {
+            stop(&conn)?;
+            secrets::delete_token(S3_CREDENTIALS_ENTRY)?;
+        }
+    }
+    invalidate_settings(app);
+    status(app)
+}
+
+/// Pull other devices' changes, then push this one's
+pub async fn sync_now(app: &AppHandle) -> Result<SyncReport, String> {
+    let running = app.state::<SyncRunning>();
+    if running.0.swap(true, Ordering::SeqCst) {
+        return Err("A sync is already running".into());
+    }
+    let result = run(app).await;
+    running.0.store(false, Ordering::SeqCst);
+
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    match &result {
+        Ok(report) => {
+            state_set(&conn, "last_sync_at", &now_iso())?;
+            conn.execute("DELETE FROM sync_state WHERE key = 'last_error'", []).map_err(|e| e.to_string())?;
+            tracing::info!(pushed = report.pushed, pulled = report.pulled, applied = report.applied, "synced");
+        }
+        Err(e) => {
+            state_set(&conn, "last_error", e)?;
+            tracing::warn!("sync failed: {}", e);
+        }
+    }
+    result
+}
+
+async fn run(app: &AppHandle) -> Result<SyncReport, String> {
+    let settings = cached_settings(app).await?;
+    if offline::is_offline(app, &settings) {
+        return Err("SpecTrail is offline; sync will work again when the connection returns".into());
+    }
+    let remote = parse_remote(settings.get(REMOTE_SETTING).map(String::as_str))?.ok_or("Sync isn't set up")?;
+    let device_id = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        state_get(&conn, "device_id")?.ok_or("Sync isn't set up")?
+    };
+    let transport = Transport::open(app, &remote).await?;
+    let mut report = SyncReport::default();
+
+    let mut segments: Vec<(String, u64, String)> = transport.list().await?
+        .into_iter()
+        .filter_map(|key| parse_segment_key(&key).map(|(device, seq)| (device, seq, key)))
+        .filter(|(device, _, _)| *device != device_id)
+        .collect();
+    segments.sort();
+    for (from, seq, key) in segments {
+        let cursor = {
+            let conn = db::connect(app).map_err(|e| e.to_string())?;
+            state_get(&conn, &cursor_key(&from))?.and_then(|c| c.parse::<u64>().ok()).unwrap_or(0)
+        };
+        if seq <= cursor {
+            continue;
+        }
+        let changes = decode_segment(&key, &transport.get(&key).await?)?;
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        report.applied += apply_segment(&conn, &device_id, &from, seq, &changes)?;
+        report.pulled += changes.len();
+    }
+    if report.applied > 0 {
+        invalidate_settings(app);
+    }
+
+    let (changes, max_seq, segment) = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        let (changes, max_seq) = pending_changes(&conn, &device_id)?;
+        let last: u64 = state_get(&conn, "push_seq")?.and_then(|s| s.parse().ok()).unwrap_or(0);
+        (changes, max_seq, last + 1)
+    };
+    if changes.is_empty() {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        mark_pushed(&conn, &[], max_seq, None)?;
+        return Ok(report);
+    }
+    transport.put(&segment_key(&device_id, segment), encode_segment(&changes)?).await?;
+    transport.publish(&format!("Sync {} changes from {}", changes.len(), device_id)).await?;
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    mark_pushed(&conn, &changes, max_seq, Some(segment))?;
+    report.pushed = changes.len();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at TEXT NOT NULL);
+             CREATE TABLE projects (id TEXT PRIMARY KEY, name TEXT NOT NULL, repo_path TEXT NOT NULL, created_at TEXT NOT NULL, last_opened_at TEXT);
+             CREATE TABLE tasks (id TEXT PRIMARY KEY, project_id TEXT NOT NULL, title TEXT NOT NULL, mode TEXT NOT NULL,
+               status TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, description TEXT);
+             CREATE TABLE phases (id TEXT PRIMARY KEY, task_id TEXT NOT NULL, idx INTEGER NOT NULL, title TEXT NOT NULL,
+               status TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, description TEXT,
+               planned_files_json TEXT NOT NULL DEFAULT '[]');
+             CREATE TABLE artifacts (id TEXT PRIMARY KEY, task_id TEXT NOT NULL, phase_id TEXT, kind TEXT NOT NULL,
+               content TEXT NOT NULL, created_at TEXT NOT NULL, pinned INTEGER NOT NULL DEFAULT 0,
+               draft INTEGER NOT NULL DEFAULT 0, content_hash TEXT);
+             CREATE TABLE blobs (hash TEXT PRIMARY KEY, content TEXT NOT NULL, size INTEGER NOT NULL, created_at TEXT NOT NULL);
+             INSERT INTO settings VALUES ('model', 'gpt-4o', '2026-03-01T09:00:00Z');
+             INSERT INTO settings VALUES ('editor', 'vscode', '2026-03-01T09:00:00Z');
+             INSERT INTO projects VALUES ('p1', 'Web', '/home/a/web', '2026-03-01T09:00:00Z', NULL);
+             INSERT INTO tasks VALUES ('t1', 'p1', 'Add login', 'plan', 'draft', '2026-03-01T09:00:00Z', '2026-03-01T09:00:00Z', NULL);
+             INSERT INTO artifacts (id, task_id, kind, content, created_at) VALUES ('a1', 't1', 'plan_md', 'v1', '2026-03-01T09:00:00Z');"
+        ).unwrap();
+        conn.execute_batch(include_str!("../../migrations/031_sync.sql")).unwrap();
+        conn
+    }
+
+    fn remote_change(entity: Entity, id: &str, changed_at: &str, data: Value) -> Change {
+        Change { entity, id: id.into(), changed_at: changed_at.into(), device_id: "laptop".into(), data: Some(data) }
+    }
+
+    #[test]
+    fn test_push_and_last_writer_wins() {
+        let conn = test_db();
+        start(&conn, "desktop").unwrap();
+
+        // Initial snapshot: everything except machine-local settings
+        let (changes, max_seq) = pending_changes(&conn, "desktop").unwrap();
+        let ids: Vec<&str> = changes.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["p1", "t1", "t1::plan_md", "model"]);
+        assert_eq!(changes[2].data.as_ref().unwrap()["content"], "v1");
+        assert_eq!(changes[0].changed_at, "2026-03-01T09:00:00.000Z");
+        mark_pushed(&conn, &changes, max_seq, Some(1)).unwrap();
+
+        // A local edit made after the remote one keeps the local plan
+        conn.execute("UPDATE artifacts SET content = 'local' WHERE id = 'a1'", []).unwrap();
+        let changes = vec![
+            remote_change(Entity::Artifact, "t1::plan_md", "2026-03-01T10:00:00.000Z", serde_json::json!({
+                "id": "a9", "task_id": "t1", "phase_id": null, "kind": "plan_md", "content": "remote", "pinned": false,
+                "created_at": "2026-03-01T10:00:00Z"
+            })),
+            remote_change(Entity::Setting, "model", "2026-03-01T10:00:00.000Z", serde_json::json!({
+                "key": "model", "value": "claude", "updated_at": "2026-03-01T10:00:00Z"
+            })),
+            remote_change(Entity::Setting, "editor", "2026-03-01T10:00:00.000Z", serde_json::json!({
+                "key": "editor", "value": "zed", "updated_at": "2026-03-01T10:00:00Z"
+            })),
+            remote_change(Entity::Setting, "command_backend", "2026-03-01T10:00:00.000Z", serde_json::json!({
+                "key": "command_backend", "value": "host", "updated_at": "2026-03-01T10:00:00Z"
+            })),
+            remote_change(Entity::Setting, "temperature", "2026-03-01T10:00:00.000Z", serde_json::json!({
+                "key": "temperature", "value": "9", "updated_at": "2026-03-01T10:00:00Z"
+            })),
+            remote_change(Entity::Task, "t2", "2026-03-01T10:00:00.000Z", serde_json::json!({
+                "id": "t2", "project_id": "p1", "title": "From laptop", "mode": "plan", "status": "draft",
+                "description": null, "created_at": "2026-03-01T10:00:00Z", "updated_at": "2026-03-01T10:00:00Z"
+            })),
+        ];
+        assert_eq!(apply_segment(&conn, "desktop", "laptop", 1, &changes).unwrap(), 3);
+
+        let value = |sql: &str| conn.query_row(sql, [], |r| r.get::<_, String>(0)).unwrap();
+        assert_eq!(value("SELECT content FROM artifacts WHERE id = 'a1'"), "local");
+        assert_eq!(value("SELECT value FROM settings WHERE key = 'model'"), "claude");
+        assert_eq!(value("SELECT value FROM settings WHERE key = 'editor'"), "vscode");
+        // Settings that aren't preferences, or don't validate, never arrive
+        let unsynced: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM settings WHERE key IN ('command_backend', 'temperature')", [], |r| r.get(0)
+        ).unwrap();
+        assert_eq!(unsynced, 0);
+        assert_eq!(value("SELECT title FROM tasks WHERE id = 't2'"), "From laptop");
+        assert_eq!(value("SELECT value FROM sync_state WHERE key = 'cursor:laptop'"), "1");
+
+        // Applied changes aren't echoed back; the local plan edit still is
+        let (changes, _) = pending_changes(&conn, "desktop").unwrap();
+        let ids: Vec<&str> = changes.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["t1::plan_md"]);
+
+        // A later remote delete wins over the pushed version
+        let delete = Change { data: None, ..remote_change(Entity::Task, "t1", "2099-01-01T00:00:00.000Z", Value::Null) };
+        assert_eq!(apply_segment(&conn, "desktop", "laptop", 2, &[delete]).unwrap(), 1);
+        let tasks: i64 = conn.query_row("SELECT COUNT(*) FROM tasks WHERE id = 't1'", [], |r| r.get(0)).unwrap();
+        assert_eq!(tasks, 0);
+    }
+
+    #[test]
+    fn test_segment_keys() {
+        assert_eq!(segment_key("abc", 7), "changes/abc/0000000007.jsonl");
+        assert_eq!(parse_segment_key("changes/abc/0000000007.jsonl"), Some(("abc".to_string(), 7)));
+        assert_eq!(parse_segment_key("README.md"), None);
+    }
+}
@@ -0,0 +1,276 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::models::{new_id, ID};
+use crate::render::numbered_section;
+use crate::workflows::findings::{extract_findings, Severity};
+
+const MAX_TAG_CHARS: usize = 40;
+
+/// Verification report recommends adding or running tests
+pub const NEEDS_TESTS: &str = "needs-tests";
+/// Verification report lists a high severity risk
+pub const HIGH_RISK: &str = "high-risk";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TagTarget {
+  Task,
+  Artifact,
+}
+
+impl TagTarget {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      TagTarget::Task => "task",
+      TagTarget::Artifact => "artifact",
+    }
+  }
+
+  fn table(self) -> &'static str {
+    match self {
+      TagTarget::Task => "tasks",
+      TagTarget::Artifact => "artifacts",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct Tag {
+  pub id: ID,
+  pub name: String,
+  pub created_at: String,
+  /// Tasks and artifacts carrying the tag
+  pub usage_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Type)]
+pub struct AppliedTag {
+  pub name: String,
+  /// user|auto
+  pub source: String,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// Tag names are lowercase with whitespace collapsed to dashes
+pub fn normalize(name: &str) -> Result<String, String> {
+  let name = name.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase();
+  if name.is_empty() {
+    return Err("Tag name cannot be empty".into());
+  }
+  if name.chars().count() > MAX_TAG_CHARS {
+    return Err(format!("Tag name is longer than {} characters", MAX_TAG_CHARS));
+  }
+  Ok(name)
+}
+
+fn ensure_tag(conn: &Connection, name: &str) -> Result<ID, String> {
+  let existing: Option<ID> = conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |r| r.get(0))
+    .optional().map_err(|e| e.to_string())?;
+  if let Some(id) = existing {
+    return Ok(id);
+  }
+  let id = new_id();
+  conn.execute("INSERT INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)", (&id, name, now_iso()))
+    .map_err(|e| e.to_string())?;
+  Ok(id)
+}
+
+/// Turn the free-form task labels from before tags into user tags on their
+/// tasks, then drop `task_labels`. Labels were only trimmed and lowercased, so
+/// they're given tag names here; a label too long for one is cut to fit.
+pub fn migrate_task_labels(conn: &Connection) -> rusqlite::Result<usize> {
+  let labels: Vec<(String, String)> = {
+    let mut stmt = conn.prepare("SELECT task_id, label FROM task_labels")?;
+    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+    rows.collect::<Result<_, _>>()?
+  };
+  let mut moved = 0;
+  for (task_id, label) in labels {
+    let name: String = label.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase()
+      .chars().take(MAX_TAG_CHARS).collect();
+    if name.is_empty() {
+      continue;
+    }
+    conn.execute("INSERT OR IGNORE INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)", (new_id(), &name, now_iso()))?;
+    moved += conn.execute(
+      "INSERT OR IGNORE INTO taggings (tag_id, target, target_id, source, created_at)
+       SELECT id, 'task', ?2, 'user', ?3 FROM tags WHERE name = ?1",
+      (&name, &task_id, now_iso())
+    )?;
+  }
+  conn.execute_batch("DROP TABLE task_labels")?;
+  if moved > 0 {
+    tracing::info!(moved, "turned task labels into tags");
+  }
+  Ok(moved)
+}
+
+fn applied(conn: &Connection, target: TagTarget, target_id: &str) -> Result<Vec<AppliedTag>, String> {
+  let mut stmt = conn.prepare(
+    "SELECT t.name, g.source FROM taggings g JOIN tags t ON t.id = g.tag_id
+     WHERE g.target = ?1 AND g.target_id = ?2 ORDER BY t.name"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map((target.as_str(), target_id), |r| Ok(AppliedTag { name: r.get(0)?, source: r.get(1)? }))
+    .map_err(|e| e.to_string())?;
+  rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// Attach `names` to a task or artifact. A user tag replaces an auto tag of
+/// the same name so regenerating the report won't remove it.
+fn tag(conn: &Connection, target: TagTarget, target_id: &str, names: &[String], source: &str) -> Result<(), String> {
+  let exists: bool = conn.query_row(
+    &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?1)", target.table()), [target_id], |r| r.get(0)
+  ).map_err(|e| e.to_string())?;
+  if !exists {
+    return Err(format!("{} {} not found", target.as_str(), target_id));
+  }
+  for name in names {
+    let tag_id = ensure_tag(conn, &normalize(name)?)?;
+    conn.execute(
+      "INSERT INTO taggings (tag_id, target, target_id, source, created_at) VALUES (?1, ?2, ?3, ?4, ?5)
+       ON CONFLICT(tag_id, target, target_id) DO UPDATE SET source = 'user' WHERE excluded.source = 'user'",
+      (&tag_id, target.as_str(), target_id, source, now_iso())
+    ).map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+pub fn list_tags(app: &AppHandle) -> Result<Vec<Tag>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(
+    "SELECT t.id, t.name, t.created_at, COUNT(g.tag_id) FROM tags t
+     LEFT JOIN taggings g ON g.tag_id = t.id
+     GROUP BY t.id ORDER BY t.name"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], |r| Ok(Tag {
+    id: r.get(0)?,
+    name: r.get(1)?,
+    created_at: r.get(2)?,
+    usage_count: r.get(3)?,
+  })).map_err(|e| e.to_string())?;
+  rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+pub fn get_tags(app: &AppHandle, target: TagTarget, target_id: &str) -> Result<Vec<AppliedTag>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  applied(&conn, target, target_id)
+}
+
+pub fn add_tags(app: &AppHandle, target: TagTarget, target_id: &str, names: &[String]) -> Result<Vec<AppliedTag>, String> {
+  let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  tag(&tx, target, target_id, names, "user")?;
+  tx.commit().map_err(|e| e.to_string())?;
+  applied(&conn, target, target_id)
+}
+
+pub fn remove_tags(app: &AppHandle, target: TagTarget, target_id: &str, names: &[String]) -> Result<Vec<AppliedTag>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  for name in names {
+    conn.execute(
+      "DELETE FROM taggings WHERE target = ?1 AND target_id = ?2
+         AND tag_id = (SELECT id FROM tags WHERE name = ?3)",
+      (target.as_str(), target_id, normalize(name)?)
+    ).map_err(|e| e.to_string())?;
+  }
+  applied(&conn, target, target_id)
+}
+
+/// Delete a tag everywhere it's used
+pub fn delete_tag(app: &AppHandle, tag_id: &str) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute("DELETE FROM taggings WHERE tag_id = ?1", [tag_id]).map_err(|e| e.to_string())?;
+  conn.execute("DELETE FROM tags WHERE id = ?1", [tag_id]).map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+/// Tags implied by a verification report: `needs-tests` when the results or
+/// next actions call for tests, `high-risk` for a high severity risk
+pub fn report_tags(report_md: &str) -> Vec<&'static str> {
+  let mut tags = vec![];
+  let mentions_tests = |number: u8, cues: &[&str]| {
+    numbered_section(report_md, number).is_some_and(|body| {
+      body.lines().map(str::to_lowercase).any(|line| line.contains("test") && cues.iter().any(|cue| line.contains(cue)))
+    })
+  };
+  if mentions_tests(6, &["add", "write", "missing", "cover", "run"])
+    || mentions_tests(5, &["no test", "not run", "missing", "none"]) {
+    tags.push(NEEDS_TESTS);
+  }
+  if extract_findings(report_md).iter().any(|f| f.section == "risk" && f.severity == Severity::High) {
+    tags.push(HIGH_RISK);
+  }
+  tags
+}
+
+/// Replace an artifact's auto tags with those derived from its report
+pub fn auto_tag_report(conn: &Connection, artifact_id: &str, report_md: &str) -> Result<(), String> {
+  conn.execute(
+    "DELETE FROM taggings WHERE target = 'artifact' AND target_id = ?1 AND source = 'auto'", [artifact_id]
+  ).map_err(|e| e.to_string())?;
+  let names: Vec<String> = report_tags(report_md).into_iter().map(str::to_string).collect();
+  tag(conn, TagTarget::Artifact, artifact_id, &names, "auto")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_auto_tags_keep_user_tags() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE tasks (id TEXT PRIMARY KEY);
+       CREATE TABLE artifacts (id TEXT PRIMARY KEY);
+       CREATE TABLE task_labels (task_id TEXT NOT NULL, label TEXT NOT NULL);
+       INSERT INTO artifacts VALUES ('a1');"
+    ).unwrap();
+    conn.execute_batch(include_str!("../migrations/034_tags.sql")).unwrap();
+
+    let report = "## 4. Risks\n| Risk | Severity | Notes |\n|---|---|---|\n| Token leak | High | `src/auth.rs` logs it |\n\n\
+                  ## 5. Test/Check Results\nAll checks passed.\n\n\
+                  ## 6. Recommended Next Actions\n- Add tests for the refresh path";
+    assert_eq!(report_tags(report), vec![NEEDS_TESTS, HIGH_RISK]);
+    assert!(report_tags("## 5. Test/Check Results\nAll tests passed.").is_empty());
+
+    tag(&conn, TagTarget::Artifact, "a1", &["Needs  Tests".into()], "user").unwrap();
+    auto_tag_report(&conn, "a1", report).unwrap();
+    auto_tag_report(&conn, "a1", "## 6. Recommended Next Actions\n- Ship it").unwrap();
+    assert_eq!(applied(&conn, TagTarget::Artifact, "a1").unwrap(), vec![
+      AppliedTag { name: NEEDS_TESTS.into(), source: "user".into() },
+    ]);
+
+    assert!(tag(&conn, TagTarget::Task, "missing", &["x".into()], "user").is_err());
+    assert!(normalize("  ").is_err());
+  }
+
+  #[test]
+  fn test_task_labels_become_tags() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE tasks (id TEXT PRIMARY KEY);
+       CREATE TABLE artifacts (id TEXT PRIMARY KEY);
+       CREATE TABLE task_labels (task_id TEXT NOT NULL, label TEXT NOT NULL);
+       INSERT INTO tasks VALUES ('t1'), ('t2');
+       INSERT INTO task_labels VALUES ('t1', 'needs review'), ('t1', 'bug'), ('t2', 'bug');"
+    ).unwrap();
+    conn.execute_batch(include_str!("../migrations/034_tags.sql")).unwrap();
+
+    assert_eq!(migrate_task_labels(&conn).unwrap(), 3);
+    assert_eq!(applied(&conn, TagTarget::Task, "t1").unwrap(), vec![
+      AppliedTag { name: "bug".into(), source: "user".into() },
+      AppliedTag { name: "needs-review".into(), source: "user".into() },
+    ]);
+    let tags: i64 = conn.query_row("SELECT COUNT(*) FROM tags", [], |r| r.get(0)).unwrap();
+    assert_eq!(tags, 2);
+    assert!(conn.prepare("SELECT 1 FROM task_labels").is_err());
+  }
+}
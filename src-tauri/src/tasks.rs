@@ -0,0 +1,317 @@
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::tags;
+use crate::models::{Task, ID};
+
+const TASK_MODES: &[&str] = &["plan", "phases", "review"];
+const TASK_STATUSES: &[&str] = &["draft", "active", "done", "archived"];
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 200;
+
+/// Fields to change on every selected task; unset fields are left alone
+#[derive(Debug, Clone, Default, Deserialize, Type)]
+pub struct TaskPatch {
+  #[serde(default)]
+  pub title: Option<String>,
+  /// An empty string clears the description
+  #[serde(default)]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub mode: Option<String>,
+  #[serde(default)]
+  pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+  #[default]
+  UpdatedDesc,
+  UpdatedAsc,
+  CreatedDesc,
+  CreatedAsc,
+  Title,
+}
+
+impl TaskSort {
+  fn order_by(&self) -> &'static str {
+    match self {
+      TaskSort::UpdatedDesc => "t.updated_at DESC",
+      TaskSort::UpdatedAsc => "t.updated_at ASC",
+      TaskSort::CreatedDesc => "t.created_at DESC",
+      TaskSort::CreatedAsc => "t.created_at ASC",
+      TaskSort::Title => "t.title COLLATE NOCASE ASC",
+    }
+  }
+}
+
+/// Filters for `search_tasks`. Each list matches any of its values; an empty
+/// list doesn't filter. A task must carry every tag given.
+#[derive(Debug, Clone, Default, Deserialize, Type)]
+pub struct TaskFilters {
+  #[serde(default)]
+  pub statuses: Vec<String>,
+  #[serde(default)]
+  pub modes: Vec<String>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub sort: TaskSort,
+  #[serde(default)]
+  pub offset: usize,
+  /// Page size; defaults to 50, at most 200
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TaskSearchPage {
+  pub tasks: Vec<Task>,
+  /// Matching tasks across all pages
+  pub total: i64,
+  pub offset: usize,
+  pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct BulkTaskResult {
+  pub task_id: ID,
+  pub ok: bool,
+  pub error: Option<String>,
+  /// The task after the change
+  pub task: Option<Task>,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn validate(patch: &TaskPatch) -> Result<(), String> {
+  if patch.title.is_none() && patch.description.is_none() && patch.mode.is_none() && patch.status.is_none() {
+    return Err("Nothing to update".into());
+  }
+  if patch.title.as_deref().is_some_and(|t| t.trim().is_empty()) {
+    return Err("Task title can't be empty".into());
+  }
+  if let Some(mode) = patch.mode.as_deref().filter(|m| !TASK_MODES.contains(m)) {
+    return Err(format!("Unknown task mode '{}' (expected {})", mode, TASK_MODES.join(", ")));
+  }
+  if let Some(status) = patch.status.as_deref().filter(|s| !TASK_STATUSES.contains(s)) {
+    return Err(format!("Unknown task status '{}' (expected {})", status, TASK_STATUSES.join(", ")));
+  }
+  Ok(())
+}
+
+fn load_task(conn: &Connection, task_id: &str) -> rusqlite::Result<Option<Task>> {
+  conn.query_row(
+    "SELECT id, project_id, title, mode, status, created_at, updated_at, description FROM tasks WHERE id = ?1",
+    [task_id],
+    |r| Ok(Task {
+      id: r.get(0)?,
+      project_id: r.get(1)?,
+      title: r.get(2)?,
+      mode: r.get(3)?,
+      status: r.get(4)?,
+      created_at: r.get(5)?,
+      updated_at: r.get(6)?,
+      description: r.get(7)?,
+    })
+  ).optional()
+}
+
+fn update_one(conn: &Connection, task_id: &str, patch: &TaskPatch, now: &str) -> Result<Task, String> {
+  let updated = conn.execute(
+    "UPDATE tasks SET
+       title = COALESCE(?1, title),
+       description = CASE WHEN ?2 IS NULL THEN description ELSE NULLIF(?2, '') END,
+       mode = COALESCE(?3, mode),
+       status = COALESCE(?4, status),
+       updated_at = ?5
+     WHERE id = ?6",
+    (patch.title.as_deref().map(str::trim), &patch.description, &patch.mode, &patch.status, now, task_id)
+  ).map_err(|e| e.to_string())?;
+  if updated == 0 {
+    return Err(format!("Task {} not found", task_id));
+  }
+  load_task(conn, task_id).map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Task {} not found", task_id))
+}
+
+/// Apply `patch` to each task in one transaction. A missing task is reported
+/// in its result without undoing the others.
+fn apply(conn: &Connection, task_ids: &[ID], patch: &TaskPatch) -> Result<Vec<BulkTaskResult>, String> {
+  validate(patch)?;
+  let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+  let now = now_iso();
+  let mut results = Vec::with_capacity(task_ids.len());
+  for task_id in task_ids {
+    let result = match update_one(&tx, task_id, patch, &now) {
+      Ok(task) => BulkTaskResult { task_id: task_id.clone(), ok: true, error: None, task: Some(task) },
+      Err(e) => BulkTaskResult { task_id: task_id.clone(), ok: false, error: Some(e), task: None },
+    };
+    results.push(result);
+  }
+  tx.commit().map_err(|e| e.to_string())?;
+  Ok(results)
+}
+
+pub fn bulk_update_tasks(app: &AppHandle, task_ids: &[ID], patch: &TaskPatch) -> Result<Vec<BulkTaskResult>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  apply(&conn, task_ids, patch)
+}
+
+pub fn bulk_archive_tasks(app: &AppHandle, task_ids: &[ID]) -> Result<Vec<BulkTaskResult>, String> {
+  let patch = TaskPatch { status: Some("archived".into()), ..TaskPatch::default() };
+  bulk_update_tasks(app, task_ids, &patch)
+}
+
+/// Escape LIKE wildcards so the query matches literally
+fn like_pattern(term: &str) -> String {
+  let escaped = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+  format!("%{}%", escaped)
+}
+
+fn in_list(column: &str, values: &[String], params: &mut Vec<Value>) -> String {
+  let placeholders: Vec<String> = values.iter()
+    .map(|v| {
+      params.push(Value::Text(v.clone()));
+      format!("?{}", params.len())
+    })
+    .collect();
+  format!("{} IN ({})", column, placeholders.join(", "))
+}
+
+fn search(conn: &Connection, project_id: &str, query: Option<&str>, filters: &TaskFilters) -> Result<TaskSearchPage, String> {
+  let mut params: Vec<Value> = vec![Value::Text(project_id.to_string())];
+  let mut conditions = vec!["t.project_id = ?1".to_string()];
+
+  // Every word has to appear in the title or the description
+  for term in query.unwrap_or_default().split_whitespace() {
+    params.push(Value::Text(like_pattern(term)));
+    let n = params.len();
+    conditions.push(format!(
+      "(t.title LIKE ?{n} ESCAPE '\\' OR COALESCE(t.description, '') LIKE ?{n} ESCAPE '\\')"
+    ));
+  }
+  if !filters.statuses.is_empty() {
+    conditions.push(in_list("t.status", &filters.statuses, &mut params));
+  }
+  if !filters.modes.is_empty() {
+    conditions.push(in_list("t.mode", &filters.modes, &mut params));
+  }
+  for tag in &filters.tags {
+    params.push(Value::Text(tags::normalize(tag)?));
+    conditions.push(format!(
+      "EXISTS (SELECT 1 FROM taggings g JOIN tags tg ON tg.id = g.tag_id
+               WHERE g.target = 'task' AND g.target_id = t.id AND tg.name = ?{})",
+      params.len()
+    ));
+  }
+  let filter = conditions.join(" AND ");
+
+  let total: i64 = conn.query_row(
+    &format!("SELECT COUNT(*) FROM tasks t WHERE {}", filter),
+    params_from_iter(params.iter()),
+    |r| r.get(0)
+  ).map_err(|e| e.to_string())?;
+
+  let limit = filters.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+  let mut stmt = conn.prepare(&format!(
+    "SELECT t.id, t.project_id, t.title, t.mode, t.status, t.created_at, t.updated_at, t.description
+     FROM tasks t WHERE {} ORDER BY {}, t.id LIMIT {} OFFSET {}",
+    filter, filters.sort.order_by(), limit, filters.offset
+  )).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map(params_from_iter(params.iter()), |r| Ok(Task {
+    id: r.get(0)?,
+    project_id: r.get(1)?,
+    title: r.get(2)?,
+    mode: r.get(3)?,
+    status: r.get(4)?,
+    created_at: r.get(5)?,
+    updated_at: r.get(6)?,
+    description: r.get(7)?,
+  })).map_err(|e| e.to_string())?;
+  let tasks = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+  Ok(TaskSearchPage { tasks, total, offset: filters.offset, limit })
+}
+
+/// One page of a project's tasks matching `query` and `filters`
+pub fn search_tasks(app: &AppHandle, project_id: &str, query: Option<&str>, filters: &TaskFilters) -> Result<TaskSearchPage, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  search(&conn, project_id, query, filters)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_bulk_update_reports_per_task() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE tasks (id TEXT PRIMARY KEY, project_id TEXT NOT NULL, title TEXT NOT NULL, mode TEXT NOT NULL,
+         status TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, description TEXT);
+       INSERT INTO tasks VALUES ('t1', 'p', 'One', 'plan', 'draft', '2026-03-01T09:00:00Z', '2026-03-01T09:00:00Z', 'keep');
+       INSERT INTO tasks VALUES ('t2', 'p', 'Two', 'plan', 'draft', '2026-03-01T09:00:00Z', '2026-03-01T09:00:00Z', 'old');"
+    ).unwrap();
+
+    let ids: Vec<ID> = vec!["t1".into(), "missing".into(), "t2".into()];
+    let archive = TaskPatch { status: Some("archived".into()), ..TaskPatch::default() };
+    let results = apply(&conn, &ids, &archive).unwrap();
+    let ok: Vec<bool> = results.iter().map(|r| r.ok).collect();
+    assert_eq!(ok, vec![true, false, true]);
+    assert_eq!(results[0].task.as_ref().unwrap().status, "archived");
+    assert_eq!(results[0].task.as_ref().unwrap().description.as_deref(), Some("keep"));
+
+    let clear = TaskPatch { description: Some(String::new()), mode: Some("phases".into()), ..TaskPatch::default() };
+    let results = apply(&conn, &ids[2..], &clear).unwrap();
+    let task = results[0].task.as_ref().unwrap();
+    assert_eq!((task.description.as_deref(), task.mode.as_str()), (None, "phases"));
+
+    assert!(apply(&conn, &ids, &TaskPatch::default()).is_err());
+    assert!(apply(&conn, &ids, &TaskPatch { status: Some("stale".into()), ..TaskPatch::default() }).is_err());
+  }
+
+  #[test]
+  fn test_search_tasks() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE tasks (id TEXT PRIMARY KEY, project_id TEXT NOT NULL, title TEXT NOT NULL, mode TEXT NOT NULL,
+         status TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, description TEXT);
+       CREATE TABLE artifacts (id TEXT PRIMARY KEY);"
+    ).unwrap();
+    conn.execute_batch(include_str!("../migrations/033_task_search.sql")).unwrap();
+    conn.execute_batch(include_str!("../migrations/034_tags.sql")).unwrap();
+    conn.execute_batch(
+      "INSERT INTO tasks VALUES ('t1', 'p', 'Login form', 'plan', 'active', '2026-03-01', '2026-03-04', 'OAuth via GitHub');
+       INSERT INTO tasks VALUES ('t2', 'p', 'Logout', 'plan', 'draft', '2026-03-02', '2026-03-03', NULL);
+       INSERT INTO tasks VALUES ('t3', 'p', 'Fix 100% CPU', 'review', 'done', '2026-03-03', '2026-03-02', 'login loop');
+       INSERT INTO tasks VALUES ('t4', 'other', 'Login', 'plan', 'active', '2026-03-04', '2026-03-01', NULL);
+       INSERT INTO tags VALUES ('g1', 'auth', '2026-03-01'), ('g2', 'perf', '2026-03-01');
+       INSERT INTO taggings VALUES ('g1', 'task', 't1', 'user', '2026-03-01'), ('g1', 'task', 't3', 'user', '2026-03-01'),
+         ('g2', 'task', 't3', 'auto', '2026-03-01');"
+    ).unwrap();
+    let ids = |page: TaskSearchPage| page.tasks.into_iter().map(|t| t.id).collect::<Vec<_>>();
+
+    let page = search(&conn, "p", Some("LOGIN"), &TaskFilters::default()).unwrap();
+    assert_eq!(page.total, 2);
+    assert_eq!(ids(page), vec!["t1", "t3"]);
+
+    let filters = TaskFilters { tags: vec!["Auth".into()], sort: TaskSort::CreatedDesc, ..TaskFilters::default() };
+    assert_eq!(ids(search(&conn, "p", None, &filters).unwrap()), vec!["t3", "t1"]);
+
+    let filters = TaskFilters { statuses: vec!["draft".into(), "done".into()], limit: Some(1), offset: 1, sort: TaskSort::Title, ..TaskFilters::default() };
+    let page = search(&conn, "p", None, &filters).unwrap();
+    assert_eq!((page.total, page.limit), (2, 1));
+    assert_eq!(ids(page), vec!["t2"]);
+
+    assert_eq!(ids(search(&conn, "p", Some("100%"), &TaskFilters::default()).unwrap()), vec!["t3"]);
+    assert!(ids(search(&conn, "p", Some("_"), &TaskFilters::default()).unwrap()).is_empty());
+  }
+}
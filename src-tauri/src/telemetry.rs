@@ -0,0 +1,284 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::events::{RunEvent, RunStatus};
+use crate::models::new_id;
+use crate::settings::{cached_settings, AppSettings};
+
+/// Bumped when the report's shape changes
+const REPORT_VERSION: u32 = 1;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_NAME_PART: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+pub struct Counter {
+  pub name: String,
+  pub count: i64,
+}
+
+/// What an upload sends: the totals of every counter recorded since the last upload
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TelemetryReport {
+  pub version: u32,
+  pub app_version: String,
+  pub os: String,
+  /// First and last day with counts (UTC, inclusive)
+  pub from_day: Option<String>,
+  pub to_day: Option<String>,
+  pub counters: Vec<Counter>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TelemetryUpload {
+  pub uploaded_at: String,
+  pub endpoint: String,
+  pub payload: String,
+}
+
+/// The report exactly as an upload would send it, for the user to check first
+#[derive(Debug, Serialize, Type)]
+pub struct TelemetryPreview {
+  pub enabled: bool,
+  pub endpoint: String,
+  pub report: TelemetryReport,
+  /// The JSON body an upload sends
+  pub payload: String,
+  /// Passed back to `upload`, which refuses to send anything but this payload
+  pub digest: String,
+  pub last_upload: Option<TelemetryUpload>,
+}
+
+/// Count a run's outcome. Best-effort, and counts nothing unless telemetry is enabled.
+pub fn on_run_event(app: &AppHandle, event: &RunEvent) {
+  let names = event_counters(event);
+  if names.is_empty() {
+    return;
+  }
+  let result = db::connect(app).map_err(|e| e.to_string())
+    .and_then(|conn| record(&conn, &today(), &names));
+  if let Err(e) = result {
+    tracing::warn!(run_id = %event.run_id, "failed to count run for telemetry: {}", e);
+  }
+}
+
+fn event_counters(event: &RunEvent) -> Vec<String> {
+  let run_type = name_part(&event.run_type);
+  match event.status {
+    RunStatus::Started => vec![],
+    RunStatus::Finished => vec![format!("run.{}.finished", run_type)],
+    RunStatus::Cancelled => vec![format!("run.{}.cancelled", run_type)],
+    RunStatus::Failed => vec![
+      format!("run.{}.failed", run_type),
+      format!("failure.{}.{}", run_type, name_part(event.code.as_deref().unwrap_or_default())),
+    ],
+  }
+}
+
+/// Run types and failure codes are constants, but the names are filtered
+/// anyway so nothing else can end up in a report
+fn name_part(value: &str) -> String {
+  let part: String = value.chars()
+    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+    .take(MAX_NAME_PART)
+    .collect();
+  if part.is_empty() { "unknown".into() } else { part }
+}
+
+fn record(conn: &Connection, day: &str, names: &[String]) -> Result<(), String> {
+  for name in names {
+    conn.execute(
+      "INSERT INTO telemetry_counters (day, name, count)
+       SELECT ?1, ?2, 1 WHERE EXISTS (SELECT 1 FROM settings WHERE key = 'telemetry_enabled' AND value = '1')
+       ON CONFLICT(day, name) DO UPDATE SET count = count + 1",
+      (day, name)
+    ).map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+/// The stored (day, name, count) rows
+fn pending(conn: &Connection) -> Result<Vec<(String, String, i64)>, String> {
+  let mut stmt = conn.prepare("SELECT day, name, count FROM telemetry_counters WHERE count > 0 ORDER BY day, name")
+    .map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+    .map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn build_report(rows: &[(String, String, i64)], app_version: &str) -> TelemetryReport {
+  let mut counters: Vec<Counter> = vec![];
+  for (_, name, count) in rows {
+    match counters.iter_mut().find(|c| &c.name == name) {
+      Some(counter) => counter.count += count,
+      None => counters.push(Counter { name: name.clone(), count: *count }),
+    }
+  }
+  counters.sort_by(|a, b| a.name.cmp(&b.name));
+  TelemetryReport {
+    version: REPORT_VERSION,
+    app_version: app_version.to_string(),
+    os: std::env::consts::OS.to_string(),
+    from_day: rows.iter().map(|(day, _, _)| day.clone()).min(),
+    to_day: rows.iter().map(|(day, _, _)| day.clone()).max(),
+    counters,
+  }
+}
+
+fn digest(payload: &str) -> String {
+  let hash = Sha256::digest(payload.as_bytes());
+  hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn last_upload(conn: &Connection) -> Result<Option<TelemetryUpload>, String> {
+  conn.query_row(
+    "SELECT uploaded_at, endpoint, payload FROM telemetry_uploads ORDER BY uploaded_at DESC LIMIT 1", [],
+    |r| Ok(TelemetryUpload { uploaded_at: r.get(0)?, endpoint: r.get(1)?, payload: r.get(2)? })
+  ).optional().map_err(|e| e.to_string())
+}
+
+pub async fn preview(app: &AppHandle) -> Result<TelemetryPreview, String> {
+  let settings = AppSettings::from_map(&*cached_settings(app).await?);
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let report = build_report(&pending(&conn)?, &app.package_info().version.to_string());
+  let payload = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+  Ok(TelemetryPreview {
+    enabled: settings.telemetry_enabled,
+    endpoint: settings.telemetry_endpoint,
+    report,
+    digest: digest(&payload),
+    payload,
+    last_upload: last_upload(&conn)?,
+  })
+}
+
+/// Send the previewed report to `telemetry_endpoint`, then start counting
+/// afresh. `digest` comes from the preview the user saw; if the counters have
+/// changed since, nothing is sent.
+pub async fn upload(app: &AppHandle, preview_digest: &str) -> Result<TelemetryUpload, String> {
+  let settings = AppSettings::from_map(&*cached_settings(app).await?);
+  if !settings.telemetry_enabled {
+    return Err("Usage statistics are off; enable them in Settings first".into());
+  }
+  let endpoint = settings.telemetry_endpoint.trim().to_string();
+  if endpoint.is_empty() {
+    return Err("Set telemetry_endpoint to upload usage statistics".into());
+  }
+
+  let rows = pending(&db::connect(app).map_err(|e| e.to_string())?)?;
+  if rows.is_empty() {
+    return Err("There are no usage statistics to upload".into());
+  }
+  let report = build_report(&rows, &app.package_info().version.to_string());
+  let payload = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+  if digest(&payload) != preview_digest {
+    return Err("The usage statistics changed since the preview; review them again before uploading".into());
+  }
+
+  let client = reqwest::Client::builder()
+    .timeout(REQUEST_TIMEOUT)
+    .build()
+    .map_err(|e| e.to_string())?;
+  let response = client.post(&endpoint)
+    .header("Content-Type", "application/json")
+    .body(payload.clone())
+    .send()
+    .await
+    .map_err(|e| format!("Telemetry upload failed: {}", e))?;
+  if !response.status().is_success() {
+    return Err(format!("Telemetry endpoint returned {}", response.status()));
+  }
+
+  // Runs that finished during the upload stay counted for the next one
+  let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  for (day, name, count) in &rows {
+    tx.execute(
+      "UPDATE telemetry_counters SET count = count - ?3 WHERE day = ?1 AND name = ?2",
+      (day, name, count)
+    ).map_err(|e| e.to_string())?;
+  }
+  tx.execute("DELETE FROM telemetry_counters WHERE count <= 0", []).map_err(|e| e.to_string())?;
+  let upload = TelemetryUpload { uploaded_at: now_iso(), endpoint, payload };
+  tx.execute(
+    "INSERT INTO telemetry_uploads (id, uploaded_at, endpoint, payload) VALUES (?1, ?2, ?3, ?4)",
+    (new_id(), &upload.uploaded_at, &upload.endpoint, &upload.payload)
+  ).map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())?;
+
+  tracing::info!(counters = report.counters.len(), "uploaded usage statistics");
+  Ok(upload)
+}
+
+/// Drop the counters recorded so far without sending them
+pub fn clear(app: &AppHandle) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute("DELETE FROM telemetry_counters", []).map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+fn today() -> String {
+  let now = time::OffsetDateTime::now_utc();
+  format!("{:04}-{:02}-{:02}", now.year(), u8::from(now.month()), now.day())
+}
+
+fn now_iso() -> String {
+  time::OffsetDateTime::now_utc()
+    .format(&time::format_description::well_known::Rfc3339)
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn event(run_type: &str, status: RunStatus, code: Option<&str>) -> RunEvent {
+    RunEvent {
+      run_id: "r1".into(),
+      project_id: "p1".into(),
+      task_id: "t1".into(),
+      run_type: run_type.into(),
+      status,
+      error: Some("failed reading /home/me/secret.rs".into()),
+      code: code.map(str::to_string),
+    }
+  }
+
+  #[test]
+  fn test_counters_and_report() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+       CREATE TABLE telemetry_counters (day TEXT NOT NULL, name TEXT NOT NULL, count INTEGER NOT NULL DEFAULT 0, PRIMARY KEY(day, name));"
+    ).unwrap();
+
+    // Nothing is counted until telemetry is enabled
+    let failed = event_counters(&event("plan", RunStatus::Failed, Some("OFFLINE")));
+    assert_eq!(failed, vec!["run.plan.failed", "failure.plan.OFFLINE"]);
+    record(&conn, "2024-05-01", &failed).unwrap();
+    assert!(pending(&conn).unwrap().is_empty());
+
+    conn.execute("INSERT INTO settings (key, value) VALUES ('telemetry_enabled', '1')", []).unwrap();
+    record(&conn, "2024-05-01", &failed).unwrap();
+    record(&conn, "2024-05-02", &failed).unwrap();
+    record(&conn, "2024-05-02", &event_counters(&event("verify", RunStatus::Finished, None))).unwrap();
+    assert!(event_counters(&event("verify", RunStatus::Started, None)).is_empty());
+    assert_eq!(event_counters(&event("re view!", RunStatus::Failed, None))[1], "failure.review.unknown");
+
+    let report = build_report(&pending(&conn).unwrap(), "1.2.3");
+    assert_eq!(report.from_day.as_deref(), Some("2024-05-01"));
+    assert_eq!(report.to_day.as_deref(), Some("2024-05-02"));
+    assert_eq!(report.counters, vec![
+      Counter { name: "failure.plan.OFFLINE".into(), count: 2 },
+      Counter { name: "run.plan.failed".into(), count: 2 },
+      Counter { name: "run.verify.finished".into(), count: 1 },
+    ]);
+    let payload = serde_json::to_string_pretty(&report).unwrap();
+    assert!(!payload.contains("secret"));
+    assert_eq!(digest(&payload).len(), 64);
+  }
+}
@@ -0,0 +1,158 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::commands::list_messages;
+use crate::models::{Message, ToolCallRow};
+use crate::repo_tools::list_tool_calls;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+  System,
+  User,
+  Assistant,
+  ToolCall,
+  ToolResult,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct TranscriptEntry {
+  /// Position in the timeline, starting at 0
+  pub index: usize,
+  pub kind: EntryKind,
+  /// Message id, or tool call id for tool_call/tool_result entries
+  pub source_id: String,
+  /// Tool name for tool_call/tool_result entries
+  pub tool_name: Option<String>,
+  /// Tool call sequence number within the run
+  pub seq: Option<i64>,
+  /// Message text, tool arguments JSON, or tool result JSON
+  pub content: String,
+  pub size_bytes: usize,
+  pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct RunTranscript {
+  pub run_id: String,
+  pub entries: Vec<TranscriptEntry>,
+  pub total_bytes: usize,
+}
+
+/// Timestamps are RFC 3339 with a variable number of fractional digits, so
+/// they don't sort correctly as strings
+fn timestamp(s: &str) -> Option<OffsetDateTime> {
+  OffsetDateTime::parse(s, &Rfc3339).ok()
+}
+
+fn message_kind(role: &str) -> EntryKind {
+  match role {
+    "system" => EntryKind::System,
+    "user" => EntryKind::User,
+    "tool" => EntryKind::ToolResult,
+    _ => EntryKind::Assistant,
+  }
+}
+
+/// Merge a run's messages and tool calls into one timeline. Tool calls keep
+/// their logged order; each becomes a call entry followed by its result.
+/// "tool" messages repeat those results as sent to the model, so they're
+/// only kept for runs whose tool calls weren't logged.
+fn merge(mut messages: Vec<Message>, tool_calls: Vec<ToolCallRow>) -> Vec<TranscriptEntry> {
+  if !tool_calls.is_empty() {
+    messages.retain(|m| m.role != "tool");
+  }
+  messages.sort_by_key(|m| timestamp(&m.created_at));
+
+  let mut entries = vec![];
+  let mut push = |kind, source_id: &str, tool_name: Option<&str>, seq, content: &str, created_at: &str| {
+    entries.push(TranscriptEntry {
+      index: entries.len(),
+      kind,
+      source_id: source_id.to_string(),
+      tool_name: tool_name.map(str::to_string),
+      seq,
+      content: content.to_string(),
+      size_bytes: content.len(),
+      created_at: created_at.to_string(),
+    });
+  };
+
+  let mut calls = tool_calls.into_iter().peekable();
+  for message in messages {
+    let at = timestamp(&message.created_at);
+    // Tool calls logged before this message; ties go to the message, which is
+    // logged before the calls it requests
+    while let Some(call) = calls.next_if(|c| timestamp(&c.created_at) < at) {
+      push(EntryKind::ToolCall, &call.id, Some(&call.name), Some(call.seq), &call.args_json, &call.created_at);
+      push(EntryKind::ToolResult, &call.id, Some(&call.name), Some(call.seq), &call.result_json, &call.created_at);
+    }
+    push(message_kind(&message.role), &message.id, None, None, &message.content, &message.created_at);
+  }
+  for call in calls {
+    push(EntryKind::ToolCall, &call.id, Some(&call.name), Some(call.seq), &call.args_json, &call.created_at);
+    push(EntryKind::ToolResult, &call.id, Some(&call.name), Some(call.seq), &call.result_json, &call.created_at);
+  }
+  entries
+}
+
+pub fn get_run_transcript(app: &AppHandle, run_id: &str) -> Result<RunTranscript, String> {
+  let messages = list_messages(app.clone(), run_id.to_string())?;
+  let tool_calls = list_tool_calls(app, run_id)?;
+  let entries = merge(messages, tool_calls);
+  let total_bytes = entries.iter().map(|e| e.size_bytes).sum();
+  Ok(RunTranscript { run_id: run_id.to_string(), entries, total_bytes })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn message(id: &str, role: &str, created_at: &str) -> Message {
+    Message { id: id.into(), run_id: "r1".into(), role: role.into(), content: format!("{} text", id), created_at: created_at.into() }
+  }
+
+  fn tool_call(id: &str, seq: i64, created_at: &str) -> ToolCallRow {
+    ToolCallRow {
+      id: id.into(),
+      run_id: "r1".into(),
+      name: "read_file".into(),
+      args_json: "{\"path\":\"a.rs\"}".into(),
+      result_json: "{\"content\":\"fn a() {}\"}".into(),
+      created_at: created_at.into(),
+      seq,
+    }
+  }
+
+  #[test]
+  fn test_merge_orders_timeline() {
+    let messages = vec![
+      message("answer", "assistant", "2026-03-01T09:00:03Z"),
+      message("sys", "system", "2026-03-01T09:00:00Z"),
+      message("ask", "assistant", "2026-03-01T09:00:01.5Z"),
+      message("result", "tool", "2026-03-01T09:00:02.9Z"),
+    ];
+    // Same timestamp, so seq decides their order
+    let calls = vec![
+      tool_call("c1", 1, "2026-03-01T09:00:01.75Z"),
+      tool_call("c2", 2, "2026-03-01T09:00:01.75Z"),
+    ];
+    let entries = merge(messages, calls);
+
+    let order: Vec<(EntryKind, &str)> = entries.iter().map(|e| (e.kind, e.source_id.as_str())).collect();
+    assert_eq!(order, vec![
+      (EntryKind::System, "sys"),
+      (EntryKind::Assistant, "ask"),
+      (EntryKind::ToolCall, "c1"),
+      (EntryKind::ToolResult, "c1"),
+      (EntryKind::ToolCall, "c2"),
+      (EntryKind::ToolResult, "c2"),
+      (EntryKind::Assistant, "answer"),
+    ]);
+    assert_eq!(entries[6].index, 6);
+    assert_eq!(entries[2].size_bytes, "{\"path\":\"a.rs\"}".len());
+  }
+}
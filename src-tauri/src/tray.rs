@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::commands::{list_projects, touch_project};
+use crate::events::{RunEvent, RunStatus, NAVIGATE};
+use crate::windows::current_task;
+use crate::workflows::verify::{verify_task, VerifyOptions};
+
+const TRAY_ID: &str = "main";
+const MAIN_WINDOW: &str = "main";
+
+/// Runs in flight (run id -> run type) and the menu line that summarizes them
+pub struct TrayState {
+  active_runs: Mutex<HashMap<String, String>>,
+  status_item: MenuItem<Wry>,
+}
+
+pub fn init_tray(app: &AppHandle) -> tauri::Result<()> {
+  let status_item = MenuItem::with_id(app, "status", status_text(0, None), false, None::<&str>)?;
+  let verify = MenuItem::with_id(app, "verify_current_task", "Verify current task", true, None::<&str>)?;
+  let open_last = MenuItem::with_id(app, "open_last_project", "Open last project", true, None::<&str>)?;
+  let show = MenuItem::with_id(app, "show", "Show SpecTrail", true, None::<&str>)?;
+  let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+  let menu = Menu::with_items(app, &[
+    &status_item,
+    &PredefinedMenuItem::separator(app)?,
+    &verify,
+    &open_last,
+    &PredefinedMenuItem::separator(app)?,
+    &show,
+    &quit,
+  ])?;
+
+  let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+    .menu(&menu)
+    .tooltip("SpecTrail")
+    .on_menu_event(handle_menu_event);
+  if let Some(icon) = app.default_window_icon() {
+    builder = builder.icon(icon.clone());
+  }
+  builder.build(app)?;
+
+  app.manage(TrayState { active_runs: Mutex::new(HashMap::new()), status_item });
+  Ok(())
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+  let result = match event.id().as_ref() {
+    "verify_current_task" => verify_current_task(app).map(|_| ()),
+    "open_last_project" => open_last_project(app).map(|_| ()),
+    "show" => show_main_window(app),
+    "quit" => {
+      app.exit(0);
+      Ok(())
+    }
+    _ => Ok(()),
+  };
+  if let Err(e) = result {
+    tracing::warn!(item = %event.id().as_ref(), "tray action failed: {}", e);
+    set_status(app, &e);
+  }
+}
+
+/// Start a verification of the task last opened in any window. Runs in the
+/// background; progress arrives through run events. Returns the task id.
+pub fn verify_current_task(app: &AppHandle) -> Result<String, String> {
+  let (project_id, task_id) = current_task(app).ok_or("No task is open to verify")?;
+  let handle = app.clone();
+  let spawned_task = task_id.clone();
+  tauri::async_runtime::spawn(async move {
+    if let Err(e) = verify_task(handle, project_id, spawned_task, VerifyOptions::default()).await {
+      tracing::warn!(code = %e.code, "tray verification failed: {}", e.message);
+    }
+  });
+  Ok(task_id)
+}
+
+/// Show the main window on the most recently opened project
+pub fn open_last_project(app: &AppHandle) -> Result<String, String> {
+  let project = list_projects(app.clone())?.into_iter().next().ok_or("No projects yet")?;
+  touch_project(app.clone(), project.id.clone())?;
+  show_main_window(app)?;
+
+  if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+    NAVIGATE.emit(&window, format!("/projects/{}", project.id))?;
+  }
+  Ok(project.id)
+}
+
+fn show_main_window(app: &AppHandle) -> Result<(), String> {
+  let window = app.get_webview_window(MAIN_WINDOW).ok_or("Main window is not available")?;
+  window.show().map_err(|e| e.to_string())?;
+  window.unminimize().map_err(|e| e.to_string())?;
+  window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Keep the tray badge in step with run events
+pub fn on_run_event(app: &AppHandle, event: &RunEvent) {
+  let Some(state) = app.try_state::<TrayState>() else {
+    return;
+  };
+  let (count, latest) = {
+    let mut active = state.active_runs.lock().unwrap();
+    match event.status {
+      RunStatus::Started => { active.insert(event.run_id.clone(), event.run_type.clone()); }
+      RunStatus::Finished | RunStatus::Failed | RunStatus::Cancelled => { active.remove(&event.run_id); }
+    }
+    (active.len(), active.values().next().cloned())
+  };
+
+  let text = match event.status {
+    RunStatus::Failed if count == 0 => format!("Last {} run failed", event.run_type),
+    _ => status_text(count, latest.as_deref()),
+  };
+  set_status(app, &text);
+
+  if let Some(tray) = app.tray_by_id(TRAY_ID) {
+    let badge = (count > 0).then(|| count.to_string());
+    let _ = tray.set_title(badge);
+    let _ = tray.set_tooltip(Some(format!("SpecTrail - {}", text)));
+  }
+}
+
+fn set_status(app: &AppHandle, text: &str) {
+  if let Some(state) = app.try_state::<TrayState>() {
+    let _ = state.status_item.set_text(text);
+  }
+}
+
+fn status_text(count: usize, run_type: Option<&str>) -> String {
+  match (count, run_type) {
+    (0, _) => "No runs in progress".to_string(),
+    (1, Some(run_type)) => format!("1 {} run in progress", run_type),
+    (n, _) => format!("{} runs in progress", n),
+  }
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::settings::cached_settings;
+
+pub const DEFAULT_CHANNEL: &str = "stable";
+
+/// Release metadata (`latest.json`) published for each channel
+const CHANNEL_ENDPOINTS: &[(&str, &str)] = &[
+  ("stable", "https://github.com/ThomasRogersF/spectrail/releases/latest/download/latest.json"),
+  ("beta", "https://github.com/ThomasRogersF/spectrail/releases/download/beta/latest.json"),
+];
+
+/// Public key for verifying update signatures, baked in by release builds.
+/// Dev builds can check for updates but installs will fail verification.
+const UPDATER_PUBKEY: Option<&str> = option_env!("SPECTRAIL_UPDATER_PUBKEY");
+
+#[derive(Debug, Serialize, Type)]
+pub struct UpdateInfo {
+  pub channel: String,
+  pub current_version: String,
+  pub available: bool,
+  pub version: Option<String>,
+  pub date: Option<String>,
+  /// Release notes for the available version
+  pub changelog: Option<String>,
+}
+
+pub fn plugin() -> tauri_plugin_updater::Builder {
+  let builder = tauri_plugin_updater::Builder::new();
+  match UPDATER_PUBKEY {
+    Some(pubkey) => builder.pubkey(pubkey),
+    None => builder,
+  }
+}
+
+pub fn channel_endpoint(channel: &str) -> Result<&'static str, String> {
+  CHANNEL_ENDPOINTS.iter()
+    .find(|(name, _)| *name == channel)
+    .map(|(_, url)| *url)
+    .ok_or_else(|| format!("Unknown update channel '{}' (expected stable or beta)", channel))
+}
+
+/// Query the configured channel. `update_endpoint` overrides the channel URL
+/// for self-hosted release feeds.
+async fn fetch_update(app: &AppHandle) -> Result<(String, Option<Update>), String> {
+  let settings = cached_settings(app).await?;
+  let channel = settings.get("update_channel")
+    .filter(|c| !c.is_empty())
+    .cloned()
+    .unwrap_or_else(|| DEFAULT_CHANNEL.to_string());
+
+  let endpoint = match settings.get("update_endpoint").filter(|u| !u.trim().is_empty()) {
+    Some(custom) => custom.trim().to_string(),
+    None => channel_endpoint(&channel)?.to_string(),
+  };
+  let url: Url = endpoint.parse().map_err(|e| format!("Invalid update endpoint '{}': {}", endpoint, e))?;
+
+  let updater = app.updater_builder()
+    .endpoints(vec![url])
+    .map_err(|e| e.to_string())?
+    .build()
+    .map_err(|e| e.to_string())?;
+  let update = updater.check().await.map_err(|e| e.to_string())?;
+  Ok((channel, update))
+}
+
+pub async fn check_for_updates(app: &AppHandle) -> Result<UpdateInfo, String> {
+  let (channel, update) = fetch_update(app).await?;
+  let current_version = app.package_info().version.to_string();
+
+  Ok(match update {
+    Some(update) => UpdateInfo {
+      channel,
+      current_version,
+      available: true,
+      version: Some(update.version.clone()),
+      date: update.date.and_then(|d| d.format(&time::format_description::well_known::Rfc3339).ok()),
+      changelog: update.body.clone(),
+    },
+    None => UpdateInfo {
+      channel,
+      current_version,
+      available: false,
+      version: None,
+      date: None,
+      changelog: None,
+    },
+  })
+}
+
+/// Download and install the available update, then restart into it
+pub async fn install_update(app: &AppHandle) -> Result<(), String> {
+  let (_, update) = fetch_update(app).await?;
+  let Some(update) = update else {
+    return Err("No update available".into());
+  };
+
+  tracing::info!(version = %update.version, "installing update");
+  update.download_and_install(|_, _| {}, || {}).await.map_err(|e| e.to_string())?;
+  app.restart()
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+use crate::commands::get_task;
+use crate::db;
+use crate::events::{self, Event, RunEvent};
+
+const TASK_WINDOW_PREFIX: &str = "task-";
+
+/// What a window is looking at. Unset fields match anything, so the main
+/// window can follow every project by subscribing with an empty scope.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowScope {
+  pub project_id: Option<String>,
+  pub task_id: Option<String>,
+}
+
+impl WindowScope {
+  fn matches(&self, project_id: &str, task_id: &str) -> bool {
+    self.project_id.as_deref().map_or(true, |p| p == project_id)
+      && self.task_id.as_deref().map_or(true, |t| t == task_id)
+  }
+}
+
+/// Window label -> scope. Windows that never subscribed receive no run events.
+#[derive(Default)]
+pub struct WindowSubscriptions {
+  scopes: Mutex<HashMap<String, WindowScope>>,
+  /// (project_id, task_id) of the task most recently shown in any window
+  current_task: Mutex<Option<(String, String)>>,
+}
+
+pub fn subscribe(app: &AppHandle, label: &str, scope: WindowScope) {
+  let subs = app.state::<WindowSubscriptions>();
+  if let (Some(project_id), Some(task_id)) = (&scope.project_id, &scope.task_id) {
+    *subs.current_task.lock().unwrap() = Some((project_id.clone(), task_id.clone()));
+  }
+  if let Err(e) = crate::sessions::open_view(app, label, scope.task_id.as_deref()) {
+    tracing::warn!(window = %label, "failed to record task session: {}", e);
+  }
+  subs.scopes.lock().unwrap().insert(label.to_string(), scope);
+}
+
+/// The task the user last had open, used by tray and shortcut actions
+pub fn current_task(app: &AppHandle) -> Option<(String, String)> {
+  app.try_state::<WindowSubscriptions>()?.current_task.lock().unwrap().clone()
+}
+
+pub fn unsubscribe(app: &AppHandle, label: &str) {
+  if let Some(subs) = app.try_state::<WindowSubscriptions>() {
+    subs.scopes.lock().unwrap().remove(label);
+  }
+  if let Err(e) = crate::sessions::close_view(app, label) {
+    tracing::warn!(window = %label, "failed to close task session: {}", e);
+  }
+}
+
+/// Update the tray status and task time log, then send a run event to each window whose scope
+/// covers the run's task.
+/// Best-effort: a closed window must not fail the workflow.
+pub fn emit_run_event(app: &AppHandle, event: RunEvent) {
+  crate::tray::on_run_event(app, &event);
+  crate::sessions::on_run_event(app, &event);
+  crate::telemetry::on_run_event(app, &event);
+
+  let (project_id, task_id) = (event.project_id.clone(), event.task_id.clone());
+  emit_to_subscribers(app, events::RUN, &project_id, &task_id, event);
+}
+
+/// Send an event about a task to each window whose scope covers it. Best-effort like
+/// `emit_run_event`.
+pub fn emit_to_subscribers<T: Serialize + Clone>(app: &AppHandle, event: Event<T>, project_id: &str, task_id: &str, payload: T) {
+  let Some(subs) = app.try_state::<WindowSubscriptions>() else {
+    return;
+  };
+  let labels: Vec<String> = subs.scopes.lock().unwrap().iter()
+    .filter(|(_, scope)| scope.matches(project_id, task_id))
+    .map(|(label, _)| label.clone())
+    .collect();
+
+  for label in labels {
+    if let Err(e) = event.emit_to(app, &label, payload.clone()) {
+      tracing::warn!(window = %label, "failed to emit {}: {}", event.name, e);
+    }
+  }
+}
+
+/// `emit_to_subscribers` for an event that only knows its run
+pub fn emit_for_run<T: Serialize + Clone>(app: &AppHandle, event: Event<T>, run_id: &str, payload: T) {
+  let scope = db::connect(app).map_err(|e| e.to_string()).and_then(|conn| {
+    conn.query_row(
+      "SELECT t.project_id, t.id FROM runs r JOIN tasks t ON t.id = r.task_id WHERE r.id = ?1",
+      [run_id],
+      |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
+    ).map_err(|e| e.to_string())
+  });
+  match scope {
+    Ok((project_id, task_id)) => emit_to_subscribers(app, event, &project_id, &task_id, payload),
+    Err(e) => tracing::warn!(run_id, "failed to find the run for {}: {}", event.name, e),
+  }
+}
+
+/// Open (or focus) a window dedicated to one task, routed straight to the task
+/// page and subscribed to that task's runs. Returns the window label.
+pub fn open_task_window(app: &AppHandle, task_id: &str) -> Result<String, String> {
+  let task = get_task(app.clone(), task_id.to_string())?;
+  let label = format!("{}{}", TASK_WINDOW_PREFIX, task.id);
+
+  if let Some(window) = app.get_webview_window(&label) {
+    window.set_focus().map_err(|e| e.to_string())?;
+    return Ok(label);
+  }
+
+  let route = format!("projects/{}/tasks/{}", task.project_id, task.id);
+  let window = WebviewWindowBuilder::new(app, label.as_str(), WebviewUrl::App(route.into()))
+    .title(format!("{} - SpecTrail", task.title))
+    .inner_size(1100.0, 760.0)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+  subscribe(app, &label, WindowScope {
+    project_id: Some(task.project_id.clone()),
+    task_id: Some(task.id.clone()),
+  });
+
+  let handle = app.clone();
+  let closed_label = label.clone();
+  window.on_window_event(move |event| {
+    if let WindowEvent::Destroyed = event {
+      unsubscribe(&handle, &closed_label);
+    }
+  });
+
+  Ok(label)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_scope_matching() {
+    let all = WindowScope::default();
+    assert!(all.matches("p1", "t1"));
+
+    let project = WindowScope { project_id: Some("p1".into()), task_id: None };
+    assert!(project.matches("p1", "t2"));
+    assert!(!project.matches("p2", "t2"));
+
+    let task = WindowScope { project_id: Some("p1".into()), task_id: Some("t1".into()) };
+    assert!(task.matches("p1", "t1"));
+    assert!(!task.matches("p1", "t2"));
+  }
+}
@@ -0,0 +1,116 @@
+use tauri::AppHandle;
+
+use crate::compression::StoredText;
+use crate::db;
+use crate::models::*;
+
+const CHUNK_CHARS: usize = 4_000;
+const MAX_ATTACHMENT_CONTEXT_CHARS: usize = 24_000;
+
+pub fn list_attachments(app: &AppHandle, task_id: &str) -> Result<Vec<TaskAttachment>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT id, task_id, name, source, source_path, content, created_at
+         FROM task_attachments WHERE task_id = ?1 ORDER BY created_at ASC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([task_id], |r| {
+        Ok(TaskAttachment {
+            id: r.get(0)?,
+            task_id: r.get(1)?,
+            name: r.get(2)?,
+            source: r.get(3)?,
+            source_path: r.get(4)?,
+            content: r.get::<_, StoredText>(5)?.0,
+            created_at: r.get(6)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut out = vec![];
+    for row in rows {
+        out.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
+
+/// Split text into chunks of at most `max_chars`, preferring paragraph then line breaks
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        if rest.chars().count() <= max_chars {
+            chunks.push(rest.to_string());
+            break;
+        }
+
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..limit];
+        let split = window.rfind("\n\n")
+            .or_else(|| window.rfind('\n'))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+
+        chunks.push(rest[..split].trim_end().to_string());
+        rest = rest[split..].trim_start();
+    }
+
+    chunks
+}
+
+/// Render a task's attachments as a prompt section, chunked and capped to a budget
+pub fn attachments_context(app: &AppHandle, task_id: &str) -> Result<Option<String>, String> {
+    let attachments = list_attachments(app, task_id)?;
+    if attachments.is_empty() {
+        return Ok(None);
+    }
+
+    let mut out = String::from("## Attached Context\n\nThe user attached the following material to this task.\n\n");
+    let mut used = 0;
+    let mut omitted = 0;
+
+    for attachment in &attachments {
+        let chunks = chunk_text(&attachment.content, CHUNK_CHARS);
+        let total = chunks.len();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            if used + chunk.len() > MAX_ATTACHMENT_CONTEXT_CHARS {
+                omitted += total - i;
+                break;
+            }
+            used += chunk.len();
+            if total > 1 {
+                out.push_str(&format!("### {} (part {}/{})\n\n", attachment.name, i + 1, total));
+            } else {
+                out.push_str(&format!("### {}\n\n", attachment.name));
+            }
+            out.push_str(&chunk);
+            out.push_str("\n\n");
+        }
+    }
+
+    if omitted > 0 {
+        out.push_str(&format!("*{} attachment chunk(s) omitted due to size limits.*\n\n", omitted));
+    }
+
+    Ok(Some(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text_prefers_paragraphs() {
+        let text = "first paragraph\n\nsecond paragraph that is longer";
+        let chunks = chunk_text(text, 20);
+        assert_eq!(chunks[0], "first paragraph");
+        assert!(chunks.iter().all(|c| c.chars().count() <= 20));
+        assert_eq!(chunks.concat().replace(' ', ""), text.replace(['\n', ' '], ""));
+    }
+
+    #[test]
+    fn test_chunk_text_short() {
+        assert_eq!(chunk_text("hello", 100), vec!["hello"]);
+        assert!(chunk_text("   ", 100).is_empty());
+    }
+}
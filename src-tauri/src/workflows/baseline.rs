@@ -0,0 +1,424 @@
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::cancellation::{self, RunCancellation};
+use crate::db;
+use crate::models::new_id;
+use crate::repo_tools::dispatch_repo_tool;
+use crate::repo_tools::safety::safe_spawn;
+use crate::repo_tools::test_results::parse_test_outcomes;
+use crate::settings::SettingsMap;
+use crate::workflows::compiler::{parse_compiler_output, parse_test_failures, CompilerDiagnostic};
+
+const DEFAULT_KINDS: [&str; 3] = ["tests", "lint", "build"];
+const STASH_MESSAGE: &str = "spectrail-baseline";
+/// Tests or diagnostics listed per group in the prompt
+const MAX_ITEMS_IN_PROMPT: usize = 30;
+
+/// How the pre-change state is checked out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BaselineMethod {
+    /// A detached worktree of the base commit in a temp directory
+    Worktree,
+    /// Stash the working tree changes in place and restore them afterwards
+    Stash,
+}
+
+impl BaselineMethod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "worktree" => Some(BaselineMethod::Worktree),
+            "stash" => Some(BaselineMethod::Stash),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BaselineMethod::Worktree => "worktree",
+            BaselineMethod::Stash => "stash",
+        }
+    }
+}
+
+/// Structured result of one check command
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+pub struct CheckResult {
+    /// tests|lint|build|typecheck
+    pub kind: String,
+    /// Exit code; None when the command couldn't run
+    pub code: Option<i64>,
+    pub error: Option<String>,
+    pub failed_tests: Vec<String>,
+    pub diagnostics: Vec<CompilerDiagnostic>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct Baseline {
+    pub id: String,
+    pub task_id: String,
+    pub run_id: String,
+    pub base_commit: String,
+    /// worktree|stash
+    pub method: String,
+    pub checks: Vec<CheckResult>,
+    pub created_at: String,
+}
+
+/// How the current checks differ from the task's baseline
+#[derive(Debug, Clone, Serialize, PartialEq, Type)]
+pub struct BaselineDelta {
+    pub baseline_id: String,
+    pub base_commit: String,
+    /// Kinds run both in the baseline and now
+    pub compared: Vec<String>,
+    pub newly_failing_tests: Vec<String>,
+    pub fixed_tests: Vec<String>,
+    pub errors_added: i64,
+    pub warnings_added: i64,
+    /// Diagnostics with no counterpart in the baseline
+    pub new_diagnostics: Vec<CompilerDiagnostic>,
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// Names of failed tests in cargo, pytest and jest/vitest output
+pub fn parse_failed_tests(output: &str) -> Vec<String> {
+    parse_test_outcomes(output).into_iter().filter(|o| !o.passed).map(|o| o.name).collect()
+}
+
+/// Structured result of a run_command call
+pub fn check_result(kind: &str, result: &Result<Value, String>) -> CheckResult {
+    let mut check = CheckResult {
+        kind: kind.to_string(),
+        code: None,
+        error: None,
+        failed_tests: vec![],
+        diagnostics: vec![],
+    };
+    match result {
+        Ok(value) => {
+            let output = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let output = format!("{}\n{}", output("stdout"), output("stderr"));
+            check.code = value.get("code").and_then(|c| c.as_i64());
+            if kind == "tests" {
+                check.failed_tests = parse_failed_tests(&output);
+                check.diagnostics = parse_test_failures(&output);
+            } else {
+                check.diagnostics = parse_compiler_output(&output);
+            }
+        }
+        Err(e) => check.error = Some(e.clone()),
+    }
+    check
+}
+
+/// Diagnostics are matched on everything but their position, which edits shift
+fn diagnostic_key(d: &CompilerDiagnostic) -> (&str, &str, Option<&str>, &str) {
+    (d.path.as_str(), d.severity.as_str(), d.code.as_deref(), d.message.as_str())
+}
+
+/// Compare current checks against the baseline's, kind by kind. None when no
+/// kind ran successfully in both.
+pub fn compare(baseline: &Baseline, current: &[CheckResult]) -> Option<BaselineDelta> {
+    let mut delta = BaselineDelta {
+        baseline_id: baseline.id.clone(),
+        base_commit: baseline.base_commit.clone(),
+        compared: vec![],
+        newly_failing_tests: vec![],
+        fixed_tests: vec![],
+        errors_added: 0,
+        warnings_added: 0,
+        new_diagnostics: vec![],
+    };
+    for now in current.iter().filter(|c| c.error.is_none()) {
+        let Some(before) = baseline.checks.iter().find(|b| b.kind == now.kind && b.error.is_none()) else { continue };
+        delta.compared.push(now.kind.clone());
+
+        delta.newly_failing_tests.extend(now.failed_tests.iter().filter(|t| !before.failed_tests.contains(t)).cloned());
+        delta.fixed_tests.extend(before.failed_tests.iter().filter(|t| !now.failed_tests.contains(t)).cloned());
+
+        let count = |checks: &[CompilerDiagnostic], severity: &str| checks.iter().filter(|d| d.severity == severity).count() as i64;
+        delta.errors_added += count(&now.diagnostics, "error") - count(&before.diagnostics, "error");
+        delta.warnings_added += count(&now.diagnostics, "warning") - count(&before.diagnostics, "warning");
+
+        let mut remaining: HashMap<_, usize> = HashMap::new();
+        for d in &before.diagnostics {
+            *remaining.entry(diagnostic_key(d)).or_default() += 1;
+        }
+        for d in &now.diagnostics {
+            match remaining.get_mut(&diagnostic_key(d)) {
+                Some(n) if *n > 0 => *n -= 1,
+                _ => delta.new_diagnostics.push(d.clone()),
+            }
+        }
+    }
+    (!delta.compared.is_empty()).then_some(delta)
+}
+
+/// Prompt section body describing the delta
+pub fn format_delta(delta: &BaselineDelta) -> String {
+    let short_commit: String = delta.base_commit.chars().take(10).collect();
+    let mut lines = vec![format!(
+        "Compared {} against baseline {}: {:+} errors, {:+} warnings.",
+        delta.compared.join(", "), short_commit, delta.errors_added, delta.warnings_added
+    )];
+    let list = |title: &str, items: &[String], lines: &mut Vec<String>| {
+        if items.is_empty() {
+            return;
+        }
+        lines.push(format!("{} ({}):", title, items.len()));
+        lines.extend(items.iter().take(MAX_ITEMS_IN_PROMPT).map(|t| format!("- {}", t)));
+    };
+    list("Newly failing tests", &delta.newly_failing_tests, &mut lines);
+    list("Tests fixed by the change", &delta.fixed_tests, &mut lines);
+    if delta.newly_failing_tests.is_empty() {
+        lines.push("No tests newly failing; any other failures also failed before the change.".into());
+    }
+    let diagnostics: Vec<String> = delta.new_diagnostics.iter()
+        .map(|d| format!("{}:{} {}: {}", d.path, d.line, d.severity, d.message))
+        .collect();
+    list("Diagnostics not in the baseline", &diagnostics, &mut lines);
+    lines.join("\n")
+}
+
+fn baseline_method(app: &AppHandle, settings: &SettingsMap, project_id: &str) -> Result<BaselineMethod, String> {
+    let project_value = db::get_project_setting(app, project_id, "baseline_method")
+        .map_err(|e| e.to_string())?;
+    Ok(project_value.as_deref()
+        .or_else(|| settings.get("baseline_method").map(String::as_str))
+        .and_then(BaselineMethod::parse)
+        .unwrap_or(BaselineMethod::Worktree))
+}
+
+async fn git(repo_path: &Path, args: &[&str]) -> Result<String, String> {
+    let (stdout, stderr, code) = safe_spawn("git", args, repo_path, 60).await.map_err(|e| e.to_string())?;
+    if code != 0 {
+        return Err(format!("git {} failed: {}", args.first().unwrap_or(&""), stderr.trim()));
+    }
+    Ok(stdout.trim().to_string())
+}
+
+/// Commit the baseline checks out: the merge-base with `baseline_ref` when set,
+/// otherwise HEAD (the state verify diffs the working tree against)
+async fn base_commit(app: &AppHandle, repo_path: &Path, project_id: &str) -> Result<String, String> {
+    let base_ref = db::get_project_setting(app, project_id, "baseline_ref")
+        .map_err(|e| e.to_string())?
+        .filter(|r| !r.trim().is_empty());
+    match base_ref {
+        Some(base_ref) => git(repo_path, &["merge-base", "HEAD", base_ref.trim()]).await,
+        None => git(repo_path, &["rev-parse", "HEAD"]).await,
+    }
+}
+
+/// Stops early once the run is cancelled; cleanup like restoring the stash still runs
+async fn run_checks(app: &AppHandle, cancellation: &RunCancellation, run_id: &str, dir: &Path, kinds: &[String]) -> Vec<CheckResult> {
+    let mut checks = vec![];
+    for kind in kinds {
+        if cancellation.is_cancelled() {
+            break;
+        }
+        let result = dispatch_repo_tool("run_command", &json!({ "kind": kind }), dir, app, run_id).await;
+        checks.push(check_result(kind, &result));
+    }
+    checks
+}
+
+/// Check out `commit` into a temporary worktree and run the checks there.
+/// Installed JS dependencies are linked in since they aren't tracked.
+async fn run_in_worktree(app: &AppHandle, cancellation: &RunCancellation, run_id: &str, repo_path: &Path, commit: &str, kinds: &[String]) -> Result<Vec<CheckResult>, String> {
+    let dir: PathBuf = std::env::temp_dir().join(format!("spectrail-baseline-{}", run_id));
+    let dir_str = dir.to_string_lossy().to_string();
+    git(repo_path, &["worktree", "add", "--detach", &dir_str, commit]).await?;
+    #[cfg(unix)]
+    if repo_path.join("node_modules").is_dir() {
+        let _ = std::os::unix::fs::symlink(repo_path.join("node_modules"), dir.join("node_modules"));
+    }
+
+    let checks = run_checks(app, cancellation, run_id, &dir, kinds).await;
+
+    if let Err(e) = git(repo_path, &["worktree", "remove", "--force", &dir_str]).await {
+        tracing::warn!("failed to remove baseline worktree {}: {}", dir_str, e);
+    }
+    Ok(checks)
+}
+
+/// Stash the working tree changes, run the checks on HEAD, then restore them
+async fn run_stashed(app: &AppHandle, cancellation: &RunCancellation, run_id: &str, repo_path: &Path, kinds: &[String]) -> Result<Vec<CheckResult>, String> {
+    let before = git(repo_path, &["stash", "list"]).await?;
+    git(repo_path, &["stash", "push", "--include-untracked", "-m", STASH_MESSAGE]).await?;
+    // Nothing to stash: HEAD is already the pre-change state
+    let stashed = git(repo_path, &["stash", "list"]).await? != before;
+
+    let checks = run_checks(app, cancellation, run_id, repo_path, kinds).await;
+
+    if stashed {
+        git(repo_path, &["stash", "pop", "--index"]).await.map_err(|e| format!(
+            "Baseline checks ran but the changes could not be restored; they are kept in the stash as '{}': {}",
+            STASH_MESSAGE, e
+        ))?;
+    }
+    Ok(checks)
+}
+
+fn create_run(app: &AppHandle, task_id: &str) -> Result<String, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let id = new_id();
+    conn.execute(
+        "INSERT INTO runs (id, task_id, run_type, started_at) VALUES (?1, ?2, 'baseline', ?3)",
+        (&id, task_id, now_iso())
+    ).map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+fn finish_run(app: &AppHandle, run_id: &str, error: Option<&str>, cancelled: bool) {
+    if let Ok(conn) = db::connect(app) {
+        let now = now_iso();
+        let _ = conn.execute(
+            "UPDATE runs SET ended_at = ?1, error = ?2, cancelled_at = ?3 WHERE id = ?4",
+            (&now, error, cancelled.then_some(&now), run_id)
+        );
+    }
+}
+
+fn save_baseline(app: &AppHandle, baseline: &Baseline) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let checks_json = serde_json::to_string(&baseline.checks).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO baselines (id, task_id, run_id, base_commit, method, checks_json, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        (&baseline.id, &baseline.task_id, &baseline.run_id, &baseline.base_commit, &baseline.method, checks_json, &baseline.created_at)
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Run the task's checks on the pre-change state and store the results for
+/// verify to compare against
+pub async fn capture_baseline(app: &AppHandle, settings: &SettingsMap, task_id: &str, kinds: Option<Vec<String>>) -> Result<Baseline, String> {
+    let (project_id, repo_path): (String, String) = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT p.id, p.repo_path FROM tasks t JOIN projects p ON p.id = t.project_id WHERE t.id = ?1",
+            [task_id],
+            |r| Ok((r.get(0)?, r.get(1)?))
+        ).optional().map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Task {} not found", task_id))?
+    };
+    let repo_path = Path::new(&repo_path);
+    let kinds = kinds.filter(|k| !k.is_empty())
+        .unwrap_or_else(|| DEFAULT_KINDS.iter().map(|k| k.to_string()).collect());
+    let method = baseline_method(app, settings, &project_id)?;
+    let commit = base_commit(app, repo_path, &project_id).await?;
+
+    let run_id = create_run(app, task_id)?;
+    let cancellation = cancellation::register(app, &run_id);
+    let result = async {
+        let checks = match method {
+            BaselineMethod::Worktree => run_in_worktree(app, &cancellation, &run_id, repo_path, &commit, &kinds).await?,
+            BaselineMethod::Stash => {
+                if commit != git(repo_path, &["rev-parse", "HEAD"]).await? {
+                    return Err("The stash method can only check HEAD; use the worktree method with baseline_ref".to_string());
+                }
+                run_stashed(app, &cancellation, &run_id, repo_path, &kinds).await?
+            }
+        };
+        // Checks cut short by a cancel aren't a baseline to compare against
+        cancellation.check()?;
+        let baseline = Baseline {
+            id: new_id(),
+            task_id: task_id.to_string(),
+            run_id: run_id.clone(),
+            base_commit: commit.clone(),
+            method: method.as_str().to_string(),
+            checks,
+            created_at: now_iso(),
+        };
+        save_baseline(app, &baseline)?;
+        Ok(baseline)
+    }.await;
+    let was_cancelled = result.is_err() && cancellation.is_cancelled();
+    finish_run(app, &run_id, result.as_ref().err().map(String::as_str), was_cancelled);
+    result
+}
+
+/// The task's most recent baseline
+pub fn latest_baseline(app: &AppHandle, task_id: &str) -> Result<Option<Baseline>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let row: Option<(String, String, String, String, String, String)> = conn.query_row(
+        "SELECT id, run_id, base_commit, method, checks_json, created_at FROM baselines
+         WHERE task_id = ?1 ORDER BY created_at DESC LIMIT 1",
+        [task_id],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?))
+    ).optional().map_err(|e| e.to_string())?;
+    let Some((id, run_id, base_commit, method, checks_json, created_at)) = row else { return Ok(None) };
+    Ok(Some(Baseline {
+        id,
+        task_id: task_id.to_string(),
+        run_id,
+        base_commit,
+        method,
+        checks: serde_json::from_str(&checks_json).map_err(|e| e.to_string())?,
+        created_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_with_baseline() {
+        let tests_before = json!({
+            "stdout": "test a::works ... ok\ntest a::flaky ... FAILED\ntest a::old_bug ... FAILED\n",
+            "stderr": "", "code": 101,
+        });
+        let lint_before = json!({ "stdout": "", "stderr": "warning: unused variable: `y`\n --> src/main.rs:4:9\n", "code": 0 });
+        let baseline = Baseline {
+            id: "b1".into(),
+            task_id: "t1".into(),
+            run_id: "r1".into(),
+            base_commit: "0123456789abcdef".into(),
+            method: "worktree".into(),
+            checks: vec![
+                check_result("tests", &Ok(tests_before)),
+                check_result("lint", &Ok(lint_before)),
+                check_result("build", &Err("Timeout".into())),
+            ],
+            created_at: "2026-01-01T00:00:00Z".into(),
+        };
+        assert_eq!(baseline.checks[0].failed_tests, vec!["a::flaky", "a::old_bug"]);
+
+        let tests_now = json!({ "stdout": "test a::works ... FAILED\ntest a::old_bug ... FAILED\n", "stderr": "", "code": 101 });
+        let lint_now = json!({
+            "stdout": "",
+            "stderr": "warning: unused variable: `y`\n --> src/main.rs:6:9\nerror: this comparison is always true\n --> src/lib.rs:2:4\n",
+            "code": 1,
+        });
+        let current = vec![
+            check_result("tests", &Ok(tests_now)),
+            check_result("lint", &Ok(lint_now)),
+            check_result("build", &Ok(json!({ "stdout": "", "stderr": "", "code": 0 }))),
+        ];
+        let delta = compare(&baseline, &current).unwrap();
+        assert_eq!(delta.compared, vec!["tests", "lint"]);
+        assert_eq!(delta.newly_failing_tests, vec!["a::works"]);
+        assert_eq!(delta.fixed_tests, vec!["a::flaky"]);
+        assert_eq!((delta.errors_added, delta.warnings_added), (1, 0));
+        assert_eq!(delta.new_diagnostics.len(), 1);
+        assert_eq!(delta.new_diagnostics[0].path, "src/lib.rs");
+        assert!(format_delta(&delta).starts_with("Compared tests, lint against baseline 0123456789: +1 errors, +0 warnings."));
+
+        assert_eq!(parse_failed_tests("FAILED tests/test_api.py::test_login - assert 1 == 2\n  ✕ renders header (12 ms)\n"),
+            vec!["tests/test_api.py::test_login", "renders header"]);
+        assert!(compare(&baseline, &current[2..]).is_none());
+    }
+}
@@ -0,0 +1,228 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::events::BUDGET_WARNING;
+use crate::llm::Usage;
+use crate::models::new_id;
+use crate::settings::SettingsMap;
+
+const WARN_FRACTION: f64 = 0.8;
+/// Project setting holding the month ("YYYY-MM") the user agreed to run over budget
+const OVERRIDE_SETTING: &str = "budget_override_month";
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Type)]
+pub struct BudgetStatus {
+    pub project_id: String,
+    /// Calendar month (UTC) the usage covers, "YYYY-MM"
+    pub month: String,
+    pub tokens_used: i64,
+    pub cost_usd: f64,
+    pub token_limit: Option<i64>,
+    pub usd_limit: Option<f64>,
+    /// Highest share of any configured budget spent; None without budgets
+    pub fraction: Option<f64>,
+    pub state: String, // ok|warning|exceeded
+    pub enforcement: String, // confirm|block
+    /// Whether the user confirmed running over budget this month
+    pub confirmed: bool,
+}
+
+/// A run refused by the budget gate, with the error code workflows report
+#[derive(Debug)]
+pub struct BudgetBlock {
+    pub code: &'static str,
+    pub message: String,
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn current_month() -> String {
+    let now = time::OffsetDateTime::now_utc();
+    format!("{:04}-{:02}", now.year(), now.month() as u8)
+}
+
+/// Project override, falling back to the global setting
+fn setting(app: &AppHandle, settings: &SettingsMap, project_id: &str, key: &str) -> Result<Option<String>, String> {
+    let value = db::get_project_setting(app, project_id, key).map_err(|e| e.to_string())?
+        .or_else(|| settings.get(key).cloned());
+    Ok(value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()))
+}
+
+/// Estimated cost of `usage` at the configured per-million-token prices
+pub fn usage_cost(settings: &SettingsMap, usage: &Usage) -> f64 {
+    let price = |key: &str| settings.get(key).and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+    (usage.prompt_tokens as f64 * price("price_input_per_mtok")
+        + usage.completion_tokens as f64 * price("price_output_per_mtok")) / 1_000_000.0
+}
+
+/// Add one LLM request's usage to its run. Best-effort: accounting must not fail the run.
+pub fn record_usage(app: &AppHandle, settings: &SettingsMap, run_id: &str, usage: Option<Usage>) {
+    let Some(usage) = usage else {
+        return;
+    };
+    let result = db::connect(app).map_err(|e| e.to_string()).and_then(|conn| {
+        conn.execute(
+            "UPDATE runs SET prompt_tokens = prompt_tokens + ?1, completion_tokens = completion_tokens + ?2,
+             cost_usd = cost_usd + ?3 WHERE id = ?4",
+            (usage.prompt_tokens, usage.completion_tokens, usage_cost(settings, &usage), run_id)
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        tracing::warn!(run_id, "failed to record token usage: {}", e);
+    }
+}
+
+/// Usage of a project-level workflow, which has no run to carry it. Best-effort like `record_usage`.
+pub fn record_project_usage(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    project_id: &str,
+    workflow: &str,
+    provider: &str,
+    model: &str,
+    usage: Option<Usage>,
+) {
+    let Some(usage) = usage else {
+        return;
+    };
+    let result = db::connect(app).map_err(|e| e.to_string()).and_then(|conn| {
+        conn.execute(
+            "INSERT INTO project_usage (id, project_id, workflow, provider, model, prompt_tokens, completion_tokens, cost_usd, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                new_id(), project_id, workflow, Some(provider).filter(|p| !p.is_empty()), Some(model).filter(|m| !m.is_empty()),
+                usage.prompt_tokens, usage.completion_tokens, usage_cost(settings, &usage), now_iso()
+            ]
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        tracing::warn!(project_id, workflow, "failed to record token usage: {}", e);
+    }
+}
+
+fn classify(tokens_used: i64, cost_usd: f64, token_limit: Option<i64>, usd_limit: Option<f64>) -> (Option<f64>, &'static str) {
+    let fractions = [
+        token_limit.filter(|l| *l > 0).map(|l| tokens_used as f64 / l as f64),
+        usd_limit.filter(|l| *l > 0.0).map(|l| cost_usd / l),
+    ];
+    let fraction = fractions.into_iter().flatten().reduce(f64::max);
+    let state = match fraction {
+        Some(f) if f >= 1.0 => "exceeded",
+        Some(f) if f >= WARN_FRACTION => "warning",
+        _ => "ok",
+    };
+    (fraction, state)
+}
+
+pub fn budget_status(app: &AppHandle, settings: &SettingsMap, project_id: &str) -> Result<BudgetStatus, String> {
+    let month = current_month();
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let (tokens_used, cost_usd): (i64, f64) = conn.query_row(
+        "SELECT COALESCE(SUM(tokens), 0), COALESCE(SUM(cost_usd), 0) FROM (
+           SELECT r.prompt_tokens + r.completion_tokens AS tokens, r.cost_usd
+           FROM runs r JOIN tasks t ON t.id = r.task_id
+           WHERE t.project_id = ?1 AND substr(r.started_at, 1, 7) = ?2
+           -- Project-level workflows have no run; their usage is kept separately
+           UNION ALL
+           SELECT prompt_tokens + completion_tokens, cost_usd FROM project_usage
+           WHERE project_id = ?1 AND substr(created_at, 1, 7) = ?2
+         )",
+        (project_id, &month),
+        |r| Ok((r.get(0)?, r.get(1)?))
+    ).map_err(|e| e.to_string())?;
+
+    let token_limit = setting(app, settings, project_id, "budget_monthly_tokens")?.and_then(|v| v.parse().ok());
+    let usd_limit = setting(app, settings, project_id, "budget_monthly_usd")?.and_then(|v| v.parse().ok());
+    let enforcement = match setting(app, settings, project_id, "budget_enforcement")?.as_deref() {
+        Some("block") => "block",
+        _ => "confirm",
+    };
+    let confirmed = db::get_project_setting(app, project_id, OVERRIDE_SETTING)
+        .map_err(|e| e.to_string())?.as_deref() == Some(month.as_str());
+    let (fraction, state) = classify(tokens_used, cost_usd, token_limit, usd_limit);
+
+    Ok(BudgetStatus {
+        project_id: project_id.to_string(),
+        month,
+        tokens_used,
+        cost_usd,
+        token_limit,
+        usd_limit,
+        fraction,
+        state: state.into(),
+        enforcement: enforcement.into(),
+        confirmed,
+    })
+}
+
+/// Let runs continue past the budget for the rest of this month
+pub fn confirm_budget_overrun(app: &AppHandle, project_id: &str) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO project_settings (project_id, key, value, updated_at) VALUES (?1, ?2, ?3, datetime('now'))
+         ON CONFLICT(project_id, key) DO UPDATE SET value=excluded.value, updated_at=excluded.updated_at",
+        (project_id, OVERRIDE_SETTING, current_month())
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Gate a new LLM run on the project's monthly budget: warn from 80%, and past
+/// 100% either block or require the user to confirm the overrun first
+pub fn check_run_budget(app: &AppHandle, settings: &SettingsMap, project_id: &str) -> Result<(), BudgetBlock> {
+    let status = budget_status(app, settings, project_id)
+        .map_err(|e| BudgetBlock { code: "DB_ERROR", message: e })?;
+    let spent = || match (status.token_limit, status.usd_limit) {
+        (_, Some(usd)) if status.cost_usd >= usd => format!("${:.2} of the ${:.2} monthly budget", status.cost_usd, usd),
+        (Some(tokens), _) => format!("{} of the {} monthly token budget", status.tokens_used, tokens),
+        _ => format!("${:.2} this month", status.cost_usd),
+    };
+
+    match status.state.as_str() {
+        "exceeded" if status.enforcement == "block" => Err(BudgetBlock {
+            code: "BUDGET_EXCEEDED",
+            message: format!("This project has used {}; runs are blocked until next month or the budget is raised", spent()),
+        }),
+        "exceeded" if !status.confirmed => Err(BudgetBlock {
+            code: "BUDGET_CONFIRMATION_REQUIRED",
+            message: format!("This project has used {}; confirm to keep running this month", spent()),
+        }),
+        "warning" | "exceeded" => {
+            tracing::warn!(project_id, fraction = ?status.fraction, "project near or over its monthly budget");
+            let _ = BUDGET_WARNING.emit(app, status.clone());
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_budget() {
+        assert_eq!(classify(500, 0.0, None, None), (None, "ok"));
+        assert_eq!(classify(500, 0.0, Some(1000), None).1, "ok");
+        assert_eq!(classify(800, 0.0, Some(1000), None).1, "warning");
+        // The tighter of the two budgets wins
+        assert_eq!(classify(100, 5.0, Some(1000), Some(5.0)).1, "exceeded");
+        assert_eq!(classify(100, 5.0, Some(0), None).1, "ok");
+    }
+
+    #[test]
+    fn test_usage_cost() {
+        let settings: SettingsMap = std::sync::Arc::new([
+            ("price_input_per_mtok".to_string(), "3".to_string()),
+            ("price_output_per_mtok".to_string(), "15".to_string()),
+        ].into_iter().collect());
+        let cost = usage_cost(&settings, &Usage { prompt_tokens: 1_000_000, completion_tokens: 100_000 });
+        assert!((cost - 4.5).abs() < 1e-9);
+    }
+}
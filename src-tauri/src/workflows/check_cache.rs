@@ -0,0 +1,119 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::blobs;
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::safety::safe_spawn;
+
+/// Lockfiles hashed even when they're gitignored, since they decide what a run installs
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock", "package-lock.json", "pnpm-lock.yaml", "yarn.lock", "poetry.lock", "uv.lock", "Pipfile.lock",
+];
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn fingerprint_of(head: &str, diff: &str, files: &[(String, Vec<u8>)]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(head.trim().as_bytes());
+    hasher.update([0]);
+    hasher.update(diff.as_bytes());
+    for (path, content) in files {
+        hasher.update([0]);
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hash of the repository state checks depend on: HEAD, staged and unstaged
+/// changes, untracked files and lockfiles
+pub async fn repo_fingerprint(repo_path: &Path) -> Result<String, String> {
+    // Untracked files and lockfiles are hashed locally
+    if remote_repo(repo_path).is_some() {
+        return Err("check results aren't reused for remote repositories".to_string());
+    }
+    let git = |args: &'static [&'static str]| safe_spawn("git", args, repo_path, 30);
+    let (head, stderr, code) = git(&["rev-parse", "HEAD"]).await.map_err(|e| e.to_string())?;
+    if code != 0 {
+        return Err(format!("git rev-parse failed: {}", stderr.trim()));
+    }
+    let (diff, _, _) = git(&["diff", "HEAD", "--binary"]).await.map_err(|e| e.to_string())?;
+    let (untracked, _, _) = git(&["ls-files", "--others", "--exclude-standard", "-z"]).await.map_err(|e| e.to_string())?;
+
+    let mut paths: Vec<&str> = untracked.split('\0').filter(|p| !p.is_empty()).collect();
+    paths.extend(LOCKFILES);
+    paths.sort_unstable();
+    paths.dedup();
+    let files: Vec<(String, Vec<u8>)> = paths.into_iter()
+        .filter_map(|p| std::fs::read(repo_path.join(p)).ok().map(|content| (p.to_string(), content)))
+        .collect();
+    Ok(fingerprint_of(&head, &diff, &files))
+}
+
+/// A passing run_command result with the same key on the same repository state.
+/// The key holds the call's arguments and what they resolved to.
+pub fn find_passing(conn: &Connection, project_id: &str, args: &Value, fingerprint: &str) -> Result<Option<Value>, String> {
+    let stored = conn.query_row(
+        "SELECT tc.result_json, tc.result_hash FROM check_fingerprints cf
+         JOIN tool_calls tc ON tc.id = cf.tool_call_id
+         WHERE cf.project_id = ?1 AND cf.args_key = ?2 AND cf.fingerprint = ?3",
+        (project_id, args.to_string(), fingerprint),
+        |r| blobs::hydrate(conn, r.get(0)?, r.get(1)?)
+    ).optional().map_err(|e| e.to_string())?;
+    // Results cut short when they were logged can't stand in for a run
+    Ok(stored
+        .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+        .filter(|result| result.get("_truncated").is_none())
+        .map(|mut result| {
+            result["reused"] = json!(true);
+            result
+        }))
+}
+
+/// Remember the run's latest run_command call, which passed, for reuse on the same state
+pub fn remember_passing(conn: &Connection, project_id: &str, run_id: &str, args: &Value, fingerprint: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO check_fingerprints (project_id, args_key, fingerprint, tool_call_id, created_at)
+         SELECT ?1, ?2, ?3, id, ?4 FROM tool_calls WHERE run_id = ?5 AND name = 'run_command'
+         ORDER BY seq DESC LIMIT 1",
+        (project_id, args.to_string(), fingerprint, now_iso(), run_id)
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuse_passing_checks() {
+        let base = fingerprint_of("abc123\n", "", &[("Cargo.lock".into(), b"v1".to_vec())]);
+        assert_eq!(base, fingerprint_of("abc123", "", &[("Cargo.lock".into(), b"v1".to_vec())]));
+        assert_ne!(base, fingerprint_of("abc123", "", &[("Cargo.lock".into(), b"v2".to_vec())]));
+        assert_ne!(base, fingerprint_of("abc123", "+fn main() {}", &[("Cargo.lock".into(), b"v1".to_vec())]));
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE projects (id TEXT PRIMARY KEY); INSERT INTO projects VALUES ('p1');
+             CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT, updated_at TEXT);
+             CREATE TABLE tool_calls (id TEXT PRIMARY KEY, run_id TEXT, name TEXT, result_json TEXT, result_hash TEXT, seq INTEGER);
+             INSERT INTO tool_calls VALUES ('tc1', 'r1', 'run_command', '{\"code\":0,\"stdout\":\"ok\"}', NULL, 1);"
+        ).unwrap();
+        conn.execute_batch(include_str!("../../migrations/043_check_fingerprints.sql")).unwrap();
+
+        let tests = json!({ "kind": "tests" });
+        assert!(find_passing(&conn, "p1", &tests, &base).unwrap().is_none());
+        remember_passing(&conn, "p1", "r1", &tests, &base).unwrap();
+        let reused = find_passing(&conn, "p1", &tests, &base).unwrap().unwrap();
+        assert_eq!((reused["code"].as_i64(), reused["reused"].as_bool()), (Some(0), Some(true)));
+        assert!(find_passing(&conn, "p1", &json!({ "kind": "lint" }), &base).unwrap().is_none());
+    }
+}
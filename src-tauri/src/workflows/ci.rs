@@ -0,0 +1,395 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::io::Read;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::models::new_id;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_CI_BYTES: usize = 20_000_000;
+/// Log lines kept before a GitHub Actions error as its details
+const ERROR_CONTEXT_LINES: usize = 15;
+const MAX_FAILURES_IN_PROMPT: usize = 50;
+const MAX_DETAIL_CHARS: usize = 1_500;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+pub struct CiFailure {
+    /// Test suite, or the workflow step for GitHub Actions logs
+    pub suite: Option<String>,
+    pub name: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CiResults {
+    pub id: String,
+    pub task_id: String,
+    pub source: String,
+    pub format: String, // junit|github_actions
+    /// Number of test cases; unknown for job logs
+    pub tests: Option<usize>,
+    pub skipped: usize,
+    pub failures: Vec<CiFailure>,
+    pub created_at: String,
+}
+
+/// Failures and counts parsed from a CI report, before it's stored
+#[derive(Debug, PartialEq)]
+struct ParsedCi {
+    format: &'static str,
+    tests: Option<usize>,
+    skipped: usize,
+    failures: Vec<CiFailure>,
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn too_large(len: u64) -> String {
+    format!("CI results are too large ({} bytes, max {})", len, MAX_CI_BYTES)
+}
+
+/// Read at most MAX_CI_BYTES, refusing anything larger before it's all in memory
+async fn read_source(path_or_url: &str) -> Result<String, String> {
+    let limit = MAX_CI_BYTES as u64;
+    let bytes = if path_or_url.starts_with("http://") || path_or_url.starts_with("https://") {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut response = client.get(path_or_url)
+            .send()
+            .await
+            .map_err(|e| format!("Cannot download CI results: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("CI results URL returned {}", response.status()));
+        }
+        if let Some(len) = response.content_length().filter(|len| *len > limit) {
+            return Err(too_large(len));
+        }
+        // The header can be missing or wrong, so the body is capped as it arrives
+        let mut bytes = vec![];
+        while let Some(chunk) = response.chunk().await.map_err(|e| format!("Cannot download CI results: {}", e))? {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > MAX_CI_BYTES {
+                return Err(format!("CI results are too large (over {} bytes)", MAX_CI_BYTES));
+            }
+        }
+        bytes
+    } else {
+        let read_error = |e: std::io::Error| format!("Cannot read CI results: {}", e);
+        let file = std::fs::File::open(path_or_url).map_err(read_error)?;
+        let len = file.metadata().map_err(read_error)?.len();
+        if len > limit {
+            return Err(too_large(len));
+        }
+        // The file may grow after the size check
+        let mut bytes = vec![];
+        file.take(limit + 1).read_to_end(&mut bytes).map_err(read_error)?;
+        if bytes.len() > MAX_CI_BYTES {
+            return Err(format!("CI results are too large (over {} bytes)", MAX_CI_BYTES));
+        }
+        bytes
+    };
+    String::from_utf8(bytes).map_err(|_| "CI results aren't UTF-8 text".to_string())
+}
+
+/// JUnit XML starts with a tag; anything else is treated as a job log
+fn parse_ci(content: &str) -> Result<ParsedCi, String> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('<') {
+        parse_junit(trimmed)
+    } else {
+        Ok(parse_github_log(content))
+    }
+}
+
+fn parse_junit(xml: &str) -> Result<ParsedCi, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| format!("Invalid JUnit XML: {}", e))?;
+    let mut tests = 0;
+    let mut skipped = 0;
+    let mut failures = vec![];
+
+    for case in doc.descendants().filter(|n| n.has_tag_name("testcase")) {
+        tests += 1;
+        if case.children().any(|c| c.has_tag_name("skipped")) {
+            skipped += 1;
+            continue;
+        }
+        let Some(failure) = case.children().find(|c| c.has_tag_name("failure") || c.has_tag_name("error")) else {
+            continue;
+        };
+
+        // Suites nest; the closest one names the failure
+        let suite = case.attribute("classname")
+            .or_else(|| case.ancestors().find(|a| a.has_tag_name("testsuite")).and_then(|s| s.attribute("name")))
+            .map(|s| s.to_string());
+        let text = failure.text().map(|t| t.trim()).filter(|t| !t.is_empty());
+        let message = failure.attribute("message")
+            .map(|m| m.to_string())
+            .or_else(|| text.and_then(|t| t.lines().next()).map(|l| l.to_string()))
+            .unwrap_or_else(|| failure.tag_name().name().to_string());
+        failures.push(CiFailure {
+            suite,
+            name: case.attribute("name").unwrap_or("unnamed").to_string(),
+            file: case.attribute("file").map(|f| f.to_string()),
+            line: case.attribute("line").and_then(|l| l.parse().ok()),
+            message,
+            details: text.map(|t| t.to_string()),
+        });
+    }
+
+    if tests == 0 {
+        return Err("No test cases found in JUnit XML".into());
+    }
+    Ok(ParsedCi { format: "junit", tests: Some(tests), skipped, failures })
+}
+
+/// Drop the timestamp GitHub prefixes to each line of a downloaded job log
+fn strip_timestamp(line: &str) -> &str {
+    match line.split_once(' ') {
+        Some((stamp, rest)) if stamp.len() >= 20 && stamp.ends_with('Z') && stamp.as_bytes()[4] == b'-' => rest,
+        _ => line,
+    }
+}
+
+/// `::error file=src/a.rs,line=3,title=T::message` workflow command
+fn parse_error_command(command: &str, step: Option<&String>) -> Option<CiFailure> {
+    let rest = command.strip_prefix("::error")?;
+    let (params, message) = rest.split_once("::")?;
+    let mut failure = CiFailure {
+        suite: step.cloned(),
+        name: step.cloned().unwrap_or_else(|| "job".into()),
+        file: None,
+        line: None,
+        message: message.trim().to_string(),
+        details: None,
+    };
+    for param in params.trim().split(',') {
+        match param.split_once('=') {
+            Some(("file", v)) => failure.file = Some(v.to_string()),
+            Some(("line", v)) => failure.line = v.parse().ok(),
+            Some(("title", v)) => failure.name = v.to_string(),
+            _ => {}
+        }
+    }
+    Some(failure)
+}
+
+/// Collect `##[error]` lines and `::error` annotations, each with the step it
+/// ran in and the log lines leading up to it
+fn parse_github_log(log: &str) -> ParsedCi {
+    let mut failures = vec![];
+    let mut step: Option<String> = None;
+    let mut recent: Vec<&str> = vec![];
+
+    for raw in log.lines() {
+        let line = strip_timestamp(raw);
+        if let Some(name) = line.strip_prefix("##[group]") {
+            step = Some(name.trim().trim_start_matches("Run ").to_string());
+            recent.clear();
+            continue;
+        }
+        if line.starts_with("##[endgroup]") {
+            continue;
+        }
+
+        let failure = if let Some(message) = line.strip_prefix("##[error]") {
+            // The runner repeats annotations as ##[error]; keep the first copy only
+            let message = message.trim().to_string();
+            if failures.iter().any(|f: &CiFailure| f.message == message && f.suite == step) {
+                None
+            } else {
+                Some(CiFailure {
+                    suite: step.clone(),
+                    name: step.clone().unwrap_or_else(|| "job".into()),
+                    file: None,
+                    line: None,
+                    message,
+                    details: (!recent.is_empty()).then(|| recent.join("\n")),
+                })
+            }
+        } else {
+            parse_error_command(line.trim_start(), step.as_ref())
+        };
+
+        match failure {
+            Some(failure) => {
+                failures.push(failure);
+                recent.clear();
+            }
+            None => {
+                recent.push(line);
+                if recent.len() > ERROR_CONTEXT_LINES {
+                    recent.remove(0);
+                }
+            }
+        }
+    }
+
+    ParsedCi { format: "github_actions", tests: None, skipped: 0, failures }
+}
+
+/// Parse JUnit XML or a GitHub Actions job log from a file or URL and store
+/// the failures for the task's next verification
+pub async fn ingest_ci_results(app: &AppHandle, task_id: &str, path_or_url: &str) -> Result<CiResults, String> {
+    let source = path_or_url.trim();
+    let content = read_source(source).await?;
+    let parsed = parse_ci(&content)?;
+
+    let results = CiResults {
+        id: new_id(),
+        task_id: task_id.to_string(),
+        source: source.to_string(),
+        format: parsed.format.to_string(),
+        tests: parsed.tests,
+        skipped: parsed.skipped,
+        failures: parsed.failures,
+        created_at: now_iso(),
+    };
+    let failures_json = serde_json::to_string(&results.failures).map_err(|e| e.to_string())?;
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO ci_results (id, task_id, source, format, tests, skipped, failures_json, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        (
+            &results.id, task_id, &results.source, &results.format,
+            results.tests.map(|n| n as i64), results.skipped as i64, &failures_json, &results.created_at,
+        )
+    ).map_err(|e| e.to_string())?;
+
+    tracing::info!(format = %results.format, failures = results.failures.len(), "ingested CI results");
+    Ok(results)
+}
+
+pub fn latest_ci_results(app: &AppHandle, task_id: &str) -> Result<Option<CiResults>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let row = conn.query_row(
+        "SELECT id, task_id, source, format, tests, skipped, failures_json, created_at
+         FROM ci_results WHERE task_id = ?1 ORDER BY created_at DESC LIMIT 1",
+        [task_id],
+        |r| Ok((
+            CiResults {
+                id: r.get(0)?,
+                task_id: r.get(1)?,
+                source: r.get(2)?,
+                format: r.get(3)?,
+                tests: r.get::<_, Option<i64>>(4)?.map(|n| n as usize),
+                skipped: r.get::<_, i64>(5)? as usize,
+                failures: vec![],
+                created_at: r.get(7)?,
+            },
+            r.get::<_, String>(6)?,
+        ))
+    );
+    match row {
+        Ok((results, failures_json)) => {
+            let failures = serde_json::from_str(&failures_json).map_err(|e| e.to_string())?;
+            Ok(Some(CiResults { failures, ..results }))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Prompt section standing in for local test output
+pub fn format_ci_results(results: &CiResults) -> String {
+    let mut out = format!("Source: {} ({}, ingested {})\n", results.source, results.format, results.created_at);
+    match results.tests {
+        Some(tests) => out.push_str(&format!(
+            "{} tests, {} failed, {} skipped\n",
+            tests, results.failures.len(), results.skipped
+        )),
+        None => out.push_str(&format!("{} errors reported by the job\n", results.failures.len())),
+    }
+    if results.failures.is_empty() {
+        out.push_str("All checks passed.\n");
+        return out;
+    }
+
+    for failure in results.failures.iter().take(MAX_FAILURES_IN_PROMPT) {
+        let location = match (&failure.file, failure.line) {
+            (Some(file), Some(line)) => format!(" ({}:{})", file, line),
+            (Some(file), None) => format!(" ({})", file),
+            _ => String::new(),
+        };
+        let name = match &failure.suite {
+            Some(suite) if *suite != failure.name => format!("{}::{}", suite, failure.name),
+            _ => failure.name.clone(),
+        };
+        out.push_str(&format!("\nFAILED {}{}: {}\n", name, location, failure.message));
+        if let Some(details) = &failure.details {
+            let details: String = details.chars().take(MAX_DETAIL_CHARS).collect();
+            out.push_str(&details);
+            out.push('\n');
+        }
+    }
+    if results.failures.len() > MAX_FAILURES_IN_PROMPT {
+        out.push_str(&format!("\n... {} more failures omitted\n", results.failures.len() - MAX_FAILURES_IN_PROMPT));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_junit() {
+        let xml = r#"<?xml version="1.0"?>
+<testsuites>
+  <testsuite name="auth" tests="3">
+    <testcase classname="auth.login" name="accepts_valid" />
+    <testcase classname="auth.login" name="rejects_expired" file="src/auth.rs" line="42">
+      <failure message="assertion failed: expired">left: 1, right: 2</failure>
+    </testcase>
+    <testcase name="slow"><skipped /></testcase>
+  </testsuite>
+</testsuites>"#;
+        let parsed = parse_ci(xml).unwrap();
+        assert_eq!(parsed.format, "junit");
+        assert_eq!(parsed.tests, Some(3));
+        assert_eq!(parsed.skipped, 1);
+        assert_eq!(parsed.failures.len(), 1);
+        let failure = &parsed.failures[0];
+        assert_eq!(failure.suite.as_deref(), Some("auth.login"));
+        assert_eq!(failure.file.as_deref(), Some("src/auth.rs"));
+        assert_eq!(failure.line, Some(42));
+        assert_eq!(failure.message, "assertion failed: expired");
+        assert_eq!(failure.details.as_deref(), Some("left: 1, right: 2"));
+
+        assert!(parse_ci("<testsuites></testsuites>").is_err());
+    }
+
+    #[test]
+    fn test_parse_github_log() {
+        let log = "2024-05-01T10:00:00.1234567Z ##[group]Run cargo test\n\
+2024-05-01T10:00:01.0000000Z running 2 tests\n\
+2024-05-01T10:00:02.0000000Z test parser::roundtrip ... FAILED\n\
+2024-05-01T10:00:03.0000000Z ::error file=src/parser.rs,line=88,title=roundtrip::values differ\n\
+2024-05-01T10:00:04.0000000Z ##[error]Process completed with exit code 101.\n\
+2024-05-01T10:00:05.0000000Z ##[endgroup]\n";
+        let parsed = parse_ci(log).unwrap();
+        assert_eq!(parsed.format, "github_actions");
+        assert_eq!(parsed.tests, None);
+        assert_eq!(parsed.failures.len(), 2);
+
+        let annotation = &parsed.failures[0];
+        assert_eq!(annotation.name, "roundtrip");
+        assert_eq!(annotation.file.as_deref(), Some("src/parser.rs"));
+        assert_eq!(annotation.line, Some(88));
+        assert_eq!(annotation.suite.as_deref(), Some("cargo test"));
+
+        let exit = &parsed.failures[1];
+        assert_eq!(exit.message, "Process completed with exit code 101.");
+        assert!(exit.details.is_none());
+    }
+}
@@ -0,0 +1,373 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use std::collections::HashMap;
+
+use crate::workflows::ci::CiFailure;
+
+const MAX_DIAGNOSTICS_IN_PROMPT: usize = 100;
+
+/// Whether a diagnostic sits on a line the change added or modified
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    Introduced,
+    Preexisting,
+}
+
+/// A compiler, linter or test failure message tied to a source location
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Type)]
+pub struct CompilerDiagnostic {
+    /// cargo|rustc|tsc|mypy|eslint|ruff|test|ci
+    pub tool: String,
+    pub path: String,
+    pub line: u32,
+    pub column: Option<u32>,
+    /// error|warning
+    pub severity: String,
+    pub message: String,
+    pub code: Option<String>,
+    pub origin: Origin,
+}
+
+fn diagnostic(tool: &'static str, path: &str, line: u32, column: Option<u32>, severity: &str, message: &str, code: Option<String>) -> CompilerDiagnostic {
+    CompilerDiagnostic {
+        tool: tool.to_string(),
+        path: path.trim().trim_start_matches("./").replace('\\', "/"),
+        line,
+        column,
+        severity: severity.to_string(),
+        message: message.trim().to_string(),
+        code,
+        origin: Origin::Preexisting,
+    }
+}
+
+/// One line of `cargo check --message-format=json`; only errors and warnings
+/// with a primary span are kept
+fn parse_cargo_line(line: &str) -> Option<CompilerDiagnostic> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    if value.get("reason").and_then(|r| r.as_str()) != Some("compiler-message") {
+        return None;
+    }
+    let message = value.get("message")?;
+    let level = message.get("level").and_then(|l| l.as_str())?;
+    if level != "error" && level != "warning" {
+        return None;
+    }
+    let span = message.get("spans").and_then(|s| s.as_array())?.iter()
+        .find(|s| s.get("is_primary").and_then(|p| p.as_bool()).unwrap_or(false))?;
+    let code = message.get("code").and_then(|c| c.get("code")).and_then(|c| c.as_str()).map(str::to_string);
+    Some(diagnostic(
+        "cargo",
+        span.get("file_name").and_then(|f| f.as_str())?,
+        span.get("line_start").and_then(|l| l.as_u64())? as u32,
+        span.get("column_start").and_then(|c| c.as_u64()).map(|c| c as u32),
+        level,
+        message.get("message").and_then(|m| m.as_str()).unwrap_or_default(),
+        code,
+    ))
+}
+
+/// `path(line,col): error TS1234: message`, or the `path:line:col - error ...`
+/// form tsc prints with `--pretty`
+fn parse_tsc_line(line: &str) -> Option<CompilerDiagnostic> {
+    let (location, rest) = match line.split_once("): ") {
+        Some((location, rest)) if location.contains('(') => (location, rest),
+        _ => line.split_once(" - ")?,
+    };
+    let (path, line_no, column) = match location.rsplit_once('(') {
+        Some((path, position)) => {
+            let (line_no, column) = position.split_once(',')?;
+            (path, line_no, column)
+        }
+        None => {
+            let (rest, column) = location.rsplit_once(':')?;
+            let (path, line_no) = rest.rsplit_once(':')?;
+            (path, line_no, column)
+        }
+    };
+    let (severity, rest) = rest.split_once(' ')?;
+    if severity != "error" && severity != "warning" {
+        return None;
+    }
+    let (code, message) = rest.split_once(": ")?;
+    if !code.starts_with("TS") {
+        return None;
+    }
+    Some(diagnostic("tsc", path, line_no.trim().parse().ok()?, column.trim().parse().ok(), severity, message, Some(code.to_string())))
+}
+
+/// `path:line[:col]: error: message  [code]`; notes are dropped
+fn parse_mypy_line(line: &str) -> Option<CompilerDiagnostic> {
+    let (severity, split) = [": error: ", ": warning: "].iter()
+        .find_map(|marker| line.find(marker).map(|at| (marker.trim_matches([':', ' ']), (at, marker.len()))))?;
+    let (location, rest) = (&line[..split.0], &line[split.0 + split.1..]);
+    let mut parts = location.rsplitn(3, ':');
+    let (last, middle) = (parts.next()?, parts.next()?);
+    let (path, line_no, column) = match (middle.parse::<u32>(), parts.next()) {
+        (Ok(line_no), Some(path)) => (path, line_no, last.parse().ok()),
+        _ => (location.rsplit_once(':')?.0, last.parse().ok()?, None),
+    };
+    let (message, code) = match rest.trim_end().strip_suffix(']').and_then(|r| r.rsplit_once("  [")) {
+        Some((message, code)) => (message, Some(code.to_string())),
+        None => (rest, None),
+    };
+    Some(diagnostic("mypy", path, line_no, column, severity, message, code))
+}
+
+/// `path:line:col: F401 message` from `ruff check`
+fn parse_ruff_line(line: &str) -> Option<CompilerDiagnostic> {
+    let mut parts = line.splitn(4, ':');
+    let (path, line_no, column, rest) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    let (code, message) = rest.trim_start().split_once(' ')?;
+    let is_rule = code.len() > 1 && code.starts_with(|c: char| c.is_ascii_uppercase())
+        && code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if !is_rule {
+        return None;
+    }
+    let message = message.trim_start_matches("[*]");
+    Some(diagnostic("ruff", path, line_no.parse().ok()?, column.parse().ok(), "error", message, Some(code.to_string())))
+}
+
+/// `  12:5  error  Message  rule-name` under a file heading in eslint's stylish output
+fn parse_eslint_line(path: &str, line: &str) -> Option<CompilerDiagnostic> {
+    let (position, rest) = line.trim().split_once(' ')?;
+    let (line_no, column) = position.split_once(':')?;
+    let (severity, rest) = rest.trim_start().split_once(' ')?;
+    if severity != "error" && severity != "warning" {
+        return None;
+    }
+    let (message, rule) = match rest.trim().rsplit_once("  ") {
+        Some((message, rule)) => (message, Some(rule.trim().to_string())),
+        None => (rest, None),
+    };
+    Some(diagnostic("eslint", path, line_no.parse().ok()?, column.parse().ok(), severity, message, rule))
+}
+
+/// Header of a human-readable rustc or clippy message, `error[E0308]: message`
+fn parse_rustc_header(line: &str) -> Option<(&str, Option<String>, &str)> {
+    let (head, message) = line.split_once(": ")?;
+    let (severity, code) = match head.split_once('[') {
+        Some((severity, code)) => (severity, Some(code.strip_suffix(']')?.to_string())),
+        None => (head, None),
+    };
+    (severity == "error" || severity == "warning").then_some((severity, code, message))
+}
+
+/// Diagnostics from compiler, type checker and linter output (cargo JSON,
+/// rustc/clippy, tsc, mypy, eslint, ruff), whichever format each line is in
+pub fn parse_compiler_output(output: &str) -> Vec<CompilerDiagnostic> {
+    let mut diagnostics: Vec<CompilerDiagnostic> = vec![];
+    // rustc prints the location on a ` --> path:line:col` line after the header
+    let mut rustc_header = None;
+    // eslint lists messages under a line naming the file
+    let mut eslint_file: Option<&str> = None;
+    for line in output.lines() {
+        let line = line.trim_end();
+        let mut parsed = None;
+        if line.starts_with('{') {
+            parsed = parse_cargo_line(line);
+        } else if let Some(location) = line.trim_start().strip_prefix("--> ") {
+            if let Some((severity, code, message)) = rustc_header.take() {
+                let mut parts = location.rsplitn(3, ':');
+                let (column, line_no, path) = (parts.next(), parts.next(), parts.next());
+                if let (Some(path), Some(Ok(line_no))) = (path, line_no.map(str::parse)) {
+                    parsed = Some(diagnostic("rustc", path, line_no, column.and_then(|c| c.parse().ok()), severity, message, code));
+                }
+            }
+        } else if line.starts_with(char::is_whitespace) {
+            parsed = eslint_file.and_then(|path| parse_eslint_line(path, line));
+        } else if !line.is_empty() {
+            rustc_header = parse_rustc_header(line);
+            parsed = parse_tsc_line(line)
+                .or_else(|| parse_mypy_line(line))
+                .or_else(|| parse_ruff_line(line));
+            eslint_file = (rustc_header.is_none() && parsed.is_none() && !line.contains(' ')).then_some(line);
+        }
+        if let Some(diagnostic) = parsed {
+            // cargo repeats messages for each target that includes the file
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Failure locations in test runner output: Rust panics and pytest tracebacks
+pub fn parse_test_failures(output: &str) -> Vec<CompilerDiagnostic> {
+    let mut diagnostics: Vec<CompilerDiagnostic> = vec![];
+    let mut lines = output.lines().peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim_end();
+        let parsed = if let Some((_, rest)) = line.split_once("panicked at ") {
+            // `panicked at src/lib.rs:10:5:` with the message on the next line, or
+            // the older `panicked at 'message', src/lib.rs:10:5`
+            let (message, location) = match rest.rsplit_once("', ") {
+                Some((message, location)) => (message.trim_start_matches('\'').to_string(), location),
+                None => (lines.peek().map(|l| l.trim().to_string()).unwrap_or_default(), rest.trim_end_matches(':')),
+            };
+            let mut parts = location.rsplitn(3, ':');
+            let (column, line_no, path) = (parts.next(), parts.next(), parts.next());
+            match (path, line_no.and_then(|l| l.parse().ok())) {
+                (Some(path), Some(line_no)) => Some(diagnostic("test", path, line_no, column.and_then(|c| c.parse().ok()), "error", &message, None)),
+                _ => None,
+            }
+        } else {
+            // pytest ends each traceback with `path.py:12: AssertionError`
+            line.split_once(": ").and_then(|(location, error)| {
+                let (path, line_no) = location.rsplit_once(':')?;
+                let is_error = !error.contains(' ') && (error.ends_with("Error") || error.ends_with("Exception"));
+                (path.ends_with(".py") && is_error)
+                    .then(|| line_no.parse().ok()).flatten()
+                    .map(|line_no| diagnostic("test", path, line_no, None, "error", error, None))
+            })
+        };
+        if let Some(diagnostic) = parsed {
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    diagnostics
+}
+
+/// CI test failures that name a file and line
+pub fn ci_failure_diagnostics(failures: &[CiFailure]) -> Vec<CompilerDiagnostic> {
+    failures.iter()
+        .filter_map(|f| Some(diagnostic("ci", f.file.as_deref()?, f.line?, None, "error", &f.message, None)))
+        .collect()
+}
+
+/// New-side line ranges each file's changes added or modified, from a unified
+/// diff. A deletion marks the line that now follows it.
+pub fn changed_ranges(diff: &str) -> HashMap<String, Vec<(u32, u32)>> {
+    let mut ranges: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
+    let mut current: Option<String> = None;
+    let mut in_hunk = false;
+    let mut new_line = 0;
+    let mut mark = |path: &Option<String>, line: u32| {
+        let Some(path) = path else { return };
+        let file = ranges.entry(path.clone()).or_default();
+        match file.last_mut() {
+            Some((_, end)) if *end + 1 >= line => *end = (*end).max(line),
+            _ => file.push((line, line)),
+        }
+    };
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            in_hunk = false;
+        } else if let (false, Some(path)) = (in_hunk, line.strip_prefix("+++ ")) {
+            current = path.strip_prefix("b/").map(str::to_string);
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            // @@ -a,b +c,d @@
+            in_hunk = true;
+            new_line = header.split(' ')
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next()?.parse().ok())
+                .unwrap_or(0);
+        } else if in_hunk {
+            match line.chars().next() {
+                Some('+') => {
+                    mark(&current, new_line);
+                    new_line += 1;
+                }
+                Some('-') => mark(&current, new_line.max(1)),
+                Some('\\') => {}
+                _ => new_line += 1,
+            }
+        }
+    }
+    ranges
+}
+
+/// Mark diagnostics on changed lines as introduced, the rest as pre-existing
+pub fn classify(diagnostics: &mut [CompilerDiagnostic], changed: &HashMap<String, Vec<(u32, u32)>>) {
+    for diagnostic in diagnostics {
+        let introduced = changed.get(&diagnostic.path)
+            .is_some_and(|ranges| ranges.iter().any(|(start, end)| (*start..=*end).contains(&diagnostic.line)));
+        diagnostic.origin = if introduced { Origin::Introduced } else { Origin::Preexisting };
+    }
+}
+
+/// Prompt listing of the diagnostics, introduced ones first
+pub fn format_compiler_diagnostics(diagnostics: &[CompilerDiagnostic]) -> String {
+    let mut sorted: Vec<&CompilerDiagnostic> = diagnostics.iter().collect();
+    sorted.sort_by_key(|d| (d.origin != Origin::Introduced, d.severity != "error"));
+    let mut lines: Vec<String> = sorted.iter().take(MAX_DIAGNOSTICS_IN_PROMPT).map(|d| {
+        let location = match d.column {
+            Some(column) => format!("{}:{}:{}", d.path, d.line, column),
+            None => format!("{}:{}", d.path, d.line),
+        };
+        let code = d.code.as_ref().map(|c| format!("[{}]", c)).unwrap_or_default();
+        let origin = match d.origin {
+            Origin::Introduced => "introduced",
+            Origin::Preexisting => "pre-existing",
+        };
+        format!("- [{}] {} {}{} ({}): {}", origin, location, d.severity, code, d.tool, d.message)
+    }).collect();
+    if diagnostics.len() > MAX_DIAGNOSTICS_IN_PROMPT {
+        lines.push(format!("- ... {} more", diagnostics.len() - MAX_DIAGNOSTICS_IN_PROMPT));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compiler_output() {
+        let output = concat!(
+            r#"{"reason":"compiler-artifact","target":{"name":"app"}}"#, "\n",
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","code":{"code":"E0308"},"spans":[{"file_name":"src/lib.rs","line_start":12,"column_start":5,"is_primary":true}]}}"#, "\n",
+            r#"{"reason":"compiler-message","message":{"level":"warning","message":"2 warnings emitted","code":null,"spans":[]}}"#, "\n",
+            "src/app.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n",
+            "src/view.ts:40:1 - warning TS6133: 'x' is declared but its value is never read.\n",
+            "pkg/mod.py:8: error: Incompatible return value type  [return-value]\n",
+            "pkg/mod.py:9:4: note: See docs\n",
+            "C:\\work\\pkg\\util.py:2:10: warning: Unused ignore\n",
+            "Found 1 error in 1 file\n",
+            "warning: unused variable: `y`\n",
+            " --> src/main.rs:4:9\n",
+            "  |\n",
+            "warning: `app` (bin \"app\") generated 1 warning\n",
+            "pkg/api.py:1:8: F401 [*] `os` imported but unused\n",
+            "/repo/src/App.tsx\n",
+            "  12:5  error    'x' is not defined  no-undef\n",
+            "\n",
+            "✖ 1 problem (1 error, 0 warnings)\n",
+        );
+        let diagnostics = parse_compiler_output(output);
+        assert_eq!(diagnostics.len(), 8);
+        assert_eq!(diagnostics[0], diagnostic("cargo", "src/lib.rs", 12, Some(5), "error", "mismatched types", Some("E0308".into())));
+        assert_eq!((diagnostics[1].path.as_str(), diagnostics[1].line, diagnostics[1].column), ("src/app.ts", 3, Some(7)));
+        assert_eq!(diagnostics[2].code.as_deref(), Some("TS6133"));
+        assert_eq!((diagnostics[3].message.as_str(), diagnostics[3].code.as_deref()), ("Incompatible return value type", Some("return-value")));
+        assert_eq!((diagnostics[4].path.as_str(), diagnostics[4].column), ("C:/work/pkg/util.py", Some(10)));
+        assert_eq!(diagnostics[5], diagnostic("rustc", "src/main.rs", 4, Some(9), "warning", "unused variable: `y`", None));
+        assert_eq!((diagnostics[6].code.as_deref(), diagnostics[6].message.as_str()), (Some("F401"), "`os` imported but unused"));
+        assert_eq!(diagnostics[7], diagnostic("eslint", "/repo/src/App.tsx", 12, Some(5), "error", "'x' is not defined", Some("no-undef".into())));
+    }
+
+    #[test]
+    fn test_classify_against_changed_lines() {
+        let tests = "thread 'tests::it_works' panicked at src/lib.rs:11:9:\nassertion failed\n\
+                     tests/test_api.py:30: AssertionError\n";
+        let mut diagnostics = parse_test_failures(tests);
+        assert_eq!(diagnostics[0], diagnostic("test", "src/lib.rs", 11, Some(9), "error", "assertion failed", None));
+        assert_eq!((diagnostics[1].path.as_str(), diagnostics[1].line), ("tests/test_api.py", 30));
+
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+                    @@ -10,3 +10,4 @@ fn main\n ctx\n+added\n+--- not a header\n ctx\n-removed\n\
+                    diff --git a/tests/test_api.py b/tests/test_api.py\n--- a/tests/test_api.py\n+++ b/tests/test_api.py\n@@ -28,3 +28,2 @@\n a\n-b\n c\n";
+        let changed = changed_ranges(diff);
+        assert_eq!(changed["src/lib.rs"], vec![(11, 12), (14, 14)]);
+        assert_eq!(changed["tests/test_api.py"], vec![(29, 29)]);
+        classify(&mut diagnostics, &changed);
+        assert_eq!(diagnostics[0].origin, Origin::Introduced);
+        assert_eq!(diagnostics[1].origin, Origin::Preexisting);
+        assert!(format_compiler_diagnostics(&diagnostics).starts_with("- [introduced] src/lib.rs:11:9 error (test): assertion failed"));
+    }
+}
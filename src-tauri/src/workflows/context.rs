@@ -0,0 +1,433 @@
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::feedback::feedback_context;
+use crate::project_artifacts::project_context;
+use crate::models::{Project, Task};
+use crate::repo_tools::context_ignore::{load_config, matcher, project_globs};
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::safety::{safe_spawn, sanitize_path, truncate_string};
+use crate::settings::SettingsMap;
+use crate::workflows::attachments::{attachments_context, list_attachments};
+use crate::workflows::retrieval::{planning_strategy, PlanningStrategy};
+use crate::workflows::sanitize::wrap_untrusted;
+use crate::workflows::templates::render_custom_prompt;
+
+const MAX_RELEVANT_FILES: usize = 20;
+/// Budget for the contents of pinned files in a plan prompt
+const MAX_PINNED_CHARS: usize = 40_000;
+/// Earlier runs of the task whose file reads are considered
+const RECENT_RUNS: i64 = 5;
+const HOT_FILES_SINCE: &str = "--since=30.days";
+const HOT_FILES_COMMITS: &str = "200";
+
+const DIRTY_SCORE: f64 = 3.0;
+const RUN_SCORE: f64 = 2.0;
+/// Extra score per additional read of a file in recent runs
+const RUN_REPEAT_SCORE: f64 = 0.25;
+const MAX_RUN_REPEAT_SCORE: f64 = 1.0;
+/// Score for the most frequently changed file; others scale down from it
+const HOT_SCORE: f64 = 1.0;
+
+/// A file likely to matter for the task, with why it was picked
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RelevantFile {
+    pub path: String,
+    pub score: f64,
+    /// pinned|dirty|recent_run|hot
+    pub reasons: Vec<&'static str>,
+}
+
+/// A file pinned into or excluded from a task's context by hand
+#[derive(Debug, Clone, Serialize, PartialEq, Type)]
+pub struct ContextOverride {
+    pub path: String,
+    /// pin|exclude
+    pub action: String,
+    pub created_at: String,
+}
+
+/// One part of the context a workflow would send, with the files it covers
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ContextSection {
+    /// task|custom_prompt|attachments|relevant_files|pinned_files|retrieval|git_diff
+    pub name: String,
+    pub chars: usize,
+    pub files: Vec<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ContextPreview {
+    pub workflow: String,
+    pub sections: Vec<ContextSection>,
+    pub overrides: Vec<ContextOverride>,
+    /// Files left out by the ignore globs or the task's exclusions
+    pub ignored: Vec<String>,
+}
+
+/// Paths changed in the working tree, from `git status --porcelain=v1`
+fn parse_dirty(status: &str) -> Vec<String> {
+    status.lines()
+        .filter(|line| line.len() > 3 && !line.starts_with("##"))
+        .map(|line| {
+            let path = &line[3..];
+            // Renames list "old -> new"
+            path.rsplit(" -> ").next().unwrap_or(path).trim_matches('"').to_string()
+        })
+        .collect()
+}
+
+/// How often each path shows up in `git log --name-only` output
+fn parse_hot(log: &str) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in log.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        *counts.entry(line).or_default() += 1;
+    }
+    counts.into_iter().map(|(path, count)| (path.to_string(), count)).collect()
+}
+
+/// Rank candidate files. Dirty files come first, then files the task's recent
+/// runs read, with frequently changed files breaking ties and filling the rest.
+fn rank(dirty: &[String], run_files: &[String], hot: &[(String, usize)]) -> Vec<RelevantFile> {
+    let mut files: HashMap<String, RelevantFile> = HashMap::new();
+    let mut add = |path: &str, score: f64, reason: &'static str| {
+        let path = path.trim_start_matches("./");
+        if path.is_empty() {
+            return;
+        }
+        let file = files.entry(path.to_string()).or_insert_with(|| RelevantFile {
+            path: path.to_string(),
+            score: 0.0,
+            reasons: vec![],
+        });
+        file.score += score;
+        if !file.reasons.contains(&reason) {
+            file.reasons.push(reason);
+        }
+    };
+
+    for path in dirty {
+        add(path, DIRTY_SCORE, "dirty");
+    }
+    let mut reads: HashMap<&str, usize> = HashMap::new();
+    for path in run_files {
+        *reads.entry(path.as_str()).or_default() += 1;
+    }
+    for (path, count) in reads {
+        let repeats = (count - 1) as f64 * RUN_REPEAT_SCORE;
+        add(path, RUN_SCORE + repeats.min(MAX_RUN_REPEAT_SCORE), "recent_run");
+    }
+    let max_changes = hot.iter().map(|(_, count)| *count).max().unwrap_or(1) as f64;
+    for (path, count) in hot {
+        add(path, HOT_SCORE * *count as f64 / max_changes, "hot");
+    }
+
+    let mut ranked: Vec<RelevantFile> = files.into_values().collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    ranked
+}
+
+/// Drop excluded files and put pinned ones first. Pinned files don't count
+/// against the limit.
+fn apply_overrides(ranked: Vec<RelevantFile>, overrides: &[ContextOverride]) -> Vec<RelevantFile> {
+    let action = |path: &str| overrides.iter().find(|o| o.path == path).map(|o| o.action.as_str());
+    let mut files: Vec<RelevantFile> = overrides.iter()
+        .filter(|o| o.action == "pin")
+        .map(|o| {
+            let ranked = ranked.iter().find(|f| f.path == o.path);
+            let mut reasons = vec!["pinned"];
+            reasons.extend(ranked.into_iter().flat_map(|f| f.reasons.iter().copied()));
+            RelevantFile { path: o.path.clone(), score: ranked.map_or(0.0, |f| f.score), reasons }
+        })
+        .collect();
+    files.extend(ranked.into_iter().filter(|f| action(&f.path).is_none()).take(MAX_RELEVANT_FILES));
+    files
+}
+
+/// A file path as stored in an override: repo-relative with forward slashes
+fn normalize_override_path(path: &str) -> Result<String, String> {
+    let normalized = path.trim().replace('\\', "/");
+    let parts: Vec<&str> = normalized.split('/').filter(|p| !p.is_empty() && *p != ".").collect();
+    if normalized.starts_with('/') || normalized.contains(':') || parts.contains(&"..") {
+        return Err(format!("'{}' must be a path inside the repository", path));
+    }
+    if parts.is_empty() {
+        return Err("path is required".to_string());
+    }
+    Ok(parts.join("/"))
+}
+
+pub fn list_context_overrides(app: &AppHandle, task_id: &str) -> Result<Vec<ContextOverride>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT path, action, created_at FROM context_overrides WHERE task_id = ?1 ORDER BY created_at ASC, path ASC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([task_id], |r| Ok(ContextOverride {
+        path: r.get(0)?,
+        action: r.get(1)?,
+        created_at: r.get(2)?,
+    })).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Pin or exclude a file for the task's runs, or clear its override with None
+pub fn set_context_override(app: &AppHandle, task_id: &str, path: &str, action: Option<&str>) -> Result<Vec<ContextOverride>, String> {
+    let path = normalize_override_path(path)?;
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    match action {
+        Some(action @ ("pin" | "exclude")) => {
+            conn.execute(
+                "INSERT INTO context_overrides (task_id, path, action, created_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(task_id, path) DO UPDATE SET action = excluded.action",
+                (task_id, &path, action, now_iso())
+            ).map_err(|e| e.to_string())?;
+        }
+        Some(action) => return Err(format!("Unknown context override '{}' (expected pin or exclude)", action)),
+        None => {
+            conn.execute("DELETE FROM context_overrides WHERE task_id = ?1 AND path = ?2", (task_id, &path))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    drop(conn);
+    list_context_overrides(app, task_id)
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// Files read or diffed by the task's most recent runs
+fn recent_run_files(app: &AppHandle, task_id: &str) -> Result<Vec<String>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT tc.name, tc.args_json FROM tool_calls tc
+         WHERE tc.name IN ('read_file', 'git_diff') AND tc.run_id IN (
+           SELECT id FROM runs WHERE task_id = ?1 ORDER BY started_at DESC LIMIT ?2
+         )"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map((task_id, RECENT_RUNS), |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut files = vec![];
+    for row in rows {
+        let (name, args) = row.map_err(|e| e.to_string())?;
+        let Ok(args) = serde_json::from_str::<Value>(&args) else { continue };
+        match name.as_str() {
+            "read_file" => files.extend(args.get("path").and_then(|p| p.as_str()).map(str::to_string)),
+            _ => files.extend(
+                args.get("paths").and_then(|p| p.as_array()).into_iter().flatten()
+                    .filter_map(|p| p.as_str()).map(str::to_string)
+            ),
+        }
+    }
+    Ok(files)
+}
+
+async fn git_output(repo_path: &Path, args: &[&str]) -> String {
+    match safe_spawn("git", args, repo_path, 10).await {
+        Ok((stdout, _, 0)) => stdout,
+        _ => String::new(),
+    }
+}
+
+/// Ranked files worth looking at first for the task. Git failures (e.g. not a
+/// repository) just leave out that source.
+pub async fn relevant_files(app: &AppHandle, task_id: &str, repo_path: &Path) -> Result<Vec<RelevantFile>, String> {
+    let status = git_output(repo_path, &["status", "--porcelain=v1"]).await;
+    let log = git_output(repo_path, &["log", HOT_FILES_SINCE, "-n", HOT_FILES_COMMITS, "--name-only", "--pretty=format:"]).await;
+    let run_files = recent_run_files(app, task_id)?;
+    let overrides = list_context_overrides(app, task_id)?;
+    Ok(apply_overrides(rank(&parse_dirty(&status), &run_files, &parse_hot(&log)), &overrides))
+}
+
+/// Prompt section listing the relevant files, or None when there are none
+pub fn relevant_files_context(files: &[RelevantFile]) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+    let list: String = files.iter()
+        .map(|file| format!("- {} ({})\n", file.path, file.reasons.join(", ")))
+        .collect();
+    Some(format!(
+        "## Relevant Files\n\nFiles with uncommitted changes, read by earlier runs of this task, or changed often recently. \
+         Start with these before exploring further.\n\n{}\n",
+        wrap_untrusted("relevant_files", list.trim_end())
+    ))
+}
+
+/// A repo file's contents, or None when it isn't text
+pub async fn read_text(repo_path: &Path, path: &str) -> Result<Option<String>, String> {
+    let content = match remote_repo(repo_path) {
+        Some(remote) => remote.read(path).await?,
+        None => {
+            let full_path = sanitize_path(repo_path, path).map_err(|e| e.to_string())?;
+            tokio::fs::read(&full_path).await.map_err(|e| e.to_string())?
+        }
+    };
+    Ok(String::from_utf8(content).ok().filter(|text| !text.contains('\0')))
+}
+
+/// Files changed most often in recent commits, with their commit counts
+pub async fn hot_files(repo_path: &Path) -> Vec<(String, usize)> {
+    let log = git_output(repo_path, &["log", HOT_FILES_SINCE, "-n", HOT_FILES_COMMITS, "--name-only", "--pretty=format:"]).await;
+    let mut hot = parse_hot(&log);
+    hot.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    hot
+}
+
+/// Prompt section with the contents of the pinned files, read up to a
+/// budget. Files that can't be read as text are named but left out.
+pub async fn pinned_files_context(repo_path: &Path, files: &[RelevantFile]) -> Option<String> {
+    let pinned: Vec<&str> = files.iter()
+        .filter(|f| f.reasons.contains(&"pinned"))
+        .map(|f| f.path.as_str())
+        .collect();
+    if pinned.is_empty() {
+        return None;
+    }
+
+    let mut out = String::from("## Pinned Files\n\nThe user pinned these files as essential context for this task.\n\n");
+    let mut used = 0;
+    for path in pinned {
+        let text = match read_text(repo_path, path).await {
+            Ok(Some(text)) => text,
+            Ok(None) => {
+                out.push_str(&format!("### {}\n(binary file, not included)\n\n", path));
+                continue;
+            }
+            Err(e) => {
+                out.push_str(&format!("### {}\n(could not be read: {})\n\n", path, e));
+                continue;
+            }
+        };
+        let remaining = MAX_PINNED_CHARS.saturating_sub(used);
+        if remaining == 0 {
+            out.push_str(&format!("### {}\n(omitted: pinned files exceed the context budget)\n\n", path));
+            continue;
+        }
+        let (text, truncated) = truncate_string(&text, remaining);
+        used += text.len();
+        let note = if truncated { " (truncated)" } else { "" };
+        out.push_str(&format!("### {}{}\n{}\n\n", path, note, wrap_untrusted("pinned_file", &format!("```\n{}\n```", text))));
+    }
+    Some(out)
+}
+
+fn section(name: &str, text: Option<&str>, files: Vec<String>) -> ContextSection {
+    ContextSection { name: name.to_string(), chars: text.map_or(0, str::len), files, note: None }
+}
+
+/// What a plan or verify run of the task would gather before its first model
+/// call. Nothing is sent: retrieval chunks are only named as a section, and
+/// verify's diff is the working tree's unstaged changes.
+pub async fn preview_context(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    task: &Task,
+    project: &Project,
+    workflow: &str,
+) -> Result<ContextPreview, String> {
+    let repo_path = Path::new(&project.repo_path);
+    let overrides = list_context_overrides(app, &task.id)?;
+    let excluded: Vec<String> = overrides.iter()
+        .filter(|o| o.action == "exclude")
+        .map(|o| o.path.clone())
+        .collect();
+    let mut sections = vec![];
+
+    if let Some(prompt) = render_custom_prompt(app, settings, workflow, task, project)? {
+        sections.push(section("custom_prompt", Some(&prompt), vec![]));
+    } else if workflow == "plan" {
+        let attachments = attachments_context(app, &task.id)?;
+        let names = list_attachments(app, &task.id)?.into_iter().map(|a| a.name).collect();
+        sections.push(section("attachments", attachments.as_deref(), names));
+    } else {
+        sections.push(section("task", Some(&task.title), vec![]));
+    }
+    if settings.get("feedback_in_prompt").is_none_or(|v| v == "1") {
+        let kind = if workflow == "plan" { "plan_md" } else { "verification_report" };
+        if let Some(feedback) = feedback_context(app, &task.id, None, kind)? {
+            sections.push(section("feedback", Some(&feedback), vec![]));
+        }
+    }
+
+    let project_artifacts = project_context(app, &project.id)?;
+    if project_artifacts.is_some() {
+        sections.push(section("project_artifacts", project_artifacts.as_deref(), vec![]));
+    }
+
+    let ignored = match workflow {
+        "plan" => {
+            let relevant = relevant_files(app, &task.id, repo_path).await?;
+            let context = relevant_files_context(&relevant);
+            let paths = relevant.iter().map(|f| f.path.clone()).collect();
+            sections.push(section("relevant_files", context.as_deref(), paths));
+            let pinned_context = pinned_files_context(repo_path, &relevant).await;
+            let pinned = relevant.iter()
+                .filter(|f| f.reasons.contains(&"pinned"))
+                .map(|f| f.path.clone())
+                .collect();
+            sections.push(section("pinned_files", pinned_context.as_deref(), pinned));
+            if planning_strategy(app, settings, &project.id)? == PlanningStrategy::Retrieval {
+                sections.push(ContextSection {
+                    note: Some("The closest indexed chunks are picked when the run starts".to_string()),
+                    ..section("retrieval", None, vec![])
+                });
+            }
+            excluded
+        }
+        _ => {
+            let config = load_config(project_globs(app, &project.id).await?, repo_path).await;
+            let ignore = matcher(&config, &excluded)?;
+            let diff = git_output(repo_path, &["diff"]).await;
+            let (diff, ignored) = ignore.filter_diff(&diff);
+            let files = git_output(repo_path, &["diff", "--name-only"]).await
+                .lines()
+                .filter(|path| !ignore.is_ignored(path))
+                .map(str::to_string)
+                .collect();
+            sections.push(section("git_diff", Some(&diff), files));
+            ignored
+        }
+    };
+
+    Ok(ContextPreview { workflow: workflow.to_string(), sections, overrides, ignored })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_relevant_files() {
+        let dirty = parse_dirty("## main...origin/main\n M src/lib.rs\nR  old.rs -> src/new.rs\n?? notes.md\n");
+        assert_eq!(dirty, vec!["src/lib.rs", "src/new.rs", "notes.md"]);
+        let hot = parse_hot("src/db.rs\nsrc/lib.rs\n\nsrc/db.rs\n\nsrc/db.rs\nREADME.md\n");
+
+        let run_files: Vec<String> = vec!["./src/db.rs".into(), "src/plan.rs".into(), "src/plan.rs".into()];
+        let ranked = rank(&dirty, &run_files, &hot);
+        let paths: Vec<&str> = ranked.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "notes.md", "src/db.rs", "src/new.rs", "src/plan.rs", "README.md"]);
+        assert_eq!(ranked[2].reasons, vec!["recent_run", "hot"]);
+
+        let context = relevant_files_context(&ranked).unwrap();
+        assert!(context.contains("- src/lib.rs (dirty, hot)"));
+
+        let pin = |path: &str, action: &str| ContextOverride { path: path.into(), action: action.into(), created_at: String::new() };
+        let curated = apply_overrides(ranked, &[pin("docs/design.md", "pin"), pin("src/db.rs", "pin"), pin("notes.md", "exclude")]);
+        let paths: Vec<&str> = curated.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["docs/design.md", "src/db.rs", "src/lib.rs", "src/new.rs", "src/plan.rs", "README.md"]);
+        assert_eq!(curated[1].reasons, vec!["pinned", "recent_run", "hot"]);
+        assert_eq!(normalize_override_path(".\\src\\db.rs").unwrap(), "src/db.rs");
+        assert!(normalize_override_path("../secrets.env").is_err());
+        assert!(relevant_files_context(&[]).is_none());
+    }
+}
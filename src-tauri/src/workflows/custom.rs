@@ -0,0 +1,412 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::llm::ChatMessage;
+use crate::models::new_id;
+use crate::repo_tools::context_ignore::REPO_CONFIG_FILE;
+use crate::repo_tools::remote::remote_repo;
+use crate::repo_tools::repo_tool_schemas;
+use crate::repo_tools::safety::truncate_middle;
+use crate::settings::SettingsMap;
+use crate::workflows::engine::{Gathered, HookFuture, Workflow, WorkflowEngine, WorkflowMode, WorkflowRun};
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+use crate::workflows::templates::{render_template, template_vars, TEMPLATE_VARIABLES};
+
+const CHECK_KINDS: &[&str] = &["tests", "lint", "build", "typecheck", "bench"];
+const DEFAULT_MAX_ITERATIONS: usize = 8;
+const MAX_ITERATIONS: usize = 20;
+const MAX_CHECK_OUTPUT_CHARS: usize = 6_000;
+/// Custom artifacts are saved under their own kinds, so a workflow (maybe
+/// one from a cloned repo) can't replace the plan that verify and phases read
+const ARTIFACT_KIND_PREFIX: &str = "custom_";
+
+const DEFAULT_SYSTEM_PROMPT: &str = "You are a senior engineer working on a task in the repository described below. \
+Follow the instructions exactly and answer in Markdown.";
+
+/// A user-defined workflow: a prompt, the tools the model may call, checks
+/// run before it's called, and the task artifact its answer is saved as.
+/// Written as JSON, or TOML like a `[workflows.<name>]` table in .spectrail.toml:
+///
+/// ```toml
+/// [workflows.security-review]
+/// prompt = "Review {{task.title}} for injection and auth issues.\n\n{{plan}}"
+/// tools = ["read_file", "grep", "git_diff"]
+/// checks = ["lint"]
+/// artifact_kind = "security_review"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowDefinition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// User prompt template, with the variables custom prompt templates take
+    pub prompt: String,
+    /// Repo tools the model may call; with none it's called once, without tools
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// `run_command` kinds run first, their results given to the model
+    #[serde(default)]
+    pub checks: Vec<String>,
+    /// The answer is saved as the task artifact `custom_<artifact_kind>`
+    pub artifact_kind: String,
+    /// Model calls the tool loop may make
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_iterations: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct CustomWorkflow {
+    pub name: String,
+    /// `project` when saved in the app, `repo` when defined in .spectrail.toml
+    pub source: String,
+    pub definition: WorkflowDefinition,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct CustomWorkflowList {
+    pub workflows: Vec<CustomWorkflow>,
+    /// Why the repository's workflows couldn't be read
+    pub repo_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct CheckSummary {
+    pub kind: String,
+    /// From run_command, or `error` when the check couldn't be run
+    pub outcome: String,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct CustomWorkflowResult {
+    pub run_id: String,
+    pub name: String,
+    pub artifact_id: String,
+    pub artifact_kind: String,
+    pub content: String,
+    pub tool_calls_count: usize,
+    pub forced_completion: bool,
+    pub checks: Vec<CheckSummary>,
+}
+
+/// Names are used in commands and config tables: letters, digits, `-` and `_`
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > 64 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid workflow name '{}' (use up to 64 letters, digits, - and _)", name));
+    }
+    Ok(())
+}
+
+pub fn validate_definition(definition: &WorkflowDefinition) -> Result<(), String> {
+    if definition.prompt.trim().is_empty() {
+        return Err("A workflow needs a prompt".into());
+    }
+    let unknown: Vec<String> = render_template(&definition.prompt, &BTreeMap::new()).unknown_variables
+        .into_iter()
+        .filter(|v| !TEMPLATE_VARIABLES.contains(&v.as_str()))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown template variable(s) in the prompt: {}. Available: {}",
+            unknown.join(", "),
+            TEMPLATE_VARIABLES.join(", ")
+        ));
+    }
+    let kind = &definition.artifact_kind;
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err(format!("Invalid artifact kind '{}' (use lowercase letters, digits and _)", kind));
+    }
+    let tools: Vec<String> = repo_tool_schemas().iter()
+        .filter_map(|schema| schema["function"]["name"].as_str().map(str::to_string))
+        .collect();
+    if let Some(tool) = definition.tools.iter().find(|t| !tools.contains(t)) {
+        return Err(format!("Unknown tool '{}' (expected {})", tool, tools.join(", ")));
+    }
+    if let Some(check) = definition.checks.iter().find(|c| !CHECK_KINDS.contains(&c.as_str())) {
+        return Err(format!("Unknown check '{}' (expected {})", check, CHECK_KINDS.join(", ")));
+    }
+    if definition.max_iterations.is_some_and(|n| n == 0 || n > MAX_ITERATIONS) {
+        return Err(format!("max_iterations must be between 1 and {}", MAX_ITERATIONS));
+    }
+    Ok(())
+}
+
+/// A definition written as JSON or TOML
+pub fn parse_definition(text: &str) -> Result<WorkflowDefinition, String> {
+    let definition: WorkflowDefinition = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| format!("Invalid workflow JSON: {}", e))?
+    } else {
+        toml::from_str(text).map_err(|e| format!("Invalid workflow TOML: {}", e))?
+    };
+    validate_definition(&definition)?;
+    Ok(definition)
+}
+
+/// `[workflows.<name>]` tables in a .spectrail.toml
+pub fn parse_repo_workflows(text: &str) -> Result<Vec<(String, WorkflowDefinition)>, String> {
+    let config: toml::Table = text.parse().map_err(|e| format!("Invalid {}: {}", REPO_CONFIG_FILE, e))?;
+    let Some(workflows) = config.get("workflows") else {
+        return Ok(vec![]);
+    };
+    let workflows = workflows.as_table()
+        .ok_or_else(|| format!("[workflows] in {} must be a table of workflows", REPO_CONFIG_FILE))?;
+    workflows.iter()
+        .map(|(name, value)| {
+            validate_name(name)?;
+            let definition: WorkflowDefinition = value.clone().try_into()
+                .map_err(|e| format!("Invalid workflow '{}' in {}: {}", name, REPO_CONFIG_FILE, e))?;
+            validate_definition(&definition)
+                .map_err(|e| format!("Workflow '{}' in {}: {}", name, REPO_CONFIG_FILE, e))?;
+            Ok((name.clone(), definition))
+        })
+        .collect()
+}
+
+async fn read_repo_config(repo_path: &Path) -> Option<String> {
+    let bytes = match remote_repo(repo_path) {
+        Some(remote) => remote.read(REPO_CONFIG_FILE).await.ok()?,
+        None => tokio::fs::read(repo_path.join(REPO_CONFIG_FILE)).await.ok()?,
+    };
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn saved_workflows(app: &AppHandle, project_id: &str) -> Result<Vec<CustomWorkflow>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare("SELECT name, definition FROM custom_workflows WHERE project_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([project_id], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+    let mut workflows = vec![];
+    for row in rows {
+        let (name, json) = row.map_err(|e| e.to_string())?;
+        let definition = serde_json::from_str(&json).map_err(|e| format!("Stored workflow '{}' is corrupt: {}", name, e))?;
+        workflows.push(CustomWorkflow { name, source: "project".into(), definition });
+    }
+    Ok(workflows)
+}
+
+/// The project's saved workflows and its repository's, by name. A saved
+/// workflow takes precedence over the repository's of the same name.
+pub async fn list_workflows(app: &AppHandle, project_id: &str) -> Result<CustomWorkflowList, String> {
+    let repo_path: String = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        conn.query_row("SELECT repo_path FROM projects WHERE id = ?1", [project_id], |r| r.get(0))
+            .map_err(|e| e.to_string())?
+    };
+    let mut workflows = saved_workflows(app, project_id)?;
+    let (repo_workflows, repo_error) = match read_repo_config(Path::new(&repo_path)).await.map(|text| parse_repo_workflows(&text)) {
+        Some(Ok(repo_workflows)) => (repo_workflows, None),
+        Some(Err(e)) => (vec![], Some(e)),
+        None => (vec![], None),
+    };
+    for (name, definition) in repo_workflows {
+        if !workflows.iter().any(|w| w.name == name) {
+            workflows.push(CustomWorkflow { name, source: "repo".into(), definition });
+        }
+    }
+    workflows.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(CustomWorkflowList { workflows, repo_error })
+}
+
+/// Save a workflow for the project from its JSON or TOML definition,
+/// replacing a saved one of the same name
+pub fn save_workflow(app: &AppHandle, project_id: &str, name: &str, text: &str) -> Result<CustomWorkflow, String> {
+    validate_name(name)?;
+    let definition = parse_definition(text)?;
+    let json = serde_json::to_string(&definition).map_err(|e| e.to_string())?;
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO custom_workflows (id, project_id, name, definition, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(project_id, name) DO UPDATE SET definition = excluded.definition, updated_at = excluded.updated_at",
+        (new_id(), project_id, name, &json, now_iso())
+    ).map_err(|e| e.to_string())?;
+    Ok(CustomWorkflow { name: name.to_string(), source: "project".into(), definition })
+}
+
+pub fn delete_workflow(app: &AppHandle, project_id: &str, name: &str) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let deleted = conn.execute("DELETE FROM custom_workflows WHERE project_id = ?1 AND name = ?2", (project_id, name))
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("No saved workflow named '{}'", name));
+    }
+    Ok(())
+}
+
+/// A check's result for the prompt: the outcome first, then its output
+fn format_check(kind: &str, result: &Result<Value, String>) -> String {
+    match result {
+        Ok(val) => {
+            let outcome = val.get("outcome").and_then(Value::as_str).unwrap_or("unknown");
+            let output = ["stdout", "stderr"].iter()
+                .filter_map(|stream| val.get(*stream).and_then(Value::as_str))
+                .filter(|text| !text.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let (output, _) = truncate_middle(&output, MAX_CHECK_OUTPUT_CHARS);
+            format!("### {} ({})\n{}\n", kind, outcome, wrap_untrusted("run_command", &format!("```\n{}\n```", output)))
+        }
+        Err(e) => format!("### {} (error)\nCould not run the check: {}\n", kind, e),
+    }
+}
+
+/// The kind a definition's answer is saved as
+fn stored_artifact_kind(definition: &WorkflowDefinition) -> String {
+    format!("{}{}", ARTIFACT_KIND_PREFIX, definition.artifact_kind)
+}
+
+/// A definition, as the engine runs it
+struct CustomRun<'d> {
+    definition: &'d WorkflowDefinition,
+    artifact_kind: String,
+}
+
+impl Workflow for CustomRun<'_> {
+    type Context = Vec<CheckSummary>;
+
+    fn run_type(&self) -> &str {
+        "custom"
+    }
+
+    fn artifact_kind(&self) -> &str {
+        &self.artifact_kind
+    }
+
+    fn mode(&self) -> WorkflowMode {
+        if self.definition.tools.is_empty() {
+            WorkflowMode::SingleCall
+        } else {
+            WorkflowMode::AgentLoop { max_iterations: self.definition.max_iterations.unwrap_or(DEFAULT_MAX_ITERATIONS) }
+        }
+    }
+
+    fn allowed_tools(&self) -> Option<&[String]> {
+        Some(&self.definition.tools)
+    }
+
+    fn gather_context<'a>(&'a self, run: &'a WorkflowRun<'a>) -> HookFuture<'a, Gathered<Vec<CheckSummary>>> {
+        Box::pin(async move {
+            let template = &self.definition.prompt;
+            let vars = template_vars(run.app, &run.task, &run.project, template)?;
+            let mut user_prompt = render_template(template, &vars).text;
+
+            let mut checks = vec![];
+            if !self.definition.checks.is_empty() {
+                user_prompt.push_str("\n\n## Checks\n");
+            }
+            for kind in &self.definition.checks {
+                let result = run.tool("run_command", json!({ "kind": kind })).await;
+                user_prompt.push_str(&format_check(kind, &result));
+                let outcome = match &result {
+                    Ok(val) => val.get("outcome").and_then(Value::as_str).unwrap_or("unknown").to_string(),
+                    Err(_) => "error".to_string(),
+                };
+                checks.push(CheckSummary { kind: kind.clone(), outcome });
+            }
+
+            let mut system_prompt = self.definition.system_prompt.clone()
+                .filter(|p| !p.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string());
+            system_prompt.push_str(UNTRUSTED_CONTENT_REMINDER);
+            Ok(Gathered {
+                messages: vec![
+                    ChatMessage { role: "system".into(), content: Some(system_prompt), tool_call_id: None, tool_calls: None },
+                    ChatMessage { role: "user".into(), content: Some(user_prompt), tool_call_id: None, tool_calls: None },
+                ],
+                context: checks,
+            })
+        })
+    }
+}
+
+/// Run the task's project's workflow `name` for the task
+pub async fn run_custom_workflow(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    api_key: &str,
+    name: &str,
+    task_id: &str,
+) -> Result<CustomWorkflowResult, String> {
+    let project_id: String = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        conn.query_row("SELECT project_id FROM tasks WHERE id = ?1", [task_id], |r| r.get(0))
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => format!("Task {} not found", task_id),
+                e => e.to_string(),
+            })?
+    };
+    let list = list_workflows(app, &project_id).await?;
+    let Some(workflow) = list.workflows.iter().find(|w| w.name == name) else {
+        let available: Vec<&str> = list.workflows.iter().map(|w| w.name.as_str()).collect();
+        let mut message = format!("No workflow named '{}' (available: {})", name, available.join(", "));
+        if let Some(e) = &list.repo_error {
+            message.push_str(&format!("; {}", e));
+        }
+        return Err(message);
+    };
+
+    let custom_run = CustomRun { definition: &workflow.definition, artifact_kind: stored_artifact_kind(&workflow.definition) };
+    let output = WorkflowEngine::new(app, settings, api_key)
+        .run(&custom_run, task_id)
+        .await?;
+    tracing::info!(workflow = name, tool_calls = output.tool_calls_count, "custom workflow finished");
+    Ok(CustomWorkflowResult {
+        run_id: output.run_id,
+        name: name.to_string(),
+        artifact_id: output.artifact_id,
+        artifact_kind: custom_run.artifact_kind,
+        content: output.content,
+        tool_calls_count: output.tool_calls_count,
+        forced_completion: output.forced_completion,
+        checks: output.context,
+    })
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_definitions() {
+        let toml = r#"
+[context]
+ignore = ["dist/**"]
+
+[workflows.security-review]
+prompt = "Review {{task.title}} for auth issues.\n\n{{plan}}"
+tools = ["read_file", "grep"]
+checks = ["lint"]
+artifact_kind = "security_review"
+"#;
+        let workflows = parse_repo_workflows(toml).unwrap();
+        assert_eq!(workflows.len(), 1);
+        assert_eq!(workflows[0].0, "security-review");
+        assert_eq!(workflows[0].1.tools, ["read_file", "grep"]);
+        assert_eq!(parse_repo_workflows("[context]\nignore = []\n").unwrap().len(), 0);
+
+        let json = r#"{"prompt": "Write release notes for {{task.title}}", "artifact_kind": "release_notes"}"#;
+        let definition = parse_definition(json).unwrap();
+        assert!(definition.tools.is_empty() && definition.checks.is_empty());
+        // Never saved over the built-in artifact of the same name
+        assert_eq!(stored_artifact_kind(&parse_definition("prompt = \"x\"\nartifact_kind = \"plan_md\"").unwrap()), "custom_plan_md");
+
+        assert!(parse_definition(r#"{"prompt": "{{task.titel}}", "artifact_kind": "x"}"#).unwrap_err().contains("task.titel"));
+        assert!(parse_definition("prompt = \"x\"\nartifact_kind = \"x\"\ntools = [\"write_file\"]").unwrap_err().contains("Unknown tool"));
+        assert!(parse_definition("prompt = \"x\"\nartifact_kind = \"x\"\nchecks = [\"deploy\"]").unwrap_err().contains("Unknown check"));
+        assert!(parse_definition("prompt = \"x\"\nartifact_kind = \"Release Notes\"").is_err());
+        assert!(parse_definition("prompt = \"x\"\nartifact_kind = \"x\"\nmodel = \"y\"").is_err());
+        assert!(validate_name("bad name").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::cancellation;
+use crate::commands::{get_project, get_task, upsert_artifact};
+use crate::db;
+use crate::demo::{is_demo_project, DEMO_READ_ONLY_MESSAGE};
+use crate::lineage::RunParent;
+use crate::llm::{ChatMessage, LlmClient};
+use crate::models::new_id;
+use crate::offline;
+use crate::render::store_plan_diagrams;
+use crate::repo_tools::safety::truncate_string;
+use crate::settings::SettingsMap;
+use crate::events::{RunEvent, RunStatus};
+use crate::windows::emit_run_event;
+use crate::workflows::budget::{check_run_budget, record_usage};
+use crate::workflows::engine::{finish_run, log_message};
+use crate::workflows::locale::{language_instruction, missing_sections, plan_headings, resolve_output_language};
+use crate::workflows::plan::{build_llm_config, build_user_prompt, generate_plan_variant, PlanVariant};
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+
+/// Artifact kinds of the two competing plans; the judge's merge is the task's `plan_md`
+pub const CANDIDATE_KINDS: [&str; 2] = ["plan_candidate_a", "plan_candidate_b"];
+
+const MAX_CANDIDATE_CHARS: usize = 40_000;
+
+/// Tokens and estimated cost of one or more runs
+#[derive(Debug, Clone, Default, Serialize, Type)]
+pub struct RunUsage {
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct DebateCandidate {
+    pub run_id: String,
+    pub model: String,
+    pub artifact_kind: String,
+    pub plan_md: String,
+    pub usage: RunUsage,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct DebateResult {
+    /// The judge's run; the candidates' runs are its children
+    pub run_id: String,
+    /// The merged plan, saved as the task's plan
+    pub plan_md: String,
+    pub candidates: Vec<DebateCandidate>,
+    pub judge_usage: RunUsage,
+    /// All three runs together
+    pub total_usage: RunUsage,
+    pub missing_sections: Vec<String>,
+    pub diagram_errors: Vec<String>,
+}
+
+/// Setting for each debate seat's model, empty for the configured model
+fn seat_model(settings: &SettingsMap, key: &str, requested: Option<&str>) -> String {
+    requested.map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(str::to_string)
+        .or_else(|| settings.get(key).map(|m| m.trim().to_string()).filter(|m| !m.is_empty()))
+        .unwrap_or_else(|| settings.get("model").cloned().unwrap_or_default())
+}
+
+fn build_judge_messages(task_prompt: &str, plans: &[(String, String)], language: &str) -> Vec<ChatMessage> {
+    let mut system_prompt = r##"You are a principal engineer judging competing implementation plans for the same task.
+
+Your task: Compare the candidate plans, then write ONE merged implementation plan that
+keeps the strongest approach for each part. Prefer the plan grounded in concrete files and
+repo facts; drop steps either candidate invented without evidence. Don't average two
+incompatible designs: pick one and say why.
+
+Use exactly the section structure the candidates follow (starting with
+"# Implementation Plan: [Title]"), then end with:
+
+## Debate Notes
+- Where the candidates disagreed, which choice the merged plan takes, and why
+- Anything both candidates missed"##.to_string();
+    if let Some(instruction) = language_instruction(language, plan_headings(language)) {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&instruction);
+    }
+    system_prompt.push_str(UNTRUSTED_CONTENT_REMINDER);
+
+    let mut user_prompt = format!("## Task\n{}\n", task_prompt);
+    for (i, (model, plan)) in plans.iter().enumerate() {
+        let (plan, truncated) = truncate_string(plan, MAX_CANDIDATE_CHARS);
+        let note = if truncated { ", truncated" } else { "" };
+        user_prompt.push_str(&format!(
+            "\n## Candidate {} ({}{})\n{}\n",
+            (b'A' + i as u8) as char, model, note, wrap_untrusted("candidate_plan", &plan)
+        ));
+    }
+
+    vec![
+        ChatMessage { role: "system".into(), content: Some(system_prompt), tool_call_id: None, tool_calls: None },
+        ChatMessage { role: "user".into(), content: Some(user_prompt), tool_call_id: None, tool_calls: None },
+    ]
+}
+
+fn create_run(app: &AppHandle, task_id: &str, provider: &str, model: &str) -> Result<String, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let id = new_id();
+    conn.execute(
+        "INSERT INTO runs (id, task_id, run_type, provider, model, started_at) VALUES (?1, ?2, 'debate', ?3, ?4, ?5)",
+        (&id, task_id, provider, model, now_iso())
+    ).map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+fn run_usage(app: &AppHandle, run_id: &str) -> Result<RunUsage, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT prompt_tokens, completion_tokens, cost_usd FROM runs WHERE id = ?1",
+        [run_id],
+        |r| Ok(RunUsage { prompt_tokens: r.get(0)?, completion_tokens: r.get(1)?, cost_usd: r.get(2)? })
+    ).map_err(|e| e.to_string())
+}
+
+/// Plan the task twice, with two planners that may use different models, then
+/// have a judge merge the candidates into the task's plan. Experimental: off
+/// unless the `plan_debate` setting is on. Both candidates are kept as their
+/// own artifacts, and their runs are recorded as the judge run's children.
+pub async fn debate_plan(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    api_key: &str,
+    project_id: &str,
+    task_id: &str,
+    models: [Option<&str>; 2],
+) -> Result<DebateResult, String> {
+    if settings.get("plan_debate").is_none_or(|v| v != "1") {
+        return Err("Plan debate is experimental; turn on the plan_debate setting to use it".into());
+    }
+    let task = get_task(app.clone(), task_id.to_string())?;
+    if task.project_id != project_id {
+        return Err(format!("Task {} is not in project {}", task_id, project_id));
+    }
+    let project = get_project(app.clone(), project_id.to_string())?;
+    if is_demo_project(app, project_id)? {
+        return Err(DEMO_READ_ONLY_MESSAGE.into());
+    }
+    check_run_budget(app, settings, project_id).map_err(|b| b.message)?;
+
+    let mut llm_config = build_llm_config(settings)?;
+    llm_config.model = seat_model(settings, "debate_judge_model", None);
+    let run_id = create_run(app, task_id, &llm_config.provider_name, &llm_config.model)?;
+    // Registered before the candidates start, which register under it
+    let cancellation = cancellation::register(app, &run_id);
+    emit_run_event(app, RunEvent {
+        run_id: run_id.clone(),
+        project_id: project_id.to_string(),
+        task_id: task_id.to_string(),
+        run_type: "debate".into(),
+        status: RunStatus::Started,
+        error: None,
+        code: None,
+    });
+
+    let result: Result<DebateResult, String> = async {
+        // The planners run side by side, each with the full plan tool loop
+        let seats = ["debate_model_a", "debate_model_b"];
+        let handles: Vec<_> = CANDIDATE_KINDS.iter().zip(seats).zip(models)
+            .map(|((kind, seat), requested)| {
+                let model = seat_model(settings, seat, requested);
+                let variant = PlanVariant { artifact_kind: kind.to_string(), model: Some(model.clone()) };
+                let planner = generate_plan_variant(
+                    app.clone(), project_id.to_string(), task_id.to_string(), variant, RunParent::new(&run_id, "debate"),
+                );
+                (model, kind.to_string(), tauri::async_runtime::spawn(planner))
+            })
+            .collect();
+        let mut candidates = vec![];
+        for (model, artifact_kind, handle) in handles {
+            let plan = handle.await.map_err(|e| e.to_string())?
+                .map_err(|e| format!("[{}] Candidate plan failed: {}", e.code, e.message))?;
+            let usage = run_usage(app, &plan.run_id)?;
+            candidates.push(DebateCandidate { run_id: plan.run_id, model, artifact_kind, plan_md: plan.plan_md, usage });
+        }
+        cancellation.check().map_err(|e| format!("[CANCELLED] {}", e))?;
+
+        let language = resolve_output_language(app, settings, project_id)?;
+        let plans: Vec<(String, String)> = candidates.iter().map(|c| (c.model.clone(), c.plan_md.clone())).collect();
+        let messages = build_judge_messages(&build_user_prompt(&task, &project, None), &plans, &language);
+        for message in &messages {
+            log_message(app, &run_id, &message.role, message.content.as_deref().unwrap_or(""), None, None)?;
+        }
+        let client = LlmClient::new(llm_config, api_key.to_string());
+        let response = cancellation.or_cancel(client.chat_with_tools(messages, vec![]))
+            .await
+            .map_err(|e| format!("[CANCELLED] {}", e))?
+            .map_err(|e| format!("[{}] {}", e.code(), e))?;
+        record_usage(app, settings, &run_id, response.usage);
+        let plan_md = response.content.filter(|c| !c.trim().is_empty())
+            .ok_or("No response from LLM")?;
+        log_message(app, &run_id, "assistant", &plan_md, None, None)?;
+
+        upsert_artifact(app.clone(), task_id.to_string(), None, "plan_md".into(), plan_md.clone())?;
+        let diagram_errors = store_plan_diagrams(app, task_id, &plan_md)?;
+        let judge_usage = run_usage(app, &run_id)?;
+        let total_usage = candidates.iter().map(|c| &c.usage).chain([&judge_usage])
+            .fold(RunUsage::default(), |total, u| RunUsage {
+                prompt_tokens: total.prompt_tokens + u.prompt_tokens,
+                completion_tokens: total.completion_tokens + u.completion_tokens,
+                cost_usd: total.cost_usd + u.cost_usd,
+            });
+        tracing::info!(
+            run_id = %run_id, tokens = total_usage.prompt_tokens + total_usage.completion_tokens,
+            cost_usd = total_usage.cost_usd, "plan debate judged"
+        );
+        Ok(DebateResult {
+            run_id: run_id.clone(),
+            missing_sections: missing_sections(&plan_md, plan_headings(&language)),
+            plan_md,
+            candidates,
+            judge_usage,
+            total_usage,
+            diagram_errors,
+        })
+    }.await;
+
+    if result.as_ref().is_err_and(|e| e.starts_with("[OFFLINE]")) {
+        offline::mark_offline(app);
+    }
+    let was_cancelled = result.is_err() && cancellation.is_cancelled();
+    finish_run(app, &run_id, result.as_ref().err().map(String::as_str), was_cancelled);
+    emit_run_event(app, RunEvent {
+        run_id: run_id.clone(),
+        project_id: project_id.to_string(),
+        task_id: task_id.to_string(),
+        run_type: "debate".into(),
+        status: match &result {
+            Ok(_) => RunStatus::Finished,
+            Err(_) if was_cancelled => RunStatus::Cancelled,
+            Err(_) => RunStatus::Failed,
+        },
+        error: result.as_ref().err().cloned(),
+        // Errors carry their code as a `[CODE]` prefix
+        code: result.as_ref().err()
+            .and_then(|e| e.strip_prefix('[')?.split_once(']').map(|(code, _)| code.to_string())),
+    });
+    result
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_seat_model_and_judge_prompt() {
+        let settings: SettingsMap = Arc::new(HashMap::from([
+            ("model".to_string(), "base".to_string()),
+            ("debate_model_b".to_string(), " other ".to_string()),
+        ]));
+        assert_eq!(seat_model(&settings, "debate_model_a", None), "base");
+        assert_eq!(seat_model(&settings, "debate_model_b", None), "other");
+        assert_eq!(seat_model(&settings, "debate_model_b", Some(" ")), "other");
+        assert_eq!(seat_model(&settings, "debate_model_a", Some("chosen")), "chosen");
+
+        let plans = vec![("base".to_string(), "# Plan A".to_string()), ("other".to_string(), "# Plan B".to_string())];
+        let messages = build_judge_messages("Add caching", &plans, "en");
+        let user = messages[1].content.as_deref().unwrap();
+        assert!(user.contains("## Candidate A (base)"));
+        assert!(user.contains("## Candidate B (other)"));
+        assert!(messages[0].content.as_deref().unwrap().contains("## Debate Notes"));
+    }
+}
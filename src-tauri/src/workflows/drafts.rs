@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::compression::encode;
+use crate::db;
+
+const CHECKPOINT_CHARS: usize = 2_000;
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Periodically persists streamed output as a draft artifact so a crash or
+/// cancellation mid-generation leaves the partial text recoverable.
+pub struct DraftCheckpointer {
+    app: AppHandle,
+    task_id: String,
+    kind: String,
+    buffer: String,
+    saved_len: usize,
+    last_saved: Instant,
+    draft_id: Option<String>,
+}
+
+impl DraftCheckpointer {
+    pub fn new(app: &AppHandle, task_id: &str, kind: &str) -> Self {
+        Self {
+            app: app.clone(),
+            task_id: task_id.to_string(),
+            kind: kind.to_string(),
+            buffer: String::new(),
+            saved_len: 0,
+            last_saved: Instant::now(),
+            draft_id: None,
+        }
+    }
+
+    /// Start a new generation; the draft row is kept and overwritten on the next checkpoint
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.saved_len = 0;
+        self.last_saved = Instant::now();
+    }
+
+    pub fn push(&mut self, delta: &str) {
+        self.buffer.push_str(delta);
+        let unsaved = self.buffer.len() - self.saved_len;
+        if unsaved >= CHECKPOINT_CHARS
+            || (unsaved > 0 && self.last_saved.elapsed() >= CHECKPOINT_INTERVAL)
+        {
+            // Checkpoints are best-effort; streaming continues if a write fails
+            let _ = self.checkpoint();
+        }
+    }
+
+    fn checkpoint(&mut self) -> Result<(), String> {
+        let conn = db::connect(&self.app).map_err(|e| e.to_string())?;
+        let created_at = now_iso();
+        let content = encode(&self.buffer).map_err(|e| e.to_string())?;
+
+        match &self.draft_id {
+            Some(id) => {
+                conn.execute(
+                    "UPDATE artifacts SET content = ?1, created_at = ?2 WHERE id = ?3",
+                    (&content, &created_at, id)
+                ).map_err(|e| e.to_string())?;
+            }
+            None => {
+                // Replace any draft left behind by an earlier crashed run
+                conn.execute(
+                    "DELETE FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = ?2 AND draft = 1",
+                    (&self.task_id, &self.kind)
+                ).map_err(|e| e.to_string())?;
+                let id = new_id();
+                conn.execute(
+                    "INSERT INTO artifacts (id, task_id, phase_id, kind, content, created_at, pinned, draft)
+                     VALUES (?1, ?2, NULL, ?3, ?4, ?5, 0, 1)",
+                    (&id, &self.task_id, &self.kind, &content, &created_at)
+                ).map_err(|e| e.to_string())?;
+                self.draft_id = Some(id);
+            }
+        }
+
+        self.saved_len = self.buffer.len();
+        self.last_saved = Instant::now();
+        Ok(())
+    }
+
+    /// Drop the draft once the final artifact has been saved
+    pub fn discard(self) -> Result<(), String> {
+        let Some(id) = self.draft_id else {
+            return Ok(());
+        };
+        let conn = db::connect(&self.app).map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM artifacts WHERE id = ?1 AND draft = 1", [&id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
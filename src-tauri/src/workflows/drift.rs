@@ -0,0 +1,475 @@
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::demo::{is_demo_project, DEMO_READ_ONLY_MESSAGE};
+use crate::models::Artifact;
+use crate::project_artifacts::{list_project_artifacts, upsert_project_artifact};
+use crate::repo_tools::context_ignore::{load_config, matcher, project_globs};
+use crate::settings::{cached_settings, AppSettings};
+use crate::workflows::context::read_text;
+use crate::workflows::templates::build_repo_map;
+
+/// Project artifact kinds written by a refresh
+pub const REPO_MAP_KIND: &str = "repo_map";
+pub const DRIFT_KIND: &str = "structure_drift";
+
+const REPO_MAP_MAX_FILES: usize = 20_000;
+const MAX_MANIFESTS: usize = 50;
+const MAX_LISTED: usize = 30;
+/// How often the scheduler looks for stale repo maps
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const FILES_HEADING: &str = "## Files";
+const DEPENDENCIES_HEADING: &str = "## Dependencies";
+
+/// A dependency declared by one of the repository's manifests
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Dependency {
+    pub manifest: String,
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+pub struct DependencyChange {
+    pub manifest: String,
+    pub name: String,
+    /// None when the dependency was added
+    pub before: Option<String>,
+    /// None when the dependency was removed
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct DriftResult {
+    pub repo_map: Artifact,
+    /// The drift summary; None on the first refresh or when nothing moved
+    pub drift: Option<Artifact>,
+    /// Directories that appeared, outermost only
+    pub new_modules: Vec<String>,
+    /// Directories that disappeared, outermost only
+    pub deleted_dirs: Vec<String>,
+    pub dependency_changes: Vec<DependencyChange>,
+    pub files_added: usize,
+    pub files_removed: usize,
+}
+
+/// What a repo map records: the file list and the declared dependencies
+#[derive(Debug, Default, PartialEq)]
+struct RepoSnapshot {
+    files: BTreeSet<String>,
+    dependencies: BTreeSet<Dependency>,
+    truncated: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Drift {
+    new_modules: Vec<String>,
+    deleted_dirs: Vec<String>,
+    dependency_changes: Vec<DependencyChange>,
+    files_added: usize,
+    files_removed: usize,
+}
+
+impl Drift {
+    fn is_empty(&self) -> bool {
+        self.new_modules.is_empty() && self.deleted_dirs.is_empty() && self.dependency_changes.is_empty()
+            && self.files_added == 0 && self.files_removed == 0
+    }
+}
+
+fn render_repo_map(snapshot: &RepoSnapshot) -> String {
+    let mut out = format!("{}\n", FILES_HEADING);
+    for file in &snapshot.files {
+        out.push_str(&format!("{}\n", file));
+    }
+    if snapshot.truncated {
+        out.push_str(&format!("... (truncated at {} files)\n", REPO_MAP_MAX_FILES));
+    }
+    out.push_str(&format!("\n{}\n", DEPENDENCIES_HEADING));
+    for dep in &snapshot.dependencies {
+        out.push_str(&format!("{}: {} {}\n", dep.manifest, dep.name, dep.version));
+    }
+    out
+}
+
+/// Read a stored repo map back. Maps without headings (edited by hand) are
+/// taken as a plain file list.
+fn parse_repo_map(content: &str) -> RepoSnapshot {
+    let mut snapshot = RepoSnapshot::default();
+    let mut in_dependencies = false;
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        match line {
+            FILES_HEADING => in_dependencies = false,
+            DEPENDENCIES_HEADING => in_dependencies = true,
+            _ if line.starts_with("... (") => snapshot.truncated = true,
+            _ if in_dependencies => {
+                let Some((manifest, rest)) = line.split_once(": ") else {
+                    continue;
+                };
+                let (name, version) = rest.split_once(' ').unwrap_or((rest, "*"));
+                snapshot.dependencies.insert(Dependency {
+                    manifest: manifest.to_string(),
+                    name: name.to_string(),
+                    version: version.to_string(),
+                });
+            }
+            _ => {
+                snapshot.files.insert(line.to_string());
+            }
+        }
+    }
+    snapshot
+}
+
+/// Every directory holding one of `files`, as "dir/" and "dir/sub/"
+fn directories(files: &BTreeSet<String>) -> BTreeSet<String> {
+    let mut dirs = BTreeSet::new();
+    for file in files {
+        let mut end = 0;
+        while let Some(i) = file[end..].find('/') {
+            end += i + 1;
+            dirs.insert(file[..end].to_string());
+        }
+    }
+    dirs
+}
+
+fn parent_dir(dir: &str) -> Option<&str> {
+    let trimmed = dir.trim_end_matches('/');
+    trimmed.rfind('/').map(|i| &dir[..=i])
+}
+
+/// Directories in `a` but not `b` whose parent is in `b` (or is the root)
+fn outermost_difference(a: &BTreeSet<String>, b: &BTreeSet<String>) -> Vec<String> {
+    a.difference(b)
+        .filter(|dir| parent_dir(dir).is_none_or(|parent| b.contains(parent)))
+        .cloned()
+        .collect()
+}
+
+fn compare(before: &RepoSnapshot, after: &RepoSnapshot) -> Drift {
+    let (dirs_before, dirs_after) = (directories(&before.files), directories(&after.files));
+    // A truncated map can't say what's missing from the part it didn't list
+    let new_modules = if before.truncated { vec![] } else { outermost_difference(&dirs_after, &dirs_before) };
+    let deleted_dirs = if after.truncated { vec![] } else { outermost_difference(&dirs_before, &dirs_after) };
+
+    let key = |d: &Dependency| (d.manifest.clone(), d.name.clone());
+    let old: BTreeMap<_, _> = before.dependencies.iter().map(|d| (key(d), d.version.clone())).collect();
+    let new: BTreeMap<_, _> = after.dependencies.iter().map(|d| (key(d), d.version.clone())).collect();
+    let mut dependency_changes = vec![];
+    for ((manifest, name), version) in &new {
+        match old.get(&(manifest.clone(), name.clone())) {
+            Some(previous) if previous == version => {}
+            previous => dependency_changes.push(DependencyChange {
+                manifest: manifest.clone(),
+                name: name.clone(),
+                before: previous.cloned(),
+                after: Some(version.clone()),
+            }),
+        }
+    }
+    for ((manifest, name), version) in &old {
+        if !new.contains_key(&(manifest.clone(), name.clone())) {
+            dependency_changes.push(DependencyChange {
+                manifest: manifest.clone(),
+                name: name.clone(),
+                before: Some(version.clone()),
+                after: None,
+            });
+        }
+    }
+    dependency_changes.sort_by(|a, b| (&a.manifest, &a.name).cmp(&(&b.manifest, &b.name)));
+
+    Drift {
+        new_modules,
+        deleted_dirs,
+        dependency_changes,
+        files_added: after.files.difference(&before.files).count(),
+        files_removed: if after.truncated { 0 } else { before.files.difference(&after.files).count() },
+    }
+}
+
+fn bullet_list(items: &[String]) -> String {
+    let mut out: String = items.iter().take(MAX_LISTED).map(|item| format!("- `{}`\n", item)).collect();
+    if items.len() > MAX_LISTED {
+        out.push_str(&format!("- ... and {} more\n", items.len() - MAX_LISTED));
+    }
+    out
+}
+
+fn render_drift(drift: &Drift, since: &str) -> String {
+    let mut out = format!(
+        "# Structure Drift\n\nSince the repository map of {}: {} files added, {} removed.\n",
+        since, drift.files_added, drift.files_removed
+    );
+    if !drift.new_modules.is_empty() {
+        out.push_str(&format!("\n## New Modules\n{}", bullet_list(&drift.new_modules)));
+    }
+    if !drift.deleted_dirs.is_empty() {
+        out.push_str(&format!("\n## Deleted Directories\n{}", bullet_list(&drift.deleted_dirs)));
+    }
+    if !drift.dependency_changes.is_empty() {
+        let lines: Vec<String> = drift.dependency_changes.iter()
+            .map(|c| match (&c.before, &c.after) {
+                (None, Some(after)) => format!("Added {} {} ({})", c.name, after, c.manifest),
+                (Some(before), None) => format!("Removed {} {} ({})", c.name, before, c.manifest),
+                (Some(before), Some(after)) => format!("{} {} → {} ({})", c.name, before, after, c.manifest),
+                (None, None) => format!("{} ({})", c.name, c.manifest),
+            })
+            .collect();
+        out.push_str("\n## Dependency Changes\n");
+        for line in lines.iter().take(MAX_LISTED) {
+            out.push_str(&format!("- {}\n", line));
+        }
+        if lines.len() > MAX_LISTED {
+            out.push_str(&format!("- ... and {} more\n", lines.len() - MAX_LISTED));
+        }
+    }
+    out
+}
+
+/// Version requirement of a Cargo dependency entry: a string, or a table with
+/// `version`, `path`, `git` or `workspace = true`
+fn cargo_version(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(version) => version.clone(),
+        toml::Value::Table(table) => table.get("version").and_then(|v| v.as_str()).map(str::to_string)
+            .or_else(|| table.get("path").and_then(|v| v.as_str()).map(|p| format!("path:{}", p)))
+            .or_else(|| table.get("git").and_then(|v| v.as_str()).map(|g| format!("git:{}", g)))
+            .unwrap_or_else(|| if table.contains_key("workspace") { "workspace".into() } else { "*".into() }),
+        _ => "*".into(),
+    }
+}
+
+fn cargo_dependencies(manifest: &str, text: &str) -> Vec<Dependency> {
+    let Ok(doc) = text.parse::<toml::Table>() else {
+        return vec![];
+    };
+    let sections = [
+        doc.get("dependencies"),
+        doc.get("dev-dependencies"),
+        doc.get("build-dependencies"),
+        doc.get("workspace").and_then(|w| w.get("dependencies")),
+    ];
+    sections.into_iter()
+        .flatten()
+        .filter_map(|section| section.as_table())
+        .flat_map(|table| table.iter())
+        .map(|(name, value)| Dependency { manifest: manifest.to_string(), name: name.clone(), version: cargo_version(value) })
+        .collect()
+}
+
+fn package_json_dependencies(manifest: &str, text: &str) -> Vec<Dependency> {
+    let Ok(doc) = serde_json::from_str::<Value>(text) else {
+        return vec![];
+    };
+    ["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"].iter()
+        .filter_map(|key| doc.get(key).and_then(|v| v.as_object()))
+        .flat_map(|deps| deps.iter())
+        .map(|(name, version)| Dependency {
+            manifest: manifest.to_string(),
+            name: name.clone(),
+            version: version.as_str().unwrap_or("*").to_string(),
+        })
+        .collect()
+}
+
+fn requirements_dependencies(manifest: &str, text: &str) -> Vec<Dependency> {
+    text.lines()
+        // Comments and environment markers don't name dependencies
+        .map(|line| line.split(['#', ';']).next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .map(|line| {
+            let split = line.find(['=', '<', '>', '~', '!', '[', ' ']).unwrap_or(line.len());
+            let rest = line[split..].trim();
+            let rest = match rest.strip_prefix('[') {
+                Some(extras) => extras.split_once(']').map_or("", |(_, version)| version),
+                None => rest,
+            };
+            let version = rest.replace(' ', "");
+            Dependency {
+                manifest: manifest.to_string(),
+                name: line[..split].to_string(),
+                version: if version.is_empty() { "*".into() } else { version },
+            }
+        })
+        .collect()
+}
+
+fn manifest_parser(path: &str) -> Option<fn(&str, &str) -> Vec<Dependency>> {
+    match path.rsplit('/').next()? {
+        "Cargo.toml" => Some(cargo_dependencies),
+        "package.json" => Some(package_json_dependencies),
+        "requirements.txt" => Some(requirements_dependencies),
+        _ => None,
+    }
+}
+
+async fn snapshot(app: &AppHandle, project_id: &str, repo_path: &Path) -> Result<RepoSnapshot, String> {
+    // Files kept out of model context stay out of the map too
+    let ignore = matcher(&load_config(project_globs(app, project_id).await?, repo_path).await, &[])?;
+    let mut snapshot = RepoSnapshot::default();
+    for line in build_repo_map(repo_path, REPO_MAP_MAX_FILES).lines().filter(|l| !l.is_empty()) {
+        if line.starts_with("... (") {
+            snapshot.truncated = true;
+        } else if !ignore.is_ignored(line) {
+            snapshot.files.insert(line.to_string());
+        }
+    }
+
+    let manifests = snapshot.files.iter()
+        .filter_map(|path| manifest_parser(path).map(|parse| (path.clone(), parse)))
+        .take(MAX_MANIFESTS)
+        .collect::<Vec<_>>();
+    for (path, parse) in manifests {
+        if let Ok(Some(text)) = read_text(repo_path, &path).await {
+            snapshot.dependencies.extend(parse(&path, &text));
+        }
+    }
+    Ok(snapshot)
+}
+
+/// Regenerate the project's repo map and, when an earlier map exists and the
+/// structure moved since, save a `structure_drift` summary of what changed
+pub async fn refresh_repo_map(app: &AppHandle, project_id: &str) -> Result<DriftResult, String> {
+    let repo_path: String = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        conn.query_row("SELECT repo_path FROM projects WHERE id = ?1", [project_id], |r| r.get(0))
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => format!("Project {} not found", project_id),
+                e => e.to_string(),
+            })?
+    };
+    if is_demo_project(app, project_id)? {
+        return Err(DEMO_READ_ONLY_MESSAGE.into());
+    }
+    let previous = list_project_artifacts(app, project_id, Some(REPO_MAP_KIND))?.into_iter().next();
+    let current = snapshot(app, project_id, Path::new(&repo_path)).await?;
+    if current.files.is_empty() {
+        return Err(format!("No files to map in {}", repo_path));
+    }
+
+    let drift = previous.as_ref().map(|p| (compare(&parse_repo_map(&p.content), &current), p.created_at.clone()));
+    let repo_map = upsert_project_artifact(app, project_id, REPO_MAP_KIND, &render_repo_map(&current))?;
+    let (drift, drift_artifact) = match drift {
+        Some((drift, since)) if !drift.is_empty() => {
+            let artifact = upsert_project_artifact(app, project_id, DRIFT_KIND, &render_drift(&drift, &since))?;
+            (drift, Some(artifact))
+        }
+        other => (other.map(|(drift, _)| drift).unwrap_or_default(), None),
+    };
+    tracing::info!(
+        project_id, files = current.files.len(), new_modules = drift.new_modules.len(),
+        deleted_dirs = drift.deleted_dirs.len(), dependency_changes = drift.dependency_changes.len(), "repo map refreshed"
+    );
+    Ok(DriftResult {
+        repo_map,
+        drift: drift_artifact,
+        new_modules: drift.new_modules,
+        deleted_dirs: drift.deleted_dirs,
+        dependency_changes: drift.dependency_changes,
+        files_added: drift.files_added,
+        files_removed: drift.files_removed,
+    })
+}
+
+/// Projects whose repo map is missing or older than `hours`
+fn stale_projects(app: &AppHandle, hours: u64) -> Result<Vec<String>, String> {
+    let cutoff = (time::OffsetDateTime::now_utc() - time::Duration::hours(hours as i64))
+        .format(&time::format_description::well_known::Rfc3339)
+        .map_err(|e| e.to_string())?;
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT p.id FROM projects p
+         LEFT JOIN artifacts a ON a.project_id = p.id AND a.kind = ?1 AND a.draft = 0
+         GROUP BY p.id HAVING MAX(a.created_at) IS NULL OR MAX(a.created_at) < ?2"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map((REPO_MAP_KIND, cutoff), |r| r.get(0)).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Refresh stale repo maps every `repo_map_refresh_hours` (0 turns it off),
+/// checking hourly for the lifetime of the app
+pub fn start_schedule(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let hours = match cached_settings(&handle).await {
+                Ok(settings) => AppSettings::from_map(&settings).repo_map_refresh_hours,
+                Err(_) => 0,
+            };
+            if hours > 0 {
+                let projects = stale_projects(&handle, hours).unwrap_or_else(|e| {
+                    tracing::warn!("failed to find stale repo maps: {}", e);
+                    vec![]
+                });
+                for project_id in projects {
+                    if is_demo_project(&handle, &project_id).unwrap_or(true) {
+                        continue;
+                    }
+                    if let Err(e) = refresh_repo_map(&handle, &project_id).await {
+                        tracing::warn!(project_id, "scheduled repo map refresh failed: {}", e);
+                    }
+                }
+            }
+            tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> BTreeSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_compare_snapshots() {
+        let before = RepoSnapshot {
+            files: files(&["Cargo.toml", "src/main.rs", "src/legacy/old.rs", "src/legacy/deep/x.rs"]),
+            dependencies: cargo_dependencies("Cargo.toml", "[dependencies]\nserde = \"1.0.1\"\nleft-pad = { version = \"0.1\" }\n").into_iter().collect(),
+            truncated: false,
+        };
+        let map = render_repo_map(&before);
+        assert_eq!(parse_repo_map(&map), before);
+
+        let after = RepoSnapshot {
+            files: files(&["Cargo.toml", "src/main.rs", "src/sync/mod.rs", "src/sync/s3/put.rs"]),
+            dependencies: cargo_dependencies("Cargo.toml", "[dependencies]\nserde = \"1.0.2\"\n[dev-dependencies]\ntempfile = { path = \"../tempfile\" }\n").into_iter().collect(),
+            truncated: false,
+        };
+        let drift = compare(&before, &after);
+        assert_eq!(drift.new_modules, ["src/sync/"]);
+        assert_eq!(drift.deleted_dirs, ["src/legacy/"]);
+        assert_eq!((drift.files_added, drift.files_removed), (2, 2));
+        let changes: Vec<(&str, Option<&str>, Option<&str>)> = drift.dependency_changes.iter()
+            .map(|c| (c.name.as_str(), c.before.as_deref(), c.after.as_deref()))
+            .collect();
+        assert_eq!(changes, [
+            ("left-pad", Some("0.1"), None),
+            ("serde", Some("1.0.1"), Some("1.0.2")),
+            ("tempfile", None, Some("path:../tempfile")),
+        ]);
+        assert!(compare(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_manifest_dependencies() {
+        let deps = package_json_dependencies("web/package.json", r#"{"dependencies": {"react": "^18.2.0"}, "devDependencies": {"vite": "5"}}"#);
+        let names: Vec<(&str, &str)> = deps.iter().map(|d| (d.name.as_str(), d.version.as_str())).collect();
+        assert_eq!(names, [("react", "^18.2.0"), ("vite", "5")]);
+
+        let deps = requirements_dependencies("requirements.txt", "# pinned\nrequests[socks]==2.31.0\nflask >= 3.0 ; python_version > '3.8'\nnumpy\n-r dev.txt\n");
+        let names: Vec<(&str, &str)> = deps.iter().map(|d| (d.name.as_str(), d.version.as_str())).collect();
+        assert_eq!(names, [("requests", "==2.31.0"), ("flask", ">=3.0"), ("numpy", "*")]);
+    }
+}
@@ -0,0 +1,444 @@
+use rusqlite::OptionalExtension;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use tauri::AppHandle;
+
+use crate::blobs;
+use crate::cancellation::{self, RunCancellation, CANCELLED_MESSAGE};
+use crate::db;
+use crate::demo::{is_demo_project, DEMO_READ_ONLY_MESSAGE};
+use crate::llm::{ChatMessage, ChatOptions, LlmClient, LlmResponse, ToolCall, ToolChoice};
+use crate::models::{new_id, Project, Task};
+use crate::offline::{self, OFFLINE_MESSAGE};
+use crate::repo_tools::{dispatch_repo_tool, repo_tool_schemas, ToolLoopGuard};
+use crate::repo_tools::permissions::tool_schemas_for;
+use crate::repo_tools::schemas::{adapt_schemas, SchemaProfile};
+use crate::settings::{AppSettings, SettingsMap};
+use crate::events::{RunEvent, RunStatus};
+use crate::windows::emit_run_event;
+use crate::workflows::budget::{check_run_budget, record_usage};
+use crate::workflows::drafts::DraftCheckpointer;
+use crate::workflows::plan::build_llm_config;
+use crate::workflows::sanitize::wrap_untrusted;
+
+const FINAL_CALL_NUDGE: &str = "This is the last response of the run and tools are now disabled. \
+Give your complete answer using only the context gathered so far.";
+const LOOP_NUDGE: &str = "You are repeating tool calls you already have results for, so tools are now disabled. \
+Give your complete answer using only the context gathered so far.";
+
+/// A hook's result, boxed so definitions can do async work like calling tools
+pub type HookFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// How the model is asked for the workflow's output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkflowMode {
+    /// One completion, without tools
+    SingleCall,
+    /// The repo tool loop, with the tools the run type is permitted, for at
+    /// most `max_iterations` model calls; the last is made with tools disabled
+    AgentLoop { max_iterations: usize },
+}
+
+/// A run in progress, as a workflow's hooks see it
+pub struct WorkflowRun<'a> {
+    pub app: &'a AppHandle,
+    pub run_id: String,
+    pub task: Task,
+    pub project: Project,
+    pub cancellation: RunCancellation,
+}
+
+impl WorkflowRun<'_> {
+    pub fn repo_path(&self) -> &Path {
+        Path::new(&self.project.repo_path)
+    }
+
+    /// Call a repo tool as part of the run, permission-checked and logged like the model's calls
+    pub async fn tool(&self, name: &str, args: Value) -> Result<Value, String> {
+        dispatch_repo_tool(name, &args, self.repo_path(), self.app, &self.run_id).await
+    }
+}
+
+/// What gathering context produced: the opening messages, and whatever the
+/// later steps need to know about how they were built
+pub struct Gathered<C> {
+    pub messages: Vec<ChatMessage>,
+    pub context: C,
+}
+
+/// A workflow, as the steps the engine runs for it: gather context, call the
+/// model (once, or in a tool loop), post-process the answer, save the artifact.
+/// The engine owns the run around them: its row, events, logged messages,
+/// usage, drafts, and the demo, offline and budget checks.
+pub trait Workflow: Send + Sync {
+    /// What `gather_context` passes on to the later hooks
+    type Context: Send;
+
+    /// Recorded on the run; also decides which tools the run may call
+    fn run_type(&self) -> &str;
+
+    /// Kind of the task artifact the output is saved as
+    fn artifact_kind(&self) -> &str;
+
+    fn mode(&self) -> WorkflowMode {
+        WorkflowMode::SingleCall
+    }
+
+    /// Tools the loop offers, narrowed from what the run type is permitted;
+    /// `None` offers all of them
+    fn allowed_tools(&self) -> Option<&[String]> {
+        None
+    }
+
+    fn gather_context<'a>(&'a self, run: &'a WorkflowRun<'a>) -> HookFuture<'a, Gathered<Self::Context>>;
+
+    /// Check or rework the model's answer before it's saved
+    fn post_process(&self, _run: &WorkflowRun, _context: &mut Self::Context, output: String) -> Result<String, String> {
+        Ok(output)
+    }
+
+    /// Runs once the artifact is saved, e.g. to store what was derived from it
+    fn after_save(&self, _run: &WorkflowRun, _context: &mut Self::Context, _artifact_id: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct WorkflowOutput<C> {
+    pub run_id: String,
+    pub artifact_id: String,
+    pub content: String,
+    pub tool_calls_count: usize,
+    /// The tool loop ran out of calls or went in circles, and the answer was made without tools
+    pub forced_completion: bool,
+    pub context: C,
+}
+
+/// Runs workflow definitions for tasks: the diff walkthrough, phases, review
+/// and custom workflows. Plan and verify keep their own loops for what the
+/// engine doesn't model (seeded reruns, continued conversations, tokens
+/// streamed to the window, error codes, cached reports and checks) and share
+/// its run bookkeeping: `finish_run`, `log_message` and `save_artifact`.
+pub struct WorkflowEngine<'a> {
+    app: &'a AppHandle,
+    settings: &'a SettingsMap,
+    api_key: &'a str,
+}
+
+impl<'a> WorkflowEngine<'a> {
+    pub fn new(app: &'a AppHandle, settings: &'a SettingsMap, api_key: &'a str) -> Self {
+        Self { app, settings, api_key }
+    }
+
+    #[tracing::instrument(skip_all, fields(run_type = workflow.run_type(), run_id = tracing::field::Empty))]
+    pub async fn run<W: Workflow>(&self, workflow: &W, task_id: &str) -> Result<WorkflowOutput<W::Context>, String> {
+        let (task, project) = load_task(self.app, task_id)?;
+        if is_demo_project(self.app, &project.id)? {
+            return Err(DEMO_READ_ONLY_MESSAGE.into());
+        }
+        if offline::is_offline(self.app, self.settings) {
+            return Err(OFFLINE_MESSAGE.into());
+        }
+        check_run_budget(self.app, self.settings, &project.id).map_err(|b| b.message)?;
+
+        let llm_config = build_llm_config(self.settings)?;
+        let run_id = create_run(self.app, task_id, workflow.run_type(), &llm_config.provider_name, &llm_config.model)?;
+        tracing::Span::current().record("run_id", run_id.as_str());
+        let event = |status: RunStatus, error: Option<String>| RunEvent {
+            run_id: run_id.clone(),
+            project_id: project.id.clone(),
+            task_id: task_id.to_string(),
+            run_type: workflow.run_type().to_string(),
+            code: matches!(status, RunStatus::Cancelled).then(|| "CANCELLED".to_string()),
+            status,
+            error,
+        };
+        emit_run_event(self.app, event(RunStatus::Started, None));
+
+        let cancellation = cancellation::register(self.app, &run_id);
+        let run = WorkflowRun { app: self.app, run_id: run_id.clone(), task, project: project.clone(), cancellation };
+        let client = LlmClient::new(llm_config, self.api_key.to_string());
+        let result = self.execute(workflow, &run, &client).await;
+
+        let cancelled = result.as_ref().is_err_and(|e| e == CANCELLED_MESSAGE);
+        finish_run(self.app, &run_id, result.as_ref().err().map(String::as_str), cancelled);
+        match &result {
+            Ok(_) => emit_run_event(self.app, event(RunStatus::Finished, None)),
+            Err(e) if cancelled => emit_run_event(self.app, event(RunStatus::Cancelled, Some(e.clone()))),
+            Err(e) => emit_run_event(self.app, event(RunStatus::Failed, Some(e.clone()))),
+        }
+        result
+    }
+
+    async fn execute<W: Workflow>(
+        &self,
+        workflow: &W,
+        run: &WorkflowRun<'_>,
+        client: &LlmClient,
+    ) -> Result<WorkflowOutput<W::Context>, String> {
+        // 1. Gather context
+        let Gathered { messages, mut context } = workflow.gather_context(run).await?;
+        for msg in &messages {
+            log_message(self.app, &run.run_id, &msg.role, msg.content.as_deref().unwrap_or(""), None, None)?;
+        }
+
+        // 2. Call the model
+        let mut draft = DraftCheckpointer::new(self.app, &run.task.id, workflow.artifact_kind());
+        let (answer, tool_calls_count, forced_completion) = match workflow.mode() {
+            WorkflowMode::SingleCall => {
+                let response = self.complete(run, client, &mut draft, messages, vec![], ChatOptions::default()).await?;
+                (response.content.unwrap_or_default(), 0, false)
+            }
+            WorkflowMode::AgentLoop { max_iterations } => {
+                self.tool_loop(workflow, run, client, &mut draft, messages, max_iterations).await?
+            }
+        };
+        if answer.trim().is_empty() {
+            return Err("No response from LLM".into());
+        }
+        log_message(self.app, &run.run_id, "assistant", &answer, None, None)?;
+
+        // 3. Post-process
+        let content = workflow.post_process(run, &mut context, answer)?;
+
+        // 4. Save the artifact
+        let artifact_id = save_artifact(self.app, &run.task.id, None, workflow.artifact_kind(), &content)?;
+        let _ = draft.discard();
+        workflow.after_save(run, &mut context, &artifact_id)?;
+
+        Ok(WorkflowOutput { run_id: run.run_id.clone(), artifact_id, content, tool_calls_count, forced_completion, context })
+    }
+
+    /// One model call, streamed into a draft unless streaming is off
+    async fn complete(
+        &self,
+        run: &WorkflowRun<'_>,
+        client: &LlmClient,
+        draft: &mut DraftCheckpointer,
+        messages: Vec<ChatMessage>,
+        tools: Vec<Value>,
+        options: ChatOptions,
+    ) -> Result<LlmResponse, String> {
+        run.cancellation.check()?;
+        draft.reset();
+        let response = run.cancellation.or_cancel(async {
+            if AppSettings::from_map(self.settings).stream_responses {
+                client.chat_stream(messages, tools, options, |delta| draft.push(delta)).await
+            } else {
+                client.chat_with_options(messages, tools, options).await
+            }
+        }).await?.map_err(|e| e.to_string())?;
+        record_usage(self.app, self.settings, &run.run_id, response.usage);
+        Ok(response)
+    }
+
+    /// Let the model call tools until it answers. Returns the answer, how many
+    /// tools it called, and whether tools had to be taken away to get it.
+    async fn tool_loop<W: Workflow>(
+        &self,
+        workflow: &W,
+        run: &WorkflowRun<'_>,
+        client: &LlmClient,
+        draft: &mut DraftCheckpointer,
+        mut messages: Vec<ChatMessage>,
+        max_iterations: usize,
+    ) -> Result<(String, usize, bool), String> {
+        let mut schemas = tool_schemas_for(workflow.run_type(), repo_tool_schemas());
+        if let Some(allowed) = workflow.allowed_tools() {
+            schemas.retain(|schema| allowed.iter().any(|tool| schema["function"]["name"] == tool.as_str()));
+        }
+        let offered: Vec<String> = schemas.iter()
+            .filter_map(|schema| schema["function"]["name"].as_str().map(str::to_string))
+            .collect();
+        let tools = adapt_schemas(schemas, SchemaProfile::from_settings(self.settings));
+        let loop_threshold = AppSettings::from_map(self.settings).tool_loop_threshold;
+        let parallel_tool_calls = self.settings.get("parallel_tool_calls").map(|v| v == "1");
+        let mut loop_guard = ToolLoopGuard::new(loop_threshold);
+        let mut tool_calls_count = 0;
+        let mut force_final = false;
+
+        for iteration in 0..max_iterations {
+            if iteration + 1 == max_iterations && !force_final {
+                force_final = true;
+                self.push_system(run, &mut messages, FINAL_CALL_NUDGE)?;
+            }
+
+            let tool_choice = if force_final { ToolChoice::None } else { ToolChoice::Auto };
+            let options = ChatOptions { tool_choice: Some(tool_choice), parallel_tool_calls };
+            let response = self.complete(run, client, draft, messages.clone(), tools.clone(), options).await?;
+
+            // Stray tool calls after tools were withdrawn are ignored
+            let Some(tool_calls) = response.tool_calls.filter(|calls| !calls.is_empty() && !force_final) else {
+                return Ok((response.content.unwrap_or_default(), tool_calls_count, force_final));
+            };
+            tool_calls_count += tool_calls.len();
+
+            let tool_names: Vec<&str> = tool_calls.iter().map(|t| t.function.name.as_str()).collect();
+            let assistant_content = response.content.clone()
+                .unwrap_or_else(|| format!("Calling tools: {}", tool_names.join(", ")));
+            log_message(self.app, &run.run_id, "assistant", &assistant_content, Some(tool_calls.as_slice()), None)?;
+
+            let mut looping = false;
+            for tool_call in &tool_calls {
+                let args = serde_json::from_str::<Value>(&tool_call.function.arguments).unwrap_or(Value::Null);
+                loop_guard.observe(&tool_call.function.name, &args);
+                looping |= loop_guard.is_looping();
+            }
+
+            messages.push(ChatMessage {
+                role: "assistant".into(),
+                content: response.content,
+                tool_call_id: None,
+                tool_calls: Some(tool_calls.clone()),
+            });
+
+            for tool_call in &tool_calls {
+                run.cancellation.check()?;
+                let name = &tool_call.function.name;
+                let result = match serde_json::from_str::<Value>(&tool_call.function.arguments) {
+                    Ok(_) if !offered.contains(name) => Err(format!("{} is not one of this workflow's tools", name)),
+                    Ok(args) => run.tool(name, args).await,
+                    Err(e) => Err(format!("Failed to parse tool args: {}", e)),
+                };
+                let content = match result {
+                    Ok(val) => val.to_string(),
+                    Err(e) => json!({ "error": e }).to_string(),
+                };
+                let content = wrap_untrusted(&tool_call.function.name, &content);
+                log_message(self.app, &run.run_id, "tool", &content, None, Some(&tool_call.id))?;
+                messages.push(ChatMessage {
+                    role: "tool".into(),
+                    content: Some(content),
+                    tool_call_id: Some(tool_call.id.clone()),
+                    tool_calls: None,
+                });
+            }
+
+            // A model going in circles answers with what it has
+            if looping && !force_final {
+                force_final = true;
+                self.push_system(run, &mut messages, LOOP_NUDGE)?;
+            }
+        }
+        Ok((String::new(), tool_calls_count, force_final))
+    }
+
+    fn push_system(&self, run: &WorkflowRun<'_>, messages: &mut Vec<ChatMessage>, content: &str) -> Result<(), String> {
+        messages.push(ChatMessage { role: "system".into(), content: Some(content.into()), tool_call_id: None, tool_calls: None });
+        log_message(self.app, &run.run_id, "system", content, None, None)
+    }
+}
+
+fn load_task(app: &AppHandle, task_id: &str) -> Result<(Task, Project), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT t.id, t.project_id, t.title, t.mode, t.status, t.created_at, t.updated_at, t.description,
+                p.name, p.repo_path, p.created_at, p.last_opened_at, p.workspace_id
+         FROM tasks t JOIN projects p ON p.id = t.project_id WHERE t.id = ?1",
+        [task_id],
+        |r| {
+            let task = Task {
+                id: r.get(0)?,
+                project_id: r.get(1)?,
+                title: r.get(2)?,
+                mode: r.get(3)?,
+                status: r.get(4)?,
+                created_at: r.get(5)?,
+                updated_at: r.get(6)?,
+                description: r.get(7)?,
+            };
+            let project = Project {
+                id: task.project_id.clone(),
+                name: r.get(8)?,
+                repo_path: r.get(9)?,
+                created_at: r.get(10)?,
+                last_opened_at: r.get(11)?,
+                workspace_id: r.get(12)?,
+            };
+            Ok((task, project))
+        }
+    ).map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("Task {} not found", task_id),
+        e => e.to_string(),
+    })
+}
+
+fn create_run(app: &AppHandle, task_id: &str, run_type: &str, provider: &str, model: &str) -> Result<String, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let id = new_id();
+    conn.execute(
+        "INSERT INTO runs (id, task_id, run_type, provider, model, started_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (&id, task_id, run_type, provider, model, now_iso())
+    ).map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Stamp the run's end time and error, if any, and whether it was cancelled.
+/// Best-effort: a failure here must not mask the workflow's own result.
+pub(crate) fn finish_run(app: &AppHandle, run_id: &str, error: Option<&str>, cancelled: bool) {
+    if let Ok(conn) = db::connect(app) {
+        let now = now_iso();
+        let _ = conn.execute(
+            "UPDATE runs SET ended_at = ?1, error = ?2, cancelled_at = ?3 WHERE id = ?4",
+            (&now, error, cancelled.then_some(&now), run_id)
+        );
+    }
+}
+
+/// Log a message with the tool calls an assistant message made, or the call a
+/// tool message answers, so the conversation can be replayed
+pub(crate) fn log_message(
+    app: &AppHandle,
+    run_id: &str,
+    role: &str,
+    content: &str,
+    tool_calls: Option<&[ToolCall]>,
+    tool_call_id: Option<&str>,
+) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let (stored, hash) = blobs::put(&conn, content).map_err(|e| e.to_string())?;
+    let tool_calls_json = tool_calls.map(serde_json::to_string).transpose().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO messages (id, run_id, role, content, created_at, content_hash, tool_calls_json, tool_call_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        (new_id(), run_id, role, &stored, now_iso(), &hash, &tool_calls_json, tool_call_id)
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Save the task's (or one of its phases') artifact of `kind`, replacing the previous one
+pub(crate) fn save_artifact(app: &AppHandle, task_id: &str, phase_id: Option<&str>, kind: &str, content: &str) -> Result<String, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let (stored, hash) = blobs::put(&conn, content).map_err(|e| e.to_string())?;
+    let existing: Option<String> = conn.query_row(
+        "SELECT id FROM artifacts WHERE task_id = ?1 AND COALESCE(phase_id, '') = COALESCE(?2, '') AND kind = ?3 AND draft = 0 LIMIT 1",
+        (task_id, phase_id, kind),
+        |r| r.get(0)
+    ).optional().map_err(|e| e.to_string())?;
+    match existing {
+        Some(id) => {
+            conn.execute(
+                "UPDATE artifacts SET content = ?1, content_hash = ?2, created_at = ?3 WHERE id = ?4",
+                (&stored, &hash, now_iso(), &id)
+            ).map_err(|e| e.to_string())?;
+            Ok(id)
+        }
+        None => {
+            let id = new_id();
+            conn.execute(
+                "INSERT INTO artifacts (id, task_id, phase_id, kind, content, content_hash, created_at, pinned)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+                (&id, task_id, phase_id, kind, &stored, &hash, now_iso())
+            ).map_err(|e| e.to_string())?;
+            Ok(id)
+        }
+    }
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
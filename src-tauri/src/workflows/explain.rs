@@ -0,0 +1,491 @@
+use rusqlite::OptionalExtension;
+use serde::Serialize;
+use serde_json::{json, Value};
+use specta::Type;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::blobs;
+use crate::db;
+use crate::demo::{is_demo_project, DEMO_READ_ONLY_MESSAGE};
+use crate::llm::{ChatMessage, LlmClient};
+use crate::models::Artifact;
+use crate::project_artifacts::upsert_project_artifact;
+use crate::repo_tools::context_ignore::{load_config, matcher, project_globs};
+use crate::repo_tools::safety::truncate_string;
+use crate::settings::SettingsMap;
+use crate::workflows::budget::{check_run_budget, record_project_usage};
+use crate::workflows::context::{hot_files, read_text};
+use crate::workflows::engine::{Gathered, HookFuture, Workflow, WorkflowEngine, WorkflowRun};
+use crate::workflows::plan::build_llm_config;
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+use crate::workflows::templates::build_repo_map;
+
+/// Artifact kind of a diff walkthrough
+pub const WALKTHROUGH_KIND: &str = "diff_walkthrough";
+
+const MAX_DIFF_CHARS: usize = 100_000;
+const MAX_PLAN_CHARS: usize = 20_000;
+
+/// Project artifact kind of an architecture overview
+pub const OVERVIEW_KIND: &str = "repo_overview";
+
+const REPO_MAP_MAX_FILES: usize = 400;
+const MAX_FOCUS_CHARS: usize = 500;
+/// Files read in full for an overview, and the budget for their contents
+const MAX_READ_FILES: usize = 16;
+const MAX_FILE_CHARS: usize = 8_000;
+const MAX_READ_CHARS: usize = 60_000;
+const MAX_ENTRY_POINTS: usize = 6;
+const MAX_FOCUS_FILES: usize = 6;
+const MAX_HOT_FILES: usize = 8;
+
+/// Root files that say what a project is and how it's built
+const KEY_FILES: &[&str] = &[
+    "README.md", "README", "README.rst", "ARCHITECTURE.md", "CONTRIBUTING.md",
+    "Cargo.toml", "package.json", "pyproject.toml", "go.mod", "pom.xml", "build.gradle", "Gemfile", "composer.json",
+];
+/// File names programs usually start from
+const ENTRY_POINTS: &[&str] = &[
+    "main.rs", "lib.rs", "main.go", "main.py", "__main__.py", "app.py", "index.ts", "index.js", "main.ts", "main.tsx", "App.tsx",
+];
+
+#[derive(Debug, Serialize, Type)]
+pub struct WalkthroughResult {
+    pub run_id: String,
+    pub artifact_id: String,
+    pub walkthrough_md: String,
+    /// Changed files in the order the walkthrough visits them
+    pub order: Vec<String>,
+    /// Changed files the walkthrough skipped; listed under "Other Changes"
+    pub unmentioned: Vec<String>,
+    /// The diff was staged rather than in the working tree
+    pub staged: bool,
+    /// The diff was cut short to fit the prompt
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct OverviewResult {
+    pub artifact: Artifact,
+    /// Files read into the prompt, in the order they were picked
+    pub read_files: Vec<String>,
+}
+
+/// A file in the diff and the size of its change
+#[derive(Debug, Clone, PartialEq)]
+struct ChangedFile {
+    path: String,
+    added: usize,
+    removed: usize,
+}
+
+/// Files in a unified diff, in the diff's order
+fn changed_files(diff: &str) -> Vec<ChangedFile> {
+    let mut files: Vec<ChangedFile> = vec![];
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header.rsplit_once(" b/").map_or(header, |(_, path)| path);
+            files.push(ChangedFile { path: path.to_string(), added: 0, removed: 0 });
+        } else if let Some(file) = files.last_mut() {
+            if line.starts_with('+') && !line.starts_with("+++") {
+                file.added += 1;
+            } else if line.starts_with('-') && !line.starts_with("---") {
+                file.removed += 1;
+            }
+        }
+    }
+    files
+}
+
+/// Changed files in the order the walkthrough's step headings name them, and
+/// the ones it never names
+fn walkthrough_order(walkthrough_md: &str, files: &[ChangedFile]) -> (Vec<String>, Vec<String>) {
+    let mut order: Vec<String> = vec![];
+    for heading in walkthrough_md.lines().filter(|l| l.starts_with("### ")) {
+        // Paths are quoted in backticks: every other piece of the split
+        for quoted in heading.split('`').skip(1).step_by(2) {
+            let quoted = quoted.trim().trim_start_matches("./");
+            if files.iter().any(|f| f.path == quoted) && !order.iter().any(|p| p == quoted) {
+                order.push(quoted.to_string());
+            }
+        }
+    }
+    let unmentioned = files.iter()
+        .filter(|f| !order.contains(&f.path))
+        .map(|f| f.path.clone())
+        .collect();
+    (order, unmentioned)
+}
+
+fn build_messages(title: &str, files: &[ChangedFile], diff: &str, plan_md: Option<&str>, truncated: bool) -> Vec<ChatMessage> {
+    let mut system_prompt = r#"You are a senior engineer guiding a reviewer through a change.
+
+Your task: Turn the diff into a walkthrough the reviewer can follow top to bottom.
+Order the steps the way the change is best understood, not alphabetically:
+start with schema, migrations, types and interfaces, then the core logic that
+uses them, then call sites, commands and UI, and finish with tests and config.
+
+Required output format (Markdown):
+
+# Diff Walkthrough: [Title]
+
+## Overview
+What the change does and how it hangs together (2-4 sentences).
+
+## Walkthrough
+### 1. `path/to/file`
+What changed in this file, why it comes at this point, and how it connects to the
+previous steps. Point out what the reviewer should check closely.
+
+### 2. `path/to/other_file`, `path/to/related_file`
+Closely related small files may share a step; quote each path in backticks.
+
+## Review Checklist
+- [ ] Specific things to verify across the change
+
+Every changed file must appear in exactly one step heading, with its exact path."#.to_string();
+    system_prompt.push_str(UNTRUSTED_CONTENT_REMINDER);
+
+    let mut user_prompt = format!("Task: {}\n\n", title);
+    if let Some(plan) = plan_md {
+        let (plan, _) = truncate_string(plan, MAX_PLAN_CHARS);
+        user_prompt.push_str(&format!("## Implementation Plan\n{}\n\n", wrap_untrusted("plan", &plan)));
+    }
+    user_prompt.push_str("## Changed Files\n");
+    for file in files {
+        user_prompt.push_str(&format!("- `{}` (+{} -{})\n", file.path, file.added, file.removed));
+    }
+    let label = if truncated { "Diff (truncated)" } else { "Diff" };
+    user_prompt.push_str(&format!("\n## {}\n{}\n", label, wrap_untrusted("git_diff", &format!("```diff\n{}\n```", diff))));
+
+    vec![
+        ChatMessage { role: "system".into(), content: Some(system_prompt), tool_call_id: None, tool_calls: None },
+        ChatMessage { role: "user".into(), content: Some(user_prompt), tool_call_id: None, tool_calls: None },
+    ]
+}
+
+/// The working tree's diff, or the staged one when everything is staged
+async fn current_diff(run: &WorkflowRun<'_>) -> Result<(String, bool, bool), String> {
+    for staged in [false, true] {
+        let result = run.tool("git_diff", json!({ "staged": staged })).await?;
+        let diff = result.get("diff").and_then(Value::as_str).unwrap_or_default();
+        if !diff.trim().is_empty() {
+            let truncated = result.get("truncated").and_then(Value::as_bool).unwrap_or(false);
+            return Ok((diff.to_string(), staged, truncated));
+        }
+    }
+    Err("There are no changes to explain".into())
+}
+
+fn load_plan(app: &AppHandle, task_id: &str) -> Result<Option<String>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT content, content_hash FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = 'plan_md' AND draft = 0 LIMIT 1",
+        [task_id],
+        |r| blobs::hydrate(&conn, r.get(0)?, r.get(1)?)
+    ).optional().map_err(|e| e.to_string())
+}
+
+/// What the walkthrough was built from, and what post-processing found
+struct WalkthroughContext {
+    files: Vec<ChangedFile>,
+    staged: bool,
+    truncated: bool,
+    order: Vec<String>,
+    unmentioned: Vec<String>,
+}
+
+/// Walkthrough of a task's current diff, as a workflow definition
+struct DiffWalkthrough;
+
+impl Workflow for DiffWalkthrough {
+    type Context = WalkthroughContext;
+
+    fn run_type(&self) -> &str {
+        "explain"
+    }
+
+    fn artifact_kind(&self) -> &str {
+        WALKTHROUGH_KIND
+    }
+
+    fn gather_context<'a>(&'a self, run: &'a WorkflowRun<'a>) -> HookFuture<'a, Gathered<WalkthroughContext>> {
+        Box::pin(async move {
+            let (diff, staged, tool_truncated) = current_diff(run).await?;
+            let files = changed_files(&diff);
+            let (diff, truncated) = truncate_string(&diff, MAX_DIFF_CHARS);
+            let truncated = truncated || tool_truncated;
+            let plan_md = load_plan(run.app, &run.task.id)?;
+            Ok(Gathered {
+                messages: build_messages(&run.task.title, &files, &diff, plan_md.as_deref(), truncated),
+                context: WalkthroughContext { files, staged, truncated, order: vec![], unmentioned: vec![] },
+            })
+        })
+    }
+
+    /// Files the model skipped still get listed so the review covers the whole diff
+    fn post_process(&self, _run: &WorkflowRun, context: &mut WalkthroughContext, output: String) -> Result<String, String> {
+        let (order, unmentioned) = walkthrough_order(&output, &context.files);
+        let walkthrough_md = if unmentioned.is_empty() {
+            output
+        } else {
+            let list: Vec<String> = unmentioned.iter().map(|p| format!("- `{}`", p)).collect();
+            format!("{}\n\n## Other Changes\n{}\n", output.trim_end(), list.join("\n"))
+        };
+        context.order = order;
+        context.unmentioned = unmentioned;
+        Ok(walkthrough_md)
+    }
+}
+
+/// Walk a reviewer through the task's current diff file by file, in the
+/// order the change is best read, and save it as the task's walkthrough
+pub async fn explain_diff(app: &AppHandle, settings: &SettingsMap, api_key: &str, task_id: &str) -> Result<WalkthroughResult, String> {
+    let output = WorkflowEngine::new(app, settings, api_key).run(&DiffWalkthrough, task_id).await?;
+    let WalkthroughContext { staged, truncated, order, unmentioned, .. } = output.context;
+    Ok(WalkthroughResult {
+        run_id: output.run_id,
+        artifact_id: output.artifact_id,
+        walkthrough_md: output.content,
+        order,
+        unmentioned,
+        staged,
+        truncated,
+    })
+}
+
+fn push_unique(picked: &mut Vec<String>, path: &str) {
+    if !picked.iter().any(|p| p == path) {
+        picked.push(path.to_string());
+    }
+}
+
+/// Files worth reading for an overview: key root files, the shallowest entry
+/// points, files matching the focus, then the most often changed files
+fn pick_files(files: &[String], hot: &[(String, usize)], focus: Option<&str>) -> Vec<String> {
+    let depth = |path: &str| path.matches('/').count();
+    let mut picked = vec![];
+    for name in KEY_FILES {
+        if files.iter().any(|f| f == name) {
+            push_unique(&mut picked, name);
+        }
+    }
+
+    let mut entries: Vec<&String> = files.iter()
+        .filter(|f| ENTRY_POINTS.contains(&f.rsplit('/').next().unwrap_or(f)))
+        .collect();
+    entries.sort_by_key(|f| (depth(f), f.len()));
+    for path in entries.into_iter().take(MAX_ENTRY_POINTS) {
+        push_unique(&mut picked, path);
+    }
+
+    let terms: Vec<String> = focus.unwrap_or_default()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|term| term.len() >= 3)
+        .map(str::to_lowercase)
+        .collect();
+    if !terms.is_empty() {
+        let mut matches: Vec<&String> = files.iter()
+            .filter(|f| terms.iter().any(|term| f.to_lowercase().contains(term.as_str())))
+            .collect();
+        matches.sort_by_key(|f| (depth(f), f.len()));
+        for path in matches.into_iter().take(MAX_FOCUS_FILES) {
+            push_unique(&mut picked, path);
+        }
+    }
+
+    for (path, _) in hot.iter().take(MAX_HOT_FILES) {
+        push_unique(&mut picked, path);
+    }
+    picked.truncate(MAX_READ_FILES);
+    picked
+}
+
+fn build_overview_messages(
+    name: &str,
+    focus: Option<&str>,
+    repo_map: &[String],
+    hot: &[(String, usize)],
+    contents: &str,
+) -> Vec<ChatMessage> {
+    let mut system_prompt = r#"You are a senior engineer onboarding a developer who is new to this codebase.
+
+Your task: Explain how the project is put together so a newcomer can find their way
+around and make a first change. Base every claim on the files you were given; say so
+when something is a guess from file names alone.
+
+Required output format (Markdown):
+
+# Architecture Overview: [Project]
+
+## 1. What This Project Does
+Purpose and main features (2-4 sentences).
+
+## 2. Tech Stack
+Languages, frameworks, build tools and notable dependencies.
+
+## 3. Directory Layout
+The top-level directories and what lives in each.
+
+## 4. Key Components
+For each major module: where it lives, what it owns, and what it talks to.
+
+## 5. How It Works
+Walk through one typical flow (a request, command or job) from entry point to result.
+
+## 6. Where to Start
+An ordered reading list of files for a newcomer, each with one line on why.
+
+## 7. Conventions & Gotchas
+Patterns the code follows, and anything surprising.
+
+## 8. Diagram (Optional)
+One mermaid `flowchart` of the main components when it helps."#.to_string();
+    if let Some(focus) = focus {
+        system_prompt.push_str(&format!(
+            "\n\nThe reader especially wants to understand: {}\nGo deeper on the parts of the codebase involved.",
+            focus
+        ));
+    }
+    system_prompt.push_str(UNTRUSTED_CONTENT_REMINDER);
+
+    let mut user_prompt = format!("Project: {}\n\n", name);
+    user_prompt.push_str(&format!("## Repository Map\n{}\n\n", wrap_untrusted("repo_map", &repo_map.join("\n"))));
+    if !hot.is_empty() {
+        let list: Vec<String> = hot.iter()
+            .take(MAX_HOT_FILES * 2)
+            .map(|(path, commits)| format!("- {} ({} commits)", path, commits))
+            .collect();
+        user_prompt.push_str(&format!(
+            "## Most Changed Files (last 30 days)\n{}\n\n",
+            wrap_untrusted("hot_files", &list.join("\n"))
+        ));
+    }
+    user_prompt.push_str(&format!("## Key Files\n\n{}", contents));
+
+    vec![
+        ChatMessage { role: "system".into(), content: Some(system_prompt), tool_call_id: None, tool_calls: None },
+        ChatMessage { role: "user".into(), content: Some(user_prompt), tool_call_id: None, tool_calls: None },
+    ]
+}
+
+/// A newcomer-oriented architecture overview of the project, from its file
+/// layout, recently busy files and a targeted read of its key files. Saved as
+/// the project's `repo_overview`, replacing the previous one; project workflows
+/// have no run, so usage is recorded against the project.
+pub async fn explain_repo(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    api_key: &str,
+    project_id: &str,
+    focus: Option<&str>,
+) -> Result<OverviewResult, String> {
+    let focus = focus.map(str::trim).filter(|f| !f.is_empty());
+    if focus.is_some_and(|f| f.chars().count() > MAX_FOCUS_CHARS) {
+        return Err(format!("The focus is limited to {} characters", MAX_FOCUS_CHARS));
+    }
+    let (name, repo_path): (String, String) = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        conn.query_row("SELECT name, repo_path FROM projects WHERE id = ?1", [project_id], |r| Ok((r.get(0)?, r.get(1)?)))
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => format!("Project {} not found", project_id),
+                e => e.to_string(),
+            })?
+    };
+    if is_demo_project(app, project_id)? {
+        return Err(DEMO_READ_ONLY_MESSAGE.into());
+    }
+    check_run_budget(app, settings, project_id).map_err(|b| b.message)?;
+
+    // What the project keeps out of model context stays out of the overview too
+    let repo_path = Path::new(&repo_path);
+    let ignore = matcher(&load_config(project_globs(app, project_id).await?, repo_path).await, &[])?;
+    let repo_map: Vec<String> = build_repo_map(repo_path, REPO_MAP_MAX_FILES)
+        .lines()
+        .filter(|line| !line.is_empty() && !ignore.is_ignored(line))
+        .map(str::to_string)
+        .collect();
+    let files: Vec<String> = repo_map.iter().filter(|line| !line.starts_with("... (")).cloned().collect();
+    let hot: Vec<(String, usize)> = hot_files(repo_path).await.into_iter()
+        .filter(|(path, _)| !ignore.is_ignored(path))
+        .collect();
+
+    let mut contents = String::new();
+    let mut read_files = vec![];
+    for path in pick_files(&files, &hot, focus) {
+        let remaining = MAX_READ_CHARS.saturating_sub(contents.len());
+        if remaining == 0 {
+            break;
+        }
+        // Files deleted since they were last changed, or binary, are skipped
+        let Ok(Some(text)) = read_text(repo_path, &path).await else {
+            continue;
+        };
+        let (text, truncated) = truncate_string(&text, MAX_FILE_CHARS.min(remaining));
+        let note = if truncated { " (truncated)" } else { "" };
+        contents.push_str(&format!("### {}{}\n{}\n\n", path, note, wrap_untrusted("file", &format!("```\n{}\n```", text))));
+        read_files.push(path);
+    }
+    if repo_map.is_empty() && read_files.is_empty() {
+        return Err(format!("No files to explain in {}", repo_path.display()));
+    }
+
+    let llm_config = build_llm_config(settings)?;
+    let (provider, model) = (llm_config.provider_name.clone(), llm_config.model.clone());
+    let messages = build_overview_messages(&name, focus, &repo_map, &hot, &contents);
+    let response = LlmClient::new(llm_config, api_key.to_string())
+        .chat_with_tools(messages, vec![])
+        .await
+        .map_err(|e| e.to_string())?;
+    record_project_usage(app, settings, project_id, "explain_repo", &provider, &model, response.usage);
+    let content = response.content.filter(|c| !c.trim().is_empty())
+        .ok_or("No response from LLM")?;
+    // The overview is replaced on each run, so note what it was asked to go deeper on
+    let content = match focus {
+        Some(focus) => format!("> Focus: {}\n\n{}", focus, content),
+        None => content,
+    };
+
+    let artifact = upsert_project_artifact(app, project_id, OVERVIEW_KIND, &content)?;
+    tracing::info!(project_id, files = read_files.len(), "architecture overview generated");
+    Ok(OverviewResult { artifact, read_files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walkthrough_order() {
+        let diff = "diff --git a/src/db.rs b/src/db.rs\n--- a/src/db.rs\n+++ b/src/db.rs\n@@ -1 +1,2 @@\n-old\n+new\n+more\n\
+                    diff --git a/migrations/002_users.sql b/migrations/002_users.sql\nnew file mode 100644\n--- /dev/null\n+++ b/migrations/002_users.sql\n@@ -0,0 +1 @@\n+CREATE TABLE users (id TEXT);\n\
+                    diff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-a\n+b\n";
+        let files = changed_files(diff);
+        assert_eq!(files[0], ChangedFile { path: "src/db.rs".into(), added: 2, removed: 1 });
+        assert_eq!(files[1], ChangedFile { path: "migrations/002_users.sql".into(), added: 1, removed: 0 });
+        assert_eq!(files.len(), 3);
+
+        let walkthrough = "# Diff Walkthrough\n\n## Walkthrough\n### 1. `migrations/002_users.sql`\nThe table.\n\
+                           ### 2. `./src/db.rs`, `src/unknown.rs`\nUses `README.md` in prose.\n### 3. `src/db.rs`\nAgain.\n";
+        let (order, unmentioned) = walkthrough_order(walkthrough, &files);
+        assert_eq!(order, ["migrations/002_users.sql", "src/db.rs"]);
+        assert_eq!(unmentioned, ["README.md"]);
+    }
+
+    #[test]
+    fn test_pick_files() {
+        let files: Vec<String> = [
+            "README.md", "Cargo.toml", "docs/guide.md", "src/main.rs", "src/sync/mod.rs", "src/sync/s3.rs",
+            "crates/cli/src/main.rs", "src/db.rs", "tests/sync_test.rs",
+        ].iter().map(|f| f.to_string()).collect();
+        let hot = vec![("src/db.rs".to_string(), 9), ("src/main.rs".to_string(), 4)];
+
+        let picked = pick_files(&files, &hot, None);
+        assert_eq!(picked, ["README.md", "Cargo.toml", "src/main.rs", "crates/cli/src/main.rs", "src/db.rs"]);
+
+        let picked = pick_files(&files, &hot, Some("How does sync work?"));
+        assert_eq!(&picked[4..7], ["tests/sync_test.rs", "src/sync/s3.rs", "src/sync/mod.rs"]);
+        assert_eq!(picked.last().map(String::as_str), Some("src/db.rs"));
+    }
+}
@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::render::numbered_section;
+
+const MAX_TITLE_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    High,
+    Medium,
+    Low,
+}
+
+impl Severity {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_matches('*').to_lowercase();
+        if text.starts_with("high") || text.starts_with("critical") {
+            Some(Severity::High)
+        } else if text.starts_with("med") {
+            Some(Severity::Medium)
+        } else if text.starts_with("low") {
+            Some(Severity::Low)
+        } else {
+            None
+        }
+    }
+}
+
+/// A verification report finding that cites a specific file
+#[derive(Debug, Clone, Serialize, PartialEq, Type)]
+pub struct Finding {
+    /// Report section the finding came from: compliance|risk|action|suggestion
+    pub section: &'static str,
+    pub severity: Severity,
+    pub title: String,
+    pub message: String,
+    pub file: String,
+    pub line: Option<u32>,
+}
+
+/// Report sections that hold findings, with the severity used when the
+/// finding doesn't state one
+const FINDING_SECTIONS: [(u8, &str, Severity); 4] = [
+    (3, "compliance", Severity::Medium),
+    (4, "risk", Severity::Medium),
+    (6, "action", Severity::Low),
+    (7, "suggestion", Severity::Low),
+];
+
+/// Pull file-specific findings from the report's compliance, risk, next
+/// action, and patch suggestion sections. Items that cite no file are skipped.
+pub fn extract_findings(report_md: &str) -> Vec<Finding> {
+    let mut findings = vec![];
+    for (number, section, default_severity) in FINDING_SECTIONS {
+        let Some(body) = numbered_section(report_md, number) else {
+            continue;
+        };
+        for line in body.lines() {
+            let line = line.trim();
+            let (title, message, severity) = if line.starts_with('|') {
+                match table_row(line) {
+                    Some(row) => row,
+                    None => continue,
+                }
+            } else {
+                let text = list_item_text(line);
+                if text.is_empty() {
+                    continue;
+                }
+                (first_sentence(text), text.to_string(), None)
+            };
+
+            for (file, file_line) in file_refs(line) {
+                let finding = Finding {
+                    section,
+                    severity: severity.unwrap_or(default_severity),
+                    title: title.clone(),
+                    message: message.clone(),
+                    file,
+                    line: file_line,
+                };
+                if !findings.contains(&finding) {
+                    findings.push(finding);
+                }
+            }
+        }
+    }
+    findings
+}
+
+/// `| Risk | Severity | Notes |` row as (title, message, severity); header and
+/// separator rows are skipped
+fn table_row(line: &str) -> Option<(String, String, Option<Severity>)> {
+    let cells: Vec<&str> = line.trim_matches('|').split('|').map(|c| c.trim()).collect();
+    let title = cells.first().filter(|c| !c.is_empty())?;
+    if title.chars().all(|c| c == '-' || c == ':') || title.eq_ignore_ascii_case("risk") {
+        return None;
+    }
+    let notes = cells.get(2).copied().unwrap_or_default();
+    let message = if notes.is_empty() { title.to_string() } else { format!("{}: {}", title, notes) };
+    Some((title.to_string(), message, cells.get(1).and_then(|s| Severity::parse(s))))
+}
+
+/// Bullet or numbered item text without its marker or checkbox
+fn list_item_text(line: &str) -> &str {
+    let line = line.trim_start_matches(['-', '*', '+']).trim_start();
+    let line = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    let line = line.strip_prefix('.').unwrap_or(line).trim_start();
+    line.strip_prefix("[ ]")
+        .or_else(|| line.strip_prefix("[x]"))
+        .unwrap_or(line)
+        .trim()
+}
+
+fn first_sentence(text: &str) -> String {
+    let sentence = text.split(". ").next().unwrap_or(text).replace(['*', '`'], "");
+    if sentence.chars().count() <= MAX_TITLE_CHARS {
+        return sentence;
+    }
+    let mut title: String = sentence.chars().take(MAX_TITLE_CHARS - 1).collect();
+    title.push('…');
+    title
+}
+
+/// Repository paths cited in `text`, with an optional line number
+/// (`src/a.rs:12`, `src/a.rs#L12`). Paths in code spans only need an
+/// extension; elsewhere they must contain a `/` to avoid matching prose.
+pub fn file_refs(text: &str) -> Vec<(String, Option<u32>)> {
+    let mut refs = vec![];
+    for (i, segment) in text.split('`').enumerate() {
+        let in_code = i % 2 == 1;
+        for token in segment.split(|c: char| c.is_whitespace() || "()[]{},;\"'<>|*".contains(c)) {
+            if let Some(found) = parse_file_ref(token, in_code) {
+                if !refs.contains(&found) {
+                    refs.push(found);
+                }
+            }
+        }
+    }
+    refs
+}
+
+fn parse_file_ref(token: &str, in_code: bool) -> Option<(String, Option<u32>)> {
+    let token = token.trim_end_matches(['.', ':']);
+    if token.contains("://") {
+        return None;
+    }
+    let (path, line) = match token.split_once("#L").or_else(|| token.split_once(':')) {
+        Some((path, rest)) => {
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            (path, digits.parse().ok())
+        }
+        None => (token, None),
+    };
+    let path = path.trim_start_matches("./");
+    if path.is_empty() || path.starts_with('/') || (!in_code && !path.contains('/')) {
+        return None;
+    }
+    if !path.chars().all(|c| c.is_alphanumeric() || "/._-".contains(c)) {
+        return None;
+    }
+
+    // Require a file name with an extension that has a letter (not a version number)
+    let name = path.rsplit('/').next()?;
+    let (stem, ext) = name.rsplit_once('.')?;
+    let valid_ext = !ext.is_empty() && ext.len() <= 10 && ext.chars().any(|c| c.is_ascii_alphabetic());
+    (!stem.is_empty() && valid_ext).then(|| (path.to_string(), line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_refs() {
+        let refs = file_refs("See `src/auth.rs:42` and src/db/mod.rs#L7, also `Cargo.toml`. Node.js v1.2 e.g. https://x.io/a.rs");
+        assert_eq!(refs, vec![
+            ("src/auth.rs".to_string(), Some(42)),
+            ("src/db/mod.rs".to_string(), Some(7)),
+            ("Cargo.toml".to_string(), None),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_findings() {
+        let report = "# Verification Report\n\n## 1. Verdict\n⚠️ **Partially Matches**\n\n\
+## 3. Plan Compliance Analysis\n- Missing token refresh in `src/auth.rs:42`\n- General note without files\n\n\
+## 4. Risk Review\n| Risk | Severity | Notes |\n|------|----------|-------|\n| SQL injection | High | Raw query in `src/db.rs:10` |\n| Perf | Low | fine |\n\n\
+## 6. Recommended Next Actions\n- [ ] Add tests for `tests/auth_test.rs`\n";
+        let findings = extract_findings(report);
+        assert_eq!(findings.len(), 3);
+
+        assert_eq!(findings[0].section, "compliance");
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(findings[0].file, "src/auth.rs");
+        assert_eq!(findings[0].line, Some(42));
+
+        assert_eq!(findings[1].section, "risk");
+        assert_eq!(findings[1].severity, Severity::High);
+        assert_eq!(findings[1].title, "SQL injection");
+        assert_eq!(findings[1].message, "SQL injection: Raw query in `src/db.rs:10`");
+
+        assert_eq!(findings[2].section, "action");
+        assert_eq!(findings[2].severity, Severity::Low);
+        assert_eq!(findings[2].title, "Add tests for tests/auth_test.rs");
+    }
+}
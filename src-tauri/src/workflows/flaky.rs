@@ -0,0 +1,156 @@
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::repo_tools::test_results::{parse_test_outcomes, TestStat};
+
+/// A test's recorded history in a project
+#[derive(Debug, Clone, Serialize, PartialEq, Type)]
+pub struct TestHistory {
+    pub test_name: String,
+    pub runs: i64,
+    pub failures: i64,
+    /// Repeated runs in which the test both passed and failed
+    pub flaky_runs: i64,
+    pub last_failed_at: Option<String>,
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// Per-test stats of a run_command tests result: the runner's aggregate for
+/// repeated runs, otherwise the failures of the single run
+pub fn result_test_stats(result: &Value) -> Vec<TestStat> {
+    if let Some(stats) = result.get("test_stats").and_then(|s| serde_json::from_value(s.clone()).ok()) {
+        return stats;
+    }
+    let output = |key: &str| result.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    parse_test_outcomes(&format!("{}\n{}", output("stdout"), output("stderr"))).into_iter()
+        .filter(|o| !o.passed)
+        .map(|o| TestStat { name: o.name, runs: 1, failures: 1 })
+        .collect()
+}
+
+/// Add a run's stats to the project's history
+pub fn record_test_stats(conn: &Connection, project_id: &str, stats: &[TestStat]) -> Result<(), String> {
+    let now = now_iso();
+    for stat in stats {
+        let last_failed_at = (stat.failures > 0).then_some(now.as_str());
+        conn.execute(
+            "INSERT INTO test_stats (project_id, test_name, runs, failures, flaky_runs, last_failed_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(project_id, test_name) DO UPDATE SET
+               runs = runs + excluded.runs,
+               failures = failures + excluded.failures,
+               flaky_runs = flaky_runs + excluded.flaky_runs,
+               last_failed_at = COALESCE(excluded.last_failed_at, last_failed_at),
+               updated_at = excluded.updated_at",
+            (project_id, &stat.name, stat.runs, stat.failures, stat.is_flaky() as i64, last_failed_at, &now)
+        ).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn query_history(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> Result<Vec<TestHistory>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params, |r| Ok(TestHistory {
+        test_name: r.get(0)?,
+        runs: r.get(1)?,
+        failures: r.get(2)?,
+        flaky_runs: r.get(3)?,
+        last_failed_at: r.get(4)?,
+    })).map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// Known-flaky tests among `names`
+pub fn known_flaky(conn: &Connection, project_id: &str, names: &[String]) -> Result<Vec<TestHistory>, String> {
+    let flaky = query_history(
+        conn,
+        "SELECT test_name, runs, failures, flaky_runs, last_failed_at FROM test_stats
+         WHERE project_id = ?1 AND flaky_runs > 0 ORDER BY test_name",
+        [project_id],
+    )?;
+    Ok(flaky.into_iter().filter(|h| names.contains(&h.test_name)).collect())
+}
+
+pub fn list_test_stats(app: &AppHandle, project_id: &str, flaky_only: bool) -> Result<Vec<TestHistory>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    query_history(
+        &conn,
+        "SELECT test_name, runs, failures, flaky_runs, last_failed_at FROM test_stats
+         WHERE project_id = ?1 AND (?2 = 0 OR flaky_runs > 0)
+         ORDER BY flaky_runs DESC, failures DESC, test_name",
+        (project_id, flaky_only),
+    )
+}
+
+/// Forget the project's test history, e.g. after fixing its flaky tests
+pub fn reset_test_stats(app: &AppHandle, project_id: &str) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM test_stats WHERE project_id = ?1", [project_id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Prompt lines for failing tests known to be flaky
+pub fn format_flaky(tests: &[TestHistory]) -> String {
+    tests.iter()
+        .map(|t| format!(
+            "- {}: failed {} of {} recorded runs, passed and failed on the same code {} time(s)",
+            t.test_name, t.failures, t.runs, t.flaky_runs
+        ))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Note appended to a verification report listing failures that didn't count
+pub fn flaky_annotation(tests: &[TestHistory]) -> Option<String> {
+    if tests.is_empty() {
+        return None;
+    }
+    let names: Vec<&str> = tests.iter().map(|t| t.test_name.as_str()).collect();
+    Some(format!(
+        "> **Known flaky tests failed in this run:** {}. These failures are not counted toward the verdict.",
+        names.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_and_find_flaky() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE projects (id TEXT PRIMARY KEY); INSERT INTO projects VALUES ('p1');").unwrap();
+        conn.execute_batch(include_str!("../../migrations/038_test_stats.sql")).unwrap();
+
+        let single = json!({ "stdout": "test a::stable ... ok\ntest a::broken ... FAILED\n", "stderr": "", "code": 101 });
+        let stats = result_test_stats(&single);
+        assert_eq!(stats, vec![TestStat { name: "a::broken".into(), runs: 1, failures: 1 }]);
+        record_test_stats(&conn, "p1", &stats).unwrap();
+
+        let repeated = json!({
+            "stdout": "", "stderr": "", "code": 101, "attempts": 3,
+            "test_stats": [
+                { "name": "a::broken", "runs": 3, "failures": 3 },
+                { "name": "a::timing", "runs": 3, "failures": 1 },
+            ],
+        });
+        record_test_stats(&conn, "p1", &result_test_stats(&repeated)).unwrap();
+
+        let failing = vec!["a::broken".to_string(), "a::timing".to_string()];
+        let flaky = known_flaky(&conn, "p1", &failing).unwrap();
+        assert_eq!(flaky.len(), 1);
+        assert_eq!((flaky[0].test_name.as_str(), flaky[0].runs, flaky[0].failures, flaky[0].flaky_runs), ("a::timing", 3, 1, 1));
+        assert!(flaky_annotation(&flaky).unwrap().contains("a::timing"));
+        assert!(flaky_annotation(&[]).is_none());
+    }
+}
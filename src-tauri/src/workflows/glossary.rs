@@ -0,0 +1,245 @@
+use serde::Serialize;
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::demo::{is_demo_project, DEMO_READ_ONLY_MESSAGE};
+use crate::llm::{ChatMessage, LlmClient};
+use crate::models::Artifact;
+use crate::project_artifacts::{list_project_artifacts, upsert_project_artifact};
+use crate::repo_tools::context_ignore::{load_config, matcher, project_globs};
+use crate::repo_tools::safety::truncate_string;
+use crate::settings::SettingsMap;
+use crate::workflows::budget::{check_run_budget, record_project_usage};
+use crate::workflows::context::read_text;
+use crate::workflows::plan::build_llm_config;
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+use crate::workflows::templates::build_repo_map;
+
+/// Project artifact kind of the domain glossary
+pub const GLOSSARY_KIND: &str = "glossary";
+
+const REPO_MAP_MAX_FILES: usize = 5_000;
+const MAX_SCAN_FILES: usize = 400;
+const MAX_SCAN_FILE_BYTES: usize = 200_000;
+const MAX_TERMS: usize = 150;
+const MAX_README_CHARS: usize = 6_000;
+const MAX_EXISTING_CHARS: usize = 12_000;
+
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "kt", "swift", "cs", "rb", "php", "scala",
+];
+/// Keywords that introduce a named type in the languages above
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "struct", "enum", "trait", "type", "class", "interface", "record", "object", "protocol", "union",
+];
+/// Type names too generic to say anything about the domain
+const GENERIC_NAMES: &[&str] = &[
+    "Self", "Error", "Errors", "Result", "Option", "Props", "State", "Test", "Tests", "Mock", "Default", "Args", "Options",
+];
+
+#[derive(Debug, Serialize, Type)]
+pub struct GlossaryResult {
+    pub artifact: Artifact,
+    /// Candidate terms found in the code and offered to the model
+    pub candidates: usize,
+    pub scanned_files: usize,
+}
+
+/// A type name declared in the codebase and how widely it's used
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate {
+    name: String,
+    keyword: String,
+    defined_in: String,
+    files: usize,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Type names declared in `text`, with the keyword that declared them
+fn declared_types(text: &str) -> Vec<(String, String)> {
+    let mut out = vec![];
+    for line in text.lines() {
+        let line = line.trim_start();
+        if line.starts_with("//") || line.starts_with('#') || line.starts_with('*') {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split(|c: char| !is_identifier_char(c)).filter(|t| !t.is_empty()).collect();
+        for pair in tokens.windows(2) {
+            let (keyword, name) = (pair[0], pair[1]);
+            if DECLARATION_KEYWORDS.contains(&keyword)
+                && name.chars().count() >= 3
+                && name.starts_with(|c: char| c.is_ascii_uppercase())
+                && !GENERIC_NAMES.contains(&name)
+            {
+                out.push((name.to_string(), keyword.to_string()));
+                break;
+            }
+        }
+    }
+    out
+}
+
+/// The most widely used declared types, by how many scanned files mention them
+fn rank_candidates(sources: &[(String, String)]) -> Vec<Candidate> {
+    let mut declared: BTreeMap<String, Candidate> = BTreeMap::new();
+    for (path, text) in sources {
+        for (name, keyword) in declared_types(text) {
+            declared.entry(name.clone()).or_insert(Candidate { name, keyword, defined_in: path.clone(), files: 0 });
+        }
+    }
+    for candidate in declared.values_mut() {
+        candidate.files = sources.iter().filter(|(_, text)| text.contains(&candidate.name)).count();
+    }
+    let mut candidates: Vec<Candidate> = declared.into_values().collect();
+    candidates.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.name.cmp(&b.name)));
+    candidates.truncate(MAX_TERMS);
+    candidates
+}
+
+fn build_glossary_messages(
+    name: &str,
+    readme: Option<&str>,
+    candidates: &[Candidate],
+    existing: Option<&str>,
+) -> Vec<ChatMessage> {
+    let mut system_prompt = r#"You are a senior engineer writing the glossary a team uses to keep its naming consistent.
+
+Your task: From the type names declared in the codebase and the project description,
+write a glossary of the project's domain terms: the nouns of the product and the core
+types that model them. Skip plumbing (generic helpers, wrappers, framework types).
+Only define terms that appear in the material you were given; when a meaning is a guess
+from the name and where it's declared, say so. Never invent terms.
+
+Required output format (Markdown):
+
+# Glossary: [Project]
+
+| Term | Meaning | Defined In |
+|------|---------|------------|
+One row per term, alphabetical. "Defined In" is the file, or "product" for nouns
+that only appear in the description.
+
+## Naming Notes
+Short bullets on how the code names things (suffixes, synonyms to avoid, terms that
+mean something specific here)."#.to_string();
+    if existing.is_some() {
+        system_prompt.push_str(
+            "\n\nA previous glossary is included. Keep its definitions where they still hold, \
+             drop terms no longer in the code, and add new ones."
+        );
+    }
+    system_prompt.push_str(UNTRUSTED_CONTENT_REMINDER);
+
+    let mut user_prompt = format!("Project: {}\n\n", name);
+    if let Some(readme) = readme {
+        user_prompt.push_str(&format!("## Project Description\n{}\n\n", wrap_untrusted("readme", readme)));
+    }
+    let list: Vec<String> = candidates.iter()
+        .map(|c| format!("- {} ({} in {}, mentioned in {} files)", c.name, c.keyword, c.defined_in, c.files))
+        .collect();
+    user_prompt.push_str(&format!("## Declared Types\n{}\n", wrap_untrusted("declared_types", &list.join("\n"))));
+    if let Some(existing) = existing {
+        user_prompt.push_str(&format!("\n## Previous Glossary\n{}\n", wrap_untrusted("glossary", existing)));
+    }
+
+    vec![
+        ChatMessage { role: "system".into(), content: Some(system_prompt), tool_call_id: None, tool_calls: None },
+        ChatMessage { role: "user".into(), content: Some(user_prompt), tool_call_id: None, tool_calls: None },
+    ]
+}
+
+/// Extract the project's domain terms into its `glossary` artifact, which plan
+/// and verify prompts always include so the model reuses the project's names.
+/// Rerunning updates the existing glossary.
+pub async fn extract_glossary(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    api_key: &str,
+    project_id: &str,
+) -> Result<GlossaryResult, String> {
+    let (name, repo_path): (String, String) = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        conn.query_row("SELECT name, repo_path FROM projects WHERE id = ?1", [project_id], |r| Ok((r.get(0)?, r.get(1)?)))
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => format!("Project {} not found", project_id),
+                e => e.to_string(),
+            })?
+    };
+    if is_demo_project(app, project_id)? {
+        return Err(DEMO_READ_ONLY_MESSAGE.into());
+    }
+    check_run_budget(app, settings, project_id).map_err(|b| b.message)?;
+
+    // Files kept out of model context don't contribute terms either
+    let repo_path = Path::new(&repo_path);
+    let ignore = matcher(&load_config(project_globs(app, project_id).await?, repo_path).await, &[])?;
+    let source_files: Vec<String> = build_repo_map(repo_path, REPO_MAP_MAX_FILES)
+        .lines()
+        .filter(|line| !line.starts_with("... (") && !ignore.is_ignored(line))
+        .filter(|line| line.rsplit_once('.').is_some_and(|(_, ext)| SOURCE_EXTENSIONS.contains(&ext)))
+        .take(MAX_SCAN_FILES)
+        .map(str::to_string)
+        .collect();
+    let mut sources = vec![];
+    for path in source_files {
+        if let Ok(Some(text)) = read_text(repo_path, &path).await {
+            if text.len() <= MAX_SCAN_FILE_BYTES {
+                sources.push((path, text));
+            }
+        }
+    }
+    let candidates = rank_candidates(&sources);
+    if candidates.is_empty() {
+        return Err(format!("No type declarations found in {}", repo_path.display()));
+    }
+
+    let readme = match read_text(repo_path, "README.md").await {
+        Ok(Some(text)) => Some(truncate_string(&text, MAX_README_CHARS).0),
+        _ => None,
+    };
+    let existing = list_project_artifacts(app, project_id, Some(GLOSSARY_KIND))?.into_iter().next()
+        .map(|a| truncate_string(&a.content, MAX_EXISTING_CHARS).0);
+
+    let llm_config = build_llm_config(settings)?;
+    let (provider, model) = (llm_config.provider_name.clone(), llm_config.model.clone());
+    let messages = build_glossary_messages(&name, readme.as_deref(), &candidates, existing.as_deref());
+    let response = LlmClient::new(llm_config, api_key.to_string())
+        .chat_with_tools(messages, vec![])
+        .await
+        .map_err(|e| e.to_string())?;
+    record_project_usage(app, settings, project_id, "extract_glossary", &provider, &model, response.usage);
+    let content = response.content.filter(|c| !c.trim().is_empty())
+        .ok_or("No response from LLM")?;
+
+    let artifact = upsert_project_artifact(app, project_id, GLOSSARY_KIND, &content)?;
+    tracing::info!(project_id, candidates = candidates.len(), files = sources.len(), "glossary extracted");
+    Ok(GlossaryResult { artifact, candidates: candidates.len(), scanned_files: sources.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_candidates() {
+        let sources = vec![
+            ("src/models.rs".to_string(), "pub struct Invoice {\n    lines: Vec<LineItem>,\n}\n// struct Commented\npub enum LineItem { Fee }\nstruct Error;\n".to_string()),
+            ("src/billing.ts".to_string(), "export interface Invoice { total: number }\nclass LedgerEntry {}\nconst x: LineItem = fee;\n".to_string()),
+            ("src/db.py".to_string(), "class Ab:\n    pass\n# Invoice table\n".to_string()),
+        ];
+        let ranked: Vec<(String, &str, usize)> = rank_candidates(&sources).into_iter()
+            .map(|c| (c.name, if c.defined_in == "src/models.rs" { "models" } else { "other" }, c.files))
+            .collect();
+        assert_eq!(ranked, [
+            ("Invoice".to_string(), "models", 3),
+            ("LineItem".to_string(), "models", 2),
+            ("LedgerEntry".to_string(), "other", 1),
+        ]);
+    }
+}
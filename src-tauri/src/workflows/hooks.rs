@@ -0,0 +1,213 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::blobs;
+use crate::db;
+use crate::models::new_id;
+use crate::repo_tools::normalize::normalize_output;
+use crate::repo_tools::run_settings::run_setting;
+use crate::repo_tools::safety::truncate_middle;
+
+const HOOK_TIMEOUT_SECS: u64 = 30;
+const MAX_LOGGED_OUTPUT: usize = 20_000;
+
+/// Points in a run's lifecycle a project can hook a script to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    /// A task's plan was generated and saved
+    PlanSaved,
+    /// Verification found the implementation matches the plan
+    VerifyPassed,
+    /// Verification found it partially matches or doesn't match
+    VerifyFailed,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 3] = [HookEvent::PlanSaved, HookEvent::VerifyPassed, HookEvent::VerifyFailed];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::PlanSaved => "plan_saved",
+            HookEvent::VerifyPassed => "verify_passed",
+            HookEvent::VerifyFailed => "verify_failed",
+        }
+    }
+}
+
+/// The `lifecycle_hooks` setting: the script to run on each event, by file
+/// name in the hooks directory, e.g. `{"verify_failed": "open-issue.sh"}`
+pub type LifecycleHooks = BTreeMap<String, String>;
+
+pub fn parse_lifecycle_hooks(json: &str) -> Result<LifecycleHooks, String> {
+    let hooks: LifecycleHooks = serde_json::from_str(json)
+        .map_err(|e| format!("lifecycle_hooks must map events to script names: {}", e))?;
+    for (event, script) in &hooks {
+        if !HookEvent::ALL.iter().any(|e| e.as_str() == event) {
+            return Err(format!("Unknown hook event '{}' (expected plan_saved, verify_passed, or verify_failed)", event));
+        }
+        // Only scripts the user put in the hooks directory run; a name can't reach outside it
+        let valid = !script.is_empty() && !script.starts_with('.') && !script.contains(['/', '\\']);
+        if !valid {
+            return Err(format!("Invalid hook script '{}' for {} (expected a file name in the hooks directory)", script, event));
+        }
+    }
+    Ok(hooks)
+}
+
+/// Directory hook scripts are run from, and the only place they may live
+pub fn hooks_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("hooks"))
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct HookScripts {
+    pub dir: String,
+    pub scripts: Vec<String>,
+}
+
+/// Scripts in the hooks directory, for choosing a project's hooks
+pub fn list_hook_scripts(app: &AppHandle) -> Result<HookScripts, String> {
+    let dir = hooks_dir(app)?;
+    let mut scripts: Vec<String> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries.flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_file()))
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| !name.starts_with('.'))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    scripts.sort();
+    Ok(HookScripts { dir: dir.to_string_lossy().to_string(), scripts })
+}
+
+/// What a hook's script is given on stdin: the event, the run it fired for,
+/// and the event's own fields
+fn hook_payload(event: HookEvent, run_id: &str, details: Value) -> Value {
+    let mut payload = json!({ "event": event.as_str(), "run_id": run_id });
+    if let (Some(payload), Value::Object(details)) = (payload.as_object_mut(), details) {
+        payload.extend(details);
+    }
+    payload
+}
+
+/// Run the script the run's project hooks to `event`, if any, and record its
+/// output in the run log. Best-effort: a missing or failing hook is logged,
+/// never returned, so it can't fail the workflow that fired it.
+pub async fn fire_hook(app: &AppHandle, run_id: &str, event: HookEvent, repo_path: &Path, details: Value) {
+    let script = match configured_script(app, run_id, event).await {
+        Ok(Some(script)) => script,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(event = event.as_str(), "failed to read lifecycle hooks: {}", e);
+            return;
+        }
+    };
+    let log = match run_script(app, &script, repo_path, &hook_payload(event, run_id, details)).await {
+        Ok(log) => log,
+        Err(e) => {
+            tracing::warn!(event = event.as_str(), script = %script, "lifecycle hook failed: {}", e);
+            format!("{} hook {} failed: {}", event.as_str(), script, e)
+        }
+    };
+    if let Err(e) = log_hook_output(app, run_id, &log) {
+        tracing::warn!("failed to log hook output: {}", e);
+    }
+}
+
+async fn configured_script(app: &AppHandle, run_id: &str, event: HookEvent) -> Result<Option<String>, String> {
+    let Some(json) = run_setting(app, run_id, "lifecycle_hooks").await?.filter(|v| !v.trim().is_empty()) else {
+        return Ok(None);
+    };
+    Ok(parse_lifecycle_hooks(&json)?.remove(event.as_str()))
+}
+
+/// Run the script with the payload on stdin; its exit code and output, for the run log
+async fn run_script(app: &AppHandle, script: &str, repo_path: &Path, payload: &Value) -> Result<String, String> {
+    let dir = hooks_dir(app)?;
+    let path = dir.join(script);
+    if !path.is_file() {
+        return Err(format!("{} is not in {}", script, dir.display()));
+    }
+    // Remote repositories aren't on this machine; their hooks run from the hooks directory
+    let cwd = if repo_path.is_dir() { repo_path } else { dir.as_path() };
+
+    let mut child = Command::new(&path)
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to start: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A script that doesn't read the payload may already have closed stdin
+        let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+    }
+    let output = timeout(Duration::from_secs(HOOK_TIMEOUT_SECS), child.wait_with_output())
+        .await
+        .map_err(|_| format!("timed out after {}s", HOOK_TIMEOUT_SECS))?
+        .map_err(|e| e.to_string())?;
+
+    let stdout = normalize_output(&String::from_utf8_lossy(&output.stdout));
+    let stderr = normalize_output(&String::from_utf8_lossy(&output.stderr));
+    let (stdout, _) = truncate_middle(stdout.trim_end(), MAX_LOGGED_OUTPUT);
+    let (stderr, _) = truncate_middle(stderr.trim_end(), MAX_LOGGED_OUTPUT);
+
+    let mut log = format!(
+        "{} hook {} exited with {}",
+        payload["event"].as_str().unwrap_or_default(),
+        script,
+        output.status.code().unwrap_or(-1)
+    );
+    for (stream, text) in [("stdout", stdout), ("stderr", stderr)] {
+        if !text.is_empty() {
+            log.push_str(&format!("\n\n{}:\n{}", stream, text));
+        }
+    }
+    Ok(log)
+}
+
+/// Hook output is kept with the run's messages, under its own role so replays skip it
+fn log_hook_output(app: &AppHandle, run_id: &str, content: &str) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let (stored, hash) = blobs::put(&conn, content).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO messages (id, run_id, role, content, created_at, content_hash) VALUES (?1, ?2, 'hook', ?3, ?4, ?5)",
+        (new_id(), run_id, &stored, now_iso(), &hash)
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lifecycle_hooks() {
+        let hooks = parse_lifecycle_hooks(r#"{"plan_saved": "post-plan.sh", "verify_failed": "open_issue.py"}"#).unwrap();
+        assert_eq!(hooks["verify_failed"], "open_issue.py");
+        assert!(parse_lifecycle_hooks(r#"{"deploy": "ship.sh"}"#).unwrap_err().contains("Unknown hook event"));
+        assert!(parse_lifecycle_hooks(r#"{"plan_saved": "../bin/rm"}"#).is_err());
+        assert!(parse_lifecycle_hooks(r#"{"plan_saved": "/usr/bin/env"}"#).is_err());
+        assert!(parse_lifecycle_hooks(r#"{"plan_saved": ".hidden"}"#).is_err());
+
+        let payload = hook_payload(HookEvent::VerifyFailed, "run-1", json!({ "verdict": "does_not_match" }));
+        assert_eq!(payload, json!({ "event": "verify_failed", "run_id": "run-1", "verdict": "does_not_match" }));
+    }
+}
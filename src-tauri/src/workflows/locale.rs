@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::db;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Supported output languages as (code, English name)
+pub const SUPPORTED_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("ja", "Japanese"),
+    ("fr", "French"),
+    ("de", "German"),
+];
+
+const PLAN_HEADINGS: &[(&str, [&str; 7])] = &[
+    ("en", [
+        "1. Summary",
+        "2. Goals & Non-Goals",
+        "3. Repo Context Assumptions",
+        "4. File-by-File Changes",
+        "5. Step-by-Step Implementation Checklist",
+        "6. Risks + Mitigations",
+        "7. Validation Steps",
+    ]),
+    ("es", [
+        "1. Resumen",
+        "2. Objetivos y No Objetivos",
+        "3. Supuestos sobre el Repositorio",
+        "4. Cambios por Archivo",
+        "5. Lista de Implementación Paso a Paso",
+        "6. Riesgos y Mitigaciones",
+        "7. Pasos de Validación",
+    ]),
+    ("ja", [
+        "1. 概要",
+        "2. 目標と対象外",
+        "3. リポジトリに関する前提",
+        "4. ファイルごとの変更",
+        "5. 実装チェックリスト",
+        "6. リスクと対策",
+        "7. 検証手順",
+    ]),
+    ("fr", [
+        "1. Résumé",
+        "2. Objectifs et Non-Objectifs",
+        "3. Hypothèses sur le Dépôt",
+        "4. Modifications par Fichier",
+        "5. Liste d'Implémentation Étape par Étape",
+        "6. Risques et Atténuations",
+        "7. Étapes de Validation",
+    ]),
+    ("de", [
+        "1. Zusammenfassung",
+        "2. Ziele und Nicht-Ziele",
+        "3. Annahmen zum Repository",
+        "4. Änderungen pro Datei",
+        "5. Schrittweise Implementierungs-Checkliste",
+        "6. Risiken und Gegenmaßnahmen",
+        "7. Validierungsschritte",
+    ]),
+];
+
+const VERIFY_HEADINGS: &[(&str, [&str; 7])] = &[
+    ("en", [
+        "1. Verdict",
+        "2. Summary of Changes Observed",
+        "3. Plan Compliance Analysis",
+        "4. Risk Review",
+        "5. Test/Check Results",
+        "6. Recommended Next Actions",
+        "7. Patch Suggestions (Optional)",
+    ]),
+    ("es", [
+        "1. Veredicto",
+        "2. Resumen de Cambios Observados",
+        "3. Análisis de Cumplimiento del Plan",
+        "4. Revisión de Riesgos",
+        "5. Resultados de Pruebas/Comprobaciones",
+        "6. Próximas Acciones Recomendadas",
+        "7. Sugerencias de Parches (Opcional)",
+    ]),
+    ("ja", [
+        "1. 判定",
+        "2. 確認された変更の概要",
+        "3. 計画への準拠分析",
+        "4. リスクレビュー",
+        "5. テスト/チェック結果",
+        "6. 推奨される次のアクション",
+        "7. パッチ提案（任意）",
+    ]),
+    ("fr", [
+        "1. Verdict",
+        "2. Résumé des Changements Observés",
+        "3. Analyse de Conformité au Plan",
+        "4. Revue des Risques",
+        "5. Résultats des Tests/Vérifications",
+        "6. Prochaines Actions Recommandées",
+        "7. Suggestions de Correctifs (Optionnel)",
+    ]),
+    ("de", [
+        "1. Urteil",
+        "2. Zusammenfassung der beobachteten Änderungen",
+        "3. Analyse der Planeinhaltung",
+        "4. Risikobewertung",
+        "5. Test-/Prüfergebnisse",
+        "6. Empfohlene nächste Schritte",
+        "7. Patch-Vorschläge (Optional)",
+    ]),
+];
+
+pub fn language_name(code: &str) -> Option<&'static str> {
+    SUPPORTED_LANGUAGES.iter().find(|(c, _)| *c == code).map(|(_, name)| *name)
+}
+
+/// Output language for a project: the project override, then the global
+/// setting, then English. Unsupported codes fall back to English.
+pub fn resolve_output_language(
+    app: &AppHandle,
+    settings: &HashMap<String, String>,
+    project_id: &str,
+) -> Result<String, String> {
+    let project_value = db::get_project_setting(app, project_id, "output_language")
+        .map_err(|e| e.to_string())?;
+    let code = project_value
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| settings.get("output_language").cloned())
+        .unwrap_or_default();
+    let code = code.trim().to_lowercase();
+
+    Ok(if language_name(&code).is_some() { code } else { DEFAULT_LANGUAGE.to_string() })
+}
+
+fn headings_for(table: &'static [(&str, [&'static str; 7])], lang: &str) -> &'static [&'static str] {
+    let (_, headings) = table.iter()
+        .find(|(code, _)| *code == lang)
+        .unwrap_or(&table[0]);
+    headings
+}
+
+pub fn plan_headings(lang: &str) -> &'static [&'static str] {
+    headings_for(PLAN_HEADINGS, lang)
+}
+
+pub fn verify_headings(lang: &str) -> &'static [&'static str] {
+    headings_for(VERIFY_HEADINGS, lang)
+}
+
+/// System prompt addendum for non-English output. Returns None for English,
+/// where the built-in prompt already lists the headings.
+pub fn language_instruction(lang: &str, headings: &[&str]) -> Option<String> {
+    if lang == DEFAULT_LANGUAGE {
+        return None;
+    }
+    let name = language_name(lang)?;
+
+    let mut out = format!(
+        "\n\nOutput language: write the entire output in {}. \
+         Use these exact section headings, in this order, instead of the English ones:\n",
+        name
+    );
+    for heading in headings {
+        out.push_str(&format!("## {}\n", heading));
+    }
+    out.push_str(
+        "Keep file paths, code identifiers, commands, and tool names unchanged. \
+         Keep verdict labels (Matches / Partially Matches / Does Not Match) in English."
+    );
+    Some(out)
+}
+
+/// Format check: return the expected `##` headings missing from the markdown.
+/// Comparison ignores case, surrounding whitespace, and bold markers.
+pub fn missing_sections(markdown: &str, headings: &[&str]) -> Vec<String> {
+    let present: Vec<String> = markdown
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("## "))
+        .map(normalize_heading)
+        .collect();
+
+    headings.iter()
+        .filter(|h| !present.contains(&normalize_heading(h)))
+        .map(|h| h.to_string())
+        .collect()
+}
+
+fn normalize_heading(heading: &str) -> String {
+    heading.replace("**", "").trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_fall_back_to_english() {
+        assert_eq!(plan_headings("ja")[0], "1. 概要");
+        assert_eq!(verify_headings("es")[0], "1. Veredicto");
+        assert_eq!(plan_headings("xx")[0], "1. Summary");
+    }
+
+    #[test]
+    fn test_missing_sections() {
+        let headings = plan_headings("es");
+        let md = "# Plan\n\n## 1. Resumen\ntexto\n\n## **2. objetivos y no objetivos**\n";
+        let missing = missing_sections(md, headings);
+        assert_eq!(missing.len(), 5);
+        assert_eq!(missing[0], "3. Supuestos sobre el Repositorio");
+    }
+
+    #[test]
+    fn test_language_instruction() {
+        assert!(language_instruction("en", plan_headings("en")).is_none());
+        let text = language_instruction("ja", plan_headings("ja")).unwrap();
+        assert!(text.contains("Japanese"));
+        assert!(text.contains("## 7. 検証手順"));
+    }
+}
@@ -1,2 +1,27 @@
+pub mod attachments;
+pub mod baseline;
+pub mod budget;
+pub mod check_cache;
+pub mod context;
+pub mod custom;
+pub mod debate;
+pub mod ci;
+pub mod compiler;
+pub mod drafts;
+pub mod drift;
+pub mod engine;
+pub mod explain;
+pub mod findings;
+pub mod flaky;
+pub mod glossary;
+pub mod hooks;
+pub mod locale;
+pub mod phases;
 pub mod plan;
+pub mod rerun;
+pub mod retrieval;
+pub mod review;
+pub mod sanitize;
+pub mod templates;
+pub mod tokens;
 pub mod verify;
@@ -0,0 +1,441 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use tauri::AppHandle;
+
+use crate::blobs;
+use crate::db;
+use crate::llm::ChatMessage;
+use crate::models::{new_id, Phase};
+use crate::repo_tools::safety::truncate_string;
+use crate::settings::SettingsMap;
+use crate::workflows::engine::{Gathered, HookFuture, Workflow, WorkflowEngine, WorkflowRun};
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+
+pub const PHASE_LIST_KIND: &str = "phase_list";
+pub const PHASE_STATUSES: [&str; 3] = ["todo", "active", "done"];
+const MAX_PHASES: usize = 12;
+const MAX_PLAN_CHARS: usize = 60_000;
+
+const SYSTEM_PROMPT: &str = r#"You split an implementation plan into phases a developer can carry out and verify one at a time.
+
+Rules:
+- Keep the plan's order; each phase should leave the code building and its tests passing
+- Prefer 2-8 phases; never more than 12
+- A phase's description says what is done in it and how to tell it's finished
+- planned_files lists the repository paths the phase creates or changes, as the plan names them
+- depends_on lists the numbers (1-based) of earlier phases that must be finished first
+
+Respond with only a JSON object, no prose and no code fence:
+{"phases": [{"title": "...", "description": "...", "planned_files": ["..."], "depends_on": [1]}]}"#;
+
+/// A phase as the model proposes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedPhase {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub planned_files: Vec<String>,
+    /// 1-based numbers of earlier phases
+    #[serde(default)]
+    pub depends_on: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+struct PhaseListOutput {
+    phases: Vec<PlannedPhase>,
+}
+
+/// Read the model's phases from its answer, tolerating a code fence or text around the JSON
+pub fn parse_phases(output: &str) -> Result<Vec<PlannedPhase>, String> {
+    let start = output.find('{').ok_or("The model's answer has no JSON object of phases")?;
+    let end = output.rfind('}').filter(|end| *end > start).ok_or("The model's answer has no JSON object of phases")?;
+    let parsed: PhaseListOutput = serde_json::from_str(&output[start..=end])
+        .map_err(|e| format!("The model's phases aren't valid JSON: {}", e))?;
+    let phases = parsed.phases;
+    if phases.is_empty() || phases.len() > MAX_PHASES {
+        return Err(format!("Expected 1 to {} phases, got {}", MAX_PHASES, phases.len()));
+    }
+    for (i, phase) in phases.iter().enumerate() {
+        if phase.title.trim().is_empty() {
+            return Err(format!("Phase {} has no title", i + 1));
+        }
+        // Only earlier phases, so the order the plan gives is always one that works
+        if let Some(dep) = phase.depends_on.iter().find(|d| **d == 0 || **d > i) {
+            return Err(format!("Phase {} depends on phase {}, which doesn't come before it", i + 1, dep));
+        }
+    }
+    Ok(phases)
+}
+
+/// The phase list artifact: the phases as Markdown
+pub fn render_phase_list(phases: &[PlannedPhase]) -> String {
+    let mut md = String::from("# Phases\n");
+    for (i, phase) in phases.iter().enumerate() {
+        md.push_str(&format!("\n## {}. {}\n", i + 1, phase.title.trim()));
+        if !phase.description.trim().is_empty() {
+            md.push_str(&format!("\n{}\n", phase.description.trim()));
+        }
+        if !phase.planned_files.is_empty() {
+            let files: Vec<String> = phase.planned_files.iter().map(|f| format!("`{}`", f)).collect();
+            md.push_str(&format!("\nFiles: {}\n", files.join(", ")));
+        }
+        if !phase.depends_on.is_empty() {
+            let deps: Vec<String> = phase.depends_on.iter().map(|d| d.to_string()).collect();
+            md.push_str(&format!("\nDepends on: {}\n", deps.join(", ")));
+        }
+    }
+    md
+}
+
+fn load_plan(app: &AppHandle, task_id: &str) -> Result<Option<String>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT content, content_hash FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = 'plan_md' AND draft = 0 LIMIT 1",
+        [task_id],
+        |r| blobs::hydrate(&conn, r.get(0)?, r.get(1)?)
+    ).optional().map_err(|e| e.to_string())
+}
+
+fn build_messages(task_title: &str, plan_md: &str) -> Vec<ChatMessage> {
+    let (plan, truncated) = truncate_string(plan_md, MAX_PLAN_CHARS);
+    let label = if truncated { "Implementation Plan (truncated)" } else { "Implementation Plan" };
+    let user_prompt = format!("# Task: {}\n\n## {}\n{}\n", task_title, label, wrap_untrusted("plan", &plan));
+    vec![
+        ChatMessage { role: "system".into(), content: Some(format!("{}{}", SYSTEM_PROMPT, UNTRUSTED_CONTENT_REMINDER)), tool_call_id: None, tool_calls: None },
+        ChatMessage { role: "user".into(), content: Some(user_prompt), tool_call_id: None, tool_calls: None },
+    ]
+}
+
+/// Phases of a task's saved plan, as a workflow definition
+struct PhaseBreakdown;
+
+impl Workflow for PhaseBreakdown {
+    /// The phases read from the answer, then the rows stored for them
+    type Context = (Vec<PlannedPhase>, Vec<Phase>);
+
+    fn run_type(&self) -> &str {
+        "phases"
+    }
+
+    fn artifact_kind(&self) -> &str {
+        PHASE_LIST_KIND
+    }
+
+    fn gather_context<'a>(&'a self, run: &'a WorkflowRun<'a>) -> HookFuture<'a, Gathered<Self::Context>> {
+        Box::pin(async move {
+            // Checked before calling the model, so a refused run costs nothing
+            if !list_phases(run.app, &run.task.id)?.is_empty() {
+                return Err("The task already has phases; delete them before generating new ones".into());
+            }
+            let plan_md = load_plan(run.app, &run.task.id)?
+                .filter(|plan| !plan.trim().is_empty())
+                .ok_or("The task has no saved plan to break into phases")?;
+            Ok(Gathered { messages: build_messages(&run.task.title, &plan_md), context: (vec![], vec![]) })
+        })
+    }
+
+    fn post_process(&self, _run: &WorkflowRun, context: &mut Self::Context, output: String) -> Result<String, String> {
+        context.0 = parse_phases(&output)?;
+        Ok(render_phase_list(&context.0))
+    }
+
+    fn after_save(&self, run: &WorkflowRun, context: &mut Self::Context, _artifact_id: &str) -> Result<(), String> {
+        context.1 = insert_phases(run.app, &run.task.id, &context.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct PhasesResult {
+    pub run_id: String,
+    pub artifact_id: String,
+    pub phase_list_md: String,
+    pub phases: Vec<Phase>,
+}
+
+/// Break the task's saved plan into ordered phases, store them, and save the
+/// list as the task's `phase_list` artifact
+pub async fn generate_phases(app: &AppHandle, settings: &SettingsMap, api_key: &str, task_id: &str) -> Result<PhasesResult, String> {
+    let output = WorkflowEngine::new(app, settings, api_key).run(&PhaseBreakdown, task_id).await?;
+    tracing::info!(phases = output.context.1.len(), "phases generated");
+    Ok(PhasesResult {
+        run_id: output.run_id,
+        artifact_id: output.artifact_id,
+        phase_list_md: output.content,
+        phases: output.context.1,
+    })
+}
+
+fn insert_phases(app: &AppHandle, task_id: &str, planned: &[PlannedPhase]) -> Result<Vec<Phase>, String> {
+    let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut phases: Vec<Phase> = vec![];
+    for (idx, p) in planned.iter().enumerate() {
+        let description = Some(p.description.trim().to_string()).filter(|d| !d.is_empty());
+        let phase = insert_phase(&tx, task_id, idx as i64, p.title.trim(), description, &p.planned_files)?;
+        for dep in &p.depends_on {
+            tx.execute(
+                "INSERT OR IGNORE INTO phase_dependencies (phase_id, depends_on_phase_id) VALUES (?1, ?2)",
+                (&phase.id, &phases[dep - 1].id)
+            ).map_err(|e| e.to_string())?;
+        }
+        phases.push(phase);
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(phases)
+}
+
+fn insert_phase(
+    conn: &Connection,
+    task_id: &str,
+    idx: i64,
+    title: &str,
+    description: Option<String>,
+    planned_files: &[String],
+) -> Result<Phase, String> {
+    let now = now_iso();
+    let phase = Phase {
+        id: new_id(),
+        task_id: task_id.to_string(),
+        idx,
+        title: title.to_string(),
+        description,
+        status: "todo".into(),
+        planned_files_json: serde_json::to_string(planned_files).map_err(|e| e.to_string())?,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    conn.execute(
+        "INSERT INTO phases (id, task_id, idx, title, description, status, planned_files_json, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        (&phase.id, &phase.task_id, phase.idx, &phase.title, &phase.description, &phase.status,
+         &phase.planned_files_json, &phase.created_at, &phase.updated_at)
+    ).map_err(|e| e.to_string())?;
+    Ok(phase)
+}
+
+const PHASE_COLUMNS: &str = "id, task_id, idx, title, description, status, planned_files_json, created_at, updated_at";
+
+fn phase_from_row(r: &rusqlite::Row) -> rusqlite::Result<Phase> {
+    Ok(Phase {
+        id: r.get(0)?,
+        task_id: r.get(1)?,
+        idx: r.get(2)?,
+        title: r.get(3)?,
+        description: r.get(4)?,
+        status: r.get(5)?,
+        planned_files_json: r.get(6)?,
+        created_at: r.get(7)?,
+        updated_at: r.get(8)?,
+    })
+}
+
+pub fn list_phases(app: &AppHandle, task_id: &str) -> Result<Vec<Phase>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM phases WHERE task_id = ?1 ORDER BY idx ASC", PHASE_COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([task_id], phase_from_row).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn get_phase(conn: &Connection, phase_id: &str) -> Result<Phase, String> {
+    conn.query_row(&format!("SELECT {} FROM phases WHERE id = ?1", PHASE_COLUMNS), [phase_id], phase_from_row)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => format!("Phase {} not found", phase_id),
+            e => e.to_string(),
+        })
+}
+
+/// Add a phase after the task's last one
+pub fn create_phase(app: &AppHandle, task_id: &str, title: &str, description: Option<String>, planned_files: &[String]) -> Result<Phase, String> {
+    if title.trim().is_empty() {
+        return Err("A phase needs a title".into());
+    }
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let idx: i64 = conn.query_row("SELECT COALESCE(MAX(idx) + 1, 0) FROM phases WHERE task_id = ?1", [task_id], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    insert_phase(&conn, task_id, idx, title.trim(), description, planned_files)
+}
+
+/// Fields to change on a phase; unset ones are left as they are
+#[derive(Debug, Default, Deserialize, Type)]
+pub struct PhaseUpdate {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub planned_files: Option<Vec<String>>,
+}
+
+pub fn update_phase(app: &AppHandle, phase_id: &str, update: PhaseUpdate) -> Result<Phase, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut phase = get_phase(&conn, phase_id)?;
+    if let Some(title) = update.title {
+        if title.trim().is_empty() {
+            return Err("A phase needs a title".into());
+        }
+        phase.title = title.trim().to_string();
+    }
+    if let Some(description) = update.description {
+        phase.description = Some(description.trim().to_string()).filter(|d| !d.is_empty());
+    }
+    if let Some(status) = update.status {
+        if !PHASE_STATUSES.contains(&status.as_str()) {
+            return Err(format!("Unknown phase status '{}' (expected todo, active, or done)", status));
+        }
+        phase.status = status;
+    }
+    if let Some(files) = update.planned_files {
+        phase.planned_files_json = serde_json::to_string(&files).map_err(|e| e.to_string())?;
+    }
+    phase.updated_at = now_iso();
+    conn.execute(
+        "UPDATE phases SET title = ?1, description = ?2, status = ?3, planned_files_json = ?4, updated_at = ?5 WHERE id = ?6",
+        (&phase.title, &phase.description, &phase.status, &phase.planned_files_json, &phase.updated_at, &phase.id)
+    ).map_err(|e| e.to_string())?;
+    Ok(phase)
+}
+
+/// Delete a phase with its own artifacts, which would otherwise be left
+/// looking like the task's; its runs stay, no longer tied to a phase
+pub fn delete_phase(app: &AppHandle, phase_id: &str) -> Result<(), String> {
+    let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM artifacts WHERE phase_id = ?1", [phase_id]).map_err(|e| e.to_string())?;
+    let deleted = tx.execute("DELETE FROM phases WHERE id = ?1", [phase_id]).map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Phase {} not found", phase_id));
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Replace the phases `phase_id` waits on
+pub fn set_dependencies(app: &AppHandle, phase_id: &str, depends_on: &[String]) -> Result<(), String> {
+    let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    check_dependencies(&tx, phase_id, depends_on)?;
+    tx.execute("DELETE FROM phase_dependencies WHERE phase_id = ?1", [phase_id])
+        .map_err(|e| e.to_string())?;
+    for dep in depends_on {
+        tx.execute(
+            "INSERT OR IGNORE INTO phase_dependencies (phase_id, depends_on_phase_id) VALUES (?1, ?2)",
+            (phase_id, dep)
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Dependencies must be other phases of the same task, and mustn't lead back
+/// to `phase_id`, or none of the phases in the cycle could ever start
+fn check_dependencies(conn: &Connection, phase_id: &str, depends_on: &[String]) -> Result<(), String> {
+    let task_id = get_phase(conn, phase_id)?.task_id;
+    for dep in depends_on {
+        if dep == phase_id {
+            return Err("A phase cannot depend on itself".into());
+        }
+        let dep_task: Option<String> = conn.query_row("SELECT task_id FROM phases WHERE id = ?1", [dep], |r| r.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        match dep_task {
+            None => return Err(format!("Phase {} not found", dep)),
+            Some(dep_task) if dep_task != task_id => {
+                return Err("A phase can only depend on phases of the same task".into());
+            }
+            Some(_) => {}
+        }
+    }
+
+    // The task's dependencies as they'd be after the change
+    let mut stmt = conn.prepare(
+        "SELECT d.phase_id, d.depends_on_phase_id FROM phase_dependencies d
+         JOIN phases p ON p.id = d.phase_id WHERE p.task_id = ?1 AND d.phase_id != ?2"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([&task_id, phase_id], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (from, to) = row.map_err(|e| e.to_string())?;
+        edges.entry(from).or_default().push(to);
+    }
+
+    let mut stack: Vec<&str> = depends_on.iter().map(String::as_str).collect();
+    let mut seen: HashSet<&str> = HashSet::new();
+    while let Some(id) = stack.pop() {
+        if id == phase_id {
+            return Err("These dependencies would create a cycle between phases".into());
+        }
+        if seen.insert(id) {
+            stack.extend(edges.get(id).into_iter().flatten().map(String::as_str));
+        }
+    }
+    Ok(())
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_phases() {
+        let output = "```json\n{\"phases\": [\
+            {\"title\": \"Add the schema\", \"description\": \"Migration and model\", \"planned_files\": [\"migrations/001.sql\"]},\
+            {\"title\": \"Wire the command\", \"depends_on\": [1]}\
+        ]}\n```";
+        let phases = parse_phases(output).unwrap();
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases[1].depends_on, [1]);
+
+        let md = render_phase_list(&phases);
+        assert!(md.contains("## 1. Add the schema\n\nMigration and model\n\nFiles: `migrations/001.sql`"));
+        assert!(md.contains("## 2. Wire the command\n\nDepends on: 1"));
+
+        assert!(parse_phases("No phases here").is_err());
+        assert!(parse_phases(r#"{"phases": []}"#).is_err());
+        assert!(parse_phases(r#"{"phases": [{"title": "A", "depends_on": [1]}]}"#).unwrap_err().contains("doesn't come before"));
+        assert!(parse_phases(r#"{"phases": [{"title": " "}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_check_dependencies() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE phases (id TEXT PRIMARY KEY, task_id TEXT NOT NULL, idx INTEGER NOT NULL, title TEXT NOT NULL,
+               description TEXT, status TEXT NOT NULL, planned_files_json TEXT NOT NULL DEFAULT '[]',
+               created_at TEXT NOT NULL, updated_at TEXT NOT NULL);
+             CREATE TABLE phase_dependencies (phase_id TEXT NOT NULL, depends_on_phase_id TEXT NOT NULL,
+               PRIMARY KEY (phase_id, depends_on_phase_id));"
+        ).unwrap();
+        for (id, task) in [("a", "t1"), ("b", "t1"), ("c", "t1"), ("x", "t2")] {
+            conn.execute(
+                "INSERT INTO phases (id, task_id, idx, title, status, created_at, updated_at) VALUES (?1, ?2, 0, ?1, 'todo', '', '')",
+                (id, task)
+            ).unwrap();
+        }
+        // c waits on b, which waits on a
+        conn.execute("INSERT INTO phase_dependencies VALUES ('b', 'a'), ('c', 'b')", []).unwrap();
+
+        let deps = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        assert!(check_dependencies(&conn, "c", &deps(&["a", "b"])).is_ok());
+        // Replacing b's own dependencies doesn't count them
+        assert!(check_dependencies(&conn, "b", &deps(&[])).is_ok());
+        assert!(check_dependencies(&conn, "a", &deps(&["a"])).unwrap_err().contains("itself"));
+        assert!(check_dependencies(&conn, "a", &deps(&["c"])).unwrap_err().contains("cycle"));
+        assert!(check_dependencies(&conn, "a", &deps(&["x"])).unwrap_err().contains("same task"));
+        assert!(check_dependencies(&conn, "a", &deps(&["missing"])).unwrap_err().contains("not found"));
+        assert!(check_dependencies(&conn, "missing", &deps(&[])).unwrap_err().contains("not found"));
+    }
+}
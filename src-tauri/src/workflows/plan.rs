@@ -1,23 +1,81 @@
 use serde::Serialize;
 use serde_json::{json, Value};
+use specta::Type;
 use tauri::AppHandle;
 use std::collections::HashMap;
 use std::path::Path;
+use std::time::Instant;
 
+use crate::cancellation;
 use crate::db;
+use crate::events::{self, RunEvent, RunStatus};
+use crate::windows::emit_run_event;
+use crate::render::store_plan_diagrams;
+use crate::demo::{is_demo_project, DEMO_READ_ONLY_MESSAGE};
+use crate::feedback::feedback_context;
+use crate::project_artifacts::project_context;
+use crate::lineage::RunParent;
 use crate::models::*;
-use crate::repo_tools::{repo_tool_schemas, dispatch_repo_tool};
-use crate::llm::{LlmClient, ChatMessage, LlmConfig, LlmError};
+use crate::offline::{self, OFFLINE_MESSAGE};
+use crate::repo_tools::{repo_tool_schemas, dispatch_repo_tool, ToolLoopGuard};
+use crate::repo_tools::permissions::tool_schemas_for;
+use crate::repo_tools::schemas::{adapt_schemas, SchemaProfile};
+use crate::settings::{cached_settings, AppSettings, SettingsMap};
+use crate::workflows::attachments::attachments_context;
+use crate::workflows::context::{pinned_files_context, relevant_files, relevant_files_context};
+use crate::workflows::rerun::record_regeneration;
+use crate::workflows::retrieval::{planning_strategy, retrieved_context, PlanningStrategy};
+use crate::workflows::budget::{check_run_budget, record_usage};
+use crate::workflows::drafts::DraftCheckpointer;
+use crate::workflows::engine::{finish_run, log_message, save_artifact};
+use crate::workflows::hooks::{fire_hook, HookEvent};
+use crate::workflows::locale::{resolve_output_language, plan_headings, language_instruction, missing_sections};
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+use crate::workflows::templates::render_custom_prompt;
+use crate::workflows::tokens::TokenEmitter;
+use crate::llm::{LlmClient, ChatMessage, ChatOptions, LlmConfig, LlmError, ToolChoice};
 
 const MAX_TOOL_ITERATIONS: usize = 12;
 const MAX_CONTEXT_CHARS: usize = 100_000;
+const MAX_LOOP_NUDGES: usize = 2;
 
-#[derive(Debug, Serialize)]
+const LOOP_NUDGE: &str = "You already made this exact tool call and have its result above. \
+Do not repeat it; use the context you have gathered and proceed to write the plan.";
+const FINAL_ANSWER_NUDGE: &str = "You keep repeating tool calls, so tools are now disabled. \
+Write the complete implementation plan now using only the context gathered so far.";
+const BUDGET_EXHAUSTED_NUDGE: &str = "The tool call budget for this run is exhausted and tools are now disabled. \
+Produce the best implementation plan you can from the context gathered so far, \
+noting any areas that could not be explored.";
+
+#[derive(Debug, Serialize, Type)]
 pub struct PlanResult {
     pub run_id: String,
     pub plan_md: String,
     pub tool_calls_count: usize,
     pub truncated: bool,
+    pub forced_completion: bool,
+    /// Language code the plan was requested in
+    pub language: String,
+    /// Expected section headings missing from the plan
+    pub missing_sections: Vec<String>,
+    /// Mermaid diagrams that failed validation and were not stored
+    pub diagram_errors: Vec<String>,
+}
+
+/// An earlier run's conversation to pick the tool loop up from, instead of
+/// building a fresh prompt
+pub struct PlanContinuation {
+    pub messages: Vec<ChatMessage>,
+    pub parent_run_id: String,
+    /// The assistant message the conversation was cut after
+    pub message_id: String,
+}
+
+/// A competing plan in a debate: saved under its own artifact kind instead of
+/// `plan_md`, optionally from another model
+pub struct PlanVariant {
+    pub artifact_kind: String,
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,7 +87,7 @@ pub struct PlanError {
 impl From<LlmError> for PlanError {
     fn from(e: LlmError) -> Self {
         PlanError {
-            code: "LLM_ERROR".to_string(),
+            code: e.code().to_string(),
             message: e.to_string(),
         }
     }
@@ -40,150 +98,469 @@ pub async fn generate_plan(
     project_id: String,
     task_id: String,
 ) -> Result<PlanResult, PlanError> {
+    generate_plan_with_seed(app, project_id, task_id, None).await
+}
+
+/// Generate a plan, sampling with `seed` instead of the configured one when set
+pub async fn generate_plan_with_seed(
+    app: AppHandle,
+    project_id: String,
+    task_id: String,
+    seed: Option<i64>,
+) -> Result<PlanResult, PlanError> {
+    run_plan(app, project_id, task_id, seed, None, None, None).await
+}
+
+/// Generate a plan recorded as a rerun or retry of `parent`
+pub async fn generate_plan_linked(
+    app: AppHandle,
+    project_id: String,
+    task_id: String,
+    seed: Option<i64>,
+    parent: RunParent,
+) -> Result<PlanResult, PlanError> {
+    run_plan(app, project_id, task_id, seed, Some(parent), None, None).await
+}
+
+/// Continue an earlier run's conversation in a new run
+pub async fn continue_plan(
+    app: AppHandle,
+    project_id: String,
+    task_id: String,
+    continuation: PlanContinuation,
+) -> Result<PlanResult, PlanError> {
+    let parent = RunParent::new(&continuation.parent_run_id, "regenerate");
+    run_plan(app, project_id, task_id, None, Some(parent), Some(continuation), None).await
+}
+
+/// Generate one of a debate's candidate plans, recorded under `parent`
+pub async fn generate_plan_variant(
+    app: AppHandle,
+    project_id: String,
+    task_id: String,
+    variant: PlanVariant,
+    parent: RunParent,
+) -> Result<PlanResult, PlanError> {
+    run_plan(app, project_id, task_id, None, Some(parent), None, Some(variant)).await
+}
+
+#[tracing::instrument(skip(app, parent, continuation, variant), fields(run_id = tracing::field::Empty))]
+async fn run_plan(
+    app: AppHandle,
+    project_id: String,
+    task_id: String,
+    seed: Option<i64>,
+    parent: Option<RunParent>,
+    continuation: Option<PlanContinuation>,
+    variant: Option<PlanVariant>,
+) -> Result<PlanResult, PlanError> {
+    let started = Instant::now();
+    let artifact_kind = variant.as_ref().map_or("plan_md", |v| v.artifact_kind.as_str()).to_string();
+
     // 1. Get task and project info
     let (task, project) = get_task_and_project(&app, &task_id, &project_id
     ).map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })?;
     
+    // Demo projects are replayed from fixtures and never call the LLM
+    if is_demo_project(&app, &project.id).map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })? {
+        return Err(PlanError { code: "DEMO_READ_ONLY".into(), message: DEMO_READ_ONLY_MESSAGE.into() });
+    }
+    
     // 2. Get settings for LLM
-    let settings = get_all_settings(&app)?;
-    let llm_config = build_llm_config(&settings);
+    let settings = get_all_settings(&app).await?;
+    let mut llm_config = build_llm_config(&settings)
+        .map_err(|e| PlanError { code: "INVALID_SETTING".into(), message: e })?;
+    if seed.is_some() {
+        llm_config.seed = seed;
+    }
+    if let Some(model) = variant.as_ref().and_then(|v| v.model.clone()) {
+        llm_config.model = model;
+    }
     let api_key = get_api_key(&settings)?;
+    if offline::is_offline(&app, &settings) {
+        // The queue replays fresh runs only; a continuation or debate candidate has
+        // to be retried by hand
+        if continuation.is_none() && variant.is_none() {
+            offline::enqueue_run(&app, &project.id, &task_id, "plan", json!({ "parent": parent, "seed": seed }))
+                .map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })?;
+        }
+        return Err(PlanError { code: "OFFLINE".into(), message: OFFLINE_MESSAGE.into() });
+    }
+    check_run_budget(&app, &settings, &project.id)
+        .map_err(|b| PlanError { code: b.code.into(), message: b.message })?;
     
     // 3. Create run
-    let run_id = create_run_plan(&app, &task_id, &llm_config
+    let run_id = create_run_plan(&app, &task_id, &llm_config, parent.as_ref()
     ).map_err(|e| PlanError { code: "RUN_ERROR".into(), message: e })?;
+    tracing::Span::current().record("run_id", run_id.as_str());
+    if let Some(continuation) = &continuation {
+        record_regeneration(&app, &run_id, &continuation.parent_run_id, &continuation.message_id
+        ).map_err(|e| PlanError { code: "RUN_ERROR".into(), message: e })?;
+    }
+    let fresh = continuation.is_none();
+    // A debate's candidates stop along with the debate
+    let cancellation = match (&variant, &parent) {
+        (Some(_), Some(parent)) => cancellation::register_child(&app, &run_id, &parent.run_id),
+        _ => cancellation::register(&app, &run_id),
+    };
     
-    // 4. Build initial messages
-    let mut messages = build_initial_messages(&task, &project);
+    emit_run_event(&app, RunEvent {
+        run_id: run_id.clone(),
+        project_id: project.id.clone(),
+        task_id: task_id.clone(),
+        run_type: "plan".into(),
+        status: RunStatus::Started,
+        error: None,
+        code: None,
+    });
     
-    // Log system and user messages
-    for msg in &messages {
-        log_message(&app, &run_id, &msg.role, msg.content.as_deref().unwrap_or("")
-        ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
-    }
+    // Everything after run creation is recorded on the run, so failures show up in diagnostics
+    let result: Result<PlanResult, PlanError> = async {
+        let language = resolve_output_language(&app, &settings, &project.id
+        ).map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })?;
+        // 4. Build initial messages (a custom prompt template replaces the default user
+        // prompt), or pick up the conversation being continued
+        let (mut messages, explore_first) = match continuation {
+            Some(continuation) => (continuation.messages, false),
+            None => {
+                let custom_prompt = render_custom_prompt(&app, &settings, "plan", &task, &project
+                ).map_err(|e| PlanError { code: "TEMPLATE_ERROR".into(), message: e })?;
+                let user_prompt = match custom_prompt {
+                    Some(prompt) => prompt,
+                    None => {
+                        let attachments = attachments_context(&app, &task_id
+                        ).map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })?;
+                        build_user_prompt(&task, &project, attachments.as_deref())
+                    }
+                };
+                // Point repeat runs at the files that mattered last time
+                let relevant = relevant_files(&app, &task_id, Path::new(&project.repo_path)
+                ).await.map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })?;
+                let user_prompt = match relevant_files_context(&relevant) {
+                    Some(context) => format!("{}\n{}", context, user_prompt),
+                    None => user_prompt,
+                };
+                // A reviewer's ratings of the plan this run replaces
+                let user_prompt = if settings.get("feedback_in_prompt").is_none_or(|v| v == "1") {
+                    match feedback_context(&app, &task_id, None, "plan_md")
+                        .map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })? {
+                        Some(feedback) => format!("{}\n{}", feedback, user_prompt),
+                        None => user_prompt,
+                    }
+                } else {
+                    user_prompt
+                };
+                // Project conventions and pinned project artifacts
+                let user_prompt = match project_context(&app, &project.id)
+                    .map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })? {
+                    Some(context) => format!("{}\n\n{}", user_prompt, context),
+                    None => user_prompt,
+                };
+                // Files the user pinned for the task go in whole
+                let user_prompt = match pinned_files_context(Path::new(&project.repo_path), &relevant).await {
+                    Some(context) => format!("{}\n\n{}", user_prompt, context),
+                    None => user_prompt,
+                };
+                // Retrieval planning starts from the embeddings index's closest chunks
+                let strategy = planning_strategy(&app, &settings, &project.id
+                ).map_err(|e| PlanError { code: "DB_ERROR".into(), message: e })?;
+                let retrieved = match strategy {
+                    // Without retrieved context the planner explores with tools instead
+                    PlanningStrategy::Retrieval => retrieved_context(&app, &settings, &api_key, &run_id, &project.id, &task)
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::warn!("retrieval failed for project {}: {}", project.id, e);
+                            None
+                        }),
+                    PlanningStrategy::Explore => None,
+                };
+                if strategy == PlanningStrategy::Retrieval && retrieved.is_none() {
+                    tracing::warn!("project {} has no embeddings index for retrieval planning; exploring instead", project.id);
+                }
+                let user_prompt = match &retrieved {
+                    Some(context) => format!("{}\n\n{}", user_prompt, context),
+                    None => user_prompt,
+                };
+                (build_initial_messages(user_prompt, &language), retrieved.is_none())
+            }
+        };
     
-    // 5. Get tool schemas
-    let tools = repo_tool_schemas();
+        // Log the opening messages; a continuation's are copied so the new run's transcript is complete
+        for msg in &messages {
+            log_message(&app, &run_id, &msg.role, msg.content.as_deref().unwrap_or(""), msg.tool_calls.as_deref(), msg.tool_call_id.as_deref()
+            ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
+        }
+    
+        // 5. Get the tool schemas plan runs are permitted to use, shaped for the provider
+        let tools = adapt_schemas(tool_schemas_for("plan", repo_tool_schemas()), SchemaProfile::from_settings(&settings));
+    
+        // 6. Tool-call loop
+        let client = LlmClient::new(llm_config, api_key);
+        let mut tool_calls_count = 0;
+        let mut truncated = false;
+        let mut final_plan = String::new();
     
-    // 6. Tool-call loop
-    let client = LlmClient::new(llm_config, api_key);
-    let mut tool_calls_count = 0;
-    let mut truncated = false;
-    let mut final_plan = String::new();
+        let typed = AppSettings::from_map(&settings);
+        let loop_threshold = typed.tool_loop_threshold;
+        let abort_on_loop = typed.tool_loop_abort;
+        let parallel_tool_calls = settings.get("parallel_tool_calls").map(|v| v == "1");
+        let stream = typed.stream_responses;
+        let mut draft = DraftCheckpointer::new(&app, &task_id, &artifact_kind);
+        let mut tokens = TokenEmitter::new(&app, events::PLAN_TOKEN, &run_id, &project.id, &task_id);
+        let mut loop_guard = ToolLoopGuard::new(loop_threshold);
+        let mut loop_nudges = 0;
+        let mut force_final = false;
+        let cancelled = |message: String| PlanError { code: "CANCELLED".into(), message };
     
-    for _iteration in 0..MAX_TOOL_ITERATIONS {
-        // Check context size
-        let context_size: usize = messages.iter()
-            .map(|m| m.content.as_ref().map_or(0, |c| c.len()))
-            .sum();
+        for iteration in 0..MAX_TOOL_ITERATIONS {
+            cancellation.check().map_err(cancelled)?;
+
+            // Reserve the last iteration for a tools-disabled final answer
+            if iteration == MAX_TOOL_ITERATIONS - 1 && !force_final {
+                force_final = true;
+                messages.push(ChatMessage {
+                    role: "system".into(),
+                    content: Some(BUDGET_EXHAUSTED_NUDGE.into()),
+                    tool_call_id: None,
+                    tool_calls: None,
+                });
+                log_message(&app, &run_id, "system", BUDGET_EXHAUSTED_NUDGE, None, None
+                ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
+            }
         
-        if context_size > MAX_CONTEXT_CHARS {
-            truncated = true;
-            messages = truncate_messages(messages, MAX_CONTEXT_CHARS);
-        }
+            // Check context size
+            let context_size: usize = messages.iter()
+                .map(|m| m.content.as_ref().map_or(0, |c| c.len()))
+                .sum();
         
-        // Call LLM
-        let response = client.chat_with_tools(messages.clone(), tools.clone()).await?;
+            if context_size > MAX_CONTEXT_CHARS {
+                truncated = true;
+                messages = truncate_messages(messages, MAX_CONTEXT_CHARS);
+            }
         
-        // Check for tool calls
-        if let Some(tool_calls) = response.tool_calls {
-            if tool_calls.is_empty() {
-                // No more tools, we have final plan
-                final_plan = response.content.unwrap_or_default();
+            // Call LLM: a fresh run's first call must explore unless retrieval
+            // already supplied context, and once the run has been forced to
+            // answer the model may no longer call tools
+            let tool_choice = if force_final {
+                ToolChoice::None
+            } else if iteration == 0 && explore_first {
+                ToolChoice::Required
+            } else {
+                ToolChoice::Auto
+            };
+            let options = ChatOptions { tool_choice: Some(tool_choice), parallel_tool_calls };
+            draft.reset();
+            tokens.next_generation();
+            // A cancelled run doesn't wait for the model to finish answering
+            let response = cancellation.or_cancel(async {
+                if stream {
+                    client.chat_stream(messages.clone(), tools.clone(), options, |delta| {
+                        draft.push(delta);
+                        tokens.push(delta);
+                    }).await
+                } else {
+                    client.chat_with_options(messages.clone(), tools.clone(), options).await
+                }
+            }).await.map_err(cancelled)??;
+            record_usage(&app, &settings, &run_id, response.usage);
+        
+            // Check for tool calls
+            if let Some(tool_calls) = response.tool_calls {
+                // Ignore stray tool calls once tools have been withdrawn
+                if tool_calls.is_empty() || force_final {
+                    // No more tools, we have final plan
+                    final_plan = response.content.unwrap_or_default();
                 
-                // Log assistant message
-                log_message(&app, &run_id, "assistant", &final_plan
+                    // Log assistant message
+                    log_message(&app, &run_id, "assistant", &final_plan, None, None
+                    ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
+                    break;
+                }
+            
+                tool_calls_count += tool_calls.len();
+            
+                // Log assistant message with tool calls
+                let tool_names: Vec<&str> = tool_calls.iter().map(|t| t.function.name.as_str()).collect();
+                let assistant_content = response.content.clone()
+                    .unwrap_or_else(|| format!("Calling tools: {}", tool_names.join(", ")));
+                log_message(&app, &run_id, "assistant", &assistant_content, Some(tool_calls.as_slice()), None
                 ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
-                break;
-            }
             
-            tool_calls_count += tool_calls.len();
+                // Detect repeated or oscillating calls; abort if configured to
+                let mut looping = false;
+                for tool_call in &tool_calls {
+                    let args = serde_json::from_str::<Value>(&tool_call.function.arguments)
+                        .unwrap_or(Value::Null);
+                    let flag = loop_guard.observe(&tool_call.function.name, &args);
+                    if let (true, Some(flag)) = (abort_on_loop, flag) {
+                        return Err(PlanError {
+                            code: "TOOL_LOOP".into(),
+                            message: format!(
+                                "Aborted run {}: {} called {} times with identical args {}",
+                                run_id, flag.name, flag.count, flag.args_json
+                            ),
+                        });
+                    }
+                    looping |= loop_guard.is_looping();
+                }
             
-            // Log assistant message with tool calls
-            let tool_names: Vec<&str> = tool_calls.iter().map(|t| t.function.name.as_str()).collect();
-            let assistant_content = response.content.clone()
-                .unwrap_or_else(|| format!("Calling tools: {}", tool_names.join(", ")));
-            log_message(&app, &run_id, "assistant", &assistant_content
-            ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
+                // Assistant message must precede its tool results in the context
+                messages.push(ChatMessage {
+                    role: "assistant".into(),
+                    content: response.content,
+                    tool_call_id: None,
+                    tool_calls: Some(tool_calls.clone()),
+                });
             
-            // Execute each tool call
-            for tool_call in &tool_calls {
-                let tool_result = execute_single_tool(
-                    &app,
-                    &run_id,
-                    &project_id,
-                    &tool_call,
-                ).await;
+                // Execute each tool call
+                for tool_call in &tool_calls {
+                    cancellation.check().map_err(cancelled)?;
+                    let tool_result = execute_single_tool(
+                        &app,
+                        &run_id,
+                        &project_id,
+                        &tool_call,
+                    ).await;
                 
-                // Add tool result as message
-                let tool_content = match &tool_result {
-                    Ok(val) => val.to_string(),
-                    Err(e) => json!({ "error": e }).to_string(),
-                };
+                    // Add tool result as message, delimited as untrusted repo content
+                    let tool_content = match &tool_result {
+                        Ok(val) => val.to_string(),
+                        Err(e) => json!({ "error": e }).to_string(),
+                    };
+                    let tool_content = wrap_untrusted(&tool_call.function.name, &tool_content);
                 
-                let tool_message = ChatMessage {
-                    role: "tool".into(),
-                    content: Some(tool_content.clone()),
-                    tool_call_id: Some(tool_call.id.clone()),
-                    tool_calls: None,
-                };
+                    let tool_message = ChatMessage {
+                        role: "tool".into(),
+                        content: Some(tool_content.clone()),
+                        tool_call_id: Some(tool_call.id.clone()),
+                        tool_calls: None,
+                    };
                 
-                messages.push(tool_message.clone());
+                    messages.push(tool_message.clone());
                 
-                // Log to database
-                log_message(&app, &run_id, "tool", &tool_content
+                    // Log to database
+                    log_message(&app, &run_id, "tool", &tool_content, None, Some(&tool_call.id)
+                    ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
+                }
+            
+                // Nudge the model out of a loop, then stop offering tools if it persists
+                if looping {
+                    loop_nudges += 1;
+                    let nudge = if loop_nudges > MAX_LOOP_NUDGES {
+                        force_final = true;
+                        FINAL_ANSWER_NUDGE
+                    } else {
+                        LOOP_NUDGE
+                    };
+                    messages.push(ChatMessage {
+                        role: "system".into(),
+                        content: Some(nudge.into()),
+                        tool_call_id: None,
+                        tool_calls: None,
+                    });
+                    log_message(&app, &run_id, "system", nudge, None, None
+                    ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
+                }
+            } else {
+                // No tool calls, we have final plan
+                final_plan = response.content.unwrap_or_default();
+            
+                // Log assistant message
+                log_message(&app, &run_id, "assistant", &final_plan, None, None
                 ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
+                break;
             }
-            
-            // Add assistant message to context for next iteration
-            messages.push(ChatMessage {
-                role: "assistant".into(),
-                content: response.content,
-                tool_call_id: None,
-                tool_calls: Some(tool_calls),
-            });
+        }
+    
+        let forced_completion = force_final && !final_plan.is_empty();
+        let missing_sections = if final_plan.is_empty() {
+            vec![]
         } else {
-            // No tool calls, we have final plan
-            final_plan = response.content.unwrap_or_default();
-            
-            // Log assistant message
-            log_message(&app, &run_id, "assistant", &final_plan
-            ).map_err(|e| PlanError { code: "LOG_ERROR".into(), message: e })?;
-            break;
+            missing_sections(&final_plan, plan_headings(&language))
+        };
+    
+        // The forced final request can still come back empty; fall back to an error note
+        if final_plan.is_empty() {
+            final_plan = format!(
+                "**Error**: Reached maximum tool call limit ({}). Unable to complete plan.\n\n\
+                 Please try:\n\
+                 1. Breaking this task into smaller, more specific tasks\n\
+                 2. Providing more context about what needs to be done\n\
+                 3. Checking if the repository is accessible and contains the expected files",
+                MAX_TOOL_ITERATIONS
+            );
+            truncated = true;
         }
-    }
     
-    // If we hit max iterations, add a note
-    if tool_calls_count >= MAX_TOOL_ITERATIONS && final_plan.is_empty() {
-        final_plan = format!(
-            "**Error**: Reached maximum tool call limit ({}). Unable to complete plan.\n\n\
-             Please try:\n\
-             1. Breaking this task into smaller, more specific tasks\n\
-             2. Providing more context about what needs to be done\n\
-             3. Checking if the repository is accessible and contains the expected files",
-            MAX_TOOL_ITERATIONS
-        );
-        truncated = true;
-    }
+        // Add truncation note if needed
+        if truncated {
+            final_plan = format!(
+                "{}\n\n---\n\n**Note**: This plan was truncated due to context size limits. Some details may be incomplete.",
+                final_plan
+            );
+        }
     
-    // Add truncation note if needed
-    if truncated {
-        final_plan = format!(
-            "{}\n\n---\n\n**Note**: This plan was truncated due to context size limits. Some details may be incomplete.",
-            final_plan
-        );
-    }
+        // 7. Save plan artifact
+        save_artifact(&app, &task_id, None, &artifact_kind, &final_plan
+        ).map_err(|e| PlanError { code: "ARTIFACT_ERROR".into(), message: e })?;
+        let _ = draft.discard();
+        // Only the task's plan gets diagrams; a debate candidate's are the judge's to keep
+        let diagram_errors = if artifact_kind == "plan_md" {
+            store_plan_diagrams(&app, &task_id, &final_plan
+            ).map_err(|e| PlanError { code: "ARTIFACT_ERROR".into(), message: e })?
+        } else {
+            vec![]
+        };
+        if artifact_kind == "plan_md" {
+            let details = json!({ "project_id": project.id, "task_id": task_id, "plan_md": final_plan });
+            fire_hook(&app, &run_id, HookEvent::PlanSaved, Path::new(&project.repo_path), details).await;
+        }
     
-    // 7. Save plan artifact
-    save_artifact(&app, &task_id, &final_plan
-    ).map_err(|e| PlanError { code: "ARTIFACT_ERROR".into(), message: e })?;
+        tracing::info!(
+            tool_calls = tool_calls_count,
+            truncated,
+            forced_completion,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "plan generated"
+        );
     
-    Ok(PlanResult {
+        Ok(PlanResult {
+            run_id: run_id.clone(),
+            plan_md: final_plan,
+            tool_calls_count,
+            truncated,
+            forced_completion,
+            language,
+            missing_sections,
+            diagram_errors,
+        })
+    }.await;
+
+    // Lost connectivity mid-run: retry the whole run once back online (a debate
+    // candidate is retried by rerunning the debate)
+    if fresh && variant.is_none() && result.as_ref().is_err_and(|e| e.code == "OFFLINE") {
+        offline::mark_offline(&app);
+        let retry = RunParent::new(&run_id, "retry");
+        if let Err(e) = offline::enqueue_run(&app, &project.id, &task_id, "plan", json!({ "parent": retry, "seed": seed })) {
+            tracing::warn!("failed to queue run: {}", e);
+        }
+    }
+    let was_cancelled = result.as_ref().is_err_and(|e| e.code == "CANCELLED");
+    finish_run(&app, &run_id, result.as_ref().err().map(|e| e.message.as_str()), was_cancelled);
+    emit_run_event(&app, RunEvent {
         run_id,
-        plan_md: final_plan,
-        tool_calls_count,
-        truncated,
-    })
+        project_id: project.id.clone(),
+        task_id,
+        run_type: "plan".into(),
+        status: match &result {
+            Ok(_) => RunStatus::Finished,
+            Err(_) if was_cancelled => RunStatus::Cancelled,
+            Err(_) => RunStatus::Failed,
+        },
+        error: result.as_ref().err().map(|e| e.message.clone()),
+        code: result.as_ref().err().map(|e| e.code.clone()),
+    });
+    result
 }
 
 fn get_task_and_project(
@@ -194,7 +571,7 @@ fn get_task_and_project(
     let conn = db::connect(app).map_err(|e| e.to_string())?;
     
     let task: Task = conn.query_row(
-        "SELECT id, project_id, title, mode, status, created_at, updated_at FROM tasks WHERE id = ?1",
+        "SELECT id, project_id, title, mode, status, created_at, updated_at, description FROM tasks WHERE id = ?1",
         [task_id],
         |r| Ok(Task {
             id: r.get(0)?,
@@ -204,11 +581,12 @@ fn get_task_and_project(
             status: r.get(4)?,
             created_at: r.get(5)?,
             updated_at: r.get(6)?,
+            description: r.get(7)?,
         })
     ).map_err(|e| e.to_string())?;
     
     let project: Project = conn.query_row(
-        "SELECT id, name, repo_path, created_at, last_opened_at FROM projects WHERE id = ?1",
+        "SELECT id, name, repo_path, created_at, last_opened_at, workspace_id FROM projects WHERE id = ?1",
         [project_id],
         |r| Ok(Project {
             id: r.get(0)?,
@@ -216,6 +594,7 @@ fn get_task_and_project(
             repo_path: r.get(2)?,
             created_at: r.get(3)?,
             last_opened_at: r.get(4)?,
+            workspace_id: r.get(5)?,
         })
     ).map_err(|e| e.to_string())?;
     
@@ -226,77 +605,25 @@ fn create_run_plan(
     app: &AppHandle,
     task_id: &str,
     llm_config: &LlmConfig,
+    parent: Option<&RunParent>,
 ) -> Result<String, String> {
     let conn = db::connect(app).map_err(|e| e.to_string())?;
     let id = new_id();
     let started_at = now_iso();
     
     conn.execute(
-        "INSERT INTO runs (id, task_id, phase_id, run_type, provider, model, started_at, ended_at) 
-         VALUES (?1, ?2, NULL, ?3, ?4, ?5, ?6, NULL)",
-        (&id, task_id, "plan", &llm_config.provider_name, &llm_config.model, &started_at
+        "INSERT INTO runs (id, task_id, phase_id, run_type, provider, model, started_at, ended_at, seed, parent_run_id, parent_relation) 
+         VALUES (?1, ?2, NULL, ?3, ?4, ?5, ?6, NULL, ?7, ?8, ?9)",
+        (&id, task_id, "plan", &llm_config.provider_name, &llm_config.model, &started_at, llm_config.seed,
+         parent.map(|p| &p.run_id), parent.map(|p| &p.relation)
         )
     ).map_err(|e| e.to_string())?;
     
     Ok(id)
 }
 
-fn log_message(
-    app: &AppHandle,
-    run_id: &str,
-    role: &str,
-    content: &str,
-) -> Result<(), String> {
-    let conn = db::connect(app).map_err(|e| e.to_string())?;
-    let id = new_id();
-    let created_at = now_iso();
-    
-    conn.execute(
-        "INSERT INTO messages (id, run_id, role, content, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-        (&id, run_id, role, content, &created_at
-        )
-    ).map_err(|e| e.to_string())?;
-    
-    Ok(())
-}
-
-fn save_artifact(
-    app: &AppHandle,
-    task_id: &str,
-    content: &str,
-) -> Result<(), String> {
-    let conn = db::connect(app).map_err(|e| e.to_string())?;
-    let created_at = now_iso();
-    let id = new_id();
-    
-    // Check if artifact exists
-    let existing: Option<String> = conn.query_row(
-        "SELECT id FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = ?2 LIMIT 1",
-        (task_id, "plan_md"),
-        |r| r.get(0)
-    ).optional().map_err(|e| e.to_string())?;
-    
-    if let Some(existing_id) = existing {
-        // Update
-        conn.execute(
-            "UPDATE artifacts SET content = ?1, created_at = ?2 WHERE id = ?3",
-            (content, &created_at, &existing_id)
-        ).map_err(|e| e.to_string())?;
-    } else {
-        // Insert
-        conn.execute(
-            "INSERT INTO artifacts (id, task_id, phase_id, kind, content, created_at, pinned) 
-             VALUES (?1, ?2, NULL, ?3, ?4, ?5, 0)",
-            (&id, task_id, "plan_md", content, &created_at
-            )
-        ).map_err(|e| e.to_string())?;
-    }
-    
-    Ok(())
-}
-
-fn build_initial_messages(task: &Task, project: &Project) -> Vec<ChatMessage> {
-    let system_prompt = r#"You are a senior technical lead creating detailed implementation plans.
+fn build_initial_messages(user_prompt: String, language: &str) -> Vec<ChatMessage> {
+    let mut system_prompt = r#"You are a senior technical lead creating detailed implementation plans.
 
 Your task: Analyze the codebase and produce a comprehensive implementation plan.
 
@@ -339,6 +666,14 @@ For each file to modify/create:
 - [ ] Lint: `run_command` with kind="lint"
 - [ ] Build: `run_command` with kind="build"
 
+## 8. Diagrams (Optional)
+When the change spans several components, include one mermaid diagram
+(`flowchart` for architecture, `sequenceDiagram` for request flows):
+```mermaid
+flowchart LR
+  A[Component] --> B[Component]
+```
+
 ---
 
 Instructions:
@@ -349,24 +684,16 @@ Instructions:
 5. Call `git_status` and `git_diff` to see current state
 6. Only write the plan after gathering sufficient context
 7. If you need more information, make another tool call
-8. When complete, output ONLY the plan in the format above (no tool calls in final output)"#;
-
-    let user_prompt = format!(
-        r#"Task: {title}
-
-Repository: {repo_path}
-
-Please explore this codebase and create a detailed implementation plan.
-
-Start by listing files to understand the project structure, then read key files to understand the codebase before writing your plan."#,
-        title = task.title,
-        repo_path = project.repo_path,
-    );
+8. When complete, output ONLY the plan in the format above (no tool calls in final output)"#.to_string();
+    if let Some(instruction) = language_instruction(language, plan_headings(language)) {
+        system_prompt.push_str(&instruction);
+    }
+    system_prompt.push_str(UNTRUSTED_CONTENT_REMINDER);
 
     vec![
         ChatMessage {
             role: "system".into(),
-            content: Some(system_prompt.into()),
+            content: Some(system_prompt),
             tool_call_id: None,
             tool_calls: None,
         },
@@ -379,6 +706,33 @@ Start by listing files to understand the project structure, then read key files
     ]
 }
 
+/// Default user prompt for planning, used when no custom template is set
+pub fn build_user_prompt(task: &Task, project: &Project, attachments: Option<&str>) -> String {
+    let mut user_prompt = format!(
+        r#"Task: {title}
+
+Repository: {repo_path}
+
+Please explore this codebase and create a detailed implementation plan.
+
+Start by listing files to understand the project structure, then read key files to understand the codebase before writing your plan."#,
+        title = task.title,
+        repo_path = project.repo_path,
+    );
+
+    if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        user_prompt.push_str("\n\n## Task Description\n\n");
+        user_prompt.push_str(description);
+    }
+
+    if let Some(attachments) = attachments {
+        user_prompt.push_str("\n\n");
+        user_prompt.push_str(attachments);
+    }
+
+    user_prompt
+}
+
 async fn execute_single_tool(
     app: &AppHandle,
     run_id: &str,
@@ -389,13 +743,6 @@ async fn execute_single_tool(
     let args: Value = serde_json::from_str(&tool_call.function.arguments)
         .map_err(|e| format!("Failed to parse tool args: {}", e))?;
     
-    // Add project_id to args if not present
-    let mut args_with_project = args.clone();
-    if let Some(obj) = args_with_project.as_object_mut() {
-        obj.entry("project_id".to_string())
-            .or_insert_with(|| json!(project_id));
-    }
-    
     // Get project repo path
     let conn = db::connect(app).map_err(|e| e.to_string())?;
     let repo_path: String = conn.query_row(
@@ -408,60 +755,50 @@ async fn execute_single_tool(
     let repo_path = Path::new(&repo_path);
     dispatch_repo_tool(
         &tool_call.function.name,
-        &args_with_project,
+        &args,
         repo_path,
         app,
         run_id,
     ).await
 }
 
-fn get_all_settings(app: &AppHandle) -> Result<HashMap<String, String>, PlanError> {
-    let conn = db::connect(app).map_err(|e| PlanError {
+async fn get_all_settings(app: &AppHandle) -> Result<SettingsMap, PlanError> {
+    cached_settings(app).await.map_err(|e| PlanError {
         code: "DB_ERROR".into(),
-        message: e.to_string(),
-    })?;
-    
-    let mut stmt = conn.prepare("SELECT key, value FROM settings")
-        .map_err(|e| PlanError {
-            code: "DB_ERROR".into(),
-            message: e.to_string(),
-        })?;
-    
-    let rows = stmt.query_map([], |r| {
-        Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
-    }).map_err(|e| PlanError {
-        code: "DB_ERROR".into(),
-        message: e.to_string(),
-    })?;
-    
-    let mut settings = HashMap::new();
-    for row in rows {
-        let (k, v) = row.map_err(|e| PlanError {
-            code: "DB_ERROR".into(),
-            message: e.to_string(),
-        })?;
-        settings.insert(k, v);
-    }
-    
-    Ok(settings)
+        message: e,
+    })
 }
 
-fn build_llm_config(settings: &HashMap<String, String>) -> LlmConfig {
-    LlmConfig {
-        provider_name: settings.get("provider_name").cloned().unwrap_or_default(),
-        base_url: settings.get("base_url").cloned().unwrap_or_default(),
-        model: settings.get("model").cloned().unwrap_or_default(),
-        temperature: settings.get("temperature")
-            .and_then(|s| s.parse().ok()).unwrap_or(0.2),
-        max_tokens: settings.get("max_tokens")
-            .and_then(|s| s.parse().ok()).unwrap_or(4000),
+pub(crate) fn build_llm_config(settings: &HashMap<String, String>) -> Result<LlmConfig, String> {
+    let typed = AppSettings::from_map(settings);
+    Ok(LlmConfig {
+        provider_name: typed.provider_name,
+        base_url: typed.base_url,
+        model: typed.model,
+        temperature: typed.temperature,
+        max_tokens: typed.max_tokens,
         extra_headers: settings.get("extra_headers_json")
             .and_then(|s| serde_json::from_str(s).ok())
             .unwrap_or_else(|| json!({})),
+        seed: configured_seed(settings)?,
+    })
+}
+
+/// The `seed` setting: empty for none, an integer, or "random" for a fresh
+/// seed per run (recorded on the run so it can be replayed). Anything else is
+/// an error rather than quietly sampling without a seed.
+pub(crate) fn configured_seed(settings: &HashMap<String, String>) -> Result<Option<i64>, String> {
+    match settings.get("seed").map(|s| s.trim()) {
+        Some("random") => Ok(Some((uuid::Uuid::new_v4().as_u128() as u32 >> 1) as i64)),
+        Some("") | None => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(|_| format!(
+            "Invalid seed setting '{}': use an integer, 'random', or leave it empty",
+            value
+        )),
     }
 }
 
-fn get_api_key(settings: &HashMap<String, String>) -> Result<String, PlanError> {
+pub(crate) fn get_api_key(settings: &HashMap<String, String>) -> Result<String, PlanError> {
     // Try to get from settings first
     if let Some(key) = settings.get("api_key") {
         if !key.is_empty() {
@@ -504,17 +841,17 @@ fn new_id() -> String {
     uuid::Uuid::new_v4().to_string()
 }
 
-// Helper trait for OptionRow
-trait OptionalRow<T> {
-    fn optional(self) -> Result<Option<T>, rusqlite::Error>;
-}
-
-impl<T> OptionalRow<T> for Result<T, rusqlite::Error> {
-    fn optional(self) -> Result<Option<T>, rusqlite::Error> {
-        match self {
-            Ok(v) => Ok(Some(v)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_seed() {
+        let with = |value: &str| HashMap::from([("seed".to_string(), value.to_string())]);
+        assert_eq!(configured_seed(&HashMap::new()), Ok(None));
+        assert_eq!(configured_seed(&with(" ")), Ok(None));
+        assert_eq!(configured_seed(&with("42")), Ok(Some(42)));
+        assert!(configured_seed(&with("random")).unwrap().is_some());
+        assert!(configured_seed(&with("4two")).unwrap_err().contains("Invalid seed setting '4two'"));
     }
 }
@@ -0,0 +1,223 @@
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::blobs;
+use crate::db;
+use crate::lineage::RunParent;
+use crate::llm::ChatMessage;
+use crate::workflows::plan::{continue_plan, generate_plan_linked, PlanContinuation};
+use crate::workflows::verify::{verify_task, VerifyOptions};
+
+#[derive(Debug, Serialize, Type)]
+pub struct RerunResult {
+    /// The new run
+    pub run_id: String,
+    pub original_run_id: String,
+    pub run_type: String,
+    pub seed: i64,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct RegenerateResult {
+    /// The new run
+    pub run_id: String,
+    pub original_run_id: String,
+    pub message_id: String,
+    /// Messages carried over from the original run
+    pub kept_messages: usize,
+}
+
+/// A persisted message of a run, as it was sent to the model
+struct StoredMessage {
+    id: String,
+    message: ChatMessage,
+}
+
+/// Repeat a plan or verify run with the seed it was sampled with, to tell
+/// nondeterministic output apart from changes in the repo or prompt
+pub async fn rerun_with_same_seed(app: &AppHandle, run_id: &str) -> Result<RerunResult, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let (project_id, task_id, run_type, seed, options_json): (String, String, String, Option<i64>, Option<String>) = conn.query_row(
+        "SELECT t.project_id, r.task_id, r.run_type, r.seed, r.options_json
+         FROM runs r JOIN tasks t ON t.id = r.task_id WHERE r.id = ?1",
+        [run_id],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?))
+    ).map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("Run {} not found", run_id),
+        e => e.to_string(),
+    })?;
+    let seed = seed.ok_or_else(|| format!(
+        "Run {} was made without a seed; set the seed setting (or 'random') to make runs replayable",
+        run_id
+    ))?;
+
+    let new_run_id = match run_type.as_str() {
+        "plan" => generate_plan_linked(app.clone(), project_id, task_id, Some(seed), RunParent::new(run_id, "rerun")).await
+            .map_err(|e| format!("[{}] {}", e.code, e.message))?
+            .run_id,
+        "verify" => {
+            // Replay the original checks so the two reports are comparable
+            let options_json = options_json.ok_or_else(|| format!(
+                "Run {} was made before verify options were recorded and can't be replayed",
+                run_id
+            ))?;
+            let mut options: VerifyOptions = serde_json::from_str(&options_json).map_err(|e| e.to_string())?;
+            options.seed = Some(seed);
+            // A cached report would just repeat the original instead of sampling again
+            options.bypass_cache = true;
+            options.parent = Some(RunParent::new(run_id, "rerun"));
+            verify_task(app.clone(), project_id, task_id, options).await
+                .map_err(|e| format!("[{}] {}", e.code, e.message))?
+                .run_id
+        }
+        other => return Err(format!("Cannot rerun a {} run", other)),
+    };
+
+    tracing::info!(original_run_id = run_id, run_id = %new_run_id, seed, "reran with same seed");
+    Ok(RerunResult { run_id: new_run_id, original_run_id: run_id.to_string(), run_type, seed })
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// Link a run to the run and message it continued from
+pub fn record_regeneration(app: &AppHandle, run_id: &str, parent_run_id: &str, message_id: &str) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO run_regenerations (run_id, parent_run_id, message_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+        (run_id, parent_run_id, message_id, now_iso())
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn load_messages(app: &AppHandle, run_id: &str) -> Result<Vec<StoredMessage>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT id, role, content, content_hash, tool_calls_json, tool_call_id FROM messages
+         WHERE run_id = ?1 AND role != 'hook' ORDER BY created_at ASC, rowid ASC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([run_id], |r| {
+        Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, blobs::hydrate(&conn, r.get(2)?, r.get(3)?)?, r.get::<_, Option<String>>(4)?, r.get(5)?))
+    }).map_err(|e| e.to_string())?;
+
+    let mut out = vec![];
+    for row in rows {
+        let (id, role, content, tool_calls_json, tool_call_id) = row.map_err(|e| e.to_string())?;
+        let tool_calls = tool_calls_json.map(|json| serde_json::from_str(&json)).transpose().map_err(|e| e.to_string())?;
+        out.push(StoredMessage { id, message: ChatMessage { role, content: Some(content), tool_calls, tool_call_id } });
+    }
+    Ok(out)
+}
+
+/// The conversation up to and including the assistant message, along with the
+/// results of the tools it called. Everything the model said after is dropped.
+fn conversation_until(messages: Vec<StoredMessage>, message_id: &str) -> Result<Vec<ChatMessage>, String> {
+    let index = messages.iter()
+        .position(|m| m.id == message_id)
+        .ok_or_else(|| format!("Message {} is not part of this run", message_id))?;
+    let chosen = &messages[index].message;
+    if chosen.role != "assistant" {
+        return Err("Runs can only be regenerated from an assistant message".to_string());
+    }
+    let end = index + 1 + messages[index + 1..].iter().take_while(|m| m.message.role == "tool").count();
+    let Some(tool_calls) = chosen.tool_calls.clone() else {
+        return Err(if end > index + 1 {
+            "This run was recorded before messages kept their tool calls and can't be replayed".to_string()
+        } else {
+            format!("Message {} is the run's final answer; regenerate from an earlier assistant message", message_id)
+        });
+    };
+
+    let kept: Vec<ChatMessage> = messages.into_iter().take(end).map(|m| m.message).collect();
+    if kept.iter().any(|m| m.role == "tool" && m.tool_call_id.is_none()) {
+        return Err("This run was recorded before messages kept their tool calls and can't be replayed".to_string());
+    }
+    let answered = |id: &str| kept[index + 1..].iter().any(|m| m.tool_call_id.as_deref() == Some(id));
+    if let Some(call) = tool_calls.iter().find(|call| !answered(&call.id)) {
+        return Err(format!("The run stopped before {} returned; regenerate from an earlier message", call.function.name));
+    }
+    Ok(kept)
+}
+
+/// Cut a plan run's conversation after one of its assistant messages and run
+/// the tool loop on from there in a new run linked to the original, so one bad
+/// turn doesn't mean starting over
+pub async fn regenerate_from_message(app: &AppHandle, run_id: &str, message_id: &str) -> Result<RegenerateResult, String> {
+    let (project_id, task_id, run_type): (String, String, String) = db::connect(app).map_err(|e| e.to_string())?.query_row(
+        "SELECT t.project_id, r.task_id, r.run_type FROM runs r JOIN tasks t ON t.id = r.task_id WHERE r.id = ?1",
+        [run_id],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+    ).map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("Run {} not found", run_id),
+        e => e.to_string(),
+    })?;
+    // Verification is a single model call, so there is no loop to pick up
+    if run_type != "plan" {
+        return Err(format!("Only plan runs can be regenerated from a message; rerun the {} run instead", run_type));
+    }
+
+    let messages = conversation_until(load_messages(app, run_id)?, message_id)?;
+    let kept_messages = messages.len();
+    let continuation = PlanContinuation {
+        messages,
+        parent_run_id: run_id.to_string(),
+        message_id: message_id.to_string(),
+    };
+    let new_run_id = continue_plan(app.clone(), project_id, task_id, continuation).await
+        .map_err(|e| format!("[{}] {}", e.code, e.message))?
+        .run_id;
+
+    tracing::info!(original_run_id = run_id, run_id = %new_run_id, message_id, "regenerated from message");
+    Ok(RegenerateResult { run_id: new_run_id, original_run_id: run_id.to_string(), message_id: message_id.to_string(), kept_messages })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{ToolCall, ToolFunction};
+
+    fn stored(id: &str, role: &str, tool_calls: Option<Vec<&str>>, tool_call_id: Option<&str>) -> StoredMessage {
+        let tool_calls = tool_calls.map(|ids| ids.into_iter().map(|id| ToolCall {
+            id: id.to_string(),
+            call_type: "function".to_string(),
+            function: ToolFunction { name: "read_file".to_string(), arguments: "{}".to_string() },
+        }).collect());
+        StoredMessage {
+            id: id.to_string(),
+            message: ChatMessage { role: role.to_string(), content: Some(id.to_string()), tool_calls, tool_call_id: tool_call_id.map(str::to_string) },
+        }
+    }
+
+    #[test]
+    fn test_conversation_until() {
+        let run = || vec![
+            stored("sys", "system", None, None),
+            stored("user", "user", None, None),
+            stored("a1", "assistant", Some(vec!["c1", "c2"]), None),
+            stored("t1", "tool", None, Some("c1")),
+            stored("t2", "tool", None, Some("c2")),
+            stored("a2", "assistant", Some(vec!["c3"]), None),
+            stored("t3", "tool", None, Some("c3")),
+            stored("final", "assistant", None, None),
+        ];
+        let kept = conversation_until(run(), "a1").unwrap();
+        let ids: Vec<&str> = kept.iter().map(|m| m.content.as_deref().unwrap()).collect();
+        assert_eq!(ids, ["sys", "user", "a1", "t1", "t2"]);
+        assert_eq!(conversation_until(run(), "a2").unwrap().len(), 7);
+
+        assert!(conversation_until(run(), "final").unwrap_err().contains("final answer"));
+        assert!(conversation_until(run(), "t1").is_err());
+        assert!(conversation_until(run(), "missing").is_err());
+
+        let mut partial = run();
+        partial.truncate(4);
+        assert!(conversation_until(partial, "a1").unwrap_err().contains("read_file"));
+
+        let legacy = vec![stored("a1", "assistant", None, None), stored("t1", "tool", None, None)];
+        assert!(conversation_until(legacy, "a1").unwrap_err().contains("can't be replayed"));
+    }
+}
@@ -0,0 +1,440 @@
+use rusqlite::Connection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::compression::{encode, StoredText};
+use crate::db;
+use crate::llm::embeddings::{embed, EmbeddingConfig};
+use crate::models::*;
+use crate::repo_tools::context_ignore::{load_config, matcher, project_globs, ContextIgnore};
+use crate::repo_tools::safety::{safe_spawn, sanitize_path};
+use crate::settings::{AppSettings, SettingsMap};
+use crate::workflows::budget::{check_run_budget, record_project_usage, record_usage};
+use crate::workflows::sanitize::wrap_untrusted;
+
+const CHUNK_LINES: usize = 60;
+const CHUNK_CHARS: usize = 4_000;
+const MAX_FILE_BYTES: usize = 256 * 1024;
+/// Chunks beyond this are left out of the index
+const MAX_INDEX_CHUNKS: usize = 20_000;
+const EMBED_BATCH: usize = 64;
+/// Files that usually hold credentials, never sent to the embeddings provider
+const SECRET_GLOBS: &[&str] = &[
+    ".env",
+    ".env.*",
+    "*.pem",
+    "*.key",
+    "*.p12",
+    "*.pfx",
+    "*.jks",
+    "*.keystore",
+    "id_rsa*",
+    "id_dsa*",
+    "id_ecdsa*",
+    "id_ed25519*",
+    ".npmrc",
+    ".pypirc",
+    ".netrc",
+    ".git-credentials",
+    "*.tfvars",
+    "credentials.json",
+    "secrets.*",
+];
+
+/// How the planner gathers context before writing the plan
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlanningStrategy {
+    /// Explore with repo tools from the first step
+    Explore,
+    /// Start from the embeddings index's top-k chunks, then use tools for follow-ups
+    Retrieval,
+}
+
+impl PlanningStrategy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "explore" => Some(PlanningStrategy::Explore),
+            "retrieval" => Some(PlanningStrategy::Retrieval),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct IndexReport {
+    pub files_indexed: usize,
+    pub files_unchanged: usize,
+    /// Binary, oversized, or unreadable files
+    pub files_skipped: usize,
+    pub files_removed: usize,
+    pub chunks: usize,
+    /// The chunk limit was reached and some files were left out
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct IndexStatus {
+    pub files: i64,
+    pub chunks: i64,
+    pub model: Option<String>,
+    pub indexed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Chunk {
+    start_line: usize,
+    end_line: usize,
+    content: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetrievedChunk {
+    pub path: String,
+    pub start_line: i64,
+    pub end_line: i64,
+    pub content: String,
+    pub score: f32,
+}
+
+/// The project's `planning_strategy` override, else the global setting
+pub fn planning_strategy(app: &AppHandle, settings: &SettingsMap, project_id: &str) -> Result<PlanningStrategy, String> {
+    let project_value = db::get_project_setting(app, project_id, "planning_strategy")
+        .map_err(|e| e.to_string())?;
+    Ok(project_value.as_deref()
+        .or_else(|| settings.get("planning_strategy").map(String::as_str))
+        .and_then(PlanningStrategy::parse)
+        .unwrap_or(PlanningStrategy::Explore))
+}
+
+pub fn embedding_config(settings: &SettingsMap) -> EmbeddingConfig {
+    let base_url = settings.get("embedding_base_url")
+        .filter(|v| !v.is_empty())
+        .or_else(|| settings.get("base_url"))
+        .cloned()
+        .unwrap_or_default();
+    EmbeddingConfig {
+        base_url,
+        model: settings.get("embedding_model").cloned().unwrap_or_else(|| "text-embedding-3-small".into()),
+    }
+}
+
+/// Split a file into line-aligned chunks of at most CHUNK_LINES lines and
+/// roughly CHUNK_CHARS characters
+fn chunk_lines(text: &str) -> Vec<Chunk> {
+    let mut chunks = vec![];
+    let mut current = String::new();
+    let mut start = 1;
+    let mut lines = 0;
+    for (i, line) in text.lines().enumerate() {
+        if lines > 0 && (lines == CHUNK_LINES || current.len() + line.len() > CHUNK_CHARS) {
+            chunks.push(Chunk { start_line: start, end_line: i, content: std::mem::take(&mut current) });
+            start = i + 1;
+            lines = 0;
+        }
+        let line: String = line.chars().take(CHUNK_CHARS).collect();
+        current.push_str(&line);
+        current.push('\n');
+        lines += 1;
+    }
+    if current.trim().is_empty() {
+        return chunks;
+    }
+    chunks.push(Chunk { start_line: start, end_line: start + lines - 1, content: current });
+    chunks
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
+
+/// The `k` chunks closest to `query` among those embedded with `model`
+fn search_chunks(conn: &Connection, project_id: &str, model: &str, query: &[f32], k: usize) -> Result<Vec<RetrievedChunk>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT path, start_line, end_line, content, embedding FROM repo_chunks WHERE project_id = ?1 AND model = ?2"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map((project_id, model), |r| {
+        let embedding: Vec<u8> = r.get(4)?;
+        Ok(RetrievedChunk {
+            path: r.get(0)?,
+            start_line: r.get(1)?,
+            end_line: r.get(2)?,
+            content: r.get::<_, StoredText>(3)?.0,
+            score: cosine(query, &decode_vector(&embedding)),
+        })
+    }).map_err(|e| e.to_string())?;
+    let mut chunks = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    chunks.sort_by(|a, b| b.score.total_cmp(&a.score));
+    chunks.truncate(k);
+    Ok(chunks)
+}
+
+fn project_repo_path(conn: &Connection, project_id: &str) -> Result<String, String> {
+    conn.query_row("SELECT repo_path FROM projects WHERE id = ?1", [project_id], |r| r.get(0))
+        .map_err(|e| format!("Project {} not found: {}", project_id, e))
+}
+
+/// The project's context ignore globs and .spectrail.toml, plus secret files
+async fn index_ignore(app: &AppHandle, project_id: &str, repo_path: &Path) -> Result<ContextIgnore, String> {
+    let mut globs = project_globs(app, project_id).await?;
+    globs.extend(SECRET_GLOBS.iter().map(|g| g.to_string()));
+    matcher(&load_config(globs, repo_path).await, &[])
+}
+
+/// Build or refresh the project's embeddings index. Files whose content and
+/// embedding model are unchanged since the last run are skipped, and ignored
+/// files are left out, or dropped when they were indexed before.
+pub async fn index_project(app: &AppHandle, settings: &SettingsMap, api_key: &str, project_id: &str) -> Result<IndexReport, String> {
+    check_run_budget(app, settings, project_id).map_err(|b| b.message)?;
+    let config = embedding_config(settings);
+    let provider = settings.get("provider_name").cloned().unwrap_or_default();
+    let repo_path = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        project_repo_path(&conn, project_id)?
+    };
+    let repo_path = Path::new(&repo_path);
+
+    let (stdout, stderr, code) = safe_spawn("git", &["ls-files", "-z"], repo_path, 30)
+        .await
+        .map_err(|e| e.to_string())?;
+    if code != 0 {
+        return Err(format!("Indexing needs a git repository: {}", stderr.trim()));
+    }
+    let ignore = index_ignore(app, project_id, repo_path).await?;
+    let tracked: Vec<&str> = stdout.split('\0').filter(|p| !p.is_empty() && !ignore.is_ignored(p)).collect();
+
+    let mut report = IndexReport {
+        files_indexed: 0, files_unchanged: 0, files_skipped: 0, files_removed: 0, chunks: 0, truncated: false,
+    };
+    let pending = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        stale_chunks(&conn, repo_path, project_id, &config.model, &tracked, &mut report)?
+    };
+
+    for batch in pending.chunks(EMBED_BATCH) {
+        let inputs: Vec<String> = batch.iter()
+            .map(|(path, _, chunk)| format!("{}\n{}", path, chunk.content))
+            .collect();
+        let (vectors, usage) = embed(&config, api_key, &inputs).await.map_err(|e| e.to_string())?;
+        record_project_usage(app, settings, project_id, "index_repository", &provider, &config.model, usage);
+
+        let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let indexed_at = now_iso();
+        for ((path, file_hash, chunk), vector) in batch.iter().zip(vectors) {
+            tx.execute(
+                "INSERT INTO repo_chunks (id, project_id, path, start_line, end_line, content, file_hash, model, embedding, indexed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                (
+                    new_id(), project_id, path, chunk.start_line as i64, chunk.end_line as i64, encode(&chunk.content).map_err(|e| e.to_string())?,
+                    file_hash, &config.model, encode_vector(&vector), &indexed_at,
+                )
+            ).map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        report.chunks += batch.len();
+    }
+    Ok(report)
+}
+
+/// Drop chunks of files that are gone or changed, returning the changed
+/// files' new chunks as (path, file hash, chunk). A failed run leaves those
+/// files unindexed, so the next run picks them up again.
+fn stale_chunks(
+    conn: &Connection,
+    repo_path: &Path,
+    project_id: &str,
+    model: &str,
+    tracked: &[&str],
+    report: &mut IndexReport,
+) -> Result<Vec<(String, String, Chunk)>, String> {
+    let mut indexed: HashMap<String, (String, String)> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT DISTINCT path, file_hash, model FROM repo_chunks WHERE project_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([project_id], |r| Ok((r.get::<_, String>(0)?, (r.get(1)?, r.get(2)?))))
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (path, entry) = row.map_err(|e| e.to_string())?;
+        indexed.insert(path, entry);
+    }
+
+    let tracked_set: HashSet<&str> = tracked.iter().copied().collect();
+    for path in indexed.keys().filter(|p| !tracked_set.contains(p.as_str())) {
+        conn.execute("DELETE FROM repo_chunks WHERE project_id = ?1 AND path = ?2", (project_id, path))
+            .map_err(|e| e.to_string())?;
+        report.files_removed += 1;
+    }
+
+    let mut pending = vec![];
+    let mut total_chunks = 0;
+    for &path in tracked {
+        let content = match sanitize_path(repo_path, path).map(std::fs::read) {
+            Ok(Ok(content)) if content.len() <= MAX_FILE_BYTES => content,
+            _ => {
+                report.files_skipped += 1;
+                continue;
+            }
+        };
+        let is_binary = content.iter().any(|&b| b == 0 || (b < 32 && b != 9 && b != 10 && b != 13));
+        let text = match String::from_utf8(content) {
+            Ok(text) if !is_binary => text,
+            _ => {
+                report.files_skipped += 1;
+                continue;
+            }
+        };
+
+        let file_hash = format!("{:x}", Sha256::digest(text.as_bytes()));
+        let chunks = chunk_lines(&text);
+        if total_chunks + chunks.len() > MAX_INDEX_CHUNKS {
+            report.truncated = true;
+            break;
+        }
+        total_chunks += chunks.len();
+        if indexed.get(path).is_some_and(|(hash, indexed_model)| *hash == file_hash && indexed_model == model) {
+            report.files_unchanged += 1;
+            continue;
+        }
+
+        conn.execute("DELETE FROM repo_chunks WHERE project_id = ?1 AND path = ?2", (project_id, path))
+            .map_err(|e| e.to_string())?;
+        report.files_indexed += 1;
+        pending.extend(chunks.into_iter().map(|chunk| (path.to_string(), file_hash.clone(), chunk)));
+    }
+    Ok(pending)
+}
+
+pub fn index_status(app: &AppHandle, project_id: &str) -> Result<IndexStatus, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT COUNT(DISTINCT path), COUNT(*), MAX(model), MAX(indexed_at) FROM repo_chunks WHERE project_id = ?1",
+        [project_id],
+        |r| Ok(IndexStatus { files: r.get(0)?, chunks: r.get(1)?, model: r.get(2)?, indexed_at: r.get(3)? })
+    ).map_err(|e| e.to_string())
+}
+
+/// Top-k chunks for the task as a prompt section, or None when the project
+/// has no index for the configured embedding model. The query's usage is
+/// charged to the run.
+pub async fn retrieved_context(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    api_key: &str,
+    run_id: &str,
+    project_id: &str,
+    task: &Task,
+) -> Result<Option<String>, String> {
+    let config = embedding_config(settings);
+    let k = AppSettings::from_map(settings).retrieval_top_k;
+    let query = match task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(description) => format!("{}\n\n{}", task.title, description),
+        None => task.title.clone(),
+    };
+
+    let indexed: bool = db::connect(app).map_err(|e| e.to_string())?.query_row(
+        "SELECT EXISTS(SELECT 1 FROM repo_chunks WHERE project_id = ?1 AND model = ?2)",
+        (project_id, &config.model),
+        |r| r.get(0)
+    ).map_err(|e| e.to_string())?;
+    if !indexed {
+        return Ok(None);
+    }
+
+    let (mut vectors, usage) = embed(&config, api_key, &[query]).await.map_err(|e| e.to_string())?;
+    record_usage(app, settings, run_id, usage);
+    let query_vector = vectors.pop().unwrap_or_default();
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let chunks = search_chunks(&conn, project_id, &config.model, &query_vector, k)?;
+    Ok(format_chunks(&chunks))
+}
+
+fn format_chunks(chunks: &[RetrievedChunk]) -> Option<String> {
+    if chunks.is_empty() {
+        return None;
+    }
+    let mut section = String::from(
+        "## Retrieved Code\n\nThese chunks were retrieved from the repository's embeddings index as the closest matches \
+         to the task. Plan from them first and use tools only for targeted follow-ups.\n\n"
+    );
+    for chunk in chunks {
+        let body = format!("```\n{}\n```", chunk.content.trim_end());
+        section.push_str(&format!("### {}:{}-{}\n\n", chunk.path, chunk.start_line, chunk.end_line));
+        section.push_str(&wrap_untrusted("retrieval", &body));
+        section.push_str("\n\n");
+    }
+    Some(section)
+}
+
+fn now_iso() -> String {
+    let t = time::OffsetDateTime::now_utc();
+    t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunking_and_search() {
+        let text: String = (1..=130).map(|i| format!("line {}\n", i)).collect();
+        let chunks = chunk_lines(&text);
+        let ranges: Vec<(usize, usize)> = chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(ranges, vec![(1, 60), (61, 120), (121, 130)]);
+        assert!(chunks[2].content.starts_with("line 121\n"));
+        assert!(chunk_lines("\n\n").is_empty());
+
+        let vector = vec![0.5, -1.25, 3.0];
+        assert_eq!(decode_vector(&encode_vector(&vector)), vector);
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE projects (id TEXT PRIMARY KEY);
+             CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at TEXT NOT NULL);
+             INSERT INTO projects VALUES ('p');"
+        ).unwrap();
+        conn.execute_batch(include_str!("../../migrations/035_embeddings.sql")).unwrap();
+        for (id, path, vector) in [("c1", "a.rs", [1.0, 0.0]), ("c2", "b.rs", [0.6, 0.8]), ("c3", "c.rs", [0.0, 1.0])] {
+            conn.execute(
+                "INSERT INTO repo_chunks VALUES (?1, 'p', ?2, 1, 10, 'fn x() {}', 'h', 'm', ?3, 'now')",
+                (id, path, encode_vector(&vector)),
+            ).unwrap();
+        }
+        let found = search_chunks(&conn, "p", "m", &[0.0, 2.0], 2).unwrap();
+        let paths: Vec<&str> = found.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["c.rs", "b.rs"]);
+        assert!(search_chunks(&conn, "p", "other-model", &[0.0, 2.0], 2).unwrap().is_empty());
+
+        let section = format_chunks(&found).unwrap();
+        assert!(section.contains("### c.rs:1-10"));
+        assert!(format_chunks(&[]).is_none());
+        assert_eq!(PlanningStrategy::parse("retrieval"), Some(PlanningStrategy::Retrieval));
+    }
+
+    #[test]
+    fn test_secret_files_are_not_indexed() {
+        let globs: Vec<String> = SECRET_GLOBS.iter().map(|g| g.to_string()).collect();
+        let ignore = ContextIgnore::new(&globs).unwrap();
+        for path in [".env", "config/.env.production", "certs/server.pem", "deploy/prod.tfvars", "home/.ssh/id_ed25519"] {
+            assert!(ignore.is_ignored(path), "{}", path);
+        }
+        for path in ["src/env.rs", "src/keys.rs", "docs/secrets-handling.md"] {
+            assert!(!ignore.is_ignored(path), "{}", path);
+        }
+    }
+}
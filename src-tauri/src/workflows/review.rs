@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::llm::ChatMessage;
+use crate::repo_tools::git::check_ref;
+use crate::settings::SettingsMap;
+use crate::workflows::engine::{Gathered, HookFuture, Workflow, WorkflowEngine, WorkflowMode, WorkflowRun};
+use crate::workflows::findings::Severity;
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+
+pub const REVIEW_KIND: &str = "review_md";
+const MAX_ITERATIONS: usize = 10;
+const REVIEW_TOOLS: &[&str] = &["read_file", "grep", "list_files", "git_diff", "git_log_short"];
+
+const SYSTEM_PROMPT: &str = r#"You are a senior engineer reviewing a change to the repository. The diff is below; use the tools to read the code around it, find callers, and check history before judging it.
+
+Review for correctness, security, error handling, performance and missing tests. Comment only on what the change does or breaks, not on code it leaves alone. Each comment names the file, the line in the new version where it applies (null for the file as a whole), a severity, and what to change:
+- high: a bug, security hole or data loss the change must not ship with
+- medium: a problem worth fixing before merging
+- low: a suggestion or nit
+
+The verdict is approve (nothing above low), request_changes (anything high), or comment.
+
+When you're done, respond with only a JSON object, no prose and no code fence:
+{"summary": "...", "verdict": "approve", "files": [{"path": "src/a.rs", "comments": [{"line": 12, "severity": "medium", "comment": "..."}]}]}"#;
+
+/// What to review: the branch against a base, the staged changes, or
+/// (with neither) the working tree's unstaged changes
+#[derive(Debug, Clone, Default, Deserialize, Type)]
+pub struct ReviewOptions {
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub staged: bool,
+}
+
+impl ReviewOptions {
+    fn diff_args(&self) -> Value {
+        match self.base.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+            Some(base) => json!({ "base": base }),
+            None => json!({ "staged": self.staged }),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.base.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+            Some(base) => format!("the current branch's changes since it diverged from `{}`", base),
+            None if self.staged => "the staged changes".to_string(),
+            None => "the unstaged changes in the working tree".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVerdict {
+    Approve,
+    RequestChanges,
+    Comment,
+}
+
+impl ReviewVerdict {
+    fn label(&self) -> &'static str {
+        match self {
+            ReviewVerdict::Approve => "Approve",
+            ReviewVerdict::RequestChanges => "Changes requested",
+            ReviewVerdict::Comment => "Comment",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReviewedFile {
+    path: String,
+    #[serde(default)]
+    comments: Vec<FileComment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FileComment {
+    #[serde(default)]
+    line: Option<u32>,
+    severity: Severity,
+    comment: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewOutput {
+    #[serde(default)]
+    summary: String,
+    verdict: ReviewVerdict,
+    #[serde(default)]
+    files: Vec<ReviewedFile>,
+}
+
+/// A review comment, as stored from the model's answer
+#[derive(Debug, Clone, Serialize, PartialEq, Type)]
+pub struct ReviewComment {
+    pub file: String,
+    pub line: Option<u32>,
+    pub severity: Severity,
+    pub comment: String,
+}
+
+/// The review read from the model's answer
+#[derive(Debug, Default)]
+pub struct Review {
+    pub summary: String,
+    pub verdict: Option<ReviewVerdict>,
+    pub comments: Vec<ReviewComment>,
+}
+
+/// Read the model's review from its answer, tolerating a code fence or text around the JSON
+pub fn parse_review(output: &str) -> Result<Review, String> {
+    let start = output.find('{').ok_or("The model's answer has no JSON review")?;
+    let end = output.rfind('}').filter(|end| *end > start).ok_or("The model's answer has no JSON review")?;
+    let parsed: ReviewOutput = serde_json::from_str(&output[start..=end])
+        .map_err(|e| format!("The model's review isn't valid JSON: {}", e))?;
+
+    let mut comments = vec![];
+    for file in parsed.files {
+        let path = file.path.trim().trim_matches('`');
+        if path.is_empty() {
+            return Err("The review has a file without a path".into());
+        }
+        for c in file.comments.into_iter().filter(|c| !c.comment.trim().is_empty()) {
+            comments.push(ReviewComment {
+                file: path.to_string(),
+                line: c.line.filter(|line| *line > 0),
+                severity: c.severity,
+                comment: c.comment.trim().to_string(),
+            });
+        }
+    }
+    Ok(Review { summary: parsed.summary.trim().to_string(), verdict: Some(parsed.verdict), comments })
+}
+
+/// The review artifact: the summary, then the comments grouped by file
+pub fn render_review(review: &Review, target: &str) -> String {
+    let count = |severity: Severity| review.comments.iter().filter(|c| c.severity == severity).count();
+    let mut md = format!("# Code review\n\nReviewed {}.\n\n", target);
+    if let Some(verdict) = review.verdict {
+        md.push_str(&format!(
+            "**Verdict:** {} · {} high, {} medium, {} low\n",
+            verdict.label(), count(Severity::High), count(Severity::Medium), count(Severity::Low)
+        ));
+    }
+    if !review.summary.is_empty() {
+        md.push_str(&format!("\n## Summary\n\n{}\n", review.summary));
+    }
+
+    md.push_str("\n## Files\n");
+    if review.comments.is_empty() {
+        md.push_str("\nNo comments.\n");
+    }
+    let mut files: Vec<&str> = vec![];
+    for comment in &review.comments {
+        if !files.contains(&comment.file.as_str()) {
+            files.push(&comment.file);
+        }
+    }
+    for file in files {
+        md.push_str(&format!("\n### `{}`\n\n", file));
+        for c in review.comments.iter().filter(|c| c.file == file) {
+            let severity = match c.severity {
+                Severity::High => "High",
+                Severity::Medium => "Medium",
+                Severity::Low => "Low",
+            };
+            match c.line {
+                Some(line) => md.push_str(&format!("- **{}** (line {}): {}\n", severity, line, c.comment)),
+                None => md.push_str(&format!("- **{}**: {}\n", severity, c.comment)),
+            }
+        }
+    }
+    md
+}
+
+/// A review of a diff with the repo tool loop, as a workflow definition. The
+/// task gives the review its purpose; it doesn't need a plan.
+struct CodeReview<'a> {
+    options: &'a ReviewOptions,
+    tools: Vec<String>,
+}
+
+impl Workflow for CodeReview<'_> {
+    type Context = Review;
+
+    fn run_type(&self) -> &str {
+        "review"
+    }
+
+    fn artifact_kind(&self) -> &str {
+        REVIEW_KIND
+    }
+
+    fn mode(&self) -> WorkflowMode {
+        WorkflowMode::AgentLoop { max_iterations: MAX_ITERATIONS }
+    }
+
+    fn allowed_tools(&self) -> Option<&[String]> {
+        Some(&self.tools)
+    }
+
+    fn gather_context<'a>(&'a self, run: &'a WorkflowRun<'a>) -> HookFuture<'a, Gathered<Review>> {
+        Box::pin(async move {
+            // Fetched before calling the model, so an empty diff costs nothing
+            let result = run.tool("git_diff", self.options.diff_args()).await?;
+            let diff = result["diff"].as_str().unwrap_or_default();
+            if diff.trim().is_empty() {
+                let stderr = result["stderr"].as_str().unwrap_or_default().trim();
+                return Err(match stderr.is_empty() {
+                    true => format!("Nothing to review: {} are empty", self.options.describe()),
+                    false => format!("Failed to get the diff: {}", stderr),
+                });
+            }
+            let label = if result["truncated"].as_bool() == Some(true) { "Diff (truncated; use git_diff with paths for the rest)" } else { "Diff" };
+
+            let mut user_prompt = format!("# Review {}\n\nTask: {}\n", self.options.describe(), run.task.title);
+            if let Some(description) = run.task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+                user_prompt.push_str(&format!("\n{}\n", wrap_untrusted("task description", description)));
+            }
+            user_prompt.push_str(&format!("\n## {}\n{}\n", label, wrap_untrusted("git diff", diff)));
+            Ok(Gathered {
+                messages: vec![
+                    ChatMessage { role: "system".into(), content: Some(format!("{}{}", SYSTEM_PROMPT, UNTRUSTED_CONTENT_REMINDER)), tool_call_id: None, tool_calls: None },
+                    ChatMessage { role: "user".into(), content: Some(user_prompt), tool_call_id: None, tool_calls: None },
+                ],
+                context: Review::default(),
+            })
+        })
+    }
+
+    fn post_process(&self, _run: &WorkflowRun, context: &mut Review, output: String) -> Result<String, String> {
+        *context = parse_review(&output)?;
+        Ok(render_review(context, &self.options.describe()))
+    }
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct ReviewResult {
+    pub run_id: String,
+    pub artifact_id: String,
+    pub review_md: String,
+    pub verdict: Option<ReviewVerdict>,
+    pub comments: Vec<ReviewComment>,
+    pub tool_calls_count: usize,
+    pub forced_completion: bool,
+}
+
+/// Review a branch or the working tree's changes for the task, saving the
+/// review as its `review_md` artifact
+pub async fn review_changes(
+    app: &AppHandle,
+    settings: &SettingsMap,
+    api_key: &str,
+    task_id: &str,
+    options: &ReviewOptions,
+) -> Result<ReviewResult, String> {
+    if let Some(base) = options.base.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+        check_ref(base)?;
+    }
+    let workflow = CodeReview { options, tools: REVIEW_TOOLS.iter().map(|t| t.to_string()).collect() };
+    let output = WorkflowEngine::new(app, settings, api_key).run(&workflow, task_id).await?;
+    tracing::info!(comments = output.context.comments.len(), tool_calls = output.tool_calls_count, "review finished");
+    Ok(ReviewResult {
+        run_id: output.run_id,
+        artifact_id: output.artifact_id,
+        review_md: output.content,
+        verdict: output.context.verdict,
+        comments: output.context.comments,
+        tool_calls_count: output.tool_calls_count,
+        forced_completion: output.forced_completion,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_review() {
+        let output = r#"Here it is:
+```json
+{"summary": "Adds login.", "verdict": "request_changes", "files": [
+  {"path": "`src/auth.rs`", "comments": [
+    {"line": 42, "severity": "high", "comment": "Password is logged."},
+    {"line": null, "severity": "low", "comment": "Add a module doc."},
+    {"line": 3, "severity": "medium", "comment": "  "}
+  ]},
+  {"path": "src/db.rs", "comments": [{"line": 0, "severity": "medium", "comment": "Query isn't parameterized."}]}
+]}
+```"#;
+        let review = parse_review(output).unwrap();
+        assert_eq!(review.verdict, Some(ReviewVerdict::RequestChanges));
+        assert_eq!(review.comments.len(), 3);
+        assert_eq!(review.comments[0], ReviewComment {
+            file: "src/auth.rs".into(),
+            line: Some(42),
+            severity: Severity::High,
+            comment: "Password is logged.".into(),
+        });
+        assert_eq!(review.comments[2].line, None);
+
+        let md = render_review(&review, "the staged changes");
+        assert!(md.contains("**Verdict:** Changes requested · 1 high, 1 medium, 1 low"));
+        assert!(md.contains("### `src/auth.rs`\n\n- **High** (line 42): Password is logged.\n- **Low**: Add a module doc."));
+
+        assert!(parse_review("Looks good to me").is_err());
+        assert!(parse_review(r#"{"verdict": "ship_it"}"#).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use ignore::WalkBuilder;
+use serde::Serialize;
+use specta::Type;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::blobs;
+use crate::db;
+use crate::models::*;
+use crate::workflows::attachments::attachments_context;
+
+const REPO_MAP_MAX_FILES: usize = 500;
+
+/// Variables a template may reference
+pub const TEMPLATE_VARIABLES: &[&str] = &[
+    "task.title",
+    "task.description",
+    "task.mode",
+    "project.name",
+    "project.repo_path",
+    "repo_map",
+    "plan",
+    "attachments",
+];
+
+#[derive(Debug, Serialize)]
+pub struct RenderedTemplate {
+    pub text: String,
+    pub used_variables: Vec<String>,
+    pub unknown_variables: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct PromptPreview {
+    pub workflow: String,
+    pub template: Option<String>,
+    pub rendered: String,
+    pub used_variables: Vec<String>,
+    pub unknown_variables: Vec<String>,
+    pub strict: bool,
+}
+
+pub fn template_setting_key(workflow: &str) -> Result<&'static str, String> {
+    match workflow {
+        "plan" => Ok("prompt_template_plan"),
+        "verify" => Ok("prompt_template_verify"),
+        _ => Err(format!("Unknown workflow '{}' (expected plan or verify)", workflow)),
+    }
+}
+
+/// Replace `{{name}}` placeholders; unknown names are left in place and reported
+pub fn render_template(template: &str, vars: &BTreeMap<String, String>) -> RenderedTemplate {
+    let mut text = String::with_capacity(template.len());
+    let mut used = vec![];
+    let mut unknown = vec![];
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            text.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        let name = after[..end].trim();
+        match vars.get(name) {
+            Some(value) => {
+                text.push_str(value);
+                if !used.iter().any(|u| u == name) {
+                    used.push(name.to_string());
+                }
+            }
+            None => {
+                text.push_str(&rest[start..start + 2 + end + 2]);
+                if !unknown.iter().any(|u| u == name) {
+                    unknown.push(name.to_string());
+                }
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    text.push_str(rest);
+
+    RenderedTemplate { text, used_variables: used, unknown_variables: unknown }
+}
+
+/// Gather variable values for a task. Expensive values (repo map, plan,
+/// attachments) are only loaded when the template references them.
+pub fn template_vars(
+    app: &AppHandle,
+    task: &Task,
+    project: &Project,
+    template: &str,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut vars = BTreeMap::new();
+    vars.insert("task.title".to_string(), task.title.clone());
+    vars.insert("task.description".to_string(), task.description.clone().unwrap_or_default());
+    vars.insert("task.mode".to_string(), task.mode.clone());
+    vars.insert("project.name".to_string(), project.name.clone());
+    vars.insert("project.repo_path".to_string(), project.repo_path.clone());
+
+    let references = |name: &str| {
+        render_template(template, &BTreeMap::new()).unknown_variables.iter().any(|v| v == name)
+    };
+    if references("repo_map") {
+        vars.insert("repo_map".to_string(), build_repo_map(Path::new(&project.repo_path), REPO_MAP_MAX_FILES));
+    }
+    if references("plan") {
+        vars.insert("plan".to_string(), load_plan(app, &task.id)?.unwrap_or_default());
+    }
+    if references("attachments") {
+        vars.insert("attachments".to_string(), attachments_context(app, &task.id)?.unwrap_or_default());
+    }
+
+    Ok(vars)
+}
+
+/// Render the user's custom template for a workflow, if one is configured.
+/// In strict mode unknown variables are an error.
+pub fn render_custom_prompt(
+    app: &AppHandle,
+    settings: &HashMap<String, String>,
+    workflow: &str,
+    task: &Task,
+    project: &Project,
+) -> Result<Option<String>, String> {
+    let key = template_setting_key(workflow)?;
+    let Some(template) = settings.get(key).filter(|t| !t.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let vars = template_vars(app, task, project, template)?;
+    let rendered = render_template(template, &vars);
+    let strict = settings.get("prompt_template_strict").map_or(true, |v| v == "1");
+    if strict && !rendered.unknown_variables.is_empty() {
+        return Err(format!(
+            "Unknown template variable(s) in {}: {}. Available: {}",
+            key,
+            rendered.unknown_variables.join(", "),
+            TEMPLATE_VARIABLES.join(", ")
+        ));
+    }
+
+    Ok(Some(rendered.text))
+}
+
+/// Compact newline-separated file listing, respecting .gitignore
+pub fn build_repo_map(repo_path: &Path, max_files: usize) -> String {
+    let walker = WalkBuilder::new(repo_path)
+        .hidden(false)
+        .git_ignore(true)
+        .filter_entry(|e| {
+            let name = e.file_name().to_str().unwrap_or("");
+            !matches!(name, ".git" | "node_modules" | "target" | "dist" | "build" | ".next" | "__pycache__" | ".venv" | "venv")
+        })
+        .build();
+
+    let mut files = vec![];
+    for entry in walker.flatten() {
+        if entry.file_type().map_or(false, |ft| ft.is_file()) {
+            if files.len() >= max_files {
+                files.push(format!("... (truncated at {} files)", max_files));
+                break;
+            }
+            files.push(
+                entry.path()
+                    .strip_prefix(repo_path)
+                    .unwrap_or(entry.path())
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            );
+        }
+    }
+    files.join("\n")
+}
+
+fn load_plan(app: &AppHandle, task_id: &str) -> Result<Option<String>, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    let result = conn.query_row(
+        "SELECT content, content_hash FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = 'plan_md' AND draft = 0 ORDER BY created_at DESC LIMIT 1",
+        [task_id],
+        |r| blobs::hydrate(&conn, r.get(0)?, r.get(1)?)
+    );
+    match result {
+        Ok(content) => Ok(Some(content)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let mut vars = BTreeMap::new();
+        vars.insert("task.title".to_string(), "Add login".to_string());
+
+        let rendered = render_template("Task: {{ task.title }} / {{task.title}} {{repo_mapp}}", &vars);
+        assert_eq!(rendered.text, "Task: Add login / Add login {{repo_mapp}}");
+        assert_eq!(rendered.used_variables, vec!["task.title"]);
+        assert_eq!(rendered.unknown_variables, vec!["repo_mapp"]);
+    }
+
+    #[test]
+    fn test_render_template_unclosed() {
+        let rendered = render_template("Hello {{task.title", &BTreeMap::new());
+        assert_eq!(rendered.text, "Hello {{task.title");
+        assert!(rendered.unknown_variables.is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use tauri::AppHandle;
+
+use crate::events::{Event, TokenEvent};
+use crate::windows::emit_to_subscribers;
+
+/// Forwards a run's streamed output to the windows following its task so
+/// reports render as they're generated. Best-effort: a window that's gone
+/// doesn't stop the run.
+pub struct TokenEmitter {
+    app: AppHandle,
+    event: Event<TokenEvent>,
+    run_id: String,
+    project_id: String,
+    task_id: String,
+    generation: u32,
+}
+
+impl TokenEmitter {
+    pub fn new(app: &AppHandle, event: Event<TokenEvent>, run_id: &str, project_id: &str, task_id: &str) -> Self {
+        Self {
+            app: app.clone(),
+            event,
+            run_id: run_id.to_string(),
+            project_id: project_id.to_string(),
+            task_id: task_id.to_string(),
+            generation: 0,
+        }
+    }
+
+    /// Start a new response; the frontend drops what it showed of the previous one
+    pub fn next_generation(&mut self) {
+        self.generation += 1;
+    }
+
+    pub fn push(&self, delta: &str) {
+        if delta.is_empty() {
+            return;
+        }
+        let event = TokenEvent {
+            run_id: self.run_id.clone(),
+            task_id: self.task_id.clone(),
+            generation: self.generation,
+            delta: delta.to_string(),
+        };
+        emit_to_subscribers(&self.app, self.event, &self.project_id, &self.task_id, event);
+    }
+}
@@ -1,28 +1,97 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use specta::Type;
 use tauri::AppHandle;
 use std::collections::HashMap;
 use std::path::Path;
+use std::time::Instant;
 
+use crate::blobs;
+use crate::cancellation;
 use crate::db;
+use crate::events::{self, RunEvent, RunStatus};
+use crate::windows::emit_run_event;
+use crate::demo::{is_demo_project, DEMO_READ_ONLY_MESSAGE};
+use crate::feedback::feedback_context;
+use crate::project_artifacts::project_context;
+use crate::lineage::RunParent;
 use crate::models::*;
+use crate::offline::{self, OFFLINE_MESSAGE};
+use crate::repo_tools::bench::{format_benchmarks, BenchComparison};
 use crate::repo_tools::dispatch_repo_tool;
-use crate::llm::{LlmClient, ChatMessage, LlmConfig};
+use crate::repo_tools::logging::log_tool_call;
+use crate::repo_tools::runner::command_identity;
+use crate::repo_tools::safety::truncate_middle;
+use crate::settings::{cached_settings, SettingsMap};
+use crate::slack::notify_regression;
+use crate::tags;
+use crate::workflows::baseline::{check_result, compare, format_delta, latest_baseline, BaselineDelta};
+use crate::workflows::budget::{check_run_budget, record_usage};
+use crate::workflows::check_cache::{find_passing, remember_passing, repo_fingerprint};
+use crate::workflows::ci::{latest_ci_results, format_ci_results};
+use crate::workflows::compiler::{
+    changed_ranges, ci_failure_diagnostics, classify, format_compiler_diagnostics, parse_compiler_output,
+    parse_test_failures, CompilerDiagnostic, Origin,
+};
+use crate::workflows::drafts::DraftCheckpointer;
+use crate::workflows::engine::{finish_run, log_message, save_artifact};
+use crate::workflows::flaky::{flaky_annotation, format_flaky, known_flaky, record_test_stats, result_test_stats, TestHistory};
+use crate::workflows::hooks::{fire_hook, HookEvent};
+use crate::workflows::locale::{resolve_output_language, verify_headings, language_instruction, missing_sections};
+use crate::workflows::plan::configured_seed;
+use crate::workflows::sanitize::{wrap_untrusted, UNTRUSTED_CONTENT_REMINDER};
+use crate::workflows::templates::render_custom_prompt;
+use crate::workflows::tokens::TokenEmitter;
+use crate::llm::{LlmClient, ChatMessage, ChatOptions, LlmConfig};
 
 const MAX_CONTEXT_CHARS: usize = 100_000;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct VerifyOptions {
     #[serde(default = "default_true")]
     pub run_tests: bool,
+    /// Run the test suite this many times to catch flaky tests
+    #[serde(default)]
+    pub test_repeat: Option<u64>,
+    /// Rerun just the failed tests to tell flaky failures from real ones
+    #[serde(default)]
+    pub rerun_failed_tests: bool,
     #[serde(default)]
     pub run_lint: bool,
     #[serde(default)]
     pub run_build: bool,
+    /// Run the project's type checker (cargo check, tsc, mypy)
+    #[serde(default)]
+    pub run_typecheck: bool,
+    /// Run the project's benchmarks and compare them with the previous results
+    #[serde(default)]
+    pub run_bench: bool,
+    /// Ask the configured language server for diagnostics on the changed files
+    #[serde(default)]
+    pub run_diagnostics: bool,
     #[serde(default)]
     pub staged: bool,
     #[serde(default = "default_max")]
     pub max_tool_calls: usize,
+    /// Scope the review to one phase's planned files and store a phase report
+    #[serde(default)]
+    pub phase_id: Option<String>,
+    /// Review the task's latest ingested CI results instead of running tests locally
+    #[serde(default)]
+    pub use_ci_results: bool,
+    /// Always call the LLM, even when an identical prompt has a cached report
+    #[serde(default)]
+    pub bypass_cache: bool,
+    /// Run tests, lint and build even when they already passed on the same repository state
+    #[serde(default)]
+    pub fresh_checks: bool,
+    /// Sample with this seed instead of the configured one
+    #[serde(default)]
+    pub seed: Option<i64>,
+    /// Record the run as a rerun or retry of an earlier one
+    #[serde(default)]
+    pub parent: Option<RunParent>,
 }
 
 fn default_true() -> bool { true }
@@ -32,27 +101,119 @@ impl Default for VerifyOptions {
     fn default() -> Self {
         Self {
             run_tests: true,
+            test_repeat: None,
+            rerun_failed_tests: false,
             run_lint: false,
             run_build: false,
+            run_typecheck: false,
+            run_bench: false,
+            run_diagnostics: false,
             staged: false,
             max_tool_calls: 8,
+            phase_id: None,
+            use_ci_results: false,
+            bypass_cache: false,
+            fresh_checks: false,
+            seed: None,
+            parent: None,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Type)]
 pub struct VerifyResult {
     pub run_id: String,
     pub report_md: String,
     pub ran_checks: RanChecks,
     pub truncated: bool,
+    pub verdict: Verdict,
+    pub phase_id: Option<String>,
+    /// New phase status when a passing verdict advanced the phase
+    pub phase_status: Option<String>,
+    /// Language code the report was requested in
+    pub language: String,
+    /// Expected section headings missing from the report
+    pub missing_sections: Vec<String>,
+    /// The report was reused from an earlier run with an identical prompt
+    pub cached: bool,
+    /// Compiler, linter and test failure locations parsed from the check output,
+    /// classified against the diff's changed lines
+    pub compiler_diagnostics: Vec<CompilerDiagnostic>,
+    /// Diagnostics on lines this change added or modified; only these count
+    /// toward the verdict
+    pub introduced_issues: usize,
+    /// Changes against the task's captured baseline, when it has one
+    pub baseline_delta: Option<BaselineDelta>,
+    /// Failed tests known to be flaky in the project; they don't count toward the verdict
+    pub flaky_failures: Vec<TestHistory>,
+    /// Benchmark results next to the project's previous ones
+    pub benchmarks: Vec<BenchComparison>,
+    /// Checks whose passing result on the same repository state was reused instead of rerun
+    pub reused_checks: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Matches,
+    PartiallyMatches,
+    DoesNotMatch,
+    Unknown,
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Matches => "matches",
+            Verdict::PartiallyMatches => "partially_matches",
+            Verdict::DoesNotMatch => "does_not_match",
+            Verdict::Unknown => "unknown",
+        }
+    }
+
+    /// Human-readable form for notifications and exports
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Matches => "Matches",
+            Verdict::PartiallyMatches => "Partially matches",
+            Verdict::DoesNotMatch => "Does not match",
+            Verdict::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Read the verdict from the report's first section ("## 1. Verdict", or its
+/// localized heading; verdict labels stay in English)
+pub fn parse_verdict(report_md: &str) -> Verdict {
+    let section = report_md
+        .split("## 1.")
+        .nth(1)
+        .and_then(|rest| rest.split("\n## ").next())
+        .unwrap_or(report_md)
+        .to_lowercase();
+
+    // Check the longer phrases first since they contain "matches"
+    if section.contains("does not match") {
+        Verdict::DoesNotMatch
+    } else if section.contains("partially match") {
+        Verdict::PartiallyMatches
+    } else if section.contains("matches") {
+        Verdict::Matches
+    } else {
+        Verdict::Unknown
+    }
+}
+
+#[derive(Debug, Serialize, Type)]
 pub struct RanChecks {
     pub tests: bool,
     pub lint: bool,
     pub build: bool,
+    pub typecheck: bool,
+    pub bench: bool,
+    pub diagnostics: bool,
+    /// Test results came from ingested CI output rather than a local run
+    pub ci: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -61,161 +222,500 @@ pub struct VerifyError {
     pub message: String,
 }
 
+#[tracing::instrument(skip(app, options), fields(phase_id = ?options.phase_id, run_id = tracing::field::Empty))]
 pub async fn verify_task(
     app: AppHandle,
     project_id: String,
     task_id: String,
     options: VerifyOptions,
 ) -> Result<VerifyResult, VerifyError> {
+    let started = Instant::now();
+
     // 1. Get task and project info
     let (task, project) = get_task_and_project(&app, &task_id, &project_id)
         .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })?;
 
+    // Demo projects are replayed from fixtures and never call the LLM
+    if is_demo_project(&app, &project.id).map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })? {
+        return Err(VerifyError { code: "DEMO_READ_ONLY".into(), message: DEMO_READ_ONLY_MESSAGE.into() });
+    }
+
     // 2. Get settings for LLM
-    let settings = get_all_settings(&app)?;
-    let llm_config = build_llm_config(&settings);
+    let settings = get_all_settings(&app).await?;
+    let mut llm_config = build_llm_config(&settings)
+        .map_err(|e| VerifyError { code: "INVALID_SETTING".into(), message: e })?;
+    if options.seed.is_some() {
+        llm_config.seed = options.seed;
+    }
     let api_key = get_api_key(&settings)?;
+    let queued_options = serde_json::to_value(&options).unwrap_or(Value::Null);
+    if offline::is_offline(&app, &settings) {
+        offline::enqueue_run(&app, &project.id, &task_id, "verify", queued_options)
+            .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })?;
+        return Err(VerifyError { code: "OFFLINE".into(), message: OFFLINE_MESSAGE.into() });
+    }
+    check_run_budget(&app, &settings, &project.id)
+        .map_err(|b| VerifyError { code: b.code.into(), message: b.message })?;
+
+    // Load the phase being verified, if scoped
+    let phase = match &options.phase_id {
+        Some(phase_id) => Some(load_phase(&app, &task_id, phase_id)
+            .map_err(|e| VerifyError { code: "PHASE_ERROR".into(), message: e })?),
+        None => None,
+    };
+    let phase_files: Vec<String> = phase.as_ref()
+        .and_then(|p| serde_json::from_str(&p.planned_files_json).ok())
+        .unwrap_or_default();
+
+    let ci_results = if options.use_ci_results {
+        let results = latest_ci_results(&app, &task_id)
+            .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })?;
+        Some(results.ok_or_else(|| VerifyError {
+            code: "NO_CI_RESULTS".into(),
+            message: "No CI results have been ingested for this task".into(),
+        })?)
+    } else {
+        None
+    };
 
     // 3. Create run
-    let run_id = create_run_verify(&app, &task_id, &llm_config)
+    let run_id = create_run_verify(&app, &task_id, options.phase_id.as_deref(), &llm_config, options.parent.as_ref(), &queued_options)
         .map_err(|e| VerifyError { code: "RUN_ERROR".into(), message: e })?;
+    tracing::Span::current().record("run_id", run_id.as_str());
+    let cancellation = cancellation::register(&app, &run_id);
 
-    // 4. Load plan artifact (if exists)
-    let plan_md = load_plan_artifact(&app, &task_id).ok();
-
-    // 5. Gather repo state
-    let repo_path = Path::new(&project.repo_path);
-    let mut truncated = false;
-    let mut tool_calls_count = 0;
-
-    // git_status
-    let status_result = execute_tool_simple(
-        &app, &run_id, &project_id, repo_path, "git_status", json!({})
-    ).await;
-    let git_status = format_tool_result(&status_result);
-    if status_result.as_ref().map_or(false, |v| {
-        v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
-    }) {
-        truncated = true;
-    }
-    tool_calls_count += 1;
+    emit_run_event(&app, RunEvent {
+        run_id: run_id.clone(),
+        project_id: project.id.clone(),
+        task_id: task_id.clone(),
+        run_type: "verify".into(),
+        status: RunStatus::Started,
+        error: None,
+        code: None,
+    });
+    
+    // Everything after run creation is recorded on the run, so failures show up in diagnostics
+    let result: Result<VerifyResult, VerifyError> = async {
+        // Checks are slow, so a cancelled run stops before starting the next one
+        let cancelled = |message: String| VerifyError { code: "CANCELLED".into(), message };
 
-    // git_diff
-    let diff_result = execute_tool_simple(
-        &app, &run_id, &project_id, repo_path, "git_diff", json!({ "staged": options.staged })
-    ).await;
-    let git_diff = format_tool_result(&diff_result);
-    if diff_result.as_ref().map_or(false, |v| {
-        v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
-    }) {
-        truncated = true;
-    }
-    tool_calls_count += 1;
+        // 4. Load plan artifact (if exists)
+        let plan_md = load_plan_artifact(&app, &task_id).ok();
 
-    // 6. Run optional checks
-    let mut ran_checks = RanChecks { tests: false, lint: false, build: false };
-    let mut test_output = String::new();
-    let mut lint_output = String::new();
-    let mut build_output = String::new();
+        // 5. Gather repo state
+        let repo_path = Path::new(&project.repo_path);
+        let mut truncated = false;
+        let mut tool_calls_count = 0;
 
-    if options.run_tests && tool_calls_count < options.max_tool_calls {
-        let result = execute_tool_simple(
-            &app, &run_id, &project_id, repo_path, "run_command", json!({ "kind": "tests" })
+        // git_status
+        let status_result = execute_tool_simple(
+            &app, &run_id, &project_id, repo_path, "git_status", json!({})
         ).await;
-        test_output = format_tool_result(&result);
-        if result.as_ref().map_or(false, |v| {
+        let git_status = format_tool_result(&status_result);
+        if status_result.as_ref().map_or(false, |v| {
             v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
         }) {
             truncated = true;
         }
-        ran_checks.tests = true;
         tool_calls_count += 1;
-    }
 
-    if options.run_lint && tool_calls_count < options.max_tool_calls {
-        let result = execute_tool_simple(
-            &app, &run_id, &project_id, repo_path, "run_command", json!({ "kind": "lint" })
+        // git_diff
+        let diff_result = execute_tool_simple(
+            &app, &run_id, &project_id, repo_path, "git_diff", json!({ "staged": options.staged, "paths": phase_files })
         ).await;
-        lint_output = format_tool_result(&result);
-        if result.as_ref().map_or(false, |v| {
+        let git_diff = format_tool_result(&diff_result);
+        if diff_result.as_ref().map_or(false, |v| {
             v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
         }) {
             truncated = true;
         }
-        ran_checks.lint = true;
         tool_calls_count += 1;
-    }
 
-    if options.run_build && tool_calls_count < options.max_tool_calls {
-        let result = execute_tool_simple(
-            &app, &run_id, &project_id, repo_path, "run_command", json!({ "kind": "build" })
-        ).await;
-        build_output = format_tool_result(&result);
-        if result.as_ref().map_or(false, |v| {
-            v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
-        }) {
-            truncated = true;
+        // 6. Run optional checks
+        let mut ran_checks = RanChecks { tests: false, lint: false, build: false, typecheck: false, bench: false, diagnostics: false, ci: false };
+        let mut test_output = String::new();
+        let mut lint_output = String::new();
+        let mut build_output = String::new();
+        let mut typecheck_output = String::new();
+        let mut diagnostics_output = String::new();
+        let mut compiler_diagnostics: Vec<CompilerDiagnostic> = vec![];
+        // Structured results of the checks run locally, compared against the baseline
+        let mut checks = vec![];
+        let mut flaky_failures = vec![];
+        let mut benchmarks: Vec<BenchComparison> = vec![];
+        let mut bench_output = String::new();
+        // Passing checks on an unchanged repository are reused rather than rerun
+        let reuse = !options.fresh_checks
+            && (options.run_tests || options.run_lint || options.run_build)
+            && settings.get("reuse_check_results").map_or(true, |v| v == "1");
+        let fingerprint = if reuse {
+            repo_fingerprint(repo_path).await
+                .map_err(|e| tracing::warn!("cannot fingerprint the repository: {}", e))
+                .ok()
+        } else {
+            None
+        };
+        let mut reused_checks = vec![];
+
+        // Large suites are run in CI; their ingested failures replace the local test run
+        if let Some(results) = &ci_results {
+            test_output = format_ci_results(results);
+            merge_diagnostics(&mut compiler_diagnostics, ci_failure_diagnostics(&results.failures));
+            ran_checks.tests = true;
+            ran_checks.ci = true;
+        } else if options.run_tests && tool_calls_count < options.max_tool_calls {
+            cancellation.check().map_err(cancelled)?;
+            let mut test_args = json!({ "kind": "tests" });
+            if let Some(repeat) = options.test_repeat.filter(|r| *r > 1) {
+                test_args["repeat"] = json!(repeat);
+            }
+            if options.rerun_failed_tests {
+                test_args["rerun_failures"] = json!(true);
+            }
+            let result = run_check(&app, &run_id, &project.id, repo_path, test_args, fingerprint.as_deref(), &mut reused_checks).await;
+            test_output = format_tool_result(&result);
+            if result.as_ref().map_or(false, |v| {
+                v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
+            }) {
+                truncated = true;
+            }
+            merge_diagnostics(&mut compiler_diagnostics, command_diagnostics(&result, parse_test_failures));
+            checks.push(check_result("tests", &result));
+            // Record the run in the project's test history, then look up which failures are known flakes
+            if let Some(value) = result.as_ref().ok().filter(|v| v.get("reused").is_none()) {
+                let stats = result_test_stats(value);
+                let failing: Vec<String> = stats.iter().filter(|s| s.failures > 0).map(|s| s.name.clone()).collect();
+                let flaky = db::connect(&app).map_err(|e| e.to_string()).and_then(|conn| {
+                    record_test_stats(&conn, &project.id, &stats)?;
+                    known_flaky(&conn, &project.id, &failing)
+                });
+                match flaky {
+                    Ok(flaky) => flaky_failures = flaky,
+                    Err(e) => tracing::warn!("failed to record test stats: {}", e),
+                }
+            }
+            ran_checks.tests = true;
+            tool_calls_count += 1;
         }
-        ran_checks.build = true;
-        tool_calls_count += 1;
-    }
 
-    // 7. Build LLM messages
-    let messages = build_verify_messages(
-        &task,
-        plan_md.as_deref(),
-        &git_status,
-        &git_diff,
-        &test_output,
-        &lint_output,
-        &build_output,
-        options.staged,
-        truncated,
-    );
+        if options.run_lint && tool_calls_count < options.max_tool_calls {
+            cancellation.check().map_err(cancelled)?;
+            let result = run_check(
+                &app, &run_id, &project.id, repo_path, json!({ "kind": "lint" }), fingerprint.as_deref(), &mut reused_checks
+            ).await;
+            lint_output = format_tool_result(&result);
+            if result.as_ref().map_or(false, |v| {
+                v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
+            }) {
+                truncated = true;
+            }
+            merge_diagnostics(&mut compiler_diagnostics, command_diagnostics(&result, parse_compiler_output));
+            checks.push(check_result("lint", &result));
+            ran_checks.lint = true;
+            tool_calls_count += 1;
+        }
+
+        if options.run_build && tool_calls_count < options.max_tool_calls {
+            cancellation.check().map_err(cancelled)?;
+            let result = run_check(
+                &app, &run_id, &project.id, repo_path, json!({ "kind": "build" }), fingerprint.as_deref(), &mut reused_checks
+            ).await;
+            build_output = format_tool_result(&result);
+            if result.as_ref().map_or(false, |v| {
+                v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
+            }) {
+                truncated = true;
+            }
+            ran_checks.build = true;
+            merge_diagnostics(&mut compiler_diagnostics, command_diagnostics(&result, parse_compiler_output));
+            checks.push(check_result("build", &result));
+            tool_calls_count += 1;
+        }
+
+        if options.run_typecheck && tool_calls_count < options.max_tool_calls {
+            cancellation.check().map_err(cancelled)?;
+            let result = execute_tool_simple(
+                &app, &run_id, &project_id, repo_path, "run_command", json!({ "kind": "typecheck" })
+            ).await;
+            let parsed = command_diagnostics(&result, parse_compiler_output);
+            // Output that isn't in a known format (e.g. a missing type checker) is shown as is
+            let passed = result.as_ref().is_ok_and(|v| v.get("code").and_then(|c| c.as_i64()) == Some(0));
+            if parsed.is_empty() {
+                typecheck_output = if passed {
+                    "No errors or warnings (exit code 0)".to_string()
+                } else {
+                    format_tool_result(&result)
+                };
+            }
+            merge_diagnostics(&mut compiler_diagnostics, parsed);
+            checks.push(check_result("typecheck", &result));
+            ran_checks.typecheck = true;
+            tool_calls_count += 1;
+        }
+
+        if options.run_bench && tool_calls_count < options.max_tool_calls {
+            cancellation.check().map_err(cancelled)?;
+            let result = execute_tool_simple(
+                &app, &run_id, &project_id, repo_path, "run_command", json!({ "kind": "bench" })
+            ).await;
+            // Without parsed results the raw output shows what went wrong
+            match result.as_ref().ok().and_then(|v| v.get("benchmarks")).and_then(|b| serde_json::from_value(b.clone()).ok()) {
+                Some(parsed) => benchmarks = parsed,
+                None => bench_output = format_tool_result(&result),
+            }
+            ran_checks.bench = true;
+            tool_calls_count += 1;
+        }
+
+        let diff_text = diff_result.as_ref().ok()
+            .and_then(|v| v.get("diff").and_then(|d| d.as_str()))
+            .unwrap_or_default();
+        classify(&mut compiler_diagnostics, &changed_ranges(diff_text));
+        let introduced_issues = compiler_diagnostics.iter().filter(|d| d.origin == Origin::Introduced).count();
+        let baseline_delta = latest_baseline(&app, &task_id)
+            .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })?
+            .and_then(|baseline| compare(&baseline, &checks));
+        let changed_files = diff_files(diff_text);
+        if options.run_diagnostics && !changed_files.is_empty() && tool_calls_count < options.max_tool_calls {
+            cancellation.check().map_err(cancelled)?;
+            let result = execute_tool_simple(
+                &app, &run_id, &project_id, repo_path, "get_diagnostics", json!({ "paths": changed_files })
+            ).await;
+            diagnostics_output = format_tool_result(&result);
+            if result.as_ref().map_or(false, |v| {
+                v.get("truncated").and_then(|t| t.as_bool()).unwrap_or(false)
+            }) {
+                truncated = true;
+            }
+            ran_checks.diagnostics = true;
+            tool_calls_count += 1;
+        }
+
+        // 7. Build LLM messages
+        let header = match render_custom_prompt(&app, &settings, "verify", &task, &project)
+            .map_err(|e| VerifyError { code: "TEMPLATE_ERROR".into(), message: e })? {
+            Some(prompt) => format!("{}\n\n", prompt),
+            None => default_header(&task),
+        };
+        // A reviewer's ratings of the report this run replaces
+        let header = if settings.get("feedback_in_prompt").is_none_or(|v| v == "1") {
+            match feedback_context(&app, &task_id, options.phase_id.as_deref(), "verification_report")
+                .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })? {
+                Some(feedback) => format!("{}{}\n", header, feedback),
+                None => header,
+            }
+        } else {
+            header
+        };
+        // Project conventions and pinned project artifacts
+        let header = match project_context(&app, &project_id)
+            .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })? {
+            Some(context) => format!("{}{}\n\n", header, context),
+            None => header,
+        };
+        let language = resolve_output_language(&app, &settings, &project.id)
+            .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })?;
+        let messages = build_verify_messages(
+            &header,
+            &language,
+            plan_md.as_deref(),
+            phase.as_ref(),
+            &phase_files,
+            &git_status,
+            &git_diff,
+            &test_output,
+            &lint_output,
+            &build_output,
+            &typecheck_output,
+            &diagnostics_output,
+            &compiler_diagnostics,
+            baseline_delta.as_ref(),
+            &flaky_failures,
+            &bench_output,
+            &benchmarks,
+            ran_checks.ci,
+            options.staged,
+            truncated,
+        );
+
+        // Log messages
+        for msg in &messages {
+            log_message(&app, &run_id, &msg.role, msg.content.as_deref().unwrap_or(""), None, None)
+                .map_err(|e| VerifyError { code: "LOG_ERROR".into(), message: e })?;
+        }
+
+        // 8. Reuse the report of an identical earlier prompt, otherwise call the LLM
+        // (single call, no tool loop needed)
+        let hash = prompt_hash(&llm_config, &messages);
+        let use_cache = !options.bypass_cache && settings.get("verify_cache").map_or(true, |v| v == "1");
+        let cached_report = if use_cache {
+            find_cached_report(&app, &task_id, &hash)
+                .map_err(|e| VerifyError { code: "DB_ERROR".into(), message: e })?
+        } else {
+            None
+        };
+        let cached = cached_report.is_some();
+        let mut draft = DraftCheckpointer::new(&app, &task_id, "verification_report");
+        let report_md = match cached_report {
+            Some(report) => {
+                tracing::info!(prompt_hash = %hash, "reusing cached verification report");
+                report
+            }
+            None => {
+                cancellation.check().map_err(cancelled)?;
+                let client = LlmClient::new(llm_config, api_key);
+                let stream = settings.get("stream_responses").map_or(true, |v| v == "1");
+                let response = cancellation.or_cancel(async {
+                    if stream {
+                        let tokens = TokenEmitter::new(&app, events::VERIFY_TOKEN, &run_id, &project_id, &task_id);
+                        client.chat_stream(messages, vec![], ChatOptions::default(), |delta| {
+                            draft.push(delta);
+                            tokens.push(delta);
+                        }).await
+                    } else {
+                        client.chat_with_tools(messages, vec![]).await
+                    }
+                }).await.map_err(cancelled)?
+                    .map_err(|e| VerifyError { code: e.code().into(), message: e.to_string() })?;
+                record_usage(&app, &settings, &run_id, response.usage);
+
+                match response.content {
+                    Some(content) => {
+                        set_prompt_hash(&app, &run_id, &hash);
+                        content
+                    }
+                    None => "**Error**: No response from LLM".to_string(),
+                }
+            }
+        };
 
-    // Log messages
-    for msg in &messages {
-        log_message(&app, &run_id, &msg.role, msg.content.as_deref().unwrap_or(""))
+        // Log assistant message
+        log_message(&app, &run_id, "assistant", &report_md, None, None)
             .map_err(|e| VerifyError { code: "LOG_ERROR".into(), message: e })?;
-    }
+        let report_md = match flaky_annotation(&flaky_failures) {
+            Some(note) => format!("{}\n\n{}\n", report_md.trim_end(), note),
+            None => report_md,
+        };
 
-    // 8. Call LLM (single call, no tool loop needed)
-    let client = LlmClient::new(llm_config, api_key);
-    let response = client.chat_with_tools(messages, vec![]).await
-        .map_err(|e| VerifyError { code: "LLM_ERROR".into(), message: e.to_string() })?;
+        // 9. Save verification report (phase-scoped when verifying a phase)
+        let previous_verdict = match options.phase_id {
+            Some(_) => None,
+            None => saved_report_verdict(&app, &task_id),
+        };
+        let report_id = save_artifact(&app, &task_id, options.phase_id.as_deref(), "verification_report", &report_md)
+            .map_err(|e| VerifyError { code: "ARTIFACT_ERROR".into(), message: e })?;
+        if let Err(e) = db::connect(&app).map_err(|e| e.to_string())
+            .and_then(|conn| tags::auto_tag_report(&conn, &report_id, &report_md)) {
+            tracing::warn!("failed to auto-tag verification report: {}", e);
+        }
+        let _ = draft.discard();
 
-    let report_md = response.content.unwrap_or_else(|| {
-        "**Error**: No response from LLM".to_string()
-    });
+        // 10. A passing verdict completes the phase
+        let verdict = parse_verdict(&report_md);
+        let missing_sections = missing_sections(&report_md, verify_headings(&language));
+        let mut phase_status = None;
+        if let (Some(phase_id), Verdict::Matches) = (&options.phase_id, verdict) {
+            set_phase_status(&app, phase_id, "done")
+                .map_err(|e| VerifyError { code: "PHASE_ERROR".into(), message: e })?;
+            phase_status = Some("done".to_string());
+        }
+        if let Some(previous) = previous_verdict {
+            notify_regression(&app, &task_id, previous, verdict).await;
+        }
+        let hook_event = match verdict {
+            Verdict::Matches => Some(HookEvent::VerifyPassed),
+            Verdict::PartiallyMatches | Verdict::DoesNotMatch => Some(HookEvent::VerifyFailed),
+            Verdict::Unknown => None,
+        };
+        if let Some(event) = hook_event {
+            let details = json!({
+                "project_id": project.id,
+                "task_id": task_id,
+                "phase_id": options.phase_id,
+                "verdict": verdict.as_str(),
+                "report_md": report_md,
+            });
+            fire_hook(&app, &run_id, event, Path::new(&project.repo_path), details).await;
+        }
 
-    // Log assistant message
-    log_message(&app, &run_id, "assistant", &report_md)
-        .map_err(|e| VerifyError { code: "LOG_ERROR".into(), message: e })?;
+        tracing::info!(
+            verdict = verdict.as_str(),
+            missing_sections = missing_sections.len(),
+            duration_ms = started.elapsed().as_millis() as u64,
+            "verification finished"
+        );
 
-    // 9. Save verification report
-    save_artifact(&app, &task_id, "verification_report", &report_md)
-        .map_err(|e| VerifyError { code: "ARTIFACT_ERROR".into(), message: e })?;
+        Ok(VerifyResult {
+            run_id: run_id.clone(),
+            report_md,
+            ran_checks,
+            truncated,
+            verdict,
+            phase_id: options.phase_id,
+            phase_status,
+            language,
+            missing_sections,
+            cached,
+            compiler_diagnostics,
+            introduced_issues,
+            baseline_delta,
+            flaky_failures,
+            benchmarks,
+            reused_checks,
+        })
+    }.await;
 
-    Ok(VerifyResult {
+    // Lost connectivity mid-run: retry the whole run once back online
+    if result.as_ref().is_err_and(|e| e.code == "OFFLINE") {
+        offline::mark_offline(&app);
+        let mut queued_options = queued_options;
+        queued_options["parent"] = json!(RunParent::new(&run_id, "retry"));
+        if let Err(e) = offline::enqueue_run(&app, &project.id, &task_id, "verify", queued_options) {
+            tracing::warn!("failed to queue run: {}", e);
+        }
+    }
+    let was_cancelled = result.as_ref().is_err_and(|e| e.code == "CANCELLED");
+    finish_run(&app, &run_id, result.as_ref().err().map(|e| e.message.as_str()), was_cancelled);
+    emit_run_event(&app, RunEvent {
         run_id,
-        report_md,
-        ran_checks,
-        truncated,
-    })
+        project_id: project.id.clone(),
+        task_id,
+        run_type: "verify".into(),
+        status: match &result {
+            Ok(_) => RunStatus::Finished,
+            Err(_) if was_cancelled => RunStatus::Cancelled,
+            Err(_) => RunStatus::Failed,
+        },
+        error: result.as_ref().err().map(|e| e.message.clone()),
+        code: result.as_ref().err().map(|e| e.code.clone()),
+    });
+    result
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_verify_messages(
-    task: &Task,
+    header: &str,
+    language: &str,
     plan_md: Option<&str>,
+    phase: Option<&Phase>,
+    phase_files: &[String],
     git_status: &str,
     git_diff: &str,
     test_output: &str,
     lint_output: &str,
     build_output: &str,
+    typecheck_output: &str,
+    diagnostics_output: &str,
+    compiler_diagnostics: &[CompilerDiagnostic],
+    baseline_delta: Option<&BaselineDelta>,
+    flaky_failures: &[TestHistory],
+    bench_output: &str,
+    benchmarks: &[BenchComparison],
+    ci_tests: bool,
     staged: bool,
     mut truncated: bool,
 ) -> Vec<ChatMessage> {
-    let system_prompt = r#"You are a senior code reviewer conducting a verification review.
+    let mut system_prompt = r#"You are a senior code reviewer conducting a verification review.
 
 Your task: Compare the actual changes in the repository against the implementation plan (if provided) and produce a verification report.
 
@@ -261,12 +761,15 @@ Instructions:
 - Be objective and thorough
 - Cite specific files/paths when discussing changes
 - If no plan was provided, do a general code review focusing on best practices
-- Always include a clear verdict at the top"#;
+- When check diagnostics are classified, only [introduced] issues count toward the verdict; list [pre-existing] ones separately in section 5
+- A check with outcome tool_missing or script_undefined never ran; report it as not run in section 5 rather than as a failure of the change
+- Always include a clear verdict at the top"#.to_string();
+    if let Some(instruction) = language_instruction(language, verify_headings(language)) {
+        system_prompt.push_str(&instruction);
+    }
+    system_prompt.push_str(UNTRUSTED_CONTENT_REMINDER);
 
-    let mut user_prompt = format!(
-        "Task: {}\n\n",
-        task.title
-    );
+    let mut user_prompt = header.to_string();
 
     if let Some(plan) = plan_md {
         user_prompt.push_str("## Implementation Plan\n\n");
@@ -281,8 +784,24 @@ Instructions:
         user_prompt.push_str("*No implementation plan provided. Conducting general code review.*\n\n");
     }
 
+    if let Some(phase) = phase {
+        user_prompt.push_str(&format!("## Phase Under Review: {}. {}\n\n", phase.idx, phase.title));
+        if let Some(description) = &phase.description {
+            user_prompt.push_str(description);
+            user_prompt.push_str("\n\n");
+        }
+        if !phase_files.is_empty() {
+            user_prompt.push_str("Planned files for this phase (the diff below is limited to these):\n");
+            for file in phase_files {
+                user_prompt.push_str(&format!("- {}\n", file));
+            }
+            user_prompt.push('\n');
+        }
+        user_prompt.push_str("Judge the verdict against this phase only, not the whole plan.\n\n---\n\n");
+    }
+
     user_prompt.push_str("## Repository State\n\n");
-    user_prompt.push_str(&format!("### Git Status\n```\n{}\n```\n\n", git_status));
+    user_prompt.push_str(&format!("### Git Status\n{}\n\n", wrap_untrusted("git_status", &format!("```\n{}\n```", git_status))));
     
     let diff_label = if staged { "Staged Changes" } else { "Unstaged Changes" };
     let truncated_diff = if git_diff.len() > 30000 {
@@ -291,7 +810,7 @@ Instructions:
     } else {
         git_diff
     };
-    user_prompt.push_str(&format!("### {}\n```diff\n{}\n```\n\n", diff_label, truncated_diff));
+    user_prompt.push_str(&format!("### {}\n{}\n\n", diff_label, wrap_untrusted("git_diff", &format!("```diff\n{}\n```", truncated_diff))));
 
     if !test_output.is_empty() {
         let truncated_test = if test_output.len() > 10000 {
@@ -300,7 +819,8 @@ Instructions:
         } else {
             test_output
         };
-        user_prompt.push_str(&format!("### Test Results\n```\n{}\n```\n\n", truncated_test));
+        let test_label = if ci_tests { "Test Results (from CI)" } else { "Test Results" };
+        user_prompt.push_str(&format!("### {}\n{}\n\n", test_label, wrap_untrusted("tests", &format!("```\n{}\n```", truncated_test))));
     }
 
     if !lint_output.is_empty() {
@@ -310,7 +830,7 @@ Instructions:
         } else {
             lint_output
         };
-        user_prompt.push_str(&format!("### Lint Results\n```\n{}\n```\n\n", truncated_lint));
+        user_prompt.push_str(&format!("### Lint Results\n{}\n\n", wrap_untrusted("lint", &format!("```\n{}\n```", truncated_lint))));
     }
 
     if !build_output.is_empty() {
@@ -320,7 +840,58 @@ Instructions:
         } else {
             build_output
         };
-        user_prompt.push_str(&format!("### Build Results\n```\n{}\n```\n\n", truncated_build));
+        user_prompt.push_str(&format!("### Build Results\n{}\n\n", wrap_untrusted("build", &format!("```\n{}\n```", truncated_build))));
+    }
+
+    if !typecheck_output.is_empty() {
+        // Checker output quotes source, often non-ASCII, so cut it at a char boundary
+        let (truncated_typecheck, omitted) = truncate_middle(typecheck_output, 5000);
+        truncated |= omitted > 0;
+        user_prompt.push_str(&format!("### Typecheck Results\n{}\n\n", wrap_untrusted("typecheck", &format!("```\n{}\n```", truncated_typecheck))));
+    }
+
+    if !diagnostics_output.is_empty() {
+        let (truncated_diagnostics, omitted) = truncate_middle(diagnostics_output, 5000);
+        truncated |= omitted > 0;
+        user_prompt.push_str(&format!(
+            "### Language Server Diagnostics (changed files)\n{}\n\n",
+            wrap_untrusted("get_diagnostics", &format!("```\n{}\n```", truncated_diagnostics))
+        ));
+    }
+
+    if !compiler_diagnostics.is_empty() {
+        user_prompt.push_str(&format!(
+            "### Check Diagnostics\n[introduced] issues are on lines this change added or modified; [pre-existing] ones are elsewhere and predate it.\n{}\n\n",
+            wrap_untrusted("compiler", &format_compiler_diagnostics(compiler_diagnostics))
+        ));
+    }
+
+    if let Some(delta) = baseline_delta {
+        user_prompt.push_str(&format!(
+            "### Baseline Comparison\nThe same checks were run on the code before this change. \
+             Judge test and warning results by what the change added, not by absolute counts.\n{}\n\n",
+            wrap_untrusted("baseline", &format_delta(delta))
+        ));
+    }
+
+    if !flaky_failures.is_empty() {
+        user_prompt.push_str(&format!(
+            "### Known Flaky Tests\nThese failing tests have passed and failed on the same code before. \
+             Don't let their failures lower the verdict; mention them in section 5.\n{}\n\n",
+            wrap_untrusted("flaky_tests", &format_flaky(flaky_failures))
+        ));
+    }
+
+    if !benchmarks.is_empty() {
+        user_prompt.push_str(&format!(
+            "### Benchmarks\nChanges are against the project's previous results. \
+             Treat [regression] entries as performance risks in section 4.\n{}\n\n",
+            wrap_untrusted("bench", &format_benchmarks(benchmarks))
+        ));
+    } else if !bench_output.is_empty() {
+        let (truncated_bench, omitted) = truncate_middle(bench_output, 5000);
+        truncated |= omitted > 0;
+        user_prompt.push_str(&format!("### Benchmark Results\n{}\n\n", wrap_untrusted("bench", &format!("```\n{}\n```", truncated_bench))));
     }
 
     if truncated {
@@ -336,7 +907,7 @@ Instructions:
     vec![
         ChatMessage {
             role: "system".into(),
-            content: Some(system_prompt.into()),
+            content: Some(system_prompt),
             tool_call_id: None,
             tool_calls: None,
         },
@@ -349,6 +920,34 @@ Instructions:
     ]
 }
 
+/// Default opening of the verify prompt, replaced by a custom template when set.
+/// Repository state and check results are always appended after it.
+pub fn default_header(task: &Task) -> String {
+    let mut header = format!(
+        "Task: {}\n\n",
+        task.title
+    );
+
+    if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        header.push_str(&format!("## Task Description\n\n{}\n\n", description));
+    }
+
+    header
+}
+
+/// Files a unified diff changes, skipping deletions
+fn diff_files(diff: &str) -> Vec<String> {
+    let mut files = vec![];
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ b/") {
+            if !files.iter().any(|f| f == path) {
+                files.push(path.to_string());
+            }
+        }
+    }
+    files
+}
+
 async fn execute_tool_simple(
     app: &AppHandle,
     run_id: &str,
@@ -360,9 +959,67 @@ async fn execute_tool_simple(
     dispatch_repo_tool(name, &args, repo_path, app, run_id).await
 }
 
+/// Run a check through run_command, or reuse the passing result of the same
+/// check on the same repository state. New passing results are remembered.
+async fn run_check(
+    app: &AppHandle,
+    run_id: &str,
+    project_id: &str,
+    repo_path: &Path,
+    args: Value,
+    fingerprint: Option<&str>,
+    reused_checks: &mut Vec<String>,
+) -> Result<Value, String> {
+    let Some(fingerprint) = fingerprint else {
+        return dispatch_repo_tool("run_command", &args, repo_path, app, run_id).await;
+    };
+    let kind = args.get("kind").and_then(|k| k.as_str()).unwrap_or_default().to_string();
+    // The same arguments can resolve to another command, backend or image once settings change
+    let key = json!({ "args": &args, "command": command_identity(repo_path, &args, app, run_id).await? });
+    let reused = {
+        let conn = db::connect(app).map_err(|e| e.to_string())?;
+        find_passing(&conn, project_id, &key, fingerprint)?
+    };
+    if let Some(result) = reused {
+        log_tool_call(app, run_id, "run_command", &args, &result)?;
+        reused_checks.push(kind);
+        return Ok(result);
+    }
+
+    let result = dispatch_repo_tool("run_command", &args, repo_path, app, run_id).await;
+    if result.as_ref().is_ok_and(|v| v.get("code").and_then(|c| c.as_i64()) == Some(0)) {
+        let remembered = db::connect(app).map_err(|e| e.to_string())
+            .and_then(|conn| remember_passing(&conn, project_id, run_id, &key, fingerprint));
+        if let Err(e) = remembered {
+            tracing::warn!("failed to remember passing check: {}", e);
+        }
+    }
+    result
+}
+
+/// Diagnostics `parse` finds in a run_command result's stdout and stderr
+fn command_diagnostics(result: &Result<Value, String>, parse: fn(&str) -> Vec<CompilerDiagnostic>) -> Vec<CompilerDiagnostic> {
+    let Ok(value) = result else { return vec![] };
+    let output = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    parse(&format!("{}\n{}", output("stdout"), output("stderr")))
+}
+
+/// Add diagnostics not already reported by an earlier check
+fn merge_diagnostics(diagnostics: &mut Vec<CompilerDiagnostic>, parsed: Vec<CompilerDiagnostic>) {
+    for diagnostic in parsed {
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
+        }
+    }
+}
+
 fn format_tool_result(result: &Result<Value, String>) -> String {
     match result {
-        Ok(val) => val.to_string(),
+        // The outcome leads, so it survives when long output is cut
+        Ok(val) => match val.get("outcome").and_then(|o| o.as_str()) {
+            Some(outcome) => format!("Outcome: {}\n{}", outcome, val),
+            None => val.to_string(),
+        },
         Err(e) => json!({ "error": e }).to_string(),
     }
 }
@@ -375,7 +1032,7 @@ fn get_task_and_project(
     let conn = db::connect(app).map_err(|e| e.to_string())?;
     
     let task: Task = conn.query_row(
-        "SELECT id, project_id, title, mode, status, created_at, updated_at FROM tasks WHERE id = ?1",
+        "SELECT id, project_id, title, mode, status, created_at, updated_at, description FROM tasks WHERE id = ?1",
         [task_id],
         |r| Ok(Task {
             id: r.get(0)?,
@@ -385,11 +1042,12 @@ fn get_task_and_project(
             status: r.get(4)?,
             created_at: r.get(5)?,
             updated_at: r.get(6)?,
+            description: r.get(7)?,
         })
     ).map_err(|e| e.to_string())?;
     
     let project: Project = conn.query_row(
-        "SELECT id, name, repo_path, created_at, last_opened_at FROM projects WHERE id = ?1",
+        "SELECT id, name, repo_path, created_at, last_opened_at, workspace_id FROM projects WHERE id = ?1",
         [project_id],
         |r| Ok(Project {
             id: r.get(0)?,
@@ -397,6 +1055,7 @@ fn get_task_and_project(
             repo_path: r.get(2)?,
             created_at: r.get(3)?,
             last_opened_at: r.get(4)?,
+            workspace_id: r.get(5)?,
         })
     ).map_err(|e| e.to_string())?;
     
@@ -406,10 +1065,10 @@ fn get_task_and_project(
 fn load_plan_artifact(app: &AppHandle, task_id: &str) -> Result<String, String> {
     let conn = db::connect(app).map_err(|e| e.to_string())?;
     
-    let content: String = conn.query_row(
-        "SELECT content FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = 'plan_md' ORDER BY created_at DESC LIMIT 1",
+    let content = conn.query_row(
+        "SELECT content, content_hash FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = 'plan_md' AND draft = 0 ORDER BY created_at DESC LIMIT 1",
         [task_id],
-        |r| r.get(0)
+        |r| blobs::hydrate(&conn, r.get(0)?, r.get(1)?)
     ).map_err(|e| e.to_string())?;
     
     Ok(content)
@@ -418,113 +1077,110 @@ fn load_plan_artifact(app: &AppHandle, task_id: &str) -> Result<String, String>
 fn create_run_verify(
     app: &AppHandle,
     task_id: &str,
+    phase_id: Option<&str>,
     llm_config: &LlmConfig,
+    parent: Option<&RunParent>,
+    options: &Value,
 ) -> Result<String, String> {
     let conn = db::connect(app).map_err(|e| e.to_string())?;
     let id = new_id();
     let started_at = now_iso();
     
     conn.execute(
-        "INSERT INTO runs (id, task_id, phase_id, run_type, provider, model, started_at, ended_at) 
-         VALUES (?1, ?2, NULL, ?3, ?4, ?5, ?6, NULL)",
+        "INSERT INTO runs (id, task_id, phase_id, run_type, provider, model, started_at, ended_at, seed, parent_run_id, parent_relation, options_json) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL, ?8, ?9, ?10, ?11)",
         (
-            &id, task_id, "verify", &llm_config.provider_name, &llm_config.model, &started_at
+            &id, task_id, phase_id, "verify", &llm_config.provider_name, &llm_config.model, &started_at, llm_config.seed,
+            parent.map(|p| &p.run_id), parent.map(|p| &p.relation), options.to_string()
         )
     ).map_err(|e| e.to_string())?;
     
     Ok(id)
 }
 
-fn log_message(
-    app: &AppHandle,
-    run_id: &str,
-    role: &str,
-    content: &str,
-) -> Result<(), String> {
-    let conn = db::connect(app).map_err(|e| e.to_string())?;
-    let id = new_id();
-    let created_at = now_iso();
-    
-    conn.execute(
-        "INSERT INTO messages (id, run_id, role, content, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-        (
-            &id, run_id, role, content, &created_at
-        )
-    ).map_err(|e| e.to_string())?;
-    
-    Ok(())
+/// Content hash of the fully assembled prompt (diff, check outputs, plan) and
+/// the model and sampling settings it's sent with
+fn prompt_hash(llm_config: &LlmConfig, messages: &[ChatMessage]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(llm_config.model.as_bytes());
+    hasher.update(format!("\0{}\0{:?}", llm_config.temperature, llm_config.seed).as_bytes());
+    for message in messages {
+        hasher.update([0]);
+        hasher.update(message.role.as_bytes());
+        hasher.update([0]);
+        hasher.update(message.content.as_deref().unwrap_or("").as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
 }
 
-fn save_artifact(
-    app: &AppHandle,
-    task_id: &str,
-    kind: &str,
-    content: &str,
-) -> Result<(), String> {
+/// Report from the task's latest successful verify run with the same prompt hash
+fn find_cached_report(app: &AppHandle, task_id: &str, hash: &str) -> Result<Option<String>, String> {
     let conn = db::connect(app).map_err(|e| e.to_string())?;
-    let created_at = now_iso();
-    let id = new_id();
-    
-    // Check if artifact exists
-    let existing: Option<String> = conn.query_row(
-        "SELECT id FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = ?2 LIMIT 1",
-        (task_id, kind),
-        |r| r.get(0)
-    ).optional().map_err(|e| e.to_string())?;
-    
-    if let Some(existing_id) = existing {
-        // Update
-        conn.execute(
-            "UPDATE artifacts SET content = ?1, created_at = ?2 WHERE id = ?3",
-            (content, &created_at, &existing_id)
-        ).map_err(|e| e.to_string())?;
-    } else {
-        // Insert
-        conn.execute(
-            "INSERT INTO artifacts (id, task_id, phase_id, kind, content, created_at, pinned) 
-             VALUES (?1, ?2, NULL, ?3, ?4, ?5, 0)",
-            (
-                &id, task_id, kind, content, &created_at
-            )
-        ).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT m.content, m.content_hash FROM runs r JOIN messages m ON m.run_id = r.id
+         WHERE r.task_id = ?1 AND r.run_type = 'verify' AND r.prompt_hash = ?2
+           AND r.ended_at IS NOT NULL AND r.error IS NULL AND m.role = 'assistant'
+         ORDER BY r.started_at DESC, m.created_at DESC LIMIT 1",
+        (task_id, hash),
+        |r| blobs::hydrate(&conn, r.get(0)?, r.get(1)?)
+    ).optional().map_err(|e| e.to_string())
+}
+
+/// Best-effort: a run without a hash just can't be reused
+fn set_prompt_hash(app: &AppHandle, run_id: &str, hash: &str) {
+    if let Ok(conn) = db::connect(app) {
+        let _ = conn.execute("UPDATE runs SET prompt_hash = ?1 WHERE id = ?2", (hash, run_id));
     }
-    
+}
+
+/// Verdict of the task-level report currently saved, read before it's replaced
+fn saved_report_verdict(app: &AppHandle, task_id: &str) -> Option<Verdict> {
+    let conn = db::connect(app).ok()?;
+    conn.query_row(
+        "SELECT content, content_hash FROM artifacts WHERE task_id = ?1 AND phase_id IS NULL AND kind = 'verification_report' AND draft = 0 LIMIT 1",
+        [task_id],
+        |r| blobs::hydrate(&conn, r.get(0)?, r.get(1)?)
+    ).ok().map(|content| parse_verdict(&content))
+}
+
+fn load_phase(app: &AppHandle, task_id: &str, phase_id: &str) -> Result<Phase, String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT id, task_id, idx, title, description, status, planned_files_json, created_at, updated_at
+         FROM phases WHERE id = ?1 AND task_id = ?2",
+        [phase_id, task_id],
+        |r| Ok(Phase {
+            id: r.get(0)?,
+            task_id: r.get(1)?,
+            idx: r.get(2)?,
+            title: r.get(3)?,
+            description: r.get(4)?,
+            status: r.get(5)?,
+            planned_files_json: r.get(6)?,
+            created_at: r.get(7)?,
+            updated_at: r.get(8)?,
+        })
+    ).map_err(|e| e.to_string())
+}
+
+fn set_phase_status(app: &AppHandle, phase_id: &str, status: &str) -> Result<(), String> {
+    let conn = db::connect(app).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE phases SET status = ?1, updated_at = ?2 WHERE id = ?3",
+        (status, &now_iso(), phase_id)
+    ).map_err(|e| e.to_string())?;
     Ok(())
 }
 
-fn get_all_settings(app: &AppHandle) -> Result<HashMap<String, String>, VerifyError> {
-    let conn = db::connect(app).map_err(|e| VerifyError {
+async fn get_all_settings(app: &AppHandle) -> Result<SettingsMap, VerifyError> {
+    cached_settings(app).await.map_err(|e| VerifyError {
         code: "DB_ERROR".into(),
-        message: e.to_string(),
-    })?;
-    
-    let mut stmt = conn.prepare("SELECT key, value FROM settings")
-        .map_err(|e| VerifyError {
-            code: "DB_ERROR".into(),
-            message: e.to_string(),
-        })?;
-    
-    let rows = stmt.query_map([], |r| {
-        Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
-    }).map_err(|e| VerifyError {
-        code: "DB_ERROR".into(),
-        message: e.to_string(),
-    })?;
-    
-    let mut settings = HashMap::new();
-    for row in rows {
-        let (k, v) = row.map_err(|e| VerifyError {
-            code: "DB_ERROR".into(),
-            message: e.to_string(),
-        })?;
-        settings.insert(k, v);
-    }
-    
-    Ok(settings)
+        message: e,
+    })
 }
 
-fn build_llm_config(settings: &HashMap<String, String>) -> LlmConfig {
-    LlmConfig {
+fn build_llm_config(settings: &HashMap<String, String>) -> Result<LlmConfig, String> {
+    Ok(LlmConfig {
         provider_name: settings.get("provider_name").cloned().unwrap_or_default(),
         base_url: settings.get("base_url").cloned().unwrap_or_default(),
         model: settings.get("model").cloned().unwrap_or_default(),
@@ -535,7 +1191,8 @@ fn build_llm_config(settings: &HashMap<String, String>) -> LlmConfig {
         extra_headers: settings.get("extra_headers_json")
             .and_then(|s| serde_json::from_str(s).ok())
             .unwrap_or_else(|| json!({})),
-    }
+        seed: configured_seed(settings)?,
+    })
 }
 
 fn get_api_key(settings: &HashMap<String, String>) -> Result<String, VerifyError> {
@@ -578,3 +1235,69 @@ impl<T> OptionalRow<T> for Result<T, rusqlite::Error> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_verdict() {
+        let report = "# Verification Report\n\n## 1. Verdict\n✅ **Matches** - all good\n\n## 2. Summary\nDoes not match anything";
+        assert_eq!(parse_verdict(report), Verdict::Matches);
+
+        let report = "## 1. Verdict\n⚠️ **Partially Matches** - minor issues";
+        assert_eq!(parse_verdict(report), Verdict::PartiallyMatches);
+
+        let report = "## 1. Verdict\n❌ **Does Not Match**";
+        assert_eq!(parse_verdict(report), Verdict::DoesNotMatch);
+
+        assert_eq!(parse_verdict("no verdict here"), Verdict::Unknown);
+    }
+    #[test]
+    fn test_diff_files() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n\
+                    diff --git a/old.rs b/old.rs\n--- a/old.rs\n+++ /dev/null\n\
+                    diff --git a/src/new.ts b/src/new.ts\n--- /dev/null\n+++ b/src/new.ts\n";
+        assert_eq!(diff_files(diff), vec!["src/lib.rs", "src/new.ts"]);
+    }
+
+    #[test]
+    fn test_long_check_output_is_cut_at_char_boundaries() {
+        // 3-byte chars put byte 5000 mid-character
+        let output = "…".repeat(2000);
+        let messages = build_verify_messages(
+            "# Task", "en", None, None, &[], "", "", "", "", "", &output, &output, &[], None, &[], &output, &[], false, false, false,
+        );
+        let prompt = messages[1].content.as_deref().unwrap();
+        assert!(prompt.contains("bytes omitted") && prompt.contains("truncated due to size limits"));
+    }
+
+    #[test]
+    fn test_prompt_hash() {
+        let message = |role: &str, content: &str| ChatMessage {
+            role: role.into(),
+            content: Some(content.into()),
+            tool_calls: None,
+            tool_call_id: None,
+        };
+        let config = |model: &str, temperature: f64, seed: Option<i64>| LlmConfig {
+            provider_name: "openai".into(),
+            base_url: String::new(),
+            model: model.into(),
+            temperature,
+            max_tokens: 4000,
+            extra_headers: Value::Null,
+            seed,
+        };
+        let gpt4o = config("gpt-4o", 0.2, None);
+        let messages = vec![message("system", "review"), message("user", "diff --git a/x")];
+        assert_eq!(prompt_hash(&gpt4o, &messages), prompt_hash(&gpt4o, &messages));
+        assert_ne!(prompt_hash(&gpt4o, &messages), prompt_hash(&config("gpt-4o-mini", 0.2, None), &messages));
+        // A report sampled differently isn't reused
+        assert_ne!(prompt_hash(&gpt4o, &messages), prompt_hash(&config("gpt-4o", 0.7, None), &messages));
+        assert_ne!(prompt_hash(&gpt4o, &messages), prompt_hash(&config("gpt-4o", 0.2, Some(7)), &messages));
+        // Content can't shift across the role boundary
+        let shifted = vec![message("system", "reviewu"), message("ser", "diff --git a/x")];
+        assert_ne!(prompt_hash(&gpt4o, &messages), prompt_hash(&gpt4o, &shifted));
+    }
+}
@@ -0,0 +1,325 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use specta::Type;
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::models::{new_id, Project, SettingsKV, Task, ID};
+
+/// How many tasks and runs the dashboard lists
+const DASHBOARD_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct Workspace {
+  pub id: ID,
+  pub name: String,
+  pub created_at: String,
+  pub updated_at: String,
+  pub project_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct WorkspaceProject {
+  pub project: Project,
+  /// Task count per status (draft|active|done|archived)
+  pub task_counts: BTreeMap<String, i64>,
+  pub last_run_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct WorkspaceTask {
+  pub task: Task,
+  pub project_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct WorkspaceRun {
+  pub run_id: ID,
+  pub project_id: ID,
+  pub project_name: String,
+  pub task_id: ID,
+  pub task_title: String,
+  pub run_type: String,
+  pub started_at: String,
+  pub ended_at: Option<String>,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct WorkspaceDashboard {
+  pub workspace: Workspace,
+  pub projects: Vec<WorkspaceProject>,
+  /// Task count per status across all member projects
+  pub task_counts: BTreeMap<String, i64>,
+  /// Most recently updated tasks that aren't archived
+  pub recent_tasks: Vec<WorkspaceTask>,
+  pub recent_runs: Vec<WorkspaceRun>,
+}
+
+fn now_iso() -> String {
+  let t = time::OffsetDateTime::now_utc();
+  t.format(&time::format_description::well_known::Rfc3339).unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+fn valid_name(name: &str) -> Result<String, String> {
+  let name = name.trim();
+  if name.is_empty() {
+    return Err("Workspace name can't be empty".into());
+  }
+  Ok(name.to_string())
+}
+
+fn find_workspace(conn: &Connection, workspace_id: &str) -> Result<Workspace, String> {
+  conn.query_row(
+    "SELECT w.id, w.name, w.created_at, w.updated_at,
+       (SELECT COUNT(*) FROM projects p WHERE p.workspace_id = w.id)
+     FROM workspaces w WHERE w.id = ?1",
+    [workspace_id],
+    |r| Ok(Workspace {
+      id: r.get(0)?,
+      name: r.get(1)?,
+      created_at: r.get(2)?,
+      updated_at: r.get(3)?,
+      project_count: r.get(4)?,
+    })
+  ).optional().map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Workspace {} not found", workspace_id))
+}
+
+pub fn list_workspaces(app: &AppHandle) -> Result<Vec<Workspace>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(
+    "SELECT w.id, w.name, w.created_at, w.updated_at,
+       (SELECT COUNT(*) FROM projects p WHERE p.workspace_id = w.id)
+     FROM workspaces w ORDER BY w.name COLLATE NOCASE"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], |r| Ok(Workspace {
+    id: r.get(0)?,
+    name: r.get(1)?,
+    created_at: r.get(2)?,
+    updated_at: r.get(3)?,
+    project_count: r.get(4)?,
+  })).map_err(|e| e.to_string())?;
+  rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+pub fn create_workspace(app: &AppHandle, name: &str) -> Result<Workspace, String> {
+  let name = valid_name(name)?;
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let id = new_id();
+  let now = now_iso();
+  conn.execute(
+    "INSERT INTO workspaces (id, name, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+    (&id, &name, &now)
+  ).map_err(|e| e.to_string())?;
+  Ok(Workspace { id, name, created_at: now.clone(), updated_at: now, project_count: 0 })
+}
+
+pub fn rename_workspace(app: &AppHandle, workspace_id: &str, name: &str) -> Result<Workspace, String> {
+  let name = valid_name(name)?;
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  conn.execute(
+    "UPDATE workspaces SET name = ?1, updated_at = ?2 WHERE id = ?3",
+    (&name, now_iso(), workspace_id)
+  ).map_err(|e| e.to_string())?;
+  find_workspace(&conn, workspace_id)
+}
+
+/// Delete a workspace and its settings; member projects are kept and ungrouped
+pub fn delete_workspace(app: &AppHandle, workspace_id: &str) -> Result<(), String> {
+  let mut conn = db::connect(app).map_err(|e| e.to_string())?;
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  tx.execute("UPDATE projects SET workspace_id = NULL WHERE workspace_id = ?1", [workspace_id])
+    .map_err(|e| e.to_string())?;
+  tx.execute("DELETE FROM workspace_settings WHERE workspace_id = ?1", [workspace_id])
+    .map_err(|e| e.to_string())?;
+  tx.execute("DELETE FROM workspaces WHERE id = ?1", [workspace_id])
+    .map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())
+}
+
+/// Move a project into a workspace, or out of any with `None`
+pub fn set_project_workspace(app: &AppHandle, project_id: &str, workspace_id: Option<&str>) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  if let Some(workspace_id) = workspace_id {
+    find_workspace(&conn, workspace_id)?;
+  }
+  let updated = conn.execute(
+    "UPDATE projects SET workspace_id = ?1 WHERE id = ?2",
+    (workspace_id, project_id)
+  ).map_err(|e| e.to_string())?;
+  if updated == 0 {
+    return Err(format!("Project {} not found", project_id));
+  }
+  Ok(())
+}
+
+pub fn get_workspace_settings(app: &AppHandle, workspace_id: &str) -> Result<Vec<SettingsKV>, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  let mut stmt = conn.prepare(
+    "SELECT key, value, updated_at FROM workspace_settings WHERE workspace_id = ?1 ORDER BY key"
+  ).map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([workspace_id], |r| Ok(SettingsKV {
+    key: r.get(0)?,
+    value: r.get(1)?,
+    updated_at: r.get(2)?,
+  })).map_err(|e| e.to_string())?;
+  rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// Set a workspace default for member projects, or clear it with `None`.
+/// Project overrides still take precedence.
+pub fn set_workspace_setting(app: &AppHandle, workspace_id: &str, key: &str, value: Option<&str>) -> Result<(), String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  find_workspace(&conn, workspace_id)?;
+  match value {
+    Some(value) => conn.execute(
+      "INSERT INTO workspace_settings (workspace_id, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+       ON CONFLICT(workspace_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+      (workspace_id, key, value, now_iso())
+    ),
+    None => conn.execute(
+      "DELETE FROM workspace_settings WHERE workspace_id = ?1 AND key = ?2",
+      (workspace_id, key)
+    ),
+  }.map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+fn dashboard(conn: &Connection, workspace_id: &str) -> Result<WorkspaceDashboard, String> {
+  let workspace = find_workspace(conn, workspace_id)?;
+
+  let mut projects: Vec<WorkspaceProject> = {
+    let mut stmt = conn.prepare(
+      "SELECT p.id, p.name, p.repo_path, p.created_at, p.last_opened_at, p.workspace_id,
+         (SELECT MAX(r.started_at) FROM runs r JOIN tasks t ON t.id = r.task_id WHERE t.project_id = p.id)
+       FROM projects p WHERE p.workspace_id = ?1 ORDER BY p.name COLLATE NOCASE"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([workspace_id], |r| Ok(WorkspaceProject {
+      project: Project {
+        id: r.get(0)?,
+        name: r.get(1)?,
+        repo_path: r.get(2)?,
+        created_at: r.get(3)?,
+        last_opened_at: r.get(4)?,
+        workspace_id: r.get(5)?,
+      },
+      task_counts: BTreeMap::new(),
+      last_run_at: r.get(6)?,
+    })).map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+  };
+
+  let mut task_counts = BTreeMap::new();
+  {
+    let mut stmt = conn.prepare(
+      "SELECT t.project_id, t.status, COUNT(*) FROM tasks t JOIN projects p ON p.id = t.project_id
+       WHERE p.workspace_id = ?1 GROUP BY t.project_id, t.status"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([workspace_id], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, i64>(2)?)))
+      .map_err(|e| e.to_string())?;
+    for row in rows {
+      let (project_id, status, count) = row.map_err(|e| e.to_string())?;
+      *task_counts.entry(status.clone()).or_insert(0) += count;
+      if let Some(project) = projects.iter_mut().find(|p| p.project.id == project_id) {
+        project.task_counts.insert(status, count);
+      }
+    }
+  }
+
+  let recent_tasks = {
+    let mut stmt = conn.prepare(
+      "SELECT t.id, t.project_id, t.title, t.mode, t.status, t.created_at, t.updated_at, t.description, p.name
+       FROM tasks t JOIN projects p ON p.id = t.project_id
+       WHERE p.workspace_id = ?1 AND t.status != 'archived'
+       ORDER BY t.updated_at DESC LIMIT ?2"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map((workspace_id, DASHBOARD_LIMIT as i64), |r| Ok(WorkspaceTask {
+      task: Task {
+        id: r.get(0)?,
+        project_id: r.get(1)?,
+        title: r.get(2)?,
+        mode: r.get(3)?,
+        status: r.get(4)?,
+        created_at: r.get(5)?,
+        updated_at: r.get(6)?,
+        description: r.get(7)?,
+      },
+      project_name: r.get(8)?,
+    })).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+  };
+
+  let recent_runs = {
+    let mut stmt = conn.prepare(
+      "SELECT r.id, p.id, p.name, t.id, t.title, r.run_type, r.started_at, r.ended_at, r.error
+       FROM runs r JOIN tasks t ON t.id = r.task_id JOIN projects p ON p.id = t.project_id
+       WHERE p.workspace_id = ?1
+       ORDER BY r.started_at DESC LIMIT ?2"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map((workspace_id, DASHBOARD_LIMIT as i64), |r| Ok(WorkspaceRun {
+      run_id: r.get(0)?,
+      project_id: r.get(1)?,
+      project_name: r.get(2)?,
+      task_id: r.get(3)?,
+      task_title: r.get(4)?,
+      run_type: r.get(5)?,
+      started_at: r.get(6)?,
+      ended_at: r.get(7)?,
+      error: r.get(8)?,
+    })).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+  };
+
+  Ok(WorkspaceDashboard { workspace, projects, task_counts, recent_tasks, recent_runs })
+}
+
+/// Tasks and recent runs across the workspace's projects
+pub fn get_workspace_dashboard(app: &AppHandle, workspace_id: &str) -> Result<WorkspaceDashboard, String> {
+  let conn = db::connect(app).map_err(|e| e.to_string())?;
+  dashboard(&conn, workspace_id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_dashboard_aggregates_member_projects() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+      "CREATE TABLE projects (id TEXT PRIMARY KEY, name TEXT NOT NULL, repo_path TEXT NOT NULL, created_at TEXT NOT NULL, last_opened_at TEXT);
+       CREATE TABLE tasks (id TEXT PRIMARY KEY, project_id TEXT NOT NULL, title TEXT NOT NULL, mode TEXT NOT NULL,
+         status TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, description TEXT);
+       CREATE TABLE runs (id TEXT PRIMARY KEY, task_id TEXT NOT NULL, run_type TEXT NOT NULL, started_at TEXT NOT NULL,
+         ended_at TEXT, error TEXT);"
+    ).unwrap();
+    conn.execute_batch(include_str!("../migrations/032_workspaces.sql")).unwrap();
+    conn.execute_batch(
+      "INSERT INTO workspaces VALUES ('w1', 'Shop', '2026-03-01T09:00:00Z', '2026-03-01T09:00:00Z');
+       INSERT INTO projects VALUES ('web', 'web', '/web', '2026-03-01T09:00:00Z', NULL, 'w1');
+       INSERT INTO projects VALUES ('api', 'api', '/api', '2026-03-01T09:00:00Z', NULL, 'w1');
+       INSERT INTO projects VALUES ('other', 'other', '/other', '2026-03-01T09:00:00Z', NULL, NULL);
+       INSERT INTO tasks VALUES ('t1', 'web', 'Login form', 'plan', 'active', '2026-03-01T09:00:00Z', '2026-03-02T09:00:00Z', NULL);
+       INSERT INTO tasks VALUES ('t2', 'api', 'Login endpoint', 'plan', 'active', '2026-03-01T09:00:00Z', '2026-03-03T09:00:00Z', NULL);
+       INSERT INTO tasks VALUES ('t3', 'api', 'Old spike', 'plan', 'archived', '2026-03-01T09:00:00Z', '2026-03-04T09:00:00Z', NULL);
+       INSERT INTO tasks VALUES ('t4', 'other', 'Unrelated', 'plan', 'active', '2026-03-01T09:00:00Z', '2026-03-05T09:00:00Z', NULL);
+       INSERT INTO runs VALUES ('r1', 't1', 'plan', '2026-03-02T09:00:00Z', NULL, NULL);
+       INSERT INTO runs VALUES ('r2', 't2', 'verify', '2026-03-03T09:00:00Z', NULL, 'timeout');
+       INSERT INTO runs VALUES ('r3', 't4', 'plan', '2026-03-05T09:00:00Z', NULL, NULL);"
+    ).unwrap();
+
+    let dashboard = dashboard(&conn, "w1").unwrap();
+    assert_eq!(dashboard.workspace.project_count, 2);
+    assert_eq!(dashboard.task_counts.get("active"), Some(&2));
+    assert_eq!(dashboard.task_counts.get("archived"), Some(&1));
+    assert_eq!(dashboard.projects[0].project.id, "api");
+    assert_eq!(dashboard.projects[0].last_run_at.as_deref(), Some("2026-03-03T09:00:00Z"));
+    let tasks: Vec<&str> = dashboard.recent_tasks.iter().map(|t| t.task.id.as_str()).collect();
+    assert_eq!(tasks, vec!["t2", "t1"]);
+    let runs: Vec<&str> = dashboard.recent_runs.iter().map(|r| r.run_id.as_str()).collect();
+    assert_eq!(runs, vec!["r2", "r1"]);
+    assert_eq!(dashboard.recent_runs[0].error.as_deref(), Some("timeout"));
+  }
+}
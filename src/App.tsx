@@ -1,15 +1,28 @@
-import React from "react";
-import { Route, Routes } from "react-router-dom";
+import React, { useEffect } from "react";
+import { Route, Routes, useNavigate } from "react-router-dom";
+import { onNavigate } from "./lib/api";
 import { AppLayout } from "./ui";
 import Projects from "./routes/Projects";
 import ProjectDetail from "./routes/ProjectDetail";
 import TaskDetail from "./routes/TaskDetail";
 import RunDetail from "./routes/RunDetail";
 import Settings from "./routes/Settings";
+import QuickCapture from "./routes/QuickCapture";
 
 export default function App() {
+  const navigate = useNavigate();
+
+  // The tray can ask the main window to jump to a route
+  useEffect(() => {
+    const unlisten = onNavigate((path) => navigate(path));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [navigate]);
+
   return (
     <Routes>
+      <Route path="/capture" element={<QuickCapture />} />
       <Route element={<AppLayout />}>
         <Route path="/" element={<Projects />} />
         <Route path="/projects" element={<Projects />} />
@@ -0,0 +1,74 @@
+import { useState } from "react";
+import { Button, Checkbox, Group, SegmentedControl, Stack, Text, Textarea } from "@mantine/core";
+import { SectionCard } from "../ui";
+import { sendAppFeedback } from "../lib/api";
+import type { FeedbackKind } from "../lib/types";
+
+/**
+ * Report a problem or suggest an idea. Goes to feedback_endpoint when one is
+ * set, otherwise opens a pre-filled GitHub issue in the browser.
+ */
+export default function SendFeedback() {
+  const [kind, setKind] = useState<FeedbackKind>("bug");
+  const [text, setText] = useState("");
+  const [includeDiagnostics, setIncludeDiagnostics] = useState(true);
+  const [busy, setBusy] = useState(false);
+  const [message, setMessage] = useState<string | null>(null);
+
+  async function handleSubmit() {
+    setBusy(true);
+    setMessage(null);
+    try {
+      const result = await sendAppFeedback(kind, text, includeDiagnostics);
+      if (result.sent) {
+        setMessage("Thanks, your feedback was sent.");
+      } else if (result.diagnostics_path) {
+        setMessage(`Opened an issue in your browser. Attach the diagnostics bundle from ${result.diagnostics_path} before submitting it.`);
+      } else {
+        setMessage("Opened an issue in your browser.");
+      }
+      setText("");
+    } catch (error) {
+      setMessage(String(error));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  return (
+    <SectionCard title="Send feedback">
+      <Stack gap="md">
+        <SegmentedControl
+          value={kind}
+          onChange={(value) => setKind(value as FeedbackKind)}
+          data={[
+            { label: "Bug", value: "bug" },
+            { label: "Idea", value: "idea" },
+            { label: "Question", value: "question" },
+          ]}
+          disabled={busy}
+        />
+        <Textarea
+          placeholder={kind === "bug" ? "What happened, and what did you expect?" : "Tell us more"}
+          value={text}
+          onChange={(e) => setText(e.currentTarget.value)}
+          autosize
+          minRows={4}
+          disabled={busy}
+        />
+        <Checkbox
+          label="Include diagnostics (logs, system info, the last failed run, and settings with credentials redacted)"
+          checked={includeDiagnostics}
+          onChange={(e) => setIncludeDiagnostics(e.currentTarget.checked)}
+          disabled={busy}
+        />
+        {message && <Text size="sm">{message}</Text>}
+        <Group justify="flex-end">
+          <Button onClick={handleSubmit} loading={busy} disabled={!text.trim()}>
+            Send
+          </Button>
+        </Group>
+      </Stack>
+    </SectionCard>
+  );
+}
@@ -0,0 +1,127 @@
+import { useEffect, useState } from "react";
+import { Button, Code, Group, Stack, Switch, Text, TextInput } from "@mantine/core";
+import { SectionCard } from "../ui";
+import { clearTelemetry, getTelemetryPreview, setSetting, uploadTelemetry } from "../lib/api";
+import type { TelemetryPreview } from "../lib/types";
+
+/**
+ * Opt-in usage statistics: anonymous counts of runs per workflow and failure
+ * codes. Nothing is sent until the user has seen the exact payload below.
+ */
+export default function UsageStatistics() {
+  const [preview, setPreview] = useState<TelemetryPreview | null>(null);
+  const [endpoint, setEndpoint] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [message, setMessage] = useState<string | null>(null);
+
+  useEffect(() => {
+    refresh();
+  }, []);
+
+  async function refresh() {
+    try {
+      const next = await getTelemetryPreview();
+      setPreview(next);
+      setEndpoint(next.endpoint);
+    } catch (error) {
+      setMessage(`Failed to load usage statistics: ${error}`);
+    }
+  }
+
+  async function run(action: () => Promise<string | null>) {
+    setBusy(true);
+    setMessage(null);
+    try {
+      setMessage(await action());
+    } catch (error) {
+      setMessage(String(error));
+    } finally {
+      setBusy(false);
+      await refresh();
+    }
+  }
+
+  if (!preview) return null;
+  const empty = preview.report.counters.length === 0;
+
+  return (
+    <SectionCard title="Usage statistics">
+      <Stack gap="md">
+        <Text size="sm" c="dimmed">
+          Counts how often each workflow runs and which error codes runs fail with, per day, on this machine.
+          No code, paths, prompts or project names are recorded, and nothing is sent until you upload the report below.
+        </Text>
+
+        <Switch
+          label="Record usage statistics"
+          checked={preview.enabled}
+          disabled={busy}
+          onChange={(e) => {
+            const enabled = e.currentTarget.checked;
+            run(async () => {
+              await setSetting("telemetry_enabled", enabled ? "1" : "0");
+              return null;
+            });
+          }}
+        />
+
+        <Group align="flex-end">
+          <TextInput
+            label="Upload endpoint"
+            placeholder="https://…"
+            value={endpoint}
+            onChange={(e) => setEndpoint(e.target.value)}
+            disabled={busy}
+            style={{ flex: 1 }}
+          />
+          <Button
+            variant="default"
+            disabled={busy || endpoint === preview.endpoint}
+            onClick={() => run(async () => {
+              await setSetting("telemetry_endpoint", endpoint.trim());
+              return "Endpoint saved";
+            })}
+          >
+            Save
+          </Button>
+        </Group>
+
+        <Text size="sm" fw={500}>
+          {empty
+            ? "Nothing recorded since the last upload."
+            : `Report to be sent (${preview.report.from_day} to ${preview.report.to_day}):`}
+        </Text>
+        {!empty && (
+          <Code block styles={{ root: { maxHeight: 240, overflow: "auto" } }}>
+            {preview.payload}
+          </Code>
+        )}
+        {preview.last_upload && (
+          <Text size="xs" c="dimmed">
+            Last uploaded {new Date(preview.last_upload.uploaded_at).toLocaleString()} to {preview.last_upload.endpoint}
+          </Text>
+        )}
+        {message && <Text size="sm">{message}</Text>}
+
+        <Group justify="flex-end">
+          <Button variant="default" disabled={busy || empty} onClick={() => run(async () => {
+            await clearTelemetry();
+            return "Discarded the recorded statistics";
+          })}>
+            Discard
+          </Button>
+          <Button
+            disabled={busy || empty || !preview.enabled || !preview.endpoint}
+            loading={busy}
+            onClick={() => run(async () => {
+              await uploadTelemetry(preview.digest);
+              return "Report uploaded";
+            })}
+          >
+            Upload this report
+          </Button>
+        </Group>
+      </Stack>
+    </SectionCard>
+  );
+}
@@ -1,138 +1,763 @@
-import { invoke } from "@tauri-apps/api/core";
-import type { Project, Task, Run, Message, Artifact } from "./types";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { commands } from "./bindings";
+import type { AnnotationExport, AppliedTag, Artifact, Backup, Baseline, BudgetStatus, BulkTaskResult, CaptureResult, CheckResult, CiResults, CommandQueueEvent, CompanionStatus, ContextIgnoreConfig, ContextOverride, ContextPreview, CustomWorkflow, CustomWorkflowList, CustomWorkflowResult, DatabaseHealth, DebateResult, DemoSeedResult, DiagnosticsBundle, DriftResult, EditorLaunch, EmailResult, EncryptionStatus, EventSchema, ExportResult, Feedback, FeedbackKind, FeedbackSubmission, FeedbackSummary, GlossaryResult, HookScripts, ImportedIssue, IndexReport, IndexStatus, IntegrationConfig, IntegrationProvider, IntegrationStatus, JsonValue, LogEntry, Message, OfflineStatus, OverviewResult, PairRequest, Phase, PhaseBoard, PhaseUpdate, PhasesResult, PlanResult, PluginList, Project, ProjectArtifactKind, ProjectRemote, PromptPreview, PullResult, RegenerateResult, RenderBlock, RerunResult, ReviewOptions, ReviewResult, Run, RunEvent, RunNode, RunToolAnalysis, RunTranscript, RunningCommand, S3Credentials, SarifExport, SettingInput, SettingsExport, SettingsKV, SlackPostResult, StepResult, SyncRemote, SyncReport, SyncStatus, Tag, TagTarget, Task, TaskAttachment, TaskFilters, TaskLink, TaskPatch, TaskSearchPage, TaskTimeSummary, TelemetryPreview, TelemetryUpload, TestHistory, TokenEvent, ToolCallRow, ToolStat, TranscriptionResult, TypedSettings, UpdateInfo, VerifyOptions, VerifyResult, WalkthroughResult, Workspace, WorkspaceDashboard } from "./types";
 
 export async function dbHealth(): Promise<{ ok: boolean; path: string }> {
-  return invoke("db_health");
+  return commands.dbHealth() as Promise<{ ok: boolean; path: string }>;
 }
 
 export async function listProjects(): Promise<Project[]> {
-  return invoke("list_projects");
+  return commands.listProjects();
 }
 
 export async function createProject(name: string, repoPath: string): Promise<Project> {
-  return invoke("create_project", { name, repoPath });
+  return commands.createProject(name, repoPath);
+}
+
+export async function createProjectFromUrl(gitUrl: string, name?: string | null, branch?: string | null, shallow?: boolean): Promise<Project> {
+  return commands.createProjectFromUrl(gitUrl, name ?? null, branch ?? null, shallow ?? null);
+}
+
+export async function getProjectRemote(projectId: string): Promise<ProjectRemote | null> {
+  return commands.getProjectRemote(projectId);
+}
+
+export async function pullProject(projectId: string): Promise<PullResult> {
+  return commands.pullProject(projectId);
 }
 
 export async function touchProject(projectId: string): Promise<void> {
-  return invoke("touch_project", { projectId });
+  await commands.touchProject(projectId);
 }
 
 export async function getProject(projectId: string): Promise<Project> {
-  return invoke("get_project", { projectId });
+  return commands.getProject(projectId);
 }
 
 export async function listTasks(projectId: string): Promise<Task[]> {
-  return invoke("list_tasks", { projectId });
+  return commands.listTasks(projectId);
 }
 
 export async function createTask(projectId: string, title: string, mode: Task["mode"]): Promise<Task> {
-  return invoke("create_task", { projectId, title, mode });
+  return commands.createTask(projectId, title, mode);
 }
 
 export async function getTask(taskId: string): Promise<Task> {
-  return invoke("get_task", { taskId });
+  return commands.getTask(taskId);
+}
+
+export async function getTaskScopePath(taskId: string): Promise<string | null> {
+  return commands.getTaskScopePath(taskId);
+}
+
+/** Root the task's list_files, grep and git_diff at a subtree; null or "" clears it */
+export async function setTaskScopePath(taskId: string, scopePath: string | null): Promise<string | null> {
+  return commands.setTaskScopePath(taskId, scopePath);
+}
+
+/** Pages through a project's tasks; every word of `query` must appear in the title or description */
+export async function searchTasks(projectId: string, query: string | null, filters?: TaskFilters): Promise<TaskSearchPage> {
+  return commands.searchTasks(projectId, query, filters ?? null);
+}
+
+export async function listTags(): Promise<Tag[]> {
+  return commands.listTags();
+}
+
+export async function getTags(target: TagTarget, targetId: string): Promise<AppliedTag[]> {
+  return commands.getTags(target, targetId);
+}
+
+/** Tags a task or artifact; names are stored lowercase with spaces as dashes */
+export async function addTags(target: TagTarget, targetId: string, names: string[]): Promise<AppliedTag[]> {
+  return commands.addTags(target, targetId, names);
 }
 
-export async function listRuns(taskId: string): Promise<Run[]> {
-  return invoke("list_runs", { taskId });
+export async function removeTags(target: TagTarget, targetId: string, names: string[]): Promise<AppliedTag[]> {
+  return commands.removeTags(target, targetId, names);
+}
+
+export async function deleteTag(tagId: string): Promise<void> {
+  await commands.deleteTag(tagId);
+}
+
+/** Applies the same change to every task in one transaction; each task gets its own result */
+export async function bulkUpdateTasks(taskIds: string[], patch: TaskPatch): Promise<BulkTaskResult[]> {
+  return commands.bulkUpdateTasks(taskIds, patch);
+}
+
+export async function bulkArchiveTasks(taskIds: string[]): Promise<BulkTaskResult[]> {
+  return commands.bulkArchiveTasks(taskIds);
+}
+
+/** Newest first; `grouped` nests reruns, retries and regenerations under the run they came from */
+export async function listRuns(taskId: string, grouped?: boolean): Promise<RunNode[]> {
+  return commands.listRuns(taskId, grouped ?? null);
+}
+
+export async function getRunLineage(runId: string): Promise<RunNode> {
+  return commands.getRunLineage(runId);
+}
+
+/** Stop a run in progress; it ends at its next model call or tool execution */
+export async function cancelRun(runId: string): Promise<void> {
+  await commands.cancelRun(runId);
 }
 
 export async function createRun(taskId: string, runType: Run["run_type"]): Promise<Run> {
-  return invoke("create_run", { taskId, runType });
+  return commands.createRun(taskId, runType);
 }
 
 export async function listMessages(runId: string): Promise<Message[]> {
-  return invoke("list_messages", { runId });
+  return commands.listMessages(runId);
 }
 
 export async function addMessage(runId: string, role: Message["role"], content: string): Promise<Message> {
-  return invoke("add_message", { runId, role, content });
+  return commands.addMessage(runId, role, content);
 }
 
-export async function listArtifacts(taskId: string): Promise<Artifact[]> {
-  return invoke("list_artifacts", { taskId });
+export async function listArtifacts(taskId: string, tag?: string): Promise<Artifact[]> {
+  return commands.listArtifacts(taskId, tag ?? null);
 }
 
 export async function upsertArtifact(taskId: string, phaseId: string | null, kind: Artifact["kind"], content: string): Promise<Artifact> {
-  return invoke("upsert_artifact", { taskId, phaseId, kind, content });
+  return commands.upsertArtifact(taskId, phaseId, kind, content);
+}
+
+export async function getArtifact(artifactId: string): Promise<Artifact> {
+  return commands.getArtifact(artifactId);
+}
+
+/** Writes a styled standalone report; PDF needs Chrome, Chromium, or Edge installed. */
+export async function exportArtifact(artifactId: string, format: "html" | "pdf", path: string): Promise<ExportResult> {
+  return commands.exportArtifact(artifactId, format, path);
+}
+
+/** Sends the task's latest verification report using the smtp_* settings. */
+export async function emailReport(taskId: string, recipients: string[]): Promise<EmailResult> {
+  return commands.emailReport(taskId, recipients);
+}
+
+/** Posts the latest verification report; with a bot token the full report follows in a thread. */
+export async function postToSlack(taskId: string, channel?: string | null): Promise<SlackPostResult> {
+  return commands.postToSlack(taskId, channel ?? null);
+}
+
+/** Report findings that cite files, as `::error`/`::warning`/`::notice` lines for a CI step to print. */
+export async function exportGithubAnnotations(taskId: string, path?: string | null): Promise<AnnotationExport> {
+  return commands.exportGithubAnnotations(taskId, path ?? null);
+}
+
+/** SARIF 2.1.0 log of the report's file-specific findings, for code-scanning upload. */
+export async function exportSarif(taskId: string, path?: string | null): Promise<SarifExport> {
+  return commands.exportSarif(taskId, path ?? null);
+}
+
+/** Opens a repo-relative path at `line` using the `editor` setting (vscode, cursor, jetbrains, or custom). */
+export async function openInEditor(projectId: string, path: string, line?: number | null): Promise<EditorLaunch> {
+  return commands.openInEditor(projectId, path, line ?? null);
+}
+
+/** Markdown split into renderable blocks; invalid mermaid comes back as `mermaid_error`. */
+export async function getArtifactRenderBlocks(artifactId: string): Promise<RenderBlock[]> {
+  return commands.getArtifactRenderBlocks(artifactId);
+}
+
+// Phase board API
+export async function getPhaseBoard(taskId: string): Promise<PhaseBoard> {
+  return commands.getPhaseBoard(taskId);
+}
+
+export async function setPhaseDependencies(phaseId: string, dependsOn: string[]): Promise<void> {
+  await commands.setPhaseDependencies(phaseId, dependsOn);
+}
+
+export async function listPhases(taskId: string): Promise<Phase[]> {
+  return commands.listPhases(taskId);
+}
+
+/** Add a phase after the task's last one */
+export async function createPhase(taskId: string, title: string, description: string | null, plannedFiles: string[]): Promise<Phase> {
+  return commands.createPhase(taskId, title, description, plannedFiles);
+}
+
+export async function updatePhase(phaseId: string, update: PhaseUpdate): Promise<Phase> {
+  return commands.updatePhase(phaseId, update);
+}
+
+/** Delete a phase along with its phase-scoped artifacts */
+export async function deletePhase(phaseId: string): Promise<void> {
+  await commands.deletePhase(phaseId);
+}
+
+/** Break the task's saved plan into phases; fails if the task already has phases */
+export async function generatePhases(taskId: string): Promise<PhasesResult> {
+  return commands.generatePhases(taskId);
+}
+
+/** Review a branch against `base`, the staged changes, or the working tree; no plan needed */
+export async function reviewChanges(taskId: string, options: ReviewOptions): Promise<ReviewResult> {
+  return commands.reviewChanges(taskId, options);
+}
+
+// Task attachments API
+export async function listTaskAttachments(taskId: string): Promise<TaskAttachment[]> {
+  return commands.listTaskAttachments(taskId);
+}
+
+export async function addTaskNote(taskId: string, name: string, content: string): Promise<TaskAttachment> {
+  return commands.addTaskNote(taskId, name, content);
+}
+
+export async function attachTaskFile(taskId: string, path: string): Promise<TaskAttachment> {
+  return commands.attachTaskFile(taskId, path);
+}
+
+export async function deleteTaskAttachment(attachmentId: string): Promise<void> {
+  await commands.deleteTaskAttachment(attachmentId);
+}
+
+// Audio intake API
+export async function transcribeAudio(
+  path: string,
+  target: { taskId?: string; projectId?: string }
+): Promise<TranscriptionResult> {
+  return commands.transcribeAudio(path, target.taskId ?? null, target.projectId ?? null);
 }
 
 // Settings API
-export async function getSettings(): Promise<Array<{ key: string; value: string; updated_at: string }>> {
-  return invoke("get_settings");
+export async function getSettings(): Promise<SettingsKV[]> {
+  return commands.getSettings();
+}
+
+export async function getSettingsTyped(): Promise<TypedSettings> {
+  return commands.getSettingsTyped();
+}
+
+/** Write the global settings to a file, without credentials */
+export async function exportSettings(path: string): Promise<SettingsExport> {
+  return commands.exportSettings(path);
+}
+
+/** Store the settings from an exported file; returns how many were imported */
+export async function importSettings(path: string): Promise<number> {
+  return commands.importSettings(path);
 }
 
 export async function getSetting(key: string): Promise<string | null> {
-  return invoke("get_setting", { key });
+  return commands.getSetting(key);
 }
 
 export async function setSetting(key: string, value: string): Promise<void> {
-  return invoke("set_setting", { key, value });
+  await commands.setSetting(key, value);
+}
+
+export async function setSettings(pairs: SettingInput[]): Promise<void> {
+  await commands.setSettings(pairs);
+}
+
+export async function getProjectSettings(projectId: string): Promise<SettingsKV[]> {
+  return commands.getProjectSettings(projectId);
+}
+
+/** Pass `null` to clear the override and use the workspace or global setting. */
+export async function setProjectSetting(projectId: string, key: string, value: string | null): Promise<void> {
+  await commands.setProjectSetting(projectId, key, value);
+}
+
+export async function getContextIgnore(projectId: string): Promise<ContextIgnoreConfig> {
+  return commands.getContextIgnore(projectId);
+}
+
+/** Globs like "*.min.js" or "fixtures/**" kept out of list_files, grep, read_file and diffs */
+export async function setContextIgnore(projectId: string, globs: string[]): Promise<void> {
+  await commands.setContextIgnore(projectId, globs);
+}
+
+/** Runs `script` when the project's standard one for `kind` is missing; null stops falling back */
+export async function approveScriptFallback(projectId: string, kind: CheckResult["kind"], script: string | null): Promise<void> {
+  await commands.approveScriptFallback(projectId, kind, script);
+}
+
+/** Builds or refreshes the embeddings index used when `planning_strategy` is "retrieval" */
+export async function indexRepository(projectId: string): Promise<IndexReport> {
+  return commands.indexRepository(projectId);
+}
+
+export async function getIndexStatus(projectId: string): Promise<IndexStatus> {
+  return commands.getIndexStatus(projectId);
+}
+
+// Workspaces API (groups of projects; workspace settings apply to member projects without an override)
+export async function listWorkspaces(): Promise<Workspace[]> {
+  return commands.listWorkspaces();
+}
+
+export async function createWorkspace(name: string): Promise<Workspace> {
+  return commands.createWorkspace(name);
+}
+
+export async function renameWorkspace(workspaceId: string, name: string): Promise<Workspace> {
+  return commands.renameWorkspace(workspaceId, name);
+}
+
+/** Projects in the workspace are kept and become ungrouped */
+export async function deleteWorkspace(workspaceId: string): Promise<void> {
+  await commands.deleteWorkspace(workspaceId);
+}
+
+/** Pass `null` to remove the project from its workspace. */
+export async function setProjectWorkspace(projectId: string, workspaceId: string | null): Promise<void> {
+  await commands.setProjectWorkspace(projectId, workspaceId);
+}
+
+export async function getWorkspaceSettings(workspaceId: string): Promise<SettingsKV[]> {
+  return commands.getWorkspaceSettings(workspaceId);
+}
+
+/** Pass `null` to clear the workspace default. */
+export async function setWorkspaceSetting(workspaceId: string, key: string, value: string | null): Promise<void> {
+  await commands.setWorkspaceSetting(workspaceId, key, value);
+}
+
+export async function getWorkspaceDashboard(workspaceId: string): Promise<WorkspaceDashboard> {
+  return commands.getWorkspaceDashboard(workspaceId);
+}
+
+// Offline mode API (toggle with the offline_mode setting)
+export async function getOfflineStatus(): Promise<OfflineStatus> {
+  return commands.getOfflineStatus();
+}
+
+export async function cancelQueuedRun(id: string): Promise<void> {
+  await commands.cancelQueuedRun(id);
+}
+
+// Usage statistics: anonymous counters, only recorded when telemetry_enabled is on
+export async function getTelemetryPreview(): Promise<TelemetryPreview> {
+  return commands.getTelemetryPreview();
+}
+
+/** Send the previewed report; fails if the counters changed since the preview */
+export async function uploadTelemetry(digest: string): Promise<TelemetryUpload> {
+  return commands.uploadTelemetry(digest);
+}
+
+export async function clearTelemetry(): Promise<void> {
+  await commands.clearTelemetry();
+}
+
+/** Post feedback to feedback_endpoint, or open a pre-filled GitHub issue without one */
+export async function sendAppFeedback(kind: FeedbackKind, text: string, includeDiagnostics: boolean): Promise<FeedbackSubmission> {
+  return commands.sendAppFeedback(kind, text, includeDiagnostics);
+}
+
+export async function getDatabaseEncryption(): Promise<EncryptionStatus> {
+  return commands.getDatabaseEncryption();
+}
+
+/** Rewrites all stored excerpts; may take a while on large databases */
+export async function setDatabaseEncryption(enabled: boolean): Promise<EncryptionStatus> {
+  return commands.setDatabaseEncryption(enabled);
+}
+
+export async function getDatabaseHealth(): Promise<DatabaseHealth> {
+  return commands.getDatabaseHealth();
+}
+
+export async function listBackups(): Promise<Backup[]> {
+  return commands.listBackups();
+}
+
+/** Replaces the database with a backup; the current database is backed up first */
+export async function restoreBackup(backupId: string): Promise<Backup> {
+  return commands.restoreBackup(backupId);
+}
+
+export async function getSyncStatus(): Promise<SyncStatus> {
+  return commands.getSyncStatus();
+}
+
+/** Sets up sync with a git or S3 remote, or turns it off with null; omit credentials to keep the saved S3 key */
+export async function configureSync(remote: SyncRemote | null, credentials?: S3Credentials): Promise<SyncStatus> {
+  return commands.configureSync(remote, credentials ?? null);
+}
+
+/** Pulls changes from other devices, then pushes this one's */
+export async function syncNow(): Promise<SyncReport> {
+  return commands.syncNow();
 }
 
-export async function setSettings(pairs: Array<{ key: string; value: string }>): Promise<void> {
-  return invoke("set_settings", { pairs });
+/** Fired when offline mode turns on or off; queued runs start automatically when back online. */
+export async function onConnectivityChanged(handler: (status: OfflineStatus) => void): Promise<UnlistenFn> {
+  return listen<OfflineStatus>("connectivity-changed", (e) => handler(e.payload));
+}
+
+// Monthly budgets API
+export async function getBudgetStatus(projectId: string): Promise<BudgetStatus> {
+  return commands.getBudgetStatus(projectId);
+}
+
+/** Allow runs over budget for the rest of the month (after a BUDGET_CONFIRMATION_REQUIRED error). */
+export async function confirmBudgetOverrun(projectId: string): Promise<void> {
+  await commands.confirmBudgetOverrun(projectId);
+}
+
+/** Fired when a run starts with the project at 80% or more of its monthly budget. */
+export async function onBudgetWarning(handler: (status: BudgetStatus) => void): Promise<UnlistenFn> {
+  return listen<BudgetStatus>("budget-warning", (e) => handler(e.payload));
+}
+
+// Companion API (VS Code extension)
+/** Server state and paired clients; enable with the companion_api_enabled setting. */
+export async function getCompanionStatus(): Promise<CompanionStatus> {
+  return commands.getCompanionStatus();
+}
+
+export async function revokeCompanionClient(clientId: string): Promise<void> {
+  await commands.revokeCompanionClient(clientId);
+}
+
+/** Fired when an extension starts pairing; show `code` for the user to type into the editor. */
+export async function onCompanionPairRequest(
+  handler: (request: PairRequest) => void
+): Promise<UnlistenFn> {
+  return listen<PairRequest>("companion-pair-request", (event) => handler(event.payload));
+}
+
+// Task time tracking API
+export async function getTaskTimeSummary(taskId: string): Promise<TaskTimeSummary> {
+  return commands.getTaskTimeSummary(taskId);
+}
+
+// Issue tracker integrations API
+export async function getIntegrationStatus(projectId: string, provider: IntegrationProvider): Promise<IntegrationStatus> {
+  return commands.getIntegrationStatus(projectId, provider);
+}
+
+export async function setIntegrationConfig(projectId: string, provider: IntegrationProvider, config: IntegrationConfig): Promise<void> {
+  await commands.setIntegrationConfig(projectId, provider, config);
+}
+
+/** Stores the token in the OS keychain; an empty string removes it. */
+export async function setIntegrationToken(provider: IntegrationProvider, token: string): Promise<void> {
+  await commands.setIntegrationToken(provider, token);
+}
+
+/** Creates a task from a Jira key (ABC-123) or Linear identifier (ENG-42). */
+export async function importIssue(projectId: string, provider: IntegrationProvider, key: string): Promise<ImportedIssue> {
+  return commands.importIssue(projectId, provider, key);
+}
+
+export async function listTaskLinks(taskId: string): Promise<TaskLink[]> {
+  return commands.listTaskLinks(taskId);
+}
+
+/** Comments the plan summary and latest verdict on every linked issue. */
+export async function pushIssueComment(taskId: string): Promise<TaskLink[]> {
+  return commands.pushIssueComment(taskId);
+}
+
+// Diagnostics API
+export async function getRecentLogs(
+  level: "trace" | "debug" | "info" | "warn" | "error" = "info",
+  limit = 200
+): Promise<LogEntry[]> {
+  return commands.getRecentLogs(level, limit);
+}
+
+/** Writes a zip for support; defaults to a timestamped file in app data. */
+export async function exportDiagnostics(path?: string): Promise<DiagnosticsBundle> {
+  return commands.exportDiagnostics(path ?? null);
+}
+
+// Onboarding API
+export async function onboardingDetectRuntimes(): Promise<StepResult[]> {
+  return commands.onboardingDetectRuntimes();
+}
+
+export async function onboardingTestLlm(): Promise<StepResult> {
+  return commands.onboardingTestLlm();
+}
+
+export async function onboardingCreateProject(repoPath: string, name?: string): Promise<StepResult> {
+  return commands.onboardingCreateProject(repoPath, name ?? null);
+}
+
+export async function onboardingSmokePlan(projectId: string): Promise<StepResult> {
+  return commands.onboardingSmokePlan(projectId);
+}
+
+// Updates API
+export async function checkForUpdates(): Promise<UpdateInfo> {
+  return commands.checkForUpdates();
+}
+
+/** Downloads and installs the update, then restarts the app. */
+export async function installUpdate(): Promise<void> {
+  await commands.installUpdate();
+}
+
+// Demo mode API
+/** Seeds a read-only demo project from bundled fixtures (no API calls). Idempotent. */
+export async function seedDemoData(): Promise<DemoSeedResult> {
+  return commands.seedDemoData();
+}
+
+/** Removes all demo projects; returns how many were deleted. */
+export async function clearDemoData(): Promise<number> {
+  return commands.clearDemoData();
+}
+
+// Multi-window
+/** Opens (or focuses) a window for the task; resolves to its window label. */
+export async function openTaskWindow(taskId: string): Promise<string> {
+  return commands.openTaskWindow(taskId);
+}
+
+/** Limits this window's run events to a project and/or task; omit both to receive all. */
+export async function subscribeWindow(projectId?: string | null, taskId?: string | null): Promise<void> {
+  await commands.subscribeWindow(projectId ?? null, taskId ?? null);
+}
+
+export async function unsubscribeWindow(): Promise<void> {
+  await commands.unsubscribeWindow();
+}
+
+export async function onRunEvent(handler: (event: RunEvent) => void): Promise<UnlistenFn> {
+  return listen<RunEvent>("run-event", (e) => handler(e.payload));
+}
+
+/** Every backend event with a JSON schema of its payload, for generating listener types. */
+export async function listEventSchemas(): Promise<EventSchema[]> {
+  return commands.listEventSchemas();
+}
+
+/** Fired with each fragment of a plan as it's generated (when the stream_responses setting is on). */
+export async function onPlanToken(handler: (event: TokenEvent) => void): Promise<UnlistenFn> {
+  return listen<TokenEvent>("plan_token", (e) => handler(e.payload));
+}
+
+/** Fired with each fragment of a verification report as it's generated. */
+export async function onVerifyToken(handler: (event: TokenEvent) => void): Promise<UnlistenFn> {
+  return listen<TokenEvent>("verify_token", (e) => handler(e.payload));
+}
+
+// Tray quick actions
+/** Starts verifying the task last opened in any window; resolves to its task id. */
+export async function verifyCurrentTask(): Promise<string> {
+  return commands.verifyCurrentTask();
+}
+
+export async function openLastProject(): Promise<string> {
+  return commands.openLastProject();
+}
+
+// Quick capture
+/** Creates a task in the most recently opened project. */
+export async function quickCapture(title: string, includeClipboard: boolean): Promise<CaptureResult> {
+  return commands.quickCapture(title, includeClipboard);
+}
+
+export async function onNavigate(handler: (path: string) => void): Promise<UnlistenFn> {
+  return listen<string>("navigate", (e) => handler(e.payload));
 }
 
 // Tool calls API
-export async function listToolCalls(runId: string): Promise<Array<{
-  id: string;
-  run_id: string;
-  name: string;
-  args_json: string;
-  result_json: string;
-  created_at: string;
-}>> {
-  return invoke("list_tool_calls_cmd", { runId });
+export async function listToolCalls(runId: string): Promise<ToolCallRow[]> {
+  return commands.listToolCallsCmd(runId);
+}
+
+export async function getRunTranscript(runId: string): Promise<RunTranscript> {
+  return commands.getRunTranscript(runId);
 }
 
 export async function executeRepoTool(
   runId: string,
   projectId: string,
   name: string,
-  args: Record<string, unknown>
-): Promise<unknown> {
-  return invoke("execute_repo_tool", { runId, projectId, name, args });
+  args: JsonValue
+): Promise<JsonValue> {
+  return commands.executeRepoTool(runId, projectId, name, args);
+}
+
+export async function getRepoToolSchemas(): Promise<JsonValue[]> {
+  return commands.getRepoToolSchemas();
+}
+
+export async function getToolStats(): Promise<ToolStat[]> {
+  return commands.getToolStats();
+}
+
+export async function analyzeRunTools(runId: string, threshold?: number): Promise<RunToolAnalysis> {
+  return commands.analyzeRunTools(runId, threshold ?? null);
+}
+
+// Prompt templates API
+export async function previewPrompt(workflow: "plan" | "verify", taskId: string): Promise<PromptPreview> {
+  return commands.previewPrompt(workflow, taskId);
+}
+
+/** Files and sections a run would gather; nothing is sent to the model */
+export async function previewContext(taskId: string, workflow: "plan" | "verify"): Promise<ContextPreview> {
+  return commands.previewContext(taskId, workflow);
+}
+
+export async function listContextOverrides(taskId: string): Promise<ContextOverride[]> {
+  return commands.listContextOverrides(taskId);
+}
+
+/** Pins a file into the task's plan prompts or hides it from its runs; null clears the override */
+export async function setContextOverride(taskId: string, path: string, action: "pin" | "exclude" | null): Promise<ContextOverride[]> {
+  return commands.setContextOverride(taskId, path, action);
+}
+
+/** Explains the task's current diff file by file, in the order a reviewer should read it */
+export async function explainDiff(taskId: string): Promise<WalkthroughResult> {
+  return commands.explainDiff(taskId);
+}
+
+/** Writes a newcomer-oriented architecture overview of the project, going deeper on `focus` when given */
+export async function explainRepo(projectId: string, focus?: string): Promise<OverviewResult> {
+  return commands.explainRepo(projectId, focus ?? null);
+}
+
+/** Extracts the project's domain terms into its glossary, which plan and verify prompts always include */
+export async function extractGlossary(projectId: string): Promise<GlossaryResult> {
+  return commands.extractGlossary(projectId);
+}
+
+/** The project's saved workflows and the ones its repo's .spectrail.toml defines */
+export async function listCustomWorkflows(projectId: string): Promise<CustomWorkflowList> {
+  return commands.listCustomWorkflows(projectId);
+}
+
+/** Saves a workflow from its JSON or TOML definition, replacing one of the same name */
+export async function saveCustomWorkflow(projectId: string, name: string, definition: string): Promise<CustomWorkflow> {
+  return commands.saveCustomWorkflow(projectId, name, definition);
+}
+
+export async function deleteCustomWorkflow(projectId: string, name: string): Promise<void> {
+  await commands.deleteCustomWorkflow(projectId, name);
+}
+
+/** Runs one of the task's project's workflows and saves its answer as the artifact kind it defines */
+export async function runCustomWorkflow(name: string, taskId: string): Promise<CustomWorkflowResult> {
+  return commands.runCustomWorkflow(name, taskId);
+}
+
+/** Scripts in the hooks directory that a project's lifecycle_hooks setting can run */
+export async function listHookScripts(): Promise<HookScripts> {
+  return commands.listHookScripts();
+}
+
+/** Re-read the tool plugin manifests in the app's plugins directory */
+export async function reloadToolPlugins(): Promise<PluginList> {
+  return commands.reloadToolPlugins();
+}
+
+/** The project's artifacts, newest first, optionally of one kind */
+export async function listProjectArtifacts(projectId: string, kind?: ProjectArtifactKind): Promise<Artifact[]> {
+  return commands.listProjectArtifacts(projectId, kind ?? null);
 }
 
-export async function getRepoToolSchemas(): Promise<Array<{
-  type: string;
-  function: {
-    name: string;
-    description: string;
-    parameters: unknown;
-  };
-}>> {
-  return invoke("get_repo_tool_schemas");
+/** Regenerates the repo map now; also runs every `repo_map_refresh_hours` */
+export async function refreshRepoMap(projectId: string): Promise<DriftResult> {
+  return commands.refreshRepoMap(projectId);
+}
+
+/** Saves the project's artifact of `kind`, replacing the current one */
+export async function upsertProjectArtifact(projectId: string, kind: ProjectArtifactKind, content: string): Promise<Artifact> {
+  return commands.upsertProjectArtifact(projectId, kind, content);
+}
+
+/** Pinned project artifacts go into plan and verify prompts; conventions, the glossary and the structure drift summary always do */
+export async function setProjectArtifactPinned(artifactId: string, pinned: boolean): Promise<Artifact> {
+  return commands.setProjectArtifactPinned(artifactId, pinned);
+}
+
+export async function deleteProjectArtifact(artifactId: string): Promise<void> {
+  await commands.deleteProjectArtifact(artifactId);
+}
+
+/** Rates a plan or verification report; with `feedback_in_prompt` on, the next run replacing it sees the feedback */
+export async function submitFeedback(artifactId: string, rating: "up" | "down", comment?: string): Promise<Feedback> {
+  return commands.submitFeedback(artifactId, rating, comment ?? null);
+}
+
+export async function listFeedback(taskId: string): Promise<Feedback[]> {
+  return commands.listFeedback(taskId);
+}
+
+export async function deleteFeedback(feedbackId: string): Promise<void> {
+  await commands.deleteFeedback(feedbackId);
+}
+
+/** Approval rates per artifact kind for a project, or all projects when omitted */
+export async function getFeedbackSummary(projectId?: string): Promise<FeedbackSummary> {
+  return commands.getFeedbackSummary(projectId ?? null);
+}
+
+// CI results API
+/** Parses JUnit XML or a GitHub Actions job log; verify with `use_ci_results` to review it instead of running tests. */
+export async function ingestCiResults(taskId: string, pathOrUrl: string): Promise<CiResults> {
+  return commands.ingestCiResults(taskId, pathOrUrl);
+}
+
+/** Runs the checks (tests, lint and build by default) on the pre-change state; verify then reports deltas against it. */
+export async function captureBaseline(taskId: string, kinds?: string[]): Promise<Baseline> {
+  return commands.captureBaseline(taskId, kinds ?? null);
+}
+
+export async function getBaseline(taskId: string): Promise<Baseline | null> {
+  return commands.getBaseline(taskId);
+}
+
+export async function listTestStats(projectId: string, flakyOnly?: boolean): Promise<TestHistory[]> {
+  return commands.listTestStats(projectId, flakyOnly ?? null);
+}
+
+export async function resetTestStats(projectId: string): Promise<void> {
+  await commands.resetTestStats(projectId);
+}
+
+/** Fired when run_command waits for another run's command in the same repo, and when it starts. */
+export async function onCommandQueue(handler: (event: CommandQueueEvent) => void): Promise<UnlistenFn> {
+  return listen<CommandQueueEvent>("command-queue", (e) => handler(e.payload));
+}
+
+/** Kill every command started by run_command, with their child processes */
+export async function killAllRunningCommands(): Promise<RunningCommand[]> {
+  return commands.killAllRunningCommands();
 }
 
 // Plan workflow API
 export async function generatePlan(
   projectId: string,
   taskId: string
-): Promise<{
-  run_id: string;
-  plan_md: string;
-  tool_calls_count: number;
-  truncated: boolean;
-}> {
-  return invoke("generate_plan_command", { projectId, taskId });
+): Promise<PlanResult> {
+  return commands.generatePlanCommand(projectId, taskId);
 }
 
 export async function verifyTask(
   projectId: string,
   taskId: string,
-  options?: {
-    run_tests?: boolean;
-    run_lint?: boolean;
-    run_build?: boolean;
-    staged?: boolean;
-  }
-): Promise<{
-  run_id: string;
-  report_md: string;
-  ran_checks: { tests: boolean; lint: boolean; build: boolean };
-  truncated: boolean;
-}> {
-  return invoke("verify_task_command", { projectId, taskId, options });
+  options?: VerifyOptions
+): Promise<VerifyResult> {
+  return commands.verifyTaskCommand(projectId, taskId, options ?? null);
+}
+
+/** Experimental (needs the `plan_debate` setting): two planners draft competing plans and a judge merges them into the task's plan */
+export async function debatePlan(projectId: string, taskId: string, modelA?: string, modelB?: string): Promise<DebateResult> {
+  return commands.debatePlan(projectId, taskId, modelA ?? null, modelB ?? null);
+}
+
+/** Repeats a plan or verify run with its recorded seed; rejects for runs made without one. */
+export async function rerunWithSameSeed(runId: string): Promise<RerunResult> {
+  return commands.rerunWithSameSeed(runId);
+}
+
+/** Starts a new plan run from the conversation up to an assistant message (and its tool results); later turns are regenerated. */
+export async function regenerateFromMessage(runId: string, messageId: string): Promise<RegenerateResult> {
+  return commands.regenerateFromMessage(runId, messageId);
 }
@@ -0,0 +1,1643 @@
+// Generated by src-tauri/src/bindings.rs; run `npm run bindings` instead of editing
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+async dbHealth() : Promise<JsonValue> {
+    return await TAURI_INVOKE("db_health");
+},
+async listProjects() : Promise<Project[]> {
+    return await TAURI_INVOKE("list_projects");
+},
+async createProject(name: string, repoPath: string) : Promise<Project> {
+    return await TAURI_INVOKE("create_project", { name, repoPath });
+},
+/**
+ * Clone `git_url` (shallow when asked) into app data and add it as a project
+ */
+async createProjectFromUrl(gitUrl: string, name: string | null, branch: string | null, shallow: boolean | null) : Promise<Project> {
+    return await TAURI_INVOKE("create_project_from_url", { gitUrl, name, branch, shallow });
+},
+async getProjectRemote(projectId: string) : Promise<ProjectRemote | null> {
+    return await TAURI_INVOKE("get_project_remote", { projectId });
+},
+async pullProject(projectId: string) : Promise<PullResult> {
+    return await TAURI_INVOKE("pull_project", { projectId });
+},
+async touchProject(projectId: string) : Promise<null> {
+    return await TAURI_INVOKE("touch_project", { projectId });
+},
+async getProject(projectId: string) : Promise<Project> {
+    return await TAURI_INVOKE("get_project", { projectId });
+},
+async listTasks(projectId: string) : Promise<Task[]> {
+    return await TAURI_INVOKE("list_tasks", { projectId });
+},
+async createTask(projectId: string, title: string, mode: string) : Promise<Task> {
+    return await TAURI_INVOKE("create_task", { projectId, title, mode });
+},
+async getTask(taskId: string) : Promise<Task> {
+    return await TAURI_INVOKE("get_task", { taskId });
+},
+async getTaskScopePath(taskId: string) : Promise<string | null> {
+    return await TAURI_INVOKE("get_task_scope_path", { taskId });
+},
+/**
+ * Limit the task's repo tools to a subtree such as services/billing; empty clears it
+ */
+async setTaskScopePath(taskId: string, scopePath: string | null) : Promise<string | null> {
+    return await TAURI_INVOKE("set_task_scope_path", { taskId, scopePath });
+},
+/**
+ * A page of the project's tasks matching a text query and filters
+ */
+async searchTasks(projectId: string, query: string | null, filters: TaskFilters | null) : Promise<TaskSearchPage> {
+    return await TAURI_INVOKE("search_tasks", { projectId, query, filters });
+},
+/**
+ * Apply the same change to many tasks in one transaction, with a result per task
+ */
+async bulkUpdateTasks(taskIds: string[], patch: TaskPatch) : Promise<BulkTaskResult[]> {
+    return await TAURI_INVOKE("bulk_update_tasks", { taskIds, patch });
+},
+async bulkArchiveTasks(taskIds: string[]) : Promise<BulkTaskResult[]> {
+    return await TAURI_INVOKE("bulk_archive_tasks", { taskIds });
+},
+async listRuns(taskId: string, grouped: boolean | null) : Promise<RunNode[]> {
+    return await TAURI_INVOKE("list_runs", { taskId, grouped });
+},
+/**
+ * The tree of reruns, retries and regenerations the run belongs to
+ */
+async getRunLineage(runId: string) : Promise<RunNode> {
+    return await TAURI_INVOKE("get_run_lineage", { runId });
+},
+/**
+ * Stop a run in progress at its next model call or tool execution, killing
+ * the commands it started; it ends with the "Run cancelled" error
+ */
+async cancelRun(runId: string) : Promise<null> {
+    return await TAURI_INVOKE("cancel_run", { runId });
+},
+async createRun(taskId: string, runType: string) : Promise<Run> {
+    return await TAURI_INVOKE("create_run", { taskId, runType });
+},
+async listMessages(runId: string) : Promise<Message[]> {
+    return await TAURI_INVOKE("list_messages", { runId });
+},
+async addMessage(runId: string, role: string, content: string) : Promise<Message> {
+    return await TAURI_INVOKE("add_message", { runId, role, content });
+},
+async listArtifacts(taskId: string, tag: string | null) : Promise<Artifact[]> {
+    return await TAURI_INVOKE("list_artifacts", { taskId, tag });
+},
+/**
+ * All tags with how many tasks and artifacts use each
+ */
+async listTags() : Promise<Tag[]> {
+    return await TAURI_INVOKE("list_tags");
+},
+async getTags(target: TagTarget, targetId: string) : Promise<AppliedTag[]> {
+    return await TAURI_INVOKE("get_tags", { target, targetId });
+},
+/**
+ * Tag a task or artifact; unknown tag names are created
+ */
+async addTags(target: TagTarget, targetId: string, names: string[]) : Promise<AppliedTag[]> {
+    return await TAURI_INVOKE("add_tags", { target, targetId, names });
+},
+async removeTags(target: TagTarget, targetId: string, names: string[]) : Promise<AppliedTag[]> {
+    return await TAURI_INVOKE("remove_tags", { target, targetId, names });
+},
+async deleteTag(tagId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_tag", { tagId });
+},
+async upsertArtifact(taskId: string, phaseId: string | null, kind: string, content: string) : Promise<Artifact> {
+    return await TAURI_INVOKE("upsert_artifact", { taskId, phaseId, kind, content });
+},
+async getArtifact(artifactId: string) : Promise<Artifact> {
+    return await TAURI_INVOKE("get_artifact", { artifactId });
+},
+/**
+ * Artifact content split into markdown and validated mermaid blocks
+ */
+async getArtifactRenderBlocks(artifactId: string) : Promise<RenderBlock[]> {
+    return await TAURI_INVOKE("get_artifact_render_blocks", { artifactId });
+},
+/**
+ * Write an artifact as a standalone HTML or PDF report to `path`
+ */
+async exportArtifact(artifactId: string, format: string, path: string) : Promise<ExportResult> {
+    return await TAURI_INVOKE("export_artifact", { artifactId, format, path });
+},
+/**
+ * Send the task's latest verification report over the configured SMTP server
+ */
+async emailReport(taskId: string, recipients: string[]) : Promise<EmailResult> {
+    return await TAURI_INVOKE("email_report", { taskId, recipients });
+},
+/**
+ * Post the latest verification report to Slack; `channel` falls back to
+ * slack_default_channel and is ignored for webhooks
+ */
+async postToSlack(taskId: string, channel: string | null) : Promise<SlackPostResult> {
+    return await TAURI_INVOKE("post_to_slack", { taskId, channel });
+},
+/**
+ * File-specific findings of the latest verification report as GitHub Actions
+ * workflow commands, also written to `path` when given
+ */
+async exportGithubAnnotations(taskId: string, path: string | null) : Promise<AnnotationExport> {
+    return await TAURI_INVOKE("export_github_annotations", { taskId, path });
+},
+/**
+ * Verification findings as a SARIF 2.1.0 log for code-scanning dashboards
+ */
+async exportSarif(taskId: string, path: string | null) : Promise<SarifExport> {
+    return await TAURI_INVOKE("export_sarif", { taskId, path });
+},
+/**
+ * Open a file cited in a plan or report at `line` in the configured editor;
+ * `path` is relative to the project's repository
+ */
+async openInEditor(projectId: string, path: string, line: number | null) : Promise<EditorLaunch> {
+    return await TAURI_INVOKE("open_in_editor", { projectId, path, line });
+},
+async getCompanionStatus() : Promise<CompanionStatus> {
+    return await TAURI_INVOKE("get_companion_status");
+},
+/**
+ * Remove a paired extension; its token stops working immediately
+ */
+async revokeCompanionClient(clientId: string) : Promise<null> {
+    return await TAURI_INVOKE("revoke_companion_client", { clientId });
+},
+/**
+ * Time the task was open and spent in runs, with the per-session activity log
+ */
+async getTaskTimeSummary(taskId: string) : Promise<TaskTimeSummary> {
+    return await TAURI_INVOKE("get_task_time_summary", { taskId });
+},
+async getBudgetStatus(projectId: string) : Promise<BudgetStatus> {
+    return await TAURI_INVOKE("get_budget_status", { projectId });
+},
+/**
+ * Answer BUDGET_CONFIRMATION_REQUIRED: allow runs over budget for the rest of the month
+ */
+async confirmBudgetOverrun(projectId: string) : Promise<null> {
+    return await TAURI_INVOKE("confirm_budget_overrun", { projectId });
+},
+async getOfflineStatus() : Promise<OfflineStatus> {
+    return await TAURI_INVOKE("get_offline_status");
+},
+/**
+ * Drop a run queued while offline
+ */
+async cancelQueuedRun(id: string) : Promise<null> {
+    return await TAURI_INVOKE("cancel_queued_run", { id });
+},
+async getTelemetryPreview() : Promise<TelemetryPreview> {
+    return await TAURI_INVOKE("get_telemetry_preview");
+},
+/**
+ * Send the report the user previewed; `digest` is the preview's
+ */
+async uploadTelemetry(digest: string) : Promise<TelemetryUpload> {
+    return await TAURI_INVOKE("upload_telemetry", { digest });
+},
+async clearTelemetry() : Promise<null> {
+    return await TAURI_INVOKE("clear_telemetry");
+},
+/**
+ * Send feedback or a bug report, with the redacted diagnostics bundle if asked
+ */
+async sendAppFeedback(kind: FeedbackKind, text: string, includeDiagnostics: boolean) : Promise<FeedbackSubmission> {
+    return await TAURI_INVOKE("send_app_feedback", { kind, text, includeDiagnostics });
+},
+async getDatabaseEncryption() : Promise<EncryptionStatus> {
+    return await TAURI_INVOKE("get_database_encryption");
+},
+/**
+ * Encrypt or decrypt the stored excerpts in place; can take a while on large databases
+ */
+async setDatabaseEncryption(enabled: boolean) : Promise<EncryptionStatus> {
+    return await TAURI_INVOKE("set_database_encryption", { enabled });
+},
+/**
+ * Startup integrity check result and the backups available to restore
+ */
+async getDatabaseHealth() : Promise<DatabaseHealth> {
+    return await TAURI_INVOKE("get_database_health");
+},
+async listBackups() : Promise<Backup[]> {
+    return await TAURI_INVOKE("list_backups");
+},
+/**
+ * Replace the database with a backup; the current one is backed up first
+ */
+async restoreBackup(backupId: string) : Promise<Backup> {
+    return await TAURI_INVOKE("restore_backup", { backupId });
+},
+async getSyncStatus() : Promise<SyncStatus> {
+    return await TAURI_INVOKE("get_sync_status");
+},
+/**
+ * Set up sync with a git or S3 remote, or turn it off when `remote` is null
+ */
+async configureSync(remote: SyncRemote | null, credentials: S3Credentials | null) : Promise<SyncStatus> {
+    return await TAURI_INVOKE("configure_sync", { remote, credentials });
+},
+/**
+ * Pull changes from other devices, then push this one's
+ */
+async syncNow() : Promise<SyncReport> {
+    return await TAURI_INVOKE("sync_now");
+},
+async getPhaseBoard(taskId: string) : Promise<PhaseBoard> {
+    return await TAURI_INVOKE("get_phase_board", { taskId });
+},
+async setPhaseDependencies(phaseId: string, dependsOn: string[]) : Promise<null> {
+    return await TAURI_INVOKE("set_phase_dependencies", { phaseId, dependsOn });
+},
+async listPhases(taskId: string) : Promise<Phase[]> {
+    return await TAURI_INVOKE("list_phases", { taskId });
+},
+async createPhase(taskId: string, title: string, description: string | null, plannedFiles: string[]) : Promise<Phase> {
+    return await TAURI_INVOKE("create_phase", { taskId, title, description, plannedFiles });
+},
+async updatePhase(phaseId: string, update: PhaseUpdate) : Promise<Phase> {
+    return await TAURI_INVOKE("update_phase", { phaseId, update });
+},
+/**
+ * Delete a phase along with its phase-scoped artifacts
+ */
+async deletePhase(phaseId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_phase", { phaseId });
+},
+/**
+ * Break the task's saved plan into phases with the LLM, store them, and save
+ * the list as the task's `phase_list` artifact
+ */
+async generatePhases(taskId: string) : Promise<PhasesResult> {
+    return await TAURI_INVOKE("generate_phases", { taskId });
+},
+/**
+ * Review a branch (against `base`), the staged changes, or the working tree
+ * with the repo tools; no plan is needed. Saved as the task's `review_md` artifact.
+ */
+async reviewChanges(taskId: string, options: ReviewOptions) : Promise<ReviewResult> {
+    return await TAURI_INVOKE("review_changes", { taskId, options });
+},
+async listTaskAttachments(taskId: string) : Promise<TaskAttachment[]> {
+    return await TAURI_INVOKE("list_task_attachments", { taskId });
+},
+async addTaskNote(taskId: string, name: string, content: string) : Promise<TaskAttachment> {
+    return await TAURI_INVOKE("add_task_note", { taskId, name, content });
+},
+async attachTaskFile(taskId: string, path: string) : Promise<TaskAttachment> {
+    return await TAURI_INVOKE("attach_task_file", { taskId, path });
+},
+async deleteTaskAttachment(attachmentId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_task_attachment", { attachmentId });
+},
+async transcribeAudio(path: string, taskId: string | null, projectId: string | null) : Promise<TranscriptionResult> {
+    return await TAURI_INVOKE("transcribe_audio", { path, taskId, projectId });
+},
+async getSettings() : Promise<SettingsKV[]> {
+    return await TAURI_INVOKE("get_settings");
+},
+/**
+ * The settings with a fixed type, and any stored values that were invalid
+ * and replaced by their default
+ */
+async getSettingsTyped() : Promise<TypedSettings> {
+    return await TAURI_INVOKE("get_settings_typed");
+},
+/**
+ * Write the global settings to a file for another machine, without credentials
+ */
+async exportSettings(path: string) : Promise<SettingsExport> {
+    return await TAURI_INVOKE("export_settings", { path });
+},
+/**
+ * Store the settings in a file written by export_settings; nothing is stored
+ * if any value is invalid. Returns how many were imported.
+ */
+async importSettings(path: string) : Promise<number> {
+    return await TAURI_INVOKE("import_settings", { path });
+},
+async getSetting(key: string) : Promise<string | null> {
+    return await TAURI_INVOKE("get_setting", { key });
+},
+async setSetting(key: string, value: string) : Promise<null> {
+    return await TAURI_INVOKE("set_setting", { key, value });
+},
+async setSettings(pairs: SettingInput[]) : Promise<null> {
+    return await TAURI_INVOKE("set_settings", { pairs });
+},
+async getProjectSettings(projectId: string) : Promise<SettingsKV[]> {
+    return await TAURI_INVOKE("get_project_settings", { projectId });
+},
+/**
+ * Set a project override, or clear it (fall back to the workspace or global setting) with `None`
+ */
+async setProjectSetting(projectId: string, key: string, value: string | null) : Promise<null> {
+    return await TAURI_INVOKE("set_project_setting", { projectId, key, value });
+},
+/**
+ * Context ignore globs for the project, from its settings and the repo's .spectrail.toml
+ */
+async getContextIgnore(projectId: string) : Promise<ContextIgnoreConfig> {
+    return await TAURI_INVOKE("get_context_ignore", { projectId });
+},
+/**
+ * Replace the project's context ignore globs, e.g. `**/*.min.js` or `fixtures/**`
+ */
+async setContextIgnore(projectId: string, globs: string[]) : Promise<null> {
+    return await TAURI_INVOKE("set_context_ignore", { projectId, globs });
+},
+/**
+ * Run `script` when the project's standard script for `kind` is missing, e.g. one
+ * run_command suggested; `None` stops falling back for that kind
+ */
+async approveScriptFallback(projectId: string, kind: string, script: string | null) : Promise<null> {
+    return await TAURI_INVOKE("approve_script_fallback", { projectId, kind, script });
+},
+/**
+ * Build or refresh the project's embeddings index; unchanged files are skipped
+ */
+async indexRepository(projectId: string) : Promise<IndexReport> {
+    return await TAURI_INVOKE("index_repository", { projectId });
+},
+async getIndexStatus(projectId: string) : Promise<IndexStatus> {
+    return await TAURI_INVOKE("get_index_status", { projectId });
+},
+async listWorkspaces() : Promise<Workspace[]> {
+    return await TAURI_INVOKE("list_workspaces");
+},
+async createWorkspace(name: string) : Promise<Workspace> {
+    return await TAURI_INVOKE("create_workspace", { name });
+},
+async renameWorkspace(workspaceId: string, name: string) : Promise<Workspace> {
+    return await TAURI_INVOKE("rename_workspace", { workspaceId, name });
+},
+/**
+ * Delete a workspace; its projects are kept and no longer grouped
+ */
+async deleteWorkspace(workspaceId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_workspace", { workspaceId });
+},
+/**
+ * Move a project into a workspace, or out of it with `None`
+ */
+async setProjectWorkspace(projectId: string, workspaceId: string | null) : Promise<null> {
+    return await TAURI_INVOKE("set_project_workspace", { projectId, workspaceId });
+},
+async getWorkspaceSettings(workspaceId: string) : Promise<SettingsKV[]> {
+    return await TAURI_INVOKE("get_workspace_settings", { workspaceId });
+},
+/**
+ * Set a default for the workspace's projects, or clear it with `None`
+ */
+async setWorkspaceSetting(workspaceId: string, key: string, value: string | null) : Promise<null> {
+    return await TAURI_INVOKE("set_workspace_setting", { workspaceId, key, value });
+},
+/**
+ * Task counts, recent tasks and recent runs across the workspace's projects
+ */
+async getWorkspaceDashboard(workspaceId: string) : Promise<WorkspaceDashboard> {
+    return await TAURI_INVOKE("get_workspace_dashboard", { workspaceId });
+},
+async getIntegrationStatus(projectId: string, provider: string) : Promise<IntegrationStatus> {
+    return await TAURI_INVOKE("get_integration_status", { projectId, provider });
+},
+async setIntegrationConfig(projectId: string, provider: string, config: IntegrationConfig) : Promise<null> {
+    return await TAURI_INVOKE("set_integration_config", { projectId, provider, config });
+},
+/**
+ * Save the API token to the OS keychain; an empty token removes it
+ */
+async setIntegrationToken(provider: string, token: string) : Promise<null> {
+    return await TAURI_INVOKE("set_integration_token", { provider, token });
+},
+async importIssue(projectId: string, provider: string, key: string) : Promise<ImportedIssue> {
+    return await TAURI_INVOKE("import_issue", { projectId, provider, key });
+},
+async listTaskLinks(taskId: string) : Promise<TaskLink[]> {
+    return await TAURI_INVOKE("list_task_links", { taskId });
+},
+/**
+ * Comment the plan summary and verification verdict on the linked issues
+ */
+async pushIssueComment(taskId: string) : Promise<TaskLink[]> {
+    return await TAURI_INVOKE("push_issue_comment", { taskId });
+},
+async getRecentLogs(level: string | null, limit: number | null) : Promise<LogEntry[]> {
+    return await TAURI_INVOKE("get_recent_logs", { level, limit });
+},
+/**
+ * Bundle logs, system info, redacted settings, and the last failed run into a zip
+ */
+async exportDiagnostics(path: string | null) : Promise<DiagnosticsBundle> {
+    return await TAURI_INVOKE("export_diagnostics", { path });
+},
+async onboardingDetectRuntimes() : Promise<StepResult[]> {
+    return await TAURI_INVOKE("onboarding_detect_runtimes");
+},
+async onboardingTestLlm() : Promise<StepResult> {
+    return await TAURI_INVOKE("onboarding_test_llm");
+},
+async onboardingCreateProject(repoPath: string, name: string | null) : Promise<StepResult> {
+    return await TAURI_INVOKE("onboarding_create_project", { repoPath, name });
+},
+async onboardingSmokePlan(projectId: string) : Promise<StepResult> {
+    return await TAURI_INVOKE("onboarding_smoke_plan", { projectId });
+},
+async checkForUpdates() : Promise<UpdateInfo> {
+    return await TAURI_INVOKE("check_for_updates");
+},
+async installUpdate() : Promise<null> {
+    return await TAURI_INVOKE("install_update");
+},
+async seedDemoData() : Promise<DemoSeedResult> {
+    return await TAURI_INVOKE("seed_demo_data");
+},
+async clearDemoData() : Promise<number> {
+    return await TAURI_INVOKE("clear_demo_data");
+},
+async openTaskWindow(taskId: string) : Promise<string> {
+    return await TAURI_INVOKE("open_task_window", { taskId });
+},
+/**
+ * Scope the calling window's run events to a project and/or task
+ */
+async subscribeWindow(projectId: string | null, taskId: string | null) : Promise<null> {
+    return await TAURI_INVOKE("subscribe_window", { projectId, taskId });
+},
+async unsubscribeWindow() : Promise<null> {
+    return await TAURI_INVOKE("unsubscribe_window");
+},
+async verifyCurrentTask() : Promise<string> {
+    return await TAURI_INVOKE("verify_current_task");
+},
+async openLastProject() : Promise<string> {
+    return await TAURI_INVOKE("open_last_project");
+},
+async quickCapture(title: string, includeClipboard: boolean) : Promise<CaptureResult> {
+    return await TAURI_INVOKE("quick_capture", { title, includeClipboard });
+},
+async listToolCallsCmd(runId: string) : Promise<ToolCallRow[]> {
+    return await TAURI_INVOKE("list_tool_calls_cmd", { runId });
+},
+/**
+ * Messages and tool calls of a run as one ordered timeline
+ */
+async getRunTranscript(runId: string) : Promise<RunTranscript> {
+    return await TAURI_INVOKE("get_run_transcript", { runId });
+},
+async executeRepoTool(runId: string, projectId: string, name: string, args: JsonValue) : Promise<JsonValue> {
+    return await TAURI_INVOKE("execute_repo_tool", { runId, projectId, name, args });
+},
+async getRepoToolSchemas() : Promise<JsonValue[]> {
+    return await TAURI_INVOKE("get_repo_tool_schemas");
+},
+async getToolStats() : Promise<ToolStat[]> {
+    return await TAURI_INVOKE("get_tool_stats");
+},
+async analyzeRunTools(runId: string, threshold: number | null) : Promise<RunToolAnalysis> {
+    return await TAURI_INVOKE("analyze_run_tools", { runId, threshold });
+},
+async previewPrompt(workflow: string, taskId: string) : Promise<PromptPreview> {
+    return await TAURI_INVOKE("preview_prompt", { workflow, taskId });
+},
+/**
+ * Files and sections a plan or verify run of the task would gather, with the task's overrides
+ */
+async previewContext(taskId: string, workflow: string) : Promise<ContextPreview> {
+    return await TAURI_INVOKE("preview_context", { taskId, workflow });
+},
+async listContextOverrides(taskId: string) : Promise<ContextOverride[]> {
+    return await TAURI_INVOKE("list_context_overrides", { taskId });
+},
+/**
+ * Pin a file into the task's context or exclude it (`pin`|`exclude`); `None` clears it
+ */
+async setContextOverride(taskId: string, path: string, action: string | null) : Promise<ContextOverride[]> {
+    return await TAURI_INVOKE("set_context_override", { taskId, path, action });
+},
+/**
+ * Explain the task's current diff file by file, in reading order, and save
+ * it as the task's `diff_walkthrough` artifact
+ */
+async explainDiff(taskId: string) : Promise<WalkthroughResult> {
+    return await TAURI_INVOKE("explain_diff", { taskId });
+},
+/**
+ * Explain the project's architecture for a newcomer, optionally going deeper on
+ * `focus`, and save it as the project's `repo_overview`
+ */
+async explainRepo(projectId: string, focus: string | null) : Promise<OverviewResult> {
+    return await TAURI_INVOKE("explain_repo", { projectId, focus });
+},
+/**
+ * Extract the project's domain terms into its `glossary`, which plan and verify
+ * prompts always include
+ */
+async extractGlossary(projectId: string) : Promise<GlossaryResult> {
+    return await TAURI_INVOKE("extract_glossary", { projectId });
+},
+async listCustomWorkflows(projectId: string) : Promise<CustomWorkflowList> {
+    return await TAURI_INVOKE("list_custom_workflows", { projectId });
+},
+/**
+ * Save a workflow from its JSON or TOML `definition`, replacing the project's
+ * saved workflow of the same name
+ */
+async saveCustomWorkflow(projectId: string, name: string, definition: string) : Promise<CustomWorkflow> {
+    return await TAURI_INVOKE("save_custom_workflow", { projectId, name, definition });
+},
+async deleteCustomWorkflow(projectId: string, name: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_custom_workflow", { projectId, name });
+},
+/**
+ * Run the workflow `name` from the task's project and save its answer as
+ * the artifact kind it defines
+ */
+async runCustomWorkflow(name: string, taskId: string) : Promise<CustomWorkflowResult> {
+    return await TAURI_INVOKE("run_custom_workflow", { name, taskId });
+},
+/**
+ * Re-read the tool plugin manifests, e.g. after installing one
+ */
+async reloadToolPlugins() : Promise<PluginList> {
+    return await TAURI_INVOKE("reload_tool_plugins");
+},
+/**
+ * Scripts in the hooks directory that a project's lifecycle hooks can run
+ */
+async listHookScripts() : Promise<HookScripts> {
+    return await TAURI_INVOKE("list_hook_scripts");
+},
+/**
+ * Every event the backend emits, with a JSON schema of its payload, so the
+ * frontend's listener types can be generated rather than kept in sync by hand
+ */
+async listEventSchemas() : Promise<EventSchema[]> {
+    return await TAURI_INVOKE("list_event_schemas");
+},
+/**
+ * The project's artifacts (repo map, architecture overview, release notes,
+ * conventions), newest first, optionally of one kind
+ */
+async listProjectArtifacts(projectId: string, kind: string | null) : Promise<Artifact[]> {
+    return await TAURI_INVOKE("list_project_artifacts", { projectId, kind });
+},
+async upsertProjectArtifact(projectId: string, kind: string, content: string) : Promise<Artifact> {
+    return await TAURI_INVOKE("upsert_project_artifact", { projectId, kind, content });
+},
+/**
+ * Regenerate the project's repo map now and summarize how its structure drifted
+ * since the last one
+ */
+async refreshRepoMap(projectId: string) : Promise<DriftResult> {
+    return await TAURI_INVOKE("refresh_repo_map", { projectId });
+},
+/**
+ * Pinned project artifacts are included in plan and verify prompts; conventions, the glossary and the structure drift summary always are
+ */
+async setProjectArtifactPinned(artifactId: string, pinned: boolean) : Promise<Artifact> {
+    return await TAURI_INVOKE("set_project_artifact_pinned", { artifactId, pinned });
+},
+async deleteProjectArtifact(artifactId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_project_artifact", { artifactId });
+},
+/**
+ * Rate a plan or verification report artifact `up` or `down`
+ */
+async submitFeedback(artifactId: string, rating: string, comment: string | null) : Promise<Feedback> {
+    return await TAURI_INVOKE("submit_feedback", { artifactId, rating, comment });
+},
+async listFeedback(taskId: string) : Promise<Feedback[]> {
+    return await TAURI_INVOKE("list_feedback", { taskId });
+},
+async deleteFeedback(feedbackId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_feedback", { feedbackId });
+},
+/**
+ * Approval rates for one project, or across all of them without `project_id`
+ */
+async getFeedbackSummary(projectId: string | null) : Promise<FeedbackSummary> {
+    return await TAURI_INVOKE("get_feedback_summary", { projectId });
+},
+/**
+ * Parse JUnit XML or a GitHub Actions job log (file path or URL) for the
+ * task's next verification with `use_ci_results`
+ */
+async ingestCiResults(taskId: string, pathOrUrl: string) : Promise<CiResults> {
+    return await TAURI_INVOKE("ingest_ci_results", { taskId, pathOrUrl });
+},
+/**
+ * Run the task's checks (tests, lint and build by default) on the pre-change
+ * state so verify reports what the change made worse rather than absolute counts
+ */
+async captureBaseline(taskId: string, kinds: string[] | null) : Promise<Baseline> {
+    return await TAURI_INVOKE("capture_baseline", { taskId, kinds });
+},
+async getBaseline(taskId: string) : Promise<Baseline | null> {
+    return await TAURI_INVOKE("get_baseline", { taskId });
+},
+/**
+ * Recorded pass/fail history of the project's failing tests, most flaky first
+ */
+async listTestStats(projectId: string, flakyOnly: boolean | null) : Promise<TestHistory[]> {
+    return await TAURI_INVOKE("list_test_stats", { projectId, flakyOnly });
+},
+async resetTestStats(projectId: string) : Promise<null> {
+    return await TAURI_INVOKE("reset_test_stats", { projectId });
+},
+/**
+ * Emergency stop: kill every command run_command started, with their child
+ * processes and containers
+ */
+async killAllRunningCommands() : Promise<RunningCommand[]> {
+    return await TAURI_INVOKE("kill_all_running_commands");
+},
+async generatePlanCommand(projectId: string, taskId: string) : Promise<PlanResult> {
+    return await TAURI_INVOKE("generate_plan_command", { projectId, taskId });
+},
+async verifyTaskCommand(projectId: string, taskId: string, options: VerifyOptions | null) : Promise<VerifyResult> {
+    return await TAURI_INVOKE("verify_task_command", { projectId, taskId, options });
+},
+/**
+ * Experimental: two planners draft competing plans (optionally with different
+ * models) and a judge merges them into the task's plan
+ */
+async debatePlan(projectId: string, taskId: string, modelA: string | null, modelB: string | null) : Promise<DebateResult> {
+    return await TAURI_INVOKE("debate_plan", { projectId, taskId, modelA, modelB });
+},
+/**
+ * Repeat a run with its recorded seed to check whether output differences are sampling noise
+ */
+async rerunWithSameSeed(runId: string) : Promise<RerunResult> {
+    return await TAURI_INVOKE("rerun_with_same_seed", { runId });
+},
+/**
+ * Continue a plan run in a new run from one of its assistant messages, dropping what came after
+ */
+async regenerateFromMessage(runId: string, messageId: string) : Promise<RegenerateResult> {
+    return await TAURI_INVOKE("regenerate_from_message", { runId, messageId });
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+export type AnnotationExport = { 
+/**
+ * Workflow commands, one per line, ready to print from a CI step
+ */
+content: string; count: number; findings: Finding[]; 
+/**
+ * Set when the commands were also written to a file
+ */
+path: string | null }
+/**
+ * The settings with a fixed type, read from the settings table's strings.
+ * Missing and invalid values take the default; `TypedSettings` says which were invalid.
+ */
+export type AppSettings = { provider_name: string; base_url: string; model: string; temperature: number; max_tokens: number; stream_responses: boolean; feedback_in_prompt: boolean; prompt_template_strict: boolean; verify_cache: boolean; reuse_check_results: boolean; plan_debate: boolean; tool_loop_threshold: number; tool_loop_abort: boolean; tool_max_calls_per_run: number; tool_max_calls_per_minute: number; tool_max_commands_per_run: number; tool_max_bytes_per_run: number; retrieval_top_k: number; lsp_timeout_secs: number; repo_map_refresh_hours: number; telemetry_enabled: boolean; telemetry_endpoint: string; feedback_endpoint: string; feedback_issue_repo: string }
+export type AppliedTag = { name: string; 
+/**
+ * user|auto
+ */
+source: string }
+export type Artifact = { id: string; task_id: string | null; project_id: string | null; phase_id: string | null; kind: string; content: string; created_at: string; pinned: number; draft: number }
+export type Backup = { 
+/**
+ * File name without extension, e.g. "spectrail-20260301-093000123-v26"
+ */
+id: string; 
+/**
+ * Schema version the backup was taken at
+ */
+schema_version: number | null; size_bytes: number; created_at: string }
+export type Baseline = { id: string; task_id: string; run_id: string; base_commit: string; 
+/**
+ * worktree|stash
+ */
+method: string; checks: CheckResult[]; created_at: string }
+/**
+ * How the current checks differ from the task's baseline
+ */
+export type BaselineDelta = { baseline_id: string; base_commit: string; 
+/**
+ * Kinds run both in the baseline and now
+ */
+compared: string[]; newly_failing_tests: string[]; fixed_tests: string[]; errors_added: number; warnings_added: number; 
+/**
+ * Diagnostics with no counterpart in the baseline
+ */
+new_diagnostics: CompilerDiagnostic[] }
+/**
+ * A benchmark result next to the project's previous result for it
+ */
+export type BenchComparison = { name: string; mean_ns: number; previous_mean_ns: number | null; 
+/**
+ * Positive when slower than before
+ */
+change_pct: number | null; 
+/**
+ * Slower than before by more than the bench_regression_pct setting
+ */
+regression: boolean }
+export type BudgetStatus = { project_id: string; 
+/**
+ * Calendar month (UTC) the usage covers, "YYYY-MM"
+ */
+month: string; tokens_used: number; cost_usd: number; token_limit: number | null; usd_limit: number | null; 
+/**
+ * Highest share of any configured budget spent; None without budgets
+ */
+fraction: number | null; state: string; enforcement: string; 
+/**
+ * Whether the user confirmed running over budget this month
+ */
+confirmed: boolean }
+export type BulkTaskResult = { task_id: string; ok: boolean; error: string | null; 
+/**
+ * The task after the change
+ */
+task: Task | null }
+export type CaptureResult = { project: Project; task: Task; 
+/**
+ * Clipboard contents saved as a note, when requested and non-empty
+ */
+attachment: TaskAttachment | null }
+/**
+ * Structured result of one check command
+ */
+export type CheckResult = { 
+/**
+ * tests|lint|build|typecheck
+ */
+kind: string; 
+/**
+ * Exit code; None when the command couldn't run
+ */
+code: number | null; error: string | null; failed_tests: string[]; diagnostics: CompilerDiagnostic[] }
+export type CheckSummary = { kind: string; 
+/**
+ * From run_command, or `error` when the check couldn't be run
+ */
+outcome: string }
+export type CiFailure = { 
+/**
+ * Test suite, or the workflow step for GitHub Actions logs
+ */
+suite: string | null; name: string; file: string | null; line: number | null; message: string; details: string | null }
+export type CiResults = { id: string; task_id: string; source: string; format: string; 
+/**
+ * Number of test cases; unknown for job logs
+ */
+tests: number | null; skipped: number; failures: CiFailure[]; created_at: string }
+/**
+ * Sent when run_command has to wait for another run's command in the same
+ * repository ("waiting"), and again once it gets to run ("started")
+ */
+export type CommandQueueEvent = { run_id: string; repo_path: string; kind: string; status: string; 
+/**
+ * Run whose command was running when this one queued
+ */
+holder_run_id: string | null }
+export type CompanionClient = { id: string; name: string; created_at: string; last_used_at: string | null }
+export type CompanionStatus = { running: boolean; port: number | null; clients: CompanionClient[] }
+/**
+ * A compiler, linter or test failure message tied to a source location
+ */
+export type CompilerDiagnostic = { 
+/**
+ * cargo|rustc|tsc|mypy|eslint|ruff|test|ci
+ */
+tool: string; path: string; line: number; column: number | null; 
+/**
+ * error|warning
+ */
+severity: string; message: string; code: string | null; origin: Origin }
+/**
+ * Where a project's context ignore globs come from
+ */
+export type ContextIgnoreConfig = { 
+/**
+ * The `context_ignore` setting
+ */
+globs: string[]; 
+/**
+ * `[context] ignore` in the repo's .spectrail.toml
+ */
+repo_globs: string[]; repo_error: string | null }
+/**
+ * A file pinned into or excluded from a task's context by hand
+ */
+export type ContextOverride = { path: string; 
+/**
+ * pin|exclude
+ */
+action: string; created_at: string }
+export type ContextPreview = { workflow: string; sections: ContextSection[]; overrides: ContextOverride[]; 
+/**
+ * Files left out by the ignore globs or the task's exclusions
+ */
+ignored: string[] }
+/**
+ * One part of the context a workflow would send, with the files it covers
+ */
+export type ContextSection = { 
+/**
+ * task|custom_prompt|attachments|relevant_files|pinned_files|retrieval|git_diff
+ */
+name: string; chars: number; files: string[]; note: string | null }
+export type Counter = { name: string; count: number }
+export type CustomWorkflow = { name: string; 
+/**
+ * `project` when saved in the app, `repo` when defined in .spectrail.toml
+ */
+source: string; definition: WorkflowDefinition }
+export type CustomWorkflowList = { workflows: CustomWorkflow[]; 
+/**
+ * Why the repository's workflows couldn't be read
+ */
+repo_error: string | null }
+export type CustomWorkflowResult = { run_id: string; name: string; artifact_id: string; artifact_kind: string; content: string; tool_calls_count: number; forced_completion: boolean; checks: CheckSummary[] }
+export type DatabaseHealth = { 
+/**
+ * Result of the startup `PRAGMA quick_check`
+ */
+ok: boolean; problems: string[]; backups: Backup[] }
+export type DayTime = { date: string; view_secs: number; run_secs: number }
+export type DebateCandidate = { run_id: string; model: string; artifact_kind: string; plan_md: string; usage: RunUsage }
+export type DebateResult = { 
+/**
+ * The judge's run; the candidates' runs are its children
+ */
+run_id: string; 
+/**
+ * The merged plan, saved as the task's plan
+ */
+plan_md: string; candidates: DebateCandidate[]; judge_usage: RunUsage; 
+/**
+ * All three runs together
+ */
+total_usage: RunUsage; missing_sections: string[]; diagram_errors: string[] }
+export type DemoSeedResult = { project: Project; 
+/**
+ * False when demo data already existed and was returned as-is
+ */
+created: boolean; task_count: number; run_count: number }
+export type DependencyChange = { manifest: string; name: string; 
+/**
+ * None when the dependency was added
+ */
+before: string | null; 
+/**
+ * None when the dependency was removed
+ */
+after: string | null }
+export type DiagnosticsBundle = { path: string; 
+/**
+ * Files included in the zip
+ */
+entries: string[] }
+export type DriftResult = { repo_map: Artifact; 
+/**
+ * The drift summary; None on the first refresh or when nothing moved
+ */
+drift: Artifact | null; 
+/**
+ * Directories that appeared, outermost only
+ */
+new_modules: string[]; 
+/**
+ * Directories that disappeared, outermost only
+ */
+deleted_dirs: string[]; dependency_changes: DependencyChange[]; files_added: number; files_removed: number }
+export type EditorLaunch = { editor: string; 
+/**
+ * Program and arguments that were spawned
+ */
+command: string[] }
+export type EmailResult = { recipients: string[]; subject: string }
+export type EncryptionStatus = { enabled: boolean; 
+/**
+ * False when encryption is on but the keychain entry is gone; encrypted
+ * data can't be read until it's restored
+ */
+key_available: boolean }
+export type EntryKind = "system" | "user" | "assistant" | "tool_call" | "tool_result"
+export type EventSchema = { name: string; version: number; description: string; 
+/**
+ * JSON schema of the payload
+ */
+payload: JsonValue }
+export type ExportResult = { path: string; format: string; bytes: number }
+/**
+ * An issue field copied into the imported task's "Issue fields" note
+ */
+export type ExtraField = { label: string; 
+/**
+ * Dotted path into the issue's fields, e.g. `priority.name` or `customfield_10010`
+ */
+path: string }
+/**
+ * A reviewer's rating of a plan or verification report
+ */
+export type Feedback = { id: string; artifact_id: string; task_id: string; 
+/**
+ * plan_md|verification_report
+ */
+kind: string; 
+/**
+ * up|down
+ */
+rating: string; comment: string | null; created_at: string }
+export type FeedbackKind = "bug" | "idea" | "question"
+/**
+ * Ratings of one artifact kind
+ */
+export type FeedbackStats = { kind: string; up: number; down: number; 
+/**
+ * Share of thumbs-up, or None before any rating
+ */
+approval_rate: number | null }
+export type FeedbackSubmission = { 
+/**
+ * Whether the report was posted to `feedback_endpoint`
+ */
+sent: boolean; 
+/**
+ * The pre-filled issue opened in the browser when no endpoint is set
+ */
+issue_url: string | null; 
+/**
+ * The diagnostics bundle written for the report; an issue can't carry it,
+ * so the user attaches it by hand
+ */
+diagnostics_path: string | null }
+export type FeedbackSummary = { stats: FeedbackStats[]; 
+/**
+ * Latest thumbs-down comments, newest first
+ */
+recent_rejections: Feedback[] }
+/**
+ * Which issue fields become the task title and description
+ */
+export type FieldMapping = { title: string; description: string; extra?: ExtraField[] }
+/**
+ * A verification report finding that cites a specific file
+ */
+export type Finding = { 
+/**
+ * Report section the finding came from: compliance|risk|action|suggestion
+ */
+section: string; severity: Severity; title: string; message: string; file: string; line: number | null }
+export type GlossaryResult = { artifact: Artifact; 
+/**
+ * Candidate terms found in the code and offered to the model
+ */
+candidates: number; scanned_files: number }
+export type HookScripts = { dir: string; scripts: string[] }
+export type ImportedIssue = { task: Task; link: TaskLink }
+export type IndexReport = { files_indexed: number; files_unchanged: number; 
+/**
+ * Binary, oversized, or unreadable files
+ */
+files_skipped: number; files_removed: number; chunks: number; 
+/**
+ * The chunk limit was reached and some files were left out
+ */
+truncated: boolean }
+export type IndexStatus = { files: number; chunks: number; model: string | null; indexed_at: string | null }
+/**
+ * Per-project integration settings, stored as JSON in `project_settings`.
+ * Tokens live in the OS keychain, never here.
+ */
+export type IntegrationConfig = { 
+/**
+ * Jira site URL, e.g. https://acme.atlassian.net (unused for Linear)
+ */
+base_url?: string | null; 
+/**
+ * Jira Cloud account email for basic auth; omit to send a bearer PAT
+ */
+email?: string | null; field_mapping?: FieldMapping | null }
+export type IntegrationStatus = { provider: string; has_token: boolean; config: IntegrationConfig | null; 
+/**
+ * Mapping in effect: the configured one or the provider default
+ */
+field_mapping: FieldMapping }
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+export type LogEntry = { timestamp: string; level: string; target: string; message: string; 
+/**
+ * Event fields other than the message (run_id, tool, duration_ms, ...)
+ */
+fields: JsonValue; 
+/**
+ * Innermost span and its fields, if the event was inside one
+ */
+span: JsonValue | null }
+export type Message = { id: string; run_id: string; role: string; content: string; created_at: string }
+export type OfflineStatus = { offline: boolean; 
+/**
+ * Offline because the user turned on offline mode
+ */
+manual: boolean; 
+/**
+ * Offline because LLM requests couldn't reach the provider
+ */
+detected: boolean; queued: QueuedRun[] }
+/**
+ * Whether a diagnostic sits on a line the change added or modified
+ */
+export type Origin = "introduced" | "preexisting"
+export type OverviewResult = { artifact: Artifact; 
+/**
+ * Files read into the prompt, in the order they were picked
+ */
+read_files: string[] }
+export type PairRequest = { pairing_id: string; client_name: string; code: string }
+export type Phase = { id: string; task_id: string; idx: number; title: string; description: string | null; status: string; planned_files_json: string; created_at: string; updated_at: string }
+export type PhaseBoard = { task_id: string; total: number; done: number; columns: PhaseColumn[] }
+export type PhaseCard = { phase: Phase; blocked_by: string[]; blocks: string[]; latest_verdict: string | null; latest_verified_at: string | null }
+export type PhaseColumn = { status: string; count: number; phases: PhaseCard[] }
+/**
+ * Fields to change on a phase; unset ones are left as they are
+ */
+export type PhaseUpdate = { title?: string | null; description?: string | null; status?: string | null; planned_files?: string[] | null }
+export type PhasesResult = { run_id: string; artifact_id: string; phase_list_md: string; phases: Phase[] }
+export type PlanResult = { run_id: string; plan_md: string; tool_calls_count: number; truncated: boolean; forced_completion: boolean; 
+/**
+ * Language code the plan was requested in
+ */
+language: string; 
+/**
+ * Expected section headings missing from the plan
+ */
+missing_sections: string[]; 
+/**
+ * Mermaid diagrams that failed validation and were not stored
+ */
+diagram_errors: string[] }
+export type PluginList = { dir: string; plugins: PluginManifest[]; 
+/**
+ * Manifests that were skipped, and why
+ */
+errors: string[] }
+/**
+ * A third-party repo tool: an executable run in the repository that reads
+ * `{"tool", "args", "repo_path"}` as JSON on stdin and prints its result,
+ * as JSON or text, on stdout. One manifest per `.json` file in the plugins
+ * directory, e.g.
+ * 
+ * ```json
+ * {
+ * "name": "terraform_plan",
+ * "description": "Show the changes terraform would make in a module",
+ * "parameters": {"type": "object", "properties": {"module": {"type": "string"}}, "required": ["module"]},
+ * "command": ["./terraform-plan-tool"],
+ * "tier": "read_run"
+ * }
+ * ```
+ */
+export type PluginManifest = { name: string; description: string; 
+/**
+ * JSON schema of the tool's arguments
+ */
+parameters?: JsonValue; 
+/**
+ * Program and arguments; a relative program path is resolved against the plugins directory
+ */
+command: string[]; 
+/**
+ * What the tool does to the repository, which decides the runs it's offered to
+ */
+tier?: ToolTier; timeout_secs?: number | null }
+export type Project = { id: string; name: string; repo_path: string; created_at: string; last_opened_at: string | null; workspace_id: string | null }
+/**
+ * Where a project cloned from a URL came from
+ */
+export type ProjectRemote = { project_id: string; git_url: string; branch: string; shallow: boolean; created_at: string; last_pulled_at: string | null }
+export type PromptPreview = { workflow: string; template: string | null; rendered: string; used_variables: string[]; unknown_variables: string[]; strict: boolean }
+export type PullResult = { branch: string; before: string; after: string; updated: boolean }
+export type QueuedRun = { id: string; project_id: string; task_id: string; run_type: string; options: JsonValue; queued_at: string }
+export type RanChecks = { tests: boolean; lint: boolean; build: boolean; typecheck: boolean; bench: boolean; diagnostics: boolean; 
+/**
+ * Test results came from ingested CI output rather than a local run
+ */
+ci: boolean }
+export type RegenerateResult = { 
+/**
+ * The new run
+ */
+run_id: string; original_run_id: string; message_id: string; 
+/**
+ * Messages carried over from the original run
+ */
+kept_messages: number }
+/**
+ * A markdown document split into pieces the frontend can render directly.
+ * Invalid diagrams fall back to their source plus the validation error.
+ */
+export type RenderBlock = { type: "markdown"; content: string } | { type: "mermaid"; source: string; diagram_type: string } | { type: "mermaid_error"; source: string; error: string }
+export type RerunResult = { 
+/**
+ * The new run
+ */
+run_id: string; original_run_id: string; run_type: string; seed: number }
+/**
+ * A review comment, as stored from the model's answer
+ */
+export type ReviewComment = { file: string; line: number | null; severity: Severity; comment: string }
+/**
+ * What to review: the branch against a base, the staged changes, or
+ * (with neither) the working tree's unstaged changes
+ */
+export type ReviewOptions = { base?: string | null; staged?: boolean }
+export type ReviewResult = { run_id: string; artifact_id: string; review_md: string; verdict: ReviewVerdict | null; comments: ReviewComment[]; tool_calls_count: number; forced_completion: boolean }
+export type ReviewVerdict = "approve" | "request_changes" | "comment"
+export type Run = { id: string; task_id: string; phase_id: string | null; run_type: string; provider: string | null; model: string | null; started_at: string; ended_at: string | null; 
+/**
+ * Set when the run failed after starting
+ */
+error: string | null; prompt_tokens: number; completion_tokens: number; 
+/**
+ * Estimated from the configured token prices; 0 when prices aren't set
+ */
+cost_usd: number; 
+/**
+ * Sampling seed sent to the provider, if any
+ */
+seed: number | null; 
+/**
+ * The run this one reran, retried or continued
+ */
+parent_run_id: string | null; 
+/**
+ * rerun|retry|regenerate
+ */
+parent_relation: string | null; 
+/**
+ * Set when the run was cancelled
+ */
+cancelled_at: string | null }
+export type RunEvent = { run_id: string; project_id: string; task_id: string; run_type: string; status: RunStatus; error: string | null; 
+/**
+ * Machine-readable failure code (e.g. `OFFLINE`, `CANCELLED`), when the workflow has one
+ */
+code: string | null }
+/**
+ * A run and the runs descended from it, oldest first
+ */
+export type RunNode = ({ id: string; task_id: string; phase_id: string | null; run_type: string; provider: string | null; model: string | null; started_at: string; ended_at: string | null; 
+/**
+ * Set when the run failed after starting
+ */
+error: string | null; prompt_tokens: number; completion_tokens: number; 
+/**
+ * Estimated from the configured token prices; 0 when prices aren't set
+ */
+cost_usd: number; 
+/**
+ * Sampling seed sent to the provider, if any
+ */
+seed: number | null; 
+/**
+ * The run this one reran, retried or continued
+ */
+parent_run_id: string | null; 
+/**
+ * rerun|retry|regenerate
+ */
+parent_relation: string | null; 
+/**
+ * Set when the run was cancelled
+ */
+cancelled_at: string | null }) & { children: RunNode[] }
+/**
+ * The run a new run reruns, retries or continues
+ */
+export type RunParent = { run_id: string; 
+/**
+ * rerun|retry|regenerate|debate
+ */
+relation: string }
+export type RunStatus = "started" | "finished" | "failed" | "cancelled"
+export type RunToolAnalysis = { run_id: string; total_calls: number; calls_by_tool: Partial<{ [key in string]: number }>; loop_flags: ToolLoopFlag[]; stuck: boolean }
+export type RunTranscript = { run_id: string; entries: TranscriptEntry[]; total_bytes: number }
+/**
+ * Tokens and estimated cost of one or more runs
+ */
+export type RunUsage = { prompt_tokens: number; completion_tokens: number; cost_usd: number }
+/**
+ * A spawned command that hasn't finished yet
+ */
+export type RunningCommand = { pid: number; run_id: string; command: string; 
+/**
+ * Backend and name of the container the command runs in
+ */
+container: [string, string] | null }
+export type S3Credentials = { access_key_id: string; secret_access_key: string }
+export type SarifExport = { path: string | null; results: number; 
+/**
+ * The SARIF 2.1.0 log
+ */
+sarif: JsonValue }
+export type SettingInput = { key: string; value: string }
+/**
+ * A stored value the typed settings couldn't use
+ */
+export type SettingIssue = { key: string; value: string; error: string }
+export type SettingsExport = { path: string; exported: number; 
+/**
+ * Credentials and settings changed through their own command, left out
+ */
+excluded: string[] }
+export type SettingsKV = { key: string; value: string; updated_at: string }
+export type Severity = "high" | "medium" | "low"
+export type SlackPostResult = { 
+/**
+ * "bot" (threaded, via chat.postMessage) or "webhook" (single message)
+ */
+via: string; channel: string | null; 
+/**
+ * Timestamp of the parent message, which identifies the thread
+ */
+ts: string | null; thread_messages: number }
+/**
+ * Outcome of one wizard step. Failures are reported here rather than as
+ * command errors so the wizard can render every step uniformly.
+ */
+export type StepResult = { step: string; status: StepStatus; message: string; details: JsonValue }
+export type StepStatus = "ok" | "warning" | "error"
+export type SyncRemote = { kind: "git"; url: string; branch?: string | null } | { kind: "s3"; bucket: string; region: string; endpoint?: string | null; prefix?: string | null }
+export type SyncReport = { pushed: number; pulled: number; 
+/**
+ * Pulled changes that were newer than the local copy
+ */
+applied: number }
+export type SyncStatus = { remote: SyncRemote | null; device_id: string | null; 
+/**
+ * Entities changed locally since the last push
+ */
+pending: number; last_sync_at: string | null; last_error: string | null; running: boolean }
+export type Tag = { id: string; name: string; created_at: string; 
+/**
+ * Tasks and artifacts carrying the tag
+ */
+usage_count: number }
+export type TagTarget = "task" | "artifact"
+export type Task = { id: string; project_id: string; title: string; description: string | null; mode: string; status: string; created_at: string; updated_at: string }
+export type TaskAttachment = { id: string; task_id: string; name: string; source: string; source_path: string | null; content: string; created_at: string }
+/**
+ * Filters for `search_tasks`. Each list matches any of its values; an empty
+ * list doesn't filter. A task must carry every tag given.
+ */
+export type TaskFilters = { statuses?: string[]; modes?: string[]; tags?: string[]; sort?: TaskSort; offset?: number; 
+/**
+ * Page size; defaults to 50, at most 200
+ */
+limit?: number | null }
+export type TaskLink = { task_id: string; provider: string; external_id: string; external_key: string; url: string; created_at: string }
+/**
+ * Fields to change on every selected task; unset fields are left alone
+ */
+export type TaskPatch = { title?: string | null; 
+/**
+ * An empty string clears the description
+ */
+description?: string | null; mode?: string | null; status?: string | null }
+export type TaskSearchPage = { tasks: Task[]; 
+/**
+ * Matching tasks across all pages
+ */
+total: number; offset: number; limit: number }
+export type TaskSession = { id: string; kind: string; run_id: string | null; run_type: string | null; started_at: string; 
+/**
+ * None while the task is still open or the run still going
+ */
+ended_at: string | null; duration_secs: number }
+export type TaskSort = "updated_desc" | "updated_asc" | "created_desc" | "created_asc" | "title"
+export type TaskTimeSummary = { task_id: string; 
+/**
+ * Time the task was open in a window
+ */
+view_secs: number; 
+/**
+ * Time spent in plan/verify runs
+ */
+run_secs: number; runs: number; days: DayTime[]; 
+/**
+ * Activity log, oldest first
+ */
+sessions: TaskSession[] }
+/**
+ * The report exactly as an upload would send it, for the user to check first
+ */
+export type TelemetryPreview = { enabled: boolean; endpoint: string; report: TelemetryReport; 
+/**
+ * The JSON body an upload sends
+ */
+payload: string; 
+/**
+ * Passed back to `upload`, which refuses to send anything but this payload
+ */
+digest: string; last_upload: TelemetryUpload | null }
+/**
+ * What an upload sends: the totals of every counter recorded since the last upload
+ */
+export type TelemetryReport = { version: number; app_version: string; os: string; 
+/**
+ * First and last day with counts (UTC, inclusive)
+ */
+from_day: string | null; to_day: string | null; counters: Counter[] }
+export type TelemetryUpload = { uploaded_at: string; endpoint: string; payload: string }
+/**
+ * A test's recorded history in a project
+ */
+export type TestHistory = { test_name: string; runs: number; failures: number; 
+/**
+ * Repeated runs in which the test both passed and failed
+ */
+flaky_runs: number; last_failed_at: string | null }
+export type TokenEvent = { run_id: string; task_id: string; 
+/**
+ * Counts the model's responses in the run; a new value means the text so
+ * far was an intermediate response (e.g. before tool calls) and starts over
+ */
+generation: number; delta: string }
+export type ToolCallRow = { id: string; run_id: string; name: string; args_json: string; result_json: string; created_at: string; 
+/**
+ * Position within the run, starting at 1
+ */
+seq: number }
+/**
+ * A tool+args combination that was repeated more often than allowed
+ */
+export type ToolLoopFlag = { name: string; args_json: string; count: number }
+export type ToolStat = { name: string; invocations: number; errors: number; total_result_bytes: number; avg_result_bytes: number; error_rate: number; updated_at: string }
+/**
+ * What a run's tools may do. Tiers are ordered: each includes the ones before it.
+ */
+export type ToolTier = "read_only" | "read_run" | "read_write"
+export type TranscriptEntry = { 
+/**
+ * Position in the timeline, starting at 0
+ */
+index: number; kind: EntryKind; 
+/**
+ * Message id, or tool call id for tool_call/tool_result entries
+ */
+source_id: string; 
+/**
+ * Tool name for tool_call/tool_result entries
+ */
+tool_name: string | null; 
+/**
+ * Tool call sequence number within the run
+ */
+seq: number | null; 
+/**
+ * Message text, tool arguments JSON, or tool result JSON
+ */
+content: string; size_bytes: number; created_at: string }
+export type TranscriptionResult = { task: Task; transcript: string; attachment: TaskAttachment }
+export type TypedSettings = { settings: AppSettings; issues: SettingIssue[] }
+export type UpdateInfo = { channel: string; current_version: string; available: boolean; version: string | null; date: string | null; 
+/**
+ * Release notes for the available version
+ */
+changelog: string | null }
+export type Verdict = "matches" | "partially_matches" | "does_not_match" | "unknown"
+export type VerifyOptions = { run_tests?: boolean; 
+/**
+ * Run the test suite this many times to catch flaky tests
+ */
+test_repeat?: number | null; 
+/**
+ * Rerun just the failed tests to tell flaky failures from real ones
+ */
+rerun_failed_tests?: boolean; run_lint?: boolean; run_build?: boolean; 
+/**
+ * Run the project's type checker (cargo check, tsc, mypy)
+ */
+run_typecheck?: boolean; 
+/**
+ * Run the project's benchmarks and compare them with the previous results
+ */
+run_bench?: boolean; 
+/**
+ * Ask the configured language server for diagnostics on the changed files
+ */
+run_diagnostics?: boolean; staged?: boolean; max_tool_calls?: number; 
+/**
+ * Scope the review to one phase's planned files and store a phase report
+ */
+phase_id?: string | null; 
+/**
+ * Review the task's latest ingested CI results instead of running tests locally
+ */
+use_ci_results?: boolean; 
+/**
+ * Always call the LLM, even when an identical prompt has a cached report
+ */
+bypass_cache?: boolean; 
+/**
+ * Run tests, lint and build even when they already passed on the same repository state
+ */
+fresh_checks?: boolean; 
+/**
+ * Sample with this seed instead of the configured one
+ */
+seed?: number | null; 
+/**
+ * Record the run as a rerun or retry of an earlier one
+ */
+parent?: RunParent | null }
+export type VerifyResult = { run_id: string; report_md: string; ran_checks: RanChecks; truncated: boolean; verdict: Verdict; phase_id: string | null; 
+/**
+ * New phase status when a passing verdict advanced the phase
+ */
+phase_status: string | null; 
+/**
+ * Language code the report was requested in
+ */
+language: string; 
+/**
+ * Expected section headings missing from the report
+ */
+missing_sections: string[]; 
+/**
+ * The report was reused from an earlier run with an identical prompt
+ */
+cached: boolean; 
+/**
+ * Compiler, linter and test failure locations parsed from the check output,
+ * classified against the diff's changed lines
+ */
+compiler_diagnostics: CompilerDiagnostic[]; 
+/**
+ * Diagnostics on lines this change added or modified; only these count
+ * toward the verdict
+ */
+introduced_issues: number; 
+/**
+ * Changes against the task's captured baseline, when it has one
+ */
+baseline_delta: BaselineDelta | null; 
+/**
+ * Failed tests known to be flaky in the project; they don't count toward the verdict
+ */
+flaky_failures: TestHistory[]; 
+/**
+ * Benchmark results next to the project's previous ones
+ */
+benchmarks: BenchComparison[]; 
+/**
+ * Checks whose passing result on the same repository state was reused instead of rerun
+ */
+reused_checks: string[] }
+export type WalkthroughResult = { run_id: string; artifact_id: string; walkthrough_md: string; 
+/**
+ * Changed files in the order the walkthrough visits them
+ */
+order: string[]; 
+/**
+ * Changed files the walkthrough skipped; listed under "Other Changes"
+ */
+unmentioned: string[]; 
+/**
+ * The diff was staged rather than in the working tree
+ */
+staged: boolean; 
+/**
+ * The diff was cut short to fit the prompt
+ */
+truncated: boolean }
+/**
+ * A user-defined workflow: a prompt, the tools the model may call, checks
+ * run before it's called, and the task artifact its answer is saved as.
+ * Written as JSON, or TOML like a `[workflows.<name>]` table in .spectrail.toml:
+ * 
+ * ```toml
+ * [workflows.security-review]
+ * prompt = "Review {{task.title}} for injection and auth issues.\n\n{{plan}}"
+ * tools = ["read_file", "grep", "git_diff"]
+ * checks = ["lint"]
+ * artifact_kind = "security_review"
+ * ```
+ */
+export type WorkflowDefinition = { description?: string | null; system_prompt?: string | null; 
+/**
+ * User prompt template, with the variables custom prompt templates take
+ */
+prompt: string; 
+/**
+ * Repo tools the model may call; with none it's called once, without tools
+ */
+tools?: string[]; 
+/**
+ * `run_command` kinds run first, their results given to the model
+ */
+checks?: string[]; 
+/**
+ * The answer is saved as the task artifact `custom_<artifact_kind>`
+ */
+artifact_kind: string; 
+/**
+ * Model calls the tool loop may make
+ */
+max_iterations?: number | null }
+export type Workspace = { id: string; name: string; created_at: string; updated_at: string; project_count: number }
+export type WorkspaceDashboard = { workspace: Workspace; projects: WorkspaceProject[]; 
+/**
+ * Task count per status across all member projects
+ */
+task_counts: Partial<{ [key in string]: number }>; 
+/**
+ * Most recently updated tasks that aren't archived
+ */
+recent_tasks: WorkspaceTask[]; recent_runs: WorkspaceRun[] }
+export type WorkspaceProject = { project: Project; 
+/**
+ * Task count per status (draft|active|done|archived)
+ */
+task_counts: Partial<{ [key in string]: number }>; last_run_at: string | null }
+export type WorkspaceRun = { run_id: string; project_id: string; project_name: string; task_id: string; task_title: string; run_type: string; started_at: string; ended_at: string | null; error: string | null }
+export type WorkspaceTask = { task: Task; project_name: string }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
@@ -10,16 +10,21 @@ import {
   Stack,
   Badge,
   Box,
+  Checkbox,
 } from "@mantine/core";
-import { IconFolder } from "@tabler/icons-react";
+import { IconFolder, IconGitBranch } from "@tabler/icons-react";
 import { PageHeader } from "../ui";
-import { createProject, dbHealth, listProjects } from "../lib/api";
+import { createProject, createProjectFromUrl, dbHealth, listProjects } from "../lib/api";
 import type { Project } from "../lib/types";
 
 export default function Projects() {
   const [projects, setProjects] = useState<Project[]>([]);
   const [health, setHealth] = useState<{ ok: boolean; path: string } | null>(null);
   const [name, setName] = useState("");
+  const [gitUrl, setGitUrl] = useState("");
+  const [shallow, setShallow] = useState(true);
+  const [cloning, setCloning] = useState(false);
+  const [cloneError, setCloneError] = useState<string | null>(null);
 
   async function refresh() {
     setProjects(await listProjects());
@@ -42,6 +47,21 @@ export default function Projects() {
     setProjects([p, ...projects]);
   }
 
+  async function onClone() {
+    setCloning(true);
+    setCloneError(null);
+    try {
+      const p = await createProjectFromUrl(gitUrl.trim(), name.trim() || null, null, shallow);
+      setName("");
+      setGitUrl("");
+      setProjects([p, ...projects]);
+    } catch (e) {
+      setCloneError(String(e));
+    } finally {
+      setCloning(false);
+    }
+  }
+
   return (
     <Box>
       <PageHeader
@@ -62,6 +82,31 @@ export default function Projects() {
         </Button>
       </Group>
 
+      <Group align="flex-end" mb="lg">
+        <TextInput
+          label="Git URL"
+          placeholder="https://github.com/org/repo.git or git@host:org/repo.git"
+          value={gitUrl}
+          onChange={(e) => setGitUrl(e.target.value)}
+          error={cloneError}
+          style={{ flex: 1 }}
+        />
+        <Checkbox
+          label="Shallow"
+          checked={shallow}
+          onChange={(e) => setShallow(e.currentTarget.checked)}
+          mb={8}
+        />
+        <Button
+          onClick={onClone}
+          loading={cloning}
+          disabled={!gitUrl.trim()}
+          leftSection={<IconGitBranch size={16} />}
+        >
+          Clone
+        </Button>
+      </Group>
+
       <Stack>
         {projects.map((p) => (
           <Card
@@ -0,0 +1,62 @@
+import React, { useState } from "react";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { Button, Checkbox, Group, Stack, Text, TextInput } from "@mantine/core";
+import { quickCapture } from "../lib/api";
+
+/** Minimal window opened by the global shortcut; hides itself after saving. */
+export default function QuickCapture() {
+  const [title, setTitle] = useState("");
+  const [includeClipboard, setIncludeClipboard] = useState(false);
+  const [saving, setSaving] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  async function close() {
+    setTitle("");
+    setError(null);
+    await getCurrentWindow().hide();
+  }
+
+  async function onSave() {
+    if (!title.trim()) return;
+    setSaving(true);
+    setError(null);
+    try {
+      await quickCapture(title, includeClipboard);
+      await close();
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setSaving(false);
+    }
+  }
+
+  return (
+    <Stack p="md" gap="sm">
+      <TextInput
+        autoFocus
+        placeholder="Task title"
+        value={title}
+        onChange={(e) => setTitle(e.currentTarget.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Enter") onSave();
+          if (e.key === "Escape") close();
+        }}
+      />
+      <Group justify="space-between">
+        <Checkbox
+          label="Attach clipboard contents"
+          checked={includeClipboard}
+          onChange={(e) => setIncludeClipboard(e.currentTarget.checked)}
+        />
+        <Button onClick={onSave} loading={saving} disabled={!title.trim()}>
+          Capture
+        </Button>
+      </Group>
+      {error && (
+        <Text c="red" size="sm">
+          {error}
+        </Text>
+      )}
+    </Stack>
+  );
+}
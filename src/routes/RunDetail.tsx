@@ -50,6 +50,7 @@ export default function RunDetail() {
       case "user": return "blue";
       case "assistant": return "green";
       case "tool": return "gray";
+      case "hook": return "violet";
       default: return "gray";
     }
   };
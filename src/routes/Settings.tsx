@@ -11,7 +11,11 @@ import {
   Box,
 } from "@mantine/core";
 import { PageHeader } from "../ui";
-import { getSettings, setSettings } from "../lib/api";
+import UsageStatistics from "../components/UsageStatistics";
+import SendFeedback from "../components/SendFeedback";
+import { open, save } from "@tauri-apps/plugin-dialog";
+import { exportSettings, getSettings, getSettingsTyped, importSettings, setSettings } from "../lib/api";
+import type { SettingIssue } from "../lib/types";
 
 interface SettingsMap {
   provider_name: string;
@@ -36,6 +40,9 @@ export default function Settings() {
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const [saveStatus, setSaveStatus] = useState<"idle" | "success" | "error">("idle");
+  const [saveError, setSaveError] = useState<string | null>(null);
+  const [issues, setIssues] = useState<SettingIssue[]>([]);
+  const [transferMessage, setTransferMessage] = useState<string | null>(null);
 
   useEffect(() => {
     loadSettings();
@@ -56,6 +63,7 @@ export default function Settings() {
         temperature: map.temperature || DEFAULT_SETTINGS.temperature,
         max_tokens: map.max_tokens || DEFAULT_SETTINGS.max_tokens,
       });
+      setIssues((await getSettingsTyped()).issues);
     } catch (error) {
       console.error("Failed to load settings:", error);
     } finally {
@@ -77,14 +85,40 @@ export default function Settings() {
       ];
       await setSettings(pairs);
       setSaveStatus("success");
+      setIssues((await getSettingsTyped()).issues);
     } catch (error) {
       console.error("Failed to save settings:", error);
+      setSaveError(String(error));
       setSaveStatus("error");
     } finally {
       setSaving(false);
     }
   }
 
+  async function handleExport() {
+    const path = await save({ defaultPath: "spectrail-settings.json", filters: [{ name: "JSON", extensions: ["json"] }] });
+    if (!path) return;
+    try {
+      const result = await exportSettings(path);
+      const excluded = result.excluded.length > 0 ? ` (left out: ${result.excluded.join(", ")})` : "";
+      setTransferMessage(`Exported ${result.exported} settings${excluded}`);
+    } catch (error) {
+      setTransferMessage(`Export failed: ${error}`);
+    }
+  }
+
+  async function handleImport() {
+    const path = await open({ multiple: false, filters: [{ name: "JSON", extensions: ["json"] }] });
+    if (typeof path !== "string") return;
+    try {
+      const imported = await importSettings(path);
+      setTransferMessage(`Imported ${imported} settings`);
+      await loadSettings();
+    } catch (error) {
+      setTransferMessage(`Import failed: ${error}`);
+    }
+  }
+
   return (
     <Box>
       <PageHeader
@@ -99,6 +133,7 @@ export default function Settings() {
           <TextInput
             label="Provider Name"
             placeholder="e.g., openai, anthropic"
+            description="Names containing anthropic or claude use the Anthropic Messages API; anything else uses OpenAI chat completions"
             value={settings.provider_name}
             onChange={(e) => setSettingsState({ ...settings, provider_name: e.target.value })}
             disabled={loading || saving}
@@ -155,10 +190,24 @@ export default function Settings() {
           )}
 
           {saveStatus === "error" && (
-            <Text c="red" size="sm">Failed to save settings. Please try again.</Text>
+            <Text c="red" size="sm">Failed to save settings: {saveError}</Text>
           )}
 
+          {issues.map((issue) => (
+            <Text key={issue.key} c="orange" size="sm">
+              Using the default for {issue.key}: {issue.error}
+            </Text>
+          ))}
+
+          {transferMessage && <Text size="sm">{transferMessage}</Text>}
+
           <Group justify="flex-end">
+            <Button variant="default" onClick={handleImport} disabled={loading || saving}>
+              Import…
+            </Button>
+            <Button variant="default" onClick={handleExport} disabled={loading || saving}>
+              Export…
+            </Button>
             <Button
               onClick={handleSave}
               loading={saving}
@@ -169,6 +218,14 @@ export default function Settings() {
           </Group>
         </Stack>
       </Card>
+
+      <Box mt="lg">
+        <UsageStatistics />
+      </Box>
+
+      <Box mt="lg">
+        <SendFeedback />
+      </Box>
     </Box>
   );
 }
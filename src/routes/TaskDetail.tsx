@@ -15,20 +15,41 @@ import {
   LoadingOverlay,
   Code,
   Anchor,
+  TextInput,
 } from "@mantine/core";
 import { IconArrowLeft, IconRobot, IconSearch, IconList, IconAlertCircle } from "@tabler/icons-react";
 import { PageHeader, CopyAction } from "../ui";
 import {
+  cancelRun,
   createRun,
   generatePlan,
   getProject,
   getTask,
+  getTaskScopePath,
   listArtifacts,
   listRuns,
+  onPlanToken,
+  onRunEvent,
+  onVerifyToken,
+  setTaskScopePath,
+  subscribeWindow,
   upsertArtifact,
   verifyTask,
 } from "../lib/api";
-import type { Artifact, Project, Run, Task } from "../lib/types";
+import type { Artifact, Project, Run, Task, TokenEvent } from "../lib/types";
+
+/** Text streamed so far for the model's current response */
+interface StreamedText {
+  generation: number;
+  text: string;
+}
+
+function appendToken(prev: StreamedText, event: TokenEvent): StreamedText {
+  if (event.generation !== prev.generation) return { generation: event.generation, text: event.delta };
+  return { generation: prev.generation, text: prev.text + event.delta };
+}
+
+const EMPTY_STREAM: StreamedText = { generation: -1, text: "" };
 
 export default function TaskDetail() {
   const { id: projectId, taskId } = useParams<{ id: string; taskId: string }>();
@@ -39,16 +60,26 @@ export default function TaskDetail() {
   const [draftPlan, setDraftPlan] = useState("");
   const [isGeneratingPlan, setIsGeneratingPlan] = useState(false);
   const [lastPlanRunId, setLastPlanRunId] = useState<string | null>(null);
+  const [streamedPlan, setStreamedPlan] = useState<StreamedText>(EMPTY_STREAM);
   const [error, setError] = useState<string | null>(null);
+  const [scopePath, setScopePath] = useState("");
+  const [scopeError, setScopeError] = useState<string | null>(null);
 
   // Sprint 5: Verify Mode state
   const [isVerifying, setIsVerifying] = useState(false);
   const [verifyError, setVerifyError] = useState<string | null>(null);
   const [lastVerifyRunId, setLastVerifyRunId] = useState<string | null>(null);
+  const [streamedReport, setStreamedReport] = useState<StreamedText>(EMPTY_STREAM);
+  // Run in progress by run type, so it can be cancelled
+  const [activeRuns, setActiveRuns] = useState<Record<string, string>>({});
   const [verifyOptions, setVerifyOptions] = useState({
     run_tests: true,
+    rerun_failed_tests: false,
     run_lint: false,
     run_build: false,
+    run_typecheck: false,
+    run_bench: false,
+    run_diagnostics: false,
     staged: false,
   });
 
@@ -67,10 +98,45 @@ export default function TaskDetail() {
     loadData();
   }, [projectId, taskId]);
 
+  // Follow this task's runs, including ones started from the tray or another window
+  useEffect(() => {
+    if (!projectId || !taskId) return;
+    subscribeWindow(projectId, taskId);
+    const unlisten = onRunEvent((event) => {
+      if (event.task_id !== taskId) return;
+      setActiveRuns((prev) => {
+        const next = { ...prev };
+        if (event.status === "started") next[event.run_type] = event.run_id;
+        else delete next[event.run_type];
+        return next;
+      });
+      if (event.status !== "started") loadData();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [projectId, taskId]);
+
+  // Render plans and reports as the model writes them
+  useEffect(() => {
+    if (!taskId) return;
+    const unlistenPlan = onPlanToken((event) => {
+      if (event.task_id === taskId) setStreamedPlan((prev) => appendToken(prev, event));
+    });
+    const unlistenVerify = onVerifyToken((event) => {
+      if (event.task_id === taskId) setStreamedReport((prev) => appendToken(prev, event));
+    });
+    return () => {
+      unlistenPlan.then((fn) => fn());
+      unlistenVerify.then((fn) => fn());
+    };
+  }, [taskId]);
+
   async function loadData() {
     if (!projectId || !taskId) return;
     setProject(await getProject(projectId));
     setTask(await getTask(taskId));
+    setScopePath((await getTaskScopePath(taskId)) ?? "");
     setRuns(await listRuns(taskId));
     setArtifacts(await listArtifacts(taskId));
   }
@@ -81,6 +147,16 @@ export default function TaskDetail() {
     setRuns([r, ...runs]);
   }
 
+  async function onSaveScope() {
+    if (!taskId) return;
+    setScopeError(null);
+    try {
+      setScopePath((await setTaskScopePath(taskId, scopePath.trim() || null)) ?? "");
+    } catch (e) {
+      setScopeError(String(e));
+    }
+  }
+
   async function onSavePlan() {
     if (!taskId) return;
     const saved = await upsertArtifact(taskId, null, "plan_md", draftPlan.trim());
@@ -91,6 +167,7 @@ export default function TaskDetail() {
     if (!projectId || !taskId) return;
 
     setIsGeneratingPlan(true);
+    setStreamedPlan(EMPTY_STREAM);
     setError(null);
 
     try {
@@ -103,6 +180,17 @@ export default function TaskDetail() {
       await loadData();
     } finally {
       setIsGeneratingPlan(false);
+      setStreamedPlan(EMPTY_STREAM);
+    }
+  }
+
+  async function handleCancel(runType: string) {
+    const runId = activeRuns[runType];
+    if (!runId) return;
+    try {
+      await cancelRun(runId);
+    } catch (err: any) {
+      console.error("Failed to cancel run:", err);
     }
   }
 
@@ -110,6 +198,7 @@ export default function TaskDetail() {
     if (!projectId || !taskId) return;
 
     setIsVerifying(true);
+    setStreamedReport(EMPTY_STREAM);
     setVerifyError(null);
 
     try {
@@ -122,6 +211,7 @@ export default function TaskDetail() {
       await loadData();
     } finally {
       setIsVerifying(false);
+      setStreamedReport(EMPTY_STREAM);
     }
   }
 
@@ -158,6 +248,21 @@ export default function TaskDetail() {
         }
       />
 
+      <Group align="flex-end" mb="md">
+        <TextInput
+          label="Scope path"
+          description="Repo tools in this task's runs list, search and diff only under this subtree"
+          placeholder="Whole repository, or e.g. services/billing"
+          value={scopePath}
+          onChange={(e) => setScopePath(e.target.value)}
+          error={scopeError}
+          style={{ flex: 1 }}
+        />
+        <Button onClick={onSaveScope} variant="light">
+          Save Scope
+        </Button>
+      </Group>
+
       <Tabs defaultValue="plan">
         <Tabs.List>
           <Tabs.Tab value="plan" leftSection={<IconRobot size={16} />}>
@@ -174,7 +279,7 @@ export default function TaskDetail() {
         {/* Plan Tab */}
         <Tabs.Panel value="plan" pt="md">
           <Card withBorder shadow="sm" radius="md" pos="relative">
-            <LoadingOverlay visible={isGeneratingPlan} overlayProps={{ blur: 2 }} />
+            <LoadingOverlay visible={isGeneratingPlan && !streamedPlan.text} zIndex={1} overlayProps={{ blur: 2 }} />
             
             <Group justify="space-between" mb="md">
               <Text fw={700} size="lg">Plan Artifact</Text>
@@ -182,6 +287,11 @@ export default function TaskDetail() {
                 {planArtifact && (
                   <CopyAction text={planArtifact.content} label="Copy Plan" />
                 )}
+                {isGeneratingPlan && activeRuns.plan && (
+                  <Button variant="default" pos="relative" style={{ zIndex: 2 }} onClick={() => handleCancel("plan")}>
+                    Cancel
+                  </Button>
+                )}
                 <Button
                   onClick={handleGeneratePlan}
                   loading={isGeneratingPlan}
@@ -199,7 +309,11 @@ export default function TaskDetail() {
               </Alert>
             )}
 
-            {planArtifact ? (
+            {isGeneratingPlan && streamedPlan.text ? (
+              <Code block styles={{ root: { maxHeight: 400, overflow: "auto" } }}>
+                {streamedPlan.text}
+              </Code>
+            ) : planArtifact ? (
               <Stack>
                 <Code block styles={{ root: { maxHeight: 400, overflow: "auto" } }}>
                   {planArtifact.content}
@@ -238,7 +352,7 @@ export default function TaskDetail() {
         {/* Verify Tab */}
         <Tabs.Panel value="verify" pt="md">
           <Card withBorder shadow="sm" radius="md" pos="relative">
-            <LoadingOverlay visible={isVerifying} overlayProps={{ blur: 2 }} />
+            <LoadingOverlay visible={isVerifying && !streamedReport.text} zIndex={1} overlayProps={{ blur: 2 }} />
             
             <Group justify="space-between" mb="md">
               <Text fw={700} size="lg">Verification Report</Text>
@@ -246,6 +360,11 @@ export default function TaskDetail() {
                 {verifyArtifact && (
                   <CopyAction text={verifyArtifact.content} label="Copy Report" />
                 )}
+                {isVerifying && activeRuns.verify && (
+                  <Button variant="default" pos="relative" style={{ zIndex: 2 }} onClick={() => handleCancel("verify")}>
+                    Cancel
+                  </Button>
+                )}
                 <Button
                   onClick={handleVerify}
                   loading={isVerifying}
@@ -265,6 +384,12 @@ export default function TaskDetail() {
                 onChange={(e) => setVerifyOptions({ ...verifyOptions, run_tests: e.currentTarget.checked })}
                 disabled={isVerifying || isGeneratingPlan}
               />
+              <Checkbox
+                label="Rerun failed tests"
+                checked={verifyOptions.rerun_failed_tests}
+                onChange={(e) => setVerifyOptions({ ...verifyOptions, rerun_failed_tests: e.currentTarget.checked })}
+                disabled={isVerifying || isGeneratingPlan || !verifyOptions.run_tests}
+              />
               <Checkbox
                 label="Lint"
                 checked={verifyOptions.run_lint}
@@ -277,6 +402,24 @@ export default function TaskDetail() {
                 onChange={(e) => setVerifyOptions({ ...verifyOptions, run_build: e.currentTarget.checked })}
                 disabled={isVerifying || isGeneratingPlan}
               />
+              <Checkbox
+                label="Typecheck"
+                checked={verifyOptions.run_typecheck}
+                onChange={(e) => setVerifyOptions({ ...verifyOptions, run_typecheck: e.currentTarget.checked })}
+                disabled={isVerifying || isGeneratingPlan}
+              />
+              <Checkbox
+                label="Benchmarks"
+                checked={verifyOptions.run_bench}
+                onChange={(e) => setVerifyOptions({ ...verifyOptions, run_bench: e.currentTarget.checked })}
+                disabled={isVerifying || isGeneratingPlan}
+              />
+              <Checkbox
+                label="Diagnostics"
+                checked={verifyOptions.run_diagnostics}
+                onChange={(e) => setVerifyOptions({ ...verifyOptions, run_diagnostics: e.currentTarget.checked })}
+                disabled={isVerifying || isGeneratingPlan}
+              />
               <Checkbox
                 label="Staged only"
                 checked={verifyOptions.staged}
@@ -291,7 +434,11 @@ export default function TaskDetail() {
               </Alert>
             )}
 
-            {verifyArtifact ? (
+            {isVerifying && streamedReport.text ? (
+              <Code block styles={{ root: { maxHeight: 400, overflow: "auto", backgroundColor: "#f0f9f0" } }}>
+                {streamedReport.text}
+              </Code>
+            ) : verifyArtifact ? (
               <Stack>
                 <Code block styles={{ root: { maxHeight: 400, overflow: "auto", backgroundColor: "#f0f9f0" } }}>
                   {verifyArtifact.content}